@@ -0,0 +1,360 @@
+use super::{Annotation, Annotations, Colour, RedactionStyle, Tool};
+
+/// The minimum distance in pixels between consecutive points of a freehand line.
+const MIN_POINT_DISTANCE: f32 = 2.0;
+
+/// The minimum length in pixels of an arrow.
+const MIN_ARROW_LENGTH: f32 = 4.0;
+
+/// Creates annotations from the user's input.
+#[derive(Clone, Debug, Default)]
+pub struct AnnotationEditor {
+    annotations: Annotations,
+    tool: Tool,
+    redaction_style: RedactionStyle,
+
+    /// The annotation that is currently being drawn or typed.
+    drawing: Option<Annotation>,
+}
+
+impl AnnotationEditor {
+    pub fn new(tool: Tool, redaction_style: RedactionStyle) -> Self {
+        Self {
+            annotations: Annotations::default(),
+            tool,
+            redaction_style,
+            drawing: None,
+        }
+    }
+
+    pub fn tool(&self) -> Tool {
+        self.tool
+    }
+
+    /// Switches tool, finishing any text that is being typed.
+    pub fn set_tool(&mut self, tool: Tool) {
+        self.finish_text();
+        self.tool = tool;
+    }
+
+    /// If the user is currently typing text.
+    pub fn is_editing_text(&self) -> bool {
+        matches!(self.drawing, Some(Annotation::Text { .. }))
+    }
+
+    pub fn mouse_clicked(&mut self, position: [f32; 2]) {
+        self.finish_text();
+
+        self.drawing = Some(match self.tool {
+            Tool::Rectangle => Annotation::Rectangle {
+                start: position,
+                end: position,
+                colour: Colour::RED,
+                width: 4.0,
+            },
+
+            Tool::Arrow => Annotation::Arrow {
+                start: position,
+                end: position,
+                colour: Colour::RED,
+                width: 4.0,
+            },
+
+            Tool::Pen => Annotation::Pen {
+                points: vec![position],
+                colour: Colour::RED,
+                width: 3.0,
+            },
+
+            Tool::Text => Annotation::Text {
+                position,
+                text: String::new(),
+                colour: Colour::RED,
+                scale: 3,
+            },
+
+            Tool::Highlighter => Annotation::Highlighter {
+                points: vec![position],
+                colour: Colour::HIGHLIGHT,
+                width: 18.0,
+            },
+
+            Tool::Pixelate => Annotation::Pixelate {
+                start: position,
+                end: position,
+                block_size: 12,
+            },
+
+            Tool::Blur => Annotation::Blur {
+                start: position,
+                end: position,
+                radius: 8,
+            },
+
+            Tool::Redact => Annotation::Redact {
+                start: position,
+                end: position,
+                style: self.redaction_style,
+            },
+
+            Tool::RedactBrush => Annotation::RedactBrush {
+                points: vec![position],
+                width: 16.0,
+                style: self.redaction_style,
+            },
+        });
+    }
+
+    pub fn mouse_moved(&mut self, position: [f32; 2]) {
+        let Some(drawing) = self.drawing.as_mut() else {
+            return;
+        };
+
+        match drawing {
+            Annotation::Rectangle { end, .. }
+            | Annotation::Arrow { end, .. }
+            | Annotation::Pixelate { end, .. }
+            | Annotation::Blur { end, .. }
+            | Annotation::Redact { end, .. } => *end = position,
+
+            Annotation::Pen { points, .. }
+            | Annotation::Highlighter { points, .. }
+            | Annotation::RedactBrush { points, .. } => {
+                // Unwrap is safe, freehand lines are created with a point.
+                let last = points.last().unwrap();
+                let distance = (position[0] - last[0]).hypot(position[1] - last[1]);
+
+                if distance >= MIN_POINT_DISTANCE {
+                    points.push(position);
+                }
+            }
+
+            Annotation::Text { .. } => {}
+        }
+    }
+
+    /// Finishes drawing the current annotation, annotations that are too small are discarded.
+    pub fn mouse_released(&mut self) {
+        if self.is_editing_text() {
+            return;
+        }
+
+        let Some(drawing) = self.drawing.take() else {
+            return;
+        };
+
+        let is_visible = match &drawing {
+            Annotation::Rectangle { start, end, .. }
+            | Annotation::Pixelate { start, end, .. }
+            | Annotation::Blur { start, end, .. }
+            | Annotation::Redact { start, end, .. } => {
+                (start[0] - end[0]).abs() >= 1.0 && (start[1] - end[1]).abs() >= 1.0
+            }
+
+            Annotation::Arrow { start, end, .. } => {
+                (start[0] - end[0]).hypot(start[1] - end[1]) >= MIN_ARROW_LENGTH
+            }
+
+            Annotation::Pen { .. }
+            | Annotation::Highlighter { .. }
+            | Annotation::RedactBrush { .. } => true,
+
+            Annotation::Text { .. } => unreachable!("Text is not finished by releasing the mouse"),
+        };
+
+        if is_visible {
+            self.annotations.items.push(drawing);
+        }
+    }
+
+    /// Types a character into the current text, returns if the character was used.
+    pub fn type_character(&mut self, character: char) -> bool {
+        match self.drawing.as_mut() {
+            Some(Annotation::Text { text, .. }) if !character.is_control() => {
+                text.push(character);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Removes the last typed character, or the last annotation if no text is being typed.
+    pub fn backspace(&mut self) {
+        match self.drawing.as_mut() {
+            Some(Annotation::Text { text, .. }) => {
+                text.pop();
+            }
+            Some(_) => {}
+            None => {
+                self.annotations.items.pop();
+            }
+        }
+    }
+
+    /// Finishes the text that is being typed, returns if there was text being typed.
+    pub fn finish_text(&mut self) -> bool {
+        if !self.is_editing_text() {
+            return false;
+        }
+
+        let Some(drawing) = self.drawing.take() else {
+            return false;
+        };
+
+        let has_text = match &drawing {
+            Annotation::Text { text, .. } => !text.trim().is_empty(),
+            _ => false,
+        };
+
+        if has_text {
+            self.annotations.items.push(drawing);
+        }
+
+        true
+    }
+
+    /// The finished annotations.
+    pub fn annotations(&self) -> &Annotations {
+        &self.annotations
+    }
+
+    /// The annotation that is being drawn or typed, it is only outlined until it is finished.
+    pub fn drawing(&self) -> Option<&Annotation> {
+        self.drawing.as_ref()
+    }
+
+    /// Finishes any text being typed and returns the annotations.
+    pub fn into_annotations(mut self) -> Annotations {
+        self.finish_text();
+        self.annotations
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::annotation::{Annotation, RedactionStyle, Tool};
+
+    use super::AnnotationEditor;
+
+    #[test]
+    fn drag_creates_rectangle() {
+        let mut editor = AnnotationEditor::new(Tool::Rectangle, RedactionStyle::Fill);
+
+        editor.mouse_clicked([10.0, 10.0]);
+        editor.mouse_moved([20.0, 15.0]);
+        editor.mouse_moved([40.0, 30.0]);
+        editor.mouse_released();
+
+        let annotations = editor.into_annotations();
+        assert_eq!(annotations.items.len(), 1);
+        assert!(matches!(
+            annotations.items[0],
+            Annotation::Rectangle {
+                start: [10.0, 10.0],
+                end: [40.0, 30.0],
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn click_without_drag_is_discarded() {
+        for tool in [
+            Tool::Rectangle,
+            Tool::Arrow,
+            Tool::Pixelate,
+            Tool::Blur,
+            Tool::Redact,
+        ] {
+            let mut editor = AnnotationEditor::new(tool, RedactionStyle::Fill);
+
+            editor.mouse_clicked([10.0, 10.0]);
+            editor.mouse_released();
+
+            assert!(editor.into_annotations().is_empty(), "{tool:?}");
+        }
+    }
+
+    #[test]
+    fn pen_skips_close_points() {
+        let mut editor = AnnotationEditor::new(Tool::Pen, RedactionStyle::Fill);
+
+        editor.mouse_clicked([0.0, 0.0]);
+        editor.mouse_moved([1.0, 0.0]);
+        editor.mouse_moved([5.0, 0.0]);
+        editor.mouse_moved([5.5, 0.5]);
+        editor.mouse_moved([5.0, 8.0]);
+        editor.mouse_released();
+
+        let annotations = editor.into_annotations();
+        let Annotation::Pen { points, .. } = &annotations.items[0] else {
+            panic!("Expected a pen annotation");
+        };
+
+        assert_eq!(points, &[[0.0, 0.0], [5.0, 0.0], [5.0, 8.0]]);
+    }
+
+    #[test]
+    fn typing_text() {
+        let mut editor = AnnotationEditor::new(Tool::Text, RedactionStyle::Fill);
+
+        // Characters are ignored until a text box is placed.
+        assert!(!editor.type_character('x'));
+
+        editor.mouse_clicked([5.0, 6.0]);
+        editor.mouse_released();
+        assert!(editor.is_editing_text());
+
+        for character in "Hellp".chars() {
+            assert!(editor.type_character(character));
+        }
+        editor.backspace();
+        assert!(editor.type_character('o'));
+        assert!(!editor.type_character('\r'));
+
+        assert!(editor.finish_text());
+        assert!(!editor.is_editing_text());
+
+        let annotations = editor.into_annotations();
+        assert_eq!(
+            annotations.items,
+            [Annotation::Text {
+                position: [5.0, 6.0],
+                text: "Hello".to_string(),
+                colour: crate::annotation::Colour::RED,
+                scale: 3,
+            }]
+        );
+    }
+
+    #[test]
+    fn empty_text_is_discarded() {
+        let mut editor = AnnotationEditor::new(Tool::Text, RedactionStyle::Fill);
+
+        editor.mouse_clicked([5.0, 6.0]);
+        editor.type_character(' ');
+        editor.set_tool(Tool::Arrow);
+
+        assert!(editor.into_annotations().is_empty());
+    }
+
+    #[test]
+    fn backspace_undoes_last_annotation() {
+        let mut editor = AnnotationEditor::new(Tool::Arrow, RedactionStyle::Fill);
+
+        editor.mouse_clicked([0.0, 0.0]);
+        editor.mouse_moved([20.0, 20.0]);
+        editor.mouse_released();
+
+        editor.set_tool(Tool::Highlighter);
+        editor.mouse_clicked([0.0, 0.0]);
+        editor.mouse_moved([20.0, 0.0]);
+        editor.mouse_released();
+
+        editor.backspace();
+
+        let annotations = editor.into_annotations();
+        assert_eq!(annotations.items.len(), 1);
+        assert!(matches!(annotations.items[0], Annotation::Arrow { .. }));
+    }
+}
@@ -0,0 +1,170 @@
+/// The width of a glyph in font pixels.
+pub const GLYPH_WIDTH: usize = 5;
+
+/// The height of a glyph in font pixels.
+pub const GLYPH_HEIGHT: usize = 7;
+
+/// The horizontal distance between the start of consecutive glyphs in font pixels.
+pub const GLYPH_ADVANCE: usize = GLYPH_WIDTH + 1;
+
+/// The vertical distance between the start of consecutive lines in font pixels.
+pub const LINE_ADVANCE: usize = GLYPH_HEIGHT + 2;
+
+/// The first character in the font.
+const FIRST: char = ' ';
+
+/// A 5x7 bitmap font covering printable ASCII, each row's leftmost pixel is bit 4.
+const GLYPHS: [[u8; GLYPH_HEIGHT]; 95] = [
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00], // ' '
+    [0x04, 0x04, 0x04, 0x04, 0x04, 0x00, 0x04], // '!'
+    [0x0A, 0x0A, 0x0A, 0x00, 0x00, 0x00, 0x00], // '"'
+    [0x0A, 0x0A, 0x1F, 0x0A, 0x1F, 0x0A, 0x0A], // '#'
+    [0x04, 0x0F, 0x14, 0x0E, 0x05, 0x1E, 0x04], // '$'
+    [0x18, 0x19, 0x02, 0x04, 0x08, 0x13, 0x03], // '%'
+    [0x0C, 0x12, 0x14, 0x08, 0x15, 0x12, 0x0D], // '&'
+    [0x0C, 0x04, 0x08, 0x00, 0x00, 0x00, 0x00], // '''
+    [0x02, 0x04, 0x08, 0x08, 0x08, 0x04, 0x02], // '('
+    [0x08, 0x04, 0x02, 0x02, 0x02, 0x04, 0x08], // ')'
+    [0x00, 0x04, 0x15, 0x0E, 0x15, 0x04, 0x00], // '*'
+    [0x00, 0x04, 0x04, 0x1F, 0x04, 0x04, 0x00], // '+'
+    [0x00, 0x00, 0x00, 0x00, 0x0C, 0x04, 0x08], // ','
+    [0x00, 0x00, 0x00, 0x1F, 0x00, 0x00, 0x00], // '-'
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x0C, 0x0C], // '.'
+    [0x00, 0x01, 0x02, 0x04, 0x08, 0x10, 0x00], // '/'
+    [0x0E, 0x11, 0x13, 0x15, 0x19, 0x11, 0x0E], // '0'
+    [0x04, 0x0C, 0x04, 0x04, 0x04, 0x04, 0x0E], // '1'
+    [0x0E, 0x11, 0x01, 0x02, 0x04, 0x08, 0x1F], // '2'
+    [0x1F, 0x02, 0x04, 0x02, 0x01, 0x11, 0x0E], // '3'
+    [0x02, 0x06, 0x0A, 0x12, 0x1F, 0x02, 0x02], // '4'
+    [0x1F, 0x10, 0x1E, 0x01, 0x01, 0x11, 0x0E], // '5'
+    [0x06, 0x08, 0x10, 0x1E, 0x11, 0x11, 0x0E], // '6'
+    [0x1F, 0x01, 0x02, 0x04, 0x08, 0x08, 0x08], // '7'
+    [0x0E, 0x11, 0x11, 0x0E, 0x11, 0x11, 0x0E], // '8'
+    [0x0E, 0x11, 0x11, 0x0F, 0x01, 0x02, 0x0C], // '9'
+    [0x00, 0x0C, 0x0C, 0x00, 0x0C, 0x0C, 0x00], // ':'
+    [0x00, 0x0C, 0x0C, 0x00, 0x0C, 0x04, 0x08], // ';'
+    [0x02, 0x04, 0x08, 0x10, 0x08, 0x04, 0x02], // '<'
+    [0x00, 0x00, 0x1F, 0x00, 0x1F, 0x00, 0x00], // '='
+    [0x08, 0x04, 0x02, 0x01, 0x02, 0x04, 0x08], // '>'
+    [0x0E, 0x11, 0x01, 0x02, 0x04, 0x00, 0x04], // '?'
+    [0x0E, 0x11, 0x01, 0x0D, 0x15, 0x15, 0x0E], // '@'
+    [0x0E, 0x11, 0x11, 0x11, 0x1F, 0x11, 0x11], // 'A'
+    [0x1E, 0x11, 0x11, 0x1E, 0x11, 0x11, 0x1E], // 'B'
+    [0x0E, 0x11, 0x10, 0x10, 0x10, 0x11, 0x0E], // 'C'
+    [0x1C, 0x12, 0x11, 0x11, 0x11, 0x12, 0x1C], // 'D'
+    [0x1F, 0x10, 0x10, 0x1E, 0x10, 0x10, 0x1F], // 'E'
+    [0x1F, 0x10, 0x10, 0x1E, 0x10, 0x10, 0x10], // 'F'
+    [0x0E, 0x11, 0x10, 0x17, 0x11, 0x11, 0x0F], // 'G'
+    [0x11, 0x11, 0x11, 0x1F, 0x11, 0x11, 0x11], // 'H'
+    [0x0E, 0x04, 0x04, 0x04, 0x04, 0x04, 0x0E], // 'I'
+    [0x07, 0x02, 0x02, 0x02, 0x02, 0x12, 0x0C], // 'J'
+    [0x11, 0x12, 0x14, 0x18, 0x14, 0x12, 0x11], // 'K'
+    [0x10, 0x10, 0x10, 0x10, 0x10, 0x10, 0x1F], // 'L'
+    [0x11, 0x1B, 0x15, 0x15, 0x11, 0x11, 0x11], // 'M'
+    [0x11, 0x11, 0x19, 0x15, 0x13, 0x11, 0x11], // 'N'
+    [0x0E, 0x11, 0x11, 0x11, 0x11, 0x11, 0x0E], // 'O'
+    [0x1E, 0x11, 0x11, 0x1E, 0x10, 0x10, 0x10], // 'P'
+    [0x0E, 0x11, 0x11, 0x11, 0x15, 0x12, 0x0D], // 'Q'
+    [0x1E, 0x11, 0x11, 0x1E, 0x14, 0x12, 0x11], // 'R'
+    [0x0F, 0x10, 0x10, 0x0E, 0x01, 0x01, 0x1E], // 'S'
+    [0x1F, 0x04, 0x04, 0x04, 0x04, 0x04, 0x04], // 'T'
+    [0x11, 0x11, 0x11, 0x11, 0x11, 0x11, 0x0E], // 'U'
+    [0x11, 0x11, 0x11, 0x11, 0x11, 0x0A, 0x04], // 'V'
+    [0x11, 0x11, 0x11, 0x15, 0x15, 0x15, 0x0A], // 'W'
+    [0x11, 0x11, 0x0A, 0x04, 0x0A, 0x11, 0x11], // 'X'
+    [0x11, 0x11, 0x11, 0x0A, 0x04, 0x04, 0x04], // 'Y'
+    [0x1F, 0x01, 0x02, 0x04, 0x08, 0x10, 0x1F], // 'Z'
+    [0x0E, 0x08, 0x08, 0x08, 0x08, 0x08, 0x0E], // '['
+    [0x00, 0x10, 0x08, 0x04, 0x02, 0x01, 0x00], // '\'
+    [0x0E, 0x02, 0x02, 0x02, 0x02, 0x02, 0x0E], // ']'
+    [0x04, 0x0A, 0x11, 0x00, 0x00, 0x00, 0x00], // '^'
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x1F], // '_'
+    [0x08, 0x04, 0x02, 0x00, 0x00, 0x00, 0x00], // '`'
+    [0x00, 0x00, 0x0E, 0x01, 0x0F, 0x11, 0x0F], // 'a'
+    [0x10, 0x10, 0x16, 0x19, 0x11, 0x11, 0x1E], // 'b'
+    [0x00, 0x00, 0x0E, 0x10, 0x10, 0x11, 0x0E], // 'c'
+    [0x01, 0x01, 0x0D, 0x13, 0x11, 0x11, 0x0F], // 'd'
+    [0x00, 0x00, 0x0E, 0x11, 0x1F, 0x10, 0x0E], // 'e'
+    [0x06, 0x09, 0x08, 0x1C, 0x08, 0x08, 0x08], // 'f'
+    [0x00, 0x0F, 0x11, 0x11, 0x0F, 0x01, 0x0E], // 'g'
+    [0x10, 0x10, 0x16, 0x19, 0x11, 0x11, 0x11], // 'h'
+    [0x04, 0x00, 0x0C, 0x04, 0x04, 0x04, 0x0E], // 'i'
+    [0x02, 0x00, 0x06, 0x02, 0x02, 0x12, 0x0C], // 'j'
+    [0x10, 0x10, 0x12, 0x14, 0x18, 0x14, 0x12], // 'k'
+    [0x0C, 0x04, 0x04, 0x04, 0x04, 0x04, 0x0E], // 'l'
+    [0x00, 0x00, 0x1A, 0x15, 0x15, 0x11, 0x11], // 'm'
+    [0x00, 0x00, 0x16, 0x19, 0x11, 0x11, 0x11], // 'n'
+    [0x00, 0x00, 0x0E, 0x11, 0x11, 0x11, 0x0E], // 'o'
+    [0x00, 0x00, 0x1E, 0x11, 0x1E, 0x10, 0x10], // 'p'
+    [0x00, 0x00, 0x0D, 0x13, 0x0F, 0x01, 0x01], // 'q'
+    [0x00, 0x00, 0x16, 0x19, 0x10, 0x10, 0x10], // 'r'
+    [0x00, 0x00, 0x0E, 0x10, 0x0E, 0x01, 0x1E], // 's'
+    [0x08, 0x08, 0x1C, 0x08, 0x08, 0x09, 0x06], // 't'
+    [0x00, 0x00, 0x11, 0x11, 0x11, 0x13, 0x0D], // 'u'
+    [0x00, 0x00, 0x11, 0x11, 0x11, 0x0A, 0x04], // 'v'
+    [0x00, 0x00, 0x11, 0x11, 0x15, 0x15, 0x0A], // 'w'
+    [0x00, 0x00, 0x11, 0x0A, 0x04, 0x0A, 0x11], // 'x'
+    [0x00, 0x00, 0x11, 0x11, 0x0F, 0x01, 0x0E], // 'y'
+    [0x00, 0x00, 0x1F, 0x02, 0x04, 0x08, 0x1F], // 'z'
+    [0x02, 0x04, 0x04, 0x08, 0x04, 0x04, 0x02], // '{'
+    [0x04, 0x04, 0x04, 0x04, 0x04, 0x04, 0x04], // '|'
+    [0x08, 0x04, 0x04, 0x02, 0x04, 0x04, 0x08], // '}'
+    [0x00, 0x00, 0x08, 0x15, 0x02, 0x00, 0x00], // '~'
+];
+
+/// Returns the rows of a character's glyph, characters outside of the font use `?`.
+fn glyph(character: char) -> &'static [u8; GLYPH_HEIGHT] {
+    let index = (character as u32)
+        .checked_sub(FIRST as u32)
+        .filter(|index| (*index as usize) < GLYPHS.len())
+        .unwrap_or('?' as u32 - FIRST as u32);
+
+    &GLYPHS[index as usize]
+}
+
+/// Returns the runs of set font pixels in some text as `(column, row, length)`, one run per
+/// horizontal span of set pixels in a glyph row.
+pub fn pixel_runs(text: &str) -> Vec<(usize, usize, usize)> {
+    let mut runs = Vec::new();
+
+    for (line_index, line) in text.lines().enumerate() {
+        for (character_index, character) in line.chars().enumerate() {
+            let glyph_x = character_index * GLYPH_ADVANCE;
+            let glyph_y = line_index * LINE_ADVANCE;
+
+            for (row, bits) in glyph(character).iter().enumerate() {
+                let mut column = 0;
+                while column < GLYPH_WIDTH {
+                    if bits & (1 << (GLYPH_WIDTH - 1 - column)) == 0 {
+                        column += 1;
+                        continue;
+                    }
+
+                    let start = column;
+                    while column < GLYPH_WIDTH && bits & (1 << (GLYPH_WIDTH - 1 - column)) != 0 {
+                        column += 1;
+                    }
+
+                    runs.push((glyph_x + start, glyph_y + row, column - start));
+                }
+            }
+        }
+    }
+
+    runs
+}
+
+/// Returns the size of some text in font pixels.
+pub fn text_size(text: &str) -> [usize; 2] {
+    let lines = text.lines().count().max(1);
+    let columns = text
+        .lines()
+        .map(|line| line.chars().count())
+        .max()
+        .unwrap_or(0);
+
+    [
+        (columns * GLYPH_ADVANCE).saturating_sub(1),
+        lines * LINE_ADVANCE - (LINE_ADVANCE - GLYPH_HEIGHT),
+    ]
+}
@@ -0,0 +1,400 @@
+use serde::{Deserialize, Serialize};
+
+pub use editor::AnnotationEditor;
+pub use redaction::{RedactionStyle, redact};
+
+mod editor;
+mod font;
+mod passes;
+mod raster;
+mod redaction;
+
+/// An `RGBA` colour with straight alpha.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Colour(pub [u8; 4]);
+
+impl Colour {
+    /// The default colour for shapes and text.
+    pub const RED: Self = Self([230, 40, 40, 255]);
+
+    /// The default colour for the highlighter.
+    pub const HIGHLIGHT: Self = Self([255, 230, 0, 102]);
+
+    /// The colour for previewing redactions.
+    pub const REDACTION: Self = Self([0, 0, 0, 255]);
+
+    /// The colour as normalised floats.
+    pub fn as_f32(self) -> [f32; 4] {
+        self.0.map(|channel| f32::from(channel) / 255.0)
+    }
+}
+
+/// The tools for creating annotations.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Tool {
+    #[default]
+    Rectangle,
+    Arrow,
+    Pen,
+    Text,
+    Highlighter,
+    Pixelate,
+    Blur,
+    Redact,
+    RedactBrush,
+}
+
+impl Tool {
+    /// The tools in the order of their keys.
+    pub const ALL: [Self; 9] = [
+        Self::Rectangle,
+        Self::Arrow,
+        Self::Pen,
+        Self::Text,
+        Self::Highlighter,
+        Self::Pixelate,
+        Self::Blur,
+        Self::Redact,
+        Self::RedactBrush,
+    ];
+
+    /// Returns the tool for a number key, `1` is the first tool.
+    pub fn from_key(character: char) -> Option<Self> {
+        let index = character.to_digit(10)?.checked_sub(1)?;
+        Self::ALL.get(index as usize).copied()
+    }
+}
+
+/// A single annotation, positions are relative to the top-left corner of the window.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "tool", rename_all = "snake_case")]
+pub enum Annotation {
+    /// A rectangle outline.
+    Rectangle {
+        start: [f32; 2],
+        end: [f32; 2],
+        colour: Colour,
+        width: f32,
+    },
+
+    /// An arrow pointing from `start` to `end`.
+    Arrow {
+        start: [f32; 2],
+        end: [f32; 2],
+        colour: Colour,
+        width: f32,
+    },
+
+    /// A freehand line.
+    Pen {
+        points: Vec<[f32; 2]>,
+        colour: Colour,
+        width: f32,
+    },
+
+    /// Text with its top-left corner at `position`, each font pixel is `scale` pixels wide.
+    Text {
+        position: [f32; 2],
+        text: String,
+        colour: Colour,
+        scale: u32,
+    },
+
+    /// A translucent freehand line.
+    Highlighter {
+        points: Vec<[f32; 2]>,
+        colour: Colour,
+        width: f32,
+    },
+
+    /// Replaces an area with blocks of its average colour.
+    Pixelate {
+        start: [f32; 2],
+        end: [f32; 2],
+        block_size: u32,
+    },
+
+    /// Blurs an area.
+    Blur {
+        start: [f32; 2],
+        end: [f32; 2],
+        radius: u32,
+    },
+
+    /// Irreversibly redacts an area.
+    Redact {
+        start: [f32; 2],
+        end: [f32; 2],
+        style: RedactionStyle,
+    },
+
+    /// Irreversibly redacts the area under a freehand line.
+    RedactBrush {
+        points: Vec<[f32; 2]>,
+        width: f32,
+        style: RedactionStyle,
+    },
+}
+
+impl Annotation {
+    /// If the annotation must be applied to the HDR capture before it is tonemapped.
+    pub fn is_redaction(&self) -> bool {
+        matches!(self, Self::Redact { .. } | Self::RedactBrush { .. })
+    }
+
+    /// Returns the strokes that draw the annotation.
+    ///
+    /// Redactions can only be previewed, so they are drawn as an outline of their area.
+    pub fn strokes(&self) -> Vec<Stroke> {
+        match self {
+            Self::Rectangle {
+                start,
+                end,
+                colour,
+                width,
+            } => vec![Stroke {
+                points: rectangle_outline(*start, *end),
+                colour: *colour,
+                width: *width,
+            }],
+
+            Self::Arrow {
+                start,
+                end,
+                colour,
+                width,
+            } => {
+                let [left, right] = arrow_head(*start, *end, *width);
+
+                vec![
+                    Stroke {
+                        points: vec![*start, *end],
+                        colour: *colour,
+                        width: *width,
+                    },
+                    Stroke {
+                        points: vec![left, *end, right],
+                        colour: *colour,
+                        width: *width,
+                    },
+                ]
+            }
+
+            Self::Pen {
+                points,
+                colour,
+                width,
+            }
+            | Self::Highlighter {
+                points,
+                colour,
+                width,
+            } => vec![Stroke {
+                points: points.clone(),
+                colour: *colour,
+                width: *width,
+            }],
+
+            Self::Text {
+                position,
+                text,
+                colour,
+                scale,
+            } => {
+                let scale = *scale as f32;
+
+                font::pixel_runs(text)
+                    .into_iter()
+                    .map(|(column, row, length)| {
+                        let x = position[0] + column as f32 * scale;
+                        let y = position[1] + (row as f32 + 0.5) * scale;
+
+                        Stroke {
+                            points: vec![[x, y], [x + length as f32 * scale, y]],
+                            colour: *colour,
+                            width: scale,
+                        }
+                    })
+                    .collect()
+            }
+
+            Self::Pixelate { start, end, .. } | Self::Blur { start, end, .. } => vec![Stroke {
+                points: rectangle_outline(*start, *end),
+                colour: Colour([255, 255, 255, 160]),
+                width: 1.0,
+            }],
+
+            Self::Redact { start, end, .. } => vec![
+                Stroke {
+                    points: rectangle_outline(*start, *end),
+                    colour: Colour::REDACTION,
+                    width: 2.0,
+                },
+                Stroke {
+                    points: vec![*start, *end],
+                    colour: Colour::REDACTION,
+                    width: 2.0,
+                },
+                Stroke {
+                    points: vec![[start[0], end[1]], [end[0], start[1]]],
+                    colour: Colour::REDACTION,
+                    width: 2.0,
+                },
+            ],
+
+            Self::RedactBrush { points, width, .. } => vec![Stroke {
+                points: points.clone(),
+                colour: Colour::REDACTION,
+                width: *width,
+            }],
+        }
+    }
+}
+
+/// A polyline for drawing an annotation.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Stroke {
+    pub points: Vec<[f32; 2]>,
+    pub colour: Colour,
+    pub width: f32,
+}
+
+/// The annotations for a capture, in the order they were created.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Annotations {
+    pub items: Vec<Annotation>,
+}
+
+impl Annotations {
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+}
+
+/// Returns the closed outline of the rectangle between two corners.
+fn rectangle_outline(start: [f32; 2], end: [f32; 2]) -> Vec<[f32; 2]> {
+    vec![
+        [start[0], start[1]],
+        [end[0], start[1]],
+        [end[0], end[1]],
+        [start[0], end[1]],
+        [start[0], start[1]],
+    ]
+}
+
+/// Returns the ends of the two lines that form the head of an arrow.
+fn arrow_head(start: [f32; 2], end: [f32; 2], width: f32) -> [[f32; 2]; 2] {
+    const HEAD_ANGLE: f32 = core::f32::consts::PI / 6.0;
+
+    let dx = end[0] - start[0];
+    let dy = end[1] - start[1];
+    let length = dx.hypot(dy);
+
+    let head_length = (width * 4.0).max(10.0).min(length);
+    let angle = dy.atan2(dx);
+
+    let [left, right] = [angle + HEAD_ANGLE, angle - HEAD_ANGLE].map(|angle| {
+        [
+            end[0] - head_length * angle.cos(),
+            end[1] - head_length * angle.sin(),
+        ]
+    });
+
+    [left, right]
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Annotation, Annotations, Colour, RedactionStyle, Tool};
+
+    #[test]
+    fn tool_from_key() {
+        assert_eq!(Tool::from_key('1'), Some(Tool::Rectangle));
+        assert_eq!(Tool::from_key('4'), Some(Tool::Text));
+        assert_eq!(Tool::from_key('7'), Some(Tool::Blur));
+        assert_eq!(Tool::from_key('9'), Some(Tool::RedactBrush));
+        assert_eq!(Tool::from_key('0'), None);
+        assert_eq!(Tool::from_key('a'), None);
+    }
+
+    #[test]
+    fn serialisation_round_trip() {
+        let annotations = Annotations {
+            items: vec![
+                Annotation::Rectangle {
+                    start: [1.0, 2.0],
+                    end: [30.0, 40.0],
+                    colour: Colour::RED,
+                    width: 4.0,
+                },
+                Annotation::Arrow {
+                    start: [5.0, 5.0],
+                    end: [50.0, 25.0],
+                    colour: Colour::RED,
+                    width: 3.0,
+                },
+                Annotation::Pen {
+                    points: vec![[0.0, 0.0], [3.0, 4.0], [6.0, 2.5]],
+                    colour: Colour([0, 0, 0, 255]),
+                    width: 2.0,
+                },
+                Annotation::Text {
+                    position: [10.0, 10.0],
+                    text: "Look here!".to_string(),
+                    colour: Colour::RED,
+                    scale: 3,
+                },
+                Annotation::Highlighter {
+                    points: vec![[0.0, 20.0], [40.0, 20.0]],
+                    colour: Colour::HIGHLIGHT,
+                    width: 18.0,
+                },
+                Annotation::Pixelate {
+                    start: [0.0, 0.0],
+                    end: [16.0, 16.0],
+                    block_size: 8,
+                },
+                Annotation::Blur {
+                    start: [4.0, 4.0],
+                    end: [20.0, 12.0],
+                    radius: 3,
+                },
+                Annotation::Redact {
+                    start: [0.0, 30.0],
+                    end: [40.0, 38.0],
+                    style: RedactionStyle::Fill,
+                },
+                Annotation::RedactBrush {
+                    points: vec![[2.0, 2.0], [12.0, 2.0]],
+                    width: 16.0,
+                    style: RedactionStyle::Pixelate,
+                },
+            ],
+        };
+
+        let serialised = toml::to_string(&annotations).unwrap();
+        let deserialised: Annotations = toml::from_str(&serialised).unwrap();
+
+        assert_eq!(deserialised, annotations);
+    }
+
+    #[test]
+    fn arrow_head_points_back() {
+        let arrow = Annotation::Arrow {
+            start: [0.0, 0.0],
+            end: [100.0, 0.0],
+            colour: Colour::RED,
+            width: 4.0,
+        };
+
+        let strokes = arrow.strokes();
+        assert_eq!(strokes.len(), 2);
+
+        let head = &strokes[1].points;
+        assert_eq!(head[1], [100.0, 0.0]);
+
+        // Both sides of the head sit behind the tip, on opposite sides of the shaft.
+        assert!(head[0][0] < 100.0 && head[2][0] < 100.0);
+        assert!(head[0][1] * head[2][1] < 0.0);
+    }
+}
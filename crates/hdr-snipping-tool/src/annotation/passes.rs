@@ -0,0 +1,218 @@
+use vulkan::{AnnotationPass, AnnotationShape};
+
+use super::{Annotation, Annotations, font};
+
+impl Annotations {
+    /// Returns the passes that draw the annotations on the GPU, in the order they were created.
+    ///
+    /// Redactions are skipped, they are applied to the HDR capture before it is tonemapped.
+    pub fn passes(&self) -> Vec<AnnotationPass> {
+        self.items.iter().filter_map(Annotation::pass).collect()
+    }
+}
+
+impl Annotation {
+    /// Returns the pass that draws the annotation, `None` for redactions.
+    fn pass(&self) -> Option<AnnotationPass> {
+        let pass = match self {
+            Self::Pixelate {
+                start,
+                end,
+                block_size,
+            } => AnnotationPass::Pixelate {
+                start: *start,
+                end: *end,
+                block_size: *block_size,
+            },
+
+            Self::Blur { start, end, radius } => AnnotationPass::Blur {
+                start: *start,
+                end: *end,
+                radius: *radius,
+            },
+
+            Self::Redact { .. } | Self::RedactBrush { .. } => return None,
+
+            Self::Text {
+                position,
+                text,
+                colour,
+                scale,
+            } => {
+                let scale = *scale as f32;
+
+                let shapes = font::pixel_runs(text)
+                    .into_iter()
+                    .map(|(column, row, length)| {
+                        let start = [
+                            position[0] + column as f32 * scale,
+                            position[1] + row as f32 * scale,
+                        ];
+                        let end = [start[0] + length as f32 * scale, start[1] + scale];
+
+                        AnnotationShape::Rectangle { start, end }
+                    })
+                    .collect();
+
+                AnnotationPass::Blend {
+                    shapes,
+                    colour: colour.as_f32(),
+                }
+            }
+
+            _ => {
+                // The strokes are blended in one pass so overlapping strokes of a translucent
+                // annotation do not darken.
+                let strokes = self.strokes();
+                let colour = strokes.first()?.colour;
+
+                AnnotationPass::Blend {
+                    shapes: strokes
+                        .iter()
+                        .flat_map(|stroke| polyline(&stroke.points, stroke.width))
+                        .collect(),
+                    colour: colour.as_f32(),
+                }
+            }
+        };
+
+        Some(pass)
+    }
+}
+
+/// Returns the line segments of a polyline, a single point is drawn as a dot.
+pub(super) fn polyline(points: &[[f32; 2]], width: f32) -> Vec<AnnotationShape> {
+    points
+        .windows(2)
+        .map(|segment| [segment[0], segment[1]])
+        .chain((points.len() == 1).then(|| [points[0], points[0]]))
+        .map(|[start, end]| AnnotationShape::Line { start, end, width })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use vulkan::{AnnotationPass, AnnotationShape};
+
+    use crate::annotation::{Annotation, Annotations, Colour, RedactionStyle};
+
+    #[test]
+    fn strokes_are_one_blend() {
+        let annotations = Annotations {
+            items: vec![Annotation::Rectangle {
+                start: [2.0, 2.0],
+                end: [10.0, 10.0],
+                colour: Colour::RED,
+                width: 2.0,
+            }],
+        };
+
+        let passes = annotations.passes();
+        assert_eq!(passes.len(), 1);
+
+        let AnnotationPass::Blend { shapes, colour } = &passes[0] else {
+            panic!("Expected a blend pass, found {:?}", passes[0]);
+        };
+        assert_eq!(*colour, Colour::RED.as_f32());
+        assert_eq!(shapes.len(), 4);
+        assert_eq!(
+            shapes[0],
+            AnnotationShape::Line {
+                start: [2.0, 2.0],
+                end: [10.0, 2.0],
+                width: 2.0,
+            }
+        );
+    }
+
+    #[test]
+    fn single_point_is_a_dot() {
+        let annotations = Annotations {
+            items: vec![Annotation::Pen {
+                points: vec![[4.0, 4.0]],
+                colour: Colour::RED,
+                width: 3.0,
+            }],
+        };
+
+        let passes = annotations.passes();
+        let AnnotationPass::Blend { shapes, .. } = &passes[0] else {
+            panic!("Expected a blend pass, found {:?}", passes[0]);
+        };
+        assert_eq!(
+            shapes.as_slice(),
+            [AnnotationShape::Line {
+                start: [4.0, 4.0],
+                end: [4.0, 4.0],
+                width: 3.0,
+            }]
+        );
+    }
+
+    #[test]
+    fn text_is_rectangles() {
+        let annotations = Annotations {
+            items: vec![Annotation::Text {
+                position: [1.0, 1.0],
+                text: "I".to_string(),
+                colour: Colour::RED,
+                scale: 2,
+            }],
+        };
+
+        let passes = annotations.passes();
+        let AnnotationPass::Blend { shapes, .. } = &passes[0] else {
+            panic!("Expected a blend pass, found {:?}", passes[0]);
+        };
+
+        // The top bar of the I
+        assert!(shapes.contains(&AnnotationShape::Rectangle {
+            start: [3.0, 1.0],
+            end: [9.0, 3.0],
+        }));
+        assert!(
+            shapes
+                .iter()
+                .all(|shape| matches!(shape, AnnotationShape::Rectangle { .. }))
+        );
+    }
+
+    #[test]
+    fn redactions_are_skipped() {
+        let annotations = Annotations {
+            items: vec![
+                Annotation::Redact {
+                    start: [0.0, 0.0],
+                    end: [10.0, 10.0],
+                    style: RedactionStyle::Fill,
+                },
+                Annotation::Pixelate {
+                    start: [0.0, 0.0],
+                    end: [16.0, 16.0],
+                    block_size: 8,
+                },
+                Annotation::Blur {
+                    start: [4.0, 4.0],
+                    end: [20.0, 12.0],
+                    radius: 3,
+                },
+            ],
+        };
+
+        assert_eq!(
+            annotations.passes(),
+            [
+                AnnotationPass::Pixelate {
+                    start: [0.0, 0.0],
+                    end: [16.0, 16.0],
+                    block_size: 8,
+                },
+                AnnotationPass::Blur {
+                    start: [4.0, 4.0],
+                    end: [20.0, 12.0],
+                    radius: 3,
+                },
+            ]
+        );
+    }
+}
@@ -0,0 +1,129 @@
+/// An area of the window covered by an image.
+#[derive(Clone, Copy)]
+pub(super) struct Area {
+    /// The top-left pixel of the area, relative to the top-left corner of the window.
+    pub position: [usize; 2],
+    pub size: [usize; 2],
+}
+
+impl Area {
+    /// Returns the range of pixels covered by an area in window coordinates, clipped to the area.
+    pub(super) fn clip(&self, start: [f32; 2], end: [f32; 2]) -> Option<[[usize; 2]; 2]> {
+        let clip_axis = |axis: usize| {
+            let low = start[axis].min(end[axis]).floor() - self.position[axis] as f32;
+            let high = start[axis].max(end[axis]).ceil() - self.position[axis] as f32;
+
+            let low = low.max(0.0) as usize;
+            let high = (high.max(0.0) as usize).min(self.size[axis]);
+
+            (low < high).then_some([low, high])
+        };
+
+        let [left, right] = clip_axis(0)?;
+        let [top, bottom] = clip_axis(1)?;
+
+        Some([[left, top], [right, bottom]])
+    }
+
+    /// Marks the pixels whose centre is within half of `width` of a polyline.
+    pub(super) fn mark_polyline(&self, mask: &mut [bool], points: &[[f32; 2]], width: f32) {
+        let radius = width / 2.0;
+
+        let segments = points
+            .windows(2)
+            .map(|segment| [segment[0], segment[1]])
+            .chain(
+                // A single point is drawn as a dot.
+                (points.len() == 1).then(|| [points[0], points[0]]),
+            );
+
+        for [start, end] in segments {
+            let Some([[left, top], [right, bottom]]) = self.clip(
+                [start[0].min(end[0]) - radius, start[1].min(end[1]) - radius],
+                [start[0].max(end[0]) + radius, start[1].max(end[1]) + radius],
+            ) else {
+                continue;
+            };
+
+            for y in top..bottom {
+                for x in left..right {
+                    let centre = [
+                        (self.position[0] + x) as f32 + 0.5,
+                        (self.position[1] + y) as f32 + 0.5,
+                    ];
+
+                    if distance_to_segment(centre, start, end) <= radius {
+                        mask[y * self.size[0] + x] = true;
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// The distance from a point to the closest point on a line segment.
+fn distance_to_segment(point: [f32; 2], start: [f32; 2], end: [f32; 2]) -> f32 {
+    let segment = [end[0] - start[0], end[1] - start[1]];
+    let to_point = [point[0] - start[0], point[1] - start[1]];
+
+    let length_squared = segment[0] * segment[0] + segment[1] * segment[1];
+    let t = if length_squared == 0.0 {
+        0.0
+    } else {
+        ((to_point[0] * segment[0] + to_point[1] * segment[1]) / length_squared).clamp(0.0, 1.0)
+    };
+
+    let closest = [start[0] + segment[0] * t, start[1] + segment[1] * t];
+    (point[0] - closest[0]).hypot(point[1] - closest[1])
+}
+
+#[cfg(test)]
+mod test {
+    use super::Area;
+
+    #[test]
+    fn clip_to_area() {
+        let area = Area {
+            position: [100, 100],
+            size: [8, 8],
+        };
+
+        assert_eq!(
+            area.clip([50.0, 104.5], [150.0, 102.0]),
+            Some([[0, 2], [8, 5]])
+        );
+        assert_eq!(area.clip([0.0, 0.0], [100.0, 100.0]), None);
+    }
+
+    #[test]
+    fn polyline_marks_pixels_within_width() {
+        let area = Area {
+            position: [0, 0],
+            size: [12, 12],
+        };
+        let mut mask = vec![false; 12 * 12];
+
+        area.mark_polyline(&mut mask, &[[2.0, 6.0], [10.0, 6.0]], 2.0);
+
+        let is_marked = |x: usize, y: usize| mask[y * 12 + x];
+        for x in 2..10 {
+            assert!(is_marked(x, 5) && is_marked(x, 6), "{x}");
+            assert!(!is_marked(x, 4) && !is_marked(x, 7), "{x}");
+        }
+        assert!(!is_marked(0, 6) && !is_marked(11, 6));
+    }
+
+    #[test]
+    fn single_point_is_a_dot() {
+        let area = Area {
+            position: [0, 0],
+            size: [8, 8],
+        };
+        let mut mask = vec![false; 8 * 8];
+
+        area.mark_polyline(&mut mask, &[[4.0, 4.0]], 2.0);
+
+        let marked: Vec<_> = (0..64).filter(|index| mask[*index]).collect();
+        assert_eq!(marked, vec![3 * 8 + 3, 3 * 8 + 4, 4 * 8 + 3, 4 * 8 + 4]);
+    }
+}
@@ -0,0 +1,365 @@
+use half::f16;
+use serde::{Deserialize, Serialize};
+use vulkan::{AnnotationShape, Redactions};
+
+use super::{Annotation, Annotations, passes::polyline, raster::Area};
+
+/// The size of the blocks for pixelated redactions, large enough that text cannot be read.
+const PIXELATE_BLOCK_SIZE: usize = 24;
+
+/// The colour that filled redactions are replaced with.
+const FILL: [f16; 4] = [f16::ZERO, f16::ZERO, f16::ZERO, f16::ONE];
+
+/// How a redaction hides the pixels under it.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RedactionStyle {
+    /// Replace the pixels with solid black.
+    #[default]
+    Fill,
+
+    /// Replace the pixels with the average of large blocks.
+    Pixelate,
+}
+
+impl Annotations {
+    /// Returns the redactions to preview over the capture.
+    ///
+    /// The preview averages the same blocks as [`redact`], so it shows what will be saved.
+    pub fn redactions(&self) -> Redactions {
+        let mut redactions = Redactions {
+            block_size: PIXELATE_BLOCK_SIZE as u32,
+            ..Default::default()
+        };
+
+        for annotation in &self.items {
+            let (shapes, style) = match annotation {
+                Annotation::Redact { start, end, style } => (
+                    vec![AnnotationShape::Rectangle {
+                        start: *start,
+                        end: *end,
+                    }],
+                    *style,
+                ),
+
+                Annotation::RedactBrush {
+                    points,
+                    width,
+                    style,
+                } => (polyline(points, *width), *style),
+
+                _ => continue,
+            };
+
+            match style {
+                RedactionStyle::Fill => redactions.fill.extend(shapes),
+                RedactionStyle::Pixelate => redactions.pixelate.extend(shapes),
+            }
+        }
+
+        redactions
+    }
+}
+
+/// Irreversibly applies the redactions to an `RGBA` HDR capture, returns if any pixels were
+/// redacted.
+///
+/// This must happen before the capture is tonemapped so that the original pixels never reach
+/// the saved image or the clipboard.
+pub fn redact(annotations: &Annotations, size: [usize; 2], pixels: &mut [f16]) -> bool {
+    assert_eq!(
+        pixels.len(),
+        size[0] * size[1] * 4,
+        "Pixels must match the capture size"
+    );
+
+    let area = Area {
+        position: [0, 0],
+        size,
+    };
+
+    let mut fill_mask = vec![false; size[0] * size[1]];
+    let mut pixelate_mask = vec![false; size[0] * size[1]];
+
+    for annotation in &annotations.items {
+        let style = match annotation {
+            Annotation::Redact { style, .. } | Annotation::RedactBrush { style, .. } => *style,
+            _ => continue,
+        };
+
+        let mask = match style {
+            RedactionStyle::Fill => &mut fill_mask,
+            RedactionStyle::Pixelate => &mut pixelate_mask,
+        };
+
+        match annotation {
+            Annotation::Redact { start, end, .. } => {
+                if let Some([[left, top], [right, bottom]]) = area.clip(*start, *end) {
+                    for y in top..bottom {
+                        mask[y * size[0] + left..y * size[0] + right].fill(true);
+                    }
+                }
+            }
+
+            Annotation::RedactBrush { points, width, .. } => {
+                area.mark_polyline(mask, points, *width)
+            }
+
+            _ => {}
+        }
+    }
+
+    // Pixelate first so blocks next to a filled area do not include its fill.
+    let mut is_redacted = pixelate(&pixelate_mask, size, pixels);
+
+    for (pixel, is_covered) in pixels.chunks_exact_mut(4).zip(&fill_mask) {
+        if *is_covered {
+            pixel.copy_from_slice(&FILL);
+            is_redacted = true;
+        }
+    }
+
+    is_redacted
+}
+
+/// Replaces the covered pixels of each block with the average of the whole block, returns if any
+/// pixels were covered.
+fn pixelate(mask: &[bool], size: [usize; 2], pixels: &mut [f16]) -> bool {
+    let mut is_redacted = false;
+
+    for block_top in (0..size[1]).step_by(PIXELATE_BLOCK_SIZE) {
+        for block_left in (0..size[0]).step_by(PIXELATE_BLOCK_SIZE) {
+            let block_right = (block_left + PIXELATE_BLOCK_SIZE).min(size[0]);
+            let block_bottom = (block_top + PIXELATE_BLOCK_SIZE).min(size[1]);
+
+            let rows = block_top..block_bottom;
+            let is_covered = rows.clone().any(|y| {
+                mask[y * size[0] + block_left..y * size[0] + block_right]
+                    .iter()
+                    .any(|is_covered| *is_covered)
+            });
+            if !is_covered {
+                continue;
+            }
+
+            // Average over the whole block, so a partially covered block does not keep the value
+            // of a lone covered pixel.
+            let mut sum = [0.0f32; 4];
+            for y in rows.clone() {
+                for x in block_left..block_right {
+                    let index = (y * size[0] + x) * 4;
+                    for (total, channel) in sum.iter_mut().zip(&pixels[index..index + 4]) {
+                        *total += channel.to_f32();
+                    }
+                }
+            }
+
+            let count = ((block_right - block_left) * (block_bottom - block_top)) as f32;
+            let average = sum.map(|total| f16::from_f32(total / count));
+
+            for y in rows {
+                for x in block_left..block_right {
+                    if mask[y * size[0] + x] {
+                        let index = (y * size[0] + x) * 4;
+                        pixels[index..index + 4].copy_from_slice(&average);
+                    }
+                }
+            }
+
+            is_redacted = true;
+        }
+    }
+
+    is_redacted
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::HashSet;
+
+    use half::f16;
+    use vulkan::AnnotationShape;
+
+    use crate::annotation::{Annotation, Annotations};
+
+    use super::{FILL, PIXELATE_BLOCK_SIZE, RedactionStyle, redact};
+
+    const SIZE: [usize; 2] = [96, 64];
+
+    /// A capture where every pixel has a unique value. Blue is a checkerboard, so the average of
+    /// any block with an even number of pixels can never match an original pixel.
+    fn unique_capture() -> Vec<f16> {
+        (0..SIZE[1])
+            .flat_map(|y| (0..SIZE[0]).map(move |x| (x, y)))
+            .flat_map(|(x, y)| {
+                let index = (y * SIZE[0] + x) as u16;
+                [
+                    f16::from_bits(f16::ONE.to_bits() + index),
+                    f16::ONE,
+                    f16::from_f32(((x + y) % 2) as f32),
+                    f16::ONE,
+                ]
+            })
+            .collect()
+    }
+
+    fn pixel(pixels: &[f16], x: usize, y: usize) -> [u16; 4] {
+        let index = (y * SIZE[0] + x) * 4;
+        [0, 1, 2, 3].map(|channel| pixels[index + channel].to_bits())
+    }
+
+    /// The pixels inside of the rectangle between two corners.
+    fn inside(left: usize, top: usize, right: usize, bottom: usize) -> Vec<(usize, usize)> {
+        (top..bottom)
+            .flat_map(|y| (left..right).map(move |x| (x, y)))
+            .collect()
+    }
+
+    #[test]
+    fn fill_replaces_every_pixel() {
+        let original = unique_capture();
+        let mut pixels = original.clone();
+
+        let annotations = Annotations {
+            items: vec![Annotation::Redact {
+                start: [10.0, 5.0],
+                end: [50.0, 30.0],
+                style: RedactionStyle::Fill,
+            }],
+        };
+
+        assert!(redact(&annotations, SIZE, &mut pixels));
+
+        let original_values: HashSet<_> = inside(0, 0, SIZE[0], SIZE[1])
+            .into_iter()
+            .map(|(x, y)| pixel(&original, x, y))
+            .collect();
+
+        for (x, y) in inside(10, 5, 50, 30) {
+            assert_eq!(pixel(&pixels, x, y), FILL.map(f16::to_bits));
+            assert!(!original_values.contains(&pixel(&pixels, x, y)));
+        }
+
+        // Outside of the redaction is untouched
+        assert_eq!(pixel(&pixels, 9, 5), pixel(&original, 9, 5));
+        assert_eq!(pixel(&pixels, 50, 29), pixel(&original, 50, 29));
+    }
+
+    #[test]
+    fn pixelate_leaves_no_original_values() {
+        let original = unique_capture();
+        let mut pixels = original.clone();
+
+        // A brush that only partially covers its blocks.
+        let annotations = Annotations {
+            items: vec![
+                Annotation::Redact {
+                    start: [3.0, 3.0],
+                    end: [61.0, 40.0],
+                    style: RedactionStyle::Pixelate,
+                },
+                Annotation::RedactBrush {
+                    points: vec![[70.0, 10.0], [90.0, 50.0]],
+                    width: 6.0,
+                    style: RedactionStyle::Pixelate,
+                },
+            ],
+        };
+
+        assert!(redact(&annotations, SIZE, &mut pixels));
+
+        let original_values: HashSet<_> = inside(0, 0, SIZE[0], SIZE[1])
+            .into_iter()
+            .map(|(x, y)| pixel(&original, x, y))
+            .collect();
+
+        let mut redacted_values = HashSet::new();
+        for (x, y) in inside(0, 0, SIZE[0], SIZE[1]) {
+            let value = pixel(&pixels, x, y);
+            if value == pixel(&original, x, y) {
+                continue;
+            }
+
+            assert!(
+                !original_values.contains(&value),
+                "Pixel ({x}, {y}) has an original value"
+            );
+            redacted_values.insert(value);
+        }
+
+        // Every covered pixel was changed
+        for (x, y) in inside(3, 3, 61, 40) {
+            assert_ne!(pixel(&pixels, x, y), pixel(&original, x, y));
+        }
+
+        // At most one value per block
+        let blocks = SIZE[0].div_ceil(PIXELATE_BLOCK_SIZE) * SIZE[1].div_ceil(PIXELATE_BLOCK_SIZE);
+        assert!(redacted_values.len() <= blocks);
+    }
+
+    #[test]
+    fn no_redactions() {
+        let original = unique_capture();
+        let mut pixels = original.clone();
+
+        let annotations = Annotations {
+            items: vec![Annotation::Pixelate {
+                start: [0.0, 0.0],
+                end: [10.0, 10.0],
+                block_size: 4,
+            }],
+        };
+
+        assert!(!redact(&annotations, SIZE, &mut pixels));
+        assert_eq!(pixels, original);
+    }
+
+    #[test]
+    fn redactions_preview() {
+        let annotations = Annotations {
+            items: vec![
+                Annotation::Redact {
+                    start: [10.0, 5.0],
+                    end: [50.0, 30.0],
+                    style: RedactionStyle::Fill,
+                },
+                Annotation::RedactBrush {
+                    points: vec![[70.0, 10.0], [90.0, 50.0], [80.0, 60.0]],
+                    width: 6.0,
+                    style: RedactionStyle::Pixelate,
+                },
+                Annotation::Pixelate {
+                    start: [0.0, 0.0],
+                    end: [10.0, 10.0],
+                    block_size: 4,
+                },
+            ],
+        };
+
+        let redactions = annotations.redactions();
+
+        assert_eq!(
+            redactions.fill,
+            [AnnotationShape::Rectangle {
+                start: [10.0, 5.0],
+                end: [50.0, 30.0],
+            }]
+        );
+        assert_eq!(
+            redactions.pixelate,
+            [
+                AnnotationShape::Line {
+                    start: [70.0, 10.0],
+                    end: [90.0, 50.0],
+                    width: 6.0,
+                },
+                AnnotationShape::Line {
+                    start: [90.0, 50.0],
+                    end: [80.0, 60.0],
+                    width: 6.0,
+                },
+            ]
+        );
+        assert_eq!(redactions.block_size, PIXELATE_BLOCK_SIZE as u32);
+    }
+}
@@ -1,13 +1,15 @@
 use vulkan::HdrImage;
-use windows_capture_provider::{Monitor, WindowsCapture};
 
-use crate::{capture_taker::Whitepoint, selection::Selection};
+use crate::{
+    capture_taker::{DesktopRect, PlatformCapture, Whitepoint},
+    selection::{Lasso, Selection},
+};
 
 pub struct CaptureResources {
-    #[expect(unused)]
-    pub monitor: Monitor,
-    pub capture: WindowsCapture,
+    pub area: DesktopRect,
+    pub captures: Vec<PlatformCapture>,
     pub hdr_capture: HdrImage,
     pub whitepoint: Whitepoint,
     pub selection: Selection,
+    pub lasso: Option<Lasso>,
 }
@@ -1,5 +1,6 @@
-use std::sync::Arc;
+use std::{rc::Rc, sync::Arc};
 
+use tracing::info;
 use vulkan::Vulkan;
 use winit::{
     dpi::PhysicalSize,
@@ -11,9 +12,14 @@ use winit::{
 
 use crate::{
     application_event_loop::Event,
-    capture_saver::CaptureSaverThread,
-    capture_taker::CaptureTakerThread,
-    config_dir,
+    burst::{BurstFrame, FrameRing},
+    capture_history::HistoryStore,
+    capture_saver::{CaptureSaverThread, SaveSettings},
+    capture_taker::{CaptureTaker, CaptureTakerThread, DesktopRect},
+    config::Config,
+    config_dir, diagnostics,
+    error_reporting::ErrorCategory,
+    history_dir,
     renderer_thread::RendererThread,
     should_debug,
     utilities::{
@@ -28,11 +34,21 @@ pub struct CoreResources {
     pub capture_saver: CaptureSaverThread,
     pub capture_taker: CaptureTakerThread,
     pub renderer: RendererThread,
+
+    /// The past captures, `None` if the history could not be opened.
+    pub history: Option<HistoryStore>,
+
+    /// The latest captures of the last burst, to pick from after the burst.
+    pub burst_frames: Rc<FrameRing<BurstFrame>>,
+
+    /// The region of the last saved screenshot, to save again without selecting it.
+    pub last_region: Option<DesktopRect>,
     pub proxy: EventLoopProxy<Event>,
+    pub config: Config,
 }
 
 impl CoreResources {
-    pub fn new(event_loop: &ActiveEventLoop, proxy: EventLoopProxy<Event>) -> Self {
+    pub fn new(event_loop: &ActiveEventLoop, proxy: EventLoopProxy<Event>, config: Config) -> Self {
         // Create the window
         let window = {
             let focused_window = get_foreground_window();
@@ -90,9 +106,20 @@ impl CoreResources {
                 },
             }
         };
-
-        let capture_saver = CaptureSaverThread::new(Arc::clone(&vulkan));
-        let capture_taker = CaptureTakerThread::new(Arc::clone(&vulkan));
+        diagnostics::record_vulkan(&vulkan);
+
+        let history = HistoryStore::open(history_dir(), config.history)
+            .report(ErrorCategory::History, "Could not open the capture history");
+
+        let capture_saver = CaptureSaverThread::new(
+            Arc::clone(&vulkan),
+            history.clone(),
+            SaveSettings::from_config(&config),
+            proxy.clone(),
+        );
+        let mut capture_taker = CaptureTakerThread::new(Arc::clone(&vulkan));
+        capture_taker.set_whitepoint_strategy(config.whitepoint);
+        capture_taker.set_monitor_overrides(config.monitors.clone());
         let renderer = RendererThread::new(Arc::clone(&vulkan), &window);
 
         Self {
@@ -101,7 +128,40 @@ impl CoreResources {
             capture_saver,
             capture_taker,
             renderer,
+            history,
+            burst_frames: Rc::default(),
+            last_region: None,
             proxy,
+            config,
+        }
+    }
+
+    /// Uses a reloaded config, the settings that are read when they are used apply from the next
+    /// capture.
+    pub fn apply_config(&mut self, config: Config) {
+        if config.whitepoint != self.config.whitepoint {
+            info!("Whitepoint strategy: {:?}", config.whitepoint);
+            self.capture_taker
+                .set_whitepoint_strategy(config.whitepoint);
         }
+
+        if config.monitors != self.config.monitors {
+            info!("Monitor overrides: {:?}", config.monitors);
+            self.capture_taker
+                .set_monitor_overrides(config.monitors.clone());
+        }
+
+        let save_settings = SaveSettings::from_config(&config);
+        if save_settings != SaveSettings::from_config(&self.config) {
+            info!("Save settings: {save_settings:?}");
+            self.capture_saver.set_settings(save_settings);
+        }
+
+        // The history is shared with the capture saver, so only opening it again can change it.
+        if config.history != self.config.history {
+            info!("The history limits apply from the next start");
+        }
+
+        self.config = config;
     }
 }
@@ -0,0 +1,231 @@
+use core::{
+    sync::atomic::{AtomicBool, Ordering},
+    time::Duration,
+};
+use std::{
+    sync::Arc,
+    thread::{self, JoinHandle},
+    time::Instant,
+};
+
+use tracing::info_span;
+use winit::event_loop::EventLoopProxy;
+
+use crate::{
+    application_event_loop::Event,
+    utilities::failure::{Failure, Ignore},
+};
+
+use super::ApplicationEvent;
+
+/// How often the ticker checks the countdown.
+const TICK_INTERVAL: Duration = Duration::from_millis(100);
+
+/// A source of the current time.
+pub trait Clock {
+    fn now(&self) -> Instant;
+}
+
+/// The system's monotonic clock.
+#[derive(Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+}
+
+/// What changed after a countdown was ticked.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CountdownTick {
+    /// The displayed number of seconds has not changed.
+    Unchanged,
+
+    /// The displayed number of seconds changed.
+    Remaining(u64),
+
+    /// The countdown has finished.
+    Finished,
+}
+
+/// A countdown to a delayed screenshot.
+pub struct Countdown<C: Clock = SystemClock> {
+    clock: C,
+    deadline: Instant,
+    displayed_seconds: Option<u64>,
+}
+
+impl<C: Clock> Countdown<C> {
+    pub fn start(clock: C, delay: Duration) -> Self {
+        let deadline = clock.now() + delay;
+
+        Self {
+            clock,
+            deadline,
+            displayed_seconds: None,
+        }
+    }
+
+    /// The time until the countdown finishes.
+    pub fn remaining(&self) -> Duration {
+        self.deadline.saturating_duration_since(self.clock.now())
+    }
+
+    /// The remaining time rounded up to whole seconds, as it should be displayed.
+    pub fn remaining_seconds(&self) -> u64 {
+        let remaining = self.remaining();
+
+        if remaining.subsec_nanos() == 0 {
+            remaining.as_secs()
+        } else {
+            remaining.as_secs() + 1
+        }
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.remaining().is_zero()
+    }
+
+    /// Checks the clock, returns if the countdown finished or if the display needs to change.
+    pub fn tick(&mut self) -> CountdownTick {
+        if self.is_finished() {
+            return CountdownTick::Finished;
+        }
+
+        let seconds = self.remaining_seconds();
+        if self.displayed_seconds == Some(seconds) {
+            return CountdownTick::Unchanged;
+        }
+
+        self.displayed_seconds = Some(seconds);
+        CountdownTick::Remaining(seconds)
+    }
+}
+
+/// A thread that sends countdown ticks to the application until it is dropped.
+pub struct CountdownTicker {
+    running: Arc<AtomicBool>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl CountdownTicker {
+    pub fn new(proxy: EventLoopProxy<Event>) -> Self {
+        let running = Arc::new(AtomicBool::new(true));
+
+        let thread = {
+            let running = Arc::clone(&running);
+
+            thread::Builder::new()
+                .name(String::from("Countdown Ticker"))
+                .spawn(move || {
+                    let _span = info_span!("[Countdown Ticker]").entered();
+
+                    while running.load(Ordering::Relaxed) {
+                        thread::sleep(TICK_INTERVAL);
+
+                        if proxy
+                            .send_event(ApplicationEvent::CountdownTick.into())
+                            .is_err()
+                        {
+                            break;
+                        }
+                    }
+                })
+                .report_and_panic("Could not start the countdown thread")
+        };
+
+        Self {
+            running,
+            thread: Some(thread),
+        }
+    }
+}
+
+impl Drop for CountdownTicker {
+    fn drop(&mut self) {
+        self.running.store(false, Ordering::Relaxed);
+
+        if let Some(thread) = self.thread.take() {
+            thread.join().ignore();
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use core::{cell::Cell, time::Duration};
+    use std::{rc::Rc, time::Instant};
+
+    use super::{Clock, Countdown, CountdownTick};
+
+    #[derive(Clone)]
+    struct FakeClock {
+        now: Rc<Cell<Instant>>,
+    }
+
+    impl FakeClock {
+        fn new() -> Self {
+            Self {
+                now: Rc::new(Cell::new(Instant::now())),
+            }
+        }
+
+        fn advance(&self, duration: Duration) {
+            self.now.set(self.now.get() + duration);
+        }
+    }
+
+    impl Clock for FakeClock {
+        fn now(&self) -> Instant {
+            self.now.get()
+        }
+    }
+
+    #[test]
+    fn counts_down_whole_seconds() {
+        let clock = FakeClock::new();
+        let mut countdown = Countdown::start(clock.clone(), Duration::from_secs(3));
+
+        assert_eq!(countdown.tick(), CountdownTick::Remaining(3));
+        assert_eq!(countdown.tick(), CountdownTick::Unchanged);
+
+        clock.advance(Duration::from_millis(900));
+        assert_eq!(countdown.tick(), CountdownTick::Unchanged);
+        assert_eq!(countdown.remaining(), Duration::from_millis(2100));
+
+        clock.advance(Duration::from_millis(100));
+        assert_eq!(countdown.tick(), CountdownTick::Remaining(2));
+
+        clock.advance(Duration::from_millis(1500));
+        assert_eq!(countdown.tick(), CountdownTick::Remaining(1));
+        assert!(!countdown.is_finished());
+    }
+
+    #[test]
+    fn finishes_at_deadline() {
+        let clock = FakeClock::new();
+        let mut countdown = Countdown::start(clock.clone(), Duration::from_secs(5));
+        countdown.tick();
+
+        clock.advance(Duration::from_millis(4999));
+        assert_eq!(countdown.tick(), CountdownTick::Remaining(1));
+
+        clock.advance(Duration::from_millis(1));
+        assert!(countdown.is_finished());
+        assert_eq!(countdown.tick(), CountdownTick::Finished);
+
+        // Late ticks keep reporting the countdown as finished.
+        clock.advance(Duration::from_secs(10));
+        assert_eq!(countdown.remaining(), Duration::ZERO);
+        assert_eq!(countdown.tick(), CountdownTick::Finished);
+    }
+
+    #[test]
+    fn zero_delay_finishes_immediately() {
+        let mut countdown = Countdown::start(FakeClock::new(), Duration::ZERO);
+
+        assert_eq!(countdown.remaining_seconds(), 0);
+        assert_eq!(countdown.tick(), CountdownTick::Finished);
+    }
+}
@@ -1,11 +1,50 @@
+use core::time::Duration;
+
 use vulkan::HdrImage;
-use windows_capture_provider::{Monitor, WindowsCapture};
 use winit::dpi::PhysicalPosition;
 
-use crate::{application_event_loop::Event, capture_taker::Whitepoint};
+use crate::{
+    application_event_loop::Event,
+    burst::BurstFrame,
+    capture_taker::{CaptureProvider, PlatformCaptureProvider, Whitepoint},
+    config::Config,
+};
 
 pub enum ApplicationEvent {
     ScreenshotKeyPressed,
+
+    /// Saves the hovered monitor without showing the screenshot.
+    InstantScreenshotKeyPressed,
+
+    /// Saves the last saved region again without showing the screenshot.
+    RepeatRegionRequested,
+
+    /// Shows the screenshot to pick a window to capture.
+    WindowCaptureRequested,
+    DelayedScreenshotRequested(Duration),
+    CountdownTick,
+    HistoryRequested,
+
+    /// Starts recording a region, or stops the running recording.
+    RecordingRequested,
+
+    /// The capture taker finished saving the recording.
+    RecordingFinished,
+
+    /// Starts a burst of captures of a region, or stops the running burst.
+    BurstRequested,
+
+    /// The capture taker took a capture of the running burst.
+    BurstFrame(BurstFrame),
+
+    /// The capture taker finished the burst.
+    BurstFinished,
+
+    /// Opens the captures kept from the last burst to pick from.
+    BurstFramesRequested,
+
+    /// The config file was changed and is valid, the settings are used from now on.
+    ConfigReloaded(Box<Config>),
     MouseEvent(MouseEvent),
     KeyboardEvent(KeyboardEvent),
     LoadingEvent(LoadingEvent),
@@ -21,6 +60,18 @@ pub enum WindowEvent {
 pub enum KeyboardEvent {
     EscapePressed,
     EnterPressed,
+    TabPressed,
+    BackspacePressed,
+    ArrowPressed(Arrow),
+    Character(char),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Arrow {
+    Up,
+    Down,
+    Left,
+    Right,
 }
 
 pub enum MouseEvent {
@@ -29,9 +80,11 @@ pub enum MouseEvent {
     Released,
 }
 
-pub enum LoadingEvent {
-    FoundMonitor(Monitor),
-    GotCapture(WindowsCapture),
+/// The progress of a capture, sent by the capture taker.
+pub enum LoadingEvent<P: CaptureProvider = PlatformCaptureProvider> {
+    FoundMonitors(Vec<P::Monitor>),
+    FoundWindow(P::Window),
+    GotCapture(P::Capture),
     ImportedCapture(HdrImage),
     SelectedWhitepoint(Whitepoint),
     Error,
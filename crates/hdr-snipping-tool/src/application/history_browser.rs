@@ -0,0 +1,155 @@
+use std::rc::Rc;
+
+use tracing::debug;
+use vulkan::{HdrImage, Vulkan};
+use winit::dpi::PhysicalPosition;
+
+use crate::{
+    burst::{BurstFrame, FrameRing},
+    capture_history::{HistoryEntry, HistoryStore},
+    error_reporting::ErrorCategory,
+    selection::Selection,
+    utilities::failure::Failure,
+};
+
+use super::capture_resources::CaptureResources;
+
+/// Steps through past captures, starting from the newest.
+pub struct HistoryBrowser {
+    source: BrowsedCaptures,
+    index: usize,
+}
+
+/// Where the browsed captures come from.
+enum BrowsedCaptures {
+    /// The saved captures in the history.
+    History {
+        store: HistoryStore,
+        entries: Vec<HistoryEntry>,
+    },
+
+    /// The captures kept from the last burst, which are not in the history.
+    Burst(Rc<FrameRing<BurstFrame>>),
+}
+
+impl HistoryBrowser {
+    /// Opens the newest capture in the history, `None` if the history is empty or unreadable.
+    pub fn open(vulkan: &Vulkan, store: HistoryStore) -> Option<(Self, CaptureResources)> {
+        let entries = store
+            .entries()
+            .report(ErrorCategory::History, "Could not read the capture history")?;
+
+        Self::open_source(vulkan, BrowsedCaptures::History { store, entries })
+    }
+
+    /// Opens the newest capture kept from the last burst, `None` if no captures were kept.
+    pub fn open_burst(
+        vulkan: &Vulkan,
+        frames: Rc<FrameRing<BurstFrame>>,
+    ) -> Option<(Self, CaptureResources)> {
+        Self::open_source(vulkan, BrowsedCaptures::Burst(frames))
+    }
+
+    fn open_source(vulkan: &Vulkan, source: BrowsedCaptures) -> Option<(Self, CaptureResources)> {
+        if source.len() == 0 {
+            return None;
+        }
+
+        let browser = Self { source, index: 0 };
+        let capture = browser.load(vulkan, 0)?;
+
+        Some((browser, capture))
+    }
+
+    /// If the browsed captures are already in the history.
+    pub fn is_history(&self) -> bool {
+        matches!(self.source, BrowsedCaptures::History { .. })
+    }
+
+    /// Loads the capture `offset` entries older than the current capture, `None` if there is no
+    /// capture there or it could not be loaded.
+    pub fn step(&mut self, vulkan: &Vulkan, offset: isize) -> Option<CaptureResources> {
+        let index = self
+            .index
+            .checked_add_signed(offset)
+            .filter(|index| *index < self.source.len())?;
+
+        let capture = self.load(vulkan, index)?;
+        self.index = index;
+
+        Some(capture)
+    }
+
+    fn load(&self, vulkan: &Vulkan, index: usize) -> Option<CaptureResources> {
+        let capture = match &self.source {
+            BrowsedCaptures::History { store, entries } => {
+                let entry = &entries[index];
+                let metadata = &entry.metadata;
+
+                let pixels = store.load(entry).report(
+                    ErrorCategory::History,
+                    "Could not open the capture from the history",
+                )?;
+
+                let hdr_capture =
+                    unsafe { HdrImage::from_cpu(vulkan, metadata.area.size(), &pixels) }.report(
+                        ErrorCategory::Import,
+                        "Could not open the capture from the history",
+                    )?;
+
+                debug!("Opened {} from the history", entry.id);
+
+                let [start_x, start_y, end_x, end_y] = metadata.selection;
+                CaptureResources {
+                    area: metadata.area,
+                    captures: Vec::new(),
+                    hdr_capture,
+                    whitepoint: metadata.whitepoint,
+                    selection: Selection {
+                        start: PhysicalPosition::new(start_x, start_y),
+                        end: PhysicalPosition::new(end_x, end_y),
+                    },
+                    lasso: None,
+                }
+            }
+
+            BrowsedCaptures::Burst(frames) => {
+                let frame = frames.newest(index)?;
+                let size = frame.area.size();
+
+                let hdr_capture = unsafe { HdrImage::from_cpu(vulkan, size, &frame.pixels) }
+                    .report(
+                        ErrorCategory::Import,
+                        "Could not open the capture from the burst",
+                    )?;
+
+                debug!("Opened burst capture {}", frame.number);
+
+                CaptureResources {
+                    area: frame.area,
+                    captures: Vec::new(),
+                    hdr_capture,
+                    whitepoint: frame.whitepoint,
+                    selection: Selection {
+                        start: PhysicalPosition::default(),
+                        end: PhysicalPosition::new(size[0] as f32, size[1] as f32),
+                    },
+                    lasso: None,
+                }
+            }
+        };
+
+        debug!("Browsing capture {} of {}", index + 1, self.source.len());
+
+        Some(capture)
+    }
+}
+
+impl BrowsedCaptures {
+    fn len(&self) -> usize {
+        match self {
+            Self::History { entries, .. } => entries.len(),
+            Self::Burst(frames) => frames.len(),
+        }
+    }
+}
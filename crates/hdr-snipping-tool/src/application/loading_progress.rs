@@ -0,0 +1,450 @@
+use vulkan::HdrImage;
+use winit::dpi::PhysicalPosition;
+
+use crate::{
+    capture_taker::{
+        CaptureProvider, DesktopRect, PlatformCaptureProvider, ProviderMonitor, Whitepoint,
+    },
+    diagnostics,
+    selection::Selection,
+};
+
+use super::LoadingEvent;
+
+/// What changed after the progress handled a loading event.
+#[derive(Clone, Copy)]
+pub enum LoadingUpdate {
+    /// The monitors were found, the capture will cover the area.
+    FoundArea(DesktopRect),
+
+    /// A monitor was captured.
+    GotCapture,
+
+    /// The capture was imported into Vulkan.
+    ImportedCapture(HdrImage),
+
+    /// The whitepoint of the capture was selected.
+    SelectedWhitepoint(Whitepoint),
+
+    /// Taking the capture failed.
+    Failed,
+}
+
+/// The state a loading application moves to after it handled a loading event.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LoadingTransition {
+    /// Some of the capture has not arrived yet.
+    Loading,
+
+    /// The capture is shown for the user to select from.
+    Active,
+
+    /// The capture is saved without being shown.
+    SaveInstantly,
+
+    /// Taking the capture failed, nothing is shown.
+    Inactive,
+}
+
+/// The resources gathered while a capture is loading.
+pub struct LoadingProgress<P: CaptureProvider = PlatformCaptureProvider> {
+    pub monitors: Vec<P::Monitor>,
+
+    /// The window being captured, if the capture is of a single window.
+    pub window: Option<P::Window>,
+
+    pub area: Option<DesktopRect>,
+    pub selection: Option<Selection>,
+    pub captures: Vec<P::Capture>,
+    pub hdr_capture: Option<HdrImage>,
+    pub whitepoint: Option<Whitepoint>,
+}
+
+impl<P: CaptureProvider> Default for LoadingProgress<P> {
+    fn default() -> Self {
+        Self {
+            monitors: Vec::new(),
+            window: None,
+            area: None,
+            selection: None,
+            captures: Vec::new(),
+            hdr_capture: None,
+            whitepoint: None,
+        }
+    }
+}
+
+impl<P: CaptureProvider> LoadingProgress<P> {
+    pub fn handle(&mut self, event: LoadingEvent<P>) -> LoadingUpdate {
+        match event {
+            LoadingEvent::FoundMonitors(monitors) => {
+                let Some(area) =
+                    DesktopRect::bounding(monitors.iter().map(|monitor| monitor.info().rect))
+                else {
+                    return LoadingUpdate::Failed;
+                };
+
+                diagnostics::record_monitors(&monitors);
+                self.monitors = monitors;
+                self.found_area(area)
+            }
+
+            LoadingEvent::FoundWindow(window) => {
+                let area = window.info().rect;
+                if area.width() == 0 || area.height() == 0 {
+                    return LoadingUpdate::Failed;
+                }
+
+                self.window = Some(window);
+                self.found_area(area)
+            }
+
+            LoadingEvent::GotCapture(capture) => {
+                self.captures.push(capture);
+                LoadingUpdate::GotCapture
+            }
+
+            LoadingEvent::ImportedCapture(hdr_capture) => {
+                self.hdr_capture = Some(hdr_capture);
+                LoadingUpdate::ImportedCapture(hdr_capture)
+            }
+
+            LoadingEvent::SelectedWhitepoint(whitepoint) => {
+                self.whitepoint = Some(whitepoint);
+                LoadingUpdate::SelectedWhitepoint(whitepoint)
+            }
+
+            LoadingEvent::Error => LoadingUpdate::Failed,
+        }
+    }
+
+    /// Covers the area with the capture and selects all of it.
+    fn found_area(&mut self, area: DesktopRect) -> LoadingUpdate {
+        self.area = Some(area);
+        self.selection = Some(Selection {
+            start: PhysicalPosition::default(),
+            end: PhysicalPosition::new(area.width() as f32, area.height() as f32),
+        });
+
+        LoadingUpdate::FoundArea(area)
+    }
+
+    /// If everything needed to show the capture has arrived.
+    pub fn is_finished(&self) -> bool {
+        self.area.is_some()
+            && !self.captures.is_empty()
+            && self.hdr_capture.is_some()
+            && self.whitepoint.is_some()
+            && self.selection.is_some()
+    }
+
+    /// The state to move to after the update, `saves_instantly` if the capture is saved without
+    /// being shown.
+    pub fn transition(&self, update: LoadingUpdate, saves_instantly: bool) -> LoadingTransition {
+        if matches!(update, LoadingUpdate::Failed) {
+            return LoadingTransition::Inactive;
+        }
+
+        if !self.is_finished() {
+            LoadingTransition::Loading
+        } else if saves_instantly {
+            LoadingTransition::SaveInstantly
+        } else {
+            LoadingTransition::Active
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::{
+        fs,
+        path::{Path, PathBuf},
+        sync::{Arc, mpsc::channel},
+    };
+
+    use ash::vk;
+    use half::f16;
+    use vulkan::{HdrImage, Vulkan};
+
+    use crate::{
+        application::LoadingEvent,
+        capture_taker::{
+            BlockingCaptureTaker, CaptureProvider, CaptureTarget, DesktopRect, SyntheticCapture,
+            SyntheticCaptureProvider, SyntheticMonitor, Whitepoint,
+        },
+        error_reporting::{ErrorCategory, test_sink},
+    };
+
+    use super::{LoadingProgress, LoadingTransition, LoadingUpdate};
+
+    fn monitor(name: &str, rect: DesktopRect, sdr_white: f32, frame: &str) -> SyntheticMonitor {
+        SyntheticMonitor {
+            name: name.to_string(),
+            rect,
+            sdr_white,
+            max_brightness: 10.0,
+            frames: vec![PathBuf::from(frame)],
+        }
+    }
+
+    fn rect(left: i32, top: i32, right: i32, bottom: i32) -> DesktopRect {
+        DesktopRect {
+            left,
+            top,
+            right,
+            bottom,
+        }
+    }
+
+    /// Writes a frame where every pixel has the same brightness.
+    fn write_frame(path: PathBuf, rect: DesktopRect, value: f32) {
+        let pixel = [value, value, value, 1.0].map(f16::from_f32);
+        let bytes: Vec<_> = (0..rect.width() * rect.height())
+            .flat_map(|_| pixel)
+            .flat_map(|channel| channel.to_le_bytes())
+            .collect();
+
+        fs::write(path, bytes).unwrap();
+    }
+
+    /// Creates an empty directory for the test's frames.
+    fn frame_directory(name: &str) -> PathBuf {
+        let directory = std::env::temp_dir()
+            .join("hdr-snipping-tool-tests")
+            .join(format!("{name}-{}", std::process::id()));
+
+        if directory.exists() {
+            fs::remove_dir_all(&directory).unwrap();
+        }
+        fs::create_dir_all(&directory).unwrap();
+
+        directory
+    }
+
+    /// Creates a provider with a dim left monitor and a hovered HDR right monitor.
+    fn provider(directory: &Path) -> SyntheticCaptureProvider {
+        provider_hovering(directory, Some(1))
+    }
+
+    /// Creates a provider with a dim left monitor and an HDR right monitor.
+    fn provider_hovering(directory: &Path, hovered: Option<usize>) -> SyntheticCaptureProvider {
+        let left = monitor("Left", rect(-32, 8, 0, 24), 1.0, "left.f16");
+        let right = monitor("Right", rect(0, 0, 48, 32), 2.0, "right.f16");
+
+        write_frame(directory.join("left.f16"), left.rect, 0.5);
+        write_frame(directory.join("right.f16"), right.rect, 6.0);
+
+        SyntheticCaptureProvider::new(directory.to_path_buf(), vec![left, right], hovered)
+    }
+
+    fn null_image() -> HdrImage {
+        HdrImage {
+            image: vk::Image::null(),
+            memory: vk::DeviceMemory::null(),
+            view: vk::ImageView::null(),
+            extent: vk::Extent2D::default().width(48).height(32),
+        }
+    }
+
+    #[test]
+    fn finishes_once_everything_arrived() {
+        let mut progress = LoadingProgress::<SyntheticCaptureProvider>::default();
+        let directory = frame_directory("finishes");
+        let monitors = provider(&directory).active_monitors().unwrap();
+
+        let update = progress.handle(LoadingEvent::FoundMonitors(monitors));
+        assert!(matches!(update, LoadingUpdate::FoundArea(area) if area == rect(-32, 0, 48, 32)));
+
+        let selection = progress.selection.unwrap();
+        assert_eq!([selection.end.x, selection.end.y], [80.0, 32.0]);
+
+        progress.handle(LoadingEvent::ImportedCapture(null_image()));
+        progress.handle(LoadingEvent::SelectedWhitepoint(Whitepoint::Hdr(10.0)));
+        assert!(!progress.is_finished());
+
+        progress.handle(LoadingEvent::GotCapture(SyntheticCapture {
+            monitor: "Right".to_string(),
+            frame: PathBuf::from("right.f16"),
+        }));
+        assert!(progress.is_finished());
+
+        fs::remove_dir_all(directory).unwrap();
+    }
+
+    #[test]
+    fn no_monitors_fails() {
+        let mut progress = LoadingProgress::<SyntheticCaptureProvider>::default();
+
+        let update = progress.handle(LoadingEvent::FoundMonitors(Vec::new()));
+        assert!(matches!(update, LoadingUpdate::Failed));
+        assert!(progress.area.is_none());
+    }
+
+    #[test]
+    fn found_window_covers_window() {
+        let mut progress = LoadingProgress::<SyntheticCaptureProvider>::default();
+        let window = monitor("Window", rect(10, 20, 40, 30), 2.0, "window.f16");
+
+        let update = progress.handle(LoadingEvent::FoundWindow(window));
+        assert!(matches!(update, LoadingUpdate::FoundArea(area) if area == rect(10, 20, 40, 30)));
+        assert!(progress.window.is_some());
+        assert!(progress.monitors.is_empty());
+
+        let selection = progress.selection.unwrap();
+        assert_eq!([selection.end.x, selection.end.y], [30.0, 10.0]);
+
+        let empty = monitor("Empty", rect(10, 20, 10, 30), 2.0, "empty.f16");
+        let mut progress = LoadingProgress::<SyntheticCaptureProvider>::default();
+        let update = progress.handle(LoadingEvent::FoundWindow(empty));
+        assert!(matches!(update, LoadingUpdate::Failed));
+    }
+
+    fn vulkan() -> Arc<Vulkan> {
+        Arc::new(
+            Vulkan::new(
+                true,
+                std::env::current_exe().unwrap().parent().unwrap(),
+                None,
+            )
+            .unwrap(),
+        )
+    }
+
+    /// Runs the capture taker against the synthetic provider and feeds its events into the
+    /// loading progress until it finishes, returning the transition after each event.
+    fn load_with_provider(
+        vulkan: &Arc<Vulkan>,
+        provider: SyntheticCaptureProvider,
+        target: CaptureTarget,
+    ) -> (
+        LoadingProgress<SyntheticCaptureProvider>,
+        Vec<LoadingTransition>,
+    ) {
+        let (sender, receiver) = channel();
+        {
+            let mut capture_taker = BlockingCaptureTaker::new(vulkan, provider);
+            capture_taker.capture_target(&sender, target);
+        }
+        drop(sender);
+
+        let mut progress = LoadingProgress::default();
+        let transitions = receiver
+            .into_iter()
+            .map(|event| {
+                let update = progress.handle(event);
+                progress.transition(update, false)
+            })
+            .collect();
+
+        (progress, transitions)
+    }
+
+    /// Loads a capture of the target, which must become active once it finishes.
+    fn load(
+        target: CaptureTarget,
+        name: &str,
+    ) -> (Arc<Vulkan>, LoadingProgress<SyntheticCaptureProvider>) {
+        let vulkan = vulkan();
+        let directory = frame_directory(name);
+
+        let (progress, transitions) = load_with_provider(&vulkan, provider(&directory), target);
+        fs::remove_dir_all(directory).unwrap();
+
+        assert_eq!(transitions.last(), Some(&LoadingTransition::Active));
+        assert!(progress.is_finished());
+        (vulkan, progress)
+    }
+
+    #[test]
+    fn loads_hovered_monitor() {
+        let (vulkan, progress) = load(CaptureTarget::HoveredMonitor, "hovered-monitor");
+
+        assert_eq!(progress.area, Some(rect(0, 0, 48, 32)));
+        assert_eq!(progress.captures.len(), 1);
+        assert!(matches!(progress.whitepoint, Some(Whitepoint::Hdr(10.0))));
+
+        let hdr_capture = progress.hdr_capture.unwrap();
+        assert_eq!(
+            [hdr_capture.extent.width, hdr_capture.extent.height],
+            [48, 32]
+        );
+
+        unsafe { hdr_capture.destroy(&vulkan) };
+    }
+
+    #[test]
+    fn loads_virtual_desktop() {
+        let (vulkan, progress) = load(CaptureTarget::VirtualDesktop, "virtual-desktop");
+
+        assert_eq!(progress.area, Some(rect(-32, 0, 48, 32)));
+        assert_eq!(progress.monitors.len(), 2);
+        assert_eq!(progress.captures.len(), 2);
+        assert!(matches!(progress.whitepoint, Some(Whitepoint::Hdr(_))));
+
+        let hdr_capture = progress.hdr_capture.unwrap();
+        assert_eq!(
+            [hdr_capture.extent.width, hdr_capture.extent.height],
+            [80, 32]
+        );
+
+        unsafe { hdr_capture.destroy(&vulkan) };
+    }
+
+    #[test]
+    fn failed_capture_reports_capture_error() {
+        let vulkan = vulkan();
+        let directory = frame_directory("no-hovered-monitor");
+        let provider = provider_hovering(&directory, None);
+
+        let ((progress, transitions), reports) = test_sink::capture(|| {
+            load_with_provider(&vulkan, provider, CaptureTarget::HoveredMonitor)
+        });
+        fs::remove_dir_all(directory).unwrap();
+
+        assert_eq!(test_sink::categories(&reports), [ErrorCategory::Capture]);
+        assert!(!reports[0].summary.contains("hovered_monitor was None"));
+        assert!(
+            !reports[0]
+                .shown_details()
+                .contains("hovered_monitor was None")
+        );
+
+        // The loading application is left for the inactive application.
+        assert_eq!(transitions, [LoadingTransition::Inactive]);
+        assert!(progress.area.is_none());
+    }
+
+    #[test]
+    fn becomes_active_once_loaded() {
+        let vulkan = vulkan();
+        let directory = frame_directory("becomes-active");
+
+        let (progress, transitions) =
+            load_with_provider(&vulkan, provider(&directory), CaptureTarget::HoveredMonitor);
+        fs::remove_dir_all(directory).unwrap();
+
+        // Loading until the last event, which finishes the capture.
+        let (last, loading) = transitions.split_last().unwrap();
+        assert!(!loading.is_empty());
+        assert!(
+            loading
+                .iter()
+                .all(|transition| *transition == LoadingTransition::Loading)
+        );
+        assert_eq!(*last, LoadingTransition::Active);
+
+        // An instant screenshot is saved rather than shown.
+        let update = LoadingUpdate::SelectedWhitepoint(progress.whitepoint.unwrap());
+        assert_eq!(
+            progress.transition(update, true),
+            LoadingTransition::SaveInstantly
+        );
+        assert_eq!(
+            progress.transition(LoadingUpdate::Failed, false),
+            LoadingTransition::Inactive
+        );
+
+        unsafe { progress.hdr_capture.unwrap().destroy(&vulkan) };
+    }
+}
@@ -1,7 +1,11 @@
 mod capture_resources;
 mod core_resources;
+mod countdown;
 mod events;
+mod history_browser;
+mod loading_progress;
 mod states;
 
-pub use events::{ApplicationEvent, KeyboardEvent, LoadingEvent, MouseEvent, WindowEvent};
+pub use events::{ApplicationEvent, Arrow, KeyboardEvent, LoadingEvent, MouseEvent, WindowEvent};
+pub use loading_progress::{LoadingProgress, LoadingTransition, LoadingUpdate};
 pub use states::{ApplicationState, initialise_state};
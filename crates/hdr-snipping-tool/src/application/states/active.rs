@@ -1,28 +1,179 @@
+use core::mem;
+use std::rc::Rc;
+
 use tracing::debug;
 use windows::Win32::Foundation::HWND;
+use winit::dpi::{PhysicalPosition, PhysicalSize};
 
 use crate::{
+    annotation::{AnnotationEditor, Tool},
     application::{
-        KeyboardEvent, MouseEvent, WindowEvent, capture_resources::CaptureResources,
-        core_resources::CoreResources,
+        Arrow, KeyboardEvent, MouseEvent, WindowEvent, capture_resources::CaptureResources,
+        core_resources::CoreResources, history_browser::HistoryBrowser,
     },
+    application_event_loop::Event,
+    burst::{BurstRequest, FrameRing},
+    capture_history::{HistoryMetadata, unix_millis},
     capture_saver::CaptureSaver,
-    selection::{Selection, SelectionEvent, SelectionState},
+    capture_taker::{CaptureTaker, DesktopRect, WindowTarget},
+    recording::{RecordingHandle, RecordingRequest},
+    selection::{Selection, SelectionEvent, SelectionMode, SelectionState, WindowRectProvider},
+    utilities::{failure::Ignore, windows_helpers::get_foreground_window},
 };
 
 use super::{
-    ApplicationEvent, ApplicationState, exited::ExitedApplication, inactive::InactiveApplication,
-    loading::LoadingApplication,
+    ApplicationEvent, ApplicationState, SelectionPurpose, exited::ExitedApplication,
+    inactive::InactiveApplication, loading::LoadingApplication,
 };
 
+/// How much each arrow key press changes the whitepoint by.
+const WHITEPOINT_STEP: f32 = 1.25;
+
 pub struct ActiveApplication {
     pub core: CoreResources,
     pub capture: CaptureResources,
     pub previous_focused_window: HWND,
     pub selection: Option<Box<dyn SelectionState>>,
+    pub windows: Box<dyn WindowRectProvider>,
+    pub selection_mode: SelectionMode,
+
+    /// The annotation tool the user has picked, if any, the selection is annotated before saving.
+    pub tool: Option<Tool>,
+
+    /// The annotations for the submitted selection, `Some` once annotating has started.
+    pub annotation: Option<AnnotationEditor>,
+
+    /// If clicking a window takes a capture of only that window.
+    pub is_picking_window: bool,
+
+    /// The window under the mouse, it is only drawn, pressing enter still saves the selection.
+    pub highlight: Option<Selection>,
+
+    /// The history being browsed, `Some` if the capture was opened from the history.
+    pub history: Option<HistoryBrowser>,
+
+    /// What submitting the selection does.
+    pub purpose: SelectionPurpose,
 }
 
 impl ActiveApplication {
+    /// A selection covering the whole capture.
+    fn full_selection(&self) -> Selection {
+        let size = self.capture.area.size();
+        Selection {
+            start: PhysicalPosition::default(),
+            end: PhysicalPosition::new(size[0] as f32, size[1] as f32),
+        }
+    }
+
+    /// Highlights the window under the mouse, or the selection if there is no window.
+    fn highlight_hovered_window(&mut self, position: PhysicalPosition<f32>) {
+        let highlight = self.windows.window_at(position);
+
+        if highlight != self.highlight {
+            self.highlight = highlight;
+            self.core
+                .renderer
+                .set_selection(highlight.unwrap_or(self.capture.selection));
+        }
+    }
+
+    /// Stops highlighting the window under the mouse, drawing the selection instead.
+    fn clear_highlight(&mut self) {
+        if self.highlight.take().is_some() {
+            self.core.renderer.set_selection(self.capture.selection);
+        }
+    }
+
+    /// Shows the capture in a window covering the area it was captured from.
+    fn show_capture(&mut self) {
+        let area = self.capture.area;
+
+        self.core
+            .renderer
+            .set_hdr_capture(Some(self.capture.hdr_capture));
+        self.core
+            .renderer
+            .set_whitepoint(self.capture.whitepoint.value());
+        self.core
+            .renderer
+            .set_max_brightness(self.capture.whitepoint.value());
+        self.highlight = None;
+        self.core.renderer.set_selection(self.capture.selection);
+        self.core.renderer.set_lasso(None);
+
+        self.core.window.set_fullscreen(None);
+        self.core.window.set_decorations(false);
+        self.core
+            .window
+            .request_inner_size(PhysicalSize::new(area.width(), area.height()))
+            .ignore();
+        self.core
+            .window
+            .set_outer_position(PhysicalPosition::new(area.left, area.top));
+
+        self.core.window.set_visible(true);
+        self.core.window.focus_window();
+        self.core.window.request_redraw();
+    }
+
+    /// Replaces the capture with an older or newer capture from the history.
+    fn step_history(&mut self, offset: isize) {
+        if self.selection.is_some() || self.annotation.is_some() {
+            return;
+        }
+
+        let Some(history) = self.history.as_mut() else {
+            return;
+        };
+
+        let Some(capture) = history.step(&self.core.vulkan, offset) else {
+            return;
+        };
+
+        let previous_capture = mem::replace(&mut self.capture, capture);
+        self.show_capture();
+        self.core.renderer.render();
+
+        let _queue_guard = unsafe { self.core.vulkan.device_wait_idle() };
+        unsafe { previous_capture.hdr_capture.destroy(&self.core.vulkan) };
+    }
+
+    /// Brightens or darkens the capture, the screenshot is tonemapped with the new whitepoint.
+    fn adjust_whitepoint(&mut self, factor: f32) {
+        self.capture.whitepoint = self.capture.whitepoint.scaled(factor);
+        debug!("Whitepoint: {}", self.capture.whitepoint.value());
+
+        self.core
+            .renderer
+            .set_whitepoint(self.capture.whitepoint.value());
+        self.core.window.request_redraw();
+    }
+
+    /// Takes a capture of a single window to replace the current capture.
+    fn pick_window(self: Box<Self>, target: WindowTarget) -> Box<dyn ApplicationState> {
+        debug!("Picked window {target:?}");
+        Box::new(LoadingApplication::from((*self, target)))
+    }
+
+    /// Switches between rectangle and freeform selections.
+    fn toggle_selection_mode(&mut self) {
+        if self.selection.is_some() || self.annotation.is_some() {
+            return;
+        }
+
+        self.selection_mode = self.selection_mode.toggled();
+        debug!("Selection mode: {:?}", self.selection_mode);
+
+        let selection = self.full_selection();
+        self.highlight = None;
+        self.capture.selection = selection;
+        self.capture.lasso = None;
+        self.core.renderer.set_selection(selection);
+        self.core.renderer.set_lasso(None);
+        self.core.window.request_redraw();
+    }
+
     fn handle_selection_update(mut self: Box<Self>) -> Box<dyn ApplicationState> {
         let Some(state) = self.selection.as_ref() else {
             return self;
@@ -33,28 +184,233 @@ impl ActiveApplication {
         };
 
         self.capture.selection = selection;
+        self.capture.lasso = state.lasso().cloned();
         self.core.renderer.set_selection(selection);
+        self.core.renderer.set_lasso(state.lasso());
         self.core.window.request_redraw();
 
         if state.is_submitted() {
-            self.save()
+            match self.tool {
+                Some(tool) => {
+                    self.start_annotating(tool);
+                    self
+                }
+                None => self.save(),
+            }
         } else {
             self
         }
     }
 
-    fn save(self: Box<Self>) -> Box<dyn ApplicationState> {
+    /// Picks the annotation tool, recordings and bursts are not annotated.
+    fn select_tool(&mut self, tool: Tool) {
+        if self.purpose != SelectionPurpose::Screenshot {
+            return;
+        }
+
+        debug!("Annotation tool: {tool:?}");
+        self.tool = Some(tool);
+
+        if let Some(annotation) = self.annotation.as_mut() {
+            annotation.set_tool(tool);
+            self.update_annotations();
+        }
+    }
+
+    /// Starts annotating the current selection.
+    fn start_annotating(&mut self, tool: Tool) {
+        debug!("Annotating");
+        self.annotation = Some(AnnotationEditor::new(
+            tool,
+            self.core.config.redaction_style,
+        ));
+        self.update_annotations();
+    }
+
+    fn update_annotations(&mut self) {
+        self.core.renderer.set_annotations(self.annotation.as_ref());
+        self.core.window.request_redraw();
+    }
+
+    fn handle_annotation_mouse_event(&mut self, mouse_event: MouseEvent) {
+        let Some(annotation) = self.annotation.as_mut() else {
+            return;
+        };
+
+        match mouse_event {
+            MouseEvent::Clicked(position) => annotation.mouse_clicked(position.into()),
+            MouseEvent::Moved(position) => annotation.mouse_moved(position.into()),
+            MouseEvent::Released => annotation.mouse_released(),
+        }
+
+        self.update_annotations();
+    }
+
+    fn handle_enter(mut self: Box<Self>) -> Box<dyn ApplicationState> {
+        // Enter finishes typing before it saves.
+        let finished_text = self
+            .annotation
+            .as_mut()
+            .is_some_and(AnnotationEditor::finish_text);
+        if finished_text {
+            self.update_annotations();
+            return self;
+        }
+
+        // Capture the window that had focus before the screenshot.
+        if self.is_picking_window && self.selection.is_none() && self.annotation.is_none() {
+            let handle = self.previous_focused_window.0 as isize;
+            return self.pick_window(WindowTarget::Handle(handle));
+        }
+
+        // Annotate the whole capture.
+        let tool = self
+            .tool
+            .filter(|_| self.annotation.is_none() && self.selection.is_none());
+        if let Some(tool) = tool {
+            self.clear_highlight();
+            self.start_annotating(tool);
+            return self;
+        }
+
+        self.save()
+    }
+
+    fn save(mut self: Box<Self>) -> Box<dyn ApplicationState> {
+        match self.purpose {
+            // Captures saved without showing them are saved before getting here.
+            SelectionPurpose::Screenshot
+            | SelectionPurpose::Instant
+            | SelectionPurpose::RepeatRegion(_) => {}
+            SelectionPurpose::Recording => return self.record(),
+            SelectionPurpose::Burst => return self.burst(),
+        }
+
         debug!("Saving");
 
+        // Remember the region to repeat, captures from the history may not match the desktop.
+        if self.history.is_none() {
+            self.core.last_region = self.selected_area();
+        }
+
+        // Captures from the history are already in it.
+        let selection = self.capture.selection;
+        let is_in_history = self
+            .history
+            .as_ref()
+            .is_some_and(HistoryBrowser::is_history);
+        let history = (!is_in_history).then(|| HistoryMetadata {
+            saved_at: unix_millis(),
+            area: self.capture.area,
+            whitepoint: self.capture.whitepoint,
+            selection: [
+                selection.start.x,
+                selection.start.y,
+                selection.end.x,
+                selection.end.y,
+            ],
+        });
+
         self.core.capture_saver.save_capture(
             self.capture.hdr_capture,
             self.capture.whitepoint.value(),
             self.capture.selection,
+            self.capture.lasso.take(),
+            self.annotation
+                .take()
+                .map(AnnotationEditor::into_annotations)
+                .unwrap_or_default(),
+            history,
+            self.core.config.file_names.screenshot_name(),
         );
 
         Box::new(InactiveApplication::from(*self))
     }
 
+    /// The selection in desktop coordinates, `None` if nothing is selected.
+    fn selected_area(&self) -> Option<DesktopRect> {
+        let [x, y] = self.capture.selection.position_as_usize();
+        let [width, height] = self.capture.selection.size_as_usize();
+        if width == 0 || height == 0 {
+            return None;
+        }
+
+        let left = self.capture.area.left + x as i32;
+        let top = self.capture.area.top + y as i32;
+        Some(DesktopRect {
+            left,
+            top,
+            right: left + width as i32,
+            bottom: top + height as i32,
+        })
+    }
+
+    /// Records the selected region, the application is hidden until the recording finishes.
+    fn record(self: Box<Self>) -> Box<dyn ApplicationState> {
+        let Some(area) = self.selected_area() else {
+            return self.cancel();
+        };
+
+        let request = RecordingRequest {
+            area,
+            file_name: self.core.config.file_names.recording_name(),
+            settings: self.core.config.recording,
+            handle: RecordingHandle::default(),
+        };
+
+        debug!("Recording {:?}", request.area);
+
+        // Hide the window before the first frame is recorded.
+        let mut application = InactiveApplication::from(*self);
+        application.recording = Some(request.handle.clone());
+        application
+            .core
+            .capture_taker
+            .record(application.core.proxy.clone(), request);
+        application
+            .core
+            .proxy
+            .send_event(Event::Recording(true))
+            .ignore();
+
+        Box::new(application)
+    }
+
+    /// Takes a burst of captures of the selected region, the application is hidden until the
+    /// burst finishes.
+    fn burst(self: Box<Self>) -> Box<dyn ApplicationState> {
+        let Some(area) = self.selected_area() else {
+            return self.cancel();
+        };
+
+        let request = BurstRequest {
+            area,
+            name: self.core.config.file_names.burst_name(),
+            settings: self.core.config.burst,
+            handle: RecordingHandle::default(),
+        };
+
+        debug!("Burst of {:?}", request.area);
+
+        // Hide the window before the first capture, the captures replace the last burst's.
+        let mut application = InactiveApplication::from(*self);
+        application.burst = Some(request.handle.clone());
+        application.core.burst_frames = Rc::new(FrameRing::new(
+            application.core.config.burst.kept_frames as usize,
+        ));
+        application
+            .core
+            .capture_taker
+            .burst(application.core.proxy.clone(), request);
+        application
+            .core
+            .proxy
+            .send_event(Event::Burst(true))
+            .ignore();
+
+        Box::new(application)
+    }
+
     fn cancel(self: Box<Self>) -> Box<dyn ApplicationState> {
         debug!("Cancelling");
         Box::new(InactiveApplication::from(*self))
@@ -70,18 +426,44 @@ impl ApplicationState for ActiveApplication {
                     self.core.window.request_redraw();
                 }
 
+                if self.annotation.is_some() {
+                    self.handle_annotation_mouse_event(mouse_event);
+                    return self;
+                }
+
                 let Some(selection) = self.selection.take() else {
-                    if let MouseEvent::Clicked(position) = mouse_event {
-                        self.selection = Some(Selection::mouse_clicked(position));
+                    match mouse_event {
+                        MouseEvent::Clicked(position) => {
+                            if self.is_picking_window && self.windows.window_at(position).is_some()
+                            {
+                                let area = self.capture.area;
+                                return self.pick_window(WindowTarget::At([
+                                    area.left + position.x as i32,
+                                    area.top + position.y as i32,
+                                ]));
+                            }
+
+                            self.clear_highlight();
+                            self.selection = Some(self.selection_mode.mouse_clicked(position))
+                        }
+                        MouseEvent::Moved(position) => {
+                            if self.selection_mode == SelectionMode::Rectangle {
+                                self.highlight_hovered_window(position)
+                            }
+                        }
+                        MouseEvent::Released => {}
                     }
                     return self;
                 };
 
                 self.selection = match mouse_event {
-                    MouseEvent::Moved(physical_position) => {
-                        selection.handle_event(SelectionEvent::MouseMoved(physical_position))
+                    MouseEvent::Moved(physical_position) => selection.handle_event(
+                        SelectionEvent::MouseMoved(physical_position),
+                        self.windows.as_ref(),
+                    ),
+                    MouseEvent::Released => {
+                        selection.handle_event(SelectionEvent::MouseReleased, self.windows.as_ref())
                     }
-                    MouseEvent::Released => selection.handle_event(SelectionEvent::MouseReleased),
                     _ => Some(selection),
                 };
 
@@ -90,7 +472,40 @@ impl ApplicationState for ActiveApplication {
 
             ApplicationEvent::KeyboardEvent(keyboard_event) => match keyboard_event {
                 KeyboardEvent::EscapePressed => self.cancel(),
-                KeyboardEvent::EnterPressed => self.save(),
+                KeyboardEvent::EnterPressed => self.handle_enter(),
+                KeyboardEvent::TabPressed => {
+                    self.toggle_selection_mode();
+                    self
+                }
+                KeyboardEvent::BackspacePressed => {
+                    if let Some(annotation) = self.annotation.as_mut() {
+                        annotation.backspace();
+                        self.update_annotations();
+                    }
+                    self
+                }
+                KeyboardEvent::ArrowPressed(arrow) => {
+                    match arrow {
+                        Arrow::Up => self.adjust_whitepoint(1.0 / WHITEPOINT_STEP),
+                        Arrow::Down => self.adjust_whitepoint(WHITEPOINT_STEP),
+                        Arrow::Left => self.step_history(1),
+                        Arrow::Right => self.step_history(-1),
+                    }
+                    self
+                }
+                KeyboardEvent::Character(character) => {
+                    let is_typed = self
+                        .annotation
+                        .as_mut()
+                        .is_some_and(|annotation| annotation.type_character(character));
+
+                    if is_typed {
+                        self.update_annotations();
+                    } else if let Some(tool) = Tool::from_key(character) {
+                        self.select_tool(tool);
+                    }
+                    self
+                }
             },
 
             ApplicationEvent::WindowEvent(window_event) => match window_event {
@@ -105,11 +520,20 @@ impl ApplicationState for ActiveApplication {
                 }
             },
 
+            ApplicationEvent::ConfigReloaded(config) => {
+                self.core.apply_config(*config);
+                self
+            }
+
             ApplicationEvent::Shutdown => Box::new(ExitedApplication::from(*self)),
 
             _ => self,
         }
     }
+
+    fn name(&self) -> &'static str {
+        "Active"
+    }
 }
 
 impl From<LoadingApplication> for ActiveApplication {
@@ -117,26 +541,30 @@ impl From<LoadingApplication> for ActiveApplication {
         debug!("[TRANSITION] Loading -> Active");
 
         let core = application.core;
+        let progress = application.progress;
+        let is_picking_window = application.pick_window
+            && progress.window.is_none()
+            && application.purpose == SelectionPurpose::Screenshot;
         let capture = CaptureResources {
-            monitor: application
-                .monitor
-                .expect("Transition to active requires monitor to be Some"),
+            area: progress
+                .area
+                .expect("Transition to active requires area to be Some"),
 
-            capture: application
-                .capture
-                .expect("Transition to active requires capture to be Some"),
+            captures: progress.captures,
 
-            hdr_capture: application
+            hdr_capture: progress
                 .hdr_capture
                 .expect("Transition to active requires hdr_capture to be Some"),
 
-            whitepoint: application
+            whitepoint: progress
                 .whitepoint
                 .expect("Transition to active requires whitepoint to be Some"),
 
-            selection: application
+            selection: progress
                 .selection
                 .expect("Transition to active requires selection to be Some"),
+
+            lasso: None,
         };
 
         Self {
@@ -144,6 +572,40 @@ impl From<LoadingApplication> for ActiveApplication {
             capture,
             previous_focused_window: application.previous_focused_window,
             selection: None,
+            windows: Box::new(application.window_rects),
+            selection_mode: SelectionMode::default(),
+            tool: None,
+            annotation: None,
+            is_picking_window,
+            highlight: None,
+            history: None,
+            purpose: application.purpose,
         }
     }
 }
+
+impl From<(InactiveApplication, HistoryBrowser, CaptureResources)> for ActiveApplication {
+    fn from(
+        (application, history, capture): (InactiveApplication, HistoryBrowser, CaptureResources),
+    ) -> Self {
+        debug!("[TRANSITION] Inactive -> Active");
+
+        let mut application = Self {
+            core: application.core,
+            capture,
+            previous_focused_window: get_foreground_window(),
+            selection: None,
+            windows: Box::new(Vec::<Selection>::new()),
+            selection_mode: SelectionMode::default(),
+            tool: None,
+            annotation: None,
+            is_picking_window: false,
+            highlight: None,
+            history: Some(history),
+            purpose: SelectionPurpose::Screenshot,
+        };
+
+        application.show_capture();
+        application
+    }
+}
@@ -13,6 +13,10 @@ impl ApplicationState for ExitedApplication {
     fn handle_event(self: Box<Self>, _event: super::ApplicationEvent) -> Box<dyn ApplicationState> {
         self
     }
+
+    fn name(&self) -> &'static str {
+        "Exited"
+    }
 }
 
 impl From<InactiveApplication> for ExitedApplication {
@@ -31,11 +35,11 @@ impl From<LoadingApplication> for ExitedApplication {
 
         let _queue_guard = unsafe { value.core.vulkan.device_wait_idle() };
 
-        if let Some(capture) = value.capture {
-            value.core.capture_taker.cleanup_windows_capture(capture);
+        for capture in value.progress.captures {
+            value.core.capture_taker.cleanup_capture(capture);
         }
 
-        if let Some(capture) = value.hdr_capture {
+        if let Some(capture) = value.progress.hdr_capture {
             unsafe { capture.destroy(&value.core.vulkan) };
         }
 
@@ -49,10 +53,9 @@ impl From<ActiveApplication> for ExitedApplication {
 
         let _queue_guard = unsafe { value.core.vulkan.device_wait_idle() };
 
-        value
-            .core
-            .capture_taker
-            .cleanup_windows_capture(value.capture.capture);
+        for capture in value.capture.captures {
+            value.core.capture_taker.cleanup_capture(capture);
+        }
 
         unsafe { value.capture.hdr_capture.destroy(&value.core.vulkan) };
 
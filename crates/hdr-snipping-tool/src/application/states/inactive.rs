@@ -1,38 +1,340 @@
-use tracing::debug;
+use core::time::Duration;
+use std::rc::Rc;
+
+use tracing::{debug, info};
 use winit::event_loop::{ActiveEventLoop, EventLoopProxy};
 
 use crate::{
-    application::core_resources::CoreResources, application_event_loop::Event,
-    capture_taker::CaptureTaker, utilities::windows_helpers::set_foreground_window,
+    application::{
+        KeyboardEvent,
+        core_resources::CoreResources,
+        countdown::{Countdown, CountdownTick, CountdownTicker, SystemClock},
+        history_browser::HistoryBrowser,
+    },
+    application_event_loop::Event,
+    capture_taker::{CaptureTaker, CaptureTarget},
+    config::Config,
+    recording::RecordingHandle,
+    save_notification::Notice,
+    utilities::{failure::Ignore, windows_helpers::set_foreground_window},
 };
 
 use super::{
-    ApplicationEvent, ApplicationState, active::ActiveApplication, exited::ExitedApplication,
-    loading::LoadingApplication,
+    ApplicationEvent, ApplicationState, SelectionPurpose, active::ActiveApplication,
+    exited::ExitedApplication, loading::LoadingApplication,
 };
 
 pub struct InactiveApplication {
     pub core: CoreResources,
+    countdown: Option<DelayedScreenshot>,
+
+    /// The running recording, `Some` until the capture taker has saved it.
+    pub recording: Option<RecordingHandle>,
+
+    /// The running burst, `Some` until the capture taker has taken its last capture.
+    pub burst: Option<RecordingHandle>,
+}
+
+/// A screenshot that will be taken once the countdown finishes.
+struct DelayedScreenshot {
+    countdown: Countdown<SystemClock>,
+    _ticker: CountdownTicker,
 }
 
 impl InactiveApplication {
-    pub fn new(event_loop: &ActiveEventLoop, proxy: EventLoopProxy<Event>) -> Self {
+    pub fn new(event_loop: &ActiveEventLoop, proxy: EventLoopProxy<Event>, config: Config) -> Self {
         Self {
-            core: CoreResources::new(event_loop, proxy),
+            core: CoreResources::new(event_loop, proxy, config),
+            countdown: None,
+            recording: None,
+            burst: None,
+        }
+    }
+
+    /// Starts counting down to a screenshot, or takes it immediately if there is no delay.
+    fn start_countdown(mut self: Box<Self>, delay: Duration) -> Box<dyn ApplicationState> {
+        if delay.is_zero() {
+            return Box::new(LoadingApplication::from(*self));
+        }
+
+        info!("Taking a screenshot in {}s", delay.as_secs());
+
+        self.countdown = Some(DelayedScreenshot {
+            countdown: Countdown::start(SystemClock, delay),
+            _ticker: CountdownTicker::new(self.core.proxy.clone()),
+        });
+
+        self.tick_countdown()
+    }
+
+    /// Updates the countdown display, or takes the screenshot if the countdown finished.
+    fn tick_countdown(mut self: Box<Self>) -> Box<dyn ApplicationState> {
+        let Some(delayed_screenshot) = self.countdown.as_mut() else {
+            return self;
+        };
+
+        match delayed_screenshot.countdown.tick() {
+            CountdownTick::Unchanged => self,
+
+            CountdownTick::Remaining(seconds) => {
+                self.core
+                    .proxy
+                    .send_event(Event::Countdown(Some(seconds)))
+                    .ignore();
+                self
+            }
+
+            CountdownTick::Finished => {
+                self.stop_countdown();
+                Box::new(LoadingApplication::from(*self))
+            }
+        }
+    }
+
+    /// Shows the notice without waiting for the user.
+    fn notify(&self, notice: Notice) {
+        self.core.proxy.send_event(Event::Notice(notice)).ignore();
+    }
+
+    /// Opens the newest capture in the history for re-editing.
+    fn open_history(mut self: Box<Self>) -> Box<dyn ApplicationState> {
+        self.stop_countdown();
+
+        let Some(history) = self.core.history.clone() else {
+            return self;
+        };
+
+        let is_empty = history.entries().is_ok_and(|entries| entries.is_empty());
+        if is_empty {
+            info!("The capture history is empty");
+            self.notify(Notice {
+                summary: "The capture history is empty",
+                details: "Saved screenshots can be re-edited from here.",
+            });
+            return self;
+        }
+
+        match HistoryBrowser::open(&self.core.vulkan, history) {
+            Some((browser, capture)) => {
+                Box::new(ActiveApplication::from((*self, browser, capture)))
+            }
+            None => self,
+        }
+    }
+
+    /// Opens the newest capture kept from the last burst, to pick the best capture to save.
+    fn open_burst_frames(mut self: Box<Self>) -> Box<dyn ApplicationState> {
+        self.stop_countdown();
+
+        if self.core.burst_frames.is_empty() {
+            info!("No captures were kept from a burst");
+            self.notify(Notice {
+                summary: "There are no burst captures",
+                details: "The captures of the last burst can be picked from here.",
+            });
+            return self;
+        }
+
+        let frames = Rc::clone(&self.core.burst_frames);
+        match HistoryBrowser::open_burst(&self.core.vulkan, frames) {
+            Some((browser, capture)) => {
+                Box::new(ActiveApplication::from((*self, browser, capture)))
+            }
+            None => self,
+        }
+    }
+
+    /// Handles an event while a burst is running, the capture taker is busy until the burst
+    /// finishes so only stopping the burst is possible.
+    fn handle_burst_event(
+        mut self: Box<Self>,
+        event: ApplicationEvent,
+    ) -> Box<dyn ApplicationState> {
+        match event {
+            ApplicationEvent::ScreenshotKeyPressed | ApplicationEvent::BurstRequested => {
+                if let Some(burst) = self.burst.as_ref() {
+                    info!("Stopping the burst");
+                    burst.stop();
+                }
+                self
+            }
+
+            ApplicationEvent::BurstFrame(frame) => {
+                debug!("Got burst capture {}", frame.number);
+                Rc::make_mut(&mut self.core.burst_frames).push(frame.clone());
+                self.core.capture_saver.save_burst_frame(frame);
+                self
+            }
+
+            ApplicationEvent::BurstFinished => {
+                self.burst = None;
+                self.core.proxy.send_event(Event::Burst(false)).ignore();
+                self
+            }
+
+            ApplicationEvent::Shutdown => {
+                if let Some(burst) = self.burst.take() {
+                    burst.stop();
+                }
+                Box::new(ExitedApplication::from(*self))
+            }
+
+            _ => self,
+        }
+    }
+
+    /// Handles an event while a region is being recorded, the capture taker is busy until the
+    /// recording finishes so only stopping the recording is possible.
+    fn handle_recording_event(
+        mut self: Box<Self>,
+        event: ApplicationEvent,
+    ) -> Box<dyn ApplicationState> {
+        match event {
+            ApplicationEvent::ScreenshotKeyPressed | ApplicationEvent::RecordingRequested => {
+                if let Some(recording) = self.recording.as_ref() {
+                    info!("Stopping the recording");
+                    recording.stop();
+                }
+                self
+            }
+
+            ApplicationEvent::RecordingFinished => {
+                self.recording = None;
+                self.core.proxy.send_event(Event::Recording(false)).ignore();
+                self
+            }
+
+            ApplicationEvent::Shutdown => {
+                if let Some(recording) = self.recording.take() {
+                    recording.stop();
+                }
+                Box::new(ExitedApplication::from(*self))
+            }
+
+            _ => self,
+        }
+    }
+
+    /// Stops the countdown and hides it.
+    fn stop_countdown(&mut self) {
+        if self.countdown.take().is_some() {
+            self.core.proxy.send_event(Event::Countdown(None)).ignore();
         }
     }
 }
 
 impl ApplicationState for InactiveApplication {
-    fn handle_event(self: Box<Self>, event: ApplicationEvent) -> Box<dyn ApplicationState> {
+    fn handle_event(mut self: Box<Self>, event: ApplicationEvent) -> Box<dyn ApplicationState> {
+        // A recording or burst keeps the settings it started with, the next uses the new config.
+        if let ApplicationEvent::ConfigReloaded(config) = event {
+            self.core.apply_config(*config);
+            return self;
+        }
+
+        if self.recording.is_some() {
+            return self.handle_recording_event(event);
+        }
+        if self.burst.is_some() {
+            return self.handle_burst_event(event);
+        }
+
         match event {
-            ApplicationEvent::ScreenshotKeyPressed => Box::new(LoadingApplication::from(*self)),
+            ApplicationEvent::ScreenshotKeyPressed => {
+                // Pressing the key during a countdown skips the rest of it.
+                if self.countdown.is_some() {
+                    self.stop_countdown();
+                    return Box::new(LoadingApplication::from(*self));
+                }
+
+                let delay = Duration::from_secs(u64::from(self.core.config.screenshot_delay));
+                self.start_countdown(delay)
+            }
+
+            ApplicationEvent::InstantScreenshotKeyPressed => {
+                self.stop_countdown();
+
+                let mut application =
+                    LoadingApplication::from((*self, CaptureTarget::HoveredMonitor));
+                application.purpose = SelectionPurpose::Instant;
+                Box::new(application)
+            }
+
+            ApplicationEvent::RepeatRegionRequested => {
+                self.stop_countdown();
+
+                let Some(region) = self.core.last_region else {
+                    info!("No region has been saved to repeat");
+                    self.notify(Notice {
+                        summary: "There is no region to repeat",
+                        details: "The region of the last saved screenshot is captured from here.",
+                    });
+                    return self;
+                };
+
+                // The region may cover more than one monitor.
+                let mut application =
+                    LoadingApplication::from((*self, CaptureTarget::VirtualDesktop));
+                application.purpose = SelectionPurpose::RepeatRegion(region);
+                Box::new(application)
+            }
 
-            ApplicationEvent::Shutdown => Box::new(ExitedApplication::from(*self)),
+            ApplicationEvent::WindowCaptureRequested => {
+                self.stop_countdown();
+
+                let mut application =
+                    LoadingApplication::from((*self, CaptureTarget::HoveredMonitor));
+                application.pick_window = true;
+                Box::new(application)
+            }
+
+            ApplicationEvent::DelayedScreenshotRequested(delay) => {
+                self.stop_countdown();
+                self.start_countdown(delay)
+            }
+
+            ApplicationEvent::CountdownTick => self.tick_countdown(),
+
+            ApplicationEvent::HistoryRequested => self.open_history(),
+
+            ApplicationEvent::RecordingRequested => {
+                self.stop_countdown();
+
+                let mut application = LoadingApplication::from(*self);
+                application.purpose = SelectionPurpose::Recording;
+                Box::new(application)
+            }
+
+            ApplicationEvent::BurstRequested => {
+                self.stop_countdown();
+
+                let mut application = LoadingApplication::from(*self);
+                application.purpose = SelectionPurpose::Burst;
+                Box::new(application)
+            }
+
+            ApplicationEvent::BurstFramesRequested => self.open_burst_frames(),
+
+            ApplicationEvent::KeyboardEvent(KeyboardEvent::EscapePressed) => {
+                if self.countdown.is_some() {
+                    info!("Cancelled the delayed screenshot");
+                    self.stop_countdown();
+                }
+
+                self
+            }
+
+            ApplicationEvent::Shutdown => {
+                self.stop_countdown();
+                Box::new(ExitedApplication::from(*self))
+            }
 
             _ => self,
         }
     }
+
+    fn name(&self) -> &'static str {
+        "Inactive"
+    }
 }
 
 impl From<LoadingApplication> for InactiveApplication {
@@ -50,15 +352,20 @@ impl From<LoadingApplication> for InactiveApplication {
 
             let _queue_guard = unsafe { core.vulkan.device_wait_idle() };
 
-            if let Some(capture) = application.hdr_capture.take() {
+            if let Some(capture) = application.progress.hdr_capture.take() {
                 unsafe { capture.destroy(&core.vulkan) };
             }
-            if let Some(capture) = application.capture.take() {
-                core.capture_taker.cleanup_windows_capture(capture);
+            for capture in application.progress.captures.drain(..) {
+                core.capture_taker.cleanup_capture(capture);
             }
         }
 
-        Self { core }
+        Self {
+            core,
+            countdown: None,
+            recording: None,
+            burst: None,
+        }
     }
 }
 
@@ -72,15 +379,23 @@ impl From<ActiveApplication> for InactiveApplication {
 
         {
             core.renderer.set_hdr_capture(None);
+            core.renderer.set_lasso(None);
+            core.renderer.set_annotations(None);
             core.renderer.render();
 
             let _queue_guard = unsafe { core.vulkan.device_wait_idle() };
 
             unsafe { application.capture.hdr_capture.destroy(&core.vulkan) };
-            core.capture_taker
-                .cleanup_windows_capture(application.capture.capture);
+            for capture in application.capture.captures {
+                core.capture_taker.cleanup_capture(capture);
+            }
         }
 
-        Self { core }
+        Self {
+            core,
+            countdown: None,
+            recording: None,
+            burst: None,
+        }
     }
 }
@@ -1,55 +1,85 @@
-use tracing::debug;
-use vulkan::HdrImage;
+use tracing::{debug, warn};
 use windows::Win32::Foundation::HWND;
-use windows_capture_provider::{Monitor, WindowsCapture};
-use winit::dpi::{PhysicalPosition, PhysicalSize};
+use winit::{
+    dpi::{PhysicalPosition, PhysicalSize},
+    window::Fullscreen,
+};
 
 use crate::{
-    application::{LoadingEvent, WindowEvent, core_resources::CoreResources},
-    capture_taker::{CaptureTaker, Whitepoint},
+    annotation::Annotations,
+    application::{
+        LoadingProgress, LoadingTransition, LoadingUpdate, WindowEvent,
+        core_resources::CoreResources,
+    },
+    application_event_loop::Event,
+    capture_history::{HistoryMetadata, unix_millis},
+    capture_saver::CaptureSaver,
+    capture_taker::{CaptureTaker, CaptureTarget, DesktopRect, WindowTarget},
+    error_reporting::{ErrorCategory, report_error},
     selection::Selection,
-    utilities::{failure::Ignore, windows_helpers::get_foreground_window},
+    utilities::{
+        failure::Ignore,
+        windows_helpers::{get_foreground_window, get_visible_window_rects},
+    },
 };
 
 use super::{
-    ApplicationEvent, ApplicationState, active::ActiveApplication, exited::ExitedApplication,
-    inactive::InactiveApplication,
+    ApplicationEvent, ApplicationState, SelectionPurpose, active::ActiveApplication,
+    exited::ExitedApplication, inactive::InactiveApplication,
 };
 
 pub struct LoadingApplication {
     pub core: CoreResources,
     pub previous_focused_window: HWND,
-    pub monitor: Option<Monitor>,
-    pub selection: Option<Selection>,
-    pub capture: Option<WindowsCapture>,
-    pub hdr_capture: Option<HdrImage>,
-    pub whitepoint: Option<Whitepoint>,
+    pub progress: LoadingProgress,
+    pub window_rects: Vec<Selection>,
     pub is_visible: bool,
+
+    /// What submitting the selection does.
+    pub purpose: SelectionPurpose,
+
+    /// If clicking a window in the capture takes a capture of only that window.
+    pub pick_window: bool,
 }
 
 impl LoadingApplication {
     fn update_window(&mut self) {
+        // Instant screenshots are saved without showing the capture.
+        if self.is_saved_instantly() {
+            return;
+        }
+
         let mut should_redraw = false;
 
-        if let Some(monitor) = self.monitor.as_ref() {
-            let window_size = monitor.size();
-            let window_size = PhysicalSize::new(window_size[0], window_size[1]);
+        if let Some(area) = self.progress.area {
+            // Borderless fullscreen is limited to one monitor, so the window covers the virtual
+            // desktop or the captured window as an undecorated window instead.
+            let is_fullscreen = self.progress.window.is_none() && self.progress.monitors.len() <= 1;
+            if !is_fullscreen && self.core.window.fullscreen().is_some() {
+                self.core.window.set_fullscreen(None);
+                self.core.window.set_decorations(false);
+            }
 
+            let window_size = PhysicalSize::new(area.width(), area.height());
             if self.core.window.inner_size() != window_size {
                 self.core.window.request_inner_size(window_size).ignore();
             }
 
-            let window_position = PhysicalPosition::new(
-                monitor.desktop_coordinates.left,
-                monitor.desktop_coordinates.top,
-            );
+            let window_position = PhysicalPosition::new(area.left, area.top);
             if self.core.window.outer_position().unwrap() != window_position {
                 self.core.window.set_outer_position(window_position);
             }
+
+            if is_fullscreen && self.core.window.fullscreen().is_none() {
+                self.core
+                    .window
+                    .set_fullscreen(Some(Fullscreen::Borderless(None)));
+            }
+
             should_redraw = true;
         }
 
-        if self.hdr_capture.is_some() && !self.is_visible {
+        if self.progress.hdr_capture.is_some() && !self.is_visible {
             self.is_visible = true;
             self.core.window.set_visible(true);
             self.core.window.focus_window();
@@ -66,69 +96,130 @@ impl LoadingApplication {
         }
     }
 
-    fn is_finished_loading(&self) -> bool {
-        self.monitor.is_some()
-            && self.capture.is_some()
-            && self.hdr_capture.is_some()
-            && self.whitepoint.is_some()
-            && self.selection.is_some()
+    /// If the capture is saved as soon as it is taken, without showing it.
+    fn is_saved_instantly(&self) -> bool {
+        matches!(
+            self.purpose,
+            SelectionPurpose::Instant | SelectionPurpose::RepeatRegion(_)
+        )
     }
 
-    fn transition_if_finished(mut self: Box<Self>) -> Box<dyn ApplicationState> {
-        if self.is_finished_loading() {
-            self.update_window();
-            Box::new(ActiveApplication::from(*self))
-        } else {
-            self
-        }
+    /// Saves the whole capture or the repeated region, then confirms it was saved as no window
+    /// was shown.
+    fn save_instantly(self: Box<Self>) -> Box<dyn ApplicationState> {
+        debug!("Saving instantly");
+
+        let area = self
+            .progress
+            .area
+            .expect("Saving instantly requires area to be Some");
+        let hdr_capture = self
+            .progress
+            .hdr_capture
+            .expect("Saving instantly requires hdr_capture to be Some");
+        let whitepoint = self
+            .progress
+            .whitepoint
+            .expect("Saving instantly requires whitepoint to be Some");
+
+        let selection = match self.purpose {
+            SelectionPurpose::RepeatRegion(region) => match region_in_area(region, area) {
+                Some(selection) => selection,
+                None => {
+                    report_error(
+                        ErrorCategory::Capture,
+                        format!("{region:?} is outside of {area:?}"),
+                        "Could not repeat the last region.\nThe region is no longer on the desktop",
+                    );
+                    return Box::new(InactiveApplication::from(*self));
+                }
+            },
+
+            _ => {
+                let [width, height] = area.size().map(|axis| axis as f32);
+                Selection {
+                    start: PhysicalPosition::default(),
+                    end: PhysicalPosition::new(width, height),
+                }
+            }
+        };
+        let history = HistoryMetadata {
+            saved_at: unix_millis(),
+            area,
+            whitepoint,
+            selection: [
+                selection.start.x,
+                selection.start.y,
+                selection.end.x,
+                selection.end.y,
+            ],
+        };
+
+        self.core.capture_saver.save_capture(
+            hdr_capture,
+            whitepoint.value(),
+            selection,
+            None,
+            Annotations::default(),
+            Some(history),
+            self.core.config.file_names.screenshot_name(),
+        );
+        self.core
+            .proxy
+            .send_event(Event::InstantScreenshotSaved)
+            .ignore();
+
+        Box::new(InactiveApplication::from(*self))
     }
 }
 
 impl ApplicationState for LoadingApplication {
     fn handle_event(mut self: Box<Self>, event: ApplicationEvent) -> Box<dyn ApplicationState> {
         match event {
-            ApplicationEvent::LoadingEvent(event) => match event {
-                LoadingEvent::FoundMonitor(monitor) => {
-                    self.monitor = Some(monitor);
-                    let selection = {
-                        let size = monitor.size();
-                        Selection {
-                            start: PhysicalPosition::default(),
-                            end: PhysicalPosition::new(size[0] as f32, size[1] as f32),
+            ApplicationEvent::LoadingEvent(event) => {
+                let update = self.progress.handle(event);
+                match update {
+                    LoadingUpdate::FoundArea(area) => {
+                        // The windows covering a window capture are not in the capture.
+                        if self.progress.window.is_none() {
+                            self.window_rects = window_rects_in_area(area);
                         }
-                    };
-                    self.selection = Some(selection);
 
-                    self.core.renderer.set_selection(selection);
+                        if let Some(selection) = self.progress.selection {
+                            self.core.renderer.set_selection(selection);
+                        }
+                    }
 
-                    self.update_window();
-                    self.transition_if_finished()
-                }
+                    LoadingUpdate::GotCapture => {}
 
-                LoadingEvent::GotCapture(windows_capture) => {
-                    self.capture = Some(windows_capture);
+                    LoadingUpdate::ImportedCapture(hdr_capture) => {
+                        self.core.renderer.set_hdr_capture(Some(hdr_capture));
+                    }
 
-                    self.update_window();
-                    self.transition_if_finished()
-                }
+                    LoadingUpdate::SelectedWhitepoint(whitepoint) => {
+                        self.core.renderer.set_whitepoint(whitepoint.value());
+                        self.core.renderer.set_max_brightness(whitepoint.value());
+                    }
 
-                LoadingEvent::ImportedCapture(hdr_image) => {
-                    self.hdr_capture = Some(hdr_image);
-                    self.core.renderer.set_hdr_capture(self.hdr_capture);
-                    self.update_window();
-                    self.transition_if_finished()
+                    LoadingUpdate::Failed => {}
                 }
 
-                LoadingEvent::SelectedWhitepoint(whitepoint) => {
-                    self.whitepoint = Some(whitepoint);
-                    self.core.renderer.set_whitepoint(whitepoint.value());
-                    self.core.renderer.set_max_brightness(whitepoint.value());
-                    self.update_window();
-                    self.transition_if_finished()
-                }
+                match self.progress.transition(update, self.is_saved_instantly()) {
+                    LoadingTransition::Loading => {
+                        self.update_window();
+                        self
+                    }
 
-                LoadingEvent::Error => Box::new(InactiveApplication::from(*self)),
-            },
+                    LoadingTransition::Active => {
+                        self.update_window();
+                        Box::new(ActiveApplication::from(*self))
+                    }
+
+                    LoadingTransition::SaveInstantly => self.save_instantly(),
+
+                    LoadingTransition::Inactive => Box::new(InactiveApplication::from(*self)),
+                }
+            }
 
             ApplicationEvent::WindowEvent(window_event) => match window_event {
                 WindowEvent::RedrawRequested => {
@@ -141,33 +232,135 @@ impl ApplicationState for LoadingApplication {
                 }
             },
 
+            ApplicationEvent::ConfigReloaded(config) => {
+                self.core.apply_config(*config);
+                self
+            }
+
             ApplicationEvent::Shutdown => Box::new(ExitedApplication::from(*self)),
 
             _ => self,
         }
     }
+
+    fn name(&self) -> &'static str {
+        "Loading"
+    }
 }
 
 impl From<InactiveApplication> for LoadingApplication {
     fn from(application: InactiveApplication) -> Self {
+        let target = if application.core.config.capture_virtual_desktop {
+            CaptureTarget::VirtualDesktop
+        } else {
+            CaptureTarget::HoveredMonitor
+        };
+
+        Self::from((application, target))
+    }
+}
+
+impl From<(InactiveApplication, CaptureTarget)> for LoadingApplication {
+    fn from((application, target): (InactiveApplication, CaptureTarget)) -> Self {
         debug!("[TRANSITION] Inactive -> Loading");
 
         let mut application = Self {
             core: application.core,
             previous_focused_window: get_foreground_window(),
-            monitor: None,
-            selection: None,
-            capture: None,
-            hdr_capture: None,
-            whitepoint: None,
+            progress: LoadingProgress::default(),
+            window_rects: Vec::new(),
             is_visible: false,
+            purpose: SelectionPurpose::default(),
+            pick_window: application.core.config.capture_window,
         };
 
         application
             .core
             .capture_taker
-            .take_capture(application.core.proxy.clone());
+            .take_capture(application.core.proxy.clone(), target);
 
         application
     }
 }
+
+impl From<(ActiveApplication, WindowTarget)> for LoadingApplication {
+    fn from((application, target): (ActiveApplication, WindowTarget)) -> Self {
+        debug!("[TRANSITION] Active -> Loading");
+
+        let mut core = application.core;
+
+        // Clean up the capture the window was picked from, the window stays visible until the
+        // window capture replaces it.
+        {
+            core.renderer.set_hdr_capture(None);
+            core.renderer.set_lasso(None);
+            core.renderer.set_annotations(None);
+            core.renderer.render();
+
+            let _queue_guard = unsafe { core.vulkan.device_wait_idle() };
+
+            unsafe { application.capture.hdr_capture.destroy(&core.vulkan) };
+            for capture in application.capture.captures {
+                core.capture_taker.cleanup_capture(capture);
+            }
+        }
+
+        core.capture_taker
+            .take_capture(core.proxy.clone(), CaptureTarget::Window(target));
+
+        Self {
+            core,
+            previous_focused_window: application.previous_focused_window,
+            progress: LoadingProgress::default(),
+            window_rects: Vec::new(),
+            is_visible: true,
+            purpose: SelectionPurpose::default(),
+            pick_window: false,
+        }
+    }
+}
+
+/// Returns a region of the desktop relative to the top-left corner of the area, clipped to the
+/// area. `None` if the region is outside of the area.
+fn region_in_area(region: DesktopRect, area: DesktopRect) -> Option<Selection> {
+    let region = region.intersection(area)?;
+
+    Some(Selection {
+        start: PhysicalPosition::new(
+            (region.left - area.left) as f32,
+            (region.top - area.top) as f32,
+        ),
+        end: PhysicalPosition::new(
+            (region.right - area.left) as f32,
+            (region.bottom - area.top) as f32,
+        ),
+    })
+}
+
+/// Returns the bounds of the visible windows in an area of the desktop, relative to the top-left
+/// corner of the area.
+fn window_rects_in_area(area: DesktopRect) -> Vec<Selection> {
+    let rects = get_visible_window_rects()
+        .inspect_err(|e| warn!("Could not get the visible windows: {e}"))
+        .unwrap_or_default();
+
+    rects
+        .into_iter()
+        .filter_map(|rect| {
+            // Clip the window to the area
+            let left = rect.left.max(area.left) - area.left;
+            let top = rect.top.max(area.top) - area.top;
+            let right = rect.right.min(area.right) - area.left;
+            let bottom = rect.bottom.min(area.bottom) - area.top;
+
+            if right <= left || bottom <= top {
+                return None;
+            }
+
+            Some(Selection {
+                start: PhysicalPosition::new(left as f32, top as f32),
+                end: PhysicalPosition::new(right as f32, bottom as f32),
+            })
+        })
+        .collect()
+}
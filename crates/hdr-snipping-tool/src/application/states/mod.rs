@@ -1,7 +1,7 @@
 use inactive::InactiveApplication;
 use winit::event_loop::{ActiveEventLoop, EventLoopProxy};
 
-use crate::application_event_loop::Event;
+use crate::{application_event_loop::Event, capture_taker::DesktopRect, config::Config};
 
 use super::events::ApplicationEvent;
 
@@ -10,13 +10,37 @@ mod exited;
 mod inactive;
 mod loading;
 
+/// What submitting the selection does.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SelectionPurpose {
+    /// Saves a screenshot of the selection.
+    #[default]
+    Screenshot,
+
+    /// Records the selected region.
+    Recording,
+
+    /// Takes a burst of captures of the selected region.
+    Burst,
+
+    /// Saves the whole capture without showing it, there is no selection.
+    Instant,
+
+    /// Saves a region of the desktop without showing the capture.
+    RepeatRegion(DesktopRect),
+}
+
 pub trait ApplicationState {
     fn handle_event(self: Box<Self>, event: ApplicationEvent) -> Box<dyn ApplicationState>;
+
+    /// The name of the state, recorded in the diagnostics.
+    fn name(&self) -> &'static str;
 }
 
 pub fn initialise_state(
     event_loop: &ActiveEventLoop,
     proxy: EventLoopProxy<Event>,
+    config: Config,
 ) -> Box<dyn ApplicationState> {
-    Box::new(InactiveApplication::new(event_loop, proxy))
+    Box::new(InactiveApplication::new(event_loop, proxy, config))
 }
@@ -1,60 +1,400 @@
+use core::time::Duration;
+use std::time::Instant;
+
 use ::tray_icon::menu::MenuEvent;
+use global_hotkey::{
+    GlobalHotKeyManager,
+    hotkey::{Code, HotKey},
+};
+use tracing::{info, warn};
 use winit::{
     application::ApplicationHandler,
     dpi::PhysicalPosition,
     event::{ElementState, MouseButton, WindowEvent},
-    event_loop::{ActiveEventLoop, EventLoopProxy},
+    event_loop::{ActiveEventLoop, ControlFlow, EventLoopProxy},
     keyboard::{Key, NamedKey},
     window::WindowId,
 };
 
 use crate::{
     application::{
-        self, ApplicationEvent, ApplicationState, KeyboardEvent, MouseEvent, initialise_state,
+        self, ApplicationEvent, ApplicationState, Arrow, KeyboardEvent, MouseEvent,
+        initialise_state,
     },
-    utilities::failure::Ignore,
+    config::{self, Config, ConfigWatcher, LoadedConfig},
+    diagnostics,
+    error_reporting::{ErrorCategory, ErrorReport, report_error},
+    hotkeys::{self, Hotkeys},
+    save_notification::{Notice, SavedScreenshot},
+    settings,
+    utilities::{failure::Ignore, windows_helpers::play_confirmation_sound},
 };
 
+use save_notification::SaveNotification;
+use status_overlay::StatusOverlay;
 pub use tray_icon::TrayIcon;
 
+mod save_notification;
+mod status_overlay;
 mod tray_icon;
 
+/// The key that cancels a delayed screenshot, registered as a hotkey while counting down.
+pub const CANCEL_COUNTDOWN_KEY: Code = Code::Escape;
+
+/// How long the overlay shows that an instant screenshot was saved.
+const SAVED_CONFIRMATION_DURATION: Duration = Duration::from_millis(1200);
+
+/// How long the save notification stays once the cursor leaves it.
+const NOTIFICATION_LINGER_DURATION: Duration = Duration::from_secs(2);
+
+/// How long an error or notice is shown for, it stays while the cursor is over it.
+const MESSAGE_NOTIFICATION_DURATION: Duration = Duration::from_secs(10);
+
 pub enum Event {
     ApplicationEvent(ApplicationEvent),
     TrayEvent(MenuEvent),
+
+    /// A registered hotkey with the id was pressed.
+    Hotkey(u32),
+
+    /// The config file should be loaded again, even if it has not changed.
+    ConfigChanged,
+
+    /// The config file was loaded after it changed, or the problems that stopped it loading.
+    ConfigLoaded(Result<LoadedConfig, String>),
+
+    /// The settings window should be opened.
+    SettingsRequested,
+
+    /// The application should exit.
+    QuitRequested,
+
+    /// The seconds until a delayed screenshot, or `None` once the countdown stops.
+    Countdown(Option<u64>),
+
+    /// If a region is being recorded.
+    Recording(bool),
+
+    /// If a burst of captures is being taken.
+    Burst(bool),
+
+    /// An instant screenshot was sent to be saved, confirms it as no window was shown.
+    InstantScreenshotSaved,
+
+    /// A screenshot was saved to the file, shows the save notification.
+    ScreenshotSaved(SavedScreenshot),
+
+    /// An error was reported, shows it in a notification.
+    ErrorReported(ErrorReport),
+
+    /// Something the user should know, shows it in a notification.
+    Notice(Notice),
 }
 
 pub struct ApplicationEventLoop {
     proxy: EventLoopProxy<Event>,
+    config: Config,
     mouse_position: PhysicalPosition<f32>,
     state: Option<Box<dyn ApplicationState>>,
+
+    tray_icon: TrayIcon,
+    hotkey_manager: GlobalHotKeyManager,
+    hotkeys: Hotkeys,
+    status_overlay: Option<StatusOverlay>,
+    save_notification: Option<SaveNotification>,
+    /// Shows errors and notices.
+    message_notification: Option<SaveNotification>,
+    is_cancel_hotkey_registered: bool,
+
+    /// When the overlay stops showing that an instant screenshot was saved.
+    saved_confirmation_until: Option<Instant>,
+
+    /// When the save notification is hidden, `None` while it is hidden or the cursor is over it.
+    notification_until: Option<Instant>,
+
+    /// When the message notification is hidden, like the save notification.
+    message_notification_until: Option<Instant>,
+
+    config_watcher: ConfigWatcher,
 }
 
 impl ApplicationEventLoop {
-    pub fn new(proxy: EventLoopProxy<Event>) -> Self {
+    pub fn new(
+        proxy: EventLoopProxy<Event>,
+        config: Config,
+        tray_icon: TrayIcon,
+        hotkey_manager: GlobalHotKeyManager,
+        hotkeys: Hotkeys,
+    ) -> Self {
+        let config_watcher = ConfigWatcher::new(proxy.clone());
+
         Self {
             proxy,
+            config,
             mouse_position: PhysicalPosition::default(),
             state: None,
+            tray_icon,
+            hotkey_manager,
+            hotkeys,
+            status_overlay: None,
+            save_notification: None,
+            message_notification: None,
+            is_cancel_hotkey_registered: false,
+            saved_confirmation_until: None,
+            notification_until: None,
+            message_notification_until: None,
+            config_watcher,
+        }
+    }
+
+    /// Applies the reloaded config file, an invalid config file keeps the current config.
+    fn reload_config(&mut self, loaded: Result<LoadedConfig, String>) {
+        let config = match loaded {
+            Ok(loaded) => {
+                config::report_warnings(&loaded.warnings);
+                loaded.config
+            }
+            Err(problems) => {
+                config::report_problems(
+                    problems,
+                    "The config file has errors, the previous config is still being used",
+                );
+                return;
+            }
+        };
+
+        if config == self.config {
+            return;
+        }
+        info!("Reloaded the config");
+
+        if config.hotkeys != self.config.hotkeys {
+            self.hotkeys.unregister(&self.hotkey_manager);
+
+            let (hotkeys, errors) = Hotkeys::register(&self.hotkey_manager, &config.hotkeys);
+            hotkeys::report(&errors);
+            self.hotkeys = hotkeys;
+        }
+
+        diagnostics::record_config(&config);
+        self.config = config.clone();
+        self.proxy
+            .send_event(ApplicationEvent::ConfigReloaded(Box::new(config)).into())
+            .ignore();
+    }
+
+    /// Returns the status overlay, creating it the first time it is needed.
+    fn status_overlay(&mut self, event_loop: &ActiveEventLoop) -> &mut StatusOverlay {
+        self.status_overlay
+            .get_or_insert_with(|| StatusOverlay::new(event_loop))
+    }
+
+    /// Plays a sound and briefly shows a tick, depending on the config.
+    fn confirm_instant_screenshot(&mut self, event_loop: &ActiveEventLoop) {
+        let settings = self.config.instant_screenshot;
+
+        if settings.sound {
+            if let Err(error) = play_confirmation_sound() {
+                warn!("Could not play the confirmation sound: {error}");
+            }
+        }
+
+        if settings.flash {
+            self.status_overlay(event_loop).show_saved();
+
+            let until = Instant::now() + SAVED_CONFIRMATION_DURATION;
+            self.saved_confirmation_until = Some(until);
+            event_loop.set_control_flow(ControlFlow::WaitUntil(until));
+        }
+    }
+
+    /// Shows the saved screenshot in the notification, which hides after the configured seconds.
+    fn show_save_notification(
+        &mut self,
+        event_loop: &ActiveEventLoop,
+        screenshot: SavedScreenshot,
+    ) {
+        let seconds = u64::from(self.config.notification.seconds);
+
+        self.save_notification
+            .get_or_insert_with(|| SaveNotification::new(event_loop))
+            .show(screenshot);
+        self.stack_notifications();
+
+        let until = Instant::now() + Duration::from_secs(seconds);
+        self.notification_until = Some(until);
+        event_loop.set_control_flow(ControlFlow::WaitUntil(until));
+    }
+
+    /// Shows the error or notice in its own notification, so it is not replaced by a saved
+    /// screenshot.
+    fn show_message_notification(
+        &mut self,
+        event_loop: &ActiveEventLoop,
+        show: impl FnOnce(&mut SaveNotification),
+    ) {
+        show(
+            self.message_notification
+                .get_or_insert_with(|| SaveNotification::new(event_loop)),
+        );
+        self.stack_notifications();
+
+        let until = Instant::now() + MESSAGE_NOTIFICATION_DURATION;
+        self.message_notification_until = Some(until);
+        event_loop.set_control_flow(ControlFlow::WaitUntil(until));
+    }
+
+    /// Shows the message notification above the save notification while both are shown.
+    fn stack_notifications(&self) {
+        let is_saved_visible = self
+            .save_notification
+            .as_ref()
+            .is_some_and(SaveNotification::is_visible);
+
+        if let Some(notification) = self.message_notification.as_ref() {
+            notification.set_slot(u32::from(is_saved_visible));
+        }
+    }
+
+    /// Handles the cursor and clicks over the save or message notification.
+    fn notification_event(
+        &mut self,
+        event_loop: &ActiveEventLoop,
+        is_message: bool,
+        event: WindowEvent,
+    ) {
+        let (notification, until) = if is_message {
+            (
+                &mut self.message_notification,
+                &mut self.message_notification_until,
+            )
+        } else {
+            (&mut self.save_notification, &mut self.notification_until)
+        };
+        let Some(notification) = notification.as_mut() else {
+            return;
+        };
+
+        match event {
+            WindowEvent::RedrawRequested => notification.draw(),
+
+            // The notification stays while the cursor is over it.
+            WindowEvent::CursorEntered { device_id: _ } => *until = None,
+
+            WindowEvent::CursorLeft { device_id: _ } => {
+                notification.set_cursor(None);
+
+                if notification.is_visible() {
+                    let linger_until = Instant::now() + NOTIFICATION_LINGER_DURATION;
+                    *until = Some(linger_until);
+                    event_loop.set_control_flow(ControlFlow::WaitUntil(linger_until));
+                }
+            }
+
+            WindowEvent::CursorMoved {
+                device_id: _,
+                position,
+            } => notification.set_cursor(Some([position.x as i32, position.y as i32])),
+
+            WindowEvent::MouseInput {
+                device_id: _,
+                state: ElementState::Released,
+                button,
+            } => {
+                let action = match button {
+                    MouseButton::Left => notification.clicked_action(),
+                    MouseButton::Right => None,
+                    _ => return,
+                };
+
+                notification.hide();
+                *until = None;
+                self.stack_notifications();
+
+                if let Some((action, screenshot)) = action {
+                    if let Err(e) = action.run(&screenshot) {
+                        report_error(
+                            ErrorCategory::Notification,
+                            e,
+                            "Could not carry out the action on the saved screenshot",
+                        );
+                    }
+                }
+            }
+
+            _ => {}
+        }
+    }
+
+    /// Shows the countdown in the tray and overlay, and lets escape cancel it from any window.
+    fn update_countdown(&mut self, event_loop: &ActiveEventLoop, seconds: Option<u64>) {
+        self.tray_icon.set_countdown(seconds);
+
+        if seconds.is_some() {
+            self.status_overlay(event_loop).set_seconds(seconds);
+        } else if let Some(overlay) = self.status_overlay.as_mut() {
+            overlay.set_seconds(seconds);
+        }
+
+        let cancel_hotkey = HotKey::new(None, CANCEL_COUNTDOWN_KEY);
+        match seconds {
+            Some(_) if !self.is_cancel_hotkey_registered => {
+                match self.hotkey_manager.register(cancel_hotkey) {
+                    Ok(()) => self.is_cancel_hotkey_registered = true,
+                    Err(error) => warn!("Could not register the countdown cancel hotkey: {error}"),
+                }
+            }
+
+            None if self.is_cancel_hotkey_registered => {
+                if let Err(error) = self.hotkey_manager.unregister(cancel_hotkey) {
+                    warn!("Could not unregister the countdown cancel hotkey: {error}");
+                }
+                self.is_cancel_hotkey_registered = false;
+            }
+
+            _ => {}
         }
     }
 }
 
 impl ApplicationHandler<Event> for ApplicationEventLoop {
     fn resumed(&mut self, event_loop: &ActiveEventLoop) {
-        self.state = Some(initialise_state(event_loop, self.proxy.clone()));
+        let state = initialise_state(event_loop, self.proxy.clone(), self.config.clone());
+        diagnostics::record_state(state.name());
+        self.state = Some(state);
     }
 
     fn window_event(
         &mut self,
         event_loop: &ActiveEventLoop,
-        _window_id: WindowId,
+        window_id: WindowId,
         event: WindowEvent,
     ) {
         if self.state.is_none() {
             return;
         }
 
+        if let Some(overlay) = self.status_overlay.as_ref() {
+            if overlay.id() == window_id {
+                if event == WindowEvent::RedrawRequested {
+                    overlay.draw();
+                }
+
+                return;
+            }
+        }
+
+        let is_window = |notification: &Option<SaveNotification>| {
+            notification
+                .as_ref()
+                .is_some_and(|notification| notification.id() == window_id)
+        };
+        if is_window(&self.save_notification) || is_window(&self.message_notification) {
+            let is_message = is_window(&self.message_notification);
+            self.notification_event(event_loop, is_message, event);
+            return;
+        }
+
         if event == WindowEvent::Destroyed {
             event_loop.exit();
             return;
@@ -73,6 +413,18 @@ impl ApplicationHandler<Event> for ApplicationEventLoop {
                 event,
                 is_synthetic: _,
             } => {
+                let pressed_text = event
+                    .text
+                    .as_ref()
+                    .filter(|_| event.state == ElementState::Pressed);
+                if let Some(text) = pressed_text {
+                    for character in text.chars().filter(|character| !character.is_control()) {
+                        self.proxy
+                            .send_event(KeyboardEvent::Character(character).into())
+                            .ignore();
+                    }
+                }
+
                 if let Key::Named(named_key) = event.logical_key {
                     match named_key {
                         NamedKey::Enter => self
@@ -85,6 +437,35 @@ impl ApplicationHandler<Event> for ApplicationEventLoop {
                             .send_event(KeyboardEvent::EscapePressed.into())
                             .ignore(),
 
+                        NamedKey::Tab if event.state == ElementState::Pressed && !event.repeat => {
+                            self.proxy
+                                .send_event(KeyboardEvent::TabPressed.into())
+                                .ignore()
+                        }
+
+                        NamedKey::Backspace if event.state == ElementState::Pressed => self
+                            .proxy
+                            .send_event(KeyboardEvent::BackspacePressed.into())
+                            .ignore(),
+
+                        NamedKey::ArrowUp
+                        | NamedKey::ArrowDown
+                        | NamedKey::ArrowLeft
+                        | NamedKey::ArrowRight
+                            if event.state == ElementState::Pressed =>
+                        {
+                            let arrow = match named_key {
+                                NamedKey::ArrowUp => Arrow::Up,
+                                NamedKey::ArrowDown => Arrow::Down,
+                                NamedKey::ArrowLeft => Arrow::Left,
+                                _ => Arrow::Right,
+                            };
+
+                            self.proxy
+                                .send_event(KeyboardEvent::ArrowPressed(arrow).into())
+                                .ignore()
+                        }
+
                         _ => {}
                     }
                 }
@@ -132,14 +513,98 @@ impl ApplicationHandler<Event> for ApplicationEventLoop {
         match event {
             Event::ApplicationEvent(application_event) => {
                 if let Some(state) = self.state.take() {
-                    self.state = Some(state.handle_event(application_event));
+                    let state = state.handle_event(application_event);
+                    diagnostics::record_state(state.name());
+                    self.state = Some(state);
                 }
             }
 
-            Event::TrayEvent(menu_event) => TrayIcon::handle_event(event_loop, menu_event),
+            Event::TrayEvent(menu_event) => {
+                TrayIcon::handle_event(event_loop, &self.proxy, menu_event)
+            }
+
+            Event::Hotkey(id) => {
+                if let Some(action) = self.hotkeys.action(id) {
+                    info!("Hotkey pressed: {action}");
+                    self.proxy.send_event(action.event().into()).ignore();
+                }
+            }
+
+            Event::ConfigChanged => self.config_watcher.request_reload(),
+
+            Event::ConfigLoaded(loaded) => self.reload_config(loaded),
+
+            Event::SettingsRequested => settings::open(self.config.clone()),
+
+            Event::QuitRequested => event_loop.exit(),
+
+            Event::Countdown(seconds) => self.update_countdown(event_loop, seconds),
+
+            Event::Recording(is_recording) => self.tray_icon.set_recording(is_recording),
+
+            Event::Burst(is_running) => self.tray_icon.set_burst(is_running),
+
+            Event::InstantScreenshotSaved => self.confirm_instant_screenshot(event_loop),
+
+            Event::ScreenshotSaved(screenshot) => {
+                self.show_save_notification(event_loop, screenshot)
+            }
+
+            Event::ErrorReported(report) => self
+                .show_message_notification(event_loop, |notification| {
+                    notification.show_error(report)
+                }),
+
+            Event::Notice(notice) => self.show_message_notification(event_loop, |notification| {
+                notification.show_notice(notice)
+            }),
         }
     }
 
+    fn about_to_wait(&mut self, event_loop: &ActiveEventLoop) {
+        let now = Instant::now();
+
+        if self
+            .saved_confirmation_until
+            .is_some_and(|until| now >= until)
+        {
+            self.saved_confirmation_until = None;
+            if let Some(overlay) = self.status_overlay.as_ref() {
+                overlay.hide_saved();
+            }
+        }
+
+        if self.notification_until.is_some_and(|until| now >= until) {
+            self.notification_until = None;
+            if let Some(notification) = self.save_notification.as_mut() {
+                notification.hide();
+            }
+            self.stack_notifications();
+        }
+
+        if self
+            .message_notification_until
+            .is_some_and(|until| now >= until)
+        {
+            self.message_notification_until = None;
+            if let Some(notification) = self.message_notification.as_mut() {
+                notification.hide();
+            }
+        }
+
+        // Wake for whichever is hidden next.
+        let control_flow = [
+            self.saved_confirmation_until,
+            self.notification_until,
+            self.message_notification_until,
+        ]
+        .into_iter()
+        .flatten()
+        .min()
+        .map_or(ControlFlow::Wait, ControlFlow::WaitUntil);
+        event_loop.set_control_flow(control_flow);
+    }
+
     fn exiting(&mut self, event_loop: &ActiveEventLoop) {
         let _ = event_loop;
 
@@ -0,0 +1,441 @@
+use core::ffi::c_void;
+
+use tracing::warn;
+use windows::{
+    Win32::{
+        Foundation::{COLORREF, HWND, RECT},
+        Graphics::Gdi::{
+            BI_RGB, BITMAPINFO, BITMAPINFOHEADER, BitBlt, CLEARTYPE_QUALITY, CLIP_DEFAULT_PRECIS,
+            CreateCompatibleBitmap, CreateCompatibleDC, CreateFontW, CreateSolidBrush,
+            DEFAULT_CHARSET, DIB_RGB_COLORS, DRAW_TEXT_FORMAT, DT_CENTER, DT_EDITCONTROL,
+            DT_END_ELLIPSIS, DT_PATH_ELLIPSIS, DT_SINGLELINE, DT_VCENTER, DT_WORDBREAK, DeleteDC,
+            DeleteObject, DrawTextW, FW_NORMAL, FW_SEMIBOLD, FillRect, GetDC, HALFTONE, HDC,
+            OUT_DEFAULT_PRECIS, ReleaseDC, SRCCOPY, SelectObject, SetBkMode, SetStretchBltMode,
+            SetTextColor, StretchDIBits, TRANSPARENT,
+        },
+        UI::WindowsAndMessaging::{
+            SPI_GETWORKAREA, SYSTEM_PARAMETERS_INFO_UPDATE_FLAGS, SystemParametersInfoW,
+        },
+    },
+    core::w,
+};
+use winit::{
+    dpi::{PhysicalPosition, PhysicalSize},
+    event_loop::ActiveEventLoop,
+    platform::windows::WindowAttributesExtWindows,
+    raw_window_handle::{HasWindowHandle, RawWindowHandle},
+    window::{Window, WindowId, WindowLevel},
+};
+
+use crate::{
+    error_reporting::ErrorReport,
+    save_notification::{Layout, Notice, NotificationAction, SavedScreenshot, fit_size},
+    utilities::{failure::Failure, windows_helpers::exclude_from_capture},
+};
+
+/// The gap between the notification and the edges of the work area at 100% scale.
+const MARGIN: f64 = 16.0;
+
+// The colours as `0x00BBGGRR`.
+const BACKGROUND: COLORREF = COLORREF(0x0020_2020);
+const BUTTON: COLORREF = COLORREF(0x0038_3838);
+const HOVERED_BUTTON: COLORREF = COLORREF(0x0050_5050);
+const TEXT: COLORREF = COLORREF(0x00FF_FFFF);
+const SECONDARY_TEXT: COLORREF = COLORREF(0x00C0_C0C0);
+const ERROR_TEXT: COLORREF = COLORREF(0x0060_A0FF);
+
+/// A window in the corner of the primary monitor's work area showing a thumbnail of the saved
+/// screenshot, with buttons to open, edit, or delete it. Errors and notices are shown in the same
+/// kind of window, so they never take the focus or wait for the user.
+pub struct SaveNotification {
+    window: Window,
+    layout: Layout,
+    scale: f64,
+    content: Option<Content>,
+    cursor: Option<[i32; 2]>,
+}
+
+/// What the notification shows.
+enum Content {
+    Screenshot(SavedScreenshot),
+    Error(ErrorReport),
+    Notice(Notice),
+}
+
+impl SaveNotification {
+    pub fn new(event_loop: &ActiveEventLoop) -> Self {
+        let scale = event_loop
+            .primary_monitor()
+            .map_or(1.0, |monitor| monitor.scale_factor());
+        let layout = Layout::new(scale);
+
+        let window_attributes = Window::default_attributes()
+            .with_title("HDR Snipping Tool Notification")
+            .with_inner_size(PhysicalSize::new(layout.size[0], layout.size[1]))
+            .with_position(position(&layout, scale, 0))
+            .with_decorations(false)
+            .with_resizable(false)
+            .with_window_level(WindowLevel::AlwaysOnTop)
+            .with_skip_taskbar(true)
+            .with_active(false)
+            .with_visible(false);
+
+        let window = event_loop
+            .create_window(window_attributes)
+            .report_and_panic("Could not create the notification window");
+
+        let notification = Self {
+            window,
+            layout,
+            scale,
+            content: None,
+            cursor: None,
+        };
+
+        // The notification must not end up in the next screenshot.
+        if let Some(handle) = notification.handle() {
+            if let Err(error) = exclude_from_capture(handle) {
+                warn!("Could not exclude the notification from captures: {error}");
+            }
+        }
+
+        notification
+    }
+
+    pub fn id(&self) -> WindowId {
+        self.window.id()
+    }
+
+    /// Shows the screenshot, replacing what was shown before.
+    pub fn show(&mut self, screenshot: SavedScreenshot) {
+        self.show_content(Content::Screenshot(screenshot));
+    }
+
+    /// Shows the error, replacing what was shown before.
+    pub fn show_error(&mut self, report: ErrorReport) {
+        self.show_content(Content::Error(report));
+    }
+
+    /// Shows the notice, replacing what was shown before.
+    pub fn show_notice(&mut self, notice: Notice) {
+        self.show_content(Content::Notice(notice));
+    }
+
+    fn show_content(&mut self, content: Content) {
+        self.content = Some(content);
+        self.window.set_visible(true);
+        self.window.request_redraw();
+    }
+
+    pub fn hide(&mut self) {
+        self.content = None;
+        self.cursor = None;
+        self.window.set_visible(false);
+    }
+
+    pub fn is_visible(&self) -> bool {
+        self.content.is_some()
+    }
+
+    /// Moves the notification to the slot, counting up from the bottom of the work area, so
+    /// notifications shown together do not cover each other.
+    pub fn set_slot(&self, slot: u32) {
+        self.window
+            .set_outer_position(position(&self.layout, self.scale, slot));
+    }
+
+    /// Highlights the button under the cursor, `None` once the cursor leaves the notification.
+    pub fn set_cursor(&mut self, cursor: Option<[i32; 2]>) {
+        let hovered =
+            |cursor: Option<[i32; 2]>| cursor.and_then(|cursor| self.layout.action_at(cursor));
+
+        if hovered(cursor) != hovered(self.cursor) {
+            self.window.request_redraw();
+        }
+        self.cursor = cursor;
+    }
+
+    /// The action under the cursor and the screenshot it is taken on, errors and notices have no
+    /// actions.
+    pub fn clicked_action(&self) -> Option<(NotificationAction, SavedScreenshot)> {
+        let Some(Content::Screenshot(screenshot)) = self.content.as_ref() else {
+            return None;
+        };
+        let action = self.layout.action_at(self.cursor?)?;
+
+        Some((action, screenshot.clone()))
+    }
+
+    pub fn draw(&self) {
+        let (Some(handle), Some(content)) = (self.handle(), self.content.as_ref()) else {
+            return;
+        };
+
+        let hovered = self.cursor.and_then(|cursor| self.layout.action_at(cursor));
+        let [width, height] = self.layout.size.map(|axis| axis as i32);
+
+        unsafe {
+            let dc = GetDC(Some(handle));
+            if dc.is_invalid() {
+                return;
+            }
+
+            // Draw off screen first, so the notification does not flicker when a button is hovered.
+            let buffer_dc = CreateCompatibleDC(Some(dc));
+            let buffer = CreateCompatibleBitmap(dc, width, height);
+            let previous_buffer = SelectObject(buffer_dc, buffer.into());
+
+            fill(buffer_dc, [0, 0, width, height], BACKGROUND);
+
+            match content {
+                Content::Screenshot(screenshot) => {
+                    self.draw_thumbnail(buffer_dc, screenshot);
+                    self.draw_file_name(buffer_dc, screenshot);
+                    self.draw_buttons(buffer_dc, hovered);
+                }
+
+                Content::Error(report) => self.draw_message(
+                    buffer_dc,
+                    &report.summary,
+                    report.shown_details(),
+                    ERROR_TEXT,
+                ),
+
+                Content::Notice(notice) => {
+                    self.draw_message(buffer_dc, notice.summary, notice.details, TEXT)
+                }
+            }
+
+            let _ = BitBlt(dc, 0, 0, width, height, Some(buffer_dc), 0, 0, SRCCOPY);
+
+            SelectObject(buffer_dc, previous_buffer);
+            let _ = DeleteObject(buffer.into());
+            let _ = DeleteDC(buffer_dc);
+            ReleaseDC(Some(handle), dc);
+        }
+    }
+
+    unsafe fn draw_buttons(&self, dc: HDC, hovered: Option<NotificationAction>) {
+        for (action, bounds) in NotificationAction::ALL.into_iter().zip(self.layout.buttons) {
+            let colour = if hovered == Some(action) {
+                HOVERED_BUTTON
+            } else {
+                BUTTON
+            };
+
+            unsafe {
+                fill(dc, bounds, colour);
+                draw_text(
+                    dc,
+                    action.label(),
+                    bounds,
+                    [bounds[3] / 2, FW_NORMAL.0 as i32],
+                    TEXT,
+                    DT_CENTER | DT_VCENTER | DT_SINGLELINE | DT_END_ELLIPSIS,
+                );
+            }
+        }
+    }
+
+    /// Draws the summary as the heading, with the details the user can act on below it. Details
+    /// that do not fit are cut off, an error's are also in the log.
+    unsafe fn draw_message(&self, dc: HDC, summary: &str, details: &str, colour: COLORREF) {
+        let [x, y, width, height] = self.layout.message;
+        let line = self.layout.text[3] / 4;
+
+        unsafe {
+            draw_text(
+                dc,
+                summary,
+                [x, y, width, line * 2],
+                [line * 3 / 4, FW_SEMIBOLD.0 as i32],
+                colour,
+                DT_WORDBREAK | DT_EDITCONTROL | DT_END_ELLIPSIS,
+            );
+            draw_text(
+                dc,
+                details,
+                [x, y + line * 2, width, height - line * 2],
+                [line * 2 / 3, FW_NORMAL.0 as i32],
+                SECONDARY_TEXT,
+                DT_WORDBREAK | DT_EDITCONTROL | DT_END_ELLIPSIS,
+            );
+        }
+    }
+
+    /// Draws the thumbnail centred in its space, without enlarging it.
+    unsafe fn draw_thumbnail(&self, dc: HDC, screenshot: &SavedScreenshot) {
+        let thumbnail = &screenshot.thumbnail;
+        let [x, y, width, height] = self.layout.thumbnail;
+
+        let fitted =
+            fit_size(thumbnail.size, [width as u32, height as u32]).map(|axis| axis as i32);
+        let position = [x + (width - fitted[0]) / 2, y + (height - fitted[1]) / 2];
+
+        let background = BACKGROUND.0.to_le_bytes();
+        let pixels = thumbnail.to_opaque_bgra([background[0], background[1], background[2]]);
+
+        let info = BITMAPINFO {
+            bmiHeader: BITMAPINFOHEADER {
+                biSize: size_of::<BITMAPINFOHEADER>() as u32,
+                biWidth: thumbnail.size[0] as i32,
+                // Negative for rows from top to bottom.
+                biHeight: -(thumbnail.size[1] as i32),
+                biPlanes: 1,
+                biBitCount: 32,
+                biCompression: BI_RGB.0,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        unsafe {
+            SetStretchBltMode(dc, HALFTONE);
+            StretchDIBits(
+                dc,
+                position[0],
+                position[1],
+                fitted[0],
+                fitted[1],
+                0,
+                0,
+                thumbnail.size[0] as i32,
+                thumbnail.size[1] as i32,
+                Some(pixels.as_ptr().cast::<c_void>()),
+                &info,
+                DIB_RGB_COLORS,
+                SRCCOPY,
+            );
+        }
+    }
+
+    /// Draws the heading and the file's name beside the thumbnail.
+    unsafe fn draw_file_name(&self, dc: HDC, screenshot: &SavedScreenshot) {
+        let [x, y, width, height] = self.layout.text;
+        let line = height / 4;
+
+        let file_name = screenshot
+            .path
+            .file_name()
+            .unwrap_or_default()
+            .to_string_lossy();
+
+        unsafe {
+            draw_text(
+                dc,
+                "Screenshot saved",
+                [x, y, width, line],
+                [line * 3 / 4, FW_SEMIBOLD.0 as i32],
+                TEXT,
+                DT_SINGLELINE | DT_END_ELLIPSIS,
+            );
+            draw_text(
+                dc,
+                &file_name,
+                [x, y + line, width, line],
+                [line * 2 / 3, FW_NORMAL.0 as i32],
+                SECONDARY_TEXT,
+                DT_SINGLELINE | DT_PATH_ELLIPSIS,
+            );
+        }
+    }
+
+    fn handle(&self) -> Option<HWND> {
+        let handle = self.window.window_handle().ok()?;
+
+        match handle.as_raw() {
+            RawWindowHandle::Win32(handle) => Some(HWND(handle.hwnd.get() as *mut c_void)),
+            _ => None,
+        }
+    }
+}
+
+/// The bottom right corner of the primary monitor's work area, above the taskbar, raised by the
+/// height of a notification for each slot.
+fn position(layout: &Layout, scale: f64, slot: u32) -> PhysicalPosition<i32> {
+    let mut work_area = RECT::default();
+    let has_work_area = unsafe {
+        SystemParametersInfoW(
+            SPI_GETWORKAREA,
+            0,
+            Some((&raw mut work_area).cast()),
+            SYSTEM_PARAMETERS_INFO_UPDATE_FLAGS(0),
+        )
+    }
+    .is_ok();
+
+    if !has_work_area {
+        warn!("Could not get the work area, the notification is shown in the top left corner");
+    }
+
+    let margin = (MARGIN * scale) as i32;
+    let [width, height] = layout.size.map(|axis| axis as i32);
+
+    let raised = slot as i32 * (height + margin);
+
+    PhysicalPosition::new(
+        (work_area.right - width - margin).max(work_area.left),
+        (work_area.bottom - height - margin - raised).max(work_area.top),
+    )
+}
+
+unsafe fn fill(dc: HDC, [x, y, width, height]: [i32; 4], colour: COLORREF) {
+    let rect = RECT {
+        left: x,
+        top: y,
+        right: x + width,
+        bottom: y + height,
+    };
+
+    unsafe {
+        let brush = CreateSolidBrush(colour);
+        FillRect(dc, &rect, brush);
+        let _ = DeleteObject(brush.into());
+    }
+}
+
+/// Draws text in the bounds with a font of the height and weight.
+unsafe fn draw_text(
+    dc: HDC,
+    text: &str,
+    [x, y, width, height]: [i32; 4],
+    [font_height, weight]: [i32; 2],
+    colour: COLORREF,
+    format: DRAW_TEXT_FORMAT,
+) {
+    let mut rect = RECT {
+        left: x,
+        top: y,
+        right: x + width,
+        bottom: y + height,
+    };
+
+    unsafe {
+        let font = CreateFontW(
+            font_height,
+            0,
+            0,
+            0,
+            weight,
+            0,
+            0,
+            0,
+            DEFAULT_CHARSET,
+            OUT_DEFAULT_PRECIS,
+            CLIP_DEFAULT_PRECIS,
+            CLEARTYPE_QUALITY,
+            0,
+            w!("Segoe UI"),
+        );
+        let previous_font = SelectObject(dc, font.into());
+
+        SetBkMode(dc, TRANSPARENT);
+        SetTextColor(dc, colour);
+
+        let mut text: Vec<u16> = text.encode_utf16().collect();
+        DrawTextW(dc, &mut text, &mut rect, format);
+
+        SelectObject(dc, previous_font);
+        let _ = DeleteObject(font.into());
+    }
+}
@@ -0,0 +1,126 @@
+use core::ffi::c_void;
+
+use tracing::warn;
+use windows::Win32::Foundation::HWND;
+use winit::{
+    dpi::{PhysicalPosition, PhysicalSize},
+    event_loop::ActiveEventLoop,
+    platform::windows::WindowAttributesExtWindows,
+    raw_window_handle::{HasWindowHandle, RawWindowHandle},
+    window::{Window, WindowId, WindowLevel},
+};
+
+use crate::utilities::{
+    failure::Failure,
+    windows_helpers::{draw_centred_text, exclude_from_capture},
+};
+
+/// The size of the overlay at 100% scale.
+const SIZE: u32 = 96;
+
+/// The gap between the overlay and the edges of the monitor at 100% scale.
+const MARGIN: i32 = 32;
+
+/// The text shown once an instant screenshot is saved.
+const SAVED_TEXT: &str = "\u{2713}";
+
+/// A small always-on-top window showing the seconds left before a delayed screenshot, or that an
+/// instant screenshot was saved.
+pub struct StatusOverlay {
+    window: Window,
+    text: String,
+}
+
+impl StatusOverlay {
+    pub fn new(event_loop: &ActiveEventLoop) -> Self {
+        let (position, size) = match event_loop.primary_monitor() {
+            Some(monitor) => {
+                let scale = monitor.scale_factor();
+                let size = (f64::from(SIZE) * scale) as u32;
+                let margin = (f64::from(MARGIN) * scale) as i32;
+
+                let position = PhysicalPosition::new(
+                    monitor.position().x + monitor.size().width as i32 - size as i32 - margin,
+                    monitor.position().y + margin,
+                );
+
+                (position, size)
+            }
+            None => (PhysicalPosition::new(MARGIN, MARGIN), SIZE),
+        };
+
+        let window_attributes = Window::default_attributes()
+            .with_title("HDR Snipping Tool Status")
+            .with_inner_size(PhysicalSize::new(size, size))
+            .with_position(position)
+            .with_decorations(false)
+            .with_resizable(false)
+            .with_window_level(WindowLevel::AlwaysOnTop)
+            .with_skip_taskbar(true)
+            .with_active(false)
+            .with_visible(false);
+
+        let window = event_loop
+            .create_window(window_attributes)
+            .report_and_panic("Could not create the status window");
+
+        let overlay = Self {
+            window,
+            text: String::new(),
+        };
+
+        // The overlay must never end up in the screenshot it is counting down to.
+        if let Some(handle) = overlay.handle() {
+            if let Err(error) = exclude_from_capture(handle) {
+                warn!("Could not exclude the status overlay from captures: {error}");
+            }
+        }
+
+        overlay
+    }
+
+    pub fn id(&self) -> WindowId {
+        self.window.id()
+    }
+
+    /// Shows the overlay with the seconds remaining, or hides it.
+    pub fn set_seconds(&mut self, seconds: Option<u64>) {
+        match seconds {
+            Some(seconds) => self.show(seconds.to_string()),
+            None => self.window.set_visible(false),
+        }
+    }
+
+    /// Shows that an instant screenshot was saved.
+    pub fn show_saved(&mut self) {
+        self.show(String::from(SAVED_TEXT));
+    }
+
+    /// Hides the overlay if it is showing that an instant screenshot was saved.
+    pub fn hide_saved(&self) {
+        if self.text == SAVED_TEXT {
+            self.window.set_visible(false);
+        }
+    }
+
+    pub fn draw(&self) {
+        if let Some(handle) = self.handle() {
+            draw_centred_text(handle, &self.text);
+        }
+    }
+
+    fn show(&mut self, text: String) {
+        self.text = text;
+        self.window.set_visible(true);
+        self.window.request_redraw();
+    }
+
+    fn handle(&self) -> Option<HWND> {
+        let handle = self.window.window_handle().ok()?;
+
+        match handle.as_raw() {
+            RawWindowHandle::Win32(handle) => Some(HWND(handle.hwnd.get() as *mut c_void)),
+            _ => None,
+        }
+    }
+}
@@ -1,36 +1,88 @@
-use tracing::debug;
+use core::time::Duration;
+
+use tracing::{debug, warn};
 use tray_icon::{
     TrayIconBuilder,
-    menu::{Menu, MenuEvent, MenuItem},
+    menu::{Menu, MenuEvent, MenuItem, Submenu},
 };
-use winit::event_loop::ActiveEventLoop;
+use winit::event_loop::{ActiveEventLoop, EventLoopProxy};
 
 use crate::{
-    VERSION, config_dir, screenshot_dir, should_debug,
+    VERSION,
+    application::ApplicationEvent,
+    config_dir, diagnostics_dir,
+    error_reporting::{ErrorCategory, report_error},
+    screenshot_dir, should_debug,
     utilities::{
-        failure::{Failure, report},
+        failure::{Failure, Ignore},
         windows_helpers::explore_directory,
     },
 };
 
+use super::Event;
+
+pub const TRAY_DELAY_3_ID: &str = "delayed_screenshot_3";
+pub const TRAY_DELAY_5_ID: &str = "delayed_screenshot_5";
+pub const TRAY_DELAY_10_ID: &str = "delayed_screenshot_10";
+pub const TRAY_RECORD_ID: &str = "record_region";
+pub const TRAY_BURST_ID: &str = "burst_capture";
+pub const TRAY_BURST_FRAMES_ID: &str = "open_burst_frames";
+pub const TRAY_HISTORY_ID: &str = "open_capture_history";
 pub const TRAY_SCREENSHOT_ID: &str = "open_screenshot_dir";
+pub const TRAY_SETTINGS_ID: &str = "open_settings";
 pub const TRAY_CONFIG_ID: &str = "open_config_dir";
+pub const TRAY_DIAGNOSTICS_ID: &str = "open_diagnostics_dir";
 pub const TRAY_QUIT_ID: &str = "quit";
 
 pub struct TrayIcon {
-    #[expect(unused)]
     tray_icon: tray_icon::TrayIcon,
+    record_item: MenuItem,
+    burst_item: MenuItem,
+    tooltip: String,
 }
 
 impl TrayIcon {
     pub fn new() -> Self {
+        let delay_3_item = MenuItem::with_id(TRAY_DELAY_3_ID, "In 3 Seconds", true, None);
+        let delay_5_item = MenuItem::with_id(TRAY_DELAY_5_ID, "In 5 Seconds", true, None);
+        let delay_10_item = MenuItem::with_id(TRAY_DELAY_10_ID, "In 10 Seconds", true, None);
+        let delay_menu = Submenu::with_items(
+            "Delayed Screenshot",
+            true,
+            &[&delay_3_item, &delay_5_item, &delay_10_item],
+        )
+        .report_and_panic("Could not create tray icon");
+
+        let record_item = MenuItem::with_id(TRAY_RECORD_ID, "Record Region", true, None);
+        let burst_item = MenuItem::with_id(TRAY_BURST_ID, "Burst Capture", true, None);
+        let burst_frames_item =
+            MenuItem::with_id(TRAY_BURST_FRAMES_ID, "Pick Burst Capture", true, None);
+        let history_item = MenuItem::with_id(TRAY_HISTORY_ID, "Capture History", true, None);
         let screenshot_item =
             MenuItem::with_id(TRAY_SCREENSHOT_ID, "Open Screenshot Directory", true, None);
+        let settings_item = MenuItem::with_id(TRAY_SETTINGS_ID, "Settings", true, None);
         let config_item = MenuItem::with_id(TRAY_CONFIG_ID, "Open Config Directory", true, None);
+        let diagnostics_item = MenuItem::with_id(
+            TRAY_DIAGNOSTICS_ID,
+            "Open Diagnostics Directory",
+            true,
+            None,
+        );
         let quit_item = MenuItem::with_id(TRAY_QUIT_ID, "Quit HDR Snipping Tool", true, None);
 
-        let tray_menu = Menu::with_items(&[&screenshot_item, &config_item, &quit_item])
-            .report_and_panic("Could not create tray icon");
+        let tray_menu = Menu::with_items(&[
+            &delay_menu,
+            &record_item,
+            &burst_item,
+            &burst_frames_item,
+            &history_item,
+            &screenshot_item,
+            &settings_item,
+            &config_item,
+            &diagnostics_item,
+            &quit_item,
+        ])
+        .report_and_panic("Could not create tray icon");
         let icon = tray_icon::Icon::from_resource(1, Some((24, 24)))
             .report_and_panic("Could not create tray icon");
         let tooltip = if should_debug() {
@@ -41,30 +93,106 @@ impl TrayIcon {
 
         let tray_icon = TrayIconBuilder::new()
             .with_menu(Box::new(tray_menu))
-            .with_tooltip(tooltip)
+            .with_tooltip(&tooltip)
             .with_icon(icon)
             .build()
             .report_and_panic("Could not create tray icon");
 
-        Self { tray_icon }
+        Self {
+            tray_icon,
+            record_item,
+            burst_item,
+            tooltip,
+        }
     }
 
-    pub fn handle_event(event_loop: &ActiveEventLoop, event: MenuEvent) {
+    /// Shows the seconds until a delayed screenshot in the tooltip, or the default tooltip.
+    pub fn set_countdown(&self, seconds: Option<u64>) {
+        let tooltip = match seconds {
+            Some(seconds) => format!("{}\nScreenshot in {seconds}s", self.tooltip),
+            None => self.tooltip.clone(),
+        };
+
+        if let Err(error) = self.tray_icon.set_tooltip(Some(tooltip)) {
+            warn!("Could not update the tray tooltip: {error}");
+        }
+    }
+
+    /// Shows that a region is being recorded in the tooltip, the record item stops the recording.
+    pub fn set_recording(&self, is_recording: bool) {
+        let (tooltip, text) = if is_recording {
+            (format!("{}\nRecording", self.tooltip), "Stop Recording")
+        } else {
+            (self.tooltip.clone(), "Record Region")
+        };
+
+        self.record_item.set_text(text);
+        if let Err(error) = self.tray_icon.set_tooltip(Some(tooltip)) {
+            warn!("Could not update the tray tooltip: {error}");
+        }
+    }
+
+    /// Shows that a burst is running in the tooltip, the burst item stops the burst.
+    pub fn set_burst(&self, is_running: bool) {
+        let (tooltip, text) = if is_running {
+            (format!("{}\nTaking a burst", self.tooltip), "Stop Burst")
+        } else {
+            (self.tooltip.clone(), "Burst Capture")
+        };
+
+        self.burst_item.set_text(text);
+        if let Err(error) = self.tray_icon.set_tooltip(Some(tooltip)) {
+            warn!("Could not update the tray tooltip: {error}");
+        }
+    }
+
+    pub fn handle_event(
+        event_loop: &ActiveEventLoop,
+        proxy: &EventLoopProxy<Event>,
+        event: MenuEvent,
+    ) {
         debug!("Tray Event: {}", event.id.0.as_str());
         match event.id.0.as_str() {
+            TRAY_DELAY_3_ID => Self::request_delayed_screenshot(proxy, 3),
+            TRAY_DELAY_5_ID => Self::request_delayed_screenshot(proxy, 5),
+            TRAY_DELAY_10_ID => Self::request_delayed_screenshot(proxy, 10),
+            TRAY_RECORD_ID => proxy
+                .send_event(ApplicationEvent::RecordingRequested.into())
+                .ignore(),
+            TRAY_BURST_ID => proxy
+                .send_event(ApplicationEvent::BurstRequested.into())
+                .ignore(),
+            TRAY_BURST_FRAMES_ID => proxy
+                .send_event(ApplicationEvent::BurstFramesRequested.into())
+                .ignore(),
+            TRAY_HISTORY_ID => proxy
+                .send_event(ApplicationEvent::HistoryRequested.into())
+                .ignore(),
             TRAY_SCREENSHOT_ID => {
                 if let Err(error) = unsafe { explore_directory(screenshot_dir().as_path()) } {
-                    report(
+                    report_error(
+                        ErrorCategory::Tray,
                         format!("{error:?}"),
                         "Failed to explore to the screenshot directory",
                     );
                 }
             }
+            TRAY_SETTINGS_ID => proxy.send_event(Event::SettingsRequested).ignore(),
             TRAY_CONFIG_ID => {
                 if let Err(error) = unsafe { explore_directory(config_dir().as_path()) } {
-                    report(
+                    report_error(
+                        ErrorCategory::Tray,
                         format!("{error:?}"),
-                        "Failed to explore to the screenshot directory",
+                        "Failed to explore to the config directory",
+                    );
+                }
+            }
+            TRAY_DIAGNOSTICS_ID => {
+                if let Err(error) = unsafe { explore_directory(diagnostics_dir().as_path()) } {
+                    report_error(
+                        ErrorCategory::Tray,
+                        format!("{error:?}"),
+                        "Failed to explore to the diagnostics directory",
                     );
                 }
             }
@@ -74,4 +202,12 @@ impl TrayIcon {
             _ => {}
         }
     }
+
+    fn request_delayed_screenshot(proxy: &EventLoopProxy<Event>, seconds: u64) {
+        proxy
+            .send_event(
+                ApplicationEvent::DelayedScreenshotRequested(Duration::from_secs(seconds)).into(),
+            )
+            .ignore();
+    }
 }
@@ -0,0 +1,162 @@
+use core::time::Duration;
+
+use half::f16;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    capture_taker::{DesktopRect, Whitepoint},
+    recording::RecordingHandle,
+};
+
+pub use ring::FrameRing;
+
+mod ring;
+
+/// How bursts of captures are taken.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BurstSettings {
+    /// The milliseconds between captures.
+    pub interval_ms: u32,
+
+    /// How many captures a burst takes, `0` keeps capturing until the burst is stopped.
+    pub frames: u32,
+
+    /// How many of the latest HDR captures are kept in memory to pick from after the burst.
+    pub kept_frames: u32,
+}
+
+impl BurstSettings {
+    /// The time between captures.
+    pub fn interval(&self) -> Duration {
+        Duration::from_millis(u64::from(self.interval_ms))
+    }
+
+    /// If a burst that has taken `taken` captures should take another.
+    pub fn wants_frame(&self, taken: u32) -> bool {
+        self.frames == 0 || taken < self.frames
+    }
+}
+
+impl Default for BurstSettings {
+    fn default() -> Self {
+        Self {
+            interval_ms: 500,
+            frames: 10,
+            kept_frames: 16,
+        }
+    }
+}
+
+/// A region of the desktop to take a burst of captures of.
+#[derive(Clone, Debug)]
+pub struct BurstRequest {
+    /// The region in desktop coordinates, it is cropped to the monitor under its centre.
+    pub area: DesktopRect,
+
+    /// The name of the burst, each capture is saved as the name and its number.
+    pub name: String,
+
+    pub settings: BurstSettings,
+
+    pub handle: RecordingHandle,
+}
+
+/// A capture taken during a burst.
+#[derive(Clone, Debug)]
+pub struct BurstFrame {
+    /// The position of the capture in the burst, starting from `1`.
+    pub number: u32,
+
+    /// The name of the file the capture is saved to.
+    pub file_name: String,
+
+    /// The desktop area the capture covers.
+    pub area: DesktopRect,
+
+    /// The whitepoint the capture is tonemapped with.
+    pub whitepoint: Whitepoint,
+
+    /// The `RGBA` pixels of the capture.
+    pub pixels: Vec<f16>,
+}
+
+/// The file name of a capture in a burst, numbered so the captures sort in the order they were
+/// taken.
+pub fn frame_file_name(burst_name: &str, number: u32) -> String {
+    format!("{burst_name} {number:03}.png")
+}
+
+/// Copies a region out of `RGBA` pixels that are `size` wide and tall.
+pub fn crop(
+    pixels: &[f16],
+    size: [usize; 2],
+    position: [usize; 2],
+    crop_size: [usize; 2],
+) -> Vec<f16> {
+    debug_assert_eq!(pixels.len(), size[0] * size[1] * 4);
+    debug_assert!(position[0] + crop_size[0] <= size[0]);
+    debug_assert!(position[1] + crop_size[1] <= size[1]);
+
+    let row_start = position[0] * 4;
+    let row_length = crop_size[0] * 4;
+
+    pixels
+        .chunks_exact(size[0] * 4)
+        .skip(position[1])
+        .take(crop_size[1])
+        .flat_map(|row| &row[row_start..row_start + row_length])
+        .copied()
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use half::f16;
+
+    use super::{BurstSettings, crop, frame_file_name};
+
+    #[test]
+    fn numbers_files_in_order() {
+        let names: Vec<_> = [1, 2, 10, 100]
+            .into_iter()
+            .map(|number| frame_file_name("Burst 2024-01-02 030405", number))
+            .collect();
+
+        assert_eq!(names[0], "Burst 2024-01-02 030405 001.png");
+        assert!(names.is_sorted());
+    }
+
+    #[test]
+    fn burst_length() {
+        let limited = BurstSettings {
+            frames: 3,
+            ..BurstSettings::default()
+        };
+        assert!(limited.wants_frame(2));
+        assert!(!limited.wants_frame(3));
+
+        let unlimited = BurstSettings {
+            frames: 0,
+            ..BurstSettings::default()
+        };
+        assert!(unlimited.wants_frame(10_000));
+    }
+
+    #[test]
+    fn crops_region() {
+        // A 3x2 image where each pixel is its index.
+        let pixels: Vec<_> = (0..6)
+            .flat_map(|index| [index as f32; 4])
+            .map(f16::from_f32)
+            .collect();
+
+        let cropped = crop(&pixels, [3, 2], [1, 0], [2, 2]);
+        let indices: Vec<_> = cropped
+            .chunks_exact(4)
+            .map(|pixel| pixel[0].to_f32() as usize)
+            .collect();
+
+        assert_eq!(indices, [1, 2, 4, 5]);
+    }
+}
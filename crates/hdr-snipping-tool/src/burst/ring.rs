@@ -0,0 +1,83 @@
+use std::collections::VecDeque;
+
+/// Keeps the latest frames up to a capacity, a new frame replaces the oldest frame once full.
+#[derive(Clone, Debug)]
+pub struct FrameRing<T> {
+    frames: VecDeque<T>,
+    capacity: usize,
+}
+
+impl<T> FrameRing<T> {
+    /// Creates an empty ring, a capacity of `0` keeps no frames.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            frames: VecDeque::with_capacity(capacity),
+            capacity,
+        }
+    }
+
+    /// Adds a frame, returns the oldest frame if it no longer fits.
+    pub fn push(&mut self, frame: T) -> Option<T> {
+        if self.capacity == 0 {
+            return Some(frame);
+        }
+
+        let evicted = if self.frames.len() == self.capacity {
+            self.frames.pop_front()
+        } else {
+            None
+        };
+
+        self.frames.push_back(frame);
+        evicted
+    }
+
+    pub fn len(&self) -> usize {
+        self.frames.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.frames.is_empty()
+    }
+
+    /// Returns the frame `index` frames older than the newest frame.
+    pub fn newest(&self, index: usize) -> Option<&T> {
+        let position = self.frames.len().checked_sub(index + 1)?;
+        self.frames.get(position)
+    }
+}
+
+impl<T> Default for FrameRing<T> {
+    fn default() -> Self {
+        Self::new(0)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::FrameRing;
+
+    #[test]
+    fn keeps_latest_frames() {
+        let mut ring = FrameRing::new(3);
+
+        for frame in 1..=3 {
+            assert_eq!(ring.push(frame), None);
+        }
+        assert_eq!(ring.push(4), Some(1));
+        assert_eq!(ring.push(5), Some(2));
+
+        assert_eq!(ring.len(), 3);
+        let newest_first: Vec<_> = (0..4).map(|index| ring.newest(index).copied()).collect();
+        assert_eq!(newest_first, [Some(5), Some(4), Some(3), None]);
+    }
+
+    #[test]
+    fn zero_capacity_keeps_nothing() {
+        let mut ring = FrameRing::new(0);
+
+        assert_eq!(ring.push(1), Some(1));
+        assert!(ring.is_empty());
+        assert_eq!(ring.newest(0), None);
+    }
+}
@@ -0,0 +1,104 @@
+use std::{
+    fs::File,
+    io::{BufReader, BufWriter, Cursor, Read, Seek, Write},
+    path::Path,
+};
+
+use exr::prelude::{
+    Encoding, Image, Layer, LayerAttributes, ReadChannels, ReadLayers, SpecificChannels, Vec2,
+    WritableImage,
+};
+use half::f16;
+
+pub use exr::error::Error as ExrError;
+
+/// The pixels of an `RGBA` image.
+struct Pixels {
+    width: usize,
+    data: Vec<f16>,
+}
+
+/// Writes `RGBA` pixels to a lossless compressed EXR file, the file is synced before returning.
+pub fn write(path: &Path, size: [u32; 2], pixels: &[f16]) -> Result<(), ExrError> {
+    let file = File::create(path)?;
+    let mut writer = BufWriter::new(file);
+    write_to(&mut writer, size, pixels)?;
+
+    writer.flush()?;
+    writer.get_ref().sync_all()?;
+
+    Ok(())
+}
+
+/// Encodes `RGBA` pixels as a lossless compressed EXR image.
+pub fn encode_exr(size: [u32; 2], pixels: &[f16]) -> Result<Vec<u8>, ExrError> {
+    let mut data = Cursor::new(Vec::new());
+    write_to(&mut data, size, pixels)?;
+
+    Ok(data.into_inner())
+}
+
+fn write_to<Writer: Write + Seek>(
+    writer: &mut Writer,
+    size: [u32; 2],
+    pixels: &[f16],
+) -> Result<(), ExrError> {
+    let width = size[0] as usize;
+    let height = size[1] as usize;
+
+    let channels = SpecificChannels::rgba(|Vec2(x, y)| {
+        let index = (y * width + x) * 4;
+        (
+            pixels[index],
+            pixels[index + 1],
+            pixels[index + 2],
+            pixels[index + 3],
+        )
+    });
+
+    let layer = Layer::new(
+        (width, height),
+        LayerAttributes::default(),
+        Encoding::SMALL_LOSSLESS,
+        channels,
+    );
+
+    Image::from_layer(layer).write().to_buffered(writer)
+}
+
+/// Reads an EXR file, returns the size and `RGBA` pixels.
+pub fn read(path: &Path) -> Result<([u32; 2], Vec<f16>), ExrError> {
+    read_from(BufReader::new(File::open(path)?))
+}
+
+/// Decodes an EXR image, returns the size and `RGBA` pixels.
+#[cfg(test)]
+pub fn decode_exr(data: &[u8]) -> Result<([u32; 2], Vec<f16>), ExrError> {
+    read_from(Cursor::new(data))
+}
+
+fn read_from<Reader: Read + Seek>(reader: Reader) -> Result<([u32; 2], Vec<f16>), ExrError> {
+    let image = exr::prelude::read()
+        .no_deep_data()
+        .largest_resolution_level()
+        .rgba_channels(
+            |resolution, _| Pixels {
+                width: resolution.width(),
+                data: vec![f16::ZERO; resolution.width() * resolution.height() * 4],
+            },
+            |pixels: &mut Pixels, position, (r, g, b, a): (f16, f16, f16, f16)| {
+                let index = (position.y() * pixels.width + position.x()) * 4;
+                pixels.data[index..index + 4].copy_from_slice(&[r, g, b, a]);
+            },
+        )
+        .first_valid_layer()
+        .all_attributes()
+        .from_buffered(reader)?;
+
+    let size = image.layer_data.size;
+
+    Ok((
+        [size.width() as u32, size.height() as u32],
+        image.layer_data.channel_data.pixels.data,
+    ))
+}
@@ -0,0 +1,610 @@
+use core::time::Duration;
+use std::{
+    collections::HashSet,
+    fs::{self, File},
+    io::{self, Write},
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use half::f16;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use tracing::{debug, warn};
+
+use crate::capture_taker::{DesktopRect, Whitepoint};
+
+use self::exr::ExrError;
+
+#[cfg(test)]
+pub use self::exr::decode_exr;
+pub use self::exr::encode_exr;
+
+mod exr;
+
+/// The extension of a capture's pixels.
+const CAPTURE_EXTENSION: &str = "exr";
+
+/// The extension of a capture's metadata, an entry only exists once its metadata exists.
+const METADATA_EXTENSION: &str = "toml";
+
+/// The extension of a file that is still being written.
+const TEMPORARY_EXTENSION: &str = "tmp";
+
+/// How much history is kept on disk.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HistoryLimits {
+    /// The most captures to keep, `0` disables the history.
+    pub max_entries: u32,
+
+    /// The most megabytes the history may take up.
+    pub max_size_mb: u32,
+
+    /// How many days a capture is kept for.
+    pub max_age_days: u32,
+}
+
+impl HistoryLimits {
+    pub fn max_bytes(&self) -> u64 {
+        u64::from(self.max_size_mb) * 1024 * 1024
+    }
+
+    pub fn max_age(&self) -> Duration {
+        Duration::from_secs(u64::from(self.max_age_days) * 24 * 60 * 60)
+    }
+}
+
+impl Default for HistoryLimits {
+    fn default() -> Self {
+        Self {
+            max_entries: 20,
+            max_size_mb: 1024,
+            max_age_days: 30,
+        }
+    }
+}
+
+/// What was saved alongside a capture's pixels.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct HistoryMetadata {
+    /// When the capture was saved, in milliseconds since the Unix epoch.
+    pub saved_at: u64,
+
+    /// The desktop area the capture covered.
+    pub area: DesktopRect,
+
+    /// The whitepoint the capture was tonemapped with.
+    pub whitepoint: Whitepoint,
+
+    /// The saved selection as `[start x, start y, end x, end y]` in capture pixels.
+    pub selection: [f32; 4],
+}
+
+impl HistoryMetadata {
+    pub fn saved_at(&self) -> SystemTime {
+        UNIX_EPOCH + Duration::from_millis(self.saved_at)
+    }
+}
+
+/// A capture in the history.
+#[derive(Clone, Debug, PartialEq)]
+pub struct HistoryEntry {
+    pub id: String,
+    pub metadata: HistoryMetadata,
+
+    /// The space the entry takes up on disk.
+    pub bytes: u64,
+}
+
+/// The past captures, stored as compressed EXR files with a TOML metadata file each.
+///
+/// Files are written under a temporary name and renamed once complete, the metadata is written
+/// last. A crash can only leave behind temporary files or pixels without metadata, which are
+/// removed when the store is opened.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HistoryStore {
+    directory: PathBuf,
+    limits: HistoryLimits,
+}
+
+impl HistoryStore {
+    /// Opens the store in a directory, removing incomplete entries and anything over the limits.
+    pub fn open(directory: PathBuf, limits: HistoryLimits) -> Result<Self, HistoryError> {
+        fs::create_dir_all(&directory)?;
+
+        let store = Self { directory, limits };
+        store.recover()?;
+        store.enforce_limits(SystemTime::now())?;
+
+        Ok(store)
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.limits.max_entries > 0
+    }
+
+    /// Adds a capture to the history, then removes the entries that no longer fit in the limits.
+    pub fn add(
+        &self,
+        metadata: HistoryMetadata,
+        pixels: &[f16],
+    ) -> Result<HistoryEntry, HistoryError> {
+        let size = metadata.area.size();
+        let expected = size[0] as usize * size[1] as usize * 4;
+        if pixels.len() != expected {
+            return Err(HistoryError::PixelCount {
+                expected,
+                found: pixels.len(),
+            });
+        }
+
+        let id = self.unused_id(metadata.saved_at);
+
+        let capture_path = self.path(&id, CAPTURE_EXTENSION);
+        let temporary_capture_path = temporary_path(&capture_path);
+        exr::write(&temporary_capture_path, size, pixels)?;
+        fs::rename(&temporary_capture_path, &capture_path)?;
+
+        let metadata_path = self.path(&id, METADATA_EXTENSION);
+        let temporary_metadata_path = temporary_path(&metadata_path);
+        {
+            let mut file = File::create(&temporary_metadata_path)?;
+            file.write_all(toml::to_string_pretty(&metadata)?.as_bytes())?;
+            file.sync_all()?;
+        }
+        fs::rename(&temporary_metadata_path, &metadata_path)?;
+
+        let entry = HistoryEntry {
+            bytes: file_size(&capture_path) + file_size(&metadata_path),
+            id,
+            metadata,
+        };
+        debug!("Added {} to the capture history", entry.id);
+
+        self.enforce_limits(entry.metadata.saved_at())?;
+
+        Ok(entry)
+    }
+
+    /// The complete entries, newest first.
+    pub fn entries(&self) -> Result<Vec<HistoryEntry>, HistoryError> {
+        let mut entries = Vec::new();
+
+        for file in fs::read_dir(&self.directory)? {
+            let path = file?.path();
+            if !has_extension(&path, METADATA_EXTENSION) {
+                continue;
+            }
+
+            let Some(id) = path.file_stem().and_then(|stem| stem.to_str()) else {
+                continue;
+            };
+
+            let capture_path = self.path(id, CAPTURE_EXTENSION);
+            if !capture_path.is_file() {
+                continue;
+            }
+
+            let Some(metadata) = fs::read_to_string(&path)
+                .ok()
+                .and_then(|metadata| toml::from_str(&metadata).ok())
+            else {
+                continue;
+            };
+
+            entries.push(HistoryEntry {
+                id: id.to_string(),
+                metadata,
+                bytes: file_size(&capture_path) + file_size(&path),
+            });
+        }
+
+        entries.sort_by(|a, b| {
+            b.metadata
+                .saved_at
+                .cmp(&a.metadata.saved_at)
+                .then_with(|| b.id.cmp(&a.id))
+        });
+
+        Ok(entries)
+    }
+
+    /// Reads the `RGBA` pixels of an entry.
+    pub fn load(&self, entry: &HistoryEntry) -> Result<Vec<f16>, HistoryError> {
+        let (size, pixels) = exr::read(&self.path(&entry.id, CAPTURE_EXTENSION))?;
+
+        if size != entry.metadata.area.size() {
+            return Err(HistoryError::Size {
+                expected: entry.metadata.area.size(),
+                found: size,
+            });
+        }
+
+        Ok(pixels)
+    }
+
+    /// Removes an entry, the metadata is removed first so a partial removal is never listed.
+    pub fn remove(&self, id: &str) -> io::Result<()> {
+        for extension in [METADATA_EXTENSION, CAPTURE_EXTENSION] {
+            match fs::remove_file(self.path(id, extension)) {
+                Err(error) if error.kind() != io::ErrorKind::NotFound => return Err(error),
+                _ => {}
+            }
+        }
+
+        debug!("Removed {id} from the capture history");
+        Ok(())
+    }
+
+    /// Removes the entries past the entry limit, over the size limit, or older than the age limit.
+    ///
+    /// Entries are kept newest first, once an entry does not fit every older entry is removed.
+    pub fn enforce_limits(&self, now: SystemTime) -> Result<(), HistoryError> {
+        let max_bytes = self.limits.max_bytes();
+        let max_age = self.limits.max_age();
+
+        let mut bytes = 0;
+        let mut is_full = false;
+
+        for (index, entry) in self.entries()?.into_iter().enumerate() {
+            bytes += entry.bytes;
+
+            let age = now
+                .duration_since(entry.metadata.saved_at())
+                .unwrap_or_default();
+
+            is_full |=
+                index >= self.limits.max_entries as usize || bytes > max_bytes || age > max_age;
+
+            if is_full {
+                self.remove(&entry.id)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Removes the files left behind by an interrupted write or removal.
+    fn recover(&self) -> Result<(), HistoryError> {
+        let mut captures = Vec::new();
+        let mut complete = HashSet::new();
+
+        for file in fs::read_dir(&self.directory)? {
+            let path = file?.path();
+
+            if has_extension(&path, TEMPORARY_EXTENSION) {
+                warn!("Removing incomplete history file {path:?}");
+                fs::remove_file(&path)?;
+            } else if has_extension(&path, CAPTURE_EXTENSION) {
+                captures.push(path);
+            }
+        }
+
+        for entry in self.entries()? {
+            complete.insert(entry.id);
+        }
+
+        for path in captures {
+            let is_complete = path
+                .file_stem()
+                .and_then(|stem| stem.to_str())
+                .is_some_and(|id| complete.contains(id));
+
+            if !is_complete {
+                warn!("Removing history capture without metadata {path:?}");
+                fs::remove_file(&path)?;
+            }
+        }
+
+        // Metadata that cannot be read or has no capture
+        for file in fs::read_dir(&self.directory)? {
+            let path = file?.path();
+
+            let is_complete = path
+                .file_stem()
+                .and_then(|stem| stem.to_str())
+                .is_some_and(|id| complete.contains(id));
+
+            if has_extension(&path, METADATA_EXTENSION) && !is_complete {
+                warn!("Removing unusable history metadata {path:?}");
+                fs::remove_file(&path)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Returns an id based on the save time that no existing entry uses.
+    fn unused_id(&self, saved_at: u64) -> String {
+        let mut id = format!("{saved_at:013}");
+        let mut suffix = 1;
+
+        while self.path(&id, METADATA_EXTENSION).exists()
+            || self.path(&id, CAPTURE_EXTENSION).exists()
+        {
+            id = format!("{saved_at:013}-{suffix}");
+            suffix += 1;
+        }
+
+        id
+    }
+
+    fn path(&self, id: &str, extension: &str) -> PathBuf {
+        self.directory.join(format!("{id}.{extension}"))
+    }
+}
+
+fn temporary_path(path: &Path) -> PathBuf {
+    let mut path = path.as_os_str().to_owned();
+    path.push(".");
+    path.push(TEMPORARY_EXTENSION);
+    path.into()
+}
+
+fn has_extension(path: &Path, extension: &str) -> bool {
+    path.extension().is_some_and(|found| found == extension)
+}
+
+fn file_size(path: &Path) -> u64 {
+    fs::metadata(path)
+        .map(|metadata| metadata.len())
+        .unwrap_or(0)
+}
+
+/// The current time in milliseconds since the Unix epoch.
+pub fn unix_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as u64
+}
+
+#[derive(Debug, Error)]
+pub enum HistoryError {
+    #[error("Could not access the history:\n{0}")]
+    Io(#[from] io::Error),
+
+    #[error("Could not encode or decode the capture:\n{0}")]
+    Exr(#[from] ExrError),
+
+    #[error("Could not write the capture metadata:\n{0}")]
+    Metadata(#[from] toml::ser::Error),
+
+    #[error("The capture has {found} channels but its size needs {expected} channels")]
+    PixelCount { expected: usize, found: usize },
+
+    #[error("The capture is {found:?} but its metadata says {expected:?}")]
+    Size { expected: [u32; 2], found: [u32; 2] },
+}
+
+#[cfg(test)]
+mod test {
+    use core::time::Duration;
+    use std::{fs, path::PathBuf, time::SystemTime};
+
+    use half::f16;
+
+    use crate::capture_taker::{DesktopRect, Whitepoint};
+
+    use super::{HistoryLimits, HistoryMetadata, HistoryStore};
+
+    const DAY: Duration = Duration::from_secs(24 * 60 * 60);
+
+    /// Creates an empty directory for the test's store.
+    fn store_directory(name: &str) -> PathBuf {
+        let directory = std::env::temp_dir()
+            .join("hdr-snipping-tool-tests")
+            .join(format!("history-{name}-{}", std::process::id()));
+
+        if directory.exists() {
+            fs::remove_dir_all(&directory).unwrap();
+        }
+        fs::create_dir_all(&directory).unwrap();
+
+        directory
+    }
+
+    fn metadata(saved_at: u64) -> HistoryMetadata {
+        HistoryMetadata {
+            saved_at,
+            area: DesktopRect {
+                left: -3,
+                top: 0,
+                right: 0,
+                bottom: 2,
+            },
+            whitepoint: Whitepoint::Hdr(4.5),
+            selection: [0.0, 0.0, 3.0, 2.0],
+        }
+    }
+
+    /// Pixels with a different HDR value in every channel.
+    fn pixels(seed: f32) -> Vec<f16> {
+        (0..3 * 2 * 4)
+            .map(|index| f16::from_f32(seed + index as f32 * 0.25))
+            .collect()
+    }
+
+    fn limits(max_entries: u32) -> HistoryLimits {
+        HistoryLimits {
+            max_entries,
+            ..Default::default()
+        }
+    }
+
+    fn ids(store: &HistoryStore) -> Vec<String> {
+        store
+            .entries()
+            .unwrap()
+            .into_iter()
+            .map(|entry| entry.id)
+            .collect()
+    }
+
+    fn now() -> u64 {
+        super::unix_millis()
+    }
+
+    #[test]
+    fn round_trips_captures() {
+        let store = HistoryStore::open(store_directory("round-trip"), limits(5)).unwrap();
+
+        let saved_at = now();
+        let first = store.add(metadata(saved_at), &pixels(1.0)).unwrap();
+        let second = store.add(metadata(saved_at + 1), &pixels(8.0)).unwrap();
+
+        let entries = store.entries().unwrap();
+        assert_eq!(entries, [second.clone(), first.clone()]);
+        assert!(first.bytes > 0);
+
+        assert_eq!(store.load(&first).unwrap(), pixels(1.0));
+        assert_eq!(store.load(&second).unwrap(), pixels(8.0));
+    }
+
+    #[test]
+    fn same_time_gets_unique_ids() {
+        let store = HistoryStore::open(store_directory("unique"), limits(5)).unwrap();
+
+        let saved_at = now();
+        let first = store.add(metadata(saved_at), &pixels(1.0)).unwrap();
+        let second = store.add(metadata(saved_at), &pixels(2.0)).unwrap();
+
+        assert_ne!(first.id, second.id);
+        assert_eq!(store.entries().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn rejects_wrong_pixel_count() {
+        let store = HistoryStore::open(store_directory("pixel-count"), limits(5)).unwrap();
+
+        assert!(store.add(metadata(now()), &pixels(1.0)[4..]).is_err());
+        assert!(store.entries().unwrap().is_empty());
+    }
+
+    #[test]
+    fn keeps_newest_entries() {
+        let store = HistoryStore::open(store_directory("entry-limit"), limits(2)).unwrap();
+
+        let saved_at = now();
+        for offset in 0..4 {
+            store
+                .add(metadata(saved_at + offset), &pixels(1.0))
+                .unwrap();
+        }
+
+        let entries = store.entries().unwrap();
+        let saved = entries.iter().map(|entry| entry.metadata.saved_at);
+        assert!(saved.eq([saved_at + 3, saved_at + 2]));
+    }
+
+    #[test]
+    fn removes_entries_over_size() {
+        let directory = store_directory("size-limit");
+        let store = HistoryStore::open(directory.clone(), limits(10)).unwrap();
+
+        let saved_at = now();
+        let entry = store.add(metadata(saved_at), &pixels(1.0)).unwrap();
+        store.add(metadata(saved_at + 1), &pixels(2.0)).unwrap();
+        store.add(metadata(saved_at + 2), &pixels(3.0)).unwrap();
+        assert_eq!(store.entries().unwrap().len(), 3);
+
+        // A limit of zero megabytes cannot fit anything
+        let store = HistoryStore::open(
+            directory,
+            HistoryLimits {
+                max_size_mb: 0,
+                ..limits(10)
+            },
+        )
+        .unwrap();
+        assert!(store.entries().unwrap().is_empty());
+        assert!(store.load(&entry).is_err());
+    }
+
+    #[test]
+    fn removes_old_entries() {
+        let store = HistoryStore::open(
+            store_directory("age-limit"),
+            HistoryLimits {
+                max_age_days: 2,
+                ..limits(10)
+            },
+        )
+        .unwrap();
+
+        let day = DAY.as_millis() as u64;
+        let saved_at = now();
+        store
+            .add(metadata(saved_at - 5 * day), &pixels(1.0))
+            .unwrap();
+        store.add(metadata(saved_at - day), &pixels(2.0)).unwrap();
+        store.add(metadata(saved_at), &pixels(3.0)).unwrap();
+        assert_eq!(store.entries().unwrap().len(), 2);
+
+        store
+            .enforce_limits(SystemTime::now() + DAY + DAY / 2)
+            .unwrap();
+        let entries = store.entries().unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].metadata.saved_at, saved_at);
+    }
+
+    #[test]
+    fn recovers_from_interrupted_writes() {
+        let directory = store_directory("recover");
+        let store = HistoryStore::open(directory.clone(), limits(5)).unwrap();
+
+        let saved_at = now();
+        let kept = store.add(metadata(saved_at), &pixels(1.0)).unwrap();
+        let orphaned = store.add(metadata(saved_at + 1), &pixels(2.0)).unwrap();
+
+        // Crashed while writing the pixels of a capture
+        fs::write(directory.join("0000000000001.exr.tmp"), b"partial").unwrap();
+
+        // Crashed after the pixels were written but before the metadata was written
+        fs::remove_file(directory.join(format!("{}.toml", orphaned.id))).unwrap();
+        fs::write(
+            directory.join(format!("{}.toml.tmp", orphaned.id)),
+            b"saved",
+        )
+        .unwrap();
+
+        // Metadata was corrupted
+        fs::copy(
+            directory.join(format!("{}.exr", kept.id)),
+            directory.join("0000000000002.exr"),
+        )
+        .unwrap();
+        fs::write(directory.join("0000000000002.toml"), b"saved_at = ").unwrap();
+
+        // Pixels were removed
+        fs::write(directory.join("0000000000003.toml"), b"").unwrap();
+
+        // Incomplete entries are never listed, even before recovery
+        assert_eq!(ids(&store), [kept.id.as_str()]);
+
+        let store = HistoryStore::open(directory.clone(), limits(5)).unwrap();
+        assert_eq!(ids(&store), [kept.id.as_str()]);
+        assert_eq!(store.load(&kept).unwrap(), pixels(1.0));
+
+        let mut files: Vec<_> = fs::read_dir(&directory)
+            .unwrap()
+            .map(|file| file.unwrap().file_name().into_string().unwrap())
+            .collect();
+        files.sort();
+        assert_eq!(
+            files,
+            [format!("{}.exr", kept.id), format!("{}.toml", kept.id)]
+        );
+    }
+
+    #[test]
+    fn zero_entries_disables_history() {
+        let store = HistoryStore::open(store_directory("disabled"), limits(0)).unwrap();
+        assert!(!store.is_enabled());
+
+        store.add(metadata(now()), &pixels(1.0)).unwrap();
+        assert!(store.entries().unwrap().is_empty());
+    }
+}
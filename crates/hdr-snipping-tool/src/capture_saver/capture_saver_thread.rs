@@ -8,16 +8,31 @@ use std::{
 
 use tracing::{error, info_span};
 use vulkan::{HdrImage, Vulkan};
+use winit::event_loop::EventLoopProxy;
 
 use crate::{
-    selection::Selection,
+    annotation::Annotations,
+    application_event_loop::Event,
+    burst::BurstFrame,
+    capture_history::{HistoryMetadata, HistoryStore},
+    selection::{Lasso, Selection},
     utilities::failure::{Failure, Ignore},
 };
 
-use super::{BlockingCaptureSaver, CaptureSaver};
+use super::{BlockingCaptureSaver, CaptureSaver, SaveSettings};
 
 enum Message {
-    Save(HdrImage, f32, Selection),
+    Save(
+        HdrImage,
+        f32,
+        Selection,
+        Option<Lasso>,
+        Annotations,
+        Option<HistoryMetadata>,
+        String,
+    ),
+    SaveBurstFrame(BurstFrame),
+    SetSettings(SaveSettings),
     Shutdown,
 }
 
@@ -27,14 +42,20 @@ pub struct CaptureSaverThread {
 }
 
 impl CaptureSaverThread {
-    pub fn new(vulkan: Arc<Vulkan>) -> Self {
+    pub fn new(
+        vulkan: Arc<Vulkan>,
+        history: Option<HistoryStore>,
+        settings: SaveSettings,
+        proxy: EventLoopProxy<Event>,
+    ) -> Self {
         let (sender, receiver) = channel();
 
         let thread = thread::Builder::new()
             .name(String::from("Capture Saver"))
             .spawn(move || {
                 let _span = info_span!("[Capture Saver]").entered();
-                let capture_saver = BlockingCaptureSaver::new(&vulkan);
+                let mut capture_saver =
+                    BlockingCaptureSaver::new(&vulkan, history, settings, proxy);
 
                 loop {
                     // unwrap should never happen, CaptureTaker owns the sender and calls shutdown on drop.
@@ -42,9 +63,25 @@ impl CaptureSaverThread {
 
                     match message {
                         Message::Shutdown => break,
-                        Message::Save(hdr_image, whitepoint, selection) => {
-                            capture_saver.save_capture(hdr_image, whitepoint, selection)
-                        }
+                        Message::Save(
+                            hdr_image,
+                            whitepoint,
+                            selection,
+                            lasso,
+                            annotations,
+                            history,
+                            file_name,
+                        ) => capture_saver.save_capture(
+                            hdr_image,
+                            whitepoint,
+                            selection,
+                            lasso,
+                            annotations,
+                            history,
+                            file_name,
+                        ),
+                        Message::SaveBurstFrame(frame) => capture_saver.save_burst_frame(frame),
+                        Message::SetSettings(settings) => capture_saver.set_settings(settings),
                     }
                 }
             })
@@ -55,12 +92,42 @@ impl CaptureSaverThread {
             sender,
         }
     }
+
+    /// Uses the settings for the captures saved from now on.
+    pub fn set_settings(&self, settings: SaveSettings) {
+        self.sender
+            .send(Message::SetSettings(settings))
+            .report_and_panic("Could not send message to capture saver");
+    }
 }
 
 impl CaptureSaver for CaptureSaverThread {
-    fn save_capture(&self, capture: HdrImage, whitepoint: f32, selection: Selection) {
+    fn save_capture(
+        &self,
+        capture: HdrImage,
+        whitepoint: f32,
+        selection: Selection,
+        lasso: Option<Lasso>,
+        annotations: Annotations,
+        history: Option<HistoryMetadata>,
+        file_name: String,
+    ) {
+        self.sender
+            .send(Message::Save(
+                capture,
+                whitepoint,
+                selection,
+                lasso,
+                annotations,
+                history,
+                file_name,
+            ))
+            .report_and_panic("Could not send message to capture saver");
+    }
+
+    fn save_burst_frame(&self, frame: BurstFrame) {
         self.sender
-            .send(Message::Save(capture, whitepoint, selection))
+            .send(Message::SaveBurstFrame(frame))
             .report_and_panic("Could not send message to capture saver");
     }
 }
@@ -0,0 +1,284 @@
+use std::{io::Cursor, path::Path};
+
+use half::f16;
+use image::{ImageBuffer, ImageError, ImageFormat, Rgba};
+
+use crate::{
+    clipboard::{self, ClipboardContent, ClipboardFormat, Flavour},
+    selection::Lasso,
+};
+
+/// A tonemapped screenshot, as it is saved.
+pub type Screenshot = ImageBuffer<Rgba<u8>, Vec<u8>>;
+
+/// Makes the tonemapped `RGBA` pixels of the selection outside of a freeform selection
+/// transparent.
+pub fn finish_screenshot(
+    mut rgba: Vec<u8>,
+    lasso: Option<&Lasso>,
+    position: [usize; 2],
+    size: [usize; 2],
+) -> Screenshot {
+    if let Some(lasso) = lasso {
+        lasso.apply_mask(position, size, &mut rgba);
+    }
+
+    ImageBuffer::from_raw(size[0] as u32, size[1] as u32, rgba)
+        .expect("The pixels must match the selection size")
+}
+
+/// Encodes the screenshot as the PNG file it is saved as.
+pub fn encode_png(screenshot: &Screenshot) -> Result<Vec<u8>, ImageError> {
+    let mut data = Cursor::new(Vec::new());
+    screenshot.write_to(&mut data, ImageFormat::Png)?;
+
+    Ok(data.into_inner())
+}
+
+/// Encodes the screenshot in each of the clipboard formats, `hdr_selection` is the redacted HDR
+/// pixels of the selection if they are copied.
+pub fn clipboard_flavours(
+    formats: &[ClipboardFormat],
+    screenshot: &Screenshot,
+    path: Option<&Path>,
+    mut hdr_selection: Option<Vec<f16>>,
+) -> Vec<Flavour> {
+    // The HDR image has the same transparent areas as a freeform selection
+    if let Some(hdr_selection) = hdr_selection.as_mut() {
+        clipboard::copy_alpha(hdr_selection, screenshot.as_raw());
+    }
+
+    let content = ClipboardContent {
+        size: [screenshot.width(), screenshot.height()],
+        rgba: screenshot.as_raw(),
+        path,
+        hdr: hdr_selection.as_deref(),
+    };
+
+    clipboard::build_flavours(formats, &content)
+}
+
+#[cfg(test)]
+mod test {
+    use core::{fmt::Debug, hash::Hash};
+    use std::{collections::HashSet, fs};
+
+    use half::f16;
+
+    use crate::{
+        annotation::{self, Annotation, Annotations, RedactionStyle},
+        capture_history::{HistoryLimits, HistoryMetadata, HistoryStore, decode_exr},
+        capture_taker::{DesktopRect, Whitepoint},
+        clipboard::{self, ClipboardFormat},
+    };
+
+    use super::{clipboard_flavours, encode_png, finish_screenshot};
+
+    const SIZE: [usize; 2] = [96, 64];
+
+    /// The selection that is saved, it partially covers both redactions.
+    const POSITION: [usize; 2] = [6, 2];
+    const SELECTION: [usize; 2] = [80, 56];
+
+    /// The areas that are redacted, as left, top, right, and bottom.
+    const FILLED: [usize; 4] = [10, 5, 50, 30];
+    const PIXELATED: [usize; 4] = [40, 24, 90, 60];
+
+    /// A capture where every pixel has a unique value. Blue is a checkerboard, so the average of
+    /// any block with an even number of pixels can never match an original pixel.
+    fn unique_capture() -> Vec<f16> {
+        (0..SIZE[1])
+            .flat_map(|y| (0..SIZE[0]).map(move |x| (x, y)))
+            .flat_map(|(x, y)| {
+                let index = (y * SIZE[0] + x) as u16;
+                [
+                    f16::from_bits(f16::ONE.to_bits() + index),
+                    f16::ONE,
+                    f16::from_f32(((x + y) % 2) as f32),
+                    f16::ONE,
+                ]
+            })
+            .collect()
+    }
+
+    /// Stands in for the tonemapper, it keeps red exactly so an original pixel can be recognised
+    /// in the output.
+    fn tonemap(pixels: &[f16]) -> Vec<u8> {
+        pixels
+            .chunks_exact(4)
+            .flat_map(|pixel| {
+                let [low, high] = pixel[0].to_bits().to_le_bytes();
+                [low, high, (pixel[2].to_f32() * 255.0).round() as u8, 255]
+            })
+            .collect()
+    }
+
+    fn hdr_pixel(pixels: &[f16], width: usize, x: usize, y: usize) -> [u16; 4] {
+        let index = (y * width + x) * 4;
+        [0, 1, 2, 3].map(|channel| pixels[index + channel].to_bits())
+    }
+
+    fn sdr_pixel(rgba: &[u8], width: usize, x: usize, y: usize) -> [u8; 4] {
+        let index = (y * width + x) * 4;
+        [0, 1, 2, 3].map(|channel| rgba[index + channel])
+    }
+
+    fn is_redacted(x: usize, y: usize) -> bool {
+        [FILLED, PIXELATED]
+            .iter()
+            .any(|&[left, top, right, bottom]| {
+                (left..right).contains(&x) && (top..bottom).contains(&y)
+            })
+    }
+
+    /// Checks that every redacted pixel of an output changed, and that no changed pixel has the
+    /// value of any pixel in the original.
+    ///
+    /// Both pixels are looked up by their position in the capture, `area` is the area of the
+    /// capture the output covers, as left, top, right, and bottom.
+    fn assert_redacted<Value: Eq + Hash + Debug>(
+        output: &str,
+        area: [usize; 4],
+        original: impl Fn(usize, usize) -> Value,
+        exported: impl Fn(usize, usize) -> Value,
+    ) {
+        let [left, top, right, bottom] = area;
+        let positions: Vec<_> = (top..bottom)
+            .flat_map(|y| (left..right).map(move |x| (x, y)))
+            .collect();
+
+        let original_values: HashSet<_> = positions.iter().map(|&(x, y)| original(x, y)).collect();
+
+        for &(x, y) in &positions {
+            let value = exported(x, y);
+
+            if value == original(x, y) {
+                assert!(!is_redacted(x, y), "{output}: Pixel ({x}, {y}) was kept");
+                continue;
+            }
+
+            assert!(
+                !original_values.contains(&value),
+                "{output}: Pixel ({x}, {y}) has an original value"
+            );
+        }
+    }
+
+    #[test]
+    fn redacted_pixels_are_never_exported() {
+        let original = unique_capture();
+        let annotations = Annotations {
+            items: vec![
+                Annotation::Redact {
+                    start: [FILLED[0] as f32, FILLED[1] as f32],
+                    end: [FILLED[2] as f32, FILLED[3] as f32],
+                    style: RedactionStyle::Fill,
+                },
+                Annotation::Redact {
+                    start: [PIXELATED[0] as f32, PIXELATED[1] as f32],
+                    end: [PIXELATED[2] as f32, PIXELATED[3] as f32],
+                    style: RedactionStyle::Pixelate,
+                },
+            ],
+        };
+
+        // Redacted the way the saver does before anything is copied or tonemapped
+        let mut redacted = original.clone();
+        assert!(annotation::redact(&annotations, SIZE, &mut redacted));
+
+        let selection_area = [
+            POSITION[0],
+            POSITION[1],
+            POSITION[0] + SELECTION[0],
+            POSITION[1] + SELECTION[1],
+        ];
+        let original_hdr = |x, y| hdr_pixel(&original, SIZE[0], x, y);
+        let original_sdr = |x, y| {
+            let index = (y * SIZE[0] + x) * 4;
+            let rgba = tonemap(&original[index..index + 4]);
+            sdr_pixel(&rgba, 1, 0, 0)
+        };
+
+        // The history
+        let directory = std::env::temp_dir()
+            .join("hdr-snipping-tool-tests")
+            .join(format!("export-{}", std::process::id()));
+        if directory.exists() {
+            fs::remove_dir_all(&directory).unwrap();
+        }
+        fs::create_dir_all(&directory).unwrap();
+
+        let store = HistoryStore::open(directory.clone(), HistoryLimits::default()).unwrap();
+        let metadata = HistoryMetadata {
+            saved_at: 1,
+            area: DesktopRect {
+                left: 0,
+                top: 0,
+                right: SIZE[0] as i32,
+                bottom: SIZE[1] as i32,
+            },
+            whitepoint: Whitepoint::Hdr(1.0),
+            selection: [0.0, 0.0, SIZE[0] as f32, SIZE[1] as f32],
+        };
+        let entry = store.add(metadata, &redacted).unwrap();
+        let history = store.load(&entry).unwrap();
+        fs::remove_dir_all(&directory).unwrap();
+
+        assert_redacted("History", [0, 0, SIZE[0], SIZE[1]], original_hdr, |x, y| {
+            hdr_pixel(&history, SIZE[0], x, y)
+        });
+
+        // The saved file
+        let hdr_selection = clipboard::crop(&redacted, SIZE[0], POSITION, SELECTION);
+        let screenshot = finish_screenshot(tonemap(&hdr_selection), None, POSITION, SELECTION);
+
+        let file = image::load_from_memory(&encode_png(&screenshot).unwrap())
+            .unwrap()
+            .into_rgba8();
+        assert_redacted("File", selection_area, original_sdr, |x, y| {
+            file.get_pixel((x - POSITION[0]) as u32, (y - POSITION[1]) as u32)
+                .0
+        });
+
+        // The clipboard
+        let formats = [
+            ClipboardFormat::Bitmap,
+            ClipboardFormat::Png,
+            ClipboardFormat::Exr,
+        ];
+        let flavours = clipboard_flavours(&formats, &screenshot, None, Some(hdr_selection));
+        assert_eq!(flavours.len(), formats.len());
+
+        for flavour in flavours {
+            match flavour.format {
+                ClipboardFormat::Bitmap => {
+                    // Bottom-up `BGRA` rows after the header
+                    let rows = &flavour.data[124..];
+                    assert_redacted("Bitmap", selection_area, original_sdr, |x, y| {
+                        let row = SELECTION[1] - 1 - (y - POSITION[1]);
+                        let [b, g, r, a] = sdr_pixel(rows, SELECTION[0], x - POSITION[0], row);
+                        [r, g, b, a]
+                    });
+                }
+
+                ClipboardFormat::Png => {
+                    let png = image::load_from_memory(&flavour.data).unwrap().into_rgba8();
+                    assert_redacted("PNG", selection_area, original_sdr, |x, y| {
+                        png.get_pixel((x - POSITION[0]) as u32, (y - POSITION[1]) as u32)
+                            .0
+                    });
+                }
+
+                ClipboardFormat::Exr => {
+                    let (size, pixels) = decode_exr(&flavour.data).unwrap();
+                    assert_eq!(size, SELECTION.map(|axis| axis as u32));
+                    assert_redacted("EXR", selection_area, original_hdr, |x, y| {
+                        hdr_pixel(&pixels, SELECTION[0], x - POSITION[0], y - POSITION[1])
+                    });
+                }
+
+                _ => unreachable!(),
+            }
+        }
+    }
+}
@@ -1,47 +1,284 @@
-use std::borrow::Cow;
+use std::{fs, path::PathBuf, sync::Arc};
 
-use arboard::{Clipboard, ImageData};
-use chrono::Local;
-use image::{ImageBuffer, ImageFormat, Rgba};
+use ash_helper::AllocationError;
+use half::f16;
+use image::ImageBuffer;
 use tracing::{info, warn};
 use utilities::DebugTime;
-use vulkan::{HdrImage, HdrToSdrTonemapper, Vulkan};
+use vulkan::{AnnotationCompositor, HdrImage, HdrToSdrTonemapper, Vulkan};
+use winit::event_loop::EventLoopProxy;
 
 use crate::{
+    annotation::{self, Annotation, Annotations},
+    application_event_loop::Event,
+    burst::BurstFrame,
+    capture_history::{HistoryMetadata, HistoryStore},
+    clipboard::{self, ClipboardSettings},
+    config::Config,
+    error_reporting::{ErrorCategory, report_error, retry_or_report},
+    save_hooks::{SaveHook, SaveHookThread, SavedFile, SavedKind},
+    save_notification::{SavedScreenshot, THUMBNAIL_SIZE, Thumbnail},
     screenshot_dir,
-    selection::Selection,
-    utilities::failure::{Failure, report},
+    selection::{Lasso, Selection},
+    utilities::failure::{Failure, Ignore},
 };
 
 pub use capture_saver_thread::CaptureSaverThread;
 
 mod capture_saver_thread;
+mod export;
 
 pub trait CaptureSaver {
-    fn save_capture(&self, capture: HdrImage, whitepoint: f32, selection: Selection);
+    /// Saves the capture as `file_name` without the extension, and adds it to the history if there
+    /// is `history` metadata.
+    fn save_capture(
+        &self,
+        capture: HdrImage,
+        whitepoint: f32,
+        selection: Selection,
+        lasso: Option<Lasso>,
+        annotations: Annotations,
+        history: Option<HistoryMetadata>,
+        file_name: String,
+    );
+
+    /// Saves a capture of a burst to its numbered file.
+    fn save_burst_frame(&self, frame: BurstFrame);
+}
+
+/// The settings from the config that the capture saver uses.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SaveSettings {
+    pub save_hooks: Vec<SaveHook>,
+    pub clipboard: ClipboardSettings,
+
+    /// If a notification is shown once a screenshot is saved.
+    pub notification: bool,
+}
+
+impl SaveSettings {
+    pub fn from_config(config: &Config) -> Self {
+        Self {
+            save_hooks: config.save_hooks.clone(),
+            clipboard: config.clipboard.clone(),
+            notification: config.notification.enabled,
+        }
+    }
 }
 
 pub struct BlockingCaptureSaver<'vulkan> {
     vulkan: &'vulkan Vulkan,
     tonemapper: HdrToSdrTonemapper<'vulkan>,
+    compositor: AnnotationCompositor,
+    history: Option<HistoryStore>,
+    save_hooks: SaveHookThread,
+    clipboard: ClipboardSettings,
+    notification: bool,
+    proxy: EventLoopProxy<Event>,
 }
 
 impl<'vulkan> BlockingCaptureSaver<'vulkan> {
-    pub fn new(vulkan: &'vulkan Vulkan) -> Self {
+    pub fn new(
+        vulkan: &'vulkan Arc<Vulkan>,
+        history: Option<HistoryStore>,
+        settings: SaveSettings,
+        proxy: EventLoopProxy<Event>,
+    ) -> Self {
         let tonemapper =
             HdrToSdrTonemapper::new(vulkan).report_and_panic("Could not create the tonemapper");
+        let compositor = AnnotationCompositor::new(Arc::clone(vulkan))
+            .report_and_panic("Could not create the annotation compositor");
+
+        Self {
+            vulkan,
+            tonemapper,
+            compositor,
+            history,
+            save_hooks: SaveHookThread::new(settings.save_hooks),
+            clipboard: settings.clipboard,
+            notification: settings.notification,
+            proxy,
+        }
+    }
 
-        Self { vulkan, tonemapper }
+    /// Uses the settings for the captures saved from now on.
+    pub fn set_settings(&mut self, settings: SaveSettings) {
+        self.save_hooks.set_hooks(settings.save_hooks);
+        self.clipboard = settings.clipboard;
+        self.notification = settings.notification;
+    }
+}
+
+impl BlockingCaptureSaver<'_> {
+    /// Returns a copy of the capture with the redactions applied.
+    unsafe fn redact(
+        &self,
+        capture: HdrImage,
+        annotations: &Annotations,
+    ) -> Result<HdrImage, AllocationError> {
+        let size = [capture.extent.width, capture.extent.height];
+
+        let mut pixels = unsafe { capture.copy_to_cpu(self.vulkan)? };
+        annotation::redact(annotations, size.map(|axis| axis as usize), &mut pixels);
+
+        unsafe { HdrImage::from_cpu(self.vulkan, size, &pixels) }
+    }
+
+    /// Copies the capture for the history, if the history is enabled.
+    unsafe fn copy_for_history(
+        &self,
+        capture: HdrImage,
+        metadata: Option<HistoryMetadata>,
+    ) -> Option<(HistoryMetadata, Vec<f16>)> {
+        let metadata =
+            metadata.filter(|_| self.history.as_ref().is_some_and(HistoryStore::is_enabled))?;
+
+        let _timing = DebugTime::start("Copying capture for the history");
+        match unsafe { capture.copy_to_cpu(self.vulkan) } {
+            Ok(pixels) => Some((metadata, pixels)),
+            Err(e) => {
+                warn!("Could not copy the capture for the history: {e}");
+                None
+            }
+        }
+    }
+
+    /// Copies the HDR pixels of the selection for the clipboard, using the pixels already copied for
+    /// the history if there are any.
+    unsafe fn copy_hdr_selection(
+        &self,
+        capture: HdrImage,
+        copied: Option<&[f16]>,
+        selection: &Selection,
+    ) -> Option<Vec<f16>> {
+        let _timing = DebugTime::start("Copying capture for the clipboard");
+
+        let copied_now;
+        let pixels = match copied {
+            Some(pixels) => pixels,
+            None => match unsafe { capture.copy_to_cpu(self.vulkan) } {
+                Ok(pixels) => {
+                    copied_now = pixels;
+                    &copied_now
+                }
+                Err(e) => {
+                    warn!("Could not copy the capture for the clipboard: {e}");
+                    return None;
+                }
+            },
+        };
+
+        Some(clipboard::crop(
+            pixels,
+            capture.extent.width as usize,
+            selection.position_as_usize(),
+            selection.size_as_usize(),
+        ))
+    }
+
+    /// Adds a capture to the history, returning the entry's id. Failures only disable re-editing
+    /// so are not reported.
+    fn add_to_history(&self, metadata: HistoryMetadata, pixels: &[f16]) -> Option<String> {
+        let history = self.history.as_ref()?;
+
+        let _timing = DebugTime::start("Saving to history");
+        match history.add(metadata, pixels) {
+            Ok(entry) => {
+                info!("Saved capture to history as {}", entry.id);
+                Some(entry.id)
+            }
+            Err(e) => {
+                warn!("Could not save the capture to the history: {e}");
+                None
+            }
+        }
     }
 }
 
 impl CaptureSaver for BlockingCaptureSaver<'_> {
-    fn save_capture(&self, capture: HdrImage, whitepoint: f32, selection: Selection) {
+    fn save_capture(
+        &self,
+        capture: HdrImage,
+        whitepoint: f32,
+        selection: Selection,
+        lasso: Option<Lasso>,
+        annotations: Annotations,
+        history: Option<HistoryMetadata>,
+        file_name: String,
+    ) {
+        // Redact the capture before it is tonemapped, so the original pixels never reach the output
+        let redacted_capture = if annotations.items.iter().any(Annotation::is_redaction) {
+            let _timing = DebugTime::start("Redacting capture");
+
+            match unsafe { self.redact(capture, &annotations) } {
+                Ok(redacted_capture) => Some(redacted_capture),
+                Err(e) => {
+                    report_error(
+                        ErrorCategory::Import,
+                        e,
+                        "Could not save the screenshot.\nEncountered an error while redacting",
+                    );
+                    return;
+                }
+            }
+        } else {
+            None
+        };
+
+        // Keep the redacted pixels, so the history never holds what was redacted
+        let history_capture =
+            unsafe { self.copy_for_history(redacted_capture.unwrap_or(capture), history) };
+
+        // Copy the HDR selection, if it is copied to the clipboard
+        let hdr_selection = if self.clipboard.needs_hdr() {
+            let history_pixels = history_capture
+                .as_ref()
+                .map(|(_, pixels)| pixels.as_slice());
+
+            unsafe {
+                self.copy_hdr_selection(
+                    redacted_capture.unwrap_or(capture),
+                    history_pixels,
+                    &selection,
+                )
+            }
+        } else {
+            None
+        };
+
+        let selection_position = selection.position_as_usize();
+        let selection_size = selection.size_as_usize();
+
         // Tonemap the image
-        let sdr_image = match unsafe { self.tonemapper.tonemap(capture, whitepoint) } {
+        let tonemap_result = unsafe {
+            self.tonemapper
+                .tonemap(redacted_capture.unwrap_or(capture), whitepoint)
+        };
+
+        // Draw the annotations
+        let composite_result = match tonemap_result.as_ref() {
+            Ok(sdr_image) if !annotations.is_empty() => unsafe {
+                self.compositor.composite(
+                    redacted_capture.unwrap_or(capture),
+                    sdr_image,
+                    selection_position,
+                    selection_size,
+                    &annotations.passes(),
+                )
+            },
+            _ => Ok(()),
+        };
+
+        // Destroy the redacted capture
+        if let Some(redacted_capture) = redacted_capture {
+            unsafe { redacted_capture.destroy(self.vulkan) };
+        }
+
+        let sdr_image = match tonemap_result {
             Ok(sdr_image) => sdr_image,
             Err(e) => {
-                report(
+                report_error(
+                    ErrorCategory::Tonemap,
                     e,
                     "Could not save the screenshot.\nEncountered an error while tonemapping",
                 );
@@ -49,8 +286,15 @@ impl CaptureSaver for BlockingCaptureSaver<'_> {
             }
         };
 
-        let selection_position = selection.position_as_usize();
-        let selection_size = selection.size_as_usize();
+        if let Err(e) = composite_result {
+            report_error(
+                ErrorCategory::Tonemap,
+                e,
+                "Could not save the screenshot.\nEncountered an error while drawing the annotations",
+            );
+            unsafe { sdr_image.destroy(self.vulkan) };
+            return;
+        }
 
         // Copy the image to CPU Memory
         let bytes = match unsafe {
@@ -58,7 +302,8 @@ impl CaptureSaver for BlockingCaptureSaver<'_> {
         } {
             Ok(bytes) => bytes,
             Err(e) => {
-                report(
+                report_error(
+                    ErrorCategory::Import,
                     e,
                     "Could not save the screenshot.\nEncountered an error while copying the screenshot to CPU Memory",
                 );
@@ -69,45 +314,166 @@ impl CaptureSaver for BlockingCaptureSaver<'_> {
         // Destroy SDR image
         unsafe { sdr_image.destroy(self.vulkan) };
 
-        // Create Image Buffer
-        let img: ImageBuffer<Rgba<u8>, Vec<u8>> =
-            ImageBuffer::from_raw(selection_size[0] as u32, selection_size[1] as u32, bytes)
-                .unwrap();
+        // Mask a freeform selection
+        let img = {
+            let _timing = DebugTime::start("Finishing screenshot");
+            export::finish_screenshot(bytes, lasso.as_ref(), selection_position, selection_size)
+        };
 
         // Save to file
-        {
-            let _timing = DebugTime::start("Saving to file");
-            let name = format!("Screenshot {}.png", Local::now().format("%F %H%M%S"));
-            let path = screenshot_dir().join(name);
-
-            match img.save_with_format(&path, ImageFormat::Png) {
-                Ok(_) => info!("Saved screenshot to file"),
-                Err(e) => report(e, "Could not save the screenshot file"),
-            }
-        }
+        let saved_path = save_to_file(&img, &format!("{file_name}.png"));
 
         // Save to clipboard
-        {
+        if !self.clipboard.formats.is_empty() {
             let _timing = DebugTime::start("Saving to clipboard");
 
-            let mut clipboard = match Clipboard::new() {
-                Ok(clipboard) => clipboard,
-                Err(e) => {
-                    warn!("Platform does not support clipboard: {e}");
-                    return;
+            let flavours = export::clipboard_flavours(
+                &self.clipboard.formats,
+                &img,
+                saved_path.as_deref(),
+                hdr_selection,
+            );
+
+            let written = retry_or_report(
+                ErrorCategory::Clipboard,
+                "Could not save the screenshot to the clipboard",
+                || clipboard::write(&flavours),
+            );
+            if written.is_some() {
+                info!("Saved screenshot to clipboard");
+            }
+        }
+
+        // Save to history
+        let history_id =
+            history_capture.and_then(|(metadata, pixels)| self.add_to_history(metadata, &pixels));
+
+        let Some(path) = saved_path else {
+            return;
+        };
+
+        let thumbnail = self.notification.then(|| {
+            let _timing = DebugTime::start("Creating thumbnail");
+            Thumbnail::new(img.as_raw(), [img.width(), img.height()], THUMBNAIL_SIZE)
+        });
+
+        // Run the save hooks last, so they see the clipboard and history as they are left. The
+        // saved screenshot is shown once they finish, as a hook may move it.
+        let proxy = self.proxy.clone();
+        let history = self.history.clone().zip(history_id);
+        self.save_hooks.run(
+            SavedFile {
+                path,
+                kind: SavedKind::Screenshot,
+                size: selection_size.map(|axis| axis as u32),
+                whitepoint,
+            },
+            move |file| {
+                if let Some(thumbnail) = thumbnail {
+                    proxy
+                        .send_event(Event::ScreenshotSaved(SavedScreenshot {
+                            path: file.path.clone(),
+                            thumbnail,
+                            history,
+                        }))
+                        .ignore();
                 }
-            };
+            },
+        );
+    }
 
-            let save_result = clipboard.set_image(ImageData {
-                width: selection_size[0],
-                height: selection_size[1],
-                bytes: Cow::Owned(img.into_vec()),
-            });
+    fn save_burst_frame(&self, frame: BurstFrame) {
+        let size = frame.area.size();
 
-            match save_result {
-                Ok(_) => info!("Saved screenshot to clipboard"),
-                Err(e) => report(e, "Could not save the screenshot to the clipboard"),
+        // Upload the capture
+        let hdr_capture = match unsafe { HdrImage::from_cpu(self.vulkan, size, &frame.pixels) } {
+            Ok(hdr_capture) => hdr_capture,
+            Err(e) => {
+                report_error(
+                    ErrorCategory::Import,
+                    e,
+                    "Could not save the burst capture.\nEncountered an error while uploading the capture",
+                );
+                return;
             }
+        };
+
+        // Tonemap the image
+        let tonemap_result = unsafe {
+            self.tonemapper
+                .tonemap(hdr_capture, frame.whitepoint.value())
+        };
+        unsafe { hdr_capture.destroy(self.vulkan) };
+
+        let sdr_image = match tonemap_result {
+            Ok(sdr_image) => sdr_image,
+            Err(e) => {
+                report_error(
+                    ErrorCategory::Tonemap,
+                    e,
+                    "Could not save the burst capture.\nEncountered an error while tonemapping",
+                );
+                return;
+            }
+        };
+
+        // Copy the image to CPU Memory
+        let size = size.map(|axis| axis as usize);
+        let copy_result = unsafe { sdr_image.copy_to_cpu(self.vulkan, [0, 0], size) };
+        unsafe { sdr_image.destroy(self.vulkan) };
+
+        let bytes = match copy_result {
+            Ok(bytes) => bytes,
+            Err(e) => {
+                report_error(
+                    ErrorCategory::Import,
+                    e,
+                    "Could not save the burst capture.\nEncountered an error while copying the capture to CPU Memory",
+                );
+                return;
+            }
+        };
+
+        let img: export::Screenshot = ImageBuffer::from_raw(size[0] as u32, size[1] as u32, bytes)
+            .expect("The copied pixels must match the frame size");
+
+        if let Some(path) = save_to_file(&img, &frame.file_name) {
+            self.save_hooks.run(
+                SavedFile {
+                    path,
+                    kind: SavedKind::BurstFrame,
+                    size: [img.width(), img.height()],
+                    whitepoint: frame.whitepoint.value(),
+                },
+                |_| {},
+            );
         }
     }
 }
+
+/// Saves an image to a PNG file in the screenshot directory, returning its path if it was saved.
+fn save_to_file(img: &export::Screenshot, name: &str) -> Option<PathBuf> {
+    let _timing = DebugTime::start("Saving to file");
+    let path = screenshot_dir().join(name);
+
+    let data = match export::encode_png(img) {
+        Ok(data) => data,
+        Err(e) => {
+            report_error(
+                ErrorCategory::Save,
+                e,
+                "Could not save the screenshot file.\nEncountered an error while encoding it",
+            );
+            return None;
+        }
+    };
+
+    retry_or_report(
+        ErrorCategory::Save,
+        "Could not save the screenshot file",
+        || fs::write(&path, &data),
+    )?;
+
+    info!("Saved {name} to file");
+    Some(path)
+}
@@ -0,0 +1,149 @@
+use core::time::Duration;
+use std::{thread, time::Instant};
+
+use half::f16;
+use tracing::{debug, info};
+
+use crate::{
+    burst::{self, BurstFrame, BurstRequest},
+    error_reporting::{ErrorCategory, report_error},
+};
+
+use super::{
+    BlockingCaptureTaker, CaptureProvider, DesktopRect, ProviderMonitor, Whitepoint,
+    WhitepointAdapter,
+};
+
+impl<P: CaptureProvider> BlockingCaptureTaker<'_, P> {
+    /// Takes captures of a region of the monitor under its centre until the burst has taken
+    /// enough captures or is stopped, passing each capture to `send_frame`. Stops early if
+    /// `send_frame` returns `false`.
+    pub fn burst_region(
+        &mut self,
+        request: &BurstRequest,
+        mut send_frame: impl FnMut(BurstFrame) -> bool,
+    ) {
+        self.provider.prepare();
+
+        let Some((monitor, area)) =
+            self.find_region_monitor(request.area, "Could not take the burst")
+        else {
+            return;
+        };
+        let (info, strategy) = self.monitor_info(&monitor);
+
+        let mut whitepoint =
+            WhitepointAdapter::new(info.sdr_white, info.max_brightness).with_strategy(strategy);
+
+        let interval = request.settings.interval();
+        let start = Instant::now();
+        let mut next_frame = start;
+        let mut taken = 0;
+
+        info!("Started burst of {area:?}");
+        while !request.handle.is_stopped() && request.settings.wants_frame(taken) {
+            next_frame += interval;
+
+            let Some((frame_whitepoint, pixels)) = (unsafe {
+                self.take_burst_frame(&monitor, area, &mut whitepoint, start.elapsed())
+            }) else {
+                break;
+            };
+
+            taken += 1;
+            let frame = BurstFrame {
+                number: taken,
+                file_name: burst::frame_file_name(&request.name, taken),
+                area,
+                whitepoint: frame_whitepoint,
+                pixels,
+            };
+
+            if !send_frame(frame) {
+                break;
+            }
+
+            thread::sleep(next_frame.saturating_duration_since(Instant::now()));
+        }
+
+        info!("Stopped burst after {taken} captures");
+    }
+
+    /// Takes a capture of the monitor and copies the region to CPU memory, reporting any errors.
+    ///
+    /// # Safety
+    /// The capture taker's Vulkan instance must be the instance the provider imports into.
+    unsafe fn take_burst_frame(
+        &mut self,
+        monitor: &P::Monitor,
+        area: DesktopRect,
+        whitepoint: &mut WhitepointAdapter,
+        timestamp: Duration,
+    ) -> Option<(Whitepoint, Vec<f16>)> {
+        let (capture, hdr_capture) = match unsafe { self.provider.capture(self.vulkan, monitor) } {
+            Ok(capture) => capture,
+            Err(e) => {
+                let message = e.user_message();
+                report_error(e.category(), e, message);
+                return None;
+            }
+        };
+
+        let size = [
+            hdr_capture.extent.width as usize,
+            hdr_capture.extent.height as usize,
+        ];
+
+        let result = unsafe { self.hdr_scanner.scan(hdr_capture) }.map(|maximum| {
+            let pixels = unsafe { hdr_capture.copy_to_cpu(self.vulkan) };
+            (maximum, pixels)
+        });
+
+        unsafe { hdr_capture.destroy(self.vulkan) };
+        self.provider.cleanup(capture);
+
+        let (maximum, pixels) = match result {
+            Ok((maximum, Ok(pixels))) => (maximum, pixels),
+            Ok((_, Err(e))) => {
+                report_error(
+                    ErrorCategory::Import,
+                    e,
+                    "Could not finish the burst.\nEncountered an error while copying a capture to CPU memory",
+                );
+                return None;
+            }
+            Err(e) => {
+                report_error(
+                    ErrorCategory::Scan,
+                    e,
+                    "Could not finish the burst.\nEncountered an error while analysing a capture",
+                );
+                return None;
+            }
+        };
+
+        let frame_whitepoint = whitepoint.update(maximum, timestamp);
+        debug!("Burst capture whitepoint: {frame_whitepoint:?}");
+
+        let rect = monitor.info().rect;
+        let position = [
+            (area.left - rect.left) as usize,
+            (area.top - rect.top) as usize,
+        ];
+        let crop_size = area.size().map(|axis| axis as usize);
+
+        if position[0] + crop_size[0] > size[0] || position[1] + crop_size[1] > size[1] {
+            report_error(
+                ErrorCategory::Capture,
+                format!("{area:?} is outside of the {size:?} capture of {rect:?}"),
+                "Could not finish the burst.\nThe capture does not cover the region",
+            );
+            return None;
+        }
+
+        Some((
+            frame_whitepoint,
+            burst::crop(&pixels, size, position, crop_size),
+        ))
+    }
+}
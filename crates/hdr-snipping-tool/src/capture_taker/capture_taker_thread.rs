@@ -9,20 +9,28 @@ use std::{
 
 use tracing::{error, info_span};
 use vulkan::Vulkan;
-use windows_capture_provider::WindowsCapture;
 use winit::event_loop::EventLoopProxy;
 
 use crate::{
     application_event_loop::Event,
+    burst::BurstRequest,
+    recording::RecordingRequest,
     utilities::failure::{Failure, Ignore},
 };
 
-use super::{BlockingCaptureTaker, CaptureTaker};
+use super::{
+    BlockingCaptureTaker, CaptureTaker, CaptureTarget, MonitorOverride, PlatformCapture,
+    PlatformCaptureProvider, WhitepointStrategy,
+};
 
 enum Message {
     Shutdown,
-    TakeCapture(EventLoopProxy<Event>),
-    CleanupWindowsCapture(WindowsCapture),
+    TakeCapture(EventLoopProxy<Event>, CaptureTarget),
+    CleanupCapture(PlatformCapture),
+    Record(EventLoopProxy<Event>, RecordingRequest),
+    Burst(EventLoopProxy<Event>, BurstRequest),
+    SetWhitepointStrategy(WhitepointStrategy),
+    SetMonitorOverrides(Vec<MonitorOverride>),
     RefreshCache,
 }
 
@@ -39,7 +47,8 @@ impl CaptureTakerThread {
             .name(String::from("Capture Taker"))
             .spawn(move || {
                 let _span = info_span!("[Capture Taker]").entered();
-                let mut capture_taker = BlockingCaptureTaker::new(&vulkan);
+                let mut capture_taker =
+                    BlockingCaptureTaker::new(&vulkan, PlatformCaptureProvider::new());
 
                 loop {
                     // Unwrap should never happen, CaptureTaker owns the sender and calls shutdown on drop.
@@ -48,9 +57,17 @@ impl CaptureTakerThread {
                     match message {
                         Message::Shutdown => break,
                         Message::RefreshCache => capture_taker.refresh_cache(),
-                        Message::TakeCapture(proxy) => capture_taker.take_capture(proxy),
-                        Message::CleanupWindowsCapture(capture) => {
-                            capture_taker.cleanup_windows_capture(capture)
+                        Message::TakeCapture(proxy, target) => {
+                            capture_taker.take_capture(proxy, target)
+                        }
+                        Message::CleanupCapture(capture) => capture_taker.cleanup_capture(capture),
+                        Message::Record(proxy, request) => capture_taker.record(proxy, request),
+                        Message::Burst(proxy, request) => capture_taker.burst(proxy, request),
+                        Message::SetWhitepointStrategy(strategy) => {
+                            capture_taker.set_whitepoint_strategy(strategy)
+                        }
+                        Message::SetMonitorOverrides(overrides) => {
+                            capture_taker.set_monitor_overrides(overrides)
                         }
                     }
                 }
@@ -84,15 +101,39 @@ impl CaptureTakerThread {
 }
 
 impl CaptureTaker for CaptureTakerThread {
-    fn cleanup_windows_capture(&self, capture: WindowsCapture) {
+    fn cleanup_capture(&self, capture: PlatformCapture) {
+        self.sender
+            .send(Message::CleanupCapture(capture))
+            .report_and_panic("Could not send message to capture taker");
+    }
+
+    fn take_capture(&mut self, proxy: EventLoopProxy<Event>, target: CaptureTarget) {
+        self.sender
+            .send(Message::TakeCapture(proxy, target))
+            .report_and_panic("Could not send message to capture taker");
+    }
+
+    fn record(&mut self, proxy: EventLoopProxy<Event>, request: RecordingRequest) {
+        self.sender
+            .send(Message::Record(proxy, request))
+            .report_and_panic("Could not send message to capture taker");
+    }
+
+    fn burst(&mut self, proxy: EventLoopProxy<Event>, request: BurstRequest) {
+        self.sender
+            .send(Message::Burst(proxy, request))
+            .report_and_panic("Could not send message to capture taker");
+    }
+
+    fn set_whitepoint_strategy(&mut self, strategy: WhitepointStrategy) {
         self.sender
-            .send(Message::CleanupWindowsCapture(capture))
+            .send(Message::SetWhitepointStrategy(strategy))
             .report_and_panic("Could not send message to capture taker");
     }
 
-    fn take_capture(&mut self, proxy: EventLoopProxy<Event>) {
+    fn set_monitor_overrides(&mut self, overrides: Vec<MonitorOverride>) {
         self.sender
-            .send(Message::TakeCapture(proxy))
+            .send(Message::SetMonitorOverrides(overrides))
             .report_and_panic("Could not send message to capture taker");
     }
 
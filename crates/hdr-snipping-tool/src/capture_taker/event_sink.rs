@@ -0,0 +1,29 @@
+use std::sync::mpsc::Sender;
+
+use thiserror::Error;
+use winit::event_loop::EventLoopProxy;
+
+use crate::{application::LoadingEvent, application_event_loop::Event};
+
+use super::{CaptureProvider, PlatformCaptureProvider};
+
+/// Receives the progress of a capture from the capture taker.
+pub trait LoadingEventSink<P: CaptureProvider> {
+    fn send_loading_event(&self, event: LoadingEvent<P>) -> Result<(), SinkClosed>;
+}
+
+#[derive(Debug, Error)]
+#[error("The receiver of the loading events was closed")]
+pub struct SinkClosed;
+
+impl LoadingEventSink<PlatformCaptureProvider> for EventLoopProxy<Event> {
+    fn send_loading_event(&self, event: LoadingEvent) -> Result<(), SinkClosed> {
+        self.send_event(event.into()).map_err(|_| SinkClosed)
+    }
+}
+
+impl<P: CaptureProvider> LoadingEventSink<P> for Sender<LoadingEvent<P>> {
+    fn send_loading_event(&self, event: LoadingEvent<P>) -> Result<(), SinkClosed> {
+        self.send(event).map_err(|_| SinkClosed)
+    }
+}
@@ -1,131 +1,131 @@
-use tracing::{debug, error, info};
+use half::f16;
+use tracing::{debug, info};
 use utilities::DebugTime;
-use vulkan::{HdrImage, HdrScanner, Vulkan};
-use windows::Win32::Foundation::CloseHandle;
-use windows_capture_provider::{CaptureItemCache, DirectX, Monitor, WindowsCapture};
+use vulkan::{HdrImage, HdrScanner, HdrToSdrTonemapper, Vulkan};
 use winit::event_loop::EventLoopProxy;
 
 use crate::{
-    application::LoadingEvent,
+    application::{ApplicationEvent, LoadingEvent},
     application_event_loop::Event,
-    utilities::failure::{Failure, Ignore, report, report_and_panic},
+    burst::BurstRequest,
+    error_reporting::{ErrorCategory, report_error},
+    recording::RecordingRequest,
+    utilities::failure::{Failure, Ignore},
 };
 
 pub use capture_taker_thread::CaptureTakerThread;
+pub use event_sink::{LoadingEventSink, SinkClosed};
+pub use monitor_override::MonitorOverride;
+pub use provider::{
+    CaptureError, CaptureProvider, MonitorIdentity, MonitorInfo, PlatformCapture,
+    PlatformCaptureProvider, PlatformMonitor, ProviderMonitor, SyntheticCapture,
+    SyntheticCaptureProvider, SyntheticError, SyntheticMonitor, WindowTarget,
+};
+pub use virtual_desktop::DesktopRect;
+pub use whitepoint::{Whitepoint, WhitepointAdapter, WhitepointStrategy};
 
-mod capture_taker_thread;
+use virtual_desktop::{DisplayCapture, composite};
 
-#[derive(Clone, Copy)]
-pub enum Whitepoint {
-    Sdr(f32),
-    Hdr(f32),
-}
-
-impl Whitepoint {
-    pub fn value(self) -> f32 {
-        match self {
-            Self::Sdr(value) => value,
-            Self::Hdr(value) => value,
-        }
-    }
+mod burst;
+mod capture_taker_thread;
+mod event_sink;
+mod monitor_override;
+mod provider;
+mod record;
+mod virtual_desktop;
+mod whitepoint;
+
+/// What a capture covers.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum CaptureTarget {
+    /// The monitor under the mouse.
+    #[default]
+    HoveredMonitor,
+
+    /// Every active monitor, composited into the virtual desktop.
+    VirtualDesktop,
+
+    /// A single window, without the windows that cover it.
+    Window(WindowTarget),
 }
 
 pub trait CaptureTaker {
     fn refresh_cache(&mut self);
 
-    fn take_capture(&mut self, proxy: EventLoopProxy<Event>);
+    fn take_capture(&mut self, proxy: EventLoopProxy<Event>, target: CaptureTarget);
+
+    fn cleanup_capture(&self, capture: PlatformCapture);
+
+    /// Records a region until the recording is stopped, then saves it.
+    fn record(&mut self, proxy: EventLoopProxy<Event>, request: RecordingRequest);
 
-    fn cleanup_windows_capture(&self, capture: WindowsCapture);
+    /// Takes a burst of captures of a region, sending each capture to the application.
+    fn burst(&mut self, proxy: EventLoopProxy<Event>, request: BurstRequest);
+
+    /// Changes how the whitepoint of the following captures is chosen.
+    fn set_whitepoint_strategy(&mut self, strategy: WhitepointStrategy);
+
+    /// Changes the corrections applied to the values monitors report for the following captures.
+    fn set_monitor_overrides(&mut self, overrides: Vec<MonitorOverride>);
 }
 
-pub struct BlockingCaptureTaker<'vulkan> {
-    direct_x: DirectX,
-    cache: CaptureItemCache,
+pub struct BlockingCaptureTaker<'vulkan, P: CaptureProvider> {
+    provider: P,
 
     vulkan: &'vulkan Vulkan,
 
     hdr_scanner: HdrScanner<'vulkan>,
-}
 
-impl<'vulkan> BlockingCaptureTaker<'vulkan> {
-    pub fn new(vulkan: &'vulkan Vulkan) -> Self {
-        let direct_x = DirectX::new().report_and_panic("Could not create DirectX devices");
-        let cache = CaptureItemCache::new();
+    tonemapper: HdrToSdrTonemapper<'vulkan>,
+
+    whitepoint_strategy: WhitepointStrategy,
 
+    monitor_overrides: Vec<MonitorOverride>,
+}
+
+impl<'vulkan, P: CaptureProvider> BlockingCaptureTaker<'vulkan, P> {
+    pub fn new(vulkan: &'vulkan Vulkan, provider: P) -> Self {
         let hdr_scanner =
             HdrScanner::new(vulkan).report_and_panic("Could not create the HDR Scanner");
+        let tonemapper =
+            HdrToSdrTonemapper::new(vulkan).report_and_panic("Could not create the tonemapper");
 
         Self {
-            direct_x,
-            cache,
+            provider,
             vulkan,
             hdr_scanner,
+            tonemapper,
+            whitepoint_strategy: WhitepointStrategy::default(),
+            monitor_overrides: Vec::new(),
         }
     }
 }
 
-impl CaptureTaker for BlockingCaptureTaker<'_> {
-    fn refresh_cache(&mut self) {
-        if !self.direct_x.devices_valid() {
-            report_and_panic(
-                "DirectX device lost",
-                "Could not refresh the cache.\nThe DirectX device was lost.",
-            );
-        }
+impl<P: CaptureProvider> BlockingCaptureTaker<'_, P> {
+    /// Takes a capture of the target, sending the progress to the sink.
+    pub fn capture_target(&mut self, sink: &impl LoadingEventSink<P>, target: CaptureTarget) {
+        self.provider.prepare();
 
-        if self
-            .direct_x
-            .dxgi_adapter_outdated()
-            .inspect_err(|e| error!("Could not check if the DirectX devices were outdated: {e}"))
-            .unwrap_or(true)
-        {
-            self.direct_x.recreate_dxgi_adapter().report_and_panic(
-                "Could not refresh the DirectX devices.\nThe DirectX device creation failed.",
-            );
-            self.cache.purge();
-            debug!("Recreated out-of-date DXGI device, purged cache");
+        match target {
+            CaptureTarget::HoveredMonitor => self.take_monitor_capture(sink),
+            CaptureTarget::VirtualDesktop => self.take_virtual_desktop_capture(sink),
+            CaptureTarget::Window(target) => self.take_window_capture(sink, target),
         }
-
-        if let Err(e) = self.cache.prune(&self.direct_x) {
-            error!("Could not prune the cache: {e}");
-        };
-
-        if let Err(e) = self.cache.cache_active(&self.direct_x) {
-            error!("Could not cache the active monitors: {e}");
-        };
     }
 
-    fn take_capture(&mut self, proxy: EventLoopProxy<Event>) {
-        if !self.direct_x.devices_valid() {
-            report_and_panic(
-                "DirectX device lost",
-                "Could not refresh the cache.\nThe DirectX device was lost.",
-            );
-        }
-
-        if self
-            .direct_x
-            .dxgi_adapter_outdated()
-            .report("Could not check if the DirectX devices were outdated.")
-            .unwrap_or(true)
-        {
-            self.direct_x.recreate_dxgi_adapter().report_and_panic(
-                "Could not refresh the DirectX devices.\nThe DirectX device creation failed.",
-            );
-            self.cache.purge();
-            debug!("Recreated out-of-date DXGI device, purged cache");
-        }
-
+    /// Takes a capture of the hovered monitor.
+    fn take_monitor_capture(&mut self, sink: &impl LoadingEventSink<P>) {
         // Get the monitor
         let monitor = {
-            let maybe_monitor = match Monitor::get_hovered_monitor(&self.direct_x) {
+            let maybe_monitor = match self.provider.hovered_monitor() {
                 Ok(maybe_monitor) => maybe_monitor,
                 Err(e) => {
-                    report(
+                    report_error(
+                        ErrorCategory::Capture,
                         e,
                         "Could not take the screenshot.\nAn error was encountered while finding the hovered monitor",
                     );
-                    proxy.send_event(LoadingEvent::Error.into()).ignore();
+                    sink.send_loading_event(LoadingEvent::Error).ignore();
                     return;
                 }
             };
@@ -133,145 +133,442 @@ impl CaptureTaker for BlockingCaptureTaker<'_> {
             let monitor = match maybe_monitor {
                 Some(monitor) => monitor,
                 None => {
-                    report(
-                        "Monitor::get_hovered_monitor was None",
+                    report_error(
+                        ErrorCategory::Capture,
+                        "CaptureProvider::hovered_monitor was None",
                         "Could not take the screenshot.\nCould not find the monitor that the cursor is on",
                     );
-                    proxy.send_event(LoadingEvent::Error.into()).ignore();
+                    sink.send_loading_event(LoadingEvent::Error).ignore();
                     return;
                 }
             };
 
             debug!("Hovered {monitor:?}");
 
-            proxy
-                .send_event(LoadingEvent::FoundMonitor(monitor).into())
+            sink.send_loading_event(LoadingEvent::FoundMonitors(vec![monitor.clone()]))
                 .report_and_panic("Eventloop exited");
 
             monitor
         };
 
         // Take the capture
-        let (windows_capture, windows_capture_resources) = {
-            let capture_item = {
-                let _timer = DebugTime::start("Getting capture item");
-
-                match self.cache.get_capture_item(monitor.handle.0) {
-                    Ok(capture_item) => capture_item,
-                    Err(e) => {
-                        report(
-                            e,
-                            "Could not take the screenshot.\nEncountered an error while creating the required resources",
-                        );
-                        proxy.send_event(LoadingEvent::Error.into()).ignore();
-                        return;
-                    }
-                }
-            };
+        let Some(hdr_capture) = self.take_provider_capture(&monitor, sink) else {
+            sink.send_loading_event(LoadingEvent::Error).ignore();
+            return;
+        };
 
-            let (capture, resources) = {
-                let _timer = DebugTime::start("Taking capture");
-
-                match WindowsCapture::take_capture(&self.direct_x, &capture_item) {
-                    Ok(capture) => capture,
-                    Err(e) => {
-                        report(
-                            e,
-                            "Could not take the screenshot.\nEncountered an error while taking the screenshot",
-                        );
-                        proxy.send_event(LoadingEvent::Error.into()).ignore();
-                        return;
-                    }
-                }
-            };
+        sink.send_loading_event(LoadingEvent::ImportedCapture(hdr_capture))
+            .report_and_panic("Eventloop exited");
 
-            proxy
-                .send_event(LoadingEvent::GotCapture(capture).into())
-                .report_and_panic("Eventloop exited");
+        // Find the whitepoint
+        let (info, strategy) = self.monitor_info(&monitor);
+        if !self.find_whitepoint(
+            hdr_capture,
+            info.sdr_white,
+            info.max_brightness,
+            strategy,
+            sink,
+        ) {
+            return;
+        }
 
-            (capture, resources)
-        };
+        info!("Got screenshot");
+    }
 
-        // Import the capture
-        let hdr_capture = unsafe {
-            let capture = match HdrImage::import_windows_capture(
-                self.vulkan,
-                windows_capture.size,
-                windows_capture.handle.0.0 as isize,
-            ) {
-                Ok(capture) => capture,
+    /// Takes a capture of a single window.
+    fn take_window_capture(&mut self, sink: &impl LoadingEventSink<P>, target: WindowTarget) {
+        // Get the window
+        let window = {
+            let maybe_window = match self.provider.find_window(target) {
+                Ok(maybe_window) => maybe_window,
                 Err(e) => {
-                    windows_capture_resources.destroy(&self.direct_x).ignore();
-                    report(
+                    report_error(
+                        ErrorCategory::Capture,
                         e,
-                        "Could not take the screenshot.\nEncountered an error while importing the screenshot from DirectX to the application",
+                        "Could not take the screenshot.\nAn error was encountered while finding the window",
                     );
-                    proxy.send_event(LoadingEvent::Error.into()).ignore();
+                    sink.send_loading_event(LoadingEvent::Error).ignore();
                     return;
                 }
             };
 
-            proxy
-                .send_event(LoadingEvent::ImportedCapture(capture).into())
+            let window = match maybe_window {
+                Some(window) => window,
+                None => {
+                    report_error(
+                        ErrorCategory::Capture,
+                        format!("CaptureProvider::find_window({target:?}) was None"),
+                        "Could not take the screenshot.\nCould not find a window to capture",
+                    );
+                    sink.send_loading_event(LoadingEvent::Error).ignore();
+                    return;
+                }
+            };
+
+            debug!("Found {window:?}");
+
+            sink.send_loading_event(LoadingEvent::FoundWindow(window.clone()))
                 .report_and_panic("Eventloop exited");
 
-            capture
+            window
         };
 
+        // Take the capture, retrying as captures fail while the display mode changes
+        let category = ErrorCategory::Capture;
+        let (capture, hdr_capture) = match category.retry_policy().run(category, || unsafe {
+            self.provider.capture_window(self.vulkan, &window)
+        }) {
+            Ok(capture) => capture,
+            Err(e) => {
+                let message = e.user_message();
+                report_error(e.category(), e, message);
+                sink.send_loading_event(LoadingEvent::Error).ignore();
+                return;
+            }
+        };
+
+        sink.send_loading_event(LoadingEvent::GotCapture(capture))
+            .report_and_panic("Eventloop exited");
+
+        sink.send_loading_event(LoadingEvent::ImportedCapture(hdr_capture))
+            .report_and_panic("Eventloop exited");
+
         // Find the whitepoint
-        {
-            let maximum = match unsafe { self.hdr_scanner.scan(hdr_capture) } {
-                Ok(maximum) => maximum,
-                Err(e) => {
-                    report(e, "Encountered an error while analysing the screenshot");
-                    proxy.send_event(LoadingEvent::Error.into()).ignore();
-                    return;
-                }
+        let (info, strategy) = self.monitor_info(&window);
+        if !self.find_whitepoint(
+            hdr_capture,
+            info.sdr_white,
+            info.max_brightness,
+            strategy,
+            sink,
+        ) {
+            return;
+        }
+
+        info!("Got window screenshot");
+    }
+
+    /// Takes a capture of every active monitor and composites them into the virtual desktop.
+    fn take_virtual_desktop_capture(&mut self, sink: &impl LoadingEventSink<P>) {
+        // Get the monitors
+        let monitors = match self.provider.active_monitors() {
+            Ok(monitors) if !monitors.is_empty() => monitors,
+            Ok(_) => {
+                report_error(
+                    ErrorCategory::Capture,
+                    "CaptureProvider::active_monitors was empty",
+                    "Could not take the screenshot.\nCould not find any active monitors",
+                );
+                sink.send_loading_event(LoadingEvent::Error).ignore();
+                return;
+            }
+            Err(e) => {
+                report_error(
+                    ErrorCategory::Capture,
+                    e,
+                    "Could not take the screenshot.\nAn error was encountered while finding the active monitors",
+                );
+                sink.send_loading_event(LoadingEvent::Error).ignore();
+                return;
+            }
+        };
+
+        for monitor in &monitors {
+            debug!("Active {monitor:?}");
+        }
+
+        sink.send_loading_event(LoadingEvent::FoundMonitors(monitors.clone()))
+            .report_and_panic("Eventloop exited");
+
+        // Capture each monitor into CPU memory
+        let mut captures = Vec::with_capacity(monitors.len());
+        for monitor in &monitors {
+            let Some(capture) = self.capture_to_cpu(monitor, sink) else {
+                sink.send_loading_event(LoadingEvent::Error).ignore();
+                return;
             };
 
-            debug!("Found maximum: {}", maximum);
+            captures.push(capture);
+        }
 
-            let is_hdr = maximum > monitor.sdr_white;
+        // Composite the monitors
+        let virtual_desktop = {
+            let _timer = DebugTime::start("Compositing virtual desktop");
+
+            let displays: Vec<_> = monitors
+                .iter()
+                .zip(&captures)
+                .map(|(monitor, (size, pixels))| {
+                    let (info, _) = self.monitor_info(monitor);
+                    let left = info.rect.left;
+                    let top = info.rect.top;
+
+                    DisplayCapture {
+                        rect: DesktopRect {
+                            left,
+                            top,
+                            right: left + size[0] as i32,
+                            bottom: top + size[1] as i32,
+                        },
+                        sdr_white: info.sdr_white,
+                        max_brightness: info.max_brightness,
+                        pixels,
+                    }
+                })
+                .collect();
 
-            if !is_hdr {
-                debug!("Selected SDR whitepoint: {}", monitor.sdr_white);
+            match composite(&displays) {
+                Ok(virtual_desktop) => virtual_desktop,
+                Err(e) => {
+                    report_error(
+                        ErrorCategory::Capture,
+                        e,
+                        "Could not take the screenshot.\nEncountered an error while combining the monitors",
+                    );
+                    sink.send_loading_event(LoadingEvent::Error).ignore();
+                    return;
+                }
+            }
+        };
 
-                proxy
-                    .send_event(
-                        LoadingEvent::SelectedWhitepoint(Whitepoint::Sdr(monitor.sdr_white)).into(),
-                    )
-                    .report_and_panic("Eventloop exited");
-            } else {
-                debug!("Selected HDR whitepoint: {}", monitor.max_brightness);
+        debug!(
+            "Composited virtual desktop {:?}, SDR white: {}, max brightness: {}",
+            virtual_desktop.rect, virtual_desktop.sdr_white, virtual_desktop.max_brightness
+        );
 
-                proxy
-                    .send_event(
-                        LoadingEvent::SelectedWhitepoint(Whitepoint::Hdr(monitor.max_brightness))
-                            .into(),
-                    )
-                    .report_and_panic("Eventloop exited");
+        // Upload the composite
+        let hdr_capture = match unsafe {
+            HdrImage::from_cpu(
+                self.vulkan,
+                virtual_desktop.rect.size(),
+                &virtual_desktop.pixels,
+            )
+        } {
+            Ok(capture) => capture,
+            Err(e) => {
+                report_error(
+                    ErrorCategory::Import,
+                    e,
+                    "Could not take the screenshot.\nEncountered an error while uploading the screenshot",
+                );
+                sink.send_loading_event(LoadingEvent::Error).ignore();
+                return;
             }
+        };
+
+        sink.send_loading_event(LoadingEvent::ImportedCapture(hdr_capture))
+            .report_and_panic("Eventloop exited");
+
+        // Find the whitepoint, the monitors are combined so their own strategies are not used.
+        if !self.find_whitepoint(
+            hdr_capture,
+            virtual_desktop.sdr_white,
+            virtual_desktop.max_brightness,
+            self.whitepoint_strategy,
+            sink,
+        ) {
+            return;
         }
 
         info!("Got screenshot");
+    }
 
-        // Clean up
-        {
-            if let Err(e) = unsafe { windows_capture_resources.destroy(&self.direct_x) } {
-                error!("Failed to destroy Windows Capture Resources: {e}");
+    /// Takes a capture of a monitor using the provider, reporting any errors.
+    fn take_provider_capture(
+        &mut self,
+        monitor: &P::Monitor,
+        sink: &impl LoadingEventSink<P>,
+    ) -> Option<HdrImage> {
+        // Captures fail while the display mode changes, so they are retried
+        let category = ErrorCategory::Capture;
+        let result = category.retry_policy().run(category, || unsafe {
+            self.provider.capture(self.vulkan, monitor)
+        });
+
+        let (capture, hdr_capture) = match result {
+            Ok(capture) => capture,
+            Err(e) => {
+                let message = e.user_message();
+                report_error(e.category(), e, message);
+                return None;
+            }
+        };
+
+        sink.send_loading_event(LoadingEvent::GotCapture(capture))
+            .report_and_panic("Eventloop exited");
+
+        Some(hdr_capture)
+    }
+
+    /// Takes a capture of a monitor and copies it to CPU memory, returns the size and pixels.
+    fn capture_to_cpu(
+        &mut self,
+        monitor: &P::Monitor,
+        sink: &impl LoadingEventSink<P>,
+    ) -> Option<([u32; 2], Vec<f16>)> {
+        let hdr_image = self.take_provider_capture(monitor, sink)?;
+        let size = [hdr_image.extent.width, hdr_image.extent.height];
+
+        let result = unsafe {
+            let pixels = hdr_image.copy_to_cpu(self.vulkan);
+            hdr_image.destroy(self.vulkan);
+            pixels
+        };
+
+        match result {
+            Ok(pixels) => Some((size, pixels)),
+            Err(e) => {
+                report_error(
+                    ErrorCategory::Import,
+                    e,
+                    "Could not take the screenshot.\nEncountered an error while copying the screenshot to CPU memory",
+                );
+                None
             }
         }
     }
 
-    fn cleanup_windows_capture(&self, capture: WindowsCapture) {
-        if capture.handle.0.is_invalid() {
-            return;
+    /// Finds the monitor under the centre of a region and crops the region to it, reporting any
+    /// errors with `failure` as the first line.
+    fn find_region_monitor(
+        &self,
+        area: DesktopRect,
+        failure: &str,
+    ) -> Option<(P::Monitor, DesktopRect)> {
+        let monitors = match self.provider.active_monitors() {
+            Ok(monitors) => monitors,
+            Err(e) => {
+                report_error(
+                    ErrorCategory::Capture,
+                    e,
+                    &format!(
+                        "{failure}.\nAn error was encountered while finding the active monitors"
+                    ),
+                );
+                return None;
+            }
+        };
+
+        let centre = area.centre();
+        let Some(monitor) = monitors
+            .into_iter()
+            .find(|monitor| monitor.info().rect.contains(centre))
+        else {
+            report_error(
+                ErrorCategory::Capture,
+                format!("No active monitor contains {centre:?}"),
+                &format!("{failure}.\nCould not find the monitor the region is on"),
+            );
+            return None;
+        };
+
+        debug!("Found {monitor:?} for {area:?}");
+
+        // A region spanning monitors is cropped to the monitor under its centre.
+        let rect = monitor.info().rect;
+        let Some(cropped) = area.intersection(rect) else {
+            report_error(
+                ErrorCategory::Capture,
+                format!("{area:?} is not on {rect:?}"),
+                &format!("{failure}.\nThe region is not on a monitor"),
+            );
+            return None;
+        };
+
+        Some((monitor, cropped))
+    }
+
+    /// The values of a monitor, or the monitor a window is on, with the configured overrides for
+    /// it applied, and how the whitepoint of its captures is chosen.
+    fn monitor_info(&self, monitor: &impl ProviderMonitor) -> (MonitorInfo, WhitepointStrategy) {
+        let identity = monitor.identity();
+        let (info, strategy) = monitor_override::apply_overrides(
+            &self.monitor_overrides,
+            &identity,
+            monitor.info(),
+            self.whitepoint_strategy,
+        );
+
+        if info != monitor.info() || strategy != self.whitepoint_strategy {
+            debug!("Overrode {identity:?}: {info:?}, {strategy:?}");
         }
 
-        unsafe {
-            if let Err(e) = CloseHandle(capture.handle.0) {
-                error!("Failed to close handle to Windows capture: {e}");
+        (info, strategy)
+    }
+
+    /// Scans the capture and sends the selected whitepoint, returns if the whitepoint was found.
+    fn find_whitepoint(
+        &mut self,
+        hdr_capture: HdrImage,
+        sdr_white: f32,
+        max_brightness: f32,
+        strategy: WhitepointStrategy,
+        sink: &impl LoadingEventSink<P>,
+    ) -> bool {
+        let maximum = match unsafe { self.hdr_scanner.scan(hdr_capture) } {
+            Ok(maximum) => maximum,
+            Err(e) => {
+                report_error(
+                    ErrorCategory::Scan,
+                    e,
+                    "Encountered an error while analysing the screenshot",
+                );
+                sink.send_loading_event(LoadingEvent::Error).ignore();
+                return false;
             }
+        };
+
+        debug!("Found maximum: {}", maximum);
+
+        let whitepoint = strategy.select(maximum, sdr_white, max_brightness);
+        match whitepoint {
+            Whitepoint::Sdr(value) => debug!("Selected SDR whitepoint: {value}"),
+            Whitepoint::Hdr(value) => debug!("Selected HDR whitepoint: {value}"),
         }
+
+        sink.send_loading_event(LoadingEvent::SelectedWhitepoint(whitepoint))
+            .report_and_panic("Eventloop exited");
+
+        true
+    }
+}
+
+impl CaptureTaker for BlockingCaptureTaker<'_, PlatformCaptureProvider> {
+    fn refresh_cache(&mut self) {
+        self.provider.refresh();
+    }
+
+    fn take_capture(&mut self, proxy: EventLoopProxy<Event>, target: CaptureTarget) {
+        self.capture_target(&proxy, target);
+    }
+
+    fn cleanup_capture(&self, capture: PlatformCapture) {
+        self.provider.cleanup(capture);
+    }
+
+    fn record(&mut self, proxy: EventLoopProxy<Event>, request: RecordingRequest) {
+        self.record_region(&request);
+        proxy
+            .send_event(ApplicationEvent::RecordingFinished.into())
+            .ignore();
+    }
+
+    fn burst(&mut self, proxy: EventLoopProxy<Event>, request: BurstRequest) {
+        self.burst_region(&request, |frame| {
+            proxy
+                .send_event(ApplicationEvent::BurstFrame(frame).into())
+                .is_ok()
+        });
+        proxy
+            .send_event(ApplicationEvent::BurstFinished.into())
+            .ignore();
+    }
+
+    fn set_whitepoint_strategy(&mut self, strategy: WhitepointStrategy) {
+        self.whitepoint_strategy = strategy;
+    }
+
+    fn set_monitor_overrides(&mut self, overrides: Vec<MonitorOverride>) {
+        self.monitor_overrides = overrides;
     }
 }
@@ -0,0 +1,178 @@
+use serde::{Deserialize, Serialize};
+
+use super::{MonitorIdentity, MonitorInfo, WhitepointStrategy};
+
+/// How many nits a luminance of `1.0` is, the unit monitors report their luminance in.
+const NITS_PER_UNIT: f32 = 80.0;
+
+/// Corrections for a monitor that reports inaccurate values, each value that is set replaces the
+/// monitor's own.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct MonitorOverride {
+    /// The monitor's device name, like `\\.\DISPLAY1`, or the identifier from its EDID, like
+    /// `DEL4123`.
+    pub monitor: String,
+
+    /// The monitor's maximum luminance in nits.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_brightness: Option<f32>,
+
+    /// The monitor's minimum luminance in nits.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min_brightness: Option<f32>,
+
+    /// The monitor's SDR white luminance in nits.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sdr_white: Option<f32>,
+
+    /// How the whitepoint of captures of the monitor is chosen.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub whitepoint: Option<WhitepointStrategy>,
+}
+
+impl MonitorOverride {
+    /// Returns if the override is for the monitor, names are compared ignoring case.
+    pub fn matches(&self, identity: &MonitorIdentity) -> bool {
+        let monitor = self.monitor.trim();
+
+        monitor.eq_ignore_ascii_case(&identity.device_name)
+            || identity
+                .edid_id
+                .as_ref()
+                .is_some_and(|edid_id| monitor.eq_ignore_ascii_case(edid_id))
+    }
+}
+
+/// Applies the overrides for a monitor to its values and the whitepoint strategy, in order so a
+/// later override replaces the values of an earlier one.
+pub fn apply_overrides(
+    overrides: &[MonitorOverride],
+    identity: &MonitorIdentity,
+    mut info: MonitorInfo,
+    mut strategy: WhitepointStrategy,
+) -> (MonitorInfo, WhitepointStrategy) {
+    for monitor_override in overrides.iter().filter(|value| value.matches(identity)) {
+        let from_nits = |nits: Option<f32>| nits.map(|nits| nits / NITS_PER_UNIT);
+
+        if let Some(max_brightness) = from_nits(monitor_override.max_brightness) {
+            info.max_brightness = max_brightness;
+        }
+        if let Some(min_brightness) = from_nits(monitor_override.min_brightness) {
+            info.min_brightness = min_brightness;
+        }
+        if let Some(sdr_white) = from_nits(monitor_override.sdr_white) {
+            info.sdr_white = sdr_white;
+        }
+        if let Some(whitepoint) = monitor_override.whitepoint {
+            strategy = whitepoint;
+        }
+    }
+
+    (info, strategy)
+}
+
+#[cfg(test)]
+mod test {
+    use crate::capture_taker::{DesktopRect, MonitorIdentity, MonitorInfo, WhitepointStrategy};
+
+    use super::{MonitorOverride, apply_overrides};
+
+    const MONITOR: MonitorInfo = MonitorInfo {
+        rect: DesktopRect {
+            left: 0,
+            top: 0,
+            right: 1920,
+            bottom: 1080,
+        },
+        sdr_white: 2.5,
+        min_brightness: 0.0,
+        max_brightness: 18.7375,
+    };
+
+    fn identity(device_name: &str, edid_id: Option<&str>) -> MonitorIdentity {
+        MonitorIdentity {
+            device_name: device_name.to_string(),
+            edid_id: edid_id.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn matches_device_name_or_edid() {
+        let monitor_override = MonitorOverride {
+            monitor: String::from("del4123"),
+            ..MonitorOverride::default()
+        };
+
+        assert!(monitor_override.matches(&identity(r"\\.\DISPLAY1", Some("DEL4123"))));
+        assert!(!monitor_override.matches(&identity(r"\\.\DISPLAY1", None)));
+
+        let monitor_override = MonitorOverride {
+            monitor: String::from(r"\\.\DISPLAY2"),
+            ..MonitorOverride::default()
+        };
+
+        assert!(monitor_override.matches(&identity(r"\\.\display2", Some("DEL4123"))));
+        assert!(!monitor_override.matches(&identity(r"\\.\DISPLAY1", Some("DEL4123"))));
+    }
+
+    #[test]
+    fn overrides_values_in_nits() {
+        let overrides = [
+            MonitorOverride {
+                monitor: String::from("DEL4123"),
+                max_brightness: Some(400.0),
+                sdr_white: Some(160.0),
+                ..MonitorOverride::default()
+            },
+            MonitorOverride {
+                monitor: String::from(r"\\.\DISPLAY1"),
+                max_brightness: Some(600.0),
+                whitepoint: Some(WhitepointStrategy::Sdr),
+                ..MonitorOverride::default()
+            },
+            MonitorOverride {
+                monitor: String::from(r"\\.\DISPLAY2"),
+                min_brightness: Some(80.0),
+                ..MonitorOverride::default()
+            },
+        ];
+
+        let (info, strategy) = apply_overrides(
+            &overrides,
+            &identity(r"\\.\DISPLAY1", Some("DEL4123")),
+            MONITOR,
+            WhitepointStrategy::Automatic,
+        );
+
+        // The later override replaces the maximum brightness of the earlier one.
+        assert_eq!(
+            info,
+            MonitorInfo {
+                sdr_white: 2.0,
+                max_brightness: 7.5,
+                ..MONITOR
+            }
+        );
+        assert_eq!(strategy, WhitepointStrategy::Sdr);
+    }
+
+    #[test]
+    fn unmatched_monitor_is_unchanged() {
+        let overrides = [MonitorOverride {
+            monitor: String::from("DEL4123"),
+            max_brightness: Some(400.0),
+            whitepoint: Some(WhitepointStrategy::Hdr),
+            ..MonitorOverride::default()
+        }];
+
+        let (info, strategy) = apply_overrides(
+            &overrides,
+            &identity("DP-1", None),
+            MONITOR,
+            WhitepointStrategy::Automatic,
+        );
+
+        assert_eq!(info, MONITOR);
+        assert_eq!(strategy, WhitepointStrategy::Automatic);
+    }
+}
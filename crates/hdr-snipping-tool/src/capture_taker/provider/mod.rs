@@ -0,0 +1,185 @@
+use core::time::Duration;
+
+use ash_helper::AllocationError;
+use thiserror::Error;
+use vulkan::{HdrImage, Vulkan};
+
+pub use synthetic::{SyntheticCapture, SyntheticCaptureProvider, SyntheticError, SyntheticMonitor};
+pub use windows_provider::WindowsCaptureProvider;
+
+use crate::error_reporting::ErrorCategory;
+
+use super::DesktopRect;
+
+mod synthetic;
+mod windows_provider;
+
+/// The capture provider used by the application.
+pub type PlatformCaptureProvider = WindowsCaptureProvider;
+
+/// The monitor type of the capture provider used by the application.
+pub type PlatformMonitor = <PlatformCaptureProvider as CaptureProvider>::Monitor;
+
+/// The capture type of the capture provider used by the application.
+pub type PlatformCapture = <PlatformCaptureProvider as CaptureProvider>::Capture;
+
+/// A frame of a recording and its imported image, `None` if the monitor did not change.
+pub type RecordedFrame<C> = Option<(C, HdrImage)>;
+
+/// A top-level window to capture.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum WindowTarget {
+    /// The window with a platform handle, e.g. the window that had focus before the screenshot.
+    Handle(isize),
+
+    /// The top-most window at a point in desktop coordinates.
+    At([i32; 2]),
+}
+
+/// The properties of a monitor needed to composite and tonemap its captures.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct MonitorInfo {
+    /// The monitor's desktop coordinates.
+    pub rect: DesktopRect,
+
+    /// The monitor's SDR white luminance.
+    pub sdr_white: f32,
+
+    /// The monitor's minimum luminance.
+    pub min_brightness: f32,
+
+    /// The monitor's maximum luminance.
+    pub max_brightness: f32,
+}
+
+/// The names a monitor can be picked out by in the config.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct MonitorIdentity {
+    /// The platform's name for the output, like `\\.\DISPLAY1` on Windows or `DP-1` on X11.
+    pub device_name: String,
+
+    /// The manufacturer and product code from the monitor's EDID, like `DEL4123`, if known.
+    pub edid_id: Option<String>,
+}
+
+/// A monitor served by a capture provider, windows describe their bounds and the monitor they are
+/// on.
+pub trait ProviderMonitor: Clone + core::fmt::Debug + Send {
+    fn info(&self) -> MonitorInfo;
+
+    /// The monitor, or the monitor a window is on.
+    fn identity(&self) -> MonitorIdentity;
+}
+
+/// A source of monitors and HDR captures of them.
+pub trait CaptureProvider {
+    type Monitor: ProviderMonitor;
+
+    type Window: ProviderMonitor;
+
+    /// A capture that is held by the application until it is cleaned up.
+    type Capture: Send;
+
+    /// A running recording of a monitor.
+    type Recording;
+
+    type Error: core::fmt::Display;
+
+    /// Refreshes any cached resources, called periodically in the background.
+    fn refresh(&mut self);
+
+    /// Ensures the provider is ready to take a capture.
+    fn prepare(&mut self);
+
+    /// Returns the monitor under the mouse, `None` if the mouse is not on an active monitor.
+    fn hovered_monitor(&self) -> Result<Option<Self::Monitor>, Self::Error>;
+
+    /// Returns every active monitor.
+    fn active_monitors(&self) -> Result<Vec<Self::Monitor>, Self::Error>;
+
+    /// Takes a capture of a monitor and imports it into Vulkan.
+    ///
+    /// # Safety
+    /// The returned image must be destroyed using the same Vulkan instance.
+    unsafe fn capture(
+        &mut self,
+        vulkan: &Vulkan,
+        monitor: &Self::Monitor,
+    ) -> Result<(Self::Capture, HdrImage), CaptureError<Self::Error>>;
+
+    /// Returns the window to capture, `None` if there is no window that can be captured.
+    fn find_window(&self, target: WindowTarget) -> Result<Option<Self::Window>, Self::Error>;
+
+    /// Takes a capture of a window, without any windows that cover it, and imports it into
+    /// Vulkan.
+    ///
+    /// # Safety
+    /// The returned image must be destroyed using the same Vulkan instance.
+    unsafe fn capture_window(
+        &mut self,
+        vulkan: &Vulkan,
+        window: &Self::Window,
+    ) -> Result<(Self::Capture, HdrImage), CaptureError<Self::Error>>;
+
+    /// Releases a capture once the application is finished with it.
+    fn cleanup(&self, capture: Self::Capture);
+
+    /// Starts recording successive frames of a monitor.
+    fn start_recording(
+        &mut self,
+        monitor: &Self::Monitor,
+    ) -> Result<Self::Recording, CaptureError<Self::Error>>;
+
+    /// Waits up to `timeout` for the next frame of a recording and imports it into Vulkan, `None`
+    /// if the monitor did not change.
+    ///
+    /// # Safety
+    /// The returned image must be destroyed using the same Vulkan instance before the next frame
+    /// is requested.
+    unsafe fn record_frame(
+        &mut self,
+        vulkan: &Vulkan,
+        recording: &mut Self::Recording,
+        timeout: Duration,
+    ) -> Result<RecordedFrame<Self::Capture>, CaptureError<Self::Error>>;
+
+    /// Stops a recording and releases its resources.
+    fn stop_recording(&mut self, recording: Self::Recording);
+}
+
+#[derive(Debug, Error)]
+pub enum CaptureError<E> {
+    #[error("Could not create the capture resources:\n{0}")]
+    Resources(E),
+
+    #[error("Could not take the capture:\n{0}")]
+    Capture(E),
+
+    #[error("Could not import the capture:\n{0}")]
+    Import(#[from] AllocationError),
+}
+
+impl<E> CaptureError<E> {
+    /// The category the failure is reported as.
+    pub fn category(&self) -> ErrorCategory {
+        match self {
+            Self::Resources(_) | Self::Capture(_) => ErrorCategory::Capture,
+            Self::Import(_) => ErrorCategory::Import,
+        }
+    }
+
+    /// The message shown to the user if taking the capture failed.
+    pub fn user_message(&self) -> &'static str {
+        match self {
+            Self::Resources(_) => {
+                "Could not take the screenshot.\nEncountered an error while creating the required resources"
+            }
+            Self::Capture(_) => {
+                "Could not take the screenshot.\nEncountered an error while taking the screenshot"
+            }
+            Self::Import(_) => {
+                "Could not take the screenshot.\nEncountered an error while importing the screenshot to the application"
+            }
+        }
+    }
+}
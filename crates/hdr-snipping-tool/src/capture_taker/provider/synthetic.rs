@@ -0,0 +1,390 @@
+use core::time::Duration;
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use half::f16;
+use serde::Deserialize;
+use thiserror::Error;
+use tracing::debug;
+use vulkan::{HdrImage, Vulkan};
+
+use crate::capture_taker::DesktopRect;
+
+use super::{
+    CaptureError, CaptureProvider, MonitorIdentity, MonitorInfo, ProviderMonitor, RecordedFrame,
+    WindowTarget,
+};
+
+/// A scripted monitor, each capture serves the next of its frames.
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+pub struct SyntheticMonitor {
+    pub name: String,
+    pub rect: DesktopRect,
+    pub sdr_white: f32,
+    pub max_brightness: f32,
+
+    /// The frame files, relative to the script, looping once the last frame has been served.
+    ///
+    /// Frames are row-major little-endian `RGBA` `f16` pixels with no padding.
+    pub frames: Vec<PathBuf>,
+}
+
+impl ProviderMonitor for SyntheticMonitor {
+    fn info(&self) -> MonitorInfo {
+        MonitorInfo {
+            rect: self.rect,
+            sdr_white: self.sdr_white,
+            min_brightness: 0.0,
+            max_brightness: self.max_brightness,
+        }
+    }
+
+    fn identity(&self) -> MonitorIdentity {
+        MonitorIdentity {
+            device_name: self.name.clone(),
+            edid_id: None,
+        }
+    }
+}
+
+/// A capture served by the synthetic provider.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SyntheticCapture {
+    pub monitor: String,
+    pub frame: PathBuf,
+}
+
+#[derive(Debug, Deserialize)]
+struct Script {
+    /// The index of the monitor under the mouse.
+    hovered: Option<usize>,
+
+    monitors: Vec<SyntheticMonitor>,
+}
+
+/// Serves scripted monitors and HDR frames from disk, for taking captures without a display.
+///
+/// The script is a TOML file:
+/// ```toml
+/// hovered = 0
+///
+/// [[monitors]]
+/// name = "Primary"
+/// rect = { left = 0, top = 0, right = 1920, bottom = 1080 }
+/// sdr_white = 2.5
+/// max_brightness = 12.5
+/// frames = ["primary.f16"]
+/// ```
+pub struct SyntheticCaptureProvider {
+    directory: PathBuf,
+    hovered: Option<usize>,
+    monitors: Vec<SyntheticMonitor>,
+
+    /// The index of the next frame for each monitor.
+    next_frames: Vec<usize>,
+}
+
+impl SyntheticCaptureProvider {
+    /// Creates a provider serving monitors whose frames are relative to `directory`.
+    pub fn new(
+        directory: PathBuf,
+        monitors: Vec<SyntheticMonitor>,
+        hovered: Option<usize>,
+    ) -> Self {
+        Self {
+            directory,
+            hovered,
+            next_frames: vec![0; monitors.len()],
+            monitors,
+        }
+    }
+
+    /// Loads a provider from a script file.
+    pub fn from_script(path: &Path) -> Result<Self, SyntheticError> {
+        let script = fs::read_to_string(path).map_err(|e| SyntheticError::Io(path.into(), e))?;
+        let script: Script = toml::from_str(&script)?;
+
+        if let Some(hovered) = script
+            .hovered
+            .filter(|index| *index >= script.monitors.len())
+        {
+            return Err(SyntheticError::UnknownMonitor(hovered));
+        }
+
+        let directory = path.parent().map(Path::to_path_buf).unwrap_or_default();
+
+        Ok(Self::new(directory, script.monitors, script.hovered))
+    }
+
+    /// Reads a frame file for a monitor.
+    fn read_frame(
+        &self,
+        monitor: &SyntheticMonitor,
+        frame: &Path,
+    ) -> Result<Vec<f16>, SyntheticError> {
+        let path = self.directory.join(frame);
+        let bytes = fs::read(&path).map_err(|e| SyntheticError::Io(path.clone(), e))?;
+
+        let size = monitor.rect.size();
+        let expected = size[0] as usize * size[1] as usize * 4 * 2;
+        if bytes.len() != expected {
+            return Err(SyntheticError::FrameSize {
+                path,
+                expected,
+                found: bytes.len(),
+            });
+        }
+
+        let pixels = bytes
+            .chunks_exact(2)
+            .map(|bytes| f16::from_le_bytes([bytes[0], bytes[1]]))
+            .collect();
+
+        Ok(pixels)
+    }
+}
+
+impl CaptureProvider for SyntheticCaptureProvider {
+    type Monitor = SyntheticMonitor;
+    type Window = SyntheticMonitor;
+    type Capture = SyntheticCapture;
+    type Recording = SyntheticMonitor;
+    type Error = SyntheticError;
+
+    fn refresh(&mut self) {}
+
+    fn prepare(&mut self) {}
+
+    fn hovered_monitor(&self) -> Result<Option<Self::Monitor>, Self::Error> {
+        Ok(self
+            .hovered
+            .and_then(|index| self.monitors.get(index))
+            .cloned())
+    }
+
+    fn active_monitors(&self) -> Result<Vec<Self::Monitor>, Self::Error> {
+        Ok(self.monitors.clone())
+    }
+
+    unsafe fn capture(
+        &mut self,
+        vulkan: &Vulkan,
+        monitor: &Self::Monitor,
+    ) -> Result<(Self::Capture, HdrImage), CaptureError<Self::Error>> {
+        let index = self
+            .monitors
+            .iter()
+            .position(|candidate| candidate.name == monitor.name)
+            .ok_or_else(|| {
+                CaptureError::Resources(SyntheticError::MissingMonitor(monitor.name.clone()))
+            })?;
+
+        let frame = {
+            let frames = &self.monitors[index].frames;
+            if frames.is_empty() {
+                return Err(CaptureError::Resources(SyntheticError::NoFrames(
+                    monitor.name.clone(),
+                )));
+            }
+
+            let frame = frames[self.next_frames[index] % frames.len()].clone();
+            self.next_frames[index] += 1;
+            frame
+        };
+
+        let pixels = self
+            .read_frame(&self.monitors[index], &frame)
+            .map_err(CaptureError::Capture)?;
+
+        let hdr_image = unsafe { HdrImage::from_cpu(vulkan, monitor.rect.size(), &pixels)? };
+
+        let capture = SyntheticCapture {
+            monitor: monitor.name.clone(),
+            frame,
+        };
+
+        Ok((capture, hdr_image))
+    }
+
+    /// Synthetic scripts describe monitors but not windows.
+    fn find_window(&self, _target: WindowTarget) -> Result<Option<Self::Window>, Self::Error> {
+        Ok(None)
+    }
+
+    unsafe fn capture_window(
+        &mut self,
+        vulkan: &Vulkan,
+        window: &Self::Window,
+    ) -> Result<(Self::Capture, HdrImage), CaptureError<Self::Error>> {
+        unsafe { self.capture(vulkan, window) }
+    }
+
+    fn cleanup(&self, capture: Self::Capture) {
+        debug!("Cleaned up synthetic capture {capture:?}");
+    }
+
+    fn start_recording(
+        &mut self,
+        monitor: &Self::Monitor,
+    ) -> Result<Self::Recording, CaptureError<Self::Error>> {
+        Ok(monitor.clone())
+    }
+
+    /// Every frame of a recording is the next frame of the monitor, without waiting.
+    unsafe fn record_frame(
+        &mut self,
+        vulkan: &Vulkan,
+        recording: &mut Self::Recording,
+        _timeout: Duration,
+    ) -> Result<RecordedFrame<Self::Capture>, CaptureError<Self::Error>> {
+        unsafe { self.capture(vulkan, recording) }.map(Some)
+    }
+
+    fn stop_recording(&mut self, recording: Self::Recording) {
+        debug!("Stopped recording synthetic monitor {}", recording.name);
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum SyntheticError {
+    #[error("Could not read {0:?}:\n{1}")]
+    Io(PathBuf, #[source] io::Error),
+
+    #[error("Could not parse the script:\n{0}")]
+    Script(#[from] toml::de::Error),
+
+    #[error("The hovered monitor {0} is not in the script")]
+    UnknownMonitor(usize),
+
+    #[error("The monitor {0:?} is not in the script")]
+    MissingMonitor(String),
+
+    #[error("The monitor {0:?} has no frames")]
+    NoFrames(String),
+
+    #[error("The frame {path:?} is {found} bytes but the monitor needs {expected} bytes")]
+    FrameSize {
+        path: PathBuf,
+        expected: usize,
+        found: usize,
+    },
+}
+
+#[cfg(test)]
+mod test {
+    use std::{fs, path::PathBuf};
+
+    use half::f16;
+
+    use crate::capture_taker::{CaptureProvider, DesktopRect};
+
+    use super::{SyntheticCaptureProvider, SyntheticError};
+
+    /// Creates an empty directory for the test's fixtures.
+    fn fixture_directory(name: &str) -> PathBuf {
+        let directory = std::env::temp_dir()
+            .join("hdr-snipping-tool-tests")
+            .join(format!("{name}-{}", std::process::id()));
+
+        if directory.exists() {
+            fs::remove_dir_all(&directory).unwrap();
+        }
+        fs::create_dir_all(&directory).unwrap();
+
+        directory
+    }
+
+    const SCRIPT: &str = r#"
+hovered = 1
+
+[[monitors]]
+name = "Left"
+rect = { left = -4, top = 0, right = 0, bottom = 2 }
+sdr_white = 2.5
+max_brightness = 12.5
+frames = ["left.f16"]
+
+[[monitors]]
+name = "Right"
+rect = { left = 0, top = 0, right = 2, bottom = 2 }
+sdr_white = 1.0
+max_brightness = 6.0
+frames = ["right-0.f16", "right-1.f16"]
+"#;
+
+    #[test]
+    fn loads_script() {
+        let directory = fixture_directory("loads-script");
+        fs::write(directory.join("monitors.toml"), SCRIPT).unwrap();
+
+        let provider =
+            SyntheticCaptureProvider::from_script(&directory.join("monitors.toml")).unwrap();
+
+        let hovered = provider.hovered_monitor().unwrap().unwrap();
+        assert_eq!(hovered.name, "Right");
+        assert_eq!(hovered.max_brightness, 6.0);
+
+        let monitors = provider.active_monitors().unwrap();
+        assert_eq!(monitors.len(), 2);
+        assert_eq!(
+            monitors[0].rect,
+            DesktopRect {
+                left: -4,
+                top: 0,
+                right: 0,
+                bottom: 2
+            }
+        );
+    }
+
+    #[test]
+    fn reads_frames() {
+        let directory = fixture_directory("reads-frames");
+        fs::write(directory.join("monitors.toml"), SCRIPT).unwrap();
+
+        let pixels: Vec<_> = (0..4 * 2 * 4)
+            .map(|index| f16::from_f32(index as f32))
+            .collect();
+        let bytes: Vec<_> = pixels
+            .iter()
+            .flat_map(|pixel| pixel.to_le_bytes())
+            .collect();
+        fs::write(directory.join("left.f16"), &bytes).unwrap();
+
+        // Too short for the monitor
+        fs::write(directory.join("right-0.f16"), &bytes[..8]).unwrap();
+
+        let provider =
+            SyntheticCaptureProvider::from_script(&directory.join("monitors.toml")).unwrap();
+        let monitors = provider.active_monitors().unwrap();
+
+        let frame = provider
+            .read_frame(&monitors[0], monitors[0].frames[0].as_path())
+            .unwrap();
+        assert_eq!(frame, pixels);
+
+        let error = provider
+            .read_frame(&monitors[1], monitors[1].frames[0].as_path())
+            .unwrap_err();
+        assert!(matches!(
+            error,
+            SyntheticError::FrameSize {
+                expected: 32,
+                found: 8,
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn rejects_unknown_hovered_monitor() {
+        let directory = fixture_directory("unknown-hovered");
+        let script = SCRIPT.replace("hovered = 1", "hovered = 2");
+        fs::write(directory.join("monitors.toml"), script).unwrap();
+
+        let result = SyntheticCaptureProvider::from_script(&directory.join("monitors.toml"));
+        assert!(matches!(result, Err(SyntheticError::UnknownMonitor(2))));
+    }
+}
@@ -0,0 +1,256 @@
+use core::{ffi::c_void, time::Duration};
+
+use tracing::{debug, error};
+use utilities::DebugTime;
+use vulkan::{HdrImage, Vulkan};
+use windows::Win32::Foundation::{CloseHandle, HWND, POINT};
+use windows_capture_provider::{
+    CaptureItemCache, DirectX, Monitor, WinError, Window, WindowsCapture, WindowsCaptureResources,
+    WindowsRecording,
+};
+
+use crate::utilities::failure::{Failure, report_and_panic};
+
+use super::{
+    CaptureError, CaptureProvider, MonitorIdentity, MonitorInfo, ProviderMonitor, RecordedFrame,
+    WindowTarget,
+};
+
+impl ProviderMonitor for Monitor {
+    fn info(&self) -> MonitorInfo {
+        MonitorInfo {
+            rect: self.desktop_coordinates.into(),
+            sdr_white: self.sdr_white,
+            min_brightness: self.min_brightness,
+            max_brightness: self.max_brightness,
+        }
+    }
+
+    fn identity(&self) -> MonitorIdentity {
+        MonitorIdentity {
+            device_name: self.name(),
+            edid_id: self.edid_id.map(|edid_id| edid_id.to_string()),
+        }
+    }
+}
+
+impl ProviderMonitor for Window {
+    fn info(&self) -> MonitorInfo {
+        MonitorInfo {
+            rect: self.desktop_coordinates.into(),
+            sdr_white: self.monitor.sdr_white,
+            min_brightness: self.monitor.min_brightness,
+            max_brightness: self.monitor.max_brightness,
+        }
+    }
+
+    fn identity(&self) -> MonitorIdentity {
+        self.monitor.identity()
+    }
+}
+
+/// Takes captures using the Windows Graphics Capture API.
+pub struct WindowsCaptureProvider {
+    direct_x: DirectX,
+    cache: CaptureItemCache,
+}
+
+impl WindowsCaptureProvider {
+    pub fn new() -> Self {
+        let direct_x = DirectX::new().report_and_panic("Could not create DirectX devices");
+        let cache = CaptureItemCache::new();
+
+        Self { direct_x, cache }
+    }
+
+    /// Panics if the DirectX devices were lost, recreates them if they are out of date.
+    fn ensure_devices(&mut self) {
+        if !self.direct_x.devices_valid() {
+            report_and_panic(
+                "DirectX device lost",
+                "Could not refresh the cache.\nThe DirectX device was lost.",
+            );
+        }
+
+        if self
+            .direct_x
+            .dxgi_adapter_outdated()
+            .inspect_err(|e| error!("Could not check if the DirectX devices were outdated: {e}"))
+            .unwrap_or(true)
+        {
+            self.direct_x.recreate_dxgi_adapter().report_and_panic(
+                "Could not refresh the DirectX devices.\nThe DirectX device creation failed.",
+            );
+            self.cache.purge();
+            debug!("Recreated out-of-date DXGI device, purged cache");
+        }
+    }
+
+    /// Imports a capture into Vulkan and destroys the resources used to take it.
+    unsafe fn import(
+        &self,
+        vulkan: &Vulkan,
+        capture: WindowsCapture,
+        resources: WindowsCaptureResources,
+    ) -> Result<(WindowsCapture, HdrImage), CaptureError<WinError>> {
+        let hdr_image = unsafe {
+            HdrImage::import_windows_capture(vulkan, capture.size, capture.handle.0.0 as isize)
+        };
+
+        if let Err(e) = unsafe { resources.destroy(&self.direct_x) } {
+            error!("Failed to destroy Windows Capture Resources: {e}");
+        }
+
+        match hdr_image {
+            Ok(hdr_image) => Ok((capture, hdr_image)),
+            Err(e) => {
+                self.cleanup(capture);
+                Err(CaptureError::Import(e))
+            }
+        }
+    }
+}
+
+impl CaptureProvider for WindowsCaptureProvider {
+    type Monitor = Monitor;
+    type Window = Window;
+    type Capture = WindowsCapture;
+    type Recording = WindowsRecording;
+    type Error = WinError;
+
+    fn refresh(&mut self) {
+        self.ensure_devices();
+
+        if let Err(e) = self.cache.prune(&self.direct_x) {
+            error!("Could not prune the cache: {e}");
+        };
+
+        if let Err(e) = self.cache.cache_active(&self.direct_x) {
+            error!("Could not cache the active monitors: {e}");
+        };
+    }
+
+    fn prepare(&mut self) {
+        self.ensure_devices();
+    }
+
+    fn hovered_monitor(&self) -> Result<Option<Self::Monitor>, Self::Error> {
+        Monitor::get_hovered_monitor(&self.direct_x)
+    }
+
+    fn active_monitors(&self) -> Result<Vec<Self::Monitor>, Self::Error> {
+        Monitor::get_active_monitors(&self.direct_x)
+    }
+
+    unsafe fn capture(
+        &mut self,
+        vulkan: &Vulkan,
+        monitor: &Self::Monitor,
+    ) -> Result<(Self::Capture, HdrImage), CaptureError<Self::Error>> {
+        let capture_item = {
+            let _timer = DebugTime::start("Getting capture item");
+
+            self.cache
+                .get_capture_item(monitor.handle.0)
+                .map_err(CaptureError::Resources)?
+        };
+
+        let (capture, resources) = {
+            let _timer = DebugTime::start("Taking capture");
+
+            WindowsCapture::take_capture(&self.direct_x, &capture_item)
+                .map_err(CaptureError::Capture)?
+        };
+
+        unsafe { self.import(vulkan, capture, resources) }
+    }
+
+    fn find_window(&self, target: WindowTarget) -> Result<Option<Self::Window>, Self::Error> {
+        match target {
+            WindowTarget::Handle(handle) => {
+                Window::from_handle(&self.direct_x, HWND(handle as *mut c_void))
+            }
+            WindowTarget::At([x, y]) => Window::get_window_at(&self.direct_x, POINT { x, y }),
+        }
+    }
+
+    unsafe fn capture_window(
+        &mut self,
+        vulkan: &Vulkan,
+        window: &Self::Window,
+    ) -> Result<(Self::Capture, HdrImage), CaptureError<Self::Error>> {
+        let capture_item = {
+            let _timer = DebugTime::start("Getting window capture item");
+
+            self.cache
+                .get_window_capture_item(window.handle.0)
+                .map_err(CaptureError::Resources)?
+        };
+
+        let (capture, resources) = {
+            let _timer = DebugTime::start("Taking window capture");
+
+            WindowsCapture::take_window_capture(&self.direct_x, &capture_item, window)
+                .map_err(CaptureError::Capture)?
+        };
+
+        unsafe { self.import(vulkan, capture, resources) }
+    }
+
+    fn cleanup(&self, capture: Self::Capture) {
+        if capture.handle.0.is_invalid() {
+            return;
+        }
+
+        unsafe {
+            if let Err(e) = CloseHandle(capture.handle.0) {
+                error!("Failed to close handle to Windows capture: {e}");
+            }
+        }
+    }
+
+    fn start_recording(
+        &mut self,
+        monitor: &Self::Monitor,
+    ) -> Result<Self::Recording, CaptureError<Self::Error>> {
+        let capture_item = self
+            .cache
+            .get_capture_item(monitor.handle.0)
+            .map_err(CaptureError::Resources)?;
+
+        WindowsRecording::start(&self.direct_x, &capture_item).map_err(CaptureError::Resources)
+    }
+
+    unsafe fn record_frame(
+        &mut self,
+        vulkan: &Vulkan,
+        recording: &mut Self::Recording,
+        timeout: Duration,
+    ) -> Result<RecordedFrame<Self::Capture>, CaptureError<Self::Error>> {
+        let Some(capture) = recording
+            .next_frame(timeout)
+            .map_err(CaptureError::Capture)?
+        else {
+            return Ok(None);
+        };
+
+        // The frame stays open in the recording, so there are no resources to destroy.
+        let hdr_image = unsafe {
+            HdrImage::import_windows_capture(vulkan, capture.size, capture.handle.0.0 as isize)
+        };
+
+        match hdr_image {
+            Ok(hdr_image) => Ok(Some((capture, hdr_image))),
+            Err(e) => {
+                self.cleanup(capture);
+                Err(CaptureError::Import(e))
+            }
+        }
+    }
+
+    fn stop_recording(&mut self, recording: Self::Recording) {
+        if let Err(e) = unsafe { recording.stop(&self.direct_x) } {
+            error!("Failed to stop Windows recording: {e}");
+        }
+    }
+}
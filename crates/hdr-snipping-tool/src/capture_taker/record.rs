@@ -0,0 +1,187 @@
+use std::{thread, time::Instant};
+
+use ash_helper::VkError;
+use thiserror::Error;
+use tracing::info;
+use utilities::DebugTime;
+use vulkan::{HdrImage, HdrScanner, HdrToSdrTonemapper, SdrImageError, TonemapperError, Vulkan};
+
+use crate::{
+    error_reporting::{ErrorCategory, report_error},
+    recording::{self, FrameSequence, FrameTonemapper, Recorder, RecordingRequest},
+    screenshot_dir,
+};
+
+use super::{BlockingCaptureTaker, CaptureProvider, DesktopRect, MonitorInfo, WhitepointStrategy};
+
+/// Tonemaps the recorded region of each frame on the GPU.
+struct VulkanFrameTonemapper<'taker, 'vulkan> {
+    vulkan: &'vulkan Vulkan,
+    hdr_scanner: &'taker mut HdrScanner<'vulkan>,
+    tonemapper: &'taker HdrToSdrTonemapper<'vulkan>,
+
+    /// The recorded region in capture pixels.
+    position: [usize; 2],
+    size: [usize; 2],
+}
+
+impl FrameTonemapper for VulkanFrameTonemapper<'_, '_> {
+    type Frame = HdrImage;
+    type Error = FrameError;
+
+    fn maximum(&mut self, frame: &Self::Frame) -> Result<f32, Self::Error> {
+        unsafe { self.hdr_scanner.scan(*frame) }.map_err(FrameError::Scan)
+    }
+
+    fn tonemap(&mut self, frame: &Self::Frame, whitepoint: f32) -> Result<Vec<u8>, Self::Error> {
+        let sdr_image = unsafe { self.tonemapper.tonemap(*frame, whitepoint)? };
+
+        let pixels = unsafe { sdr_image.copy_to_cpu(self.vulkan, self.position, self.size) };
+        unsafe { sdr_image.destroy(self.vulkan) };
+
+        Ok(pixels?)
+    }
+}
+
+impl<P: CaptureProvider> BlockingCaptureTaker<'_, P> {
+    /// Records a region of the monitor under its centre until the recording is stopped or runs
+    /// out of time, then saves it to the screenshot directory.
+    pub fn record_region(&mut self, request: &RecordingRequest) {
+        self.provider.prepare();
+
+        let Some((monitor, area)) =
+            self.find_region_monitor(request.area, "Could not record the region")
+        else {
+            return;
+        };
+        let (info, strategy) = self.monitor_info(&monitor);
+
+        let mut recording = match self.provider.start_recording(&monitor) {
+            Ok(recording) => recording,
+            Err(e) => {
+                report_error(
+                    e.category(),
+                    e,
+                    "Could not record the region.\nEncountered an error while starting the recording",
+                );
+                return;
+            }
+        };
+
+        let sequence = self.record_frames(&mut recording, request, info, strategy, area);
+        self.provider.stop_recording(recording);
+
+        // Save to file
+        {
+            let _timing = DebugTime::start("Saving recording to file");
+            let format = request.settings.format;
+            let name = format!("{}.{}", request.file_name, format.extension());
+            let path = screenshot_dir().join(name);
+
+            match recording::save(&sequence, format, &path) {
+                Ok(_) => info!("Saved {} frame recording to file", sequence.frames.len()),
+                Err(e) => report_error(ErrorCategory::Save, e, "Could not save the recording file"),
+            }
+        }
+    }
+
+    /// Records frames at the configured frame rate until the recording is stopped, runs out of
+    /// time, or fills its memory budget.
+    fn record_frames(
+        &mut self,
+        recording: &mut P::Recording,
+        request: &RecordingRequest,
+        info: MonitorInfo,
+        strategy: WhitepointStrategy,
+        area: DesktopRect,
+    ) -> FrameSequence {
+        let Self {
+            provider,
+            vulkan,
+            hdr_scanner,
+            tonemapper,
+            ..
+        } = self;
+        let vulkan: &Vulkan = vulkan;
+
+        let tonemapper = VulkanFrameTonemapper {
+            vulkan,
+            hdr_scanner,
+            tonemapper,
+            position: [
+                (area.left - info.rect.left) as usize,
+                (area.top - info.rect.top) as usize,
+            ],
+            size: area.size().map(|axis| axis as usize),
+        };
+        let mut recorder =
+            Recorder::new(tonemapper, info, area.size()).with_whitepoint_strategy(strategy);
+
+        let interval = request.settings.frame_interval();
+        let max_duration = request.settings.max_duration();
+        let start = Instant::now();
+        let mut next_frame = start;
+
+        info!("Started recording");
+        while !request.handle.is_stopped() && start.elapsed() < max_duration {
+            if recorder.is_full() {
+                info!("Stopped recording, the recording is too large to continue");
+                break;
+            }
+
+            next_frame += interval;
+
+            let timeout = next_frame.saturating_duration_since(Instant::now());
+            match unsafe { provider.record_frame(vulkan, recording, timeout) } {
+                Ok(Some((capture, hdr_frame))) => {
+                    let result = recorder.add_frame(&hdr_frame, start.elapsed());
+
+                    unsafe { hdr_frame.destroy(vulkan) };
+                    provider.cleanup(capture);
+
+                    if let Err(e) = result {
+                        report_error(
+                            ErrorCategory::Tonemap,
+                            e,
+                            "Could not finish the recording.\nEncountered an error while tonemapping a frame",
+                        );
+                        break;
+                    }
+                }
+
+                // The monitor did not change, the previous frame is shown for longer.
+                Ok(None) => {}
+
+                Err(e) => {
+                    report_error(
+                        e.category(),
+                        e,
+                        "Could not finish the recording.\nEncountered an error while capturing a frame",
+                    );
+                    break;
+                }
+            }
+
+            // Providers that do not wait for changes return immediately.
+            thread::sleep(next_frame.saturating_duration_since(Instant::now()));
+        }
+
+        let end = start.elapsed().min(max_duration);
+        info!("Stopped recording after {:.1}s", end.as_secs_f32());
+
+        recorder.finish(end)
+    }
+}
+
+/// An error while tonemapping a recorded frame.
+#[derive(Debug, Error)]
+pub enum FrameError {
+    #[error("Could not scan the frame:\n{0}")]
+    Scan(#[source] VkError),
+
+    #[error("Could not tonemap the frame:\n{0}")]
+    Tonemap(#[from] TonemapperError),
+
+    #[error("Could not copy the frame to CPU memory:\n{0}")]
+    Copy(#[from] SdrImageError),
+}
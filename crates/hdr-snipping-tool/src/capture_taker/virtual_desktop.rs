@@ -0,0 +1,341 @@
+use half::f16;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use windows::Win32::Foundation::RECT;
+
+/// A rectangle in desktop coordinates, relative to the top-left point of the primary monitor.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DesktopRect {
+    pub left: i32,
+    pub top: i32,
+    pub right: i32,
+    pub bottom: i32,
+}
+
+impl DesktopRect {
+    pub fn width(&self) -> u32 {
+        self.left.abs_diff(self.right)
+    }
+
+    pub fn height(&self) -> u32 {
+        self.top.abs_diff(self.bottom)
+    }
+
+    pub fn size(&self) -> [u32; 2] {
+        [self.width(), self.height()]
+    }
+
+    /// Returns the smallest rectangle containing both rectangles.
+    pub fn union(self, other: Self) -> Self {
+        Self {
+            left: self.left.min(other.left),
+            top: self.top.min(other.top),
+            right: self.right.max(other.right),
+            bottom: self.bottom.max(other.bottom),
+        }
+    }
+
+    /// Returns the smallest rectangle containing all of the rectangles.
+    pub fn bounding(rects: impl IntoIterator<Item = Self>) -> Option<Self> {
+        rects.into_iter().reduce(Self::union)
+    }
+
+    /// Returns the area covered by both rectangles, `None` if they do not overlap.
+    pub fn intersection(self, other: Self) -> Option<Self> {
+        let intersection = Self {
+            left: self.left.max(other.left),
+            top: self.top.max(other.top),
+            right: self.right.min(other.right),
+            bottom: self.bottom.min(other.bottom),
+        };
+
+        (intersection.left < intersection.right && intersection.top < intersection.bottom)
+            .then_some(intersection)
+    }
+
+    /// Returns the centre of the rectangle, rounded towards the top-left.
+    pub fn centre(&self) -> [i32; 2] {
+        [
+            self.left + self.width() as i32 / 2,
+            self.top + self.height() as i32 / 2,
+        ]
+    }
+
+    /// Returns if a point is inside the rectangle, the right and bottom edges are outside.
+    pub fn contains(&self, [x, y]: [i32; 2]) -> bool {
+        (self.left..self.right).contains(&x) && (self.top..self.bottom).contains(&y)
+    }
+}
+
+impl From<RECT> for DesktopRect {
+    fn from(value: RECT) -> Self {
+        Self {
+            left: value.left,
+            top: value.top,
+            right: value.right,
+            bottom: value.bottom,
+        }
+    }
+}
+
+/// The capture of a single display.
+pub struct DisplayCapture<'a> {
+    /// The display's desktop coordinates.
+    pub rect: DesktopRect,
+
+    /// The display's SDR white luminance.
+    pub sdr_white: f32,
+
+    /// The display's maximum luminance.
+    pub max_brightness: f32,
+
+    /// The display's `RGBA` pixels, row-major with no padding.
+    pub pixels: &'a [f16],
+}
+
+/// A capture of every display composited into the virtual desktop.
+pub struct VirtualDesktopCapture {
+    /// The desktop coordinates that the capture covers.
+    pub rect: DesktopRect,
+
+    /// The SDR white luminance that every display was normalised to.
+    pub sdr_white: f32,
+
+    /// The maximum luminance of the brightest display after normalisation.
+    pub max_brightness: f32,
+
+    /// The `RGBA` pixels, row-major with no padding. Areas not covered by a display are
+    /// transparent.
+    pub pixels: Vec<f16>,
+}
+
+#[derive(Debug, Error)]
+pub enum CompositeError {
+    #[error("There are no displays to composite")]
+    NoDisplays,
+
+    #[error("The display at {rect:?} has {len} pixel values, expected {expected}")]
+    DisplaySize {
+        rect: DesktopRect,
+        len: usize,
+        expected: usize,
+    },
+}
+
+/// Composites the displays into a single capture of the virtual desktop.
+///
+/// Each display presents SDR content at its own SDR white, so the displays are scaled to the
+/// brightest SDR white to make SDR content consistent across the capture.
+///
+/// A display's pixels may not match its size if the display changed during the capture.
+pub fn composite(displays: &[DisplayCapture<'_>]) -> Result<VirtualDesktopCapture, CompositeError> {
+    let rect = DesktopRect::bounding(displays.iter().map(|display| display.rect))
+        .ok_or(CompositeError::NoDisplays)?;
+
+    for display in displays {
+        let expected = display.rect.width() as usize * display.rect.height() as usize * 4;
+        if display.pixels.len() != expected {
+            return Err(CompositeError::DisplaySize {
+                rect: display.rect,
+                len: display.pixels.len(),
+                expected,
+            });
+        }
+    }
+
+    let sdr_white = displays
+        .iter()
+        .map(|display| display.sdr_white)
+        .fold(f32::MIN, f32::max);
+
+    let max_brightness = displays
+        .iter()
+        .map(|display| display.max_brightness * normalisation_scale(display, sdr_white))
+        .fold(f32::MIN, f32::max);
+
+    let width = rect.width() as usize;
+    let mut pixels = vec![f16::ZERO; width * rect.height() as usize * 4];
+
+    for display in displays {
+        let scale = normalisation_scale(display, sdr_white);
+        let display_width = display.rect.width() as usize;
+
+        let offset_x = display.rect.left.abs_diff(rect.left) as usize;
+        let offset_y = display.rect.top.abs_diff(rect.top) as usize;
+
+        for (row, source) in display.pixels.chunks_exact(display_width * 4).enumerate() {
+            let start = ((offset_y + row) * width + offset_x) * 4;
+            let destination = &mut pixels[start..start + display_width * 4];
+
+            for (output, input) in destination.chunks_exact_mut(4).zip(source.chunks_exact(4)) {
+                output[0] = f16::from_f32(input[0].to_f32() * scale);
+                output[1] = f16::from_f32(input[1].to_f32() * scale);
+                output[2] = f16::from_f32(input[2].to_f32() * scale);
+                output[3] = input[3];
+            }
+        }
+    }
+
+    Ok(VirtualDesktopCapture {
+        rect,
+        sdr_white,
+        max_brightness,
+        pixels,
+    })
+}
+
+/// The scale that maps a display's SDR white to the reference SDR white.
+fn normalisation_scale(display: &DisplayCapture<'_>, reference_sdr_white: f32) -> f32 {
+    if display.sdr_white <= 0.0 {
+        return 1.0;
+    }
+
+    reference_sdr_white / display.sdr_white
+}
+
+#[cfg(test)]
+mod test {
+    use half::f16;
+
+    use super::{CompositeError, DesktopRect, DisplayCapture, composite};
+
+    fn rect(left: i32, top: i32, right: i32, bottom: i32) -> DesktopRect {
+        DesktopRect {
+            left,
+            top,
+            right,
+            bottom,
+        }
+    }
+
+    fn solid(size: [u32; 2], value: f32) -> Vec<f16> {
+        vec![f16::from_f32(value); (size[0] * size[1] * 4) as usize]
+    }
+
+    fn pixel(pixels: &[f16], width: u32, x: u32, y: u32) -> [f32; 4] {
+        let start = ((y * width + x) * 4) as usize;
+        [
+            pixels[start].to_f32(),
+            pixels[start + 1].to_f32(),
+            pixels[start + 2].to_f32(),
+            pixels[start + 3].to_f32(),
+        ]
+    }
+
+    #[test]
+    fn intersection_is_overlap() {
+        let monitor = rect(0, 0, 1920, 1080);
+
+        assert_eq!(
+            rect(1800, -20, 2000, 100).intersection(monitor),
+            Some(rect(1800, 0, 1920, 100))
+        );
+        assert_eq!(rect(1920, 0, 2000, 100).intersection(monitor), None);
+        assert!(monitor.contains(rect(1800, -20, 2000, 100).centre()));
+        assert!(!monitor.contains([1920, 0]));
+    }
+
+    #[test]
+    fn bounding_covers_all() {
+        let bounds = DesktopRect::bounding([
+            rect(0, 0, 1920, 1080),
+            rect(-1280, 200, 0, 1224),
+            rect(1920, -300, 4480, 1140),
+        ]);
+
+        assert_eq!(bounds, Some(rect(-1280, -300, 4480, 1224)));
+        assert_eq!(DesktopRect::bounding([]), None);
+    }
+
+    #[test]
+    fn composites_at_desktop_coordinates() {
+        let left = [0.25, 0.5, 0.75, 1.0];
+        let left_pixels: Vec<_> = left.iter().copied().cycle().take(2 * 2 * 4).collect();
+        let left_pixels: Vec<_> = left_pixels.into_iter().map(f16::from_f32).collect();
+        let right_pixels = solid([3, 1], 2.0);
+
+        let capture = composite(&[
+            DisplayCapture {
+                rect: rect(-2, 0, 0, 2),
+                sdr_white: 2.5,
+                max_brightness: 10.0,
+                pixels: &left_pixels,
+            },
+            DisplayCapture {
+                rect: rect(0, 1, 3, 2),
+                sdr_white: 2.5,
+                max_brightness: 10.0,
+                pixels: &right_pixels,
+            },
+        ])
+        .unwrap();
+
+        assert_eq!(capture.rect, rect(-2, 0, 3, 2));
+        assert_eq!(capture.pixels.len(), 5 * 2 * 4);
+
+        assert_eq!(pixel(&capture.pixels, 5, 0, 0), left);
+        assert_eq!(pixel(&capture.pixels, 5, 1, 1), left);
+        assert_eq!(pixel(&capture.pixels, 5, 2, 1), [2.0; 4]);
+        assert_eq!(pixel(&capture.pixels, 5, 4, 1), [2.0; 4]);
+
+        // Not covered by a display
+        assert_eq!(pixel(&capture.pixels, 5, 3, 0), [0.0; 4]);
+    }
+
+    #[test]
+    fn normalises_to_brightest_sdr_white() {
+        let dim_pixels = solid([1, 1], 2.0);
+        let bright_pixels = solid([1, 1], 4.0);
+
+        let capture = composite(&[
+            DisplayCapture {
+                rect: rect(0, 0, 1, 1),
+                sdr_white: 2.0,
+                max_brightness: 8.0,
+                pixels: &dim_pixels,
+            },
+            DisplayCapture {
+                rect: rect(1, 0, 2, 1),
+                sdr_white: 4.0,
+                max_brightness: 12.0,
+                pixels: &bright_pixels,
+            },
+        ])
+        .unwrap();
+
+        assert_eq!(capture.sdr_white, 4.0);
+
+        // The dim display's SDR white is scaled to match the bright display.
+        assert_eq!(pixel(&capture.pixels, 2, 0, 0), [4.0, 4.0, 4.0, 2.0]);
+        assert_eq!(pixel(&capture.pixels, 2, 1, 0), [4.0, 4.0, 4.0, 4.0]);
+
+        // The dim display's peak of 8.0 is scaled to 16.0.
+        assert_eq!(capture.max_brightness, 16.0);
+    }
+
+    #[test]
+    fn empty_is_error() {
+        assert!(matches!(composite(&[]), Err(CompositeError::NoDisplays)));
+    }
+
+    #[test]
+    fn mismatched_pixels_are_error() {
+        let pixels = solid([2, 2], 1.0);
+
+        let result = composite(&[DisplayCapture {
+            rect: rect(0, 0, 3, 2),
+            sdr_white: 2.5,
+            max_brightness: 10.0,
+            pixels: &pixels,
+        }]);
+
+        assert!(matches!(
+            result,
+            Err(CompositeError::DisplaySize {
+                len: 16,
+                expected: 24,
+                ..
+            })
+        ));
+    }
+}
@@ -0,0 +1,309 @@
+use core::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+/// How many seconds the whitepoint takes to move most of the way up to a brighter frame.
+const BRIGHTEN_TIME_CONSTANT: f32 = 0.3;
+
+/// How many seconds the whitepoint takes to move most of the way down to a darker frame, slower
+/// than brightening like the eye adapting to the dark.
+const DARKEN_TIME_CONSTANT: f32 = 1.0;
+
+/// How far above SDR white, as a fraction of SDR white, a frame must be to switch an SDR sequence
+/// to HDR.
+const HDR_HYSTERESIS: f32 = 0.1;
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum Whitepoint {
+    Sdr(f32),
+    Hdr(f32),
+}
+
+impl Whitepoint {
+    /// Selects the whitepoint for a capture from the brightest value in the capture.
+    pub fn select(maximum: f32, sdr_white: f32, max_brightness: f32) -> Self {
+        let is_hdr = maximum > sdr_white;
+
+        if is_hdr {
+            Self::Hdr(max_brightness)
+        } else {
+            Self::Sdr(sdr_white)
+        }
+    }
+
+    pub fn value(self) -> f32 {
+        match self {
+            Self::Sdr(value) => value,
+            Self::Hdr(value) => value,
+        }
+    }
+
+    /// Returns the whitepoint multiplied by a factor, a smaller whitepoint brightens the capture.
+    pub fn scaled(self, factor: f32) -> Self {
+        match self {
+            Self::Sdr(value) => Self::Sdr(value * factor),
+            Self::Hdr(value) => Self::Hdr(value * factor),
+        }
+    }
+
+    /// Returns a whitepoint of the same kind with a different value.
+    fn with_value(self, value: f32) -> Self {
+        match self {
+            Self::Sdr(_) => Self::Sdr(value),
+            Self::Hdr(_) => Self::Hdr(value),
+        }
+    }
+}
+
+/// How the whitepoint of a capture is chosen.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WhitepointStrategy {
+    /// SDR white, or the monitor's maximum brightness if the capture is brighter than SDR white.
+    #[default]
+    Automatic,
+
+    /// Always SDR white, brighter content is clipped.
+    Sdr,
+
+    /// Always the monitor's maximum brightness.
+    Hdr,
+}
+
+impl WhitepointStrategy {
+    /// Selects the whitepoint for a capture from the brightest value in the capture.
+    pub fn select(self, maximum: f32, sdr_white: f32, max_brightness: f32) -> Whitepoint {
+        match self {
+            Self::Automatic => Whitepoint::select(maximum, sdr_white, max_brightness),
+            Self::Sdr => Whitepoint::Sdr(sdr_white),
+            Self::Hdr => Whitepoint::Hdr(max_brightness),
+        }
+    }
+}
+
+/// Selects the whitepoint of each frame in a sequence, adapting to changes in brightness over
+/// time like the eye instead of flickering between whitepoints frame by frame.
+///
+/// The first frame gets the same whitepoint as a single capture, after that the whitepoint eases
+/// towards each frame's whitepoint and frames just above SDR white do not switch an SDR sequence
+/// to HDR.
+#[derive(Clone, Copy, Debug)]
+pub struct WhitepointAdapter {
+    sdr_white: f32,
+    max_brightness: f32,
+
+    /// How many seconds the whitepoint takes to move most of the way up to a brighter frame.
+    brighten_time_constant: f32,
+
+    /// How many seconds the whitepoint takes to move most of the way down to a darker frame.
+    darken_time_constant: f32,
+
+    /// How far above SDR white, as a fraction of SDR white, a frame must be to switch from SDR
+    /// to HDR.
+    hysteresis: f32,
+
+    /// How the whitepoint of each frame is chosen, only an automatic whitepoint adapts.
+    strategy: WhitepointStrategy,
+
+    /// The whitepoint of the previous frame and when that frame was taken.
+    current: Option<(Whitepoint, Duration)>,
+}
+
+impl WhitepointAdapter {
+    pub fn new(sdr_white: f32, max_brightness: f32) -> Self {
+        Self {
+            sdr_white,
+            max_brightness,
+            brighten_time_constant: BRIGHTEN_TIME_CONSTANT,
+            darken_time_constant: DARKEN_TIME_CONSTANT,
+            hysteresis: HDR_HYSTERESIS,
+            strategy: WhitepointStrategy::Automatic,
+            current: None,
+        }
+    }
+
+    /// Chooses the whitepoint of each frame with a strategy instead of automatically.
+    pub fn with_strategy(mut self, strategy: WhitepointStrategy) -> Self {
+        self.strategy = strategy;
+        self
+    }
+
+    /// Adapts to a frame whose brightest value is `maximum`, taken `timestamp` after the sequence
+    /// started. Returns the whitepoint for the frame.
+    pub fn update(&mut self, maximum: f32, timestamp: Duration) -> Whitepoint {
+        let Some((current, updated_at)) = self.current else {
+            let whitepoint = self
+                .strategy
+                .select(maximum, self.sdr_white, self.max_brightness);
+            self.current = Some((whitepoint, timestamp));
+            return whitepoint;
+        };
+
+        let target = self.target(current, maximum);
+
+        let time_constant = if target.value() > current.value() {
+            self.brighten_time_constant
+        } else {
+            self.darken_time_constant
+        };
+        let elapsed = timestamp.saturating_sub(updated_at).as_secs_f32();
+        let blend = 1.0 - (-elapsed / time_constant).exp();
+
+        let value = current.value() + (target.value() - current.value()) * blend;
+        let whitepoint = target.with_value(value);
+
+        self.current = Some((whitepoint, timestamp));
+        whitepoint
+    }
+
+    /// Selects the whitepoint the sequence is adapting towards.
+    fn target(&self, current: Whitepoint, maximum: f32) -> Whitepoint {
+        if self.strategy != WhitepointStrategy::Automatic {
+            return self
+                .strategy
+                .select(maximum, self.sdr_white, self.max_brightness);
+        }
+
+        // Frames just above SDR white keep the current decision, so a sequence near the threshold
+        // does not switch back and forth.
+        let threshold = match current {
+            Whitepoint::Sdr(_) => self.sdr_white * (1.0 + self.hysteresis),
+            Whitepoint::Hdr(_) => self.sdr_white,
+        };
+
+        if maximum > threshold {
+            Whitepoint::Hdr(self.max_brightness)
+        } else {
+            Whitepoint::Sdr(self.sdr_white)
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use core::time::Duration;
+
+    use super::{Whitepoint, WhitepointAdapter, WhitepointStrategy};
+
+    const SDR_WHITE: f32 = 1.0;
+    const MAX_BRIGHTNESS: f32 = 4.0;
+
+    fn milliseconds(milliseconds: u64) -> Duration {
+        Duration::from_millis(milliseconds)
+    }
+
+    /// Adapts to each maximum in turn, one frame every 100 milliseconds.
+    fn adapt(maximums: &[f32]) -> Vec<Whitepoint> {
+        let mut adapter = WhitepointAdapter::new(SDR_WHITE, MAX_BRIGHTNESS);
+        maximums
+            .iter()
+            .enumerate()
+            .map(|(index, maximum)| adapter.update(*maximum, milliseconds(index as u64 * 100)))
+            .collect()
+    }
+
+    /// How far the second whitepoint moved from the first towards `target`.
+    fn progress(whitepoints: &[Whitepoint], target: f32) -> f32 {
+        let start = whitepoints[0].value();
+        (whitepoints[1].value() - start) / (target - start)
+    }
+
+    #[test]
+    fn first_frame_matches_a_single_capture() {
+        for maximum in [0.5, 1.0, 1.05, 3.0] {
+            assert_eq!(
+                adapt(&[maximum])[0],
+                Whitepoint::select(maximum, SDR_WHITE, MAX_BRIGHTNESS)
+            );
+        }
+    }
+
+    #[test]
+    fn brightens_faster_than_it_darkens() {
+        let brightening = adapt(&[0.5, 3.0]);
+        let darkening = adapt(&[3.0, 0.5]);
+
+        assert!(matches!(brightening[1], Whitepoint::Hdr(_)));
+        assert!(matches!(darkening[1], Whitepoint::Sdr(_)));
+
+        let brightened = progress(&brightening, MAX_BRIGHTNESS);
+        let darkened = progress(&darkening, SDR_WHITE);
+        assert!(0.0 < darkened && darkened < brightened && brightened < 1.0);
+    }
+
+    #[test]
+    fn adapting_depends_on_elapsed_time() {
+        let mut once = WhitepointAdapter::new(SDR_WHITE, MAX_BRIGHTNESS);
+        once.update(0.5, Duration::ZERO);
+        let after_once = once.update(3.0, milliseconds(300));
+
+        // One time constant covers most of the change.
+        let expected = SDR_WHITE + (MAX_BRIGHTNESS - SDR_WHITE) * (1.0 - (-1.0f32).exp());
+        assert!((after_once.value() - expected).abs() < 1e-4);
+
+        // More frequent frames end up at the same whitepoint.
+        let mut often = WhitepointAdapter::new(SDR_WHITE, MAX_BRIGHTNESS);
+        often.update(0.5, Duration::ZERO);
+        often.update(3.0, milliseconds(100));
+        often.update(3.0, milliseconds(200));
+        let after_often = often.update(3.0, milliseconds(300));
+        assert!((after_once.value() - after_often.value()).abs() < 1e-4);
+
+        // It converges on the target.
+        let converged = once.update(3.0, Duration::from_secs(10));
+        assert!((converged.value() - MAX_BRIGHTNESS).abs() < 1e-3);
+    }
+
+    #[test]
+    fn hysteresis_holds_decision_near_sdr_white() {
+        // Just above SDR white stays SDR until a frame is clearly HDR.
+        let whitepoints = adapt(&[1.0, 1.05, 1.08, 1.5, 1.05, 1.0]);
+        let kinds: Vec<_> = whitepoints
+            .iter()
+            .map(|whitepoint| matches!(whitepoint, Whitepoint::Hdr(_)))
+            .collect();
+        assert_eq!(kinds, [false, false, false, true, true, false]);
+
+        assert_eq!(whitepoints[1], Whitepoint::Sdr(SDR_WHITE));
+        assert_eq!(whitepoints[2], Whitepoint::Sdr(SDR_WHITE));
+    }
+
+    #[test]
+    fn noisy_statistics_do_not_flicker() {
+        let maximums: Vec<_> = (0..50)
+            .map(|index| if index % 2 == 0 { 0.98 } else { 1.06 })
+            .collect();
+
+        let whitepoints = adapt(&maximums);
+        assert!(
+            whitepoints
+                .iter()
+                .all(|whitepoint| *whitepoint == Whitepoint::Sdr(SDR_WHITE))
+        );
+
+        // The same statistics always adapt the same way.
+        assert_eq!(whitepoints, adapt(&maximums));
+    }
+
+    #[test]
+    fn fixed_strategy_does_not_adapt() {
+        let mut adapter = WhitepointAdapter::new(SDR_WHITE, MAX_BRIGHTNESS)
+            .with_strategy(WhitepointStrategy::Sdr);
+
+        let whitepoints: Vec<_> = [0.5, 4.0, 3.0, 1.0]
+            .into_iter()
+            .enumerate()
+            .map(|(index, maximum)| adapter.update(maximum, milliseconds(index as u64 * 100)))
+            .collect();
+
+        assert!(
+            whitepoints
+                .iter()
+                .all(|whitepoint| *whitepoint == Whitepoint::Sdr(SDR_WHITE))
+        );
+        assert_eq!(
+            WhitepointStrategy::Hdr.select(0.5, SDR_WHITE, MAX_BRIGHTNESS),
+            Whitepoint::Hdr(MAX_BRIGHTNESS)
+        );
+    }
+}
@@ -0,0 +1,471 @@
+use core::fmt::Write as _;
+use std::{io::Cursor, path::Path};
+
+use half::f16;
+use image::{ExtendedColorType, ImageFormat};
+use serde::{Deserialize, Serialize};
+use tracing::{debug, warn};
+
+use crate::capture_history::encode_exr;
+
+pub use win32::write;
+
+mod win32;
+
+/// The formats a screenshot is copied to the clipboard in, applications paste the one they
+/// understand best.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ClipboardSettings {
+    /// The formats to copy, an empty list leaves the clipboard alone.
+    pub formats: Vec<ClipboardFormat>,
+}
+
+impl Default for ClipboardSettings {
+    fn default() -> Self {
+        Self {
+            formats: vec![
+                ClipboardFormat::Bitmap,
+                ClipboardFormat::Png,
+                ClipboardFormat::File,
+                ClipboardFormat::Html,
+            ],
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ClipboardFormat {
+    /// An uncompressed bitmap with transparency, understood by most applications.
+    Bitmap,
+
+    /// The PNG encoded screenshot, keeps transparency exactly.
+    Png,
+
+    /// The saved file, pasting in a file manager or chat application pastes the file.
+    File,
+
+    /// An HTML image of the saved file, for rich text editors.
+    Html,
+
+    /// The HDR capture as an EXR image under the `image/x-exr` format, without the annotations
+    /// other than redactions.
+    Exr,
+}
+
+/// What is copied to the clipboard.
+pub struct ClipboardContent<'a> {
+    /// The width and height of the screenshot in pixels.
+    pub size: [u32; 2],
+
+    /// The `RGBA` pixels of the screenshot.
+    pub rgba: &'a [u8],
+
+    /// Where the screenshot was saved, `None` if it could not be saved.
+    pub path: Option<&'a Path>,
+
+    /// The `RGBA` pixels of the HDR capture, `None` if the EXR format is not copied.
+    pub hdr: Option<&'a [f16]>,
+}
+
+/// The data of the content in one clipboard format.
+#[derive(Debug, PartialEq, Eq)]
+pub struct Flavour {
+    pub format: ClipboardFormat,
+    pub data: Vec<u8>,
+}
+
+impl ClipboardSettings {
+    /// If the HDR pixels are needed for the clipboard.
+    pub fn needs_hdr(&self) -> bool {
+        self.formats.contains(&ClipboardFormat::Exr)
+    }
+}
+
+/// Encodes the content in each of the formats, formats the content cannot be put in are skipped.
+pub fn build_flavours(formats: &[ClipboardFormat], content: &ClipboardContent<'_>) -> Vec<Flavour> {
+    let mut flavours: Vec<Flavour> = Vec::with_capacity(formats.len());
+
+    for &format in formats {
+        if flavours.iter().any(|flavour| flavour.format == format) {
+            continue;
+        }
+
+        let data = match format {
+            ClipboardFormat::Bitmap => Some(bitmap(content.size, content.rgba)),
+
+            ClipboardFormat::Png => png(content.size, content.rgba),
+
+            ClipboardFormat::File => content.path.map(file_drop),
+
+            ClipboardFormat::Html => content
+                .path
+                .map(|path| html(&image_fragment(path, content.size))),
+
+            ClipboardFormat::Exr => content.hdr.and_then(|pixels| {
+                encode_exr(content.size, pixels)
+                    .inspect_err(|e| warn!("Could not encode the HDR clipboard image: {e}"))
+                    .ok()
+            }),
+        };
+
+        match data {
+            Some(data) => flavours.push(Flavour { format, data }),
+            None => debug!("Skipped the {format:?} clipboard format"),
+        }
+    }
+
+    flavours
+}
+
+/// Sets the alpha of the HDR pixels from the screenshot's, so the HDR image has the same
+/// transparent areas as a freeform selection.
+pub fn copy_alpha(hdr: &mut [f16], rgba: &[u8]) {
+    for (hdr, rgba) in hdr.chunks_exact_mut(4).zip(rgba.chunks_exact(4)) {
+        if rgba[3] == 0 {
+            hdr.fill(f16::ZERO);
+        }
+    }
+}
+
+/// Copies an area of `RGBA` pixels, `width` is the width of the whole image.
+pub fn crop<T: Copy>(pixels: &[T], width: usize, position: [usize; 2], size: [usize; 2]) -> Vec<T> {
+    let mut cropped = Vec::with_capacity(size[0] * size[1] * 4);
+
+    for y in position[1]..position[1] + size[1] {
+        let start = (y * width + position[0]) * 4;
+        cropped.extend_from_slice(&pixels[start..start + size[0] * 4]);
+    }
+
+    cropped
+}
+
+/// A `CF_DIBV5` bitmap, a `BITMAPV5HEADER` followed by bottom-up `BGRA` rows with straight alpha.
+fn bitmap(size: [u32; 2], rgba: &[u8]) -> Vec<u8> {
+    const HEADER_SIZE: u32 = 124;
+    const BI_BITFIELDS: u32 = 3;
+    const LCS_SRGB: u32 = 0x7352_4742;
+    const LCS_GM_IMAGES: u32 = 4;
+
+    let [width, height] = size;
+    let image_size = width * height * 4;
+
+    let mut data = Vec::with_capacity((HEADER_SIZE + image_size) as usize);
+    data.extend_from_slice(&HEADER_SIZE.to_le_bytes());
+    data.extend_from_slice(&width.to_le_bytes());
+    data.extend_from_slice(&height.to_le_bytes());
+    data.extend_from_slice(&1u16.to_le_bytes()); // Planes
+    data.extend_from_slice(&32u16.to_le_bytes()); // Bits per pixel
+    data.extend_from_slice(&BI_BITFIELDS.to_le_bytes());
+    data.extend_from_slice(&image_size.to_le_bytes());
+    data.extend_from_slice(&[0; 16]); // Resolution and palette
+    for mask in [0x00FF_0000u32, 0x0000_FF00, 0x0000_00FF, 0xFF00_0000] {
+        data.extend_from_slice(&mask.to_le_bytes());
+    }
+    data.extend_from_slice(&LCS_SRGB.to_le_bytes());
+    data.extend_from_slice(&[0; 48]); // Endpoints and gamma, unused for sRGB
+    data.extend_from_slice(&LCS_GM_IMAGES.to_le_bytes());
+    data.extend_from_slice(&[0; 12]); // Profile and reserved
+
+    let row_length = width as usize * 4;
+    for row in rgba.chunks_exact(row_length).rev() {
+        for pixel in row.chunks_exact(4) {
+            data.extend_from_slice(&[pixel[2], pixel[1], pixel[0], pixel[3]]);
+        }
+    }
+
+    data
+}
+
+fn png(size: [u32; 2], rgba: &[u8]) -> Option<Vec<u8>> {
+    let mut data = Cursor::new(Vec::new());
+
+    match image::write_buffer_with_format(
+        &mut data,
+        rgba,
+        size[0],
+        size[1],
+        ExtendedColorType::Rgba8,
+        ImageFormat::Png,
+    ) {
+        Ok(()) => Some(data.into_inner()),
+        Err(e) => {
+            warn!("Could not encode the PNG clipboard image: {e}");
+            None
+        }
+    }
+}
+
+/// A `CF_HDROP` list of one file, a `DROPFILES` header followed by the wide path, ending with two
+/// nulls.
+fn file_drop(path: &Path) -> Vec<u8> {
+    const HEADER_SIZE: u32 = 20;
+
+    let mut data = Vec::new();
+    data.extend_from_slice(&HEADER_SIZE.to_le_bytes());
+    data.extend_from_slice(&[0; 8]); // Drop point
+    data.extend_from_slice(&0u32.to_le_bytes()); // Not in a non-client area
+    data.extend_from_slice(&1u32.to_le_bytes()); // Wide characters
+
+    let path = path.to_string_lossy();
+    for unit in path.encode_utf16().chain([0, 0]) {
+        data.extend_from_slice(&unit.to_le_bytes());
+    }
+
+    data
+}
+
+/// An HTML `<img>` of the file.
+fn image_fragment(path: &Path, size: [u32; 2]) -> String {
+    format!(
+        "<img src=\"{}\" width=\"{}\" height=\"{}\">",
+        file_url(path),
+        size[0],
+        size[1]
+    )
+}
+
+/// The `HTML Format` clipboard data, the header gives the byte offsets of the document and the
+/// fragment within it.
+fn html(fragment: &str) -> Vec<u8> {
+    const HEADER: &str = "Version:0.9\r\nStartHTML:{start_html}\r\nEndHTML:{end_html}\r\nStartFragment:{start_fragment}\r\nEndFragment:{end_fragment}\r\n";
+    const PLACEHOLDERS: [&str; 4] = [
+        "{start_html}",
+        "{end_html}",
+        "{start_fragment}",
+        "{end_fragment}",
+    ];
+    const OFFSET_DIGITS: usize = 10;
+
+    let header_length = PLACEHOLDERS
+        .iter()
+        .fold(HEADER.len(), |length, placeholder| {
+            length - placeholder.len() + OFFSET_DIGITS
+        });
+
+    let before = "<html><body><!--StartFragment-->";
+    let after = "<!--EndFragment--></body></html>";
+
+    let start_fragment = header_length + before.len();
+    let end_fragment = start_fragment + fragment.len();
+    let offsets = [
+        header_length,
+        end_fragment + after.len(),
+        start_fragment,
+        end_fragment,
+    ];
+
+    let mut data = PLACEHOLDERS.iter().zip(offsets).fold(
+        String::from(HEADER),
+        |header, (placeholder, offset)| {
+            header.replace(placeholder, &format!("{offset:0OFFSET_DIGITS$}"))
+        },
+    );
+    data.push_str(before);
+    data.push_str(fragment);
+    data.push_str(after);
+
+    data.into_bytes()
+}
+
+/// A `file:` URL of an absolute path, with everything but unreserved characters percent-encoded.
+fn file_url(path: &Path) -> String {
+    let path = path.to_string_lossy().replace('\\', "/");
+
+    let mut url = String::from("file://");
+    if !path.starts_with('/') {
+        url.push('/');
+    }
+
+    for byte in path.bytes() {
+        if byte.is_ascii_alphanumeric() || b"-._~/:".contains(&byte) {
+            url.push(char::from(byte));
+        } else {
+            let _ = write!(url, "%{byte:02X}");
+        }
+    }
+
+    url
+}
+
+#[cfg(test)]
+mod test {
+    use std::path::Path;
+
+    use half::f16;
+
+    use super::{
+        ClipboardContent, ClipboardFormat, Flavour, build_flavours, copy_alpha, crop, file_url,
+        html,
+    };
+
+    /// A 2x2 image with a transparent bottom right pixel.
+    const RGBA: [u8; 16] = [
+        255, 0, 0, 255, 0, 255, 0, 255, //
+        0, 0, 255, 255, 10, 20, 30, 0,
+    ];
+
+    fn content(path: Option<&Path>) -> ClipboardContent<'_> {
+        ClipboardContent {
+            size: [2, 2],
+            rgba: &RGBA,
+            path,
+            hdr: None,
+        }
+    }
+
+    fn data(flavours: &[Flavour], format: ClipboardFormat) -> &[u8] {
+        &flavours
+            .iter()
+            .find(|flavour| flavour.format == format)
+            .unwrap()
+            .data
+    }
+
+    #[test]
+    fn builds_requested_formats() {
+        let formats = [
+            ClipboardFormat::Png,
+            ClipboardFormat::Bitmap,
+            ClipboardFormat::File,
+            ClipboardFormat::Exr,
+            ClipboardFormat::Png,
+        ];
+
+        // The file formats need the saved file, and EXR needs the HDR pixels.
+        let flavours = build_flavours(&formats, &content(None));
+        let built: Vec<_> = flavours.iter().map(|flavour| flavour.format).collect();
+        assert_eq!(built, [ClipboardFormat::Png, ClipboardFormat::Bitmap]);
+
+        let path = Path::new("Screenshot.png");
+        let flavours = build_flavours(&formats, &content(Some(path)));
+        let built: Vec<_> = flavours.iter().map(|flavour| flavour.format).collect();
+        assert_eq!(
+            built,
+            [
+                ClipboardFormat::Png,
+                ClipboardFormat::Bitmap,
+                ClipboardFormat::File
+            ]
+        );
+
+        assert!(build_flavours(&[], &content(Some(path))).is_empty());
+    }
+
+    #[test]
+    fn exr_is_built_from_hdr_pixels() {
+        let hdr = [f16::ONE; 16];
+        let content = ClipboardContent {
+            hdr: Some(&hdr),
+            ..content(None)
+        };
+
+        let flavours = build_flavours(&[ClipboardFormat::Exr], &content);
+
+        // The OpenEXR magic number
+        assert!(data(&flavours, ClipboardFormat::Exr).starts_with(&[0x76, 0x2F, 0x31, 0x01]));
+    }
+
+    #[test]
+    fn png_keeps_pixels() {
+        let flavours = build_flavours(&[ClipboardFormat::Png], &content(None));
+        let decoded = image::load_from_memory(data(&flavours, ClipboardFormat::Png))
+            .unwrap()
+            .into_rgba8();
+
+        assert_eq!(decoded.dimensions(), (2, 2));
+        assert_eq!(decoded.into_raw(), RGBA);
+    }
+
+    #[test]
+    fn bitmap_is_bottom_up_bgra() {
+        let flavours = build_flavours(&[ClipboardFormat::Bitmap], &content(None));
+        let data = data(&flavours, ClipboardFormat::Bitmap);
+
+        assert_eq!(data.len(), 124 + 16);
+        assert_eq!(data[0..4], 124u32.to_le_bytes());
+        assert_eq!(data[4..8], 2u32.to_le_bytes());
+        assert_eq!(data[8..12], 2u32.to_le_bytes());
+        assert_eq!(data[14..16], 32u16.to_le_bytes());
+
+        // The alpha mask
+        assert_eq!(data[52..56], 0xFF00_0000u32.to_le_bytes());
+
+        // The bottom row comes first, with the transparency kept.
+        assert_eq!(
+            data[124..],
+            [
+                255, 0, 0, 255, 30, 20, 10, 0, //
+                0, 0, 255, 255, 0, 255, 0, 255,
+            ]
+        );
+    }
+
+    #[test]
+    fn file_drop_lists_the_file() {
+        let path = Path::new("Screenshot.png");
+        let flavours = build_flavours(&[ClipboardFormat::File], &content(Some(path)));
+        let data = data(&flavours, ClipboardFormat::File);
+
+        assert_eq!(data[0..4], 20u32.to_le_bytes());
+        assert_eq!(data[16..20], 1u32.to_le_bytes());
+
+        let units: Vec<u16> = data[20..]
+            .chunks_exact(2)
+            .map(|unit| u16::from_le_bytes([unit[0], unit[1]]))
+            .collect();
+        let expected: Vec<u16> = "Screenshot.png".encode_utf16().chain([0, 0]).collect();
+        assert_eq!(units, expected);
+    }
+
+    #[test]
+    fn html_offsets_locate_the_fragment() {
+        let fragment = "<img src=\"file:///C:/Screenshot.png\">";
+        let data = String::from_utf8(html(fragment)).unwrap();
+
+        let offset = |name: &str| -> usize {
+            let start = data.find(&format!("{name}:")).unwrap() + name.len() + 1;
+            data[start..start + 10].parse().unwrap()
+        };
+
+        assert_eq!(
+            &data[offset("StartFragment")..offset("EndFragment")],
+            fragment
+        );
+        assert!(data[offset("StartHTML")..].starts_with("<html>"));
+        assert_eq!(offset("EndHTML"), data.len());
+    }
+
+    #[test]
+    fn file_urls_are_encoded() {
+        assert_eq!(
+            file_url(Path::new(r"C:\Users\Me\My Shots\100%.png")),
+            "file:///C:/Users/Me/My%20Shots/100%25.png"
+        );
+        assert_eq!(
+            file_url(Path::new("/home/me/shot.png")),
+            "file:///home/me/shot.png"
+        );
+    }
+
+    #[test]
+    fn hdr_matches_the_screenshot() {
+        let width = 3;
+        let pixels: Vec<f16> = (0..width * 2 * 4)
+            .map(|i| f16::from_f32(i as f32))
+            .collect();
+
+        let mut cropped = crop(&pixels, width, [1, 0], [2, 2]);
+        let expected: Vec<f16> = [4..12, 16..24]
+            .into_iter()
+            .flatten()
+            .map(|i| f16::from_f32(i as f32))
+            .collect();
+        assert_eq!(cropped, expected);
+
+        copy_alpha(&mut cropped, &RGBA);
+        assert!(cropped[12..].iter().all(|value| *value == f16::ZERO));
+        assert_eq!(cropped[..12], expected[..12]);
+    }
+}
@@ -0,0 +1,109 @@
+use core::{ptr, time::Duration};
+use std::thread;
+
+use windows::{
+    Win32::{
+        Foundation::{GetLastError, HANDLE},
+        System::{
+            DataExchange::{
+                CloseClipboard, EmptyClipboard, OpenClipboard, RegisterClipboardFormatW,
+                SetClipboardData,
+            },
+            Memory::{GMEM_MOVEABLE, GlobalAlloc, GlobalFree, GlobalLock, GlobalUnlock},
+            Ole::{CF_DIBV5, CF_HDROP},
+        },
+    },
+    core::w,
+};
+use windows_capture_provider::{LabelledWinResult, WinError};
+
+use crate::utilities::failure::Ignore;
+
+use super::{ClipboardFormat, Flavour};
+
+/// How many times opening the clipboard is tried, another application may briefly have it open.
+const OPEN_ATTEMPTS: u32 = 10;
+
+/// How long to wait between attempts to open the clipboard.
+const OPEN_RETRY_DELAY: Duration = Duration::from_millis(20);
+
+/// Replaces the contents of the clipboard with the flavours.
+pub fn write(flavours: &[Flavour]) -> LabelledWinResult<()> {
+    open_clipboard()?;
+
+    let result = unsafe { set_flavours(flavours) };
+    let close_result = unsafe { CloseClipboard() }.map_err(|e| WinError::new(e, "CloseClipboard"));
+
+    result.and(close_result)
+}
+
+fn open_clipboard() -> LabelledWinResult<()> {
+    let mut attempt = 1;
+
+    loop {
+        match unsafe { OpenClipboard(None) } {
+            Ok(()) => return Ok(()),
+            Err(e) if attempt >= OPEN_ATTEMPTS => return Err(WinError::new(e, "OpenClipboard")),
+            Err(_) => {
+                attempt += 1;
+                thread::sleep(OPEN_RETRY_DELAY);
+            }
+        }
+    }
+}
+
+/// Empties the open clipboard and sets each flavour, the clipboard owns the memory once it is set.
+unsafe fn set_flavours(flavours: &[Flavour]) -> LabelledWinResult<()> {
+    unsafe { EmptyClipboard() }.map_err(|e| WinError::new(e, "EmptyClipboard"))?;
+
+    for flavour in flavours {
+        let format = format_id(flavour.format)?;
+
+        let memory = unsafe { GlobalAlloc(GMEM_MOVEABLE, flavour.data.len()) }
+            .map_err(|e| WinError::new(e, "GlobalAlloc"))?;
+
+        let pointer = unsafe { GlobalLock(memory) };
+        if pointer.is_null() {
+            let error = unsafe { GetLastError() };
+            unsafe { GlobalFree(Some(memory)) }.ignore();
+            return Err(WinError::from_win32(error, "GlobalLock"));
+        }
+
+        unsafe {
+            ptr::copy_nonoverlapping(
+                flavour.data.as_ptr(),
+                pointer.cast::<u8>(),
+                flavour.data.len(),
+            );
+
+            // Returns an error once the memory is no longer locked, which is expected here.
+            let _ = GlobalUnlock(memory);
+        }
+
+        if let Err(e) = unsafe { SetClipboardData(format, Some(HANDLE(memory.0))) } {
+            unsafe { GlobalFree(Some(memory)) }.ignore();
+            return Err(WinError::new(e, "SetClipboardData"));
+        }
+    }
+
+    Ok(())
+}
+
+/// The clipboard format identifier, registering the format if it is not a standard format.
+fn format_id(format: ClipboardFormat) -> LabelledWinResult<u32> {
+    let name = match format {
+        ClipboardFormat::Bitmap => return Ok(u32::from(CF_DIBV5.0)),
+        ClipboardFormat::File => return Ok(u32::from(CF_HDROP.0)),
+        ClipboardFormat::Png => w!("PNG"),
+        ClipboardFormat::Html => w!("HTML Format"),
+        ClipboardFormat::Exr => w!("image/x-exr"),
+    };
+
+    match unsafe { RegisterClipboardFormatW(name) } {
+        0 => Err(WinError::from_win32(
+            unsafe { GetLastError() },
+            "RegisterClipboardFormatW",
+        )),
+        id => Ok(id),
+    }
+}
@@ -0,0 +1,145 @@
+use core::fmt::Write;
+
+use chrono::{
+    DateTime, Local,
+    format::{Item, StrftimeItems},
+};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use tracing::warn;
+
+/// The characters that cannot be in a file name.
+const RESERVED_CHARACTERS: [char; 9] = ['<', '>', ':', '"', '/', '\\', '|', '?', '*'];
+
+const DEFAULT_SCREENSHOT: &str = "Screenshot %F %H%M%S";
+const DEFAULT_RECORDING: &str = "Recording %F %H%M%S";
+const DEFAULT_BURST: &str = "Burst %F %H%M%S";
+
+/// The names captures are saved with, without the extension. Each is a pattern where `%` codes
+/// are replaced with the time the capture was taken, like `Screenshot %F %H%M%S`.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FileNames {
+    pub screenshot: String,
+    pub recording: String,
+
+    /// The name of a burst, each capture is numbered after it.
+    pub burst: String,
+}
+
+/// Why a file name pattern cannot be used.
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum FileNameError {
+    #[error("is empty")]
+    Empty,
+
+    #[error("has a `%` code that is not a time")]
+    InvalidCode,
+
+    #[error("contains `{0}`, which cannot be in a file name")]
+    ReservedCharacter(char),
+}
+
+impl Default for FileNames {
+    fn default() -> Self {
+        Self {
+            screenshot: String::from(DEFAULT_SCREENSHOT),
+            recording: String::from(DEFAULT_RECORDING),
+            burst: String::from(DEFAULT_BURST),
+        }
+    }
+}
+
+impl FileNames {
+    /// The name of a screenshot taken now.
+    pub fn screenshot_name(&self) -> String {
+        name_now(&self.screenshot, DEFAULT_SCREENSHOT)
+    }
+
+    /// The name of a recording started now.
+    pub fn recording_name(&self) -> String {
+        name_now(&self.recording, DEFAULT_RECORDING)
+    }
+
+    /// The name of a burst started now.
+    pub fn burst_name(&self) -> String {
+        name_now(&self.burst, DEFAULT_BURST)
+    }
+}
+
+/// Formats a pattern with the current time, using the default pattern if it cannot be used.
+fn name_now(pattern: &str, default: &str) -> String {
+    let now = Local::now();
+
+    format_file_name(pattern, &now).unwrap_or_else(|error| {
+        warn!("The file name \"{pattern}\" {error}, the default name is used");
+        format_file_name(default, &now).unwrap_or_else(|_| String::from("Capture"))
+    })
+}
+
+/// Replaces the `%` codes in a file name pattern with a time.
+pub fn format_file_name(pattern: &str, time: &DateTime<Local>) -> Result<String, FileNameError> {
+    if StrftimeItems::new(pattern).any(|item| item == Item::Error) {
+        return Err(FileNameError::InvalidCode);
+    }
+
+    // Some codes, like time zone names, can fail even though they parse.
+    let mut name = String::new();
+    write!(
+        name,
+        "{}",
+        time.format_with_items(StrftimeItems::new(pattern))
+    )
+    .map_err(|_| FileNameError::InvalidCode)?;
+
+    let name = name.trim();
+    if name.is_empty() {
+        return Err(FileNameError::Empty);
+    }
+
+    if let Some(character) = name
+        .chars()
+        .find(|character| RESERVED_CHARACTERS.contains(character) || character.is_control())
+    {
+        return Err(FileNameError::ReservedCharacter(character));
+    }
+
+    Ok(name.to_string())
+}
+
+#[cfg(test)]
+mod test {
+    use chrono::{Local, TimeZone};
+
+    use super::{FileNameError, FileNames, format_file_name};
+
+    #[test]
+    fn formats_the_time() {
+        let time = Local.with_ymd_and_hms(2024, 1, 2, 3, 4, 5).unwrap();
+
+        let names = FileNames::default();
+        assert_eq!(
+            format_file_name(&names.screenshot, &time),
+            Ok(String::from("Screenshot 2024-01-02 030405"))
+        );
+        assert_eq!(
+            format_file_name("%Y/%m", &time),
+            Err(FileNameError::ReservedCharacter('/'))
+        );
+    }
+
+    #[test]
+    fn rejects_unusable_patterns() {
+        let time = Local.with_ymd_and_hms(2024, 1, 2, 3, 4, 5).unwrap();
+
+        assert_eq!(format_file_name("  ", &time), Err(FileNameError::Empty));
+        assert_eq!(
+            format_file_name("Screenshot %Q", &time),
+            Err(FileNameError::InvalidCode)
+        );
+        assert_eq!(
+            format_file_name("Screenshot?", &time),
+            Err(FileNameError::ReservedCharacter('?'))
+        );
+    }
+}
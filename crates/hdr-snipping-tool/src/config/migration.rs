@@ -0,0 +1,345 @@
+use std::{fs, path::Path};
+
+use toml::{Table, Value};
+use tracing::{info, warn};
+
+use crate::utilities::failure::Failure;
+
+use super::{Config, ConfigError};
+
+/// The version of the config schema, increased whenever a change would stop older files from
+/// loading the same way.
+pub const CURRENT_VERSION: u32 = 2;
+
+/// The migrations between versions, `MIGRATIONS[0]` migrates version 1 to version 2 and so on.
+const MIGRATIONS: [fn(&mut Table); CURRENT_VERSION as usize - 1] = [hotkey_bindings];
+
+/// A config file that was migrated to the current version.
+#[derive(Debug)]
+pub struct Migrated {
+    /// The version the file was written in.
+    pub from_version: u32,
+
+    /// The file, migrated to the current version.
+    pub contents: String,
+}
+
+impl Migrated {
+    /// Backs up the original file next to it and replaces it with the migrated file. The original
+    /// is left as it is if it could not be backed up.
+    pub fn save(&self, path: &Path, original: &str) {
+        let backup_path = path.with_extension(format!("v{}.backup.toml", self.from_version));
+
+        if let Err(e) = fs::write(&backup_path, original) {
+            warn!(
+                "Could not back up the config before migrating it, the file is left unchanged: {e}"
+            );
+            return;
+        }
+
+        // The migrated contents are still used, the file is migrated again the next time.
+        if let Err(e) = fs::write(path, self.contents.as_bytes()) {
+            warn!("Could not save the migrated config: {e}");
+            return;
+        }
+
+        info!(
+            "Migrated the config from version {} to {CURRENT_VERSION}, the original is backed up at {}",
+            self.from_version,
+            backup_path.display()
+        );
+    }
+}
+
+/// Migrates the contents of a config file to the current version, `None` if the file is already
+/// the current version.
+pub fn migrate(contents: &str) -> Result<Option<Migrated>, Vec<ConfigError>> {
+    let mut table: Table =
+        toml::from_str(contents).map_err(|error| vec![ConfigError::parse(&error, contents)])?;
+
+    let from_version = version(&table)
+        .map_err(|problem| vec![ConfigError::invalid(contents, "version", problem)])?;
+    if from_version == CURRENT_VERSION {
+        return Ok(None);
+    }
+
+    for migration in &MIGRATIONS[from_version as usize - 1..] {
+        migration(&mut table);
+    }
+    table.insert(
+        String::from("version"),
+        Value::Integer(i64::from(CURRENT_VERSION)),
+    );
+
+    let contents = toml::to_string_pretty(&table)
+        .report_and_panic("Could not write the migrated configuration file");
+
+    Ok(Some(Migrated {
+        from_version,
+        contents,
+    }))
+}
+
+/// The version a config file was written in, files from before versioning are version `1`.
+fn version(table: &Table) -> Result<u32, String> {
+    let Some(value) = table.get("version") else {
+        return Ok(1);
+    };
+
+    let Some(version) = value.as_integer() else {
+        return Err(format!("must be a whole number, not {value}"));
+    };
+
+    match u32::try_from(version) {
+        Ok(version @ 1..=CURRENT_VERSION) => Ok(version),
+        Ok(version) if version > CURRENT_VERSION => Err(format!(
+            "is {version}, the file is from a newer version of HDR Snipping Tool which this \
+            version cannot read, the newest version it can read is {CURRENT_VERSION}"
+        )),
+        _ => Err(format!(
+            "must be between 1 and {CURRENT_VERSION}, not {version}"
+        )),
+    }
+}
+
+/// Version 2 replaced the keys each set in their own section, without modifiers, with a list of
+/// hotkey bindings.
+fn hotkey_bindings(table: &mut Table) {
+    let mut take_key = |section: &str| {
+        table
+            .get_mut(section)
+            .and_then(Value::as_table_mut)
+            .and_then(|section| section.remove("key"))
+    };
+    let instant_screenshot_key = take_key("instant_screenshot");
+    let burst_key = take_key("burst");
+    let screenshot_key = table
+        .remove("screenshot_key")
+        .unwrap_or_else(|| Value::String(String::from("PrintScreen")));
+
+    // Files written between hotkey bindings and versioning already have bindings.
+    if table.contains_key("hotkeys") {
+        return;
+    }
+
+    let keys = [
+        (Some(screenshot_key), "region_capture"),
+        (instant_screenshot_key, "full_capture"),
+        (burst_key, "burst"),
+    ];
+
+    let bindings = keys
+        .into_iter()
+        .filter_map(|(key, action)| {
+            let mut binding = Table::new();
+            binding.insert(String::from("shortcut"), key?);
+            binding.insert(String::from("action"), Value::String(String::from(action)));
+            Some(Value::Table(binding))
+        })
+        .collect();
+
+    table.insert(String::from("hotkeys"), Value::Array(bindings));
+}
+
+/// The keys in the file that are not settings, written like `recording.frame_rat`. They are ignored
+/// when the file is loaded.
+pub fn unknown_keys(table: &Table) -> Vec<String> {
+    let known = Table::try_from(Config::default())
+        .report_and_panic("Could not list the configuration settings");
+
+    let mut unknown = Vec::new();
+    find_unknown_keys(table, &known, "", &mut unknown);
+    unknown
+}
+
+fn find_unknown_keys(table: &Table, known: &Table, path: &str, unknown: &mut Vec<String>) {
+    for (key, value) in table {
+        let key_path = if path.is_empty() {
+            key.clone()
+        } else {
+            format!("{path}.{key}")
+        };
+
+        match (value, known.get(key)) {
+            (_, None) => unknown.push(key_path),
+
+            (Value::Table(table), Some(Value::Table(known))) => {
+                find_unknown_keys(table, known, &key_path, unknown);
+            }
+
+            // Arrays of tables are checked against the first default entry, if there is one.
+            (Value::Array(array), Some(Value::Array(known))) => {
+                let Some(Value::Table(known)) = known.first() else {
+                    continue;
+                };
+
+                for (index, value) in array.iter().enumerate() {
+                    if let Value::Table(table) = value {
+                        find_unknown_keys(table, known, &format!("{key_path}[{index}]"), unknown);
+                    }
+                }
+            }
+
+            // A value of the wrong type is a parse error rather than an unknown key.
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use global_hotkey::hotkey::{Code, Modifiers};
+
+    use crate::{
+        capture_taker::WhitepointStrategy,
+        config::{Config, ConfigError},
+        hotkeys::{HotkeyAction, HotkeyBinding, Shortcut},
+        recording::RecordingFormat,
+    };
+
+    use super::{CURRENT_VERSION, migrate};
+
+    /// Loads a file the way the application does, returning the version it was migrated from.
+    fn load(contents: &str) -> (Config, Option<u32>) {
+        let migrated = migrate(contents).unwrap();
+        let from_version = migrated.as_ref().map(|migrated| migrated.from_version);
+        let contents = migrated.map_or_else(|| contents.to_string(), |migrated| migrated.contents);
+
+        // A migrated file does not need migrating again.
+        assert!(migrate(&contents).unwrap().is_none());
+
+        let loaded = Config::parse(&contents).unwrap();
+        assert!(loaded.warnings.is_empty(), "{:?}", loaded.warnings);
+        (loaded.config, from_version)
+    }
+
+    fn binding(shortcut: &str, action: HotkeyAction) -> HotkeyBinding {
+        HotkeyBinding {
+            shortcut: shortcut.parse().unwrap(),
+            action,
+        }
+    }
+
+    #[test]
+    fn migrates_version_1() {
+        let (config, from_version) = load(include_str!("fixtures/v1.toml"));
+
+        assert_eq!(from_version, Some(1));
+        assert_eq!(
+            config,
+            Config {
+                hotkeys: vec![HotkeyBinding {
+                    shortcut: Shortcut::new(Modifiers::empty(), Code::F8),
+                    action: HotkeyAction::RegionCapture,
+                }],
+                ..Config::default()
+            }
+        );
+    }
+
+    #[test]
+    fn migrates_version_1_sections() {
+        let (config, from_version) = load(include_str!("fixtures/v1_sections.toml"));
+
+        assert_eq!(from_version, Some(1));
+        assert_eq!(
+            config.hotkeys,
+            [
+                binding("PrintScreen", HotkeyAction::RegionCapture),
+                binding("F9", HotkeyAction::FullCapture),
+                binding("B", HotkeyAction::Burst),
+            ]
+        );
+        assert_eq!(config.screenshot_delay, 3);
+        assert!(!config.instant_screenshot.sound);
+        assert_eq!(config.burst.frames, 5);
+        assert_eq!(config.recording.format, RecordingFormat::Gif);
+        assert_eq!(config.history.max_entries, 50);
+    }
+
+    #[test]
+    fn migrates_version_1_hotkeys() {
+        let (config, from_version) = load(include_str!("fixtures/v1_hotkeys.toml"));
+
+        assert_eq!(from_version, Some(1));
+        assert_eq!(
+            config.hotkeys,
+            [
+                binding("Ctrl+Shift+S", HotkeyAction::RegionCapture),
+                binding("Ctrl+Shift+D", HotkeyAction::DelayedCapture(5)),
+            ]
+        );
+        assert_eq!(config.whitepoint, WhitepointStrategy::Hdr);
+    }
+
+    #[test]
+    fn loads_version_2() {
+        let (config, from_version) = load(include_str!("fixtures/v2.toml"));
+
+        assert_eq!(from_version, None);
+        assert_eq!(
+            config.hotkeys,
+            [
+                binding("PrintScreen", HotkeyAction::RegionCapture),
+                binding("Alt+PrintScreen", HotkeyAction::FullCapture),
+            ]
+        );
+        assert!(config.capture_virtual_desktop);
+    }
+
+    #[test]
+    fn saved_config_is_current() {
+        let saved = toml::to_string_pretty(&Config::default()).unwrap();
+
+        let (config, from_version) = load(&saved);
+        assert_eq!(from_version, None);
+        assert_eq!(config, Config::default());
+        assert_eq!(config.version, CURRENT_VERSION);
+    }
+
+    #[test]
+    fn rejects_newer_versions() {
+        let errors = migrate("version = 3\nscreenshot_delay = 1\n").unwrap_err();
+
+        assert!(matches!(
+            &errors[..],
+            [ConfigError::Invalid { field, .. }] if field == "version"
+        ));
+    }
+
+    #[test]
+    fn warns_about_unknown_keys() {
+        let contents = r#"
+version = 2
+screenshot_dealy = 3
+
+[[hotkeys]]
+shortcut = "PrintScreen"
+action = "region_capture"
+modifiers = "ctrl"
+
+[recording]
+frame_rat = 30
+"#;
+
+        let loaded = Config::parse(contents).unwrap();
+        let unknown: Vec<_> = loaded
+            .warnings
+            .iter()
+            .map(|warning| match warning {
+                ConfigError::Unknown { location, field } => (field.as_str(), location.line),
+                _ => panic!("unexpected error: {warning}"),
+            })
+            .collect();
+
+        assert_eq!(
+            unknown,
+            [
+                ("hotkeys[0].modifiers", 8),
+                ("recording.frame_rat", 11),
+                ("screenshot_dealy", 3),
+            ]
+        );
+        assert_eq!(loaded.config.screenshot_delay, 0);
+    }
+}
@@ -0,0 +1,280 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use global_hotkey::hotkey::{Code, Modifiers};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    annotation::RedactionStyle,
+    burst::BurstSettings,
+    capture_history::HistoryLimits,
+    capture_taker::{MonitorOverride, WhitepointStrategy},
+    clipboard::ClipboardSettings,
+    config_dir,
+    error_reporting::{ErrorCategory, report_details},
+    hotkeys::{HotkeyAction, HotkeyBinding, Shortcut},
+    recording::RecordingSettings,
+    save_hooks::SaveHook,
+    save_notification::NotificationSettings,
+    utilities::failure::Failure,
+};
+
+use migration::CURRENT_VERSION;
+
+pub use file_names::{FileNameError, FileNames, format_file_name};
+pub use validation::{ConfigError, describe};
+pub use watcher::ConfigWatcher;
+
+mod file_names;
+mod migration;
+mod validation;
+mod watcher;
+
+const FILE_NAME: &str = "hdr-config.toml";
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Config {
+    /// The version of the config schema the file was written in, older files are migrated when
+    /// they are loaded.
+    #[serde(default = "current_version")]
+    pub version: u32,
+
+    /// The global hotkeys, each with a shortcut like `"Ctrl+Shift+S"` and the action it takes.
+    #[serde(default = "default_hotkeys")]
+    pub hotkeys: Vec<HotkeyBinding>,
+
+    /// How many seconds the screenshot key counts down before capturing, `0` captures immediately.
+    #[serde(default)]
+    pub screenshot_delay: u32,
+
+    /// If screenshots should cover every monitor instead of only the hovered monitor.
+    #[serde(default)]
+    pub capture_virtual_desktop: bool,
+
+    /// If screenshots should be of a single window, picked by clicking it in the screenshot.
+    #[serde(default)]
+    pub capture_window: bool,
+
+    /// How the whitepoint of a capture is chosen.
+    #[serde(default)]
+    pub whitepoint: WhitepointStrategy,
+
+    /// Corrections for monitors that report inaccurate brightness, each for a monitor picked out
+    /// by its device name or EDID identifier.
+    #[serde(default)]
+    pub monitors: Vec<MonitorOverride>,
+
+    /// How redactions hide the pixels under them.
+    #[serde(default)]
+    pub redaction_style: RedactionStyle,
+
+    /// The names captures are saved with.
+    #[serde(default)]
+    pub file_names: FileNames,
+
+    /// How many past captures are kept for re-editing, and for how long.
+    #[serde(default)]
+    pub history: HistoryLimits,
+
+    /// How regions are recorded.
+    #[serde(default)]
+    pub recording: RecordingSettings,
+
+    /// How bursts of captures are taken.
+    #[serde(default)]
+    pub burst: BurstSettings,
+
+    /// How a full capture confirms the screenshot was saved, as the screenshot is not shown.
+    #[serde(default)]
+    pub instant_screenshot: InstantScreenshotSettings,
+
+    /// The formats screenshots are copied to the clipboard in.
+    #[serde(default)]
+    pub clipboard: ClipboardSettings,
+
+    /// The notification shown once a screenshot is saved.
+    #[serde(default)]
+    pub notification: NotificationSettings,
+
+    /// The actions taken on each file once it is saved, in order.
+    #[serde(default)]
+    pub save_hooks: Vec<SaveHook>,
+}
+
+/// How screenshots that are saved without showing the screenshot are confirmed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct InstantScreenshotSettings {
+    /// If a sound plays once the screenshot is saved.
+    pub sound: bool,
+
+    /// If a tick is shown in the corner of the screen once the screenshot is saved.
+    pub flash: bool,
+}
+
+impl Default for InstantScreenshotSettings {
+    fn default() -> Self {
+        Self {
+            sound: true,
+            flash: true,
+        }
+    }
+}
+
+/// A loaded config, with the problems that did not stop it from loading.
+#[derive(Debug)]
+pub struct LoadedConfig {
+    pub config: Config,
+
+    /// The keys in the file that are not settings, and were ignored.
+    pub warnings: Vec<ConfigError>,
+}
+
+impl Config {
+    /// Loads the config file, migrating it if it is from an older version, `None` if there is no
+    /// config file.
+    pub fn try_load_config() -> Result<Option<LoadedConfig>, Vec<ConfigError>> {
+        Self::load_from(&Self::file_path())
+    }
+
+    /// Loads the config file at the path, a file that cannot be read is a problem rather than a
+    /// failure so a file that is still being written keeps the current config.
+    fn load_from(path: &Path) -> Result<Option<LoadedConfig>, Vec<ConfigError>> {
+        let bytes = match fs::read(path) {
+            Ok(bytes) => bytes,
+            Err(error) if error.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(error) => return Err(vec![ConfigError::Read(error.to_string())]),
+        };
+
+        let contents = String::from_utf8(bytes)
+            .map_err(|error| vec![ConfigError::Encoding(error.utf8_error().to_string())])?;
+
+        // Problems are reported against the migrated file, as that is what is now saved.
+        let contents = match migration::migrate(&contents)? {
+            Some(migrated) => {
+                migrated.save(path, &contents);
+                migrated.contents
+            }
+            None => contents,
+        };
+
+        Self::parse(&contents).map(Some)
+    }
+
+    /// Parses and validates the contents of a config file of the current version.
+    pub fn parse(contents: &str) -> Result<LoadedConfig, Vec<ConfigError>> {
+        let parse_error = |error: toml::de::Error| vec![ConfigError::parse(&error, contents)];
+
+        // The contents are parsed rather than a table, so errors have a position in the file.
+        let config: Self = toml::from_str(contents).map_err(parse_error)?;
+        let table: toml::Table = toml::from_str(contents).map_err(parse_error)?;
+
+        let errors = config.validate(contents);
+        if !errors.is_empty() {
+            return Err(errors);
+        }
+
+        let warnings = migration::unknown_keys(&table)
+            .into_iter()
+            .map(|field| ConfigError::unknown(contents, field))
+            .collect();
+
+        Ok(LoadedConfig { config, warnings })
+    }
+
+    pub fn save(&self) {
+        let toml_string =
+            toml::to_string_pretty(self).report_and_panic("Could not save the configuration file");
+
+        fs::write(Self::file_path(), toml_string.as_bytes())
+            .report_and_panic("Could not save the configuration file");
+    }
+
+    pub fn file_path() -> PathBuf {
+        config_dir().join(FILE_NAME)
+    }
+}
+
+/// Lists the keys that were ignored when loading the config, without interrupting the application.
+pub fn report_warnings(warnings: &[ConfigError]) {
+    if warnings.is_empty() {
+        return;
+    }
+
+    report_details(
+        ErrorCategory::Config,
+        describe(warnings),
+        "The config file has keys that are not settings, they may be misspelled",
+    );
+}
+
+/// Reports the problems that stopped the config file from loading, the problems are shown as they
+/// need to be fixed in the file.
+pub fn report_problems(problems: String, message: &str) {
+    report_details(ErrorCategory::Config, problems, message);
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            version: CURRENT_VERSION,
+            hotkeys: default_hotkeys(),
+            screenshot_delay: 0,
+            capture_virtual_desktop: false,
+            capture_window: false,
+            whitepoint: WhitepointStrategy::default(),
+            monitors: Vec::new(),
+            redaction_style: RedactionStyle::default(),
+            file_names: FileNames::default(),
+            history: HistoryLimits::default(),
+            recording: RecordingSettings::default(),
+            burst: BurstSettings::default(),
+            instant_screenshot: InstantScreenshotSettings::default(),
+            clipboard: ClipboardSettings::default(),
+            notification: NotificationSettings::default(),
+            save_hooks: Vec::new(),
+        }
+    }
+}
+
+fn current_version() -> u32 {
+    CURRENT_VERSION
+}
+
+fn default_hotkeys() -> Vec<HotkeyBinding> {
+    vec![HotkeyBinding {
+        shortcut: Shortcut::new(Modifiers::empty(), Code::PrintScreen),
+        action: HotkeyAction::RegionCapture,
+    }]
+}
+
+#[cfg(test)]
+mod test {
+    use std::fs;
+
+    use super::{Config, ConfigError};
+
+    #[test]
+    fn unreadable_file_is_a_problem() {
+        let directory = std::env::temp_dir()
+            .join("hdr-snipping-tool-tests")
+            .join(format!("config-unreadable-{}", std::process::id()));
+        fs::create_dir_all(&directory).unwrap();
+
+        let missing = Config::load_from(&directory.join("missing.toml"));
+        assert!(matches!(missing, Ok(None)));
+
+        let not_text = directory.join("not-text.toml");
+        fs::write(&not_text, b"screenshot_delay = 3\n\xff\xfe").unwrap();
+        let errors = Config::load_from(&not_text).unwrap_err();
+        assert!(matches!(errors.as_slice(), [ConfigError::Encoding(_)]));
+        assert!(!errors[0].is_read());
+
+        // A directory cannot be read as a file.
+        let errors = Config::load_from(&directory).unwrap_err();
+        assert!(matches!(errors.as_slice(), [ConfigError::Read(_)]));
+        assert!(errors[0].is_read());
+    }
+}
@@ -0,0 +1,459 @@
+use core::{fmt, ops::RangeInclusive};
+
+use chrono::Local;
+use thiserror::Error;
+
+use crate::{hotkeys::HotkeyAction, save_hooks::SaveHookAction};
+
+use super::{Config, format_file_name};
+
+/// A problem with the config file.
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum ConfigError {
+    /// The file is not valid TOML, or a value has the wrong type.
+    #[error("{location}: {message}")]
+    Parse { location: Location, message: String },
+
+    /// A value the application cannot use.
+    #[error("{location}: `{field}` {problem}")]
+    Invalid {
+        location: Location,
+        field: String,
+        problem: String,
+    },
+
+    /// A key that is not a setting, it is ignored so files keep loading as settings change.
+    #[error("{location}: `{field}` is not a setting, it is ignored")]
+    Unknown { location: Location, field: String },
+
+    /// The file could not be read, an editor may still be writing it.
+    #[error("Could not read the file: {0}")]
+    Read(String),
+
+    /// The file is not text.
+    #[error("The file is not valid UTF-8: {0}")]
+    Encoding(String),
+}
+
+/// Where in the config file a problem is, both start from `1`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Location {
+    pub line: usize,
+    pub column: usize,
+}
+
+impl fmt::Display for Location {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Line {}, column {}", self.line, self.column)
+    }
+}
+
+impl ConfigError {
+    /// If the problem may be gone when the file is read again.
+    pub fn is_read(&self) -> bool {
+        matches!(self, Self::Read(_))
+    }
+
+    /// Converts a TOML error into a problem at its position in the file.
+    pub fn parse(error: &toml::de::Error, contents: &str) -> Self {
+        let offset = error.span().map_or(0, |span| span.start);
+
+        Self::Parse {
+            location: Location::of_offset(contents, offset),
+            message: error.message().trim().to_string(),
+        }
+    }
+
+    /// A problem with the value of a field, written like `recording.frame_rate` or
+    /// `hotkeys[1].action`.
+    pub(super) fn invalid(contents: &str, field: &str, problem: String) -> Self {
+        Self::Invalid {
+            location: Location::of_field(contents, field),
+            field: field.to_string(),
+            problem,
+        }
+    }
+
+    /// A key that is not a setting, written like a field in [`Self::invalid`].
+    pub(super) fn unknown(contents: &str, field: String) -> Self {
+        Self::Unknown {
+            location: Location::of_field(contents, &field),
+            field,
+        }
+    }
+}
+
+impl Location {
+    fn of_offset(contents: &str, offset: usize) -> Self {
+        let before = &contents[..offset.min(contents.len())];
+        let line_start = before.rfind('\n').map_or(0, |index| index + 1);
+
+        Self {
+            line: before.matches('\n').count() + 1,
+            column: before[line_start..].chars().count() + 1,
+        }
+    }
+
+    /// Finds the line a field is set on, or the start of the file if it is not found.
+    fn of_field(contents: &str, field: &str) -> Self {
+        let (table, key) = match field.rsplit_once('.') {
+            Some((table, key)) => (Some(table), key),
+            None => (None, field),
+        };
+
+        // Array tables are written as `name[index]`.
+        let table = table.map(|table| match table.split_once('[') {
+            Some((name, index)) => (name, index.trim_end_matches(']').parse().ok()),
+            None => (table, None),
+        });
+
+        let mut current: Option<(&str, Option<usize>)> = None;
+        let mut array_counts: Vec<(&str, usize)> = Vec::new();
+
+        for (index, line) in contents.lines().enumerate() {
+            let trimmed = line.trim_start();
+
+            if let Some(name) = trimmed
+                .strip_prefix("[[")
+                .and_then(|rest| rest.split_once("]]"))
+                .map(|(name, _)| name.trim())
+            {
+                let count = match array_counts.iter_mut().find(|(array, _)| *array == name) {
+                    Some((_, count)) => {
+                        *count += 1;
+                        *count
+                    }
+                    None => {
+                        array_counts.push((name, 0));
+                        0
+                    }
+                };
+                current = Some((name, Some(count)));
+                continue;
+            }
+
+            if let Some(name) = trimmed
+                .strip_prefix('[')
+                .and_then(|rest| rest.split_once(']'))
+                .map(|(name, _)| name.trim())
+            {
+                current = Some((name, None));
+                continue;
+            }
+
+            let sets_key = trimmed
+                .strip_prefix(key)
+                .is_some_and(|rest| rest.trim_start().starts_with('='));
+            if sets_key && current == table {
+                return Self {
+                    line: index + 1,
+                    column: line.len() - trimmed.len() + 1,
+                };
+            }
+        }
+
+        Self { line: 1, column: 1 }
+    }
+}
+
+impl Config {
+    /// Checks the values that parse but that the application cannot use, `contents` is the file
+    /// the config was parsed from.
+    pub fn validate(&self, contents: &str) -> Vec<ConfigError> {
+        let mut errors = Vec::new();
+
+        let mut check = |field: &str, value: u32, range: RangeInclusive<u32>, unit: &str| {
+            if !range.contains(&value) {
+                let problem = if *range.end() == u32::MAX {
+                    format!("must be at least {}{unit}, not {value}", range.start())
+                } else {
+                    format!(
+                        "must be between {} and {}{unit}, not {value}",
+                        range.start(),
+                        range.end()
+                    )
+                };
+                errors.push(ConfigError::invalid(contents, field, problem));
+            }
+        };
+
+        check(
+            "screenshot_delay",
+            self.screenshot_delay,
+            0..=60,
+            " seconds",
+        );
+
+        for (index, binding) in self.hotkeys.iter().enumerate() {
+            if let HotkeyAction::DelayedCapture(seconds) = binding.action {
+                let field = format!("hotkeys[{index}].action");
+                check(&field, seconds, 1..=60, " seconds");
+            }
+        }
+
+        check(
+            "history.max_size_mb",
+            self.history.max_size_mb,
+            1..=u32::MAX,
+            " MB",
+        );
+        check(
+            "history.max_age_days",
+            self.history.max_age_days,
+            1..=u32::MAX,
+            " days",
+        );
+
+        check(
+            "recording.frame_rate",
+            self.recording.frame_rate,
+            1..=60,
+            " frames per second",
+        );
+        check(
+            "recording.max_seconds",
+            self.recording.max_seconds,
+            1..=600,
+            " seconds",
+        );
+
+        check(
+            "burst.interval_ms",
+            self.burst.interval_ms,
+            50..=60_000,
+            " milliseconds",
+        );
+        check(
+            "burst.kept_frames",
+            self.burst.kept_frames,
+            0..=64,
+            " frames",
+        );
+
+        check(
+            "notification.seconds",
+            self.notification.seconds,
+            1..=60,
+            " seconds",
+        );
+
+        for (index, hook) in self.save_hooks.iter().enumerate() {
+            let field = format!("save_hooks[{index}].timeout_seconds");
+            check(&field, hook.timeout_seconds, 1..=3600, " seconds");
+        }
+
+        let now = Local::now();
+        let file_names = [
+            ("file_names.screenshot", &self.file_names.screenshot),
+            ("file_names.recording", &self.file_names.recording),
+            ("file_names.burst", &self.file_names.burst),
+        ];
+        for (field, pattern) in file_names {
+            if let Err(error) = format_file_name(pattern, &now) {
+                errors.push(ConfigError::invalid(contents, field, error.to_string()));
+            }
+        }
+
+        for (index, monitor) in self.monitors.iter().enumerate() {
+            let field = |name: &str| format!("monitors[{index}].{name}");
+
+            if monitor.monitor.trim().is_empty() {
+                errors.push(ConfigError::invalid(
+                    contents,
+                    &field("monitor"),
+                    String::from("must be a device name or EDID identifier"),
+                ));
+            }
+
+            let luminances = [
+                ("max_brightness", monitor.max_brightness, false),
+                ("min_brightness", monitor.min_brightness, true),
+                ("sdr_white", monitor.sdr_white, false),
+            ];
+            for (name, nits, allows_zero) in luminances {
+                let Some(nits) = nits else {
+                    continue;
+                };
+
+                let is_valid = nits.is_finite() && (nits > 0.0 || (allows_zero && nits == 0.0));
+                if !is_valid {
+                    let least = if allows_zero { "at least" } else { "more than" };
+                    let problem = format!("must be {least} 0 nits, not {nits}");
+                    errors.push(ConfigError::invalid(contents, &field(name), problem));
+                }
+            }
+        }
+
+        for (index, hook) in self.save_hooks.iter().enumerate() {
+            let problem = match &hook.action {
+                SaveHookAction::Command(arguments)
+                    if arguments
+                        .first()
+                        .is_none_or(|program| program.trim().is_empty()) =>
+                {
+                    Some("must start with the program to run")
+                }
+                SaveHookAction::MoveTo(folder) if folder.as_os_str().is_empty() => {
+                    Some("must be the folder to move the file to")
+                }
+                _ => None,
+            };
+            if let Some(problem) = problem {
+                errors.push(ConfigError::invalid(
+                    contents,
+                    &format!("save_hooks[{index}].action"),
+                    problem.to_string(),
+                ));
+            }
+        }
+
+        errors
+    }
+}
+
+/// Lists the problems, one per line.
+pub fn describe(errors: &[ConfigError]) -> String {
+    let lines: Vec<_> = errors.iter().map(|error| format!("• {error}")).collect();
+    lines.join("\n")
+}
+
+#[cfg(test)]
+mod test {
+    use crate::config::Config;
+
+    use super::{ConfigError, Location};
+
+    fn load(contents: &str) -> Result<Config, Vec<ConfigError>> {
+        Config::parse(contents).map(|loaded| loaded.config)
+    }
+
+    #[test]
+    fn locates_syntax_errors() {
+        let contents = "screenshot_delay = 3\ncapture_window = yes\n";
+
+        let errors = load(contents).unwrap_err();
+        assert!(matches!(
+            errors[..],
+            [ConfigError::Parse {
+                location: Location {
+                    line: 2,
+                    column: 18
+                },
+                ..
+            }]
+        ));
+    }
+
+    #[test]
+    fn locates_invalid_values() {
+        let contents = r#"
+screenshot_delay = 3
+
+[[hotkeys]]
+shortcut = "PrintScreen"
+action = "region_capture"
+
+[[hotkeys]]
+shortcut = "Ctrl+D"
+action = { delayed_capture = 0 }
+
+[recording]
+format = "gif"
+  frame_rate = 120
+"#;
+
+        let errors = load(contents).unwrap_err();
+        let found: Vec<_> = errors
+            .iter()
+            .map(|error| match error {
+                ConfigError::Invalid {
+                    location, field, ..
+                } => (field.as_str(), location.line, location.column),
+                _ => panic!("unexpected error: {error}"),
+            })
+            .collect();
+
+        assert_eq!(
+            found,
+            [
+                ("hotkeys[1].action", 10, 1),
+                ("recording.frame_rate", 14, 3)
+            ]
+        );
+        assert_eq!(
+            errors[1].to_string(),
+            "Line 14, column 3: `recording.frame_rate` must be between 1 and 60 frames per second, not 120"
+        );
+    }
+
+    #[test]
+    fn validates_monitor_overrides() {
+        let contents = r#"
+[[monitors]]
+monitor = "DEL4123"
+max_brightness = 600.0
+min_brightness = 0.0
+
+[[monitors]]
+monitor = ""
+sdr_white = -80.0
+"#;
+
+        let errors = load(contents).unwrap_err();
+        let fields: Vec<_> = errors
+            .iter()
+            .map(|error| match error {
+                ConfigError::Invalid {
+                    location, field, ..
+                } => (field.as_str(), location.line),
+                _ => panic!("unexpected error: {error}"),
+            })
+            .collect();
+
+        assert_eq!(
+            fields,
+            [("monitors[1].monitor", 8), ("monitors[1].sdr_white", 9)]
+        );
+    }
+
+    #[test]
+    fn validates_save_hooks() {
+        let contents = r#"
+[[save_hooks]]
+action = { command = ["upload.cmd", "{path}"] }
+
+[[save_hooks]]
+action = { command = [] }
+timeout_seconds = 0
+
+[[save_hooks]]
+action = { move_to = "" }
+"#;
+
+        let errors = load(contents).unwrap_err();
+        let fields: Vec<_> = errors
+            .iter()
+            .map(|error| match error {
+                ConfigError::Invalid {
+                    location, field, ..
+                } => (field.as_str(), location.line),
+                _ => panic!("unexpected error: {error}"),
+            })
+            .collect();
+
+        assert_eq!(
+            fields,
+            [
+                ("save_hooks[1].timeout_seconds", 7),
+                ("save_hooks[1].action", 6),
+                ("save_hooks[2].action", 10),
+            ]
+        );
+    }
+
+    #[test]
+    fn defaults_are_valid() {
+        assert!(Config::default().validate("").is_empty());
+        assert!(load("").is_ok());
+    }
+}
@@ -0,0 +1,194 @@
+use core::{
+    fmt,
+    sync::atomic::{AtomicBool, Ordering},
+    time::Duration,
+};
+use std::{
+    fs,
+    path::Path,
+    sync::Arc,
+    thread::{self, JoinHandle},
+    time::SystemTime,
+};
+
+use tracing::{debug, info_span};
+use winit::event_loop::EventLoopProxy;
+
+use crate::{
+    application_event_loop::Event,
+    error_reporting::ErrorCategory,
+    utilities::failure::{Failure, Ignore},
+};
+
+use super::{Config, ConfigError, LoadedConfig, describe};
+
+/// How often the config file is checked for changes.
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Loads the config file once it has been changed and is no longer being written to, sending
+/// the result as [`Event::ConfigLoaded`]. The file is loaded on this thread, so reading it again
+/// while it is locked does not hold up the event loop.
+pub struct ConfigWatcher {
+    running: Arc<AtomicBool>,
+    reload_requested: Arc<AtomicBool>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl ConfigWatcher {
+    pub fn new(proxy: EventLoopProxy<Event>) -> Self {
+        let running = Arc::new(AtomicBool::new(true));
+        let reload_requested = Arc::new(AtomicBool::new(false));
+
+        let thread = {
+            let running = Arc::clone(&running);
+            let reload_requested = Arc::clone(&reload_requested);
+
+            thread::Builder::new()
+                .name(String::from("Config Watcher"))
+                .spawn(move || {
+                    let _span = info_span!("[Config Watcher]").entered();
+
+                    let path = Config::file_path();
+                    let mut changes = ChangeDebouncer::new(modified_time(&path));
+
+                    while running.load(Ordering::Relaxed) {
+                        thread::sleep(POLL_INTERVAL);
+
+                        let changed = changes.poll(modified_time(&path));
+                        let requested = reload_requested.swap(false, Ordering::Relaxed);
+                        if !changed && !requested {
+                            continue;
+                        }
+
+                        debug!("Reloading the config file");
+                        let Some(loaded) = load() else {
+                            continue;
+                        };
+
+                        if proxy.send_event(Event::ConfigLoaded(loaded)).is_err() {
+                            break;
+                        }
+                    }
+                })
+                .report_and_panic("Could not start the config watcher thread")
+        };
+
+        Self {
+            running,
+            reload_requested,
+            thread: Some(thread),
+        }
+    }
+
+    /// Loads the config file on the next poll, even if it has not changed.
+    pub fn request_reload(&self) {
+        self.reload_requested.store(true, Ordering::Relaxed);
+    }
+}
+
+impl Drop for ConfigWatcher {
+    fn drop(&mut self) {
+        self.running.store(false, Ordering::Relaxed);
+
+        if let Some(thread) = self.thread.take() {
+            thread.join().ignore();
+        }
+    }
+}
+
+/// The problems that stopped the config file from loading.
+struct LoadFailure(Vec<ConfigError>);
+
+impl fmt::Display for LoadFailure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", describe(&self.0))
+    }
+}
+
+/// Loads the config file, `None` if there is no config file. Only a file that could not be read is
+/// read again, as the problems in a file that was read stay until it is saved again.
+fn load() -> Option<Result<LoadedConfig, String>> {
+    let category = ErrorCategory::Config;
+    let loaded = category.retry_policy().run_if(
+        category,
+        |failure: &LoadFailure| failure.0.iter().any(ConfigError::is_read),
+        || Config::try_load_config().map_err(LoadFailure),
+    );
+
+    match loaded {
+        Ok(loaded) => loaded.map(Ok),
+        Err(failure) => Some(Err(failure.to_string())),
+    }
+}
+
+/// When the file was last modified, `None` if it does not exist.
+fn modified_time(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path)
+        .and_then(|metadata| metadata.modified())
+        .ok()
+}
+
+/// Reports a change once the modified time stops changing, so a file that is saved in several
+/// writes is only read once it is complete.
+struct ChangeDebouncer {
+    last_modified: Option<SystemTime>,
+    is_changing: bool,
+}
+
+impl ChangeDebouncer {
+    fn new(modified: Option<SystemTime>) -> Self {
+        Self {
+            last_modified: modified,
+            is_changing: false,
+        }
+    }
+
+    /// Returns if the file changed and has since stayed the same, a removed file is not a change.
+    fn poll(&mut self, modified: Option<SystemTime>) -> bool {
+        if modified != self.last_modified {
+            self.last_modified = modified;
+            self.is_changing = modified.is_some();
+            return false;
+        }
+
+        let changed = self.is_changing;
+        self.is_changing = false;
+        changed
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use core::time::Duration;
+    use std::time::SystemTime;
+
+    use super::ChangeDebouncer;
+
+    fn time(seconds: u64) -> Option<SystemTime> {
+        Some(SystemTime::UNIX_EPOCH + Duration::from_secs(seconds))
+    }
+
+    #[test]
+    fn reports_once_writes_settle() {
+        let mut changes = ChangeDebouncer::new(time(1));
+
+        let polls: Vec<_> = [time(1), time(2), time(3), time(3), time(3)]
+            .into_iter()
+            .map(|modified| changes.poll(modified))
+            .collect();
+
+        assert_eq!(polls, [false, false, false, true, false]);
+    }
+
+    #[test]
+    fn ignores_removed_file() {
+        let mut changes = ChangeDebouncer::new(time(1));
+
+        assert!(!changes.poll(None));
+        assert!(!changes.poll(None));
+
+        // Creating the file again is a change.
+        assert!(!changes.poll(time(2)));
+        assert!(changes.poll(time(2)));
+    }
+}
@@ -0,0 +1,383 @@
+use core::{
+    fmt::Write as _,
+    sync::atomic::{AtomicBool, Ordering},
+    time::Duration,
+};
+use std::{
+    collections::VecDeque,
+    fs, io,
+    panic::{self, PanicHookInfo},
+    path::{Path, PathBuf},
+    sync::{Mutex, MutexGuard, OnceLock, PoisonError},
+    thread,
+    time::{Instant, SystemTime, UNIX_EPOCH},
+};
+
+use tracing::{error, info};
+
+use crate::{
+    VERSION, capture_taker::ProviderMonitor, config::Config, config_dir, diagnostics_dir,
+    utilities::directories::LOG_FILE_NAME,
+};
+
+/// How many lines from the end of the log are kept in a bundle.
+const LOG_LINES: usize = 200;
+
+/// How many state changes are kept, the oldest are dropped first.
+const STATE_HISTORY: usize = 32;
+
+/// How many bundles are kept in the diagnostics directory, the oldest are removed first.
+const MAX_BUNDLES: usize = 5;
+
+const BUNDLE_PREFIX: &str = "crash-";
+const BUNDLE_EXTENSION: &str = "txt";
+
+/// What the application was doing, recorded as it runs and written to a bundle if it crashes.
+static DIAGNOSTICS: Mutex<Diagnostics> = Mutex::new(Diagnostics::new());
+
+/// When the application started, state changes are timed from it.
+static START: OnceLock<Instant> = OnceLock::new();
+
+/// If a bundle was written, only the first panic is written as later panics are often caused by
+/// it, like joining the thread that panicked.
+static WROTE_BUNDLE: AtomicBool = AtomicBool::new(false);
+
+/// The application's context that is written to a diagnostic bundle.
+#[derive(Debug)]
+pub struct Diagnostics {
+    /// The `Debug` output of the Vulkan instance, with the device name and API version.
+    vulkan: Option<String>,
+
+    /// The monitors of the last capture.
+    monitors: Vec<String>,
+
+    /// The states the application was in, with the time they were entered since it started.
+    states: VecDeque<(Duration, &'static str)>,
+
+    /// The config in use, as it would be saved.
+    config: Option<String>,
+}
+
+impl Diagnostics {
+    pub const fn new() -> Self {
+        Self {
+            vulkan: None,
+            monitors: Vec::new(),
+            states: VecDeque::new(),
+            config: None,
+        }
+    }
+
+    /// Records the state being entered, repeats of the current state are not recorded.
+    pub fn record_state(&mut self, elapsed: Duration, state: &'static str) {
+        if self
+            .states
+            .back()
+            .is_some_and(|(_, current)| *current == state)
+        {
+            return;
+        }
+
+        if self.states.len() == STATE_HISTORY {
+            self.states.pop_front();
+        }
+        self.states.push_back((elapsed, state));
+    }
+
+    /// Writes the bundle for a panic, with the last lines of the log.
+    pub fn bundle(&self, panic: &str, log: Option<&str>) -> String {
+        let none = || String::from("Not recorded\n");
+        let mut bundle = format!("HDR Snipping Tool v{VERSION} diagnostic bundle\n");
+
+        section(&mut bundle, "Panic", format!("{panic}\n"));
+
+        section(
+            &mut bundle,
+            "Vulkan",
+            self.vulkan
+                .as_ref()
+                .map_or_else(none, |vulkan| format!("{vulkan}\n")),
+        );
+
+        let monitors = if self.monitors.is_empty() {
+            none()
+        } else {
+            self.monitors
+                .iter()
+                .map(|monitor| format!("{monitor}\n"))
+                .collect()
+        };
+        section(&mut bundle, "Monitors", monitors);
+
+        let states = if self.states.is_empty() {
+            none()
+        } else {
+            self.states
+                .iter()
+                .map(|(elapsed, state)| format!("{:>10.3}s {state}\n", elapsed.as_secs_f64()))
+                .collect()
+        };
+        section(&mut bundle, "State History", states);
+
+        section(
+            &mut bundle,
+            "Config",
+            self.config.clone().unwrap_or_else(none),
+        );
+
+        let log = match log {
+            Some(log) => last_lines(log, LOG_LINES)
+                .iter()
+                .map(|line| format!("{line}\n"))
+                .collect(),
+            None => String::from("No log file\n"),
+        };
+        section(&mut bundle, &format!("Log (last {LOG_LINES} lines)"), log);
+
+        bundle
+    }
+}
+
+/// Writes the bundle for a panic to the diagnostics directory, then runs the previous hook.
+pub fn install_panic_hook() {
+    START.get_or_init(Instant::now);
+
+    let previous = panic::take_hook();
+    panic::set_hook(Box::new(move |info| {
+        if !WROTE_BUNDLE.swap(true, Ordering::SeqCst) {
+            write_panic_bundle(info);
+        }
+
+        previous(info);
+    }));
+}
+
+/// Records the Vulkan instance the application uses.
+pub fn record_vulkan(vulkan: &impl core::fmt::Debug) {
+    let vulkan = format!("{vulkan:#?}");
+    diagnostics().vulkan = Some(vulkan);
+}
+
+/// Records the monitors a capture was taken of.
+pub fn record_monitors<M: ProviderMonitor>(monitors: &[M]) {
+    let monitors = monitors
+        .iter()
+        .map(|monitor| format!("{:?} {:?}", monitor.identity(), monitor.info()))
+        .collect();
+    diagnostics().monitors = monitors;
+}
+
+/// Records the state the application entered.
+pub fn record_state(state: &'static str) {
+    let elapsed = START.get_or_init(Instant::now).elapsed();
+    diagnostics().record_state(elapsed, state);
+}
+
+/// Records the config the application uses.
+pub fn record_config(config: &Config) {
+    let config = match toml::to_string_pretty(config) {
+        Ok(config) => config,
+        Err(e) => format!("Could not serialise the config: {e}\n"),
+    };
+    diagnostics().config = Some(config);
+}
+
+/// Writes the bundle to a new file in the directory, removing the oldest bundles so at most
+/// `MAX_BUNDLES` are kept.
+pub fn write_bundle(directory: &Path, bundle: &str, time: SystemTime) -> io::Result<PathBuf> {
+    fs::create_dir_all(directory)?;
+
+    let seconds = time
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
+
+    let mut path = directory.join(format!("{BUNDLE_PREFIX}{seconds}.{BUNDLE_EXTENSION}"));
+    let mut index = 1;
+    while path.exists() {
+        path = directory.join(format!(
+            "{BUNDLE_PREFIX}{seconds}-{index}.{BUNDLE_EXTENSION}"
+        ));
+        index += 1;
+    }
+
+    fs::write(&path, bundle)?;
+    remove_old_bundles(directory)?;
+
+    Ok(path)
+}
+
+/// The last `count` lines of the text.
+pub fn last_lines(text: &str, count: usize) -> Vec<&str> {
+    let lines: Vec<_> = text.lines().collect();
+    lines[lines.len().saturating_sub(count)..].to_vec()
+}
+
+fn diagnostics() -> MutexGuard<'static, Diagnostics> {
+    DIAGNOSTICS.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+fn section(bundle: &mut String, title: &str, contents: String) {
+    let _ = write!(bundle, "\n## {title}\n\n{contents}");
+}
+
+/// Writes the bundle for the panic, failures are only logged as the application is exiting.
+fn write_panic_bundle(info: &PanicHookInfo<'_>) {
+    let thread = thread::current();
+    let panic = format!("Thread '{}' {info}", thread.name().unwrap_or("<unnamed>"));
+
+    // The log is written from a background thread, so the last few lines may be missing.
+    let log = fs::read(config_dir().join(LOG_FILE_NAME))
+        .ok()
+        .map(|log| String::from_utf8_lossy(&log).into_owned());
+
+    let bundle = diagnostics().bundle(&panic, log.as_deref());
+
+    match write_bundle(&diagnostics_dir(), &bundle, SystemTime::now()) {
+        Ok(path) => info!("Wrote the diagnostic bundle to {}", path.display()),
+        Err(e) => error!("Could not write the diagnostic bundle: {e}"),
+    }
+}
+
+/// Removes the oldest bundles in the directory until at most `MAX_BUNDLES` remain.
+fn remove_old_bundles(directory: &Path) -> io::Result<()> {
+    let mut bundles = Vec::new();
+    for entry in fs::read_dir(directory)? {
+        let entry = entry?;
+        let name = entry.file_name().to_string_lossy().into_owned();
+        if !name.starts_with(BUNDLE_PREFIX) || !name.ends_with(BUNDLE_EXTENSION) {
+            continue;
+        }
+
+        let modified = entry.metadata()?.modified().unwrap_or(UNIX_EPOCH);
+        bundles.push((modified, entry.path()));
+    }
+
+    if bundles.len() <= MAX_BUNDLES {
+        return Ok(());
+    }
+
+    bundles.sort();
+    for (_, path) in &bundles[..bundles.len() - MAX_BUNDLES] {
+        fs::remove_file(path)?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use core::time::Duration;
+    use std::{
+        fs,
+        path::PathBuf,
+        time::{SystemTime, UNIX_EPOCH},
+    };
+
+    use super::{Diagnostics, MAX_BUNDLES, STATE_HISTORY, last_lines, write_bundle};
+
+    fn directory(name: &str) -> PathBuf {
+        let directory = std::env::temp_dir()
+            .join("hdr-snipping-tool-tests")
+            .join(format!("diagnostics-{name}-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&directory);
+        directory
+    }
+
+    #[test]
+    fn keeps_last_lines() {
+        assert_eq!(last_lines("one\ntwo\nthree\n", 2), ["two", "three"]);
+        assert_eq!(last_lines("one\ntwo", 5), ["one", "two"]);
+        assert!(last_lines("", 5).is_empty());
+    }
+
+    #[test]
+    fn records_state_changes() {
+        let mut diagnostics = Diagnostics::new();
+        diagnostics.record_state(Duration::ZERO, "Inactive");
+        diagnostics.record_state(Duration::from_secs(1), "Inactive");
+        diagnostics.record_state(Duration::from_secs(2), "Loading");
+
+        let states: Vec<_> = diagnostics.states.iter().map(|(_, state)| *state).collect();
+        assert_eq!(states, ["Inactive", "Loading"]);
+
+        let names = ["Inactive", "Loading"];
+        for index in 0..STATE_HISTORY * 2 {
+            diagnostics.record_state(Duration::from_secs(index as u64), names[index % 2]);
+        }
+        assert_eq!(diagnostics.states.len(), STATE_HISTORY);
+        assert_eq!(
+            diagnostics.states.back().unwrap().0,
+            Duration::from_secs(STATE_HISTORY as u64 * 2 - 1)
+        );
+    }
+
+    #[test]
+    fn bundle_has_every_section() {
+        let mut diagnostics = Diagnostics::new();
+        diagnostics.vulkan = Some(String::from("Vulkan { device_name: \"GPU\", .. }"));
+        diagnostics.monitors = vec![String::from("DISPLAY1"), String::from("DISPLAY2")];
+        diagnostics.record_state(Duration::from_millis(1500), "Active");
+        diagnostics.config = Some(String::from("version = 1\n"));
+
+        let log: String = (0..300).map(|line| format!("line {line}\n")).collect();
+        let bundle = diagnostics.bundle(
+            "Thread 'main' panicked at src/main.rs:1:1:\nboom",
+            Some(&log),
+        );
+
+        for expected in [
+            "## Panic\n\nThread 'main' panicked at src/main.rs:1:1:\nboom\n",
+            "## Vulkan\n\nVulkan { device_name: \"GPU\", .. }\n",
+            "## Monitors\n\nDISPLAY1\nDISPLAY2\n",
+            "## State History\n\n     1.500s Active\n",
+            "## Config\n\nversion = 1\n",
+            "## Log (last 200 lines)\n\nline 100\n",
+        ] {
+            assert!(bundle.contains(expected), "{expected:?} in:\n{bundle}");
+        }
+        assert!(bundle.ends_with("line 299\n"));
+        assert!(!bundle.contains("line 99\n"));
+    }
+
+    #[test]
+    fn bundle_notes_missing_context() {
+        let bundle = Diagnostics::new().bundle("panicked", None);
+
+        assert!(bundle.contains("## Vulkan\n\nNot recorded\n"));
+        assert!(bundle.contains("## Monitors\n\nNot recorded\n"));
+        assert!(bundle.contains("No log file\n"));
+    }
+
+    #[test]
+    fn writes_bundles_and_removes_oldest() {
+        let directory = directory("write");
+        let time = UNIX_EPOCH + Duration::from_secs(1000);
+
+        let first = write_bundle(&directory, "first", time).unwrap();
+        let second = write_bundle(&directory, "second", time).unwrap();
+        assert_eq!(first.file_name().unwrap(), "crash-1000.txt");
+        assert_eq!(second.file_name().unwrap(), "crash-1000-1.txt");
+        assert_eq!(fs::read_to_string(&first).unwrap(), "first");
+
+        fs::write(directory.join("notes.txt"), "kept").unwrap();
+        for index in 0..MAX_BUNDLES {
+            write_bundle(
+                &directory,
+                "later",
+                SystemTime::now() + Duration::from_secs(index as u64),
+            )
+            .unwrap();
+        }
+
+        let bundles = fs::read_dir(&directory)
+            .unwrap()
+            .filter(|entry| {
+                let name = entry.as_ref().unwrap().file_name();
+                name.to_string_lossy().starts_with("crash-")
+            })
+            .count();
+        assert_eq!(bundles, MAX_BUNDLES);
+        assert!(directory.join("notes.txt").exists());
+    }
+}
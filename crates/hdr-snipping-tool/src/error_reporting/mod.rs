@@ -0,0 +1,483 @@
+use core::{fmt, time::Duration};
+use std::{sync::OnceLock, thread};
+
+use tracing::{error, warn};
+
+pub use notification::NotificationSink;
+
+mod notification;
+
+/// The sink the reports are shown with, reports are only logged until one is installed.
+static SINK: OnceLock<Box<dyn ReportSink>> = OnceLock::new();
+
+/// What failed, which decides if it is retried and how it is shown.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ErrorCategory {
+    /// Finding the monitors or windows, or taking the capture.
+    Capture,
+
+    /// Importing the capture into Vulkan or copying it back to CPU memory.
+    Import,
+
+    /// Analysing a capture for its brightest pixel.
+    Scan,
+
+    /// Converting a capture from HDR to SDR.
+    Tonemap,
+
+    /// Writing a screenshot or recording to its file.
+    Save,
+
+    /// Copying a screenshot to the clipboard.
+    Clipboard,
+
+    /// Running a save hook on a saved file.
+    SaveHook,
+
+    /// Reading the capture history or opening a capture from it.
+    History,
+
+    /// Taking an action from the save notification.
+    Notification,
+
+    /// Opening a folder from the tray icon.
+    Tray,
+
+    /// Opening the settings window.
+    Settings,
+
+    /// Registering the hotkeys.
+    Hotkeys,
+
+    /// Reading the config file or creating the directories it is kept in.
+    Config,
+
+    /// A failure the application cannot continue after, it exits once it is reported.
+    Fatal,
+}
+
+/// How many times an operation is tried before it is reported.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RetryPolicy {
+    /// How many times the operation is tried in total, at least once.
+    pub attempts: u32,
+
+    /// How long to wait after the first failure, each following wait is this much longer.
+    pub delay: Duration,
+}
+
+/// A failure as it is shown to the user and written to the log.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ErrorReport {
+    pub category: ErrorCategory,
+
+    /// What failed, shown to the user and written to the log.
+    pub summary: String,
+
+    /// The error and its causes, only written to the log unless `shows_detail` is set.
+    pub detail: String,
+
+    /// If the detail is meant for the user, like the problems in the config file.
+    pub shows_detail: bool,
+}
+
+/// Shows reports to the user, after they are logged.
+pub trait ReportSink: Send + Sync {
+    fn show(&self, report: ErrorReport);
+}
+
+impl ErrorCategory {
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Capture => "capture",
+            Self::Import => "import",
+            Self::Scan => "scan",
+            Self::Tonemap => "tonemap",
+            Self::Save => "save",
+            Self::Clipboard => "clipboard",
+            Self::SaveHook => "save hook",
+            Self::History => "history",
+            Self::Notification => "notification",
+            Self::Tray => "tray",
+            Self::Settings => "settings",
+            Self::Hotkeys => "hotkeys",
+            Self::Config => "config",
+            Self::Fatal => "fatal",
+        }
+    }
+
+    /// How failures are retried, only failures that are often gone a moment later are retried.
+    pub fn retry_policy(self) -> RetryPolicy {
+        let retry = |attempts, milliseconds| RetryPolicy {
+            attempts,
+            delay: Duration::from_millis(milliseconds),
+        };
+
+        match self {
+            // Captures fail while the display mode changes or the desktop switches.
+            Self::Capture => retry(3, 100),
+
+            // Another application may briefly have the clipboard open.
+            Self::Clipboard => retry(3, 50),
+
+            // Antivirus and sync applications briefly lock new files, and editors may be
+            // part-way through writing the config file.
+            Self::Save | Self::Config => retry(3, 100),
+
+            // Hooks run commands, which may not be safe to run twice.
+            Self::SaveHook => RetryPolicy::NONE,
+
+            Self::Import | Self::Scan | Self::Tonemap => RetryPolicy::NONE,
+
+            Self::History | Self::Notification | Self::Tray | Self::Settings | Self::Hotkeys => {
+                RetryPolicy::NONE
+            }
+
+            Self::Fatal => RetryPolicy::NONE,
+        }
+    }
+
+    /// If a failure may not happen again, so is logged as a warning rather than an error.
+    pub fn is_transient(self) -> bool {
+        self.retry_policy().attempts > 1
+    }
+}
+
+impl fmt::Display for ErrorCategory {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+impl RetryPolicy {
+    pub const NONE: Self = Self {
+        attempts: 1,
+        delay: Duration::ZERO,
+    };
+
+    /// Runs the operation until it succeeds or the attempts run out, returning the last error.
+    /// Each failed attempt that is retried is logged as a warning.
+    pub fn run<T, E, Operation>(self, category: ErrorCategory, operation: Operation) -> Result<T, E>
+    where
+        E: fmt::Display,
+        Operation: FnMut() -> Result<T, E>,
+    {
+        self.run_if(category, |_| true, operation)
+    }
+
+    /// Runs the operation like [`Self::run`], but only retries the errors `is_transient` accepts.
+    pub fn run_if<T, E, IsTransient, Operation>(
+        self,
+        category: ErrorCategory,
+        is_transient: IsTransient,
+        mut operation: Operation,
+    ) -> Result<T, E>
+    where
+        E: fmt::Display,
+        IsTransient: Fn(&E) -> bool,
+        Operation: FnMut() -> Result<T, E>,
+    {
+        let attempts = self.attempts.max(1);
+        let mut attempt = 1;
+
+        loop {
+            match operation() {
+                Ok(value) => return Ok(value),
+                Err(error) if attempt >= attempts || !is_transient(&error) => return Err(error),
+                Err(error) => {
+                    warn!("{category} attempt {attempt} of {attempts} failed, retrying: {error}");
+                    thread::sleep(self.delay * attempt);
+                    attempt += 1;
+                }
+            }
+        }
+    }
+}
+
+impl ErrorReport {
+    /// A report of the error, where `summary` is what failed in the words of the user.
+    pub fn new<Err: fmt::Display>(category: ErrorCategory, error: Err, summary: &str) -> Self {
+        Self {
+            category,
+            summary: summary.to_string(),
+            detail: error.to_string(),
+            shows_detail: false,
+        }
+    }
+
+    /// The text shown to the user below the summary.
+    pub fn shown_details(&self) -> &str {
+        if self.shows_detail {
+            &self.detail
+        } else {
+            "See the logs for more details."
+        }
+    }
+
+    /// The text written to the log.
+    pub fn log_message(&self) -> String {
+        format!("[{}] {}: {}", self.category, self.summary, self.detail)
+    }
+}
+
+/// Shows reports with the sink from now on, returns `false` if a sink was already installed.
+pub fn install(sink: Box<dyn ReportSink>) -> bool {
+    SINK.set(sink).is_ok()
+}
+
+/// Logs the report and shows it with the sink, without waiting for the user.
+pub fn report(report: ErrorReport) {
+    if report.category.is_transient() {
+        warn!("{}", report.log_message());
+    } else {
+        error!("{}", report.log_message());
+    }
+
+    #[cfg(test)]
+    let report = match test_sink::record(report) {
+        Some(report) => report,
+        None => return,
+    };
+
+    if let Some(sink) = SINK.get() {
+        sink.show(report);
+    }
+}
+
+/// Reports an error in the formats:
+///
+/// * Log: `"[{category}] {summary}: {error}"`
+/// * Notification: `"{summary}"`, with `"See the logs for more details."` below it
+pub fn report_error<Err: fmt::Display>(category: ErrorCategory, error: Err, summary: &str) {
+    report(ErrorReport::new(category, error, summary));
+}
+
+/// Reports a problem the user can fix, in the formats:
+///
+/// * Log: `"[{category}] {summary}: {details}"`
+/// * Notification: `"{summary}"`, with the details below it
+pub fn report_details<Details: fmt::Display>(
+    category: ErrorCategory,
+    details: Details,
+    summary: &str,
+) {
+    report(ErrorReport {
+        shows_detail: true,
+        ..ErrorReport::new(category, details, summary)
+    });
+}
+
+/// Runs the operation with the category's retry policy, reporting the error if it still fails.
+pub fn retry_or_report<T, E, Operation>(
+    category: ErrorCategory,
+    summary: &str,
+    operation: Operation,
+) -> Option<T>
+where
+    E: fmt::Display,
+    Operation: FnMut() -> Result<T, E>,
+{
+    match category.retry_policy().run(category, operation) {
+        Ok(value) => Some(value),
+        Err(error) => {
+            report_error(category, error, summary);
+            None
+        }
+    }
+}
+
+/// Records the reports made on a thread, so tests can check the errors a flow reports.
+#[cfg(test)]
+pub mod test_sink {
+    use core::cell::RefCell;
+
+    use super::{ErrorCategory, ErrorReport};
+
+    thread_local! {
+        static REPORTS: RefCell<Option<Vec<ErrorReport>>> = const { RefCell::new(None) };
+    }
+
+    /// Runs `f`, returning what it returned and the reports it made on this thread instead of
+    /// showing them.
+    pub fn capture<T>(f: impl FnOnce() -> T) -> (T, Vec<ErrorReport>) {
+        let previous = REPORTS.with_borrow_mut(|reports| reports.replace(Vec::new()));
+        let value = f();
+        let reports = REPORTS.with_borrow_mut(|reports| match previous {
+            Some(previous) => reports.replace(previous),
+            None => reports.take(),
+        });
+
+        (value, reports.unwrap_or_default())
+    }
+
+    /// The categories of the reports, in the order they were made.
+    pub fn categories(reports: &[ErrorReport]) -> Vec<ErrorCategory> {
+        reports.iter().map(|report| report.category).collect()
+    }
+
+    /// Records the report if this thread is capturing reports, otherwise returns it to be shown.
+    pub(super) fn record(report: ErrorReport) -> Option<ErrorReport> {
+        REPORTS.with_borrow_mut(|reports| match reports {
+            Some(reports) => {
+                reports.push(report);
+                None
+            }
+            None => Some(report),
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use core::time::Duration;
+
+    use super::{
+        ErrorCategory, ErrorReport, RetryPolicy, report_error, retry_or_report, test_sink,
+    };
+
+    const QUICK_RETRY: RetryPolicy = RetryPolicy {
+        attempts: 3,
+        delay: Duration::ZERO,
+    };
+
+    #[test]
+    fn retries_until_success() {
+        let mut calls = 0;
+        let result: Result<u32, &str> = QUICK_RETRY.run(ErrorCategory::Save, || {
+            calls += 1;
+            if calls < 3 { Err("locked") } else { Ok(calls) }
+        });
+
+        assert_eq!(result, Ok(3));
+    }
+
+    #[test]
+    fn returns_the_last_error() {
+        let mut calls = 0;
+        let result: Result<(), String> = QUICK_RETRY.run(ErrorCategory::Save, || {
+            calls += 1;
+            Err(format!("failure {calls}"))
+        });
+
+        assert_eq!(result, Err(String::from("failure 3")));
+
+        calls = 0;
+        let result: Result<(), String> = RetryPolicy::NONE.run(ErrorCategory::Scan, || {
+            calls += 1;
+            Err(String::from("failure"))
+        });
+        assert!(result.is_err());
+        assert_eq!(calls, 1);
+    }
+
+    #[test]
+    fn only_retries_transient_errors() {
+        let mut calls = 0;
+        let result: Result<(), &str> = QUICK_RETRY.run_if(
+            ErrorCategory::Config,
+            |error| *error == "locked",
+            || {
+                calls += 1;
+                if calls == 1 {
+                    Err("locked")
+                } else {
+                    Err("invalid")
+                }
+            },
+        );
+
+        assert_eq!(result, Err("invalid"));
+        assert_eq!(calls, 2);
+    }
+
+    #[test]
+    fn only_transient_categories_are_retried() {
+        let categories = [
+            ErrorCategory::Capture,
+            ErrorCategory::Import,
+            ErrorCategory::Scan,
+            ErrorCategory::Tonemap,
+            ErrorCategory::Save,
+            ErrorCategory::Clipboard,
+            ErrorCategory::SaveHook,
+            ErrorCategory::History,
+            ErrorCategory::Notification,
+            ErrorCategory::Tray,
+            ErrorCategory::Settings,
+            ErrorCategory::Hotkeys,
+            ErrorCategory::Config,
+            ErrorCategory::Fatal,
+        ];
+
+        for category in categories {
+            let policy = category.retry_policy();
+            assert!(policy.attempts >= 1);
+            assert_eq!(category.is_transient(), policy.attempts > 1, "{category}");
+        }
+
+        assert!(ErrorCategory::Capture.is_transient());
+        assert!(!ErrorCategory::Tonemap.is_transient());
+    }
+
+    #[test]
+    fn keeps_user_messages_apart_from_details() {
+        let report = ErrorReport::new(
+            ErrorCategory::Save,
+            "Access is denied. (os error 5)",
+            "Could not save the screenshot file",
+        );
+
+        assert_eq!(report.shown_details(), "See the logs for more details.");
+        assert_eq!(
+            report.log_message(),
+            "[save] Could not save the screenshot file: Access is denied. (os error 5)"
+        );
+
+        let report = ErrorReport {
+            shows_detail: true,
+            ..ErrorReport::new(
+                ErrorCategory::Config,
+                "• Line 2",
+                "The config file has errors",
+            )
+        };
+        assert_eq!(report.shown_details(), "• Line 2");
+    }
+
+    #[test]
+    fn test_sink_records_reports() {
+        let ((), reports) = test_sink::capture(|| {
+            report_error(
+                ErrorCategory::Scan,
+                "device lost",
+                "Could not analyse the capture",
+            );
+            report_error(ErrorCategory::Clipboard, "busy", "Could not copy");
+        });
+
+        assert_eq!(
+            test_sink::categories(&reports),
+            [ErrorCategory::Scan, ErrorCategory::Clipboard]
+        );
+        assert_eq!(reports[0].detail, "device lost");
+
+        // Reports after capturing are no longer recorded.
+        let ((), reports) = test_sink::capture(|| {});
+        assert!(reports.is_empty());
+    }
+
+    #[test]
+    fn reports_after_retries_run_out() {
+        let mut calls = 0;
+        let (value, reports) = test_sink::capture(|| {
+            retry_or_report(ErrorCategory::Import, "Could not import", || {
+                calls += 1;
+                Err::<(), _>("out of memory")
+            })
+        });
+
+        assert_eq!(value, None);
+        assert_eq!(calls, 1);
+        assert_eq!(test_sink::categories(&reports), [ErrorCategory::Import]);
+    }
+}
@@ -0,0 +1,33 @@
+use std::sync::Mutex;
+
+use tracing::debug;
+use winit::event_loop::EventLoopProxy;
+
+use crate::application_event_loop::Event;
+
+use super::{ErrorReport, ReportSink};
+
+/// Shows each report in a notification in the corner of the screen, which never takes the focus
+/// or waits for the user. A newer report replaces the one shown.
+pub struct NotificationSink {
+    proxy: Mutex<EventLoopProxy<Event>>,
+}
+
+impl NotificationSink {
+    pub fn new(proxy: EventLoopProxy<Event>) -> Self {
+        Self {
+            proxy: Mutex::new(proxy),
+        }
+    }
+}
+
+impl ReportSink for NotificationSink {
+    fn show(&self, report: ErrorReport) {
+        let proxy = self.proxy.lock().unwrap_or_else(|error| error.into_inner());
+
+        // The report is already logged, it can only not be shown once the application is exiting.
+        if proxy.send_event(Event::ErrorReported(report)).is_err() {
+            debug!("Could not show the error, the event loop has exited");
+        }
+    }
+}
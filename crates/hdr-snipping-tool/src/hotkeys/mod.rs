@@ -0,0 +1,304 @@
+use core::{fmt, time::Duration};
+
+use global_hotkey::{GlobalHotKeyManager, hotkey::HotKey};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use tracing::{info, warn};
+
+use crate::{
+    application::ApplicationEvent,
+    application_event_loop::CANCEL_COUNTDOWN_KEY,
+    error_reporting::{ErrorCategory, report_details},
+};
+
+pub use shortcut::{Shortcut, ShortcutError};
+pub use virtual_key::key_code;
+
+mod shortcut;
+mod virtual_key;
+
+/// A shortcut and what pressing it does.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HotkeyBinding {
+    pub shortcut: Shortcut,
+    pub action: HotkeyAction,
+}
+
+/// What pressing a hotkey does.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HotkeyAction {
+    /// Shows the screenshot to select a region of, after the configured delay.
+    RegionCapture,
+
+    /// Saves the hovered monitor without showing the screenshot.
+    FullCapture,
+
+    /// Saves the last saved region again without showing the screenshot.
+    RepeatLastRegion,
+
+    /// Shows the screenshot to pick a window to capture.
+    WindowCapture,
+
+    /// Counts down the seconds before showing the screenshot.
+    DelayedCapture(u32),
+
+    /// Opens the newest capture in the history for re-editing.
+    OpenHistory,
+
+    /// Starts selecting a region to record, or stops the running recording.
+    Recording,
+
+    /// Starts selecting a region to take a burst of, or stops the running burst.
+    Burst,
+}
+
+impl HotkeyAction {
+    /// The event sent to the application when the hotkey is pressed.
+    pub fn event(self) -> ApplicationEvent {
+        match self {
+            Self::RegionCapture => ApplicationEvent::ScreenshotKeyPressed,
+            Self::FullCapture => ApplicationEvent::InstantScreenshotKeyPressed,
+            Self::RepeatLastRegion => ApplicationEvent::RepeatRegionRequested,
+            Self::WindowCapture => ApplicationEvent::WindowCaptureRequested,
+            Self::DelayedCapture(seconds) => ApplicationEvent::DelayedScreenshotRequested(
+                Duration::from_secs(u64::from(seconds)),
+            ),
+            Self::OpenHistory => ApplicationEvent::HistoryRequested,
+            Self::Recording => ApplicationEvent::RecordingRequested,
+            Self::Burst => ApplicationEvent::BurstRequested,
+        }
+    }
+}
+
+impl fmt::Display for HotkeyAction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::RegionCapture => write!(f, "region capture"),
+            Self::FullCapture => write!(f, "full capture"),
+            Self::RepeatLastRegion => write!(f, "repeat last region"),
+            Self::WindowCapture => write!(f, "window capture"),
+            Self::DelayedCapture(seconds) => write!(f, "{seconds}s delayed capture"),
+            Self::OpenHistory => write!(f, "open history"),
+            Self::Recording => write!(f, "recording"),
+            Self::Burst => write!(f, "burst capture"),
+        }
+    }
+}
+
+/// A binding that was not registered.
+#[derive(Debug, Error)]
+pub enum HotkeyError {
+    #[error("{shortcut} is bound to both {used} and {ignored}, it is only used for {used}")]
+    Conflict {
+        shortcut: Shortcut,
+        used: HotkeyAction,
+        ignored: HotkeyAction,
+    },
+
+    #[error("{0} is reserved for cancelling a delayed screenshot")]
+    Reserved(Shortcut),
+
+    #[error(
+        "{shortcut} for {action} could not be registered, another application may be using it:\n{source}"
+    )]
+    Registration {
+        shortcut: Shortcut,
+        action: HotkeyAction,
+        source: global_hotkey::Error,
+    },
+}
+
+/// The registered hotkeys and their actions.
+#[derive(Debug, Default)]
+pub struct Hotkeys {
+    registered: Vec<(HotKey, HotkeyAction)>,
+}
+
+impl Hotkeys {
+    /// Registers the bindings, the bindings that were not registered are returned as errors.
+    pub fn register(
+        manager: &GlobalHotKeyManager,
+        bindings: &[HotkeyBinding],
+    ) -> (Self, Vec<HotkeyError>) {
+        let (bindings, mut errors) = resolve_conflicts(bindings);
+        let mut hotkeys = Self::default();
+
+        for binding in bindings {
+            let hotkey = binding.shortcut.hotkey();
+
+            match manager.register(hotkey) {
+                Ok(()) => {
+                    info!("Registered {} for {}", binding.shortcut, binding.action);
+                    hotkeys.registered.push((hotkey, binding.action));
+                }
+                Err(source) => errors.push(HotkeyError::Registration {
+                    shortcut: binding.shortcut,
+                    action: binding.action,
+                    source,
+                }),
+            }
+        }
+
+        (hotkeys, errors)
+    }
+
+    /// Unregisters every hotkey, so the bindings can be registered again.
+    pub fn unregister(&mut self, manager: &GlobalHotKeyManager) {
+        for (hotkey, action) in self.registered.drain(..) {
+            if let Err(e) = manager.unregister(hotkey) {
+                warn!("Could not unregister the {action} hotkey: {e}");
+            }
+        }
+    }
+
+    /// The action of a registered hotkey.
+    pub fn action(&self, id: u32) -> Option<HotkeyAction> {
+        self.registered
+            .iter()
+            .find(|(hotkey, _)| hotkey.id() == id)
+            .map(|(_, action)| *action)
+    }
+}
+
+/// Removes the bindings that cannot be registered, the first binding of a shortcut is kept.
+pub fn resolve_conflicts(bindings: &[HotkeyBinding]) -> (Vec<HotkeyBinding>, Vec<HotkeyError>) {
+    let cancel_id = HotKey::new(None, CANCEL_COUNTDOWN_KEY).id();
+
+    let mut kept: Vec<HotkeyBinding> = Vec::with_capacity(bindings.len());
+    let mut errors = Vec::new();
+
+    for binding in bindings {
+        let id = binding.shortcut.hotkey().id();
+
+        if id == cancel_id {
+            errors.push(HotkeyError::Reserved(binding.shortcut));
+            continue;
+        }
+
+        let existing = kept
+            .iter()
+            .find(|existing| existing.shortcut.hotkey().id() == id);
+        match existing {
+            // The same binding written twice does not need reporting.
+            Some(existing) if existing.action == binding.action => {}
+
+            Some(existing) => errors.push(HotkeyError::Conflict {
+                shortcut: binding.shortcut,
+                used: existing.action,
+                ignored: binding.action,
+            }),
+
+            None => kept.push(*binding),
+        }
+    }
+
+    (kept, errors)
+}
+
+/// Lists the bindings that were not registered, without interrupting the application.
+pub fn report(errors: &[HotkeyError]) {
+    if errors.is_empty() {
+        return;
+    }
+
+    let list: Vec<_> = errors.iter().map(|error| format!("• {error}")).collect();
+    report_details(
+        ErrorCategory::Hotkeys,
+        list.join("\n"),
+        "Some hotkeys could not be registered, they can be changed in the config",
+    );
+}
+
+#[cfg(test)]
+mod test {
+    use super::{HotkeyAction, HotkeyBinding, HotkeyError, resolve_conflicts};
+
+    fn binding(shortcut: &str, action: HotkeyAction) -> HotkeyBinding {
+        HotkeyBinding {
+            shortcut: shortcut.parse().unwrap(),
+            action,
+        }
+    }
+
+    #[test]
+    fn keeps_first_of_conflicting_bindings() {
+        let bindings = [
+            binding("Ctrl+Shift+S", HotkeyAction::RegionCapture),
+            binding("PrintScreen", HotkeyAction::FullCapture),
+            binding("shift+ctrl+s", HotkeyAction::OpenHistory),
+        ];
+
+        let (kept, errors) = resolve_conflicts(&bindings);
+
+        assert_eq!(kept, bindings[..2]);
+        assert!(matches!(
+            errors[..],
+            [HotkeyError::Conflict {
+                used: HotkeyAction::RegionCapture,
+                ignored: HotkeyAction::OpenHistory,
+                ..
+            }]
+        ));
+    }
+
+    #[test]
+    fn ignores_repeated_bindings() {
+        let bindings = [
+            binding("Alt+H", HotkeyAction::OpenHistory),
+            binding("alt+h", HotkeyAction::OpenHistory),
+        ];
+
+        let (kept, errors) = resolve_conflicts(&bindings);
+
+        assert_eq!(kept, bindings[..1]);
+        assert!(errors.is_empty());
+    }
+
+    #[test]
+    fn reserves_cancel_key() {
+        let bindings = [
+            binding("Escape", HotkeyAction::RegionCapture),
+            binding("Ctrl+Escape", HotkeyAction::RegionCapture),
+        ];
+
+        let (kept, errors) = resolve_conflicts(&bindings);
+
+        assert_eq!(kept, bindings[1..]);
+        assert!(matches!(errors[..], [HotkeyError::Reserved(_)]));
+    }
+
+    #[test]
+    fn bindings_round_trip_through_config() {
+        #[derive(serde::Serialize, serde::Deserialize)]
+        struct Bindings {
+            hotkeys: Vec<HotkeyBinding>,
+        }
+
+        let toml = r#"
+            [[hotkeys]]
+            shortcut = "Ctrl+Shift+S"
+            action = "region_capture"
+
+            [[hotkeys]]
+            shortcut = "Ctrl+Shift+D"
+            action = { delayed_capture = 5 }
+        "#;
+
+        let bindings: Bindings = toml::from_str(toml).unwrap();
+        assert_eq!(
+            bindings.hotkeys,
+            [
+                binding("Ctrl+Shift+S", HotkeyAction::RegionCapture),
+                binding("Ctrl+Shift+D", HotkeyAction::DelayedCapture(5)),
+            ]
+        );
+
+        let saved = toml::to_string_pretty(&bindings).unwrap();
+        let reloaded: Bindings = toml::from_str(&saved).unwrap();
+        assert_eq!(reloaded.hotkeys, bindings.hotkeys);
+
+        let invalid = "[[hotkeys]]\nshortcut = \"Ctrl+\"\naction = \"region_capture\"";
+        assert!(toml::from_str::<Bindings>(invalid).is_err());
+    }
+}
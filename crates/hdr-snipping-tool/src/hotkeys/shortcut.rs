@@ -0,0 +1,210 @@
+use core::{fmt, str::FromStr};
+
+use global_hotkey::hotkey::{Code, HotKey, Modifiers};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+/// The modifiers in the order they are written, with the name they are written as.
+const MODIFIER_NAMES: [(Modifiers, &str); 4] = [
+    (Modifiers::CONTROL, "Ctrl"),
+    (Modifiers::ALT, "Alt"),
+    (Modifiers::SHIFT, "Shift"),
+    (Modifiers::SUPER, "Win"),
+];
+
+/// A key and the modifiers held with it, written like `"Ctrl+Shift+S"`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct Shortcut {
+    pub modifiers: Modifiers,
+    pub key: Code,
+}
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum ShortcutError {
+    #[error("The shortcut is empty")]
+    Empty,
+
+    #[error("\"{0}\" has no key, only modifiers")]
+    MissingKey(String),
+
+    #[error("\"{0}\" has more than one key")]
+    MultipleKeys(String),
+
+    #[error("\"{0}\" repeats a modifier")]
+    RepeatedModifier(String),
+
+    #[error("\"{key}\" in \"{shortcut}\" is not a key")]
+    UnknownKey { shortcut: String, key: String },
+}
+
+impl Shortcut {
+    pub fn new(modifiers: Modifiers, key: Code) -> Self {
+        Self { modifiers, key }
+    }
+
+    /// The hotkey that is registered for the shortcut.
+    pub fn hotkey(&self) -> HotKey {
+        HotKey::new(Some(self.modifiers), self.key)
+    }
+}
+
+impl FromStr for Shortcut {
+    type Err = ShortcutError;
+
+    fn from_str(shortcut: &str) -> Result<Self, Self::Err> {
+        if shortcut.trim().is_empty() {
+            return Err(ShortcutError::Empty);
+        }
+
+        let mut modifiers = Modifiers::empty();
+        let mut key = None;
+
+        for token in shortcut.split('+').map(str::trim) {
+            if let Some(modifier) = parse_modifier(token) {
+                if modifiers.contains(modifier) {
+                    return Err(ShortcutError::RepeatedModifier(shortcut.to_string()));
+                }
+                modifiers |= modifier;
+                continue;
+            }
+
+            if key.is_some() {
+                return Err(ShortcutError::MultipleKeys(shortcut.to_string()));
+            }
+
+            // Only the key is passed on, so the hotkey parser's modifier names are not accepted as
+            // keys.
+            let hotkey = HotKey::from_str(token).map_err(|_| ShortcutError::UnknownKey {
+                shortcut: shortcut.to_string(),
+                key: token.to_string(),
+            })?;
+            key = Some(hotkey.key);
+        }
+
+        match key {
+            Some(key) => Ok(Self::new(modifiers, key)),
+            None => Err(ShortcutError::MissingKey(shortcut.to_string())),
+        }
+    }
+}
+
+impl TryFrom<String> for Shortcut {
+    type Error = ShortcutError;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+impl From<Shortcut> for String {
+    fn from(value: Shortcut) -> Self {
+        value.to_string()
+    }
+}
+
+impl fmt::Display for Shortcut {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (modifier, name) in MODIFIER_NAMES {
+            if self.modifiers.contains(modifier) {
+                write!(f, "{name}+")?;
+            }
+        }
+
+        // Letters and digits are written as they are printed on the key.
+        let key = self.key.to_string();
+        let key = key
+            .strip_prefix("Key")
+            .or_else(|| key.strip_prefix("Digit"))
+            .filter(|name| name.len() == 1)
+            .unwrap_or(&key);
+
+        write!(f, "{key}")
+    }
+}
+
+fn parse_modifier(token: &str) -> Option<Modifiers> {
+    match token.to_uppercase().as_str() {
+        "CTRL" | "CONTROL" => Some(Modifiers::CONTROL),
+        "ALT" => Some(Modifiers::ALT),
+        "SHIFT" => Some(Modifiers::SHIFT),
+        "WIN" | "WINDOWS" | "SUPER" => Some(Modifiers::SUPER),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use global_hotkey::hotkey::{Code, Modifiers};
+
+    use super::{Shortcut, ShortcutError};
+
+    #[test]
+    fn parses_modifiers_and_key() {
+        let shortcut: Shortcut = "Ctrl+Shift+S".parse().unwrap();
+        assert_eq!(
+            shortcut,
+            Shortcut::new(Modifiers::CONTROL | Modifiers::SHIFT, Code::KeyS)
+        );
+
+        let shortcut: Shortcut = " win + alt + printscreen ".parse().unwrap();
+        assert_eq!(
+            shortcut,
+            Shortcut::new(Modifiers::SUPER | Modifiers::ALT, Code::PrintScreen)
+        );
+
+        let shortcut: Shortcut = "PrintScreen".parse().unwrap();
+        assert_eq!(
+            shortcut,
+            Shortcut::new(Modifiers::empty(), Code::PrintScreen)
+        );
+    }
+
+    #[test]
+    fn writes_readable_shortcuts() {
+        let shortcuts = ["Ctrl+Shift+S", "Alt+Win+1", "PrintScreen", "Shift+F9"];
+
+        for written in shortcuts {
+            let shortcut: Shortcut = written.parse().unwrap();
+            assert_eq!(shortcut.to_string(), written);
+        }
+
+        // Modifiers are written in the same order, however they were typed.
+        let shortcut: Shortcut = "shift+ctrl+keyq".parse().unwrap();
+        assert_eq!(shortcut.to_string(), "Ctrl+Shift+Q");
+    }
+
+    #[test]
+    fn rejects_invalid_shortcuts() {
+        assert_eq!("".parse::<Shortcut>(), Err(ShortcutError::Empty));
+        assert_eq!(
+            "Ctrl+Shift".parse::<Shortcut>(),
+            Err(ShortcutError::MissingKey("Ctrl+Shift".to_string()))
+        );
+        assert_eq!(
+            "Ctrl+A+B".parse::<Shortcut>(),
+            Err(ShortcutError::MultipleKeys("Ctrl+A+B".to_string()))
+        );
+        assert_eq!(
+            "Ctrl+Control+A".parse::<Shortcut>(),
+            Err(ShortcutError::RepeatedModifier(
+                "Ctrl+Control+A".to_string()
+            ))
+        );
+        assert_eq!(
+            "Ctrl+Nope".parse::<Shortcut>(),
+            Err(ShortcutError::UnknownKey {
+                shortcut: "Ctrl+Nope".to_string(),
+                key: "Nope".to_string(),
+            })
+        );
+    }
+
+    #[test]
+    fn equal_shortcuts_share_a_hotkey() {
+        let first: Shortcut = "Ctrl+Shift+S".parse().unwrap();
+        let second: Shortcut = "SHIFT+CONTROL+KeyS".parse().unwrap();
+
+        assert_eq!(first.hotkey().id(), second.hotkey().id());
+    }
+}
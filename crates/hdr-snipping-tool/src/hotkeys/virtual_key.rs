@@ -0,0 +1,173 @@
+use global_hotkey::hotkey::Code;
+
+const LETTERS: [Code; 26] = [
+    Code::KeyA,
+    Code::KeyB,
+    Code::KeyC,
+    Code::KeyD,
+    Code::KeyE,
+    Code::KeyF,
+    Code::KeyG,
+    Code::KeyH,
+    Code::KeyI,
+    Code::KeyJ,
+    Code::KeyK,
+    Code::KeyL,
+    Code::KeyM,
+    Code::KeyN,
+    Code::KeyO,
+    Code::KeyP,
+    Code::KeyQ,
+    Code::KeyR,
+    Code::KeyS,
+    Code::KeyT,
+    Code::KeyU,
+    Code::KeyV,
+    Code::KeyW,
+    Code::KeyX,
+    Code::KeyY,
+    Code::KeyZ,
+];
+
+const DIGITS: [Code; 10] = [
+    Code::Digit0,
+    Code::Digit1,
+    Code::Digit2,
+    Code::Digit3,
+    Code::Digit4,
+    Code::Digit5,
+    Code::Digit6,
+    Code::Digit7,
+    Code::Digit8,
+    Code::Digit9,
+];
+
+const NUMPAD_DIGITS: [Code; 10] = [
+    Code::Numpad0,
+    Code::Numpad1,
+    Code::Numpad2,
+    Code::Numpad3,
+    Code::Numpad4,
+    Code::Numpad5,
+    Code::Numpad6,
+    Code::Numpad7,
+    Code::Numpad8,
+    Code::Numpad9,
+];
+
+const FUNCTION_KEYS: [Code; 24] = [
+    Code::F1,
+    Code::F2,
+    Code::F3,
+    Code::F4,
+    Code::F5,
+    Code::F6,
+    Code::F7,
+    Code::F8,
+    Code::F9,
+    Code::F10,
+    Code::F11,
+    Code::F12,
+    Code::F13,
+    Code::F14,
+    Code::F15,
+    Code::F16,
+    Code::F17,
+    Code::F18,
+    Code::F19,
+    Code::F20,
+    Code::F21,
+    Code::F22,
+    Code::F23,
+    Code::F24,
+];
+
+/// The keys that are not in a run of consecutive virtual keys.
+const OTHER_KEYS: [(u16, Code); 39] = [
+    (0x08, Code::Backspace),
+    (0x09, Code::Tab),
+    (0x0D, Code::Enter),
+    (0x13, Code::Pause),
+    (0x14, Code::CapsLock),
+    (0x1B, Code::Escape),
+    (0x20, Code::Space),
+    (0x21, Code::PageUp),
+    (0x22, Code::PageDown),
+    (0x23, Code::End),
+    (0x24, Code::Home),
+    (0x25, Code::ArrowLeft),
+    (0x26, Code::ArrowUp),
+    (0x27, Code::ArrowRight),
+    (0x28, Code::ArrowDown),
+    (0x2C, Code::PrintScreen),
+    (0x2D, Code::Insert),
+    (0x2E, Code::Delete),
+    (0x6A, Code::NumpadMultiply),
+    (0x6B, Code::NumpadAdd),
+    (0x6D, Code::NumpadSubtract),
+    (0x6E, Code::NumpadDecimal),
+    (0x6F, Code::NumpadDivide),
+    (0x90, Code::NumLock),
+    (0x91, Code::ScrollLock),
+    (0xAD, Code::AudioVolumeMute),
+    (0xAE, Code::AudioVolumeDown),
+    (0xAF, Code::AudioVolumeUp),
+    (0xBA, Code::Semicolon),
+    (0xBB, Code::Equal),
+    (0xBC, Code::Comma),
+    (0xBD, Code::Minus),
+    (0xBE, Code::Period),
+    (0xBF, Code::Slash),
+    (0xC0, Code::Backquote),
+    (0xDB, Code::BracketLeft),
+    (0xDC, Code::Backslash),
+    (0xDD, Code::BracketRight),
+    (0xDE, Code::Quote),
+];
+
+/// The key of a Windows virtual key code, `None` for modifiers and keys that cannot be a hotkey.
+pub fn key_code(virtual_key: u16) -> Option<Code> {
+    let in_run = |first: u16, run: &[Code]| {
+        virtual_key
+            .checked_sub(first)
+            .and_then(|index| run.get(usize::from(index)))
+            .copied()
+    };
+
+    in_run(0x41, &LETTERS)
+        .or_else(|| in_run(0x30, &DIGITS))
+        .or_else(|| in_run(0x60, &NUMPAD_DIGITS))
+        .or_else(|| in_run(0x70, &FUNCTION_KEYS))
+        .or_else(|| {
+            OTHER_KEYS
+                .iter()
+                .find(|(key, _)| *key == virtual_key)
+                .map(|(_, code)| *code)
+        })
+}
+
+#[cfg(test)]
+mod test {
+    use global_hotkey::hotkey::Code;
+
+    use super::key_code;
+
+    #[test]
+    fn maps_virtual_keys() {
+        assert_eq!(key_code(0x41), Some(Code::KeyA));
+        assert_eq!(key_code(0x5A), Some(Code::KeyZ));
+        assert_eq!(key_code(0x39), Some(Code::Digit9));
+        assert_eq!(key_code(0x69), Some(Code::Numpad9));
+        assert_eq!(key_code(0x87), Some(Code::F24));
+        assert_eq!(key_code(0x2C), Some(Code::PrintScreen));
+        assert_eq!(key_code(0xDE), Some(Code::Quote));
+    }
+
+    #[test]
+    fn ignores_modifiers() {
+        // Shift, Ctrl, Alt and the Windows keys.
+        for virtual_key in [0x10, 0x11, 0x12, 0x5B, 0x5C, 0xA0, 0xA5] {
+            assert_eq!(key_code(virtual_key), None);
+        }
+    }
+}
@@ -0,0 +1,194 @@
+use core::time::Duration;
+use std::{
+    io,
+    sync::{Arc, Mutex, PoisonError},
+    thread,
+};
+
+use thiserror::Error;
+use tracing::{debug, info_span, warn};
+
+use crate::{application::ApplicationEvent, application_event_loop::Event};
+
+pub use protocol::{ArgumentError, Command, PROTOCOL_VERSION, Response};
+
+#[cfg(windows)]
+mod pipe;
+mod protocol;
+#[cfg(unix)]
+mod socket;
+
+#[cfg(windows)]
+use pipe as transport;
+#[cfg(unix)]
+use socket as transport;
+
+/// The name of the channel the running instance listens for commands on.
+pub const CHANNEL_NAME: &str = "hdr-snipping-tool";
+
+/// How many times a second invocation tries to connect, the running instance may still be
+/// starting.
+const CONNECT_ATTEMPTS: u32 = 10;
+
+/// How long to wait between attempts to connect or to accept a client.
+const RETRY_DELAY: Duration = Duration::from_millis(100);
+
+/// How long a client has to send its request, where the transport supports a timeout.
+const READ_TIMEOUT: Duration = Duration::from_secs(5);
+
+#[derive(Debug, Error)]
+pub enum IpcError {
+    #[error("Could not connect to the running instance:\n{0}")]
+    Connect(#[source] io::Error),
+
+    #[error("Could not send the command to the running instance:\n{0}")]
+    Io(#[from] io::Error),
+
+    #[error("The running instance refused the command:\n{0}")]
+    Refused(String),
+}
+
+impl Command {
+    /// The event sent to the application when the command is received.
+    pub fn event(self) -> Event {
+        match self {
+            Self::Capture => ApplicationEvent::ScreenshotKeyPressed.into(),
+            Self::CaptureFull => ApplicationEvent::InstantScreenshotKeyPressed.into(),
+            Self::RepeatLast => ApplicationEvent::RepeatRegionRequested.into(),
+            Self::ReloadConfig => Event::ConfigChanged,
+            Self::Quit => Event::QuitRequested,
+        }
+    }
+}
+
+/// Listens for commands on the channel and passes each to `handle` on an `IPC Server` thread,
+/// answering with the error `handle` returns if it could not carry the command out.
+///
+/// Each client is served on its own thread, so a client that never sends its request does not
+/// hold up the others.
+pub fn serve<Handle>(name: &str, handle: Handle) -> io::Result<()>
+where
+    Handle: FnMut(Command) -> Result<(), String> + Send + 'static,
+{
+    let mut listener = transport::Listener::bind(name)?;
+    let handle = Arc::new(Mutex::new(handle));
+
+    thread::Builder::new()
+        .name(String::from("IPC Server"))
+        .spawn(move || {
+            let _span = info_span!("[IPC Server]").entered();
+
+            loop {
+                let stream = match listener.accept() {
+                    Ok(stream) => stream,
+                    Err(error) => {
+                        warn!("Could not accept a client:\n{error}");
+                        thread::sleep(RETRY_DELAY);
+                        continue;
+                    }
+                };
+
+                let handle = Arc::clone(&handle);
+                let spawn_result = thread::Builder::new()
+                    .name(String::from("IPC Client"))
+                    .spawn(move || {
+                        let _span = info_span!("[IPC Client]").entered();
+
+                        // Commands are handled one at a time, in the order they are read.
+                        let mut logged_handle = |command: Command| {
+                            debug!("Received command: {command}");
+                            let mut handle = handle.lock().unwrap_or_else(PoisonError::into_inner);
+                            handle(command)
+                        };
+
+                        if let Err(error) = protocol::serve_connection(stream, &mut logged_handle) {
+                            warn!("Could not answer a client:\n{error}");
+                        }
+                    });
+
+                if let Err(error) = spawn_result {
+                    warn!("Could not start a thread for a client:\n{error}");
+                }
+            }
+        })?;
+
+    Ok(())
+}
+
+/// Forwards the command to the instance listening on the channel.
+pub fn send(name: &str, command: Command) -> Result<(), IpcError> {
+    let mut attempt = 1;
+    let stream = loop {
+        match transport::connect(name) {
+            Ok(stream) => break stream,
+            Err(error) if attempt >= CONNECT_ATTEMPTS => return Err(IpcError::Connect(error)),
+            Err(_) => {
+                attempt += 1;
+                thread::sleep(RETRY_DELAY);
+            }
+        }
+    };
+
+    match protocol::send_request(stream, command)? {
+        Response::Accepted => Ok(()),
+        Response::Refused(reason) => Err(IpcError::Refused(reason)),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::{process, sync::mpsc};
+
+    use super::{Command, IpcError, send, serve, transport};
+
+    fn channel_name(test: &str) -> String {
+        format!("hdr-snipping-tool-test-{test}-{}", process::id())
+    }
+
+    #[test]
+    fn forwards_commands() {
+        let name = channel_name("forwards");
+        let (sender, receiver) = mpsc::channel();
+        serve(&name, move |command| {
+            sender.send(command).map_err(|error| error.to_string())
+        })
+        .unwrap();
+
+        for command in Command::ALL {
+            send(&name, command).unwrap();
+            assert_eq!(receiver.recv().unwrap(), command);
+        }
+    }
+
+    #[test]
+    fn silent_client_does_not_block_others() {
+        let name = channel_name("silent");
+        let (sender, receiver) = mpsc::channel();
+        serve(&name, move |command| {
+            sender.send(command).map_err(|error| error.to_string())
+        })
+        .unwrap();
+
+        // Connects without ever sending a request.
+        let _silent = transport::connect(&name).unwrap();
+
+        send(&name, Command::Capture).unwrap();
+        assert_eq!(receiver.recv().unwrap(), Command::Capture);
+    }
+
+    #[test]
+    fn reports_refused_commands() {
+        let name = channel_name("refused");
+        serve(&name, |command| match command {
+            Command::Quit => Err(String::from("Quitting is disabled")),
+            _ => Ok(()),
+        })
+        .unwrap();
+
+        send(&name, Command::Capture).unwrap();
+        assert!(matches!(
+            send(&name, Command::Quit),
+            Err(IpcError::Refused(reason)) if reason == "Quitting is disabled"
+        ));
+    }
+}
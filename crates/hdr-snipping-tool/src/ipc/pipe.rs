@@ -0,0 +1,215 @@
+use core::{ffi::c_void, mem::size_of};
+use std::{
+    fs::{File, OpenOptions},
+    io::{self, Read, Write},
+    os::windows::io::{AsRawHandle, FromRawHandle},
+};
+
+use windows::{
+    Win32::{
+        Foundation::{CloseHandle, ERROR_PIPE_CONNECTED, HANDLE, HLOCAL, LocalFree},
+        Security::{
+            Authorization::{
+                ConvertSidToStringSidW, ConvertStringSecurityDescriptorToSecurityDescriptorW,
+                SDDL_REVISION_1,
+            },
+            GetTokenInformation, PSECURITY_DESCRIPTOR, SECURITY_ATTRIBUTES, TOKEN_QUERY,
+            TOKEN_USER, TokenUser,
+        },
+        Storage::FileSystem::{FILE_FLAG_FIRST_PIPE_INSTANCE, PIPE_ACCESS_DUPLEX},
+        System::{
+            Pipes::{
+                ConnectNamedPipe, CreateNamedPipeW, PIPE_READMODE_BYTE, PIPE_REJECT_REMOTE_CLIENTS,
+                PIPE_TYPE_BYTE, PIPE_UNLIMITED_INSTANCES, PIPE_WAIT,
+            },
+            RemoteDesktop::ProcessIdToSessionId,
+            Threading::{GetCurrentProcess, GetCurrentProcessId, OpenProcessToken},
+        },
+    },
+    core::{HSTRING, PWSTR},
+};
+
+/// The size of the pipe's input and output buffers, requests and responses are a single line.
+const BUFFER_SIZE: u32 = 1024;
+
+/// Listens for commands on a named pipe that only accepts local clients of the same user.
+pub struct Listener {
+    path: HSTRING,
+
+    /// Only allows the current user to open the pipe.
+    security: SecurityDescriptor,
+
+    /// The instance of the pipe the next client connects to.
+    next: Option<File>,
+}
+
+/// A connection to a client through an instance of the named pipe.
+pub struct PipeStream(File);
+
+/// A security descriptor allocated by Windows, freed when dropped.
+struct SecurityDescriptor(PSECURITY_DESCRIPTOR);
+
+// The descriptor is only read by Windows after it is created.
+unsafe impl Send for SecurityDescriptor {}
+
+impl Listener {
+    pub fn bind(name: &str) -> io::Result<Self> {
+        let sid = current_user_sid()?;
+        let path = HSTRING::from(pipe_path(name, &sid)?);
+
+        // Only the user, and nobody else, has access to the pipe.
+        let security = SecurityDescriptor::from_sddl(&format!("D:P(A;;GA;;;{sid})"))?;
+
+        // Creating the first instance claims the name, so another process cannot already be
+        // listening on it.
+        let first = create_instance(&path, &security, true)?;
+
+        Ok(Self {
+            path,
+            security,
+            next: Some(first),
+        })
+    }
+
+    /// Waits for the next client.
+    pub fn accept(&mut self) -> io::Result<PipeStream> {
+        let pipe = match self.next.take() {
+            Some(pipe) => pipe,
+            None => create_instance(&self.path, &self.security, false)?,
+        };
+
+        let handle = HANDLE(pipe.as_raw_handle());
+        if let Err(error) = unsafe { ConnectNamedPipe(handle, None) } {
+            // The client connected between creating the instance and waiting for one.
+            if error.code() != ERROR_PIPE_CONNECTED.to_hresult() {
+                return Err(io::Error::from(error));
+            }
+        }
+
+        Ok(PipeStream(pipe))
+    }
+}
+
+impl SecurityDescriptor {
+    fn from_sddl(sddl: &str) -> io::Result<Self> {
+        let mut descriptor = PSECURITY_DESCRIPTOR::default();
+        unsafe {
+            ConvertStringSecurityDescriptorToSecurityDescriptorW(
+                &HSTRING::from(sddl),
+                SDDL_REVISION_1,
+                &mut descriptor,
+                None,
+            )?;
+        }
+
+        Ok(Self(descriptor))
+    }
+}
+
+impl Drop for SecurityDescriptor {
+    fn drop(&mut self) {
+        unsafe {
+            let _ = LocalFree(Some(HLOCAL(self.0.0)));
+        }
+    }
+}
+
+impl Read for PipeStream {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.0.read(buf)
+    }
+}
+
+impl Write for PipeStream {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.write(buf)
+    }
+
+    /// Waits for the client to read the response, closing the pipe before then discards it.
+    fn flush(&mut self) -> io::Result<()> {
+        self.0.sync_all()
+    }
+}
+
+pub fn connect(name: &str) -> io::Result<File> {
+    let path = pipe_path(name, &current_user_sid()?)?;
+
+    OpenOptions::new().read(true).write(true).open(path)
+}
+
+/// The pipe's path, pipe names are machine wide, so the name is scoped to the user and their
+/// session as an instance runs in each session.
+fn pipe_path(name: &str, sid: &str) -> io::Result<String> {
+    let mut session = 0;
+    unsafe { ProcessIdToSessionId(GetCurrentProcessId(), &mut session)? };
+
+    Ok(format!(r"\\.\pipe\{name}-{sid}-{session}"))
+}
+
+/// The security identifier of the user running the application, like `S-1-5-21-...`.
+fn current_user_sid() -> io::Result<String> {
+    unsafe {
+        let mut token = HANDLE::default();
+        OpenProcessToken(GetCurrentProcess(), TOKEN_QUERY, &mut token)?;
+
+        // The first call finds the size of the user's information.
+        let mut size = 0;
+        let _ = GetTokenInformation(token, TokenUser, None, 0, &mut size);
+
+        let mut buffer = vec![0u8; size as usize];
+        let result = GetTokenInformation(
+            token,
+            TokenUser,
+            Some(buffer.as_mut_ptr().cast::<c_void>()),
+            size,
+            &mut size,
+        );
+        let _ = CloseHandle(token);
+        result?;
+
+        let user = buffer.as_ptr().cast::<TOKEN_USER>().read_unaligned();
+
+        let mut sid = PWSTR::null();
+        ConvertSidToStringSidW(user.User.Sid, &mut sid)?;
+        let string = sid.to_string();
+        let _ = LocalFree(Some(HLOCAL(sid.0.cast())));
+
+        string.map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))
+    }
+}
+
+fn create_instance(
+    path: &HSTRING,
+    security: &SecurityDescriptor,
+    is_first: bool,
+) -> io::Result<File> {
+    let mut open_mode = PIPE_ACCESS_DUPLEX;
+    if is_first {
+        open_mode |= FILE_FLAG_FIRST_PIPE_INSTANCE;
+    }
+
+    let attributes = SECURITY_ATTRIBUTES {
+        nLength: size_of::<SECURITY_ATTRIBUTES>() as u32,
+        lpSecurityDescriptor: security.0.0,
+        bInheritHandle: false.into(),
+    };
+
+    let handle = unsafe {
+        CreateNamedPipeW(
+            path,
+            open_mode,
+            PIPE_TYPE_BYTE | PIPE_READMODE_BYTE | PIPE_WAIT | PIPE_REJECT_REMOTE_CLIENTS,
+            PIPE_UNLIMITED_INSTANCES,
+            BUFFER_SIZE,
+            BUFFER_SIZE,
+            0,
+            Some(&attributes),
+        )
+    };
+
+    if handle.is_invalid() {
+        return Err(io::Error::last_os_error());
+    }
+
+    Ok(unsafe { File::from_raw_handle(handle.0) })
+}
@@ -0,0 +1,372 @@
+use core::fmt;
+use std::io::{self, BufRead, BufReader, Read, Write};
+
+use thiserror::Error;
+
+/// The version of the command protocol, increased whenever a change would stop an older instance
+/// from understanding a request the same way.
+pub const PROTOCOL_VERSION: u32 = 1;
+
+/// Starts every request, followed by `/` and the protocol version.
+const PROTOCOL_NAME: &str = "hdr-snipping-tool";
+
+/// The longest request or response that is read, so a stuck client cannot grow the buffer forever.
+const MAX_LINE_LENGTH: u64 = 1024;
+
+/// A command a second invocation forwards to the running instance.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Command {
+    /// Shows the screenshot to select a region of, like the region capture hotkey.
+    Capture,
+
+    /// Saves the hovered monitor without showing the screenshot.
+    CaptureFull,
+
+    /// Saves the last saved region again without showing the screenshot.
+    RepeatLast,
+
+    /// Reloads the config file.
+    ReloadConfig,
+
+    /// Exits the running instance.
+    Quit,
+}
+
+/// A problem with the command-line arguments.
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum ArgumentError {
+    #[error("The option \"{0}\" does not exist, the options are {options}", options = Command::usage())]
+    Unknown(String),
+
+    #[error("Only one command can be sent at a time, both {0} and {1} were given")]
+    MultipleCommands(Command, Command),
+}
+
+/// A request or response that does not follow the protocol.
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum ProtocolError {
+    #[error("The request \"{0}\" is malformed")]
+    Malformed(String),
+
+    #[error(
+        "Protocol version {0} is not supported, this version of HDR Snipping Tool supports version {PROTOCOL_VERSION}"
+    )]
+    UnsupportedVersion(u32),
+
+    #[error("The command \"{0}\" does not exist")]
+    UnknownCommand(String),
+}
+
+/// The running instance's answer to a request.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Response {
+    Accepted,
+
+    /// The request was not carried out, with why.
+    Refused(String),
+}
+
+impl Command {
+    pub const ALL: [Self; 5] = [
+        Self::Capture,
+        Self::CaptureFull,
+        Self::RepeatLast,
+        Self::ReloadConfig,
+        Self::Quit,
+    ];
+
+    /// The name of the command in requests, the command-line option is the name after `--`.
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Capture => "capture",
+            Self::CaptureFull => "capture-full",
+            Self::RepeatLast => "repeat-last",
+            Self::ReloadConfig => "reload-config",
+            Self::Quit => "quit",
+        }
+    }
+
+    /// Finds the command in the command-line arguments, without the program name. `None` if there
+    /// is no command, `--debug` is allowed alongside one.
+    pub fn from_args<Args>(args: Args) -> Result<Option<Self>, ArgumentError>
+    where
+        Args: IntoIterator,
+        Args::Item: AsRef<str>,
+    {
+        let mut found = None;
+
+        for arg in args {
+            let arg = arg.as_ref();
+            if arg == "--debug" {
+                continue;
+            }
+
+            let command = arg
+                .strip_prefix("--")
+                .and_then(Self::from_name)
+                .ok_or_else(|| ArgumentError::Unknown(arg.to_string()))?;
+
+            if let Some(previous) = found {
+                return Err(ArgumentError::MultipleCommands(previous, command));
+            }
+            found = Some(command);
+        }
+
+        Ok(found)
+    }
+
+    fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|command| command.name() == name)
+    }
+
+    /// The command-line options, written like `--capture, --quit`.
+    fn usage() -> String {
+        let options: Vec<_> = Self::ALL
+            .iter()
+            .map(|command| format!("--{}", command.name()))
+            .chain([String::from("--debug")])
+            .collect();
+        options.join(", ")
+    }
+}
+
+impl fmt::Display for Command {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "--{}", self.name())
+    }
+}
+
+/// Writes a request, like `hdr-snipping-tool/1 capture`.
+pub fn encode_request(command: Command) -> String {
+    format!("{PROTOCOL_NAME}/{PROTOCOL_VERSION} {}\n", command.name())
+}
+
+pub fn decode_request(request: &str) -> Result<Command, ProtocolError> {
+    let request = request.trim();
+    let malformed = || ProtocolError::Malformed(request.to_string());
+
+    let (version, command) = request
+        .strip_prefix(PROTOCOL_NAME)
+        .and_then(|rest| rest.strip_prefix('/'))
+        .and_then(|rest| rest.split_once(' '))
+        .ok_or_else(malformed)?;
+    let version: u32 = version.parse().map_err(|_| malformed())?;
+
+    // A newer client may send commands this version does not have, so the version is checked
+    // first to explain why.
+    if version != PROTOCOL_VERSION {
+        return Err(ProtocolError::UnsupportedVersion(version));
+    }
+
+    Command::from_name(command.trim())
+        .ok_or_else(|| ProtocolError::UnknownCommand(command.trim().to_string()))
+}
+
+/// Writes a response, `ok` or `error` followed by why.
+pub fn encode_response(response: &Response) -> String {
+    match response {
+        Response::Accepted => String::from("ok\n"),
+        Response::Refused(reason) => format!("error {}\n", reason.replace('\n', " ")),
+    }
+}
+
+pub fn decode_response(response: &str) -> Result<Response, ProtocolError> {
+    let response = response.trim_end_matches(['\r', '\n']);
+
+    if response == "ok" {
+        return Ok(Response::Accepted);
+    }
+
+    response
+        .strip_prefix("error ")
+        .map(|reason| Response::Refused(reason.to_string()))
+        .ok_or_else(|| ProtocolError::Malformed(response.to_string()))
+}
+
+/// Reads a request from a client, passes its command to `handle` and answers with the result.
+pub fn serve_connection<Stream, Handle>(stream: Stream, handle: &mut Handle) -> io::Result<()>
+where
+    Stream: Read + Write,
+    Handle: FnMut(Command) -> Result<(), String>,
+{
+    let mut reader = BufReader::new(stream);
+    let request = read_line(&mut reader)?;
+
+    let response = match decode_request(&request) {
+        Ok(command) => match handle(command) {
+            Ok(()) => Response::Accepted,
+            Err(reason) => Response::Refused(reason),
+        },
+        Err(error) => Response::Refused(error.to_string()),
+    };
+
+    let stream = reader.get_mut();
+    stream.write_all(encode_response(&response).as_bytes())?;
+    stream.flush()
+}
+
+/// Sends a request to the running instance and reads its response.
+pub fn send_request<Stream>(stream: Stream, command: Command) -> io::Result<Response>
+where
+    Stream: Read + Write,
+{
+    let mut reader = BufReader::new(stream);
+
+    let stream = reader.get_mut();
+    stream.write_all(encode_request(command).as_bytes())?;
+    stream.flush()?;
+
+    let response = read_line(&mut reader)?;
+    decode_response(&response).map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))
+}
+
+fn read_line<Stream: Read>(reader: &mut BufReader<Stream>) -> io::Result<String> {
+    let mut line = String::new();
+    reader.by_ref().take(MAX_LINE_LENGTH).read_line(&mut line)?;
+
+    if line.is_empty() {
+        return Err(io::Error::from(io::ErrorKind::UnexpectedEof));
+    }
+
+    Ok(line)
+}
+
+#[cfg(test)]
+mod test {
+    use std::io::{self, Cursor, Read, Write};
+
+    use super::{
+        ArgumentError, Command, PROTOCOL_VERSION, ProtocolError, Response, decode_request,
+        decode_response, encode_request, encode_response, serve_connection,
+    };
+
+    /// A client that has sent `request` and records the response.
+    struct Client {
+        request: Cursor<Vec<u8>>,
+        response: Vec<u8>,
+    }
+
+    impl Client {
+        fn new(request: &str) -> Self {
+            Self {
+                request: Cursor::new(request.as_bytes().to_vec()),
+                response: Vec::new(),
+            }
+        }
+    }
+
+    impl Read for Client {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            self.request.read(buf)
+        }
+    }
+
+    impl Write for Client {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.response.write(buf)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn parses_arguments() {
+        assert_eq!(Command::from_args(["--debug"]), Ok(None));
+        assert_eq!(
+            Command::from_args(["--capture-full", "--debug"]),
+            Ok(Some(Command::CaptureFull))
+        );
+        assert_eq!(
+            Command::from_args(["--reload-config"]),
+            Ok(Some(Command::ReloadConfig))
+        );
+
+        assert_eq!(
+            Command::from_args(["--capture", "--quit"]),
+            Err(ArgumentError::MultipleCommands(
+                Command::Capture,
+                Command::Quit
+            ))
+        );
+        assert_eq!(
+            Command::from_args(["capture"]),
+            Err(ArgumentError::Unknown(String::from("capture")))
+        );
+    }
+
+    #[test]
+    fn requests_round_trip() {
+        for command in Command::ALL {
+            assert_eq!(decode_request(&encode_request(command)), Ok(command));
+        }
+
+        assert_eq!(
+            encode_request(Command::RepeatLast),
+            format!("hdr-snipping-tool/{PROTOCOL_VERSION} repeat-last\n")
+        );
+    }
+
+    #[test]
+    fn rejects_other_versions_and_commands() {
+        assert_eq!(
+            decode_request("hdr-snipping-tool/2 capture-window"),
+            Err(ProtocolError::UnsupportedVersion(2))
+        );
+        assert_eq!(
+            decode_request("hdr-snipping-tool/1 capture-window"),
+            Err(ProtocolError::UnknownCommand(String::from(
+                "capture-window"
+            )))
+        );
+        assert_eq!(
+            decode_request("GET / HTTP/1.1"),
+            Err(ProtocolError::Malformed(String::from("GET / HTTP/1.1")))
+        );
+    }
+
+    #[test]
+    fn responses_round_trip() {
+        let responses = [
+            Response::Accepted,
+            Response::Refused(String::from("the application is shutting down")),
+        ];
+
+        for response in responses {
+            assert_eq!(decode_response(&encode_response(&response)), Ok(response));
+        }
+
+        // Reasons stay on a single line.
+        let response = Response::Refused(String::from("first\nsecond"));
+        assert_eq!(
+            decode_response(&encode_response(&response)),
+            Ok(Response::Refused(String::from("first second")))
+        );
+    }
+
+    #[test]
+    fn answers_requests() {
+        let mut received = Vec::new();
+        let mut handle = |command| {
+            received.push(command);
+            Ok(())
+        };
+
+        let mut client = Client::new("hdr-snipping-tool/1 capture-full\n");
+        serve_connection(&mut client, &mut handle).unwrap();
+        assert_eq!(client.response, b"ok\n");
+
+        // The handler is not called for requests it cannot understand.
+        let mut client = Client::new("hdr-snipping-tool/2 capture\n");
+        serve_connection(&mut client, &mut handle).unwrap();
+        assert_eq!(
+            decode_response(&String::from_utf8(client.response).unwrap()),
+            Ok(Response::Refused(
+                ProtocolError::UnsupportedVersion(2).to_string()
+            ))
+        );
+
+        assert_eq!(received, [Command::CaptureFull]);
+    }
+}
@@ -0,0 +1,120 @@
+use std::{
+    env,
+    fs::{self, DirBuilder},
+    io,
+    os::unix::{
+        fs::{DirBuilderExt, MetadataExt},
+        net::{UnixListener, UnixStream},
+    },
+    path::PathBuf,
+};
+
+use super::READ_TIMEOUT;
+
+/// Listens for commands on a Unix socket in the runtime directory.
+pub struct Listener {
+    listener: UnixListener,
+}
+
+impl Listener {
+    pub fn bind(name: &str) -> io::Result<Self> {
+        let path = socket_path(name)?;
+
+        // A socket left behind by an instance that did not exit cleanly can be replaced, but not
+        // one another instance is still listening on.
+        if path.exists() {
+            if UnixStream::connect(&path).is_ok() {
+                return Err(io::Error::new(
+                    io::ErrorKind::AddrInUse,
+                    "Another instance is listening on the socket",
+                ));
+            }
+
+            fs::remove_file(&path)?;
+        }
+
+        let listener = UnixListener::bind(path)?;
+        Ok(Self { listener })
+    }
+
+    /// Waits for the next client.
+    pub fn accept(&mut self) -> io::Result<UnixStream> {
+        let (stream, _) = self.listener.accept()?;
+        stream.set_read_timeout(Some(READ_TIMEOUT))?;
+
+        Ok(stream)
+    }
+}
+
+pub fn connect(name: &str) -> io::Result<UnixStream> {
+    UnixStream::connect(socket_path(name)?)
+}
+
+fn socket_path(name: &str) -> io::Result<PathBuf> {
+    let directory = match dirs::runtime_dir() {
+        Some(directory) => directory,
+        None => private_temp_dir()?,
+    };
+
+    Ok(directory.join(format!("{name}.sock")))
+}
+
+/// A directory in the shared temporary directory that only the user can access, used when there
+/// is no runtime directory.
+fn private_temp_dir() -> io::Result<PathBuf> {
+    let uid = unsafe { libc::geteuid() };
+    let directory = env::temp_dir().join(format!("hdr-snipping-tool-{uid}"));
+
+    match DirBuilder::new().mode(0o700).create(&directory) {
+        Ok(()) => {}
+        Err(error) if error.kind() == io::ErrorKind::AlreadyExists => {}
+        Err(error) => return Err(error),
+    }
+
+    // Another user could have created the directory first, to replace the socket in it.
+    let metadata = fs::symlink_metadata(&directory)?;
+    if !metadata.is_dir() || metadata.uid() != uid || metadata.mode() & 0o077 != 0 {
+        return Err(io::Error::new(
+            io::ErrorKind::PermissionDenied,
+            format!(
+                "The socket directory {} is not private to the user",
+                directory.display()
+            ),
+        ));
+    }
+
+    Ok(directory)
+}
+
+#[cfg(test)]
+mod test {
+    use std::{
+        fs,
+        os::unix::{fs::MetadataExt, net::UnixListener},
+        process,
+    };
+
+    use super::{Listener, private_temp_dir, socket_path};
+
+    #[test]
+    fn only_replaces_stale_sockets() {
+        let name = format!("hdr-snipping-tool-test-stale-{}", process::id());
+
+        // A socket whose listener was dropped without removing it.
+        drop(UnixListener::bind(socket_path(&name).unwrap()).unwrap());
+        let listener = Listener::bind(&name).unwrap();
+
+        assert!(Listener::bind(&name).is_err());
+        drop(listener);
+    }
+
+    #[test]
+    fn temp_dir_is_private() {
+        let directory = private_temp_dir().unwrap();
+        let metadata = fs::symlink_metadata(&directory).unwrap();
+
+        assert!(metadata.is_dir());
+        assert_eq!(metadata.mode() & 0o777, 0o700);
+        assert_eq!(metadata.uid(), unsafe { libc::geteuid() });
+    }
+}
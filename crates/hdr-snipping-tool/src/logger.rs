@@ -2,7 +2,7 @@ use tracing::{Level, subscriber::set_global_default};
 use tracing_appender::non_blocking::WorkerGuard;
 use tracing_subscriber::layer::SubscriberExt;
 
-use crate::config_dir;
+use crate::{config_dir, utilities::directories::LOG_FILE_NAME};
 
 pub fn setup_logger(should_debug: bool) -> [WorkerGuard; 2] {
     let level = if should_debug {
@@ -14,7 +14,7 @@ pub fn setup_logger(should_debug: bool) -> [WorkerGuard; 2] {
     let filter = tracing_subscriber::filter::Targets::new().with_default(level);
 
     // file logger
-    let file_appender = tracing_appender::rolling::never(config_dir(), "hdr-snipping-tool.log");
+    let file_appender = tracing_appender::rolling::never(config_dir(), LOG_FILE_NAME);
     let (file_writer, _file_guard) = tracing_appender::non_blocking(file_appender);
 
     let file_logger = tracing_subscriber::fmt::layer()
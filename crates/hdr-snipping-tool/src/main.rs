@@ -6,35 +6,49 @@
 // hide console window on Windows in release
 #![cfg_attr(feature = "hide-console", windows_subsystem = "windows")]
 
-use application::ApplicationEvent;
-use application_event_loop::{ApplicationEventLoop, Event, TrayIcon};
+use std::process::ExitCode;
+
+use application::KeyboardEvent;
+use application_event_loop::{ApplicationEventLoop, CANCEL_COUNTDOWN_KEY, Event, TrayIcon};
 
 #[global_allocator]
 static GLOBAL: mimalloc::MiMalloc = mimalloc::MiMalloc;
 
-pub use utilities::directories::{config_dir, screenshot_dir};
+pub use utilities::directories::{config_dir, diagnostics_dir, history_dir, screenshot_dir};
 
 use config::Config;
+use error_reporting::NotificationSink;
 use global_hotkey::{GlobalHotKeyEvent, GlobalHotKeyManager, HotKeyState, hotkey::HotKey};
-use tracing::{info, info_span, warn};
+use hotkeys::Hotkeys;
+use ipc::Command;
+use tracing::{error, info, info_span, warn};
 use utilities::{
-    failure::{Failure, Ignore, report_and_panic},
-    windows_helpers::{display_message, is_first_instance},
-};
-use windows::Win32::UI::WindowsAndMessaging::{
-    IDNO, IDYES, MB_DEFBUTTON2, MB_ICONWARNING, MB_SETFOREGROUND, MB_YESNO,
+    failure::{Failure, Ignore},
+    windows_helpers::is_first_instance,
 };
 use winit::event_loop::EventLoop;
 
+mod annotation;
 mod application;
 mod application_event_loop;
+mod burst;
+mod capture_history;
 mod capture_saver;
 mod capture_taker;
+mod clipboard;
 mod config;
+mod diagnostics;
+mod error_reporting;
+mod hotkeys;
+mod ipc;
 #[cfg(feature = "log")]
 mod logger;
+mod recording;
 mod renderer_thread;
+mod save_hooks;
+mod save_notification;
 mod selection;
+mod settings;
 mod utilities;
 
 /// The Cargo package version.
@@ -50,7 +64,7 @@ pub fn should_debug() -> bool {
     std::env::args().any(|arg| arg.eq("--debug"))
 }
 
-fn main() {
+fn main() -> ExitCode {
     // Set up logger
     #[cfg(feature = "log")]
     let _logger_guards = logger::setup_logger(should_debug());
@@ -59,58 +73,46 @@ fn main() {
     let _span = info_span!("[Main Thread]").entered();
     info!("HDR Snipping Tool v{}", VERSION);
 
-    // Ensure this instance is the first instance running.
+    // Parse the command to send to the running instance
+    let command = match Command::from_args(std::env::args().skip(1)) {
+        Ok(command) => command,
+        Err(error) => {
+            error!("Exiting: {error}");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    // Ensure this instance is the first instance running, otherwise forward the command to it.
     {
         let is_fist_instance = is_first_instance()
             .report_and_panic("Could not check if HDR Snipping Tool was already running");
 
         if !is_fist_instance {
-            warn!("Exiting: HDR Snipping Tool is already running.");
-            return;
+            let Some(command) = command else {
+                warn!("Exiting: HDR Snipping Tool is already running.");
+                return ExitCode::SUCCESS;
+            };
+
+            return match ipc::send(ipc::CHANNEL_NAME, command) {
+                Ok(()) => {
+                    info!("Exiting: Sent {command} to the running instance.");
+                    ExitCode::SUCCESS
+                }
+                Err(error) => {
+                    error!("Exiting: Could not send {command}:\n{error}");
+                    ExitCode::FAILURE
+                }
+            };
         }
-    }
 
-    // Load config
-    let config = {
-        let maybe_config = match Config::try_load_config() {
-            Ok(maybe_config) => maybe_config,
-            Err(error) => {
-                warn!("Could not deserialize config file:\n{error}");
-                let action = display_message(
-                    "Your config file is invalid.\nMore details are in the logs.\n\nClear and reset your config file?",
-                    MB_SETFOREGROUND | MB_YESNO | MB_ICONWARNING | MB_DEFBUTTON2,
-                );
-
-                match action {
-                    // If user wants default config, maybe_config should be none for a new one to be
-                    // created.
-                    IDYES => {
-                        info!("Resetting config file.");
-                        None
-                    }
-
-                    IDNO => {
-                        warn!("Exiting: Invalid config.");
-                        return;
-                    }
-
-                    value => report_and_panic(
-                        format!("Message box returned an unexpected response: {value:?}"),
-                        "Message box returned an unexpected response",
-                    ),
-                }
-            }
-        };
-
-        match maybe_config {
-            Some(config) => config,
-            None => {
-                let config = Config::default();
-                config.save();
-                config
-            }
+        if command == Some(Command::Quit) {
+            info!("Exiting: HDR Snipping Tool is not running.");
+            return ExitCode::SUCCESS;
         }
-    };
+    }
+
+    // Write a diagnostic bundle if the application crashes
+    diagnostics::install_panic_hook();
 
     // Create event loop
     let event_loop: EventLoop<Event> = EventLoop::with_user_event()
@@ -118,8 +120,58 @@ fn main() {
         .report_and_panic("Could not create the application window");
     event_loop.set_control_flow(winit::event_loop::ControlFlow::Wait);
 
+    // Show errors without interrupting the application, they are shown once the event loop runs
+    error_reporting::install(Box::new(NotificationSink::new(event_loop.create_proxy())));
+
+    // Load config
+    let config = match Config::try_load_config() {
+        Ok(Some(loaded)) => {
+            config::report_warnings(&loaded.warnings);
+            loaded.config
+        }
+
+        Ok(None) => {
+            let config = Config::default();
+            config.save();
+            config
+        }
+
+        // The file is kept so it can be fixed, it is loaded once it is saved without errors.
+        Err(errors) => {
+            config::report_problems(
+                config::describe(&errors),
+                "The config file has errors, the default config is being used until they are fixed",
+            );
+            Config::default()
+        }
+    };
+    diagnostics::record_config(&config);
+
+    // Listen for commands from later invocations
+    {
+        let proxy = event_loop.create_proxy();
+
+        let result = ipc::serve(ipc::CHANNEL_NAME, move |command| {
+            proxy
+                .send_event(command.event())
+                .map_err(|_| String::from("HDR Snipping Tool is exiting"))
+        });
+
+        if let Err(error) = result {
+            warn!("Could not listen for commands from other invocations:\n{error}");
+        }
+    }
+
+    // Carry out the command this instance was started with once the app is running
+    if let Some(command) = command {
+        event_loop
+            .create_proxy()
+            .send_event(command.event())
+            .ignore();
+    }
+
     // Register tray icon event handler
-    let _tray_icon = {
+    let tray_icon = {
         let tray_icon = TrayIcon::new();
 
         let proxy = event_loop.create_proxy();
@@ -130,36 +182,48 @@ fn main() {
         tray_icon
     };
 
-    // Register screenshot hotkey
-    let _hotkey_manager = {
+    // Register hotkeys
+    let (hotkey_manager, hotkeys) = {
         let hotkey_manager =
-            GlobalHotKeyManager::new().report_and_panic("Could not setup screenshot hotkey");
+            GlobalHotKeyManager::new().report_and_panic("Could not setup the hotkeys");
 
-        let hotkey = HotKey::new(None, config.screenshot_key);
-        hotkey_manager
-            .register(hotkey)
-            .report_and_panic("Could not register screenshot hotkey");
+        let (hotkeys, errors) = Hotkeys::register(&hotkey_manager, &config.hotkeys);
+        hotkeys::report(&errors);
 
-        hotkey_manager
+        (hotkey_manager, hotkeys)
     };
 
     // Setup hotkey event handler
     {
         let proxy = event_loop.create_proxy();
+        let cancel_countdown_id = HotKey::new(None, CANCEL_COUNTDOWN_KEY).id();
 
         GlobalHotKeyEvent::set_event_handler(Some(move |event: GlobalHotKeyEvent| {
-            if event.state == HotKeyState::Pressed {
-                info!("Hotkey pressed");
+            if event.state != HotKeyState::Pressed {
+                return;
+            }
+
+            if event.id == cancel_countdown_id {
                 proxy
-                    .send_event(ApplicationEvent::ScreenshotKeyPressed.into())
+                    .send_event(KeyboardEvent::EscapePressed.into())
                     .ignore();
+            } else {
+                proxy.send_event(Event::Hotkey(event.id)).ignore();
             }
         }));
     }
 
     // Create the app
-    let mut app = ApplicationEventLoop::new(event_loop.create_proxy());
+    let mut app = ApplicationEventLoop::new(
+        event_loop.create_proxy(),
+        config,
+        tray_icon,
+        hotkey_manager,
+        hotkeys,
+    );
 
     // Run the app
     event_loop.run_app(&mut app).ignore();
+
+    ExitCode::SUCCESS
 }
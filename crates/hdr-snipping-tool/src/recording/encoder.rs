@@ -0,0 +1,191 @@
+use core::time::Duration;
+use std::{
+    fs::File,
+    io::{self, BufWriter, Write},
+    path::Path,
+};
+
+use image::{
+    Delay, Frame, ImageError, RgbaImage,
+    codecs::gif::{GifEncoder, Repeat},
+};
+use png::{BitDepth, ColorType, EncodingError};
+use thiserror::Error;
+
+use super::{FrameSequence, RecordingFormat};
+
+/// How hard the GIF encoder works to find each frame's palette, from `1` (best) to `30`.
+const GIF_SPEED: i32 = 10;
+
+/// Encodes a recording to a file, the file is synced before returning.
+pub fn save(
+    sequence: &FrameSequence,
+    format: RecordingFormat,
+    path: &Path,
+) -> Result<(), EncodeError> {
+    let file = File::create(path)?;
+    let mut writer = BufWriter::new(file);
+    encode(sequence, format, &mut writer)?;
+
+    writer.flush()?;
+    writer.get_ref().sync_all()?;
+
+    Ok(())
+}
+
+/// Encodes a recording to an animated image that loops forever.
+pub fn encode(
+    sequence: &FrameSequence,
+    format: RecordingFormat,
+    writer: impl Write,
+) -> Result<(), EncodeError> {
+    if sequence.frames.is_empty() {
+        return Err(EncodeError::Empty);
+    }
+
+    match format {
+        RecordingFormat::Apng => encode_apng(sequence, writer),
+        RecordingFormat::Gif => encode_gif(sequence, writer),
+    }
+}
+
+fn encode_apng(sequence: &FrameSequence, writer: impl Write) -> Result<(), EncodeError> {
+    let [width, height] = sequence.size;
+
+    let mut encoder = png::Encoder::new(writer, width, height);
+    encoder.set_color(ColorType::Rgba);
+    encoder.set_depth(BitDepth::Eight);
+    encoder.set_animated(sequence.frames.len() as u32, 0)?;
+
+    let mut writer = encoder.write_header()?;
+    for frame in &sequence.frames {
+        writer.set_frame_delay(delay_milliseconds(frame.delay), 1000)?;
+        writer.write_image_data(&frame.pixels)?;
+    }
+    writer.finish()?;
+
+    Ok(())
+}
+
+fn encode_gif(sequence: &FrameSequence, writer: impl Write) -> Result<(), EncodeError> {
+    let [width, height] = sequence.size;
+
+    let mut encoder = GifEncoder::new_with_speed(writer, GIF_SPEED);
+    encoder.set_repeat(Repeat::Infinite)?;
+
+    for frame in &sequence.frames {
+        let image = RgbaImage::from_raw(width, height, frame.pixels.clone())
+            .expect("Frames should match the size of the sequence");
+
+        encoder.encode_frame(Frame::from_parts(
+            image,
+            0,
+            0,
+            Delay::from_saturating_duration(frame.delay),
+        ))?;
+    }
+
+    Ok(())
+}
+
+/// Converts a frame delay to whole milliseconds, a delay of `0` is shown as briefly as possible
+/// so it is at least `1`.
+fn delay_milliseconds(delay: Duration) -> u16 {
+    delay.as_millis().clamp(1, u128::from(u16::MAX)) as u16
+}
+
+#[derive(Debug, Error)]
+pub enum EncodeError {
+    #[error("The recording has no frames")]
+    Empty,
+
+    #[error("Could not write the file:\n{0}")]
+    Io(#[from] io::Error),
+
+    #[error("Could not encode the PNG:\n{0}")]
+    Png(#[from] EncodingError),
+
+    #[error("Could not encode the GIF:\n{0}")]
+    Gif(#[from] ImageError),
+}
+
+#[cfg(test)]
+mod test {
+    use core::time::Duration;
+    use std::io::Cursor;
+
+    use image::{AnimationDecoder, codecs::gif::GifDecoder};
+
+    use crate::recording::{FrameSequence, RecordingFormat, SdrFrame};
+
+    use super::{EncodeError, encode};
+
+    /// Two 2x1 frames, red then blue.
+    fn sequence() -> FrameSequence {
+        FrameSequence {
+            size: [2, 1],
+            frames: vec![
+                SdrFrame {
+                    pixels: [255, 0, 0, 255].repeat(2),
+                    delay: Duration::from_millis(70),
+                },
+                SdrFrame {
+                    pixels: [0, 0, 255, 255].repeat(2),
+                    delay: Duration::from_millis(120),
+                },
+            ],
+        }
+    }
+
+    #[test]
+    fn encodes_apng() {
+        let sequence = sequence();
+        let mut bytes = Vec::new();
+        encode(&sequence, RecordingFormat::Apng, &mut bytes).unwrap();
+
+        let mut decoder = png::Decoder::new(Cursor::new(bytes)).read_info().unwrap();
+        let animation = decoder.info().animation_control.unwrap();
+        assert_eq!(animation.num_frames, 2);
+        assert_eq!(animation.num_plays, 0);
+
+        let mut buffer = vec![0; decoder.output_buffer_size().unwrap()];
+        for (frame, delay) in sequence.frames.iter().zip([70, 120]) {
+            decoder.next_frame(&mut buffer).unwrap();
+            assert_eq!(buffer, frame.pixels);
+
+            let control = decoder.info().frame_control.unwrap();
+            assert_eq!((control.delay_num, control.delay_den), (delay, 1000));
+        }
+    }
+
+    #[test]
+    fn encodes_gif() {
+        let sequence = sequence();
+        let mut bytes = Vec::new();
+        encode(&sequence, RecordingFormat::Gif, &mut bytes).unwrap();
+
+        let frames = GifDecoder::new(Cursor::new(bytes))
+            .unwrap()
+            .into_frames()
+            .collect_frames()
+            .unwrap();
+        assert_eq!(frames.len(), 2);
+
+        // GIF delays are in hundredths of a second.
+        for ((frame, expected), delay) in frames.iter().zip(&sequence.frames).zip([70, 120]) {
+            assert_eq!(frame.buffer().as_raw(), &expected.pixels);
+            assert_eq!(frame.delay().numer_denom_ms(), (delay, 1));
+        }
+    }
+
+    #[test]
+    fn rejects_empty_recordings() {
+        let sequence = FrameSequence {
+            size: [2, 1],
+            frames: Vec::new(),
+        };
+
+        let result = encode(&sequence, RecordingFormat::Apng, Vec::new());
+        assert!(matches!(result, Err(EncodeError::Empty)));
+    }
+}
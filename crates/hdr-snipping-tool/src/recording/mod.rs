@@ -0,0 +1,103 @@
+use core::{
+    sync::atomic::{AtomicBool, Ordering},
+    time::Duration,
+};
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+
+use crate::capture_taker::DesktopRect;
+
+pub use encoder::{EncodeError, encode, save};
+pub use sequence::{FrameSequence, FrameTonemapper, Recorder, SdrFrame};
+
+mod encoder;
+mod sequence;
+
+/// The animated format recordings are saved in.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RecordingFormat {
+    /// Animated PNG, lossless with full colour.
+    #[default]
+    Apng,
+
+    /// GIF, smaller and more widely supported but limited to 256 colours per frame.
+    Gif,
+}
+
+impl RecordingFormat {
+    /// The file extension of the format.
+    pub fn extension(self) -> &'static str {
+        match self {
+            Self::Apng => "png",
+            Self::Gif => "gif",
+        }
+    }
+}
+
+/// How regions are recorded.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RecordingSettings {
+    /// The format recordings are saved in.
+    pub format: RecordingFormat,
+
+    /// How many frames are recorded each second.
+    pub frame_rate: u32,
+
+    /// The longest a recording may run for before it stops by itself.
+    pub max_seconds: u32,
+}
+
+impl RecordingSettings {
+    /// The time between frames.
+    pub fn frame_interval(&self) -> Duration {
+        Duration::from_secs(1) / self.frame_rate.max(1)
+    }
+
+    pub fn max_duration(&self) -> Duration {
+        Duration::from_secs(u64::from(self.max_seconds))
+    }
+}
+
+impl Default for RecordingSettings {
+    fn default() -> Self {
+        Self {
+            format: RecordingFormat::default(),
+            frame_rate: 15,
+            max_seconds: 30,
+        }
+    }
+}
+
+/// Stops a running recording or burst, shared between the application and the capture taker.
+#[derive(Clone, Debug, Default)]
+pub struct RecordingHandle {
+    stopped: Arc<AtomicBool>,
+}
+
+impl RecordingHandle {
+    /// Asks the recording or burst to stop after the current frame.
+    pub fn stop(&self) {
+        self.stopped.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_stopped(&self) -> bool {
+        self.stopped.load(Ordering::Relaxed)
+    }
+}
+
+/// A region of the desktop to record.
+#[derive(Clone, Debug)]
+pub struct RecordingRequest {
+    /// The region in desktop coordinates, it is cropped to the monitor under its centre.
+    pub area: DesktopRect,
+
+    /// The name the recording is saved with, without the extension.
+    pub file_name: String,
+
+    pub settings: RecordingSettings,
+
+    pub handle: RecordingHandle,
+}
@@ -0,0 +1,285 @@
+use core::time::Duration;
+
+use crate::capture_taker::{MonitorInfo, WhitepointAdapter, WhitepointStrategy};
+
+/// The most memory the tonemapped frames of a recording may take up.
+const MAX_SEQUENCE_BYTES: usize = 1024 * 1024 * 1024;
+
+/// Tonemaps the HDR frames of a recording.
+pub trait FrameTonemapper {
+    type Frame;
+
+    type Error;
+
+    /// Returns the brightest value in the frame.
+    fn maximum(&mut self, frame: &Self::Frame) -> Result<f32, Self::Error>;
+
+    /// Tonemaps the recorded region of the frame to `RGBA` bytes, values are clamped to the
+    /// whitepoint.
+    fn tonemap(&mut self, frame: &Self::Frame, whitepoint: f32) -> Result<Vec<u8>, Self::Error>;
+}
+
+/// A tonemapped frame of a recording.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SdrFrame {
+    /// The `RGBA` pixels.
+    pub pixels: Vec<u8>,
+
+    /// How long the frame is shown for.
+    pub delay: Duration,
+}
+
+/// The tonemapped frames of a finished recording.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FrameSequence {
+    pub size: [u32; 2],
+    pub frames: Vec<SdrFrame>,
+}
+
+/// Tonemaps the frames of a recording as they arrive.
+pub struct Recorder<T: FrameTonemapper> {
+    tonemapper: T,
+    size: [u32; 2],
+    whitepoint: WhitepointAdapter,
+
+    /// The most memory the frames may take up.
+    max_bytes: usize,
+
+    /// The frames and when they were recorded.
+    frames: Vec<(Vec<u8>, Duration)>,
+}
+
+impl<T: FrameTonemapper> Recorder<T> {
+    /// Creates a recorder for a region of `size` on a monitor.
+    pub fn new(tonemapper: T, monitor: MonitorInfo, size: [u32; 2]) -> Self {
+        Self {
+            tonemapper,
+            size,
+            whitepoint: WhitepointAdapter::new(monitor.sdr_white, monitor.max_brightness),
+            max_bytes: MAX_SEQUENCE_BYTES,
+            frames: Vec::new(),
+        }
+    }
+
+    /// Chooses the whitepoint of each frame with a strategy instead of automatically.
+    pub fn with_whitepoint_strategy(mut self, strategy: WhitepointStrategy) -> Self {
+        self.whitepoint = self.whitepoint.with_strategy(strategy);
+        self
+    }
+
+    /// Tonemaps a frame recorded `timestamp` after the recording started.
+    pub fn add_frame(&mut self, frame: &T::Frame, timestamp: Duration) -> Result<(), T::Error> {
+        let maximum = self.tonemapper.maximum(frame)?;
+        let whitepoint = self.whitepoint.update(maximum, timestamp);
+
+        let pixels = self.tonemapper.tonemap(frame, whitepoint.value())?;
+        debug_assert_eq!(pixels.len(), self.frame_bytes());
+
+        // An unchanged frame only extends how long the previous frame is shown.
+        if self.frames.last().is_some_and(|(last, _)| *last == pixels) {
+            return Ok(());
+        }
+
+        self.frames.push((pixels, timestamp));
+        Ok(())
+    }
+
+    /// If another frame would take the recording over its memory budget.
+    pub fn is_full(&self) -> bool {
+        (self.frames.len() + 1) * self.frame_bytes() > self.max_bytes
+    }
+
+    /// Finishes a recording that ended `end` after it started.
+    pub fn finish(self, end: Duration) -> FrameSequence {
+        // Each frame is shown until the next frame, the last until the recording ended.
+        let ends: Vec<_> = self
+            .frames
+            .iter()
+            .skip(1)
+            .map(|(_, timestamp)| *timestamp)
+            .chain([end])
+            .collect();
+
+        let frames = self
+            .frames
+            .into_iter()
+            .zip(ends)
+            .map(|((pixels, start), end)| SdrFrame {
+                pixels,
+                delay: end.saturating_sub(start),
+            })
+            .collect();
+
+        FrameSequence {
+            size: self.size,
+            frames,
+        }
+    }
+
+    fn frame_bytes(&self) -> usize {
+        self.size[0] as usize * self.size[1] as usize * 4
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use core::{convert::Infallible, time::Duration};
+    use std::io::Cursor;
+
+    use half::f16;
+
+    use crate::{
+        capture_taker::{DesktopRect, MonitorInfo},
+        recording::{RecordingFormat, encode},
+    };
+
+    use super::{FrameTonemapper, Recorder};
+
+    /// Tonemaps `RGBA` frames on the CPU the same way as the tonemapping shader.
+    #[derive(Default)]
+    struct CpuTonemapper {
+        /// The whitepoint each frame was tonemapped with.
+        whitepoints: Vec<f32>,
+    }
+
+    impl FrameTonemapper for CpuTonemapper {
+        type Frame = Vec<f16>;
+        type Error = Infallible;
+
+        fn maximum(&mut self, frame: &Self::Frame) -> Result<f32, Self::Error> {
+            Ok(frame
+                .chunks_exact(4)
+                .flat_map(|pixel| &pixel[..3])
+                .map(|component| component.to_f32())
+                .fold(0.0, f32::max))
+        }
+
+        fn tonemap(
+            &mut self,
+            frame: &Self::Frame,
+            whitepoint: f32,
+        ) -> Result<Vec<u8>, Self::Error> {
+            self.whitepoints.push(whitepoint);
+
+            let tonemap = |value: f16| {
+                let value = value.to_f32().clamp(0.0, whitepoint) / whitepoint;
+                let value = if value <= 0.003_130_8 {
+                    value * 12.92
+                } else {
+                    1.055 * value.powf(1.0 / 2.4) - 0.055
+                };
+                (value * 255.0).round() as u8
+            };
+
+            Ok(frame
+                .chunks_exact(4)
+                .flat_map(|pixel| [tonemap(pixel[0]), tonemap(pixel[1]), tonemap(pixel[2]), 255])
+                .collect())
+        }
+    }
+
+    const MONITOR: MonitorInfo = MonitorInfo {
+        rect: DesktopRect {
+            left: 0,
+            top: 0,
+            right: 2,
+            bottom: 2,
+        },
+        sdr_white: 1.0,
+        min_brightness: 0.0,
+        max_brightness: 4.0,
+    };
+
+    /// A 2x2 frame where every component is `value`.
+    fn frame(value: f32) -> Vec<f16> {
+        [value, value, value, 1.0]
+            .repeat(4)
+            .into_iter()
+            .map(f16::from_f32)
+            .collect()
+    }
+
+    fn milliseconds(milliseconds: u64) -> Duration {
+        Duration::from_millis(milliseconds)
+    }
+
+    #[test]
+    fn whitepoint_fades_into_hdr() {
+        let mut recorder = Recorder::new(CpuTonemapper::default(), MONITOR, [2, 2]);
+
+        recorder.add_frame(&frame(0.5), Duration::ZERO).unwrap();
+        for index in 1..=30 {
+            recorder
+                .add_frame(&frame(4.0), milliseconds(index * 100))
+                .unwrap();
+        }
+
+        let whitepoints = &recorder.tonemapper.whitepoints;
+        assert_eq!(whitepoints[0], MONITOR.sdr_white);
+        assert!(whitepoints.windows(2).all(|pair| pair[0] < pair[1]));
+        assert!(whitepoints[1] < 2.0);
+        assert!((whitepoints[30] - MONITOR.max_brightness).abs() < 0.01);
+
+        // The SDR frame is tonemapped relative to SDR white.
+        let sequence = recorder.finish(milliseconds(3100));
+        assert_eq!(sequence.frames[0].pixels[..4], [188, 188, 188, 255]);
+    }
+
+    #[test]
+    fn unchanged_frames_extend_the_previous_frame() {
+        let mut recorder = Recorder::new(CpuTonemapper::default(), MONITOR, [2, 2]);
+
+        recorder.add_frame(&frame(0.25), Duration::ZERO).unwrap();
+        recorder.add_frame(&frame(0.25), milliseconds(100)).unwrap();
+        recorder.add_frame(&frame(0.5), milliseconds(300)).unwrap();
+
+        let sequence = recorder.finish(milliseconds(400));
+        assert_eq!(sequence.size, [2, 2]);
+
+        let delays: Vec<_> = sequence.frames.iter().map(|frame| frame.delay).collect();
+        assert_eq!(delays, [milliseconds(300), milliseconds(100)]);
+    }
+
+    #[test]
+    fn fills_memory_budget() {
+        let mut recorder = Recorder::new(CpuTonemapper::default(), MONITOR, [2, 2]);
+        recorder.max_bytes = 2 * 2 * 4 * 2;
+
+        recorder.add_frame(&frame(0.25), Duration::ZERO).unwrap();
+        assert!(!recorder.is_full());
+
+        recorder.add_frame(&frame(0.5), milliseconds(100)).unwrap();
+        assert!(recorder.is_full());
+    }
+
+    #[test]
+    fn records_and_encodes_synthetic_frames() {
+        let mut recorder = Recorder::new(CpuTonemapper::default(), MONITOR, [2, 2]);
+
+        let values = [0.25, 0.5, 2.0, 0.5, 0.25];
+        for (index, value) in values.into_iter().enumerate() {
+            recorder
+                .add_frame(&frame(value), milliseconds(index as u64 * 100))
+                .unwrap();
+        }
+        let sequence = recorder.finish(milliseconds(500));
+        assert_eq!(sequence.frames.len(), 5);
+
+        let mut bytes = Vec::new();
+        encode(&sequence, RecordingFormat::Apng, &mut bytes).unwrap();
+
+        let mut decoder = png::Decoder::new(Cursor::new(bytes)).read_info().unwrap();
+        let animation = decoder.info().animation_control.unwrap();
+        assert_eq!(animation.num_frames, 5);
+
+        let mut buffer = vec![0; decoder.output_buffer_size().unwrap()];
+        for frame in &sequence.frames {
+            decoder.next_frame(&mut buffer).unwrap();
+            assert_eq!(buffer, frame.pixels);
+
+            let control = decoder.info().frame_control.unwrap();
+            assert_eq!(control.delay_num, 100);
+            assert_eq!(control.delay_den, 1000);
+        }
+    }
+}
@@ -8,7 +8,7 @@ use std::{
 
 use parking_lot::Mutex;
 use tracing::{error, info_span};
-use vulkan::{HdrImage, RendererState, Vulkan};
+use vulkan::{HdrImage, RendererState, RendererStroke, Vulkan};
 use winit::{
     dpi::PhysicalPosition,
     raw_window_handle::{HasDisplayHandle, HasWindowHandle},
@@ -16,7 +16,8 @@ use winit::{
 };
 
 use crate::{
-    selection::Selection,
+    annotation::{Annotation, AnnotationEditor},
+    selection::{Lasso, Selection},
     utilities::failure::{Failure, Ignore},
 };
 
@@ -116,6 +117,37 @@ impl RendererThread {
         state.selection = [selection.start.into(), selection.end.into()];
     }
 
+    pub fn set_lasso(&mut self, lasso: Option<&Lasso>) {
+        let mut state = self.state.lock();
+        state.lasso = match lasso {
+            Some(lasso) => lasso.points.iter().map(|point| (*point).into()).collect(),
+            None => Vec::new(),
+        };
+    }
+
+    pub fn set_annotations(&mut self, annotations: Option<&AnnotationEditor>) {
+        let mut state = self.state.lock();
+        state.strokes = match annotations {
+            Some(annotations) => annotations
+                .drawing()
+                .into_iter()
+                .flat_map(Annotation::strokes)
+                .map(|stroke| RendererStroke {
+                    points: stroke.points,
+                    width: stroke.width,
+                    colour: stroke.colour.as_f32(),
+                })
+                .collect(),
+            None => Vec::new(),
+        };
+        state.annotations = annotations
+            .map(|annotations| annotations.annotations().passes())
+            .unwrap_or_default();
+        state.redactions = annotations
+            .map(|annotations| annotations.annotations().redactions())
+            .unwrap_or_default();
+    }
+
     pub fn set_hdr_capture(&mut self, hdr_capture: Option<HdrImage>) {
         let mut state = self.state.lock();
         state.capture = hdr_capture;
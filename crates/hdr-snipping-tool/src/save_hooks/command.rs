@@ -0,0 +1,309 @@
+use core::time::Duration;
+use std::{
+    collections::VecDeque,
+    io::{BufRead, BufReader, Read},
+    process::{Command, Stdio},
+    thread::{self, JoinHandle},
+    time::Instant,
+};
+
+use tracing::{info, info_span, warn};
+
+use crate::utilities::failure::{Failure, Ignore};
+
+use super::{SaveHookError, SavedFile};
+
+/// How often a running command is checked for having finished.
+const POLL_INTERVAL: Duration = Duration::from_millis(20);
+
+/// How long to wait for the rest of the output once a command finishes, a program it started may
+/// still be holding the output open.
+const OUTPUT_GRACE: Duration = Duration::from_millis(500);
+
+/// How many of the last lines of output are included when a command fails.
+const REPORTED_LINES: usize = 10;
+
+/// Starts the name of each environment variable.
+const VARIABLE_PREFIX: &str = "HDR_SNIPPING_TOOL_";
+
+/// The details of the file given to commands, as `{name}` placeholders in the arguments and as
+/// `HDR_SNIPPING_TOOL_NAME` environment variables.
+fn variables(file: &SavedFile) -> [(&'static str, String); 6] {
+    let file_name = file
+        .path
+        .file_name()
+        .unwrap_or_default()
+        .to_string_lossy()
+        .into_owned();
+
+    [
+        ("path", file.path.display().to_string()),
+        ("file_name", file_name),
+        ("kind", file.kind.name().to_string()),
+        ("width", file.size[0].to_string()),
+        ("height", file.size[1].to_string()),
+        ("whitepoint", file.whitepoint.to_string()),
+    ]
+}
+
+/// Replaces the placeholders in the arguments with the details of the file.
+fn expand_arguments(arguments: &[String], file: &SavedFile) -> Vec<String> {
+    let variables = variables(file);
+
+    arguments
+        .iter()
+        .map(|argument| {
+            variables
+                .iter()
+                .fold(argument.clone(), |argument, (name, value)| {
+                    argument.replace(&format!("{{{name}}}"), value)
+                })
+        })
+        .collect()
+}
+
+/// Runs the command for the file, logging its output and stopping it after the timeout.
+pub fn run_command(
+    arguments: &[String],
+    file: &SavedFile,
+    timeout: Duration,
+) -> Result<(), SaveHookError> {
+    let mut arguments = expand_arguments(arguments, file).into_iter();
+    let program = arguments
+        .next()
+        .filter(|program| !program.trim().is_empty())
+        .ok_or(SaveHookError::EmptyCommand)?;
+
+    let mut command = Command::new(&program);
+    command
+        .args(arguments)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped());
+    for (name, value) in variables(file) {
+        command.env(format!("{VARIABLE_PREFIX}{}", name.to_uppercase()), value);
+    }
+
+    // Console programs would otherwise flash a console window.
+    #[cfg(windows)]
+    {
+        use std::os::windows::process::CommandExt;
+        use windows::Win32::System::Threading::CREATE_NO_WINDOW;
+
+        command.creation_flags(CREATE_NO_WINDOW.0);
+    }
+
+    let mut child = command
+        .spawn()
+        .map_err(|error| SaveHookError::Start(program.clone(), error))?;
+    info!("Started {program:?}");
+
+    // Commands are often run through a shell, stopping only the shell would leave the programs
+    // it started running.
+    #[cfg(windows)]
+    let job = match super::job::Job::containing(&child) {
+        Ok(job) => Some(job),
+        Err(e) => {
+            warn!("Could not put {program:?} in a job, only it will be stopped on timeout: {e}");
+            None
+        }
+    };
+
+    let stdout = log_output(&program, child.stdout.take(), false);
+    let stderr = log_output(&program, child.stderr.take(), true);
+
+    let started = Instant::now();
+    let status = loop {
+        match child.try_wait() {
+            Ok(Some(status)) => break status,
+
+            Ok(None) if started.elapsed() < timeout => thread::sleep(POLL_INTERVAL),
+
+            Ok(None) => {
+                #[cfg(windows)]
+                if let Some(job) = job.as_ref() {
+                    job.terminate().ignore();
+                }
+                child.kill().ignore();
+                child.wait().ignore();
+                return Err(SaveHookError::TimedOut {
+                    program,
+                    seconds: timeout.as_secs(),
+                });
+            }
+
+            Err(error) => return Err(SaveHookError::Wait(program, error)),
+        }
+    };
+
+    // Programs the command opened, like an editor, keep running.
+    #[cfg(windows)]
+    if let Some(job) = job {
+        job.release().ignore();
+    }
+
+    let stdout = finish_output(stdout);
+    let stderr = finish_output(stderr);
+
+    if status.success() {
+        info!("{program:?} finished");
+        return Ok(());
+    }
+
+    let output = if stderr.is_empty() { stdout } else { stderr };
+    Err(SaveHookError::Failed {
+        program,
+        status,
+        output: output.join("\n"),
+    })
+}
+
+/// Logs each line of the output as it is written, keeping the last lines to report.
+fn log_output<Output>(
+    program: &str,
+    output: Option<Output>,
+    is_error: bool,
+) -> Option<JoinHandle<Vec<String>>>
+where
+    Output: Read + Send + 'static,
+{
+    let output = output?;
+    let program = program.to_string();
+
+    let thread = thread::Builder::new()
+        .name(String::from("Save Hook Output"))
+        .spawn(move || {
+            let _span = info_span!("[Save Hooks]").entered();
+            let mut last_lines = VecDeque::with_capacity(REPORTED_LINES);
+
+            for line in BufReader::new(output).lines().map_while(Result::ok) {
+                if is_error {
+                    warn!("{program:?}: {line}");
+                } else {
+                    info!("{program:?}: {line}");
+                }
+
+                if last_lines.len() == REPORTED_LINES {
+                    last_lines.pop_front();
+                }
+                last_lines.push_back(line);
+            }
+
+            Vec::from(last_lines)
+        })
+        .report_and_panic("Could not start the save hook output thread");
+
+    Some(thread)
+}
+
+/// The last lines of the output, or none if it is still being held open.
+fn finish_output(reader: Option<JoinHandle<Vec<String>>>) -> Vec<String> {
+    let Some(reader) = reader else {
+        return Vec::new();
+    };
+
+    let until = Instant::now() + OUTPUT_GRACE;
+    while !reader.is_finished() && Instant::now() < until {
+        thread::sleep(POLL_INTERVAL);
+    }
+
+    if !reader.is_finished() {
+        return Vec::new();
+    }
+
+    reader.join().unwrap_or_default()
+}
+
+#[cfg(test)]
+mod test {
+    use core::time::Duration;
+    use std::{path::PathBuf, time::Instant};
+
+    use crate::save_hooks::{SaveHookError, SavedFile, SavedKind};
+
+    use super::{expand_arguments, run_command};
+
+    fn saved_file() -> SavedFile {
+        SavedFile {
+            path: PathBuf::from("Screenshots").join("Screenshot.png"),
+            kind: SavedKind::Screenshot,
+            size: [1920, 1080],
+            whitepoint: 2.5,
+        }
+    }
+
+    /// Runs the script in the platform's shell.
+    fn shell(script: &str) -> Vec<String> {
+        #[cfg(windows)]
+        let shell = ["cmd", "/C"];
+        #[cfg(not(windows))]
+        let shell = ["sh", "-c"];
+
+        shell
+            .into_iter()
+            .map(String::from)
+            .chain([script.to_string()])
+            .collect()
+    }
+
+    #[test]
+    fn expands_placeholders() {
+        let arguments = [
+            "upload",
+            "--file={path}",
+            "{width}x{height}",
+            "{kind}",
+            "{other}",
+        ]
+        .map(String::from);
+
+        assert_eq!(
+            expand_arguments(&arguments, &saved_file()),
+            [
+                String::from("upload"),
+                format!("--file={}", saved_file().path.display()),
+                String::from("1920x1080"),
+                String::from("screenshot"),
+                String::from("{other}"),
+            ]
+        );
+    }
+
+    #[test]
+    fn reports_failed_commands() {
+        #[cfg(windows)]
+        let script = "echo %HDR_SNIPPING_TOOL_FILE_NAME% 1>&2 & exit /b 3";
+        #[cfg(not(windows))]
+        let script = "echo \"$HDR_SNIPPING_TOOL_FILE_NAME\" >&2; exit 3";
+
+        let timeout = Duration::from_secs(10);
+        assert!(run_command(&shell("exit 0"), &saved_file(), timeout).is_ok());
+
+        match run_command(&shell(script), &saved_file(), timeout) {
+            Err(SaveHookError::Failed { status, output, .. }) => {
+                assert_eq!(status.code(), Some(3));
+                assert_eq!(output.trim(), "Screenshot.png");
+            }
+            result => panic!("unexpected result: {result:?}"),
+        }
+
+        assert!(matches!(
+            run_command(&[], &saved_file(), timeout),
+            Err(SaveHookError::EmptyCommand)
+        ));
+    }
+
+    #[test]
+    fn stops_commands_after_the_timeout() {
+        #[cfg(windows)]
+        let script = "ping -n 6 127.0.0.1 > nul";
+        #[cfg(not(windows))]
+        let script = "sleep 5";
+
+        let started = Instant::now();
+        let result = run_command(&shell(script), &saved_file(), Duration::from_millis(200));
+
+        assert!(matches!(result, Err(SaveHookError::TimedOut { .. })));
+        assert!(started.elapsed() < Duration::from_secs(4));
+    }
+}
@@ -0,0 +1,67 @@
+use core::ffi::c_void;
+use std::{io, os::windows::io::AsRawHandle, process::Child};
+
+use windows::{
+    Win32::{
+        Foundation::{CloseHandle, HANDLE},
+        System::JobObjects::{
+            AssignProcessToJobObject, CreateJobObjectW, JOB_OBJECT_LIMIT,
+            JOB_OBJECT_LIMIT_KILL_ON_JOB_CLOSE, JOBOBJECT_EXTENDED_LIMIT_INFORMATION,
+            JobObjectExtendedLimitInformation, SetInformationJobObject, TerminateJobObject,
+        },
+    },
+    core::PCWSTR,
+};
+
+/// A job object holding a command and every process it starts, so they can be stopped together.
+///
+/// The processes are stopped when the job is dropped, or if the application exits, unless the
+/// job is released first.
+pub struct Job(HANDLE);
+
+impl Job {
+    /// Puts the child in a new job, the processes it already started are not in the job.
+    pub fn containing(child: &Child) -> io::Result<Self> {
+        let job = Self(unsafe { CreateJobObjectW(None, PCWSTR::null()) }?);
+        job.set_limits(JOB_OBJECT_LIMIT_KILL_ON_JOB_CLOSE)?;
+
+        unsafe { AssignProcessToJobObject(job.0, HANDLE(child.as_raw_handle())) }?;
+
+        Ok(job)
+    }
+
+    /// Stops every process in the job.
+    pub fn terminate(&self) -> io::Result<()> {
+        unsafe { TerminateJobObject(self.0, 1) }?;
+        Ok(())
+    }
+
+    /// Closes the job without stopping the processes in it, like a program the command opened.
+    pub fn release(self) -> io::Result<()> {
+        self.set_limits(JOB_OBJECT_LIMIT(0))
+    }
+
+    fn set_limits(&self, limits: JOB_OBJECT_LIMIT) -> io::Result<()> {
+        let mut information = JOBOBJECT_EXTENDED_LIMIT_INFORMATION::default();
+        information.BasicLimitInformation.LimitFlags = limits;
+
+        unsafe {
+            SetInformationJobObject(
+                self.0,
+                JobObjectExtendedLimitInformation,
+                (&raw const information).cast::<c_void>(),
+                size_of::<JOBOBJECT_EXTENDED_LIMIT_INFORMATION>() as u32,
+            )
+        }?;
+
+        Ok(())
+    }
+}
+
+impl Drop for Job {
+    fn drop(&mut self) {
+        unsafe {
+            let _ = CloseHandle(self.0);
+        }
+    }
+}
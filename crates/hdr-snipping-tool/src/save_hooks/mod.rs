@@ -0,0 +1,486 @@
+use core::time::Duration;
+use std::{
+    ffi::OsString,
+    fs::{self, File, OpenOptions},
+    io,
+    path::{Path, PathBuf},
+    process::ExitStatus,
+    sync::mpsc::{Sender, channel},
+    thread::{self, JoinHandle},
+};
+
+use arboard::Clipboard;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use tracing::{error, info, info_span};
+
+use crate::{
+    error_reporting::{ErrorCategory, report_error},
+    utilities::{
+        failure::{Failure, Ignore},
+        windows_helpers::open_in_editor,
+    },
+};
+
+use command::run_command;
+
+mod command;
+#[cfg(windows)]
+mod job;
+
+/// How many numbered names are tried for a moved file before giving up.
+const MAX_NUMBERED_NAMES: u32 = 1000;
+
+/// An action taken on each file once it is saved.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SaveHook {
+    pub action: SaveHookAction,
+
+    /// How many seconds a command may run before it is stopped.
+    #[serde(default = "default_timeout_seconds")]
+    pub timeout_seconds: u32,
+
+    /// If the hook also runs for each capture of a burst, rather than only for screenshots.
+    #[serde(default)]
+    pub bursts: bool,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SaveHookAction {
+    /// Runs the program with the arguments, `{path}` and the other placeholders in them are
+    /// replaced with the file's details.
+    Command(Vec<String>),
+
+    /// Replaces the copied image with the file's path.
+    CopyPath,
+
+    /// Opens the file in the default editor for PNG files.
+    OpenInEditor,
+
+    /// Moves the file into the folder, the hooks after it use the moved file.
+    MoveTo(PathBuf),
+}
+
+/// What was saved.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SavedKind {
+    Screenshot,
+    BurstFrame,
+}
+
+/// A file the capture saver wrote.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SavedFile {
+    pub path: PathBuf,
+    pub kind: SavedKind,
+
+    /// The width and height of the image in pixels.
+    pub size: [u32; 2],
+
+    /// The whitepoint the capture was tonemapped with.
+    pub whitepoint: f32,
+}
+
+#[derive(Debug, Error)]
+pub enum SaveHookError {
+    #[error("Could not start {0:?}:\n{1}")]
+    Start(String, #[source] io::Error),
+
+    #[error("Could not wait for {0:?} to finish:\n{1}")]
+    Wait(String, #[source] io::Error),
+
+    #[error("{program:?} did not finish within {seconds} seconds and was stopped")]
+    TimedOut { program: String, seconds: u64 },
+
+    #[error("{program:?} failed with {status}:\n{output}")]
+    Failed {
+        program: String,
+        status: ExitStatus,
+        output: String,
+    },
+
+    #[error("The command is empty")]
+    EmptyCommand,
+
+    #[error("Could not copy the path to the clipboard:\n{0}")]
+    Clipboard(#[from] arboard::Error),
+
+    #[error("Could not open the file in an editor:\n{0}")]
+    Editor(String),
+
+    #[error("Could not move the file to {0:?}:\n{1}")]
+    Move(PathBuf, #[source] io::Error),
+}
+
+impl SaveHook {
+    /// If the hook should run for the file.
+    pub fn applies_to(&self, file: &SavedFile) -> bool {
+        match file.kind {
+            SavedKind::Screenshot => true,
+            SavedKind::BurstFrame => self.bursts,
+        }
+    }
+
+    /// Takes the hook's action on the file, updating its path if it was moved.
+    pub fn run(&self, file: &mut SavedFile) -> Result<(), SaveHookError> {
+        match &self.action {
+            SaveHookAction::Command(arguments) => run_command(
+                arguments,
+                file,
+                Duration::from_secs(u64::from(self.timeout_seconds)),
+            ),
+
+            SaveHookAction::CopyPath => {
+                let mut clipboard = Clipboard::new()?;
+                clipboard.set_text(file.path.display().to_string())?;
+                info!("Copied {} to the clipboard", file.path.display());
+                Ok(())
+            }
+
+            SaveHookAction::OpenInEditor => unsafe { open_in_editor(&file.path) }
+                .map_err(|error| SaveHookError::Editor(format!("{error:?}"))),
+
+            SaveHookAction::MoveTo(folder) => {
+                file.path = move_file(&file.path, folder)
+                    .map_err(|error| SaveHookError::Move(folder.clone(), error))?;
+                info!("Moved the file to {}", file.path.display());
+                Ok(())
+            }
+        }
+    }
+}
+
+impl SavedKind {
+    /// The name given to commands.
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Screenshot => "screenshot",
+            Self::BurstFrame => "burst",
+        }
+    }
+}
+
+/// Runs the hooks in order, a hook that fails is reported and the rest still run. Returns the file
+/// as the hooks left it.
+pub fn run_hooks(hooks: &[SaveHook], mut file: SavedFile) -> SavedFile {
+    for hook in hooks {
+        if !hook.applies_to(&file) {
+            continue;
+        }
+
+        if let Err(e) = hook.run(&mut file) {
+            report_error(
+                ErrorCategory::SaveHook,
+                e,
+                &format!(
+                    "A save hook failed for {}",
+                    file.path.file_name().unwrap_or_default().to_string_lossy()
+                ),
+            );
+        }
+    }
+
+    file
+}
+
+/// Moves the file into the folder, copying it if the folder is on another drive. A file already
+/// in the folder is never replaced, the moved file is numbered like `Screenshot (2).png` instead.
+fn move_file(path: &Path, folder: &Path) -> io::Result<PathBuf> {
+    fs::create_dir_all(folder)?;
+
+    let name = path.file_name().unwrap_or_default();
+    if folder.join(name) == path {
+        return Ok(path.to_path_buf());
+    }
+
+    for number in 1..=MAX_NUMBERED_NAMES {
+        let destination = folder.join(numbered_name(Path::new(name), number));
+
+        match move_without_replacing(path, &destination) {
+            Ok(()) => return Ok(destination),
+            Err(e) if e.kind() == io::ErrorKind::AlreadyExists => continue,
+            Err(e) => return Err(e),
+        }
+    }
+
+    Err(io::Error::new(
+        io::ErrorKind::AlreadyExists,
+        "The folder already has a file with each numbered name",
+    ))
+}
+
+/// The file name with ` (number)` before the extension, the first number is the name itself.
+fn numbered_name(name: &Path, number: u32) -> OsString {
+    if number == 1 {
+        return name.as_os_str().to_os_string();
+    }
+
+    let mut numbered = name.file_stem().unwrap_or_default().to_os_string();
+    numbered.push(format!(" ({number})"));
+    if let Some(extension) = name.extension() {
+        numbered.push(".");
+        numbered.push(extension);
+    }
+
+    numbered
+}
+
+/// Moves the file, failing with `AlreadyExists` rather than replacing the destination.
+///
+/// Linking the destination fails if it exists, unlike renaming, where the folder is on another
+/// drive or does not support links the file is copied into a new file instead.
+fn move_without_replacing(path: &Path, destination: &Path) -> io::Result<()> {
+    match fs::hard_link(path, destination) {
+        Ok(()) => return fs::remove_file(path),
+        Err(e) if e.kind() == io::ErrorKind::AlreadyExists => return Err(e),
+        Err(_) => {}
+    }
+
+    let mut source = File::open(path)?;
+    let mut target = OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(destination)?;
+
+    if let Err(e) = io::copy(&mut source, &mut target) {
+        drop(target);
+        fs::remove_file(destination).ignore();
+        return Err(e);
+    }
+    drop((source, target));
+
+    fs::remove_file(path)
+}
+
+fn default_timeout_seconds() -> u32 {
+    30
+}
+
+/// Called with the file once the hooks have run.
+type Finished = Box<dyn FnOnce(&SavedFile) + Send>;
+
+enum Message {
+    Run(SavedFile, Finished),
+    SetHooks(Vec<SaveHook>),
+    Shutdown,
+}
+
+/// Runs the save hooks away from the capture saver, so slow hooks do not hold up the next capture.
+pub struct SaveHookThread {
+    thread: Option<JoinHandle<()>>,
+    sender: Sender<Message>,
+}
+
+impl SaveHookThread {
+    pub fn new(hooks: Vec<SaveHook>) -> Self {
+        let (sender, receiver) = channel();
+
+        let thread = thread::Builder::new()
+            .name(String::from("Save Hooks"))
+            .spawn(move || {
+                let _span = info_span!("[Save Hooks]").entered();
+                let mut hooks = hooks;
+
+                // The sender is only dropped after shutdown is sent.
+                while let Ok(message) = receiver.recv() {
+                    match message {
+                        Message::Shutdown => break,
+                        Message::Run(file, finished) => finished(&run_hooks(&hooks, file)),
+                        Message::SetHooks(new_hooks) => hooks = new_hooks,
+                    }
+                }
+            })
+            .report_and_panic("Could not start the save hooks thread");
+
+        Self {
+            thread: Some(thread),
+            sender,
+        }
+    }
+
+    /// Runs the hooks for a file that was saved, then calls `finished` with the file as the hooks
+    /// left it, a hook may have moved it.
+    pub fn run(&self, file: SavedFile, finished: impl FnOnce(&SavedFile) + Send + 'static) {
+        self.sender
+            .send(Message::Run(file, Box::new(finished)))
+            .ignore();
+    }
+
+    /// Uses the hooks for the files saved from now on.
+    pub fn set_hooks(&self, hooks: Vec<SaveHook>) {
+        self.sender.send(Message::SetHooks(hooks)).ignore();
+    }
+}
+
+impl Drop for SaveHookThread {
+    fn drop(&mut self) {
+        self.sender.send(Message::Shutdown).ignore();
+        if let Some(thread) = self.thread.take() {
+            if thread.join().is_err() {
+                error!("Joining Save Hooks thread returned an error");
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use core::time::Duration;
+    use std::{env, fs, path::PathBuf, process, sync::mpsc::channel};
+
+    use super::{SaveHook, SaveHookAction, SaveHookThread, SavedFile, SavedKind, move_file};
+
+    fn saved_file(kind: SavedKind) -> SavedFile {
+        SavedFile {
+            path: PathBuf::from("Screenshot.png"),
+            kind,
+            size: [1920, 1080],
+            whitepoint: 1.0,
+        }
+    }
+
+    #[test]
+    fn parses_hooks() {
+        let contents = r#"
+[[save_hooks]]
+action = { command = ["upload.cmd", "--file", "{path}"] }
+timeout_seconds = 60
+
+[[save_hooks]]
+action = "copy_path"
+bursts = true
+
+[[save_hooks]]
+action = { move_to = 'D:\Archive' }
+"#;
+
+        #[derive(serde::Deserialize)]
+        struct Hooks {
+            save_hooks: Vec<SaveHook>,
+        }
+        let hooks: Hooks = toml::from_str(contents).unwrap();
+
+        assert_eq!(
+            hooks.save_hooks,
+            [
+                SaveHook {
+                    action: SaveHookAction::Command(vec![
+                        String::from("upload.cmd"),
+                        String::from("--file"),
+                        String::from("{path}"),
+                    ]),
+                    timeout_seconds: 60,
+                    bursts: false,
+                },
+                SaveHook {
+                    action: SaveHookAction::CopyPath,
+                    timeout_seconds: 30,
+                    bursts: true,
+                },
+                SaveHook {
+                    action: SaveHookAction::MoveTo(PathBuf::from(r"D:\Archive")),
+                    timeout_seconds: 30,
+                    bursts: false,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn skips_bursts_unless_enabled() {
+        let mut hook = SaveHook {
+            action: SaveHookAction::OpenInEditor,
+            timeout_seconds: 30,
+            bursts: false,
+        };
+
+        assert!(hook.applies_to(&saved_file(SavedKind::Screenshot)));
+        assert!(!hook.applies_to(&saved_file(SavedKind::BurstFrame)));
+
+        hook.bursts = true;
+        assert!(hook.applies_to(&saved_file(SavedKind::BurstFrame)));
+    }
+
+    #[test]
+    fn moves_files() {
+        let directory = env::temp_dir().join(format!("hdr-save-hooks-{}", process::id()));
+        let path = directory.join("Screenshot.png");
+        fs::create_dir_all(&directory).unwrap();
+        fs::write(&path, b"png").unwrap();
+
+        let folder = directory.join("Archive");
+        let hook = SaveHook {
+            action: SaveHookAction::MoveTo(folder.clone()),
+            timeout_seconds: 30,
+            bursts: false,
+        };
+        let mut file = SavedFile {
+            path: path.clone(),
+            ..saved_file(SavedKind::Screenshot)
+        };
+        hook.run(&mut file).unwrap();
+
+        assert_eq!(file.path, folder.join("Screenshot.png"));
+        assert!(!path.exists());
+        assert_eq!(fs::read(&file.path).unwrap(), b"png");
+
+        fs::remove_dir_all(directory).unwrap();
+    }
+
+    #[test]
+    fn never_replaces_moved_files() {
+        let directory = env::temp_dir().join(format!("hdr-save-hooks-unique-{}", process::id()));
+        let folder = directory.join("Archive");
+        fs::create_dir_all(&folder).unwrap();
+        fs::write(folder.join("Screenshot.png"), b"first").unwrap();
+        fs::write(folder.join("Screenshot (2).png"), b"second").unwrap();
+
+        let path = directory.join("Screenshot.png");
+        fs::write(&path, b"third").unwrap();
+
+        let moved = move_file(&path, &folder).unwrap();
+
+        assert_eq!(moved, folder.join("Screenshot (3).png"));
+        assert!(!path.exists());
+        assert_eq!(fs::read(folder.join("Screenshot.png")).unwrap(), b"first");
+        assert_eq!(
+            fs::read(folder.join("Screenshot (2).png")).unwrap(),
+            b"second"
+        );
+        assert_eq!(fs::read(&moved).unwrap(), b"third");
+
+        fs::remove_dir_all(directory).unwrap();
+    }
+
+    #[test]
+    fn finishes_with_the_moved_file() {
+        let directory = env::temp_dir().join(format!("hdr-save-hooks-thread-{}", process::id()));
+        let path = directory.join("Screenshot.png");
+        fs::create_dir_all(&directory).unwrap();
+        fs::write(&path, b"png").unwrap();
+
+        let folder = directory.join("Archive");
+        let thread = SaveHookThread::new(vec![SaveHook {
+            action: SaveHookAction::MoveTo(folder.clone()),
+            timeout_seconds: 30,
+            bursts: false,
+        }]);
+
+        let (sender, receiver) = channel();
+        thread.run(
+            SavedFile {
+                path,
+                ..saved_file(SavedKind::Screenshot)
+            },
+            move |file| sender.send(file.path.clone()).unwrap(),
+        );
+
+        let finished = receiver.recv_timeout(Duration::from_secs(5)).unwrap();
+        assert_eq!(finished, folder.join("Screenshot.png"));
+        assert!(finished.exists());
+
+        drop(thread);
+        fs::remove_dir_all(directory).unwrap();
+    }
+}
@@ -0,0 +1,261 @@
+use std::{io, path::PathBuf};
+
+use arboard::Clipboard;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use tracing::info;
+
+use crate::{
+    capture_history::HistoryStore,
+    utilities::windows_helpers::{move_to_recycle_bin, open_file, open_in_editor, show_in_folder},
+};
+
+pub use thumbnail::{Thumbnail, fit_size};
+
+mod thumbnail;
+
+/// The largest thumbnail sent with a notification in pixels, large enough for the notification at
+/// 200% scale.
+pub const THUMBNAIL_SIZE: [u32; 2] = [256, 192];
+
+// The layout at 100% scale.
+const WIDTH: i32 = 360;
+const MARGIN: i32 = 12;
+const GAP: i32 = 6;
+const THUMBNAIL_WIDTH: i32 = 128;
+const THUMBNAIL_HEIGHT: i32 = 96;
+const BUTTON_HEIGHT: i32 = 28;
+const HEIGHT: i32 = MARGIN + THUMBNAIL_HEIGHT + MARGIN + BUTTON_HEIGHT + MARGIN;
+
+/// If a notification is shown once a screenshot is saved, and for how long.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NotificationSettings {
+    pub enabled: bool,
+
+    /// How many seconds the notification is shown for, it stays while the cursor is over it.
+    pub seconds: u32,
+}
+
+impl Default for NotificationSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            seconds: 6,
+        }
+    }
+}
+
+/// A screenshot that was saved, shown in the notification.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SavedScreenshot {
+    /// Where the screenshot is once the save hooks have run.
+    pub path: PathBuf,
+    pub thumbnail: Thumbnail,
+
+    /// The history and the id of the screenshot's entry in it, if it was kept.
+    pub history: Option<(HistoryStore, String)>,
+}
+
+/// Something the user should know that is not an error, like why nothing opened.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Notice {
+    pub summary: &'static str,
+
+    /// What the user can do about it, shown below the summary.
+    pub details: &'static str,
+}
+
+/// What can be done with the saved screenshot from the notification.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum NotificationAction {
+    Open,
+    OpenFolder,
+    Edit,
+
+    /// Moves the file to the recycle bin and removes it from the history.
+    Delete,
+    CopyPath,
+}
+
+#[derive(Debug, Error)]
+pub enum NotificationError {
+    #[error("Could not open the file:\n{0}")]
+    Open(String),
+
+    #[error("Could not open the folder:\n{0}")]
+    OpenFolder(#[source] io::Error),
+
+    #[error("Could not open the file in an editor:\n{0}")]
+    Editor(String),
+
+    #[error("Could not delete the file:\n{0}")]
+    Delete(#[source] io::Error),
+
+    #[error("Could not remove the screenshot from the history:\n{0}")]
+    History(#[source] io::Error),
+
+    #[error("Could not copy the path to the clipboard:\n{0}")]
+    Clipboard(#[from] arboard::Error),
+}
+
+impl NotificationAction {
+    /// The actions in the order their buttons are shown.
+    pub const ALL: [Self; 5] = [
+        Self::Open,
+        Self::OpenFolder,
+        Self::Edit,
+        Self::Delete,
+        Self::CopyPath,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Open => "Open",
+            Self::OpenFolder => "Folder",
+            Self::Edit => "Edit",
+            Self::Delete => "Delete",
+            Self::CopyPath => "Copy path",
+        }
+    }
+
+    /// Takes the action on the saved screenshot.
+    pub fn run(self, screenshot: &SavedScreenshot) -> Result<(), NotificationError> {
+        let path = screenshot.path.as_path();
+
+        match self {
+            Self::Open => unsafe { open_file(path) }
+                .map_err(|error| NotificationError::Open(format!("{error:?}"))),
+
+            Self::OpenFolder => show_in_folder(path).map_err(NotificationError::OpenFolder),
+
+            Self::Edit => unsafe { open_in_editor(path) }
+                .map_err(|error| NotificationError::Editor(format!("{error:?}"))),
+
+            Self::Delete => {
+                move_to_recycle_bin(path).map_err(NotificationError::Delete)?;
+                info!("Moved {} to the recycle bin", path.display());
+
+                if let Some((history, id)) = screenshot.history.as_ref() {
+                    history.remove(id).map_err(NotificationError::History)?;
+                }
+                Ok(())
+            }
+
+            Self::CopyPath => {
+                let mut clipboard = Clipboard::new()?;
+                clipboard.set_text(path.display().to_string())?;
+                info!("Copied {} to the clipboard", path.display());
+                Ok(())
+            }
+        }
+    }
+}
+
+/// Where the parts of the notification are, as `[x, y, width, height]` in physical pixels.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Layout {
+    pub size: [u32; 2],
+    pub thumbnail: [i32; 4],
+    pub text: [i32; 4],
+
+    /// Where an error's message is, it covers the whole notification.
+    pub message: [i32; 4],
+    pub buttons: [[i32; 4]; NotificationAction::ALL.len()],
+}
+
+impl Layout {
+    pub fn new(scale: f64) -> Self {
+        let scaled = |value: i32| (f64::from(value) * scale).round() as i32;
+
+        let count = NotificationAction::ALL.len() as i32;
+        let button_width = f64::from(WIDTH - 2 * MARGIN - (count - 1) * GAP) / f64::from(count);
+        let buttons_y = MARGIN + THUMBNAIL_HEIGHT + MARGIN;
+
+        // Each button ends where the next starts less the gap, so rounding never overlaps them.
+        let buttons = core::array::from_fn(|index| {
+            let left = f64::from(MARGIN) + index as f64 * (button_width + f64::from(GAP));
+            let x = (left * scale).round() as i32;
+            let right = ((left + button_width) * scale).round() as i32;
+
+            [x, scaled(buttons_y), right - x, scaled(BUTTON_HEIGHT)]
+        });
+
+        let text_x = MARGIN + THUMBNAIL_WIDTH + MARGIN;
+
+        Self {
+            size: [scaled(WIDTH) as u32, scaled(HEIGHT) as u32],
+            thumbnail: [MARGIN, MARGIN, THUMBNAIL_WIDTH, THUMBNAIL_HEIGHT].map(scaled),
+            text: [text_x, MARGIN, WIDTH - text_x - MARGIN, THUMBNAIL_HEIGHT].map(scaled),
+            message: [MARGIN, MARGIN, WIDTH - 2 * MARGIN, HEIGHT - 2 * MARGIN].map(scaled),
+            buttons,
+        }
+    }
+
+    /// The action taken by clicking the position, clicking the thumbnail opens the file.
+    pub fn action_at(&self, position: [i32; 2]) -> Option<NotificationAction> {
+        if contains(self.thumbnail, position) {
+            return Some(NotificationAction::Open);
+        }
+
+        NotificationAction::ALL
+            .into_iter()
+            .zip(self.buttons)
+            .find(|(_, bounds)| contains(*bounds, position))
+            .map(|(action, _)| action)
+    }
+}
+
+fn contains([x, y, width, height]: [i32; 4], [position_x, position_y]: [i32; 2]) -> bool {
+    (x..x + width).contains(&position_x) && (y..y + height).contains(&position_y)
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Layout, NotificationAction, NotificationSettings};
+
+    #[test]
+    fn finds_the_clicked_action() {
+        let layout = Layout::new(1.0);
+        let centre = |[x, y, width, height]: [i32; 4]| [x + width / 2, y + height / 2];
+
+        for (action, bounds) in NotificationAction::ALL.into_iter().zip(layout.buttons) {
+            assert_eq!(layout.action_at(centre(bounds)), Some(action));
+        }
+
+        assert_eq!(
+            layout.action_at(centre(layout.thumbnail)),
+            Some(NotificationAction::Open)
+        );
+        assert_eq!(layout.action_at(centre(layout.text)), None);
+        assert_eq!(layout.action_at([0, 0]), None);
+    }
+
+    #[test]
+    fn buttons_fit_at_every_scale() {
+        for scale in [1.0, 1.25, 1.5, 1.75, 2.0] {
+            let layout = Layout::new(scale);
+
+            for pair in layout.buttons.windows(2) {
+                assert!(pair[0][0] + pair[0][2] < pair[1][0], "{scale}: {pair:?}");
+            }
+
+            let last = layout.buttons[NotificationAction::ALL.len() - 1];
+            assert!(last[0] + last[2] < layout.size[0] as i32);
+            assert!(last[1] + last[3] < layout.size[1] as i32);
+        }
+    }
+
+    #[test]
+    fn parses_settings() {
+        let settings: NotificationSettings = toml::from_str("seconds = 10").unwrap();
+
+        assert_eq!(
+            settings,
+            NotificationSettings {
+                enabled: true,
+                seconds: 10,
+            }
+        );
+    }
+}
@@ -0,0 +1,184 @@
+/// A small copy of a screenshot.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Thumbnail {
+    /// The width and height of the thumbnail in pixels.
+    pub size: [u32; 2],
+
+    /// The `RGBA` pixels of the thumbnail.
+    pub rgba: Vec<u8>,
+}
+
+impl Thumbnail {
+    /// Downscales the `RGBA` pixels of an image to fit within `max_size`, keeping its aspect ratio.
+    pub fn new(rgba: &[u8], size: [u32; 2], max_size: [u32; 2]) -> Self {
+        let thumbnail_size = fit_size(size, max_size);
+
+        Self {
+            size: thumbnail_size,
+            rgba: downscale(rgba, size, thumbnail_size),
+        }
+    }
+
+    /// The pixels as `BGRA` drawn over the background colour, for APIs that ignore transparency.
+    pub fn to_opaque_bgra(&self, background: [u8; 3]) -> Vec<u8> {
+        let mut bgra = Vec::with_capacity(self.rgba.len());
+
+        for pixel in self.rgba.chunks_exact(4) {
+            let alpha = u32::from(pixel[3]);
+            let blend = |channel: u8, background: u8| {
+                let value = u32::from(channel) * alpha + u32::from(background) * (255 - alpha);
+                ((value + 127) / 255) as u8
+            };
+
+            bgra.extend_from_slice(&[
+                blend(pixel[2], background[2]),
+                blend(pixel[1], background[1]),
+                blend(pixel[0], background[0]),
+                255,
+            ]);
+        }
+
+        bgra
+    }
+}
+
+/// The largest size with the aspect ratio of `size` that fits within `max_size`, images that
+/// already fit are not enlarged and neither side is less than a pixel.
+pub fn fit_size(size: [u32; 2], max_size: [u32; 2]) -> [u32; 2] {
+    let [width, height] = size.map(|axis| u64::from(axis.max(1)));
+    let [max_width, max_height] = max_size.map(|axis| u64::from(axis.max(1)));
+
+    if width <= max_width && height <= max_height {
+        return [width, height].map(|axis| axis as u32);
+    }
+
+    // Compare the aspect ratios without dividing, the limiting side is scaled to its maximum.
+    let fitted = if width * max_height >= height * max_width {
+        [max_width, (height * max_width + width / 2) / width]
+    } else {
+        [(width * max_height + height / 2) / height, max_height]
+    };
+
+    fitted.map(|axis| axis.max(1) as u32)
+}
+
+/// Downscales `RGBA` pixels to `new_size` by averaging the pixels each new pixel covers. The
+/// colours are weighted by their alpha, so transparent pixels do not darken the edges they touch.
+pub fn downscale(rgba: &[u8], size: [u32; 2], new_size: [u32; 2]) -> Vec<u8> {
+    let [width, height] = size.map(|axis| axis as usize);
+    let [new_width, new_height] = new_size.map(|axis| axis as usize);
+
+    if [width, height] == [new_width, new_height] {
+        return rgba.to_vec();
+    }
+
+    // The range of source pixels each new pixel covers along an axis, at least one pixel.
+    let span = |index: usize, length: usize, new_length: usize| {
+        let start = index * length / new_length;
+        let end = ((index + 1) * length / new_length).max(start + 1);
+        start..end.min(length)
+    };
+
+    let mut output = Vec::with_capacity(new_width * new_height * 4);
+
+    for new_y in 0..new_height {
+        let rows = span(new_y, height, new_height);
+
+        for new_x in 0..new_width {
+            let columns = span(new_x, width, new_width);
+
+            let mut colour = [0u64; 3];
+            let mut alpha = 0u64;
+            let mut count = 0u64;
+
+            for y in rows.clone() {
+                for x in columns.clone() {
+                    let pixel = &rgba[(y * width + x) * 4..][..4];
+                    let pixel_alpha = u64::from(pixel[3]);
+
+                    for (sum, channel) in colour.iter_mut().zip(pixel) {
+                        *sum += u64::from(*channel) * pixel_alpha;
+                    }
+                    alpha += pixel_alpha;
+                    count += 1;
+                }
+            }
+
+            let colour = match alpha {
+                0 => [0; 3],
+                alpha => colour.map(|sum| ((sum + alpha / 2) / alpha) as u8),
+            };
+            let alpha = ((alpha + count / 2) / count.max(1)) as u8;
+
+            output.extend_from_slice(&[colour[0], colour[1], colour[2], alpha]);
+        }
+    }
+
+    output
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Thumbnail, downscale, fit_size};
+
+    #[test]
+    fn fits_keeping_the_aspect_ratio() {
+        // Wide and tall images are limited by their longer side.
+        assert_eq!(fit_size([1920, 1080], [256, 192]), [256, 144]);
+        assert_eq!(fit_size([1080, 1920], [256, 192]), [108, 192]);
+
+        // Images with the same aspect ratio fill the space.
+        assert_eq!(fit_size([1024, 768], [256, 192]), [256, 192]);
+
+        // Small images are not enlarged.
+        assert_eq!(fit_size([100, 50], [256, 192]), [100, 50]);
+
+        // Very thin images keep at least a pixel.
+        assert_eq!(fit_size([10_000, 2], [256, 192]), [256, 1]);
+        assert_eq!(fit_size([1, 5000], [256, 192]), [1, 192]);
+        assert_eq!(fit_size([0, 0], [256, 192]), [1, 1]);
+    }
+
+    #[test]
+    fn averages_the_covered_pixels() {
+        // A 4x2 image of a black and a white 2x2 block.
+        let black = [0, 0, 0, 255];
+        let white = [255, 255, 255, 255];
+        let rgba: Vec<u8> = [black, black, white, white, black, black, white, white].concat();
+
+        assert_eq!(downscale(&rgba, [4, 2], [2, 1]), [black, white].concat());
+        assert_eq!(downscale(&rgba, [4, 2], [1, 1]), [128, 128, 128, 255]);
+        assert_eq!(downscale(&rgba, [4, 2], [4, 2]), rgba);
+    }
+
+    #[test]
+    fn transparent_pixels_do_not_change_the_colour() {
+        let rgba: Vec<u8> = [[255, 0, 0, 255], [0, 0, 0, 0]].concat();
+
+        assert_eq!(downscale(&rgba, [2, 1], [1, 1]), [255, 0, 0, 128]);
+        assert_eq!(downscale(&[0, 0, 0, 0], [1, 1], [1, 1]), [0, 0, 0, 0]);
+    }
+
+    #[test]
+    fn thumbnails_have_the_fitted_size() {
+        let size = [300, 100];
+        let rgba = vec![255; 300 * 100 * 4];
+
+        let thumbnail = Thumbnail::new(&rgba, size, [60, 60]);
+        assert_eq!(thumbnail.size, [60, 20]);
+        assert_eq!(thumbnail.rgba.len(), 60 * 20 * 4);
+    }
+
+    #[test]
+    fn opaque_pixels_are_drawn_over_the_background() {
+        let thumbnail = Thumbnail {
+            size: [2, 1],
+            rgba: vec![255, 128, 0, 255, 255, 255, 255, 0],
+        };
+
+        assert_eq!(
+            thumbnail.to_opaque_bgra([32, 32, 32]),
+            [0, 128, 255, 255, 32, 32, 32, 255]
+        );
+    }
+}
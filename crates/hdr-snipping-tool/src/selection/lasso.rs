@@ -0,0 +1,344 @@
+use winit::dpi::PhysicalPosition;
+
+use super::{Selection, SelectionEvent, SelectionState, WindowRectProvider};
+
+/// The minimum distance in pixels between consecutive points of a lasso.
+const MIN_POINT_DISTANCE: f32 = 2.0;
+
+/// A closed polygon traced by the user, relative to the top-left corner of the window.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Lasso {
+    pub points: Vec<PhysicalPosition<f32>>,
+}
+
+impl Lasso {
+    pub fn mouse_clicked(position: PhysicalPosition<f32>) -> Box<dyn SelectionState> {
+        Box::new(Tracing(Self {
+            points: vec![position],
+        }))
+    }
+
+    /// Returns the bounding box of the lasso, expanded to whole pixels.
+    pub fn bounds(&self) -> Selection {
+        let mut left = f32::INFINITY;
+        let mut top = f32::INFINITY;
+        let mut right = f32::NEG_INFINITY;
+        let mut bottom = f32::NEG_INFINITY;
+
+        for point in &self.points {
+            left = left.min(point.x);
+            top = top.min(point.y);
+            right = right.max(point.x);
+            bottom = bottom.max(point.y);
+        }
+
+        Selection {
+            start: PhysicalPosition::new(left.floor().max(0.0), top.floor().max(0.0)),
+            end: PhysicalPosition::new(right.ceil().max(0.0), bottom.ceil().max(0.0)),
+        }
+    }
+
+    /// Rasterises the lasso into a coverage mask for the pixels in an area, a pixel is covered if
+    /// its centre lies inside the lasso using the even-odd rule.
+    ///
+    /// The mask is row-major, `position` is the top-left pixel of the area.
+    pub fn mask(&self, position: [usize; 2], size: [usize; 2]) -> Vec<bool> {
+        let [width, height] = size;
+        let mut mask = vec![false; width * height];
+
+        if self.points.len() < 3 {
+            return mask;
+        }
+
+        let mut crossings = Vec::new();
+        for row in 0..height {
+            let y = (position[1] + row) as f32 + 0.5;
+
+            // Find where the scanline crosses each edge.
+            crossings.clear();
+            for (index, start) in self.points.iter().enumerate() {
+                let end = self.points[(index + 1) % self.points.len()];
+
+                if (start.y <= y) != (end.y <= y) {
+                    crossings.push(edge_crossing(*start, end, y));
+                }
+            }
+            crossings.sort_by(f32::total_cmp);
+
+            // Fill the pixels whose centre lies between pairs of crossings.
+            for span in crossings.chunks_exact(2) {
+                let first = pixel_at_or_after(span[0], position[0]).min(width);
+                let last = pixel_at_or_after(span[1], position[0]).min(width);
+
+                mask[row * width + first..row * width + last].fill(true);
+            }
+        }
+
+        mask
+    }
+
+    /// Clears the pixels of an `RGBA8` image that lie outside of the lasso to transparent.
+    ///
+    /// `position` and `size` describe the area of the window that the image covers.
+    pub fn apply_mask(&self, position: [usize; 2], size: [usize; 2], rgba: &mut [u8]) {
+        let mask = self.mask(position, size);
+
+        for (pixel, is_inside) in rgba.chunks_exact_mut(4).zip(mask) {
+            if !is_inside {
+                pixel.fill(0);
+            }
+        }
+    }
+}
+
+/// The x coordinate where a horizontal line at `y` crosses the edge from `start` to `end`.
+fn edge_crossing(start: PhysicalPosition<f32>, end: PhysicalPosition<f32>, y: f32) -> f32 {
+    start.x + (y - start.y) * (end.x - start.x) / (end.y - start.y)
+}
+
+/// The index of the first pixel in a row whose centre is at or after `x`.
+fn pixel_at_or_after(x: f32, row_start: usize) -> usize {
+    (x - 0.5 - row_start as f32).ceil().max(0.0) as usize
+}
+
+struct Traced(Lasso);
+impl SelectionState for Traced {
+    fn handle_event(
+        self: Box<Self>,
+        _event: SelectionEvent,
+        _windows: &dyn WindowRectProvider,
+    ) -> Option<Box<dyn SelectionState>> {
+        Some(self)
+    }
+
+    fn is_submitted(&self) -> bool {
+        true
+    }
+
+    fn selection(&self) -> Option<Selection> {
+        Some(self.0.bounds())
+    }
+
+    fn lasso(&self) -> Option<&Lasso> {
+        Some(&self.0)
+    }
+}
+
+struct Tracing(Lasso);
+impl SelectionState for Tracing {
+    fn handle_event(
+        mut self: Box<Self>,
+        event: SelectionEvent,
+        _windows: &dyn WindowRectProvider,
+    ) -> Option<Box<dyn SelectionState>> {
+        match event {
+            SelectionEvent::MouseMoved(physical_position) => {
+                // Unwrap is safe, the lasso is created with a point.
+                let last = self.0.points.last().unwrap();
+                let distance = (physical_position.x - last.x).hypot(physical_position.y - last.y);
+
+                if distance >= MIN_POINT_DISTANCE {
+                    self.0.points.push(physical_position);
+                }
+
+                Some(self)
+            }
+
+            SelectionEvent::MouseReleased => {
+                let size = self.0.bounds().size();
+
+                if self.0.points.len() < 3 || size.width < 1.0 || size.height < 1.0 {
+                    None
+                } else {
+                    Some(Box::new(Traced(self.0)))
+                }
+            }
+        }
+    }
+
+    fn is_submitted(&self) -> bool {
+        false
+    }
+
+    fn selection(&self) -> Option<Selection> {
+        if self.0.points.len() < 3 {
+            return None;
+        }
+
+        Some(self.0.bounds())
+    }
+
+    fn lasso(&self) -> Option<&Lasso> {
+        Some(&self.0)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use winit::dpi::PhysicalPosition;
+
+    use crate::selection::{Selection, SelectionEvent};
+
+    use super::{Lasso, edge_crossing};
+
+    /// CPU reference, tests each pixel centre against the polygon with the even-odd rule.
+    fn reference_mask(lasso: &Lasso, position: [usize; 2], size: [usize; 2]) -> Vec<bool> {
+        let points = &lasso.points;
+        let mut mask = Vec::with_capacity(size[0] * size[1]);
+
+        for row in 0..size[1] {
+            for column in 0..size[0] {
+                let x = (position[0] + column) as f32 + 0.5;
+                let y = (position[1] + row) as f32 + 0.5;
+
+                let mut is_inside = false;
+                for (index, start) in points.iter().enumerate() {
+                    let end = points[(index + 1) % points.len()];
+
+                    if (start.y <= y) != (end.y <= y) && x < edge_crossing(*start, end, y) {
+                        is_inside = !is_inside;
+                    }
+                }
+
+                mask.push(is_inside);
+            }
+        }
+
+        mask
+    }
+
+    fn lasso(points: &[[f32; 2]]) -> Lasso {
+        Lasso {
+            points: points
+                .iter()
+                .map(|point| PhysicalPosition::new(point[0], point[1]))
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn rectangle_mask() {
+        let lasso = lasso(&[[2.0, 1.0], [6.0, 1.0], [6.0, 4.0], [2.0, 4.0]]);
+
+        let mask = lasso.mask([0, 0], [8, 6]);
+        for (index, is_inside) in mask.into_iter().enumerate() {
+            let x = index % 8;
+            let y = index / 8;
+            let expected = (2..6).contains(&x) && (1..4).contains(&y);
+
+            assert_eq!(is_inside, expected, "Pixel ({x}, {y})");
+        }
+    }
+
+    #[test]
+    fn matches_reference() {
+        let polygons = [
+            // Triangle
+            lasso(&[[3.2, 1.7], [27.9, 8.4], [9.1, 30.6]]),
+            // Concave arrow
+            lasso(&[
+                [1.0, 10.0],
+                [20.0, 10.0],
+                [20.0, 2.0],
+                [31.5, 16.0],
+                [20.0, 30.0],
+                [20.0, 22.0],
+                [1.0, 22.0],
+            ]),
+            // Self-intersecting bow tie
+            lasso(&[[0.5, 0.5], [30.5, 30.5], [30.5, 0.5], [0.5, 30.5]]),
+            // Star
+            lasso(&[
+                [16.0, 0.0],
+                [20.0, 12.0],
+                [32.0, 12.0],
+                [22.0, 19.0],
+                [26.0, 31.0],
+                [16.0, 23.0],
+                [6.0, 31.0],
+                [10.0, 19.0],
+                [0.0, 12.0],
+                [12.0, 12.0],
+            ]),
+        ];
+
+        for polygon in polygons {
+            for position in [[0, 0], [5, 3], [12, 20]] {
+                let size = [32 - position[0], 32 - position[1]];
+
+                assert_eq!(
+                    polygon.mask(position, size),
+                    reference_mask(&polygon, position, size),
+                    "{polygon:?} at {position:?}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn apply_mask_clears_outside() {
+        let lasso = lasso(&[[1.0, 1.0], [3.0, 1.0], [3.0, 3.0], [1.0, 3.0]]);
+        let mut rgba = vec![255u8; 4 * 4 * 4];
+
+        lasso.apply_mask([0, 0], [4, 4], &mut rgba);
+
+        for (index, pixel) in rgba.chunks_exact(4).enumerate() {
+            let x = index % 4;
+            let y = index / 4;
+
+            if (1..3).contains(&x) && (1..3).contains(&y) {
+                assert_eq!(pixel, [255, 255, 255, 255], "Pixel ({x}, {y})");
+            } else {
+                assert_eq!(pixel, [0, 0, 0, 0], "Pixel ({x}, {y})");
+            }
+        }
+    }
+
+    #[test]
+    fn bounds_cover_points() {
+        let lasso = lasso(&[[1.5, 2.25], [10.75, 4.0], [6.0, 9.5]]);
+
+        assert_eq!(
+            lasso.bounds(),
+            Selection {
+                start: PhysicalPosition::new(1.0, 2.0),
+                end: PhysicalPosition::new(11.0, 10.0),
+            }
+        );
+    }
+
+    #[test]
+    fn trace_submits_lasso() {
+        let windows: Vec<Selection> = Vec::new();
+        let mut state = Some(Lasso::mouse_clicked(PhysicalPosition::new(10.0, 10.0)));
+
+        for event in [
+            SelectionEvent::MouseMoved(PhysicalPosition::new(10.5, 10.5)),
+            SelectionEvent::MouseMoved(PhysicalPosition::new(30.0, 10.0)),
+            SelectionEvent::MouseMoved(PhysicalPosition::new(20.0, 25.0)),
+            SelectionEvent::MouseReleased,
+        ] {
+            state = state.and_then(|state| state.handle_event(event, &windows));
+        }
+
+        let state = state.expect("Trace should produce a selection");
+        assert!(state.is_submitted());
+
+        // Points closer than the minimum distance are skipped.
+        let lasso = state.lasso().expect("Selection should be a lasso");
+        assert_eq!(lasso.points.len(), 3);
+    }
+
+    #[test]
+    fn short_trace_cancels() {
+        let windows: Vec<Selection> = Vec::new();
+        let state = Lasso::mouse_clicked(PhysicalPosition::new(10.0, 10.0))
+            .handle_event(
+                SelectionEvent::MouseMoved(PhysicalPosition::new(20.0, 10.0)),
+                &windows,
+            )
+            .and_then(|state| state.handle_event(SelectionEvent::MouseReleased, &windows));
+
+        assert!(state.is_none());
+    }
+}
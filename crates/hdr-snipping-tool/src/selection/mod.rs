@@ -0,0 +1,377 @@
+use winit::dpi::{PhysicalPosition, PhysicalSize};
+
+pub use lasso::Lasso;
+
+mod lasso;
+
+/// The maximum distance in pixels that a selection edge will snap to a window border.
+pub const SNAP_DISTANCE: f32 = 8.0;
+
+pub trait SelectionState {
+    fn handle_event(
+        self: Box<Self>,
+        event: SelectionEvent,
+        windows: &dyn WindowRectProvider,
+    ) -> Option<Box<dyn SelectionState>>;
+
+    /// Has the selection been submitted.
+    fn is_submitted(&self) -> bool;
+
+    fn selection(&self) -> Option<Selection>;
+
+    /// The freeform selection, if the selection is not a rectangle.
+    fn lasso(&self) -> Option<&Lasso> {
+        None
+    }
+}
+
+/// Provides the bounds of the top-level windows that are visible under the overlay.
+pub trait WindowRectProvider {
+    /// The window bounds relative to the top-left corner of the overlay, ordered from the top-most
+    /// window to the bottom-most window.
+    fn window_rects(&self) -> &[Selection];
+
+    /// Returns the bounds of the top-most window that contains the position.
+    fn window_at(&self, position: PhysicalPosition<f32>) -> Option<Selection> {
+        self.window_rects()
+            .iter()
+            .find(|rect| rect.contains(position))
+            .copied()
+    }
+
+    /// Snaps each axis of the position to the nearest window border within [`SNAP_DISTANCE`].
+    fn snap(&self, position: PhysicalPosition<f32>) -> PhysicalPosition<f32> {
+        let mut snapped = position;
+        let mut nearest_x = f32::INFINITY;
+        let mut nearest_y = f32::INFINITY;
+
+        for rect in self.window_rects() {
+            let [left, top, right, bottom] = rect.bounds();
+
+            // Only snap to a border if the position is alongside it.
+            if position.y >= top - SNAP_DISTANCE && position.y <= bottom + SNAP_DISTANCE {
+                for edge in [left, right] {
+                    let distance = (position.x - edge).abs();
+                    if distance <= SNAP_DISTANCE && distance < nearest_x {
+                        nearest_x = distance;
+                        snapped.x = edge;
+                    }
+                }
+            }
+
+            if position.x >= left - SNAP_DISTANCE && position.x <= right + SNAP_DISTANCE {
+                for edge in [top, bottom] {
+                    let distance = (position.y - edge).abs();
+                    if distance <= SNAP_DISTANCE && distance < nearest_y {
+                        nearest_y = distance;
+                        snapped.y = edge;
+                    }
+                }
+            }
+        }
+
+        snapped
+    }
+}
+
+impl WindowRectProvider for Vec<Selection> {
+    fn window_rects(&self) -> &[Selection] {
+        self
+    }
+}
+
+/// The shape that a new selection is made with.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SelectionMode {
+    #[default]
+    Rectangle,
+    Freeform,
+}
+
+impl SelectionMode {
+    /// Returns the other selection mode.
+    pub fn toggled(self) -> Self {
+        match self {
+            Self::Rectangle => Self::Freeform,
+            Self::Freeform => Self::Rectangle,
+        }
+    }
+
+    /// Starts a selection in this mode.
+    pub fn mouse_clicked(self, position: PhysicalPosition<f32>) -> Box<dyn SelectionState> {
+        match self {
+            Self::Rectangle => Selection::mouse_clicked(position),
+            Self::Freeform => Lasso::mouse_clicked(position),
+        }
+    }
+}
+
+pub enum SelectionEvent {
+    MouseMoved(PhysicalPosition<f32>),
+    MouseReleased,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Selection {
+    pub start: PhysicalPosition<f32>,
+    pub end: PhysicalPosition<f32>,
+}
+impl Selection {
+    pub fn mouse_clicked(position: PhysicalPosition<f32>) -> Box<dyn SelectionState> {
+        Box::new(Started(position))
+    }
+
+    pub fn position(&self) -> PhysicalPosition<f32> {
+        let x = self.start.x.min(self.end.x);
+        let y = self.start.y.min(self.end.y);
+
+        PhysicalPosition { x, y }
+    }
+
+    pub fn size(&self) -> PhysicalSize<f32> {
+        let [left, top, right, bottom] = self.bounds();
+
+        PhysicalSize::new(right - left, bottom - top)
+    }
+
+    /// Returns the `[left, top, right, bottom]` edges of the selection.
+    pub fn bounds(&self) -> [f32; 4] {
+        let left = self.start.x.min(self.end.x);
+        let right = self.start.x.max(self.end.x);
+        let top = self.start.y.min(self.end.y);
+        let bottom = self.start.y.max(self.end.y);
+
+        [left, top, right, bottom]
+    }
+
+    /// Returns if the position lies within the selection, including the edges.
+    pub fn contains(&self, position: PhysicalPosition<f32>) -> bool {
+        let [left, top, right, bottom] = self.bounds();
+
+        position.x >= left && position.x <= right && position.y >= top && position.y <= bottom
+    }
+
+    pub fn position_as_usize(&self) -> [usize; 2] {
+        let position = self.position();
+        [position.x as usize, position.y as usize]
+    }
+
+    pub fn size_as_usize(&self) -> [usize; 2] {
+        let size = self.size();
+        [size.width as usize, size.height as usize]
+    }
+}
+
+struct Selected(Selection);
+impl SelectionState for Selected {
+    fn handle_event(
+        self: Box<Self>,
+        _event: SelectionEvent,
+        _windows: &dyn WindowRectProvider,
+    ) -> Option<Box<dyn SelectionState>> {
+        Some(self)
+    }
+
+    fn is_submitted(&self) -> bool {
+        true
+    }
+
+    fn selection(&self) -> Option<Selection> {
+        Some(self.0)
+    }
+}
+
+struct Selecting(Selection);
+impl SelectionState for Selecting {
+    fn handle_event(
+        mut self: Box<Self>,
+        event: SelectionEvent,
+        windows: &dyn WindowRectProvider,
+    ) -> Option<Box<dyn SelectionState>> {
+        match event {
+            SelectionEvent::MouseMoved(physical_position) => {
+                let physical_position = windows.snap(physical_position);
+
+                if physical_position.x == self.0.start.x || physical_position.y == self.0.start.y {
+                    Some(self)
+                } else {
+                    self.0.end = physical_position;
+                    Some(self)
+                }
+            }
+            SelectionEvent::MouseReleased => Some(Box::new(Selected(self.0))),
+        }
+    }
+
+    fn is_submitted(&self) -> bool {
+        false
+    }
+
+    fn selection(&self) -> Option<Selection> {
+        Some(self.0)
+    }
+}
+
+struct Started(PhysicalPosition<f32>);
+impl SelectionState for Started {
+    fn handle_event(
+        self: Box<Self>,
+        event: SelectionEvent,
+        windows: &dyn WindowRectProvider,
+    ) -> Option<Box<dyn SelectionState>> {
+        match event {
+            SelectionEvent::MouseMoved(physical_position) => {
+                if physical_position.x == self.0.x || physical_position.y == self.0.y {
+                    Some(self)
+                } else {
+                    let start = windows.snap(self.0);
+                    let end = windows.snap(physical_position);
+
+                    if end.x == start.x || end.y == start.y {
+                        return Some(self);
+                    }
+
+                    Some(Box::new(Selecting(Selection { start, end })))
+                }
+            }
+
+            // A click without a drag selects the window under the mouse.
+            SelectionEvent::MouseReleased => windows
+                .window_at(self.0)
+                .map(|window| Box::new(Selected(window)) as Box<dyn SelectionState>),
+        }
+    }
+
+    fn is_submitted(&self) -> bool {
+        false
+    }
+
+    fn selection(&self) -> Option<Selection> {
+        None
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use winit::dpi::PhysicalPosition;
+
+    use super::{SNAP_DISTANCE, Selection, SelectionEvent, SelectionState, WindowRectProvider};
+
+    fn rect(left: f32, top: f32, right: f32, bottom: f32) -> Selection {
+        Selection {
+            start: PhysicalPosition::new(left, top),
+            end: PhysicalPosition::new(right, bottom),
+        }
+    }
+
+    fn windows() -> Vec<Selection> {
+        vec![
+            rect(100.0, 100.0, 300.0, 250.0),
+            rect(50.0, 50.0, 500.0, 400.0),
+        ]
+    }
+
+    fn drive(
+        windows: &dyn WindowRectProvider,
+        click: PhysicalPosition<f32>,
+        events: Vec<SelectionEvent>,
+    ) -> Option<Box<dyn SelectionState>> {
+        let mut state = Some(Selection::mouse_clicked(click));
+
+        for event in events {
+            state = state.and_then(|state| state.handle_event(event, windows));
+        }
+
+        state
+    }
+
+    #[test]
+    fn window_at_returns_top_most() {
+        let windows = windows();
+
+        let hovered = windows.window_at(PhysicalPosition::new(150.0, 150.0));
+        assert_eq!(hovered, Some(windows[0]));
+
+        let hovered = windows.window_at(PhysicalPosition::new(400.0, 350.0));
+        assert_eq!(hovered, Some(windows[1]));
+
+        let hovered = windows.window_at(PhysicalPosition::new(10.0, 10.0));
+        assert_eq!(hovered, None);
+    }
+
+    #[test]
+    fn click_selects_window() {
+        let windows = windows();
+
+        let state = drive(
+            &windows,
+            PhysicalPosition::new(200.0, 200.0),
+            vec![SelectionEvent::MouseReleased],
+        )
+        .expect("Click on a window should select it");
+
+        assert!(state.is_submitted());
+        assert_eq!(state.selection(), Some(windows[0]));
+    }
+
+    #[test]
+    fn click_outside_windows_cancels() {
+        let windows = windows();
+
+        let state = drive(
+            &windows,
+            PhysicalPosition::new(10.0, 10.0),
+            vec![SelectionEvent::MouseReleased],
+        );
+
+        assert!(state.is_none());
+    }
+
+    #[test]
+    fn drag_snaps_to_window_borders() {
+        let windows = windows();
+
+        let state = drive(
+            &windows,
+            PhysicalPosition::new(103.0, 96.0),
+            vec![
+                SelectionEvent::MouseMoved(PhysicalPosition::new(200.0, 200.0)),
+                SelectionEvent::MouseMoved(PhysicalPosition::new(295.0, 254.0)),
+                SelectionEvent::MouseReleased,
+            ],
+        )
+        .expect("Drag should produce a selection");
+
+        assert!(state.is_submitted());
+        assert_eq!(state.selection(), Some(windows[0]));
+    }
+
+    #[test]
+    fn snap_ignores_distant_borders() {
+        let windows = windows();
+
+        let position = PhysicalPosition::new(100.0 + SNAP_DISTANCE + 1.0, 180.0);
+        assert_eq!(windows.snap(position), position);
+
+        // Alongside the border, but not next to it.
+        let position = PhysicalPosition::new(302.0, 450.0);
+        assert_eq!(windows.snap(position), position);
+    }
+
+    #[test]
+    fn drag_without_windows_is_unchanged() {
+        let windows: Vec<Selection> = Vec::new();
+
+        let state = drive(
+            &windows,
+            PhysicalPosition::new(10.0, 20.0),
+            vec![
+                SelectionEvent::MouseMoved(PhysicalPosition::new(31.0, 42.0)),
+                SelectionEvent::MouseReleased,
+            ],
+        )
+        .expect("Drag should produce a selection");
+
+        assert_eq!(state.selection(), Some(rect(10.0, 20.0, 31.0, 42.0)));
+    }
+}
@@ -0,0 +1,4 @@
+pub use window::open;
+
+mod model;
+mod window;
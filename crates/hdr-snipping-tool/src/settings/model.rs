@@ -0,0 +1,1108 @@
+use core::{fmt, mem};
+
+use chrono::{DateTime, Local};
+
+use crate::{
+    annotation::RedactionStyle,
+    burst,
+    capture_taker::{MonitorOverride, WhitepointStrategy},
+    clipboard::ClipboardFormat,
+    config::{Config, ConfigError, format_file_name},
+    hotkeys::{self, HotkeyAction, HotkeyBinding, Shortcut},
+    recording::RecordingFormat,
+};
+
+const WHITEPOINTS: [(WhitepointStrategy, &str); 3] = [
+    (WhitepointStrategy::Automatic, "Automatic"),
+    (WhitepointStrategy::Sdr, "SDR white"),
+    (WhitepointStrategy::Hdr, "Maximum brightness"),
+];
+
+const REDACTION_STYLES: [(RedactionStyle, &str); 2] = [
+    (RedactionStyle::Fill, "Fill"),
+    (RedactionStyle::Pixelate, "Pixelate"),
+];
+
+const RECORDING_FORMATS: [(RecordingFormat, &str); 2] = [
+    (RecordingFormat::Apng, "Animated PNG"),
+    (RecordingFormat::Gif, "GIF"),
+];
+
+/// The actions a hotkey can be bound to, a delayed capture keeps its seconds when picked again.
+const HOTKEY_ACTIONS: [(HotkeyAction, &str); 8] = [
+    (HotkeyAction::RegionCapture, "Region capture"),
+    (HotkeyAction::FullCapture, "Full capture"),
+    (HotkeyAction::RepeatLastRegion, "Repeat last region"),
+    (HotkeyAction::WindowCapture, "Window capture"),
+    (HotkeyAction::DelayedCapture(5), "Delayed capture"),
+    (HotkeyAction::OpenHistory, "Open history"),
+    (HotkeyAction::Recording, "Record region"),
+    (HotkeyAction::Burst, "Burst capture"),
+];
+
+const WHITEPOINT_NAMES: [&str; WHITEPOINTS.len()] = names(&WHITEPOINTS);
+const REDACTION_STYLE_NAMES: [&str; REDACTION_STYLES.len()] = names(&REDACTION_STYLES);
+const RECORDING_FORMAT_NAMES: [&str; RECORDING_FORMATS.len()] = names(&RECORDING_FORMATS);
+
+/// The names of the actions a hotkey can be bound to, in the order of
+/// [`ConfigModel::action_index`].
+pub const ACTION_NAMES: [&str; HOTKEY_ACTIONS.len()] = names(&HOTKEY_ACTIONS);
+
+/// The names of the choices of a monitor's whitepoint, the first uses the global strategy.
+pub const MONITOR_WHITEPOINTS: [&str; 4] =
+    ["Default", "Automatic", "SDR white", "Maximum brightness"];
+
+/// A setting with a single value, the hotkeys and monitor overrides are lists edited on their own.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Field {
+    ScreenshotDelay,
+    CaptureVirtualDesktop,
+    CaptureWindow,
+    Whitepoint,
+    RedactionStyle,
+    ScreenshotName,
+    RecordingName,
+    BurstName,
+    InstantSound,
+    InstantFlash,
+    NotificationEnabled,
+    NotificationSeconds,
+    HistoryMaxEntries,
+    HistoryMaxSizeMb,
+    HistoryMaxAgeDays,
+    RecordingFormat,
+    RecordingFrameRate,
+    RecordingMaxSeconds,
+    BurstIntervalMs,
+    BurstFrames,
+    BurstKeptFrames,
+    ClipboardBitmap,
+    ClipboardPng,
+    ClipboardFile,
+    ClipboardHtml,
+    ClipboardExr,
+}
+
+/// How a field is edited.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FieldKind {
+    Toggle,
+
+    /// A whole number, with the unit it is in.
+    Number(&'static str),
+
+    /// One of the named choices.
+    Choice(&'static [&'static str]),
+
+    /// A file name pattern, shown with a preview of the name it gives.
+    FileName,
+}
+
+/// The value of a field as it is shown.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Value {
+    Toggle(bool),
+    Text(String),
+    Choice(usize),
+}
+
+/// The editable values of a monitor override.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MonitorField {
+    Monitor,
+    MaxBrightness,
+    MinBrightness,
+    SdrWhite,
+}
+
+/// Any value that is edited as text, so text that does not parse can be kept until it is fixed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Setting {
+    Field(Field),
+    HotkeyDelay(usize),
+    Monitor(usize, MonitorField),
+}
+
+/// Text that was typed into a setting but could not be used, the setting keeps its last value.
+#[derive(Clone, Debug)]
+struct Rejected {
+    setting: Setting,
+    text: String,
+    problem: String,
+}
+
+/// A problem that stops the settings from being saved.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Problem {
+    /// The config key of the setting, like `recording.frame_rate` or `hotkeys[1].action`.
+    pub key: String,
+    pub message: String,
+}
+
+impl fmt::Display for Problem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+/// The config being edited in the settings window, tracking what changed since it was saved and
+/// what stops it from being saved.
+///
+/// The save hooks are only edited in the config file, they are saved as they were loaded.
+#[derive(Debug)]
+pub struct ConfigModel {
+    saved: Config,
+    config: Config,
+    rejected: Vec<Rejected>,
+}
+
+impl Field {
+    pub const ALL: [Self; 26] = [
+        Self::ScreenshotDelay,
+        Self::CaptureVirtualDesktop,
+        Self::CaptureWindow,
+        Self::Whitepoint,
+        Self::RedactionStyle,
+        Self::ScreenshotName,
+        Self::RecordingName,
+        Self::BurstName,
+        Self::InstantSound,
+        Self::InstantFlash,
+        Self::NotificationEnabled,
+        Self::NotificationSeconds,
+        Self::HistoryMaxEntries,
+        Self::HistoryMaxSizeMb,
+        Self::HistoryMaxAgeDays,
+        Self::RecordingFormat,
+        Self::RecordingFrameRate,
+        Self::RecordingMaxSeconds,
+        Self::BurstIntervalMs,
+        Self::BurstFrames,
+        Self::BurstKeptFrames,
+        Self::ClipboardBitmap,
+        Self::ClipboardPng,
+        Self::ClipboardFile,
+        Self::ClipboardHtml,
+        Self::ClipboardExr,
+    ];
+
+    /// The key of the field in the config file.
+    pub fn key(self) -> &'static str {
+        match self {
+            Self::ScreenshotDelay => "screenshot_delay",
+            Self::CaptureVirtualDesktop => "capture_virtual_desktop",
+            Self::CaptureWindow => "capture_window",
+            Self::Whitepoint => "whitepoint",
+            Self::RedactionStyle => "redaction_style",
+            Self::ScreenshotName => "file_names.screenshot",
+            Self::RecordingName => "file_names.recording",
+            Self::BurstName => "file_names.burst",
+            Self::InstantSound => "instant_screenshot.sound",
+            Self::InstantFlash => "instant_screenshot.flash",
+            Self::NotificationEnabled => "notification.enabled",
+            Self::NotificationSeconds => "notification.seconds",
+            Self::HistoryMaxEntries => "history.max_entries",
+            Self::HistoryMaxSizeMb => "history.max_size_mb",
+            Self::HistoryMaxAgeDays => "history.max_age_days",
+            Self::RecordingFormat => "recording.format",
+            Self::RecordingFrameRate => "recording.frame_rate",
+            Self::RecordingMaxSeconds => "recording.max_seconds",
+            Self::BurstIntervalMs => "burst.interval_ms",
+            Self::BurstFrames => "burst.frames",
+            Self::BurstKeptFrames => "burst.kept_frames",
+            Self::ClipboardBitmap
+            | Self::ClipboardPng
+            | Self::ClipboardFile
+            | Self::ClipboardHtml
+            | Self::ClipboardExr => "clipboard.formats",
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::ScreenshotDelay => "Delay",
+            Self::CaptureVirtualDesktop => "Capture every monitor",
+            Self::CaptureWindow => "Capture a single window",
+            Self::Whitepoint => "Whitepoint",
+            Self::RedactionStyle => "Redactions",
+            Self::ScreenshotName => "Screenshots",
+            Self::RecordingName => "Recordings",
+            Self::BurstName => "Bursts",
+            Self::InstantSound => "Play a sound",
+            Self::InstantFlash => "Show a tick",
+            Self::NotificationEnabled => "Show once saved",
+            Self::NotificationSeconds => "Shown for",
+            Self::HistoryMaxEntries => "Captures kept",
+            Self::HistoryMaxSizeMb => "Maximum size",
+            Self::HistoryMaxAgeDays => "Maximum age",
+            Self::RecordingFormat => "Format",
+            Self::RecordingFrameRate => "Frame rate",
+            Self::RecordingMaxSeconds => "Maximum length",
+            Self::BurstIntervalMs => "Interval",
+            Self::BurstFrames => "Captures",
+            Self::BurstKeptFrames => "Captures kept",
+            Self::ClipboardBitmap => "Bitmap",
+            Self::ClipboardPng => "PNG",
+            Self::ClipboardFile => "Saved file",
+            Self::ClipboardHtml => "HTML image",
+            Self::ClipboardExr => "HDR image (EXR)",
+        }
+    }
+
+    /// The heading the field is grouped under.
+    pub fn section(self) -> &'static str {
+        match self {
+            Self::ScreenshotDelay
+            | Self::CaptureVirtualDesktop
+            | Self::CaptureWindow
+            | Self::Whitepoint
+            | Self::RedactionStyle => "Screenshots",
+            Self::ScreenshotName | Self::RecordingName | Self::BurstName => "File names",
+            Self::InstantSound | Self::InstantFlash => "Full captures",
+            Self::NotificationEnabled | Self::NotificationSeconds => "Notification",
+            Self::HistoryMaxEntries | Self::HistoryMaxSizeMb | Self::HistoryMaxAgeDays => "History",
+            Self::RecordingFormat | Self::RecordingFrameRate | Self::RecordingMaxSeconds => {
+                "Recording"
+            }
+            Self::BurstIntervalMs | Self::BurstFrames | Self::BurstKeptFrames => "Burst",
+            Self::ClipboardBitmap
+            | Self::ClipboardPng
+            | Self::ClipboardFile
+            | Self::ClipboardHtml
+            | Self::ClipboardExr => "Clipboard",
+        }
+    }
+
+    pub fn kind(self) -> FieldKind {
+        match self {
+            Self::CaptureVirtualDesktop
+            | Self::CaptureWindow
+            | Self::InstantSound
+            | Self::InstantFlash
+            | Self::NotificationEnabled
+            | Self::ClipboardBitmap
+            | Self::ClipboardPng
+            | Self::ClipboardFile
+            | Self::ClipboardHtml
+            | Self::ClipboardExr => FieldKind::Toggle,
+            Self::ScreenshotDelay | Self::NotificationSeconds | Self::RecordingMaxSeconds => {
+                FieldKind::Number("seconds")
+            }
+            Self::HistoryMaxEntries | Self::BurstFrames | Self::BurstKeptFrames => {
+                FieldKind::Number("captures")
+            }
+            Self::HistoryMaxSizeMb => FieldKind::Number("MB"),
+            Self::HistoryMaxAgeDays => FieldKind::Number("days"),
+            Self::RecordingFrameRate => FieldKind::Number("per second"),
+            Self::BurstIntervalMs => FieldKind::Number("ms"),
+            Self::Whitepoint => FieldKind::Choice(&WHITEPOINT_NAMES),
+            Self::RedactionStyle => FieldKind::Choice(&REDACTION_STYLE_NAMES),
+            Self::RecordingFormat => FieldKind::Choice(&RECORDING_FORMAT_NAMES),
+            Self::ScreenshotName | Self::RecordingName | Self::BurstName => FieldKind::FileName,
+        }
+    }
+
+    /// The clipboard format a field turns on or off, `None` if it is not a clipboard field.
+    fn clipboard_format(self) -> Option<ClipboardFormat> {
+        match self {
+            Self::ClipboardBitmap => Some(ClipboardFormat::Bitmap),
+            Self::ClipboardPng => Some(ClipboardFormat::Png),
+            Self::ClipboardFile => Some(ClipboardFormat::File),
+            Self::ClipboardHtml => Some(ClipboardFormat::Html),
+            Self::ClipboardExr => Some(ClipboardFormat::Exr),
+            _ => None,
+        }
+    }
+}
+
+impl MonitorField {
+    pub const ALL: [Self; 4] = [
+        Self::Monitor,
+        Self::MaxBrightness,
+        Self::MinBrightness,
+        Self::SdrWhite,
+    ];
+
+    pub fn key(self) -> &'static str {
+        match self {
+            Self::Monitor => "monitor",
+            Self::MaxBrightness => "max_brightness",
+            Self::MinBrightness => "min_brightness",
+            Self::SdrWhite => "sdr_white",
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Monitor => "Monitor",
+            Self::MaxBrightness => "Maximum nits",
+            Self::MinBrightness => "Minimum nits",
+            Self::SdrWhite => "SDR white nits",
+        }
+    }
+}
+
+impl Setting {
+    fn key(self) -> String {
+        match self {
+            Self::Field(field) => field.key().to_string(),
+            Self::HotkeyDelay(index) => format!("hotkeys[{index}].action"),
+            Self::Monitor(index, field) => format!("monitors[{index}].{}", field.key()),
+        }
+    }
+
+    /// The setting once the entry at `removed` is removed from its list, `None` if it was the
+    /// removed entry.
+    fn after_removing(self, list: List, removed: usize) -> Option<Self> {
+        let shift = |index: usize| match index.cmp(&removed) {
+            core::cmp::Ordering::Less => Some(index),
+            core::cmp::Ordering::Equal => None,
+            core::cmp::Ordering::Greater => Some(index - 1),
+        };
+
+        match (self, list) {
+            (Self::HotkeyDelay(index), List::Hotkeys) => shift(index).map(Self::HotkeyDelay),
+            (Self::Monitor(index, field), List::Monitors) => {
+                shift(index).map(|index| Self::Monitor(index, field))
+            }
+            (setting, _) => Some(setting),
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum List {
+    Hotkeys,
+    Monitors,
+}
+
+impl ConfigModel {
+    pub fn new(config: Config) -> Self {
+        Self {
+            saved: config.clone(),
+            config,
+            rejected: Vec::new(),
+        }
+    }
+
+    /// The config with the edits that could be used.
+    pub fn config(&self) -> &Config {
+        &self.config
+    }
+
+    /// The value of a field, or the text typed into it if that could not be used.
+    pub fn value(&self, field: Field) -> Value {
+        match self.rejected_text(Setting::Field(field)) {
+            Some(text) => Value::Text(text.to_string()),
+            None => value_of(&self.config, field),
+        }
+    }
+
+    pub fn set_toggle(&mut self, field: Field, value: bool) {
+        // A format that is turned on is copied after the formats already in the list.
+        if let Some(format) = field.clipboard_format() {
+            let formats = &mut self.config.clipboard.formats;
+            formats.retain(|copied| *copied != format);
+            if value {
+                formats.push(format);
+            }
+            return;
+        }
+
+        let toggle = match field {
+            Field::CaptureVirtualDesktop => &mut self.config.capture_virtual_desktop,
+            Field::CaptureWindow => &mut self.config.capture_window,
+            Field::InstantSound => &mut self.config.instant_screenshot.sound,
+            Field::InstantFlash => &mut self.config.instant_screenshot.flash,
+            Field::NotificationEnabled => &mut self.config.notification.enabled,
+            _ => return,
+        };
+
+        *toggle = value;
+    }
+
+    pub fn set_choice(&mut self, field: Field, index: usize) {
+        match field {
+            Field::Whitepoint => set_choice(&mut self.config.whitepoint, &WHITEPOINTS, index),
+            Field::RedactionStyle => {
+                set_choice(&mut self.config.redaction_style, &REDACTION_STYLES, index);
+            }
+            Field::RecordingFormat => {
+                set_choice(&mut self.config.recording.format, &RECORDING_FORMATS, index);
+            }
+            _ => {}
+        }
+    }
+
+    /// Sets a number or file name field from the text typed into it, text that is not a number is
+    /// kept as a problem until it is replaced.
+    pub fn set_text(&mut self, field: Field, text: &str) {
+        let setting = Setting::Field(field);
+
+        let number = match field.kind() {
+            FieldKind::Number(_) => self.number_mut(field),
+            FieldKind::FileName => {
+                let pattern = match field {
+                    Field::ScreenshotName => &mut self.config.file_names.screenshot,
+                    Field::RecordingName => &mut self.config.file_names.recording,
+                    _ => &mut self.config.file_names.burst,
+                };
+                *pattern = text.to_string();
+                return;
+            }
+            FieldKind::Toggle | FieldKind::Choice(_) => return,
+        };
+
+        match parse_number(text) {
+            Ok(value) => {
+                if let Some(number) = number {
+                    *number = value;
+                }
+                self.accept(setting);
+            }
+            Err(problem) => self.reject(setting, text, problem),
+        }
+    }
+
+    /// A preview of the file name a file name field gives now, or why it cannot be used.
+    pub fn preview(&self, field: Field, time: &DateTime<Local>) -> Option<Result<String, String>> {
+        let (pattern, extension) = match field {
+            Field::ScreenshotName => (&self.config.file_names.screenshot, "png"),
+            Field::RecordingName => (
+                &self.config.file_names.recording,
+                self.config.recording.format.extension(),
+            ),
+            Field::BurstName => (&self.config.file_names.burst, "png"),
+            _ => return None,
+        };
+
+        let preview = format_file_name(pattern, time)
+            .map(|name| match field {
+                Field::BurstName => burst::frame_file_name(&name, 1),
+                _ => format!("{name}.{extension}"),
+            })
+            .map_err(|error| format!("The name {error}"));
+
+        Some(preview)
+    }
+
+    pub fn hotkeys(&self) -> &[HotkeyBinding] {
+        &self.config.hotkeys
+    }
+
+    /// The index of a hotkey's action in [`ACTION_NAMES`].
+    pub fn action_index(&self, index: usize) -> Option<usize> {
+        let action = self.config.hotkeys.get(index)?.action;
+
+        HOTKEY_ACTIONS
+            .iter()
+            .position(|(choice, _)| mem::discriminant(choice) == mem::discriminant(&action))
+    }
+
+    /// Adds a region capture binding for the shortcut, returning its index.
+    pub fn add_hotkey(&mut self, shortcut: Shortcut) -> usize {
+        self.config.hotkeys.push(HotkeyBinding {
+            shortcut,
+            action: HotkeyAction::RegionCapture,
+        });
+        self.config.hotkeys.len() - 1
+    }
+
+    pub fn remove_hotkey(&mut self, index: usize) {
+        if index < self.config.hotkeys.len() {
+            self.config.hotkeys.remove(index);
+            self.remove_rejected(List::Hotkeys, index);
+        }
+    }
+
+    pub fn set_shortcut(&mut self, index: usize, shortcut: Shortcut) {
+        if let Some(binding) = self.config.hotkeys.get_mut(index) {
+            binding.shortcut = shortcut;
+        }
+    }
+
+    /// Sets a hotkey's action from its index in [`ACTION_NAMES`].
+    pub fn set_action(&mut self, index: usize, action_index: usize) {
+        let Some((action, _)) = HOTKEY_ACTIONS.get(action_index) else {
+            return;
+        };
+        let Some(binding) = self.config.hotkeys.get_mut(index) else {
+            return;
+        };
+
+        if mem::discriminant(action) != mem::discriminant(&binding.action) {
+            binding.action = *action;
+            self.accept(Setting::HotkeyDelay(index));
+        }
+    }
+
+    /// The seconds of a delayed capture hotkey, `None` for other actions.
+    pub fn hotkey_delay(&self, index: usize) -> Option<String> {
+        let HotkeyAction::DelayedCapture(seconds) = self.config.hotkeys.get(index)?.action else {
+            return None;
+        };
+
+        let text = self
+            .rejected_text(Setting::HotkeyDelay(index))
+            .map_or_else(|| seconds.to_string(), str::to_string);
+        Some(text)
+    }
+
+    pub fn set_hotkey_delay(&mut self, index: usize, text: &str) {
+        let setting = Setting::HotkeyDelay(index);
+        let Some(binding) = self.config.hotkeys.get_mut(index) else {
+            return;
+        };
+        let HotkeyAction::DelayedCapture(seconds) = &mut binding.action else {
+            return;
+        };
+
+        match parse_number(text) {
+            Ok(value) => {
+                *seconds = value;
+                self.accept(setting);
+            }
+            Err(problem) => self.reject(setting, text, problem),
+        }
+    }
+
+    pub fn monitors(&self) -> &[MonitorOverride] {
+        &self.config.monitors
+    }
+
+    /// Adds an override without any values, returning its index.
+    pub fn add_monitor(&mut self) -> usize {
+        self.config.monitors.push(MonitorOverride::default());
+        self.config.monitors.len() - 1
+    }
+
+    pub fn remove_monitor(&mut self, index: usize) {
+        if index < self.config.monitors.len() {
+            self.config.monitors.remove(index);
+            self.remove_rejected(List::Monitors, index);
+        }
+    }
+
+    /// The text of a monitor override value, empty if it is not overridden.
+    pub fn monitor_text(&self, index: usize, field: MonitorField) -> String {
+        if let Some(text) = self.rejected_text(Setting::Monitor(index, field)) {
+            return text.to_string();
+        }
+        let Some(monitor) = self.config.monitors.get(index) else {
+            return String::new();
+        };
+
+        let nits = match field {
+            MonitorField::Monitor => return monitor.monitor.clone(),
+            MonitorField::MaxBrightness => monitor.max_brightness,
+            MonitorField::MinBrightness => monitor.min_brightness,
+            MonitorField::SdrWhite => monitor.sdr_white,
+        };
+        nits.map(|nits| nits.to_string()).unwrap_or_default()
+    }
+
+    /// Sets a monitor override value, empty text removes the override of a luminance.
+    pub fn set_monitor_text(&mut self, index: usize, field: MonitorField, text: &str) {
+        let setting = Setting::Monitor(index, field);
+        let Some(monitor) = self.config.monitors.get_mut(index) else {
+            return;
+        };
+
+        let nits = match field {
+            MonitorField::Monitor => {
+                monitor.monitor = text.trim().to_string();
+                return;
+            }
+            MonitorField::MaxBrightness => &mut monitor.max_brightness,
+            MonitorField::MinBrightness => &mut monitor.min_brightness,
+            MonitorField::SdrWhite => &mut monitor.sdr_white,
+        };
+
+        let text = text.trim();
+        if text.is_empty() {
+            *nits = None;
+            self.accept(setting);
+            return;
+        }
+
+        match text.parse::<f32>() {
+            Ok(value) => {
+                *nits = Some(value);
+                self.accept(setting);
+            }
+            Err(_) => self.reject(
+                setting,
+                text,
+                String::from("must be a number of nits, or empty to use the monitor's own"),
+            ),
+        }
+    }
+
+    /// The index of a monitor's whitepoint in [`MONITOR_WHITEPOINTS`].
+    pub fn monitor_whitepoint(&self, index: usize) -> usize {
+        let whitepoint = self
+            .config
+            .monitors
+            .get(index)
+            .and_then(|monitor| monitor.whitepoint);
+
+        match whitepoint {
+            Some(whitepoint) => choice_index(whitepoint, &WHITEPOINTS) + 1,
+            None => 0,
+        }
+    }
+
+    pub fn set_monitor_whitepoint(&mut self, index: usize, choice: usize) {
+        if let Some(monitor) = self.config.monitors.get_mut(index) {
+            monitor.whitepoint = choice
+                .checked_sub(1)
+                .and_then(|choice| WHITEPOINTS.get(choice))
+                .map(|(whitepoint, _)| *whitepoint);
+        }
+    }
+
+    /// The problems that stop the config from being saved.
+    pub fn problems(&self) -> Vec<Problem> {
+        let mut problems: Vec<_> = self
+            .rejected
+            .iter()
+            .map(|rejected| {
+                let key = rejected.setting.key();
+                Problem {
+                    message: format!("`{key}` {}", rejected.problem),
+                    key,
+                }
+            })
+            .collect();
+
+        // Rejected text does not reach the config, so the config's own problems never repeat it.
+        for error in self.config.validate("") {
+            if let ConfigError::Invalid { field, problem, .. } = error {
+                problems.push(Problem {
+                    message: format!("`{field}` {problem}"),
+                    key: field,
+                });
+            }
+        }
+
+        let (_, errors) = hotkeys::resolve_conflicts(&self.config.hotkeys);
+        for error in errors {
+            problems.push(Problem {
+                key: String::from("hotkeys"),
+                message: error.to_string(),
+            });
+        }
+
+        problems
+    }
+
+    /// The problem with a setting, written like the config key of [`Problem::key`].
+    pub fn problem_with(&self, key: &str) -> Option<Problem> {
+        self.problems()
+            .into_iter()
+            .find(|problem| problem.key == key)
+    }
+
+    /// If anything was edited since the config was last saved.
+    pub fn is_modified(&self) -> bool {
+        self.config != self.saved || !self.rejected.is_empty()
+    }
+
+    pub fn is_field_modified(&self, field: Field) -> bool {
+        self.value(field) != value_of(&self.saved, field)
+    }
+
+    /// Takes the edited config to be saved, or the problems that stop it from being saved.
+    pub fn commit(&mut self) -> Result<Config, Vec<Problem>> {
+        let problems = self.problems();
+        if !problems.is_empty() {
+            return Err(problems);
+        }
+
+        self.saved = self.config.clone();
+        Ok(self.config.clone())
+    }
+
+    /// Discards the edits since the config was last saved.
+    pub fn revert(&mut self) {
+        self.config = self.saved.clone();
+        self.rejected.clear();
+    }
+
+    /// Replaces the edited config with the defaults, which still have to be saved. The save hooks
+    /// are kept, as they are not shown.
+    pub fn reset_to_defaults(&mut self) {
+        self.config = Config {
+            save_hooks: mem::take(&mut self.config.save_hooks),
+            ..Config::default()
+        };
+        self.rejected.clear();
+    }
+
+    fn number_mut(&mut self, field: Field) -> Option<&mut u32> {
+        let config = &mut self.config;
+
+        let number = match field {
+            Field::ScreenshotDelay => &mut config.screenshot_delay,
+            Field::NotificationSeconds => &mut config.notification.seconds,
+            Field::HistoryMaxEntries => &mut config.history.max_entries,
+            Field::HistoryMaxSizeMb => &mut config.history.max_size_mb,
+            Field::HistoryMaxAgeDays => &mut config.history.max_age_days,
+            Field::RecordingFrameRate => &mut config.recording.frame_rate,
+            Field::RecordingMaxSeconds => &mut config.recording.max_seconds,
+            Field::BurstIntervalMs => &mut config.burst.interval_ms,
+            Field::BurstFrames => &mut config.burst.frames,
+            Field::BurstKeptFrames => &mut config.burst.kept_frames,
+            _ => return None,
+        };
+
+        Some(number)
+    }
+
+    fn rejected_text(&self, setting: Setting) -> Option<&str> {
+        self.rejected
+            .iter()
+            .find(|rejected| rejected.setting == setting)
+            .map(|rejected| rejected.text.as_str())
+    }
+
+    fn accept(&mut self, setting: Setting) {
+        self.rejected.retain(|rejected| rejected.setting != setting);
+    }
+
+    fn reject(&mut self, setting: Setting, text: &str, problem: String) {
+        self.accept(setting);
+        self.rejected.push(Rejected {
+            setting,
+            text: text.to_string(),
+            problem,
+        });
+    }
+
+    fn remove_rejected(&mut self, list: List, removed: usize) {
+        self.rejected = mem::take(&mut self.rejected)
+            .into_iter()
+            .filter_map(|rejected| {
+                let setting = rejected.setting.after_removing(list, removed)?;
+                Some(Rejected {
+                    setting,
+                    ..rejected
+                })
+            })
+            .collect();
+    }
+}
+
+fn value_of(config: &Config, field: Field) -> Value {
+    let number = |value: u32| Value::Text(value.to_string());
+
+    match field {
+        Field::ScreenshotDelay => number(config.screenshot_delay),
+        Field::CaptureVirtualDesktop => Value::Toggle(config.capture_virtual_desktop),
+        Field::CaptureWindow => Value::Toggle(config.capture_window),
+        Field::Whitepoint => Value::Choice(choice_index(config.whitepoint, &WHITEPOINTS)),
+        Field::RedactionStyle => {
+            Value::Choice(choice_index(config.redaction_style, &REDACTION_STYLES))
+        }
+        Field::ScreenshotName => Value::Text(config.file_names.screenshot.clone()),
+        Field::RecordingName => Value::Text(config.file_names.recording.clone()),
+        Field::BurstName => Value::Text(config.file_names.burst.clone()),
+        Field::InstantSound => Value::Toggle(config.instant_screenshot.sound),
+        Field::InstantFlash => Value::Toggle(config.instant_screenshot.flash),
+        Field::NotificationEnabled => Value::Toggle(config.notification.enabled),
+        Field::NotificationSeconds => number(config.notification.seconds),
+        Field::HistoryMaxEntries => number(config.history.max_entries),
+        Field::HistoryMaxSizeMb => number(config.history.max_size_mb),
+        Field::HistoryMaxAgeDays => number(config.history.max_age_days),
+        Field::RecordingFormat => {
+            Value::Choice(choice_index(config.recording.format, &RECORDING_FORMATS))
+        }
+        Field::RecordingFrameRate => number(config.recording.frame_rate),
+        Field::RecordingMaxSeconds => number(config.recording.max_seconds),
+        Field::BurstIntervalMs => number(config.burst.interval_ms),
+        Field::BurstFrames => number(config.burst.frames),
+        Field::BurstKeptFrames => number(config.burst.kept_frames),
+        Field::ClipboardBitmap
+        | Field::ClipboardPng
+        | Field::ClipboardFile
+        | Field::ClipboardHtml
+        | Field::ClipboardExr => {
+            let format = field.clipboard_format();
+            Value::Toggle(
+                config
+                    .clipboard
+                    .formats
+                    .iter()
+                    .any(|copied| Some(*copied) == format),
+            )
+        }
+    }
+}
+
+fn parse_number(text: &str) -> Result<u32, String> {
+    text.trim()
+        .parse()
+        .map_err(|_| format!("must be a whole number, not \"{}\"", text.trim()))
+}
+
+const fn names<T: Copy, const N: usize>(choices: &[(T, &'static str); N]) -> [&'static str; N] {
+    let mut names = [""; N];
+
+    let mut index = 0;
+    while index < N {
+        names[index] = choices[index].1;
+        index += 1;
+    }
+
+    names
+}
+
+fn choice_index<T: PartialEq>(value: T, choices: &[(T, &str)]) -> usize {
+    choices
+        .iter()
+        .position(|(choice, _)| *choice == value)
+        .unwrap_or_default()
+}
+
+fn set_choice<T: Copy>(value: &mut T, choices: &[(T, &str)], index: usize) {
+    if let Some((choice, _)) = choices.get(index) {
+        *value = *choice;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use chrono::{Local, TimeZone};
+
+    use crate::{
+        clipboard::ClipboardFormat,
+        config::Config,
+        hotkeys::{HotkeyAction, Shortcut},
+        save_hooks::{SaveHook, SaveHookAction},
+    };
+
+    use super::{ConfigModel, Field, MonitorField, Value};
+
+    fn shortcut(shortcut: &str) -> Shortcut {
+        shortcut.parse().unwrap()
+    }
+
+    fn problem_keys(model: &ConfigModel) -> Vec<String> {
+        model
+            .problems()
+            .into_iter()
+            .map(|problem| problem.key)
+            .collect()
+    }
+
+    #[test]
+    fn tracks_changes() {
+        let mut model = ConfigModel::new(Config::default());
+        assert!(!model.is_modified());
+
+        model.set_toggle(Field::CaptureWindow, true);
+        model.set_text(Field::RecordingFrameRate, "30");
+        assert!(model.is_modified());
+        assert!(model.is_field_modified(Field::CaptureWindow));
+        assert!(model.is_field_modified(Field::RecordingFrameRate));
+        assert!(!model.is_field_modified(Field::CaptureVirtualDesktop));
+
+        // Changing a value back is not a change.
+        model.set_toggle(Field::CaptureWindow, false);
+        assert!(!model.is_field_modified(Field::CaptureWindow));
+
+        model.revert();
+        assert!(!model.is_modified());
+        assert_eq!(model.config(), &Config::default());
+    }
+
+    #[test]
+    fn keeps_text_that_does_not_parse() {
+        let mut model = ConfigModel::new(Config::default());
+        let frame_rate = model.config().recording.frame_rate;
+
+        model.set_text(Field::RecordingFrameRate, "fast");
+        assert_eq!(
+            model.value(Field::RecordingFrameRate),
+            Value::Text(String::from("fast"))
+        );
+        assert_eq!(model.config().recording.frame_rate, frame_rate);
+        assert_eq!(problem_keys(&model), ["recording.frame_rate"]);
+        assert!(model.is_modified());
+        assert!(model.commit().is_err());
+
+        model.set_text(Field::RecordingFrameRate, " 24 ");
+        assert!(model.problems().is_empty());
+        assert_eq!(model.config().recording.frame_rate, 24);
+    }
+
+    #[test]
+    fn reports_invalid_values() {
+        let mut model = ConfigModel::new(Config::default());
+
+        model.set_text(Field::RecordingFrameRate, "120");
+        model.set_text(Field::ScreenshotName, "Screenshot %F?");
+        model.add_hotkey(shortcut("Escape"));
+
+        assert_eq!(
+            problem_keys(&model),
+            ["recording.frame_rate", "file_names.screenshot", "hotkeys"]
+        );
+        assert_eq!(
+            model.problem_with("recording.frame_rate").unwrap().message,
+            "`recording.frame_rate` must be between 1 and 60 frames per second, not 120"
+        );
+    }
+
+    #[test]
+    fn previews_file_names() {
+        let time = Local.with_ymd_and_hms(2024, 1, 2, 3, 4, 5).unwrap();
+        let mut model = ConfigModel::new(Config::default());
+
+        model.set_text(Field::ScreenshotName, "Capture %Y-%m-%d");
+        model.set_choice(Field::RecordingFormat, 1);
+
+        assert_eq!(
+            model.preview(Field::ScreenshotName, &time),
+            Some(Ok(String::from("Capture 2024-01-02.png")))
+        );
+        assert_eq!(
+            model.preview(Field::RecordingName, &time),
+            Some(Ok(String::from("Recording 2024-01-02 030405.gif")))
+        );
+        assert_eq!(
+            model.preview(Field::BurstName, &time),
+            Some(Ok(String::from("Burst 2024-01-02 030405 001.png")))
+        );
+        assert_eq!(model.preview(Field::ScreenshotDelay, &time), None);
+
+        model.set_text(Field::BurstName, "");
+        assert_eq!(
+            model.preview(Field::BurstName, &time),
+            Some(Err(String::from("The name is empty")))
+        );
+    }
+
+    #[test]
+    fn edits_hotkeys() {
+        let mut model = ConfigModel::new(Config::default());
+
+        let index = model.add_hotkey(shortcut("Ctrl+Shift+D"));
+        model.set_action(index, 4);
+        assert_eq!(
+            model.hotkeys()[index].action,
+            HotkeyAction::DelayedCapture(5)
+        );
+        assert_eq!(model.action_index(index), Some(4));
+
+        model.set_hotkey_delay(index, "soon");
+        assert_eq!(model.hotkey_delay(index), Some(String::from("soon")));
+        assert_eq!(problem_keys(&model), ["hotkeys[1].action"]);
+
+        // The rejected delay follows its hotkey when an earlier hotkey is removed.
+        model.remove_hotkey(0);
+        assert_eq!(problem_keys(&model), ["hotkeys[0].action"]);
+
+        model.set_hotkey_delay(0, "10");
+        assert_eq!(model.hotkeys()[0].action, HotkeyAction::DelayedCapture(10));
+
+        // Picking another action discards the delay.
+        model.set_action(0, 0);
+        assert_eq!(model.hotkey_delay(0), None);
+        assert!(model.problems().is_empty());
+    }
+
+    #[test]
+    fn edits_monitor_overrides() {
+        let mut model = ConfigModel::new(Config::default());
+
+        let index = model.add_monitor();
+        assert_eq!(problem_keys(&model), ["monitors[0].monitor"]);
+
+        model.set_monitor_text(index, MonitorField::Monitor, " DEL4123 ");
+        model.set_monitor_text(index, MonitorField::MaxBrightness, "600");
+        model.set_monitor_text(index, MonitorField::SdrWhite, "bright");
+        model.set_monitor_whitepoint(index, 2);
+
+        let monitor = &model.monitors()[index];
+        assert_eq!(monitor.monitor, "DEL4123");
+        assert_eq!(monitor.max_brightness, Some(600.0));
+        assert_eq!(monitor.sdr_white, None);
+        assert_eq!(model.monitor_whitepoint(index), 2);
+        assert_eq!(problem_keys(&model), ["monitors[0].sdr_white"]);
+
+        // Empty text uses the monitor's own value.
+        model.set_monitor_text(index, MonitorField::SdrWhite, "");
+        model.set_monitor_text(index, MonitorField::MaxBrightness, "");
+        assert_eq!(model.monitors()[index].max_brightness, None);
+        assert!(model.problems().is_empty());
+
+        model.remove_monitor(index);
+        assert!(model.monitors().is_empty());
+    }
+
+    #[test]
+    fn commits_valid_changes() {
+        let mut model = ConfigModel::new(Config::default());
+
+        model.set_choice(Field::Whitepoint, 2);
+        model.set_text(Field::BurstFrames, "12");
+
+        let config = model.commit().unwrap();
+        assert_eq!(config.burst.frames, 12);
+        assert_eq!(config, *model.config());
+        assert!(!model.is_modified());
+
+        model.reset_to_defaults();
+        assert!(model.is_modified());
+        assert_eq!(model.config(), &Config::default());
+    }
+
+    #[test]
+    fn defaults_keep_save_hooks() {
+        let hook = SaveHook {
+            action: SaveHookAction::CopyPath,
+            timeout_seconds: 30,
+            bursts: false,
+        };
+        let mut model = ConfigModel::new(Config {
+            save_hooks: vec![hook.clone()],
+            ..Config::default()
+        });
+
+        // The save hooks are not shown, so resetting the settings must not remove them.
+        model.reset_to_defaults();
+        assert_eq!(model.config().save_hooks, [hook]);
+        assert!(!model.is_modified());
+    }
+
+    #[test]
+    fn edits_clipboard_formats() {
+        let mut model = ConfigModel::new(Config::default());
+        assert_eq!(model.value(Field::ClipboardPng), Value::Toggle(true));
+        assert_eq!(model.value(Field::ClipboardExr), Value::Toggle(false));
+
+        model.set_toggle(Field::ClipboardPng, false);
+        model.set_toggle(Field::ClipboardExr, true);
+        assert_eq!(
+            model.config().clipboard.formats,
+            [
+                ClipboardFormat::Bitmap,
+                ClipboardFormat::File,
+                ClipboardFormat::Html,
+                ClipboardFormat::Exr
+            ]
+        );
+        assert!(model.is_field_modified(Field::ClipboardPng));
+        assert!(!model.is_field_modified(Field::ClipboardBitmap));
+
+        // Turning a format on twice copies it once.
+        model.set_toggle(Field::ClipboardExr, true);
+        assert_eq!(model.config().clipboard.formats.len(), 4);
+
+        model.set_toggle(Field::ClipboardPng, true);
+        model.set_toggle(Field::ClipboardExr, false);
+        assert!(!model.is_field_modified(Field::ClipboardPng));
+        assert_eq!(model.value(Field::ClipboardExr), Value::Toggle(false));
+    }
+}
@@ -0,0 +1,1141 @@
+use core::{
+    cell::RefCell,
+    ffi::c_void,
+    sync::atomic::{AtomicIsize, Ordering},
+};
+use std::{sync::OnceLock, thread};
+
+use chrono::Local;
+use global_hotkey::hotkey::Modifiers;
+use tracing::{info, info_span};
+use windows::{
+    Win32::{
+        Foundation::{GetLastError, HINSTANCE, HWND, LPARAM, LRESULT, RECT, WPARAM},
+        Graphics::Gdi::{
+            CLEARTYPE_QUALITY, CLIP_DEFAULT_PRECIS, COLOR_BTNFACE, CreateFontW, DEFAULT_CHARSET,
+            DeleteObject, FW_NORMAL, HBRUSH, HFONT, OUT_DEFAULT_PRECIS,
+        },
+        System::LibraryLoader::GetModuleHandleW,
+        UI::{
+            HiDpi::GetDpiForSystem,
+            Input::KeyboardAndMouse::{
+                EnableWindow, GetKeyState, VK_CONTROL, VK_ESCAPE, VK_LWIN, VK_MENU, VK_RWIN,
+                VK_SHIFT, VK_SNAPSHOT,
+            },
+            WindowsAndMessaging::{
+                AdjustWindowRectEx, BM_GETCHECK, BM_SETCHECK, BN_CLICKED, BS_AUTOCHECKBOX,
+                BS_PUSHBUTTON, CB_ADDSTRING, CB_GETCURSEL, CB_SETCURSEL, CBN_SELCHANGE,
+                CBS_DROPDOWNLIST, CW_USEDEFAULT, CreateWindowExW, DefWindowProcW, DestroyWindow,
+                DispatchMessageW, EN_CHANGE, ES_AUTOHSCROLL, GWLP_USERDATA, GetMessageW,
+                GetWindowLongPtrW, GetWindowTextLengthW, GetWindowTextW, HMENU, IDC_ARROW,
+                IDCANCEL, IDYES, IsDialogMessageW, LB_ADDSTRING, LB_GETCURSEL, LB_RESETCONTENT,
+                LB_SETCURSEL, LBN_SELCHANGE, LBS_NOTIFY, LoadCursorW, MB_ICONWARNING, MB_YESNO,
+                MSG, MessageBoxW, PostQuitMessage, RegisterClassW, SW_SHOW, SendMessageW,
+                SetForegroundWindow, SetWindowLongPtrW, SetWindowTextW, ShowWindow,
+                TranslateMessage, WINDOW_EX_STYLE, WINDOW_STYLE, WM_CHAR, WM_CLOSE, WM_COMMAND,
+                WM_DESTROY, WM_KEYDOWN, WM_KEYUP, WM_SETFONT, WM_SYSCHAR, WM_SYSKEYDOWN,
+                WM_SYSKEYUP, WNDCLASSW, WS_BORDER, WS_CAPTION, WS_CHILD, WS_EX_CLIENTEDGE,
+                WS_MINIMIZEBOX, WS_OVERLAPPED, WS_SYSMENU, WS_TABSTOP, WS_VISIBLE, WS_VSCROLL,
+            },
+        },
+    },
+    core::{HSTRING, PCWSTR, h, w},
+};
+use windows_capture_provider::{LabelledWinResult, WinError};
+
+use crate::{
+    config::Config,
+    error_reporting::{ErrorCategory, report_error},
+    hotkeys::{self, Shortcut},
+    utilities::failure::Ignore,
+};
+
+use super::model::{
+    ACTION_NAMES, ConfigModel, Field, FieldKind, MONITOR_WHITEPOINTS, MonitorField, Value,
+};
+
+const TITLE: &str = "HDR Snipping Tool Settings";
+
+// The layout at 100% scale.
+const MARGIN: i32 = 12;
+const ROW: i32 = 28;
+const CONTROL_HEIGHT: i32 = 23;
+const LABEL_WIDTH: i32 = 120;
+const CONTROL_WIDTH: i32 = 180;
+const NUMBER_WIDTH: i32 = 70;
+const BUTTON_WIDTH: i32 = 100;
+const GAP: i32 = 8;
+const COLUMN_WIDTH: i32 = LABEL_WIDTH + GAP + CONTROL_WIDTH;
+const COLUMN_GAP: i32 = 24;
+
+/// The section that starts the second column of fields, the hotkeys and monitors are in the third.
+const SECOND_COLUMN: &str = "History";
+
+// The control IDs, each field's is `FIELD_ID` plus its index in `Field::ALL`.
+const FIELD_ID: i32 = 1000;
+const HOTKEY_LIST_ID: i32 = 2000;
+const RECORD_ID: i32 = 2001;
+const ACTION_ID: i32 = 2002;
+const DELAY_ID: i32 = 2003;
+const ADD_HOTKEY_ID: i32 = 2004;
+const REMOVE_HOTKEY_ID: i32 = 2005;
+const MONITOR_LIST_ID: i32 = 2100;
+const MONITOR_FIELD_ID: i32 = 2101;
+const MONITOR_WHITEPOINT_ID: i32 = 2110;
+const ADD_MONITOR_ID: i32 = 2111;
+const REMOVE_MONITOR_ID: i32 = 2112;
+const SAVE_ID: i32 = 3000;
+const REVERT_ID: i32 = 3001;
+const DEFAULTS_ID: i32 = 3002;
+
+/// The open settings window, `0` if there is none, so opening the settings again brings it forward
+/// instead of opening a second window.
+static OPEN_WINDOW: AtomicIsize = AtomicIsize::new(0);
+
+/// Stands in for the window in `OPEN_WINDOW` while it is being created.
+const OPENING: isize = -1;
+
+/// The atom of the window class, registered the first time the settings are opened.
+static WINDOW_CLASS: OnceLock<u16> = OnceLock::new();
+
+/// Opens the settings window on its own thread, editing a copy of the config. Saving writes the
+/// config file, which the config watcher then reloads.
+pub fn open(config: Config) {
+    // Claimed before the thread starts, so opening the settings twice quickly opens one window.
+    match OPEN_WINDOW.compare_exchange(0, OPENING, Ordering::AcqRel, Ordering::Acquire) {
+        Ok(_) => {}
+
+        // The window is shown in the foreground once it is created.
+        Err(OPENING) => return,
+
+        Err(open_window) => {
+            let _ = unsafe { SetForegroundWindow(HWND(open_window as *mut c_void)) };
+            return;
+        }
+    }
+
+    let spawn_result = thread::Builder::new()
+        .name(String::from("Settings"))
+        .spawn(move || {
+            let _span = info_span!("[Settings]").entered();
+
+            if let Err(error) = unsafe { run(config) } {
+                report_error(
+                    ErrorCategory::Settings,
+                    error,
+                    "Could not open the settings",
+                );
+            }
+            OPEN_WINDOW.store(0, Ordering::Release);
+        });
+
+    if let Err(error) = spawn_result {
+        OPEN_WINDOW.store(0, Ordering::Release);
+        report_error(
+            ErrorCategory::Settings,
+            error,
+            "Could not open the settings",
+        );
+    }
+}
+
+/// Registers the window class the first time it is needed.
+unsafe fn register_class(instance: HINSTANCE) -> LabelledWinResult<PCWSTR> {
+    let class_name = w!("HdrSnippingToolSettings");
+    if WINDOW_CLASS.get().is_some() {
+        return Ok(class_name);
+    }
+
+    let class = WNDCLASSW {
+        lpfnWndProc: Some(window_procedure),
+        hInstance: instance,
+        hCursor: unsafe { LoadCursorW(None, IDC_ARROW) }
+            .map_err(|e| WinError::new(e, "LoadCursorW"))?,
+        hbrBackground: HBRUSH((COLOR_BTNFACE.0 + 1) as usize as *mut c_void),
+        lpszClassName: class_name,
+        ..WNDCLASSW::default()
+    };
+
+    match unsafe { RegisterClassW(&class) } {
+        0 => Err(WinError::from_win32(
+            unsafe { GetLastError() },
+            "RegisterClassW",
+        )),
+        atom => {
+            let _ = WINDOW_CLASS.set(atom);
+            Ok(class_name)
+        }
+    }
+}
+
+/// Shows the window and handles its messages until it is closed.
+unsafe fn run(config: Config) -> LabelledWinResult<()> {
+    let window = unsafe { SettingsWindow::create(config)? };
+    let handle = window.window;
+
+    let state = Box::into_raw(Box::new(RefCell::new(window)));
+    unsafe {
+        SetWindowLongPtrW(handle, GWLP_USERDATA, state as isize);
+        let _ = ShowWindow(handle, SW_SHOW);
+    }
+    OPEN_WINDOW.store(handle.0 as isize, Ordering::Release);
+    info!("Opened the settings");
+
+    let mut message = MSG::default();
+    while unsafe { GetMessageW(&mut message, None, 0, 0) }.as_bool() {
+        // Keys pressed while recording a shortcut must not move the focus or press buttons.
+        let is_recorded = match unsafe { &*state }.try_borrow_mut() {
+            Ok(mut window) => window.record_shortcut(&message),
+            Err(_) => false,
+        };
+        if is_recorded {
+            continue;
+        }
+
+        unsafe {
+            if !IsDialogMessageW(handle, &message).as_bool() {
+                let _ = TranslateMessage(&message);
+                DispatchMessageW(&message);
+            }
+        }
+    }
+
+    // The window is destroyed, so its procedure no longer uses the state.
+    let window = unsafe { Box::from_raw(state) };
+    let _ = unsafe { DeleteObject(window.borrow().font.into()) };
+    info!("Closed the settings");
+
+    Ok(())
+}
+
+/// What a recorded shortcut is for.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Recording {
+    Hotkey(usize),
+    NewHotkey,
+}
+
+struct Controls {
+    fields: Vec<(Field, HWND)>,
+    previews: Vec<(Field, HWND)>,
+
+    hotkey_list: HWND,
+    record: HWND,
+    action: HWND,
+    delay: HWND,
+    remove_hotkey: HWND,
+
+    monitor_list: HWND,
+    monitor_fields: Vec<(MonitorField, HWND)>,
+    monitor_whitepoint: HWND,
+    remove_monitor: HWND,
+
+    problems: HWND,
+    save: HWND,
+    revert: HWND,
+}
+
+struct SettingsWindow {
+    window: HWND,
+    font: HFONT,
+    model: ConfigModel,
+    controls: Controls,
+    recording: Option<Recording>,
+}
+
+/// Creates the child controls at 100% scale positions, scaled to the system DPI.
+struct ControlBuilder {
+    parent: HWND,
+    instance: HINSTANCE,
+    font: HFONT,
+    dpi: i32,
+}
+
+impl SettingsWindow {
+    unsafe fn create(config: Config) -> LabelledWinResult<Self> {
+        let instance: HINSTANCE = unsafe { GetModuleHandleW(None) }
+            .map_err(|e| WinError::new(e, "GetModuleHandleW"))?
+            .into();
+        let dpi = unsafe { GetDpiForSystem() } as i32;
+
+        let class_name = unsafe { register_class(instance)? };
+
+        let style = WS_OVERLAPPED | WS_CAPTION | WS_SYSMENU | WS_MINIMIZEBOX;
+        let mut rect = RECT {
+            left: 0,
+            top: 0,
+            right: scale(MARGIN * 2 + COLUMN_WIDTH * 3 + COLUMN_GAP * 2, dpi),
+            // The hotkeys and monitors are the tallest column, followed by the problems and buttons.
+            bottom: scale(
+                MARGIN * 2 + ROW * 19 + GAP * 2 + ROW * 3 + CONTROL_HEIGHT,
+                dpi,
+            ),
+        };
+        unsafe { AdjustWindowRectEx(&mut rect, style, false, WINDOW_EX_STYLE::default()) }
+            .map_err(|e| WinError::new(e, "AdjustWindowRectEx"))?;
+
+        let window = unsafe {
+            CreateWindowExW(
+                WINDOW_EX_STYLE::default(),
+                class_name,
+                &HSTRING::from(TITLE),
+                style,
+                CW_USEDEFAULT,
+                CW_USEDEFAULT,
+                rect.right - rect.left,
+                rect.bottom - rect.top,
+                None,
+                None,
+                Some(instance),
+                None,
+            )
+        }
+        .map_err(|e| WinError::new(e, "CreateWindowExW"))?;
+
+        let font = unsafe {
+            CreateFontW(
+                -scale(12, dpi),
+                0,
+                0,
+                0,
+                FW_NORMAL.0 as i32,
+                0,
+                0,
+                0,
+                DEFAULT_CHARSET,
+                OUT_DEFAULT_PRECIS,
+                CLIP_DEFAULT_PRECIS,
+                CLEARTYPE_QUALITY,
+                0,
+                w!("Segoe UI"),
+            )
+        };
+
+        let builder = ControlBuilder {
+            parent: window,
+            instance,
+            font,
+            dpi,
+        };
+        let controls = unsafe { builder.build() }.inspect_err(|_| unsafe {
+            DestroyWindow(window).ignore();
+        })?;
+
+        let settings = Self {
+            window,
+            font,
+            model: ConfigModel::new(config),
+            controls,
+            recording: None,
+        };
+
+        settings.show_fields();
+        settings.show_hotkeys(Some(0));
+        settings.show_monitors(Some(0));
+        settings.show_state();
+
+        Ok(settings)
+    }
+
+    /// Handles a control being changed, returning if it was one of the window's controls.
+    fn command(&mut self, id: i32, notification: u32, control: HWND) -> bool {
+        if let Some(field) = usize::try_from(id - FIELD_ID)
+            .ok()
+            .and_then(|index| Field::ALL.get(index).copied())
+        {
+            match (field.kind(), notification) {
+                (FieldKind::Toggle, BN_CLICKED) => {
+                    let is_checked = send(control, BM_GETCHECK, 0, 0) == 1;
+                    self.model.set_toggle(field, is_checked);
+                }
+                (FieldKind::Choice(_), CBN_SELCHANGE) => {
+                    if let Some(index) = selection(control, CB_GETCURSEL) {
+                        self.model.set_choice(field, index);
+                    }
+                }
+                (FieldKind::Number(_) | FieldKind::FileName, EN_CHANGE) => {
+                    self.model.set_text(field, &text(control));
+                }
+                _ => return true,
+            }
+
+            self.show_state();
+            return true;
+        }
+
+        if let Some(field) = usize::try_from(id - MONITOR_FIELD_ID)
+            .ok()
+            .and_then(|index| MonitorField::ALL.get(index).copied())
+        {
+            if notification == EN_CHANGE {
+                if let Some(index) = self.selected_monitor() {
+                    self.model.set_monitor_text(index, field, &text(control));
+                    if field == MonitorField::Monitor {
+                        self.show_monitor_list(Some(index));
+                    }
+                    self.show_state();
+                }
+            }
+            return true;
+        }
+
+        match (id, notification) {
+            (HOTKEY_LIST_ID, LBN_SELCHANGE) => {
+                self.recording = None;
+                self.show_hotkey_editor();
+            }
+            (RECORD_ID, BN_CLICKED) => {
+                self.recording = self.selected_hotkey().map(Recording::Hotkey);
+                self.show_hotkey_editor();
+            }
+            (ACTION_ID, CBN_SELCHANGE) => {
+                if let (Some(index), Some(action)) = (
+                    self.selected_hotkey(),
+                    selection(self.controls.action, CB_GETCURSEL),
+                ) {
+                    self.model.set_action(index, action);
+                    self.show_hotkeys(Some(index));
+                    self.show_state();
+                }
+            }
+            (DELAY_ID, EN_CHANGE) => {
+                if let Some(index) = self.selected_hotkey() {
+                    self.model.set_hotkey_delay(index, &text(control));
+                    self.show_hotkey_list(Some(index));
+                    self.show_state();
+                }
+            }
+            (ADD_HOTKEY_ID, BN_CLICKED) => {
+                self.recording = Some(Recording::NewHotkey);
+                self.show_hotkey_editor();
+            }
+            (REMOVE_HOTKEY_ID, BN_CLICKED) => {
+                if let Some(index) = self.selected_hotkey() {
+                    self.recording = None;
+                    self.model.remove_hotkey(index);
+                    self.show_hotkeys(Some(index.saturating_sub(1)));
+                    self.show_state();
+                }
+            }
+
+            (MONITOR_LIST_ID, LBN_SELCHANGE) => self.show_monitor_editor(),
+            (MONITOR_WHITEPOINT_ID, CBN_SELCHANGE) => {
+                if let (Some(index), Some(whitepoint)) = (
+                    self.selected_monitor(),
+                    selection(self.controls.monitor_whitepoint, CB_GETCURSEL),
+                ) {
+                    self.model.set_monitor_whitepoint(index, whitepoint);
+                    self.show_state();
+                }
+            }
+            (ADD_MONITOR_ID, BN_CLICKED) => {
+                let index = self.model.add_monitor();
+                self.show_monitors(Some(index));
+                self.show_state();
+            }
+            (REMOVE_MONITOR_ID, BN_CLICKED) => {
+                if let Some(index) = self.selected_monitor() {
+                    self.model.remove_monitor(index);
+                    self.show_monitors(Some(index.saturating_sub(1)));
+                    self.show_state();
+                }
+            }
+
+            (SAVE_ID, BN_CLICKED) => self.save(),
+            (REVERT_ID, BN_CLICKED) => {
+                self.model.revert();
+                self.show_everything();
+            }
+            (DEFAULTS_ID, BN_CLICKED) => {
+                self.model.reset_to_defaults();
+                self.show_everything();
+            }
+
+            _ => return false,
+        }
+
+        true
+    }
+
+    /// Records the shortcut pressed while recording, returning if the message was a key press
+    /// that is handled by recording.
+    ///
+    /// Shortcuts that are registered as hotkeys are taken by the hotkey before they reach the
+    /// window, so a hotkey's own shortcut cannot be recorded again.
+    fn record_shortcut(&mut self, message: &MSG) -> bool {
+        let Some(recording) = self.recording else {
+            return false;
+        };
+
+        let virtual_key = message.wParam.0 as u16;
+
+        // Print Screen only sends a key up.
+        let is_press = matches!(message.message, WM_KEYDOWN | WM_SYSKEYDOWN)
+            || (message.message == WM_KEYUP && virtual_key == VK_SNAPSHOT.0);
+        if !is_press {
+            return matches!(
+                message.message,
+                WM_KEYUP | WM_SYSKEYUP | WM_CHAR | WM_SYSCHAR
+            );
+        }
+
+        let modifiers = held_modifiers();
+        if virtual_key == VK_ESCAPE.0 && modifiers.is_empty() {
+            self.recording = None;
+            self.show_hotkey_editor();
+            return true;
+        }
+
+        // Only modifiers are held so far.
+        let Some(key) = hotkeys::key_code(virtual_key) else {
+            return true;
+        };
+
+        let shortcut = Shortcut::new(modifiers, key);
+        let index = match recording {
+            Recording::Hotkey(index) => {
+                self.model.set_shortcut(index, shortcut);
+                index
+            }
+            Recording::NewHotkey => self.model.add_hotkey(shortcut),
+        };
+
+        self.recording = None;
+        self.show_hotkeys(Some(index));
+        self.show_state();
+        true
+    }
+
+    fn save(&mut self) {
+        // Saving is disabled while there are problems, which are already shown.
+        if let Ok(config) = self.model.commit() {
+            config.save();
+            info!("Saved the settings");
+        }
+
+        self.show_state();
+    }
+
+    /// Asks before discarding unsaved changes, returning if the window should close.
+    fn confirm_close(&self) -> bool {
+        if !self.model.is_modified() {
+            return true;
+        }
+
+        let result = unsafe {
+            MessageBoxW(
+                Some(self.window),
+                h!("The settings have unsaved changes, close without saving them?"),
+                &HSTRING::from(TITLE),
+                MB_YESNO | MB_ICONWARNING,
+            )
+        };
+        result == IDYES
+    }
+
+    fn show_everything(&mut self) {
+        self.recording = None;
+        self.show_fields();
+        self.show_hotkeys(Some(0));
+        self.show_monitors(Some(0));
+        self.show_state();
+    }
+
+    /// Shows the model's value in each field's control.
+    fn show_fields(&self) {
+        for (field, control) in &self.controls.fields {
+            match self.model.value(*field) {
+                Value::Toggle(is_checked) => {
+                    send(*control, BM_SETCHECK, usize::from(is_checked), 0);
+                }
+                Value::Text(value) => set_text(*control, &value),
+                Value::Choice(index) => {
+                    send(*control, CB_SETCURSEL, index, 0);
+                }
+            }
+        }
+    }
+
+    /// Shows the previews, the problems and if there is anything to save.
+    fn show_state(&self) {
+        let now = Local::now();
+        for (field, control) in &self.controls.previews {
+            let preview = match self.model.preview(*field, &now) {
+                Some(Ok(name)) => format!("Saved as \"{name}\""),
+                Some(Err(problem)) => problem,
+                None => String::new(),
+            };
+            set_text(*control, &preview);
+        }
+
+        let problems = self.model.problems();
+        let status = if problems.is_empty() {
+            String::new()
+        } else {
+            let lines: Vec<_> = problems
+                .iter()
+                .map(|problem| format!("• {problem}"))
+                .collect();
+            lines.join("\n")
+        };
+        set_text(self.controls.problems, &status);
+
+        let is_modified = self.model.is_modified();
+        let title = if is_modified {
+            format!("{TITLE}*")
+        } else {
+            String::from(TITLE)
+        };
+        set_text(self.window, &title);
+
+        enable(self.controls.save, is_modified && problems.is_empty());
+        enable(self.controls.revert, is_modified);
+    }
+
+    fn show_hotkeys(&self, selected: Option<usize>) {
+        self.show_hotkey_list(selected);
+        self.show_hotkey_editor();
+    }
+
+    fn show_hotkey_list(&self, selected: Option<usize>) {
+        let list = self.controls.hotkey_list;
+        send(list, LB_RESETCONTENT, 0, 0);
+
+        for binding in self.model.hotkeys() {
+            add_item(
+                list,
+                LB_ADDSTRING,
+                &format!("{}: {}", binding.shortcut, binding.action),
+            );
+        }
+
+        let count = self.model.hotkeys().len();
+        if let Some(selected) = selected.filter(|_| count > 0) {
+            send(list, LB_SETCURSEL, selected.min(count - 1), 0);
+        }
+    }
+
+    /// Shows the selected hotkey in the controls that edit it.
+    fn show_hotkey_editor(&self) {
+        let selected = self.selected_hotkey();
+        let controls = &self.controls;
+
+        let record_text = match (self.recording, selected) {
+            (Some(_), _) => String::from("Press a shortcut, Esc cancels"),
+            (None, Some(index)) => self.model.hotkeys()[index].shortcut.to_string(),
+            (None, None) => String::new(),
+        };
+        set_text(controls.record, &record_text);
+
+        let action = selected.and_then(|index| self.model.action_index(index));
+        send(
+            controls.action,
+            CB_SETCURSEL,
+            action.unwrap_or(usize::MAX),
+            0,
+        );
+
+        let delay = selected.and_then(|index| self.model.hotkey_delay(index));
+        set_text(controls.delay, delay.as_deref().unwrap_or_default());
+
+        enable(controls.record, selected.is_some());
+        enable(controls.action, selected.is_some());
+        enable(controls.delay, delay.is_some());
+        enable(controls.remove_hotkey, selected.is_some());
+    }
+
+    fn show_monitors(&self, selected: Option<usize>) {
+        self.show_monitor_list(selected);
+        self.show_monitor_editor();
+    }
+
+    fn show_monitor_list(&self, selected: Option<usize>) {
+        let list = self.controls.monitor_list;
+        send(list, LB_RESETCONTENT, 0, 0);
+
+        for monitor in self.model.monitors() {
+            let name = if monitor.monitor.is_empty() {
+                "New monitor"
+            } else {
+                &monitor.monitor
+            };
+            add_item(list, LB_ADDSTRING, name);
+        }
+
+        let count = self.model.monitors().len();
+        if let Some(selected) = selected.filter(|_| count > 0) {
+            send(list, LB_SETCURSEL, selected.min(count - 1), 0);
+        }
+    }
+
+    /// Shows the selected monitor override in the controls that edit it.
+    fn show_monitor_editor(&self) {
+        let selected = self.selected_monitor();
+        let controls = &self.controls;
+
+        for (field, control) in &controls.monitor_fields {
+            let value = selected
+                .map(|index| self.model.monitor_text(index, *field))
+                .unwrap_or_default();
+            set_text(*control, &value);
+            enable(*control, selected.is_some());
+        }
+
+        let whitepoint = selected.map_or(usize::MAX, |index| self.model.monitor_whitepoint(index));
+        send(controls.monitor_whitepoint, CB_SETCURSEL, whitepoint, 0);
+
+        enable(controls.monitor_whitepoint, selected.is_some());
+        enable(controls.remove_monitor, selected.is_some());
+    }
+
+    fn selected_hotkey(&self) -> Option<usize> {
+        selection(self.controls.hotkey_list, LB_GETCURSEL)
+            .filter(|index| *index < self.model.hotkeys().len())
+    }
+
+    fn selected_monitor(&self) -> Option<usize> {
+        selection(self.controls.monitor_list, LB_GETCURSEL)
+            .filter(|index| *index < self.model.monitors().len())
+    }
+}
+
+impl ControlBuilder {
+    unsafe fn build(&self) -> LabelledWinResult<Controls> {
+        let column = |index: i32| MARGIN + index * (COLUMN_WIDTH + COLUMN_GAP);
+        let control_x = |x: i32| x + LABEL_WIDTH + GAP;
+
+        let mut fields = Vec::with_capacity(Field::ALL.len());
+        let mut previews = Vec::new();
+
+        let mut x = column(0);
+        let mut y = MARGIN;
+        let mut section = "";
+        let mut bottom = y;
+
+        for (index, field) in Field::ALL.into_iter().enumerate() {
+            let id = FIELD_ID + index as i32;
+
+            if field.section() != section {
+                section = field.section();
+                if section == SECOND_COLUMN {
+                    x = column(1);
+                    y = MARGIN;
+                } else if y > MARGIN {
+                    y += GAP;
+                }
+
+                self.label(section, [x, y, COLUMN_WIDTH, CONTROL_HEIGHT])?;
+                y += ROW;
+            }
+
+            let control = match field.kind() {
+                FieldKind::Toggle => self.control(
+                    w!("BUTTON"),
+                    field.label(),
+                    WS_TABSTOP | WINDOW_STYLE(BS_AUTOCHECKBOX as u32),
+                    [x, y, COLUMN_WIDTH, CONTROL_HEIGHT],
+                    id,
+                )?,
+
+                FieldKind::Number(unit) => {
+                    self.label(field.label(), [x, y + 3, LABEL_WIDTH, CONTROL_HEIGHT])?;
+                    self.label(
+                        unit,
+                        [
+                            control_x(x) + NUMBER_WIDTH + GAP,
+                            y + 3,
+                            CONTROL_WIDTH - NUMBER_WIDTH - GAP,
+                            CONTROL_HEIGHT,
+                        ],
+                    )?;
+                    self.edit([control_x(x), y, NUMBER_WIDTH, CONTROL_HEIGHT], id)?
+                }
+
+                FieldKind::Choice(choices) => {
+                    self.label(field.label(), [x, y + 3, LABEL_WIDTH, CONTROL_HEIGHT])?;
+                    self.combo_box(choices, [control_x(x), y, CONTROL_WIDTH], id)?
+                }
+
+                FieldKind::FileName => {
+                    self.label(field.label(), [x, y + 3, LABEL_WIDTH, CONTROL_HEIGHT])?;
+                    let edit = self.edit([control_x(x), y, CONTROL_WIDTH, CONTROL_HEIGHT], id)?;
+
+                    y += ROW;
+                    let preview =
+                        self.label("", [control_x(x), y, CONTROL_WIDTH, CONTROL_HEIGHT])?;
+                    previews.push((field, preview));
+
+                    edit
+                }
+            };
+            fields.push((field, control));
+
+            y += ROW;
+            bottom = bottom.max(y);
+        }
+
+        // The hotkeys and monitor overrides.
+        let x = column(2);
+        let mut y = MARGIN;
+
+        self.label("Hotkeys", [x, y, COLUMN_WIDTH, CONTROL_HEIGHT])?;
+        y += ROW;
+        let hotkey_list = self.list_box([x, y, COLUMN_WIDTH, ROW * 4 - GAP], HOTKEY_LIST_ID)?;
+        y += ROW * 4;
+
+        self.label("Shortcut", [x, y + 3, LABEL_WIDTH, CONTROL_HEIGHT])?;
+        let record = self.button(
+            "",
+            [control_x(x), y, CONTROL_WIDTH, CONTROL_HEIGHT],
+            RECORD_ID,
+        )?;
+        y += ROW;
+
+        self.label("Action", [x, y + 3, LABEL_WIDTH, CONTROL_HEIGHT])?;
+        let action = self.combo_box(&ACTION_NAMES, [control_x(x), y, CONTROL_WIDTH], ACTION_ID)?;
+        y += ROW;
+
+        self.label("Delay", [x, y + 3, LABEL_WIDTH, CONTROL_HEIGHT])?;
+        let delay = self.edit([control_x(x), y, NUMBER_WIDTH, CONTROL_HEIGHT], DELAY_ID)?;
+        self.label(
+            "seconds",
+            [
+                control_x(x) + NUMBER_WIDTH + GAP,
+                y + 3,
+                NUMBER_WIDTH,
+                CONTROL_HEIGHT,
+            ],
+        )?;
+        y += ROW;
+
+        let [add_x, remove_x] = [
+            x + COLUMN_WIDTH - BUTTON_WIDTH * 2 - GAP,
+            x + COLUMN_WIDTH - BUTTON_WIDTH,
+        ];
+        self.button(
+            "Add Hotkey",
+            [add_x, y, BUTTON_WIDTH, CONTROL_HEIGHT],
+            ADD_HOTKEY_ID,
+        )?;
+        let remove_hotkey = self.button(
+            "Remove",
+            [remove_x, y, BUTTON_WIDTH, CONTROL_HEIGHT],
+            REMOVE_HOTKEY_ID,
+        )?;
+        y += ROW + GAP;
+
+        self.label("Monitor corrections", [x, y, COLUMN_WIDTH, CONTROL_HEIGHT])?;
+        y += ROW;
+        let monitor_list = self.list_box([x, y, COLUMN_WIDTH, ROW * 3 - GAP], MONITOR_LIST_ID)?;
+        y += ROW * 3;
+
+        let mut monitor_fields = Vec::with_capacity(MonitorField::ALL.len());
+        for (index, field) in MonitorField::ALL.into_iter().enumerate() {
+            let width = match field {
+                MonitorField::Monitor => CONTROL_WIDTH,
+                _ => NUMBER_WIDTH,
+            };
+
+            self.label(field.label(), [x, y + 3, LABEL_WIDTH, CONTROL_HEIGHT])?;
+            let edit = self.edit(
+                [control_x(x), y, width, CONTROL_HEIGHT],
+                MONITOR_FIELD_ID + index as i32,
+            )?;
+            monitor_fields.push((field, edit));
+            y += ROW;
+        }
+
+        self.label("Whitepoint", [x, y + 3, LABEL_WIDTH, CONTROL_HEIGHT])?;
+        let monitor_whitepoint = self.combo_box(
+            &MONITOR_WHITEPOINTS,
+            [control_x(x), y, CONTROL_WIDTH],
+            MONITOR_WHITEPOINT_ID,
+        )?;
+        y += ROW;
+
+        self.button(
+            "Add Monitor",
+            [add_x, y, BUTTON_WIDTH, CONTROL_HEIGHT],
+            ADD_MONITOR_ID,
+        )?;
+        let remove_monitor = self.button(
+            "Remove",
+            [remove_x, y, BUTTON_WIDTH, CONTROL_HEIGHT],
+            REMOVE_MONITOR_ID,
+        )?;
+        y += ROW;
+        bottom = bottom.max(y) + GAP;
+
+        // The problems and buttons along the bottom.
+        let width = COLUMN_WIDTH * 3 + COLUMN_GAP * 2;
+        let problems = self.label("", [MARGIN, bottom, width, ROW * 3 - GAP])?;
+        let y = bottom + ROW * 3;
+
+        self.button(
+            "Restore Defaults",
+            [MARGIN, y, BUTTON_WIDTH + GAP * 2, CONTROL_HEIGHT],
+            DEFAULTS_ID,
+        )?;
+        let revert = self.button(
+            "Revert",
+            [
+                MARGIN + width - BUTTON_WIDTH * 2 - GAP,
+                y,
+                BUTTON_WIDTH,
+                CONTROL_HEIGHT,
+            ],
+            REVERT_ID,
+        )?;
+        let save = self.button(
+            "Save",
+            [
+                MARGIN + width - BUTTON_WIDTH,
+                y,
+                BUTTON_WIDTH,
+                CONTROL_HEIGHT,
+            ],
+            SAVE_ID,
+        )?;
+
+        Ok(Controls {
+            fields,
+            previews,
+            hotkey_list,
+            record,
+            action,
+            delay,
+            remove_hotkey,
+            monitor_list,
+            monitor_fields,
+            monitor_whitepoint,
+            remove_monitor,
+            problems,
+            save,
+            revert,
+        })
+    }
+
+    fn label(&self, text: &str, rect: [i32; 4]) -> LabelledWinResult<HWND> {
+        self.control(w!("STATIC"), text, WINDOW_STYLE::default(), rect, 0)
+    }
+
+    fn button(&self, text: &str, rect: [i32; 4], id: i32) -> LabelledWinResult<HWND> {
+        self.control(
+            w!("BUTTON"),
+            text,
+            WS_TABSTOP | WINDOW_STYLE(BS_PUSHBUTTON as u32),
+            rect,
+            id,
+        )
+    }
+
+    fn edit(&self, rect: [i32; 4], id: i32) -> LabelledWinResult<HWND> {
+        self.control_ex(
+            WS_EX_CLIENTEDGE,
+            w!("EDIT"),
+            "",
+            WS_TABSTOP | WS_BORDER | WINDOW_STYLE(ES_AUTOHSCROLL as u32),
+            rect,
+            id,
+        )
+    }
+
+    /// A drop down list of the choices, `rect` has no height as the list drops below it.
+    fn combo_box(&self, choices: &[&str], rect: [i32; 3], id: i32) -> LabelledWinResult<HWND> {
+        let [x, y, width] = rect;
+
+        let combo_box = self.control(
+            w!("COMBOBOX"),
+            "",
+            WS_TABSTOP | WS_VSCROLL | WINDOW_STYLE(CBS_DROPDOWNLIST as u32),
+            [x, y, width, ROW * 8],
+            id,
+        )?;
+        for choice in choices {
+            add_item(combo_box, CB_ADDSTRING, choice);
+        }
+
+        Ok(combo_box)
+    }
+
+    fn list_box(&self, rect: [i32; 4], id: i32) -> LabelledWinResult<HWND> {
+        self.control_ex(
+            WS_EX_CLIENTEDGE,
+            w!("LISTBOX"),
+            "",
+            WS_TABSTOP | WS_VSCROLL | WINDOW_STYLE(LBS_NOTIFY as u32),
+            rect,
+            id,
+        )
+    }
+
+    fn control(
+        &self,
+        class: PCWSTR,
+        text: &str,
+        style: WINDOW_STYLE,
+        rect: [i32; 4],
+        id: i32,
+    ) -> LabelledWinResult<HWND> {
+        self.control_ex(WINDOW_EX_STYLE::default(), class, text, style, rect, id)
+    }
+
+    fn control_ex(
+        &self,
+        extended_style: WINDOW_EX_STYLE,
+        class: PCWSTR,
+        text: &str,
+        style: WINDOW_STYLE,
+        rect: [i32; 4],
+        id: i32,
+    ) -> LabelledWinResult<HWND> {
+        let [x, y, width, height] = rect.map(|value| scale(value, self.dpi));
+
+        let control = unsafe {
+            CreateWindowExW(
+                extended_style,
+                class,
+                &HSTRING::from(text),
+                WS_CHILD | WS_VISIBLE | style,
+                x,
+                y,
+                width,
+                height,
+                Some(self.parent),
+                Some(HMENU(id as isize as *mut c_void)),
+                Some(self.instance),
+                None,
+            )
+        }
+        .map_err(|e| WinError::new(e, "CreateWindowExW"))?;
+
+        send(control, WM_SETFONT, self.font.0 as usize, 0);
+
+        Ok(control)
+    }
+}
+
+unsafe extern "system" fn window_procedure(
+    window: HWND,
+    message: u32,
+    wparam: WPARAM,
+    lparam: LPARAM,
+) -> LRESULT {
+    if message == WM_DESTROY {
+        unsafe {
+            SetWindowLongPtrW(window, GWLP_USERDATA, 0);
+            PostQuitMessage(0);
+        }
+        return LRESULT(0);
+    }
+
+    let state =
+        unsafe { GetWindowLongPtrW(window, GWLP_USERDATA) } as *const RefCell<SettingsWindow>;
+
+    // Messages sent while the window is being created or changed from a handler, like a field's
+    // text being shown, are left to the default procedure.
+    let mut settings = match unsafe { state.as_ref() }.map(RefCell::try_borrow_mut) {
+        Some(Ok(settings)) => settings,
+        _ => return unsafe { DefWindowProcW(window, message, wparam, lparam) },
+    };
+
+    let is_handled = match message {
+        WM_COMMAND => {
+            let id = (wparam.0 & 0xFFFF) as i32;
+            let notification = ((wparam.0 >> 16) & 0xFFFF) as u32;
+            let control = HWND(lparam.0 as *mut c_void);
+
+            // Escape closes the window like its close button.
+            if id == IDCANCEL.0 {
+                let should_close = settings.confirm_close();
+                drop(settings);
+                close(window, should_close);
+                return LRESULT(0);
+            }
+
+            settings.command(id, notification, control)
+        }
+
+        WM_CLOSE => {
+            let should_close = settings.confirm_close();
+            drop(settings);
+            close(window, should_close);
+            return LRESULT(0);
+        }
+
+        _ => false,
+    };
+
+    if is_handled {
+        return LRESULT(0);
+    }
+
+    drop(settings);
+    unsafe { DefWindowProcW(window, message, wparam, lparam) }
+}
+
+/// Destroys the window, once nothing is borrowing its state as destroying it sends messages to it.
+fn close(window: HWND, should_close: bool) {
+    if should_close {
+        unsafe { DestroyWindow(window) }.ignore();
+    }
+}
+
+/// Scales a length at 100% scale to the DPI.
+fn scale(value: i32, dpi: i32) -> i32 {
+    value * dpi / 96
+}
+
+/// The modifiers that are held down.
+fn held_modifiers() -> Modifiers {
+    let is_held = |key: u16| unsafe { GetKeyState(i32::from(key)) } < 0;
+
+    let mut modifiers = Modifiers::empty();
+    if is_held(VK_CONTROL.0) {
+        modifiers |= Modifiers::CONTROL;
+    }
+    if is_held(VK_MENU.0) {
+        modifiers |= Modifiers::ALT;
+    }
+    if is_held(VK_SHIFT.0) {
+        modifiers |= Modifiers::SHIFT;
+    }
+    if is_held(VK_LWIN.0) || is_held(VK_RWIN.0) {
+        modifiers |= Modifiers::SUPER;
+    }
+
+    modifiers
+}
+
+fn send(control: HWND, message: u32, wparam: usize, lparam: isize) -> isize {
+    unsafe { SendMessageW(control, message, Some(WPARAM(wparam)), Some(LPARAM(lparam))) }.0
+}
+
+fn add_item(control: HWND, message: u32, text: &str) {
+    let text = HSTRING::from(text);
+    send(control, message, 0, text.as_ptr() as isize);
+}
+
+/// The selected item of a list or combo box, `None` if nothing is selected.
+fn selection(control: HWND, message: u32) -> Option<usize> {
+    usize::try_from(send(control, message, 0, 0)).ok()
+}
+
+fn text(control: HWND) -> String {
+    let length = unsafe { GetWindowTextLengthW(control) };
+    let mut buffer = vec![0; usize::try_from(length).unwrap_or_default() + 1];
+
+    let copied = unsafe { GetWindowTextW(control, &mut buffer) };
+    String::from_utf16_lossy(&buffer[..usize::try_from(copied).unwrap_or_default()])
+}
+
+/// Sets the text of a control if it changed, so the cursor in a field being typed in stays put.
+fn set_text(control: HWND, value: &str) {
+    if text(control) != value {
+        unsafe { SetWindowTextW(control, &HSTRING::from(value)) }.ignore();
+    }
+}
+
+fn enable(control: HWND, is_enabled: bool) {
+    let _ = unsafe { EnableWindow(control, is_enabled) };
+}
@@ -1,7 +1,12 @@
 use std::fs::create_dir_all;
 
+use crate::error_reporting::ErrorCategory;
+
 use super::failure::{Failure, Ignore};
 
+/// The name of the log file in the config directory.
+pub const LOG_FILE_NAME: &str = "hdr-snipping-tool.log";
+
 /// Path to the screenshot directory.
 pub fn screenshot_dir() -> std::path::PathBuf {
     let dir = dirs::picture_dir()
@@ -9,7 +14,27 @@ pub fn screenshot_dir() -> std::path::PathBuf {
         .join("Screenshots");
 
     create_dir_all(&dir)
-        .report("Could not create the screenshot directory")
+        .report(
+            ErrorCategory::Save,
+            "Could not create the screenshot directory",
+        )
+        .ignore();
+
+    dir
+}
+
+/// Path to the capture history directory.
+pub fn history_dir() -> std::path::PathBuf {
+    let dir = dirs::cache_dir()
+        .report_and_panic("The cache directory could not be retreived")
+        .join("HDR Snipping Tool")
+        .join("History");
+
+    create_dir_all(&dir)
+        .report(
+            ErrorCategory::History,
+            "Could not create the capture history directory",
+        )
         .ignore();
 
     dir
@@ -22,7 +47,24 @@ pub fn config_dir() -> std::path::PathBuf {
         .join("HDR Snipping Tool");
 
     create_dir_all(&dir)
-        .report("Could not create the config directory")
+        .report(
+            ErrorCategory::Config,
+            "Could not create the config directory",
+        )
+        .ignore();
+
+    dir
+}
+
+/// Path to the directory diagnostic bundles are written to when the application crashes.
+pub fn diagnostics_dir() -> std::path::PathBuf {
+    let dir = config_dir().join("Diagnostics");
+
+    create_dir_all(&dir)
+        .report(
+            ErrorCategory::Config,
+            "Could not create the diagnostics directory",
+        )
         .ignore();
 
     dir
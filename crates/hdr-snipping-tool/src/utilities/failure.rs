@@ -1,9 +1,6 @@
 use tracing::{debug, error};
-use windows::Win32::UI::WindowsAndMessaging::{
-    MB_ICONERROR, MB_ICONWARNING, MB_OK, MB_SETFOREGROUND,
-};
 
-use crate::utilities::windows_helpers::display_message;
+use crate::error_reporting::{ErrorCategory, report_error};
 
 pub fn log_and_panic<Err: core::fmt::Display>(error: Err, message: &str) -> ! {
     error!("{message}: {error}");
@@ -11,31 +8,21 @@ pub fn log_and_panic<Err: core::fmt::Display>(error: Err, message: &str) -> ! {
     panic!("{message}: {error}");
 }
 
+/// Reports the error with the installed sink before panicking, it is only logged until a sink
+/// is installed.
 pub fn report_and_panic<Err: core::fmt::Display>(error: Err, message: &str) -> ! {
-    error!("{message}: {error}");
-
-    let user_message =
-        format!("ERROR:\n{message}.\nSee the logs for more details, the application will exit.");
-    display_message(&user_message, MB_ICONERROR | MB_OK | MB_SETFOREGROUND);
+    let summary = format!(
+        "{message}.\nThe application will exit, a diagnostic bundle is written to the Diagnostics folder in the config directory"
+    );
+    report_error(ErrorCategory::Fatal, &error, &summary);
 
     panic!("{message}: {error}");
 }
 
-/// Reports an error in the formats:
-///
-/// * Log: `"{message}: {error}"`
-/// * Dialogue: `"{message}.\nSee the logs for more details."`
-pub fn report<Err: core::fmt::Display>(error: Err, message: &str) {
-    error!("{message}: {error}");
-
-    let user_message = format!("{message}.\nSee the logs for more details.");
-    display_message(&user_message, MB_ICONWARNING | MB_OK | MB_SETFOREGROUND);
-}
-
 #[allow(unused)]
 pub trait Failure<T> {
     fn report_and_panic(self, message: &str) -> T;
-    fn report(self, message: &str) -> Option<T>;
+    fn report(self, category: ErrorCategory, message: &str) -> Option<T>;
     fn log_and_panic(self, message: &str) -> T;
 }
 
@@ -51,11 +38,11 @@ impl<T, E: core::fmt::Display> Failure<T> for Result<T, E> {
         }
     }
 
-    fn report(self, message: &str) -> Option<T> {
+    fn report(self, category: ErrorCategory, message: &str) -> Option<T> {
         match self {
             Ok(value) => Some(value),
             Err(error) => {
-                report(error, message);
+                report_error(category, error, message);
                 None
             }
         }
@@ -86,11 +73,11 @@ impl<T> Failure<T> for Option<T> {
         }
     }
 
-    fn report(self, message: &str) -> Self {
+    fn report(self, category: ErrorCategory, message: &str) -> Self {
         match self {
             Some(value) => Some(value),
             None => {
-                report("Was None", message);
+                report_error(category, "Was None", message);
                 None
             }
         }
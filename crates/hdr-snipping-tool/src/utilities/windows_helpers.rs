@@ -1,18 +1,35 @@
-use std::path::Path;
+use std::{io, os::windows::ffi::OsStrExt, path::Path, process::Command};
 
 use windows::{
     Win32::{
-        Foundation::{GetLastError, HWND, WIN32_ERROR},
-        System::Threading::{CreateMutexW, MUTEX_ALL_ACCESS, OpenMutexW},
+        Foundation::{COLORREF, GetLastError, HWND, LPARAM, RECT, WIN32_ERROR},
+        Graphics::{
+            Dwm::{DWMWA_CLOAKED, DWMWA_EXTENDED_FRAME_BOUNDS, DwmGetWindowAttribute},
+            Gdi::{
+                BLACK_BRUSH, CLEARTYPE_QUALITY, CLIP_DEFAULT_PRECIS, CreateFontW, DEFAULT_CHARSET,
+                DT_CENTER, DT_SINGLELINE, DT_VCENTER, DeleteObject, DrawTextW, FW_SEMIBOLD,
+                FillRect, GetDC, GetStockObject, HBRUSH, OUT_DEFAULT_PRECIS, ReleaseDC,
+                SelectObject, SetBkMode, SetTextColor, TRANSPARENT,
+            },
+        },
+        System::{
+            Diagnostics::Debug::MessageBeep,
+            Threading::{CreateMutexW, MUTEX_ALL_ACCESS, OpenMutexW},
+        },
         UI::{
-            Shell::ShellExecuteW,
+            Shell::{
+                FO_DELETE, FOF_ALLOWUNDO, FOF_NOCONFIRMATION, FOF_NOERRORUI, FOF_SILENT,
+                SHFILEOPSTRUCTW, SHFileOperationW, ShellExecuteW,
+            },
             WindowsAndMessaging::{
-                GetForegroundWindow, MESSAGEBOX_RESULT, MESSAGEBOX_STYLE, MessageBoxW,
-                SetForegroundWindow,
+                EnumWindows, GWL_EXSTYLE, GetClassNameW, GetClientRect, GetForegroundWindow,
+                GetShellWindow, GetWindowLongW, IsIconic, IsWindowVisible, MB_OK,
+                SetForegroundWindow, SetWindowDisplayAffinity, WDA_EXCLUDEFROMCAPTURE,
+                WINDOW_EX_STYLE, WS_EX_LAYERED, WS_EX_TOOLWINDOW, WS_EX_TRANSPARENT,
             },
         },
     },
-    core::{HRESULT, HSTRING, h, w},
+    core::{BOOL, HRESULT, HSTRING, PCWSTR, w},
 };
 use windows_capture_provider::{LabelledWinResult, WinError};
 
@@ -48,13 +65,9 @@ pub fn is_first_instance() -> LabelledWinResult<bool> {
     Ok(true)
 }
 
-/// Display a Windows message box.
-pub fn display_message(message: &str, style: MESSAGEBOX_STYLE) -> MESSAGEBOX_RESULT {
-    unsafe {
-        let message = HSTRING::from(message);
-
-        MessageBoxW(None, &message, h!("HDR Snipping Tool"), style)
-    }
+/// Plays the system's default sound.
+pub fn play_confirmation_sound() -> LabelledWinResult<()> {
+    unsafe { MessageBeep(MB_OK) }.map_err(|e| WinError::new(e, "MessageBeep"))
 }
 
 /// Gets the handle to the current foreground window.
@@ -71,6 +84,62 @@ pub fn set_foreground_window(handle: HWND) -> bool {
     unsafe { SetForegroundWindow(handle).as_bool() }
 }
 
+/// Excludes a window from screen captures, so it does not appear in screenshots.
+pub fn exclude_from_capture(handle: HWND) -> LabelledWinResult<()> {
+    unsafe { SetWindowDisplayAffinity(handle, WDA_EXCLUDEFROMCAPTURE) }
+        .map_err(|e| WinError::new(e, "SetWindowDisplayAffinity"))
+}
+
+/// Fills a window black and draws white text in the centre of it.
+pub fn draw_centred_text(handle: HWND, text: &str) {
+    let mut rect = RECT::default();
+    if unsafe { GetClientRect(handle, &mut rect) }.is_err() {
+        return;
+    }
+
+    unsafe {
+        let dc = GetDC(Some(handle));
+        if dc.is_invalid() {
+            return;
+        }
+
+        FillRect(dc, &rect, HBRUSH(GetStockObject(BLACK_BRUSH).0));
+
+        let font = CreateFontW(
+            (rect.bottom - rect.top) / 2,
+            0,
+            0,
+            0,
+            FW_SEMIBOLD.0 as i32,
+            0,
+            0,
+            0,
+            DEFAULT_CHARSET,
+            OUT_DEFAULT_PRECIS,
+            CLIP_DEFAULT_PRECIS,
+            CLEARTYPE_QUALITY,
+            0,
+            w!("Segoe UI"),
+        );
+        let previous_font = SelectObject(dc, font.into());
+
+        SetBkMode(dc, TRANSPARENT);
+        SetTextColor(dc, COLORREF(0x00FFFFFF));
+
+        let mut text: Vec<u16> = text.encode_utf16().collect();
+        DrawTextW(
+            dc,
+            &mut text,
+            &mut rect,
+            DT_CENTER | DT_VCENTER | DT_SINGLELINE,
+        );
+
+        SelectObject(dc, previous_font);
+        let _ = DeleteObject(font.into());
+        ReleaseDC(Some(handle), dc);
+    }
+}
+
 /// Open a directory in the default file manager.
 pub unsafe fn explore_directory(directory: &Path) -> Result<(), WIN32_ERROR> {
     let result = unsafe {
@@ -91,3 +160,160 @@ pub unsafe fn explore_directory(directory: &Path) -> Result<(), WIN32_ERROR> {
 
     Err(unsafe { GetLastError() })
 }
+
+/// Open a file in the default application for its type.
+pub unsafe fn open_file(file: &Path) -> Result<(), WIN32_ERROR> {
+    let result = unsafe {
+        ShellExecuteW(
+            None,
+            w!("open"),
+            &HSTRING::from(file),
+            None,
+            None,
+            windows::Win32::UI::WindowsAndMessaging::SW_SHOWNORMAL,
+        )
+    };
+
+    // Success
+    if result.0 as i64 > 32 {
+        return Ok(());
+    }
+
+    Err(unsafe { GetLastError() })
+}
+
+/// Open the folder containing a file in the file manager, with the file selected.
+pub fn show_in_folder(file: &Path) -> io::Result<()> {
+    use std::os::windows::process::CommandExt;
+
+    // Explorer only understands the path when it is quoted after the comma.
+    Command::new("explorer")
+        .raw_arg(format!("/select,\"{}\"", file.display()))
+        .spawn()?;
+
+    Ok(())
+}
+
+/// Open a file in the default editor for its type, or the default application if it has no editor.
+pub unsafe fn open_in_editor(file: &Path) -> Result<(), WIN32_ERROR> {
+    for verb in [w!("edit"), w!("open")] {
+        let result = unsafe {
+            ShellExecuteW(
+                None,
+                verb,
+                &HSTRING::from(file),
+                None,
+                None,
+                windows::Win32::UI::WindowsAndMessaging::SW_SHOWNORMAL,
+            )
+        };
+
+        // Success
+        if result.0 as i64 > 32 {
+            return Ok(());
+        }
+    }
+
+    Err(unsafe { GetLastError() })
+}
+
+/// Moves a file to the recycle bin, without asking or showing progress.
+pub fn move_to_recycle_bin(file: &Path) -> io::Result<()> {
+    // The list of files ends with an empty path.
+    let from: Vec<u16> = file.as_os_str().encode_wide().chain([0, 0]).collect();
+
+    let mut operation = SHFILEOPSTRUCTW {
+        wFunc: FO_DELETE,
+        pFrom: PCWSTR(from.as_ptr()),
+        fFlags: (FOF_ALLOWUNDO | FOF_NOCONFIRMATION | FOF_NOERRORUI | FOF_SILENT).0 as u16,
+        ..Default::default()
+    };
+
+    let result = unsafe { SHFileOperationW(&mut operation) };
+    if result != 0 {
+        return Err(io::Error::other(format!("The shell returned {result:#x}")));
+    }
+    if operation.fAnyOperationsAborted.as_bool() {
+        return Err(io::Error::other("The shell cancelled moving the file"));
+    }
+
+    Ok(())
+}
+
+/// The classes of the windows that draw the desktop's wallpaper and icons.
+const DESKTOP_CLASSES: [&str; 2] = ["Progman", "WorkerW"];
+
+/// Gets the bounds of the visible top-level windows in desktop coordinates, ordered from the
+/// top-most window to the bottom-most window. The bounds exclude the window's drop shadow.
+///
+/// The desktop and overlays that clicks pass through are not included, as they cover whole
+/// monitors without being something the user sees as a window.
+pub fn get_visible_window_rects() -> LabelledWinResult<Vec<RECT>> {
+    unsafe extern "system" fn enum_window(handle: HWND, rects: LPARAM) -> BOOL {
+        let rects = unsafe { &mut *(rects.0 as *mut Vec<RECT>) };
+
+        if !unsafe { IsWindowVisible(handle) }.as_bool() || unsafe { IsIconic(handle) }.as_bool() {
+            return true.into();
+        }
+
+        if unsafe { is_desktop_or_overlay(handle) } {
+            return true.into();
+        }
+
+        // Windows on other virtual desktops or suspended UWP windows are cloaked.
+        let mut cloaked = 0u32;
+        let is_cloaked = unsafe {
+            DwmGetWindowAttribute(
+                handle,
+                DWMWA_CLOAKED,
+                (&raw mut cloaked).cast(),
+                size_of::<u32>() as u32,
+            )
+        }
+        .is_ok_and(|_| cloaked != 0);
+        if is_cloaked {
+            return true.into();
+        }
+
+        let mut rect = RECT::default();
+        let has_bounds = unsafe {
+            DwmGetWindowAttribute(
+                handle,
+                DWMWA_EXTENDED_FRAME_BOUNDS,
+                (&raw mut rect).cast(),
+                size_of::<RECT>() as u32,
+            )
+        }
+        .is_ok();
+
+        if has_bounds && rect.right > rect.left && rect.bottom > rect.top {
+            rects.push(rect);
+        }
+
+        true.into()
+    }
+
+    let mut rects: Vec<RECT> = Vec::new();
+    unsafe { EnumWindows(Some(enum_window), LPARAM((&raw mut rects) as isize)) }
+        .map_err(|e| WinError::new(e, "EnumWindows"))?;
+
+    Ok(rects)
+}
+
+/// If the window is the shell's desktop, or an overlay that clicks pass through, like the
+/// transparent layered tool windows screen recorders and game overlays draw with.
+unsafe fn is_desktop_or_overlay(handle: HWND) -> bool {
+    if handle == unsafe { GetShellWindow() } {
+        return true;
+    }
+
+    let mut class_name = [0u16; 16];
+    let length = unsafe { GetClassNameW(handle, &mut class_name) };
+    let class_name = String::from_utf16_lossy(&class_name[..length.max(0) as usize]);
+    if DESKTOP_CLASSES.contains(&class_name.as_str()) {
+        return true;
+    }
+
+    let style = WINDOW_EX_STYLE(unsafe { GetWindowLongW(handle, GWL_EXSTYLE) } as u32);
+    style.contains(WS_EX_TRANSPARENT) || style.contains(WS_EX_LAYERED | WS_EX_TOOLWINDOW)
+}
@@ -0,0 +1,178 @@
+use alloc::sync::Arc;
+
+use ash::{ext, vk};
+use ash_helper::{AllocationError, Context, VkError, VulkanContext};
+use thiserror::Error;
+
+use crate::{Vulkan, shaders::composite_annotations};
+
+pub(crate) use run::Target;
+
+mod new;
+mod run;
+
+/// Composites annotations onto an image in `R8G8B8A8_UNORM` format, used by the capture saver and
+/// the renderer so both draw the annotations the same way.
+pub struct AnnotationCompositor {
+    vulkan: Arc<Vulkan>,
+
+    descriptor_layouts: Vec<vk::DescriptorSetLayout>,
+    pipeline_layout: vk::PipelineLayout,
+    shader: vk::ShaderEXT,
+}
+
+impl Drop for AnnotationCompositor {
+    fn drop(&mut self) {
+        unsafe {
+            let shader_device: &ext::shader_object::Device = self.vulkan.context();
+            shader_device.destroy_shader(self.shader, None);
+            self.vulkan
+                .device()
+                .destroy_pipeline_layout(self.pipeline_layout, None);
+
+            self.descriptor_layouts.iter().for_each(|layout| {
+                self.vulkan
+                    .device()
+                    .destroy_descriptor_set_layout(*layout, None);
+            });
+        }
+    }
+}
+
+/// An area of the window that an annotation covers, positions are relative to the top-left corner
+/// of the window.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Shape {
+    /// The pixels whose centre is within half of `width` of a line.
+    Line {
+        /// The start of the line.
+        start: [f32; 2],
+
+        /// The end of the line, the same as `start` for a dot.
+        end: [f32; 2],
+
+        /// The width of the line in pixels.
+        width: f32,
+    },
+
+    /// The pixels that a rectangle overlaps.
+    Rectangle {
+        /// A corner of the rectangle.
+        start: [f32; 2],
+
+        /// The opposite corner of the rectangle.
+        end: [f32; 2],
+    },
+}
+
+impl Shape {
+    /// The area (start, end) that the shape covers.
+    fn bounds(&self) -> [[f32; 2]; 2] {
+        match self {
+            Self::Line { start, end, width } => {
+                let radius = width / 2.0;
+                [
+                    [start[0].min(end[0]) - radius, start[1].min(end[1]) - radius],
+                    [start[0].max(end[0]) + radius, start[1].max(end[1]) + radius],
+                ]
+            }
+
+            Self::Rectangle { start, end } => [
+                [start[0].min(end[0]), start[1].min(end[1])],
+                [start[0].max(end[0]), start[1].max(end[1])],
+            ],
+        }
+    }
+
+    /// The shape as it is read by the shader.
+    fn as_shader_shape(&self) -> composite_annotations::Shape {
+        match *self {
+            Self::Line { start, end, width } => composite_annotations::Shape {
+                start,
+                end,
+                radius: width / 2.0,
+                kind: 0,
+            },
+
+            Self::Rectangle { start, end } => composite_annotations::Shape {
+                start,
+                end,
+                radius: 0.0,
+                kind: 1,
+            },
+        }
+    }
+}
+
+/// A pass that draws an annotation, passes are run in order.
+#[derive(Clone, Debug, PartialEq)]
+pub enum AnnotationPass {
+    /// Blends a colour over the pixels covered by any of the shapes, each pixel is blended once.
+    Blend {
+        /// The shapes that make up the annotation.
+        shapes: Vec<Shape>,
+
+        /// The `RGBA` colour with straight alpha.
+        colour: [f32; 4],
+    },
+
+    /// Replaces each block of an area with the average colour of the block.
+    Pixelate {
+        /// A corner of the area.
+        start: [f32; 2],
+
+        /// The opposite corner of the area.
+        end: [f32; 2],
+
+        /// The width and height of the blocks in pixels.
+        block_size: u32,
+    },
+
+    /// Blurs an area, pixels outside of the area do not contribute.
+    Blur {
+        /// A corner of the area.
+        start: [f32; 2],
+
+        /// The opposite corner of the area.
+        end: [f32; 2],
+
+        /// The radius of the blur in pixels.
+        radius: u32,
+    },
+}
+
+/// The redactions to preview over a capture.
+///
+/// The preview is only for showing the redactions, the capture saver must redact the capture
+/// itself before it is tonemapped.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Redactions {
+    /// The shapes that are filled with black.
+    pub fill: Vec<Shape>,
+
+    /// The shapes that are replaced with the average of the blocks of the capture under them.
+    pub pixelate: Vec<Shape>,
+
+    /// The width and height of the pixelated blocks, blocks are aligned to the capture.
+    pub block_size: u32,
+}
+
+impl Redactions {
+    /// If there are no redactions.
+    pub fn is_empty(&self) -> bool {
+        self.fill.is_empty() && self.pixelate.is_empty()
+    }
+}
+
+/// Annotation compositor error variants.
+#[derive(Debug, Error)]
+#[non_exhaustive]
+pub enum CompositorError {
+    /// An allocation failed, likely from the shape buffer or the blur image.
+    #[error(transparent)]
+    AllocationError(#[from] AllocationError),
+
+    /// A Vulkan call returned an error.
+    #[error(transparent)]
+    VkError(#[from] VkError),
+}
@@ -0,0 +1,93 @@
+use alloc::sync::Arc;
+use core::slice;
+
+use ash::{ext, vk};
+use ash_helper::{Context, VkError, VulkanContext, try_name, try_name_all};
+
+use crate::{Vulkan, shaders::composite_annotations};
+
+use super::{AnnotationCompositor, CompositorError};
+
+impl AnnotationCompositor {
+    /// Creates a new instance of the annotation compositor.
+    pub fn new(vulkan: Arc<Vulkan>) -> Result<Self, CompositorError> {
+        // Descriptor layouts
+        let descriptor_layouts = {
+            let layouts = unsafe {
+                composite_annotations::set_layouts(
+                    vulkan.device(),
+                    vk::DescriptorSetLayoutCreateFlags::PUSH_DESCRIPTOR_KHR,
+                )
+                .map_err(|e| VkError::new(e, "vkCreateDescriptorSetLayout"))?
+            };
+
+            unsafe {
+                try_name_all(
+                    vulkan.as_ref(),
+                    &layouts,
+                    "AnnotationCompositor Descriptor Layout",
+                )
+            };
+
+            layouts
+        };
+
+        // Pipeline layout
+        let pipeline_layout = {
+            let push_range = composite_annotations::PushConstants::push_constant_range();
+
+            let create_info = vk::PipelineLayoutCreateInfo::default()
+                .set_layouts(&descriptor_layouts)
+                .push_constant_ranges(slice::from_ref(&push_range));
+
+            let layout = unsafe { vulkan.device().create_pipeline_layout(&create_info, None) }
+                .map_err(|e| VkError::new(e, "vkCreatePiplineLayout"))?;
+
+            unsafe {
+                try_name(
+                    vulkan.as_ref(),
+                    layout,
+                    "AnnotationCompositor Pipeline Layout",
+                )
+            };
+
+            layout
+        };
+
+        // Shader
+        let shader = {
+            let push_range = composite_annotations::PushConstants::push_constant_range();
+
+            let create_info = vk::ShaderCreateInfoEXT::default()
+                .code(composite_annotations::BYTES)
+                .code_type(vk::ShaderCodeTypeEXT::SPIRV)
+                .stage(composite_annotations::compute_main::STAGE)
+                .name(composite_annotations::compute_main::ENTRY_POINT)
+                .set_layouts(&descriptor_layouts)
+                .push_constant_ranges(slice::from_ref(&push_range));
+
+            let device: &ext::shader_object::Device = unsafe { vulkan.context() };
+            let shaders = unsafe { device.create_shaders(slice::from_ref(&create_info), None) }
+                .map_err(|(_, e)| VkError::new(e, "vkCreateShadersEXT"))?;
+
+            let shader = shaders[0];
+            unsafe {
+                try_name(
+                    vulkan.as_ref(),
+                    shader,
+                    "AnnotationCompositor Compute Shader",
+                )
+            };
+
+            shader
+        };
+
+        Ok(Self {
+            vulkan,
+
+            descriptor_layouts,
+            pipeline_layout,
+            shader,
+        })
+    }
+}
@@ -0,0 +1,655 @@
+use core::slice;
+
+use ash::{ext, khr, vk};
+use ash_helper::{
+    Context, VkError, VulkanContext, allocate_buffer, allocate_image, cmd_transition_image,
+    cmd_try_begin_label, cmd_try_end_label, onetime_command,
+};
+use bytemuck::{bytes_of, cast_slice};
+use utilities::DebugTime;
+
+use crate::{
+    HdrImage, QueuePurpose, SdrImage,
+    shaders::composite_annotations::{self, PushConstants, compute_main::DISPATCH_SIZE},
+};
+
+use super::{AnnotationCompositor, AnnotationPass, CompositorError, Redactions, Shape};
+
+/// The number of box blur passes, three passes approximate a gaussian blur.
+const BLUR_PASSES: usize = 3;
+
+/// The largest update that `vkCmdUpdateBuffer` accepts.
+const MAX_UPDATE_SIZE: usize = 65536;
+
+/// The modes of the shader, matching `Mode` in `composite_annotations.slang`.
+#[derive(Clone, Copy)]
+#[repr(u32)]
+enum Mode {
+    Tonemap = 0,
+    Redact = 1,
+    Blend = 2,
+    Pixelate = 3,
+    BlurHorizontal = 4,
+    BlurVertical = 5,
+}
+
+/// The images that annotations are composited onto.
+#[derive(Clone, Copy)]
+pub(crate) struct Target {
+    /// The view of the `R8G8B8A8_UNORM` image to composite onto, in `GENERAL` layout.
+    pub view: vk::ImageView,
+
+    /// The view of an image the same size as the target for the blur passes, in `GENERAL` layout.
+    /// Only used if there are blur passes.
+    pub scratch_view: vk::ImageView,
+
+    /// The buffer the shapes are uploaded to, it must hold at least
+    /// [`AnnotationCompositor::shape_count`] shapes.
+    pub shape_buffer: vk::Buffer,
+
+    /// The HDR capture, in `GENERAL` layout.
+    pub capture: HdrImage,
+
+    /// The pixel of the capture at the top-left of the target.
+    pub origin: [u32; 2],
+
+    /// The area (start, end) of the target that is composited.
+    pub area: [[u32; 2]; 2],
+}
+
+impl Target {
+    /// Returns the pixels of the target covered by an area of the window, clipped to the
+    /// composited area.
+    fn clip(&self, start: [f32; 2], end: [f32; 2]) -> Option<[[u32; 2]; 2]> {
+        let clip_axis = |axis: usize| {
+            let origin = self.origin[axis] as f32;
+
+            let low = start[axis].min(end[axis]).floor() - origin;
+            let high = start[axis].max(end[axis]).ceil() - origin;
+
+            let low = (low.max(0.0) as u32).max(self.area[0][axis]);
+            let high = (high.max(0.0) as u32).min(self.area[1][axis]);
+
+            (low < high).then_some([low, high])
+        };
+
+        let [left, right] = clip_axis(0)?;
+        let [top, bottom] = clip_axis(1)?;
+
+        Some([[left, top], [right, bottom]])
+    }
+}
+
+impl AnnotationCompositor {
+    /// Composites annotations onto the selection of a tonemapped capture.
+    ///
+    /// Redactions are not drawn, the capture must be redacted before it is tonemapped.
+    ///
+    /// ## Image Requirements
+    /// * `capture`: the capture that `sdr_image` was tonemapped from, `layout: GENERAL`
+    /// * `sdr_image`: `layout: GENERAL`, `usage: STORAGE`
+    pub unsafe fn composite(
+        &self,
+        capture: HdrImage,
+        sdr_image: &SdrImage,
+        selection_position: [usize; 2],
+        selection_size: [usize; 2],
+        passes: &[AnnotationPass],
+    ) -> Result<(), CompositorError> {
+        let _timing = DebugTime::start("Compositing annotations");
+
+        let has_blur = passes
+            .iter()
+            .any(|pass| matches!(pass, AnnotationPass::Blur { .. }));
+
+        let view = unsafe { self.create_view(sdr_image.image)? };
+
+        // The blur passes need a second image to read from.
+        let scratch = if has_blur {
+            let (image, memory) = unsafe {
+                self.create_image(
+                    sdr_image.extent,
+                    vk::ImageUsageFlags::STORAGE,
+                    "AnnotationCompositor Blur Image",
+                )?
+            };
+            let scratch_view = unsafe { self.create_view(image)? };
+
+            Some((image, memory, scratch_view))
+        } else {
+            None
+        };
+
+        let shape_count = Self::shape_count(&Redactions::default(), passes);
+        let (shape_buffer, shape_memory) =
+            unsafe { self.create_shape_buffer(shape_count, "AnnotationCompositor Shape Buffer")? };
+
+        let target = Target {
+            view,
+            scratch_view: scratch.map_or(view, |(_, _, scratch_view)| scratch_view),
+            shape_buffer,
+            capture,
+            origin: [0, 0],
+            area: [
+                selection_position.map(|axis| axis as u32),
+                [
+                    (selection_position[0] + selection_size[0]) as u32,
+                    (selection_position[1] + selection_size[1]) as u32,
+                ],
+            ],
+        };
+
+        unsafe {
+            onetime_command(
+                self.vulkan.as_ref(),
+                self.vulkan.transient_pool(),
+                self.vulkan.queue(QueuePurpose::Compute),
+                |vulkan, command_buffer| {
+                    cmd_try_begin_label(vulkan, command_buffer, "AnnotationCompositor::composite");
+
+                    if let Some((image, _, _)) = scratch {
+                        cmd_transition_image(
+                            vulkan,
+                            command_buffer,
+                            image,
+                            vk::ImageLayout::UNDEFINED,
+                            vk::ImageLayout::GENERAL,
+                        )
+                        .unwrap();
+                    }
+
+                    self.cmd_composite(
+                        command_buffer,
+                        &target,
+                        None,
+                        &Redactions::default(),
+                        passes,
+                    );
+
+                    cmd_try_end_label(vulkan, command_buffer);
+                },
+                "AnnotationCompositor::composite > Dispatch",
+            )?;
+        }
+
+        // Clean up
+        unsafe {
+            let device = self.vulkan.device();
+
+            device.destroy_buffer(shape_buffer, None);
+            device.free_memory(shape_memory, None);
+
+            if let Some((image, memory, scratch_view)) = scratch {
+                device.destroy_image_view(scratch_view, None);
+                device.destroy_image(image, None);
+                device.free_memory(memory, None);
+            }
+
+            device.destroy_image_view(view, None);
+        }
+
+        Ok(())
+    }
+
+    /// Records the commands to composite annotations onto a target.
+    ///
+    /// If there is a `whitepoint`, the area is first tonemapped from the capture and the
+    /// redactions are previewed over it.
+    pub(crate) unsafe fn cmd_composite(
+        &self,
+        command_buffer: vk::CommandBuffer,
+        target: &Target,
+        whitepoint: Option<f32>,
+        redactions: &Redactions,
+        passes: &[AnnotationPass],
+    ) {
+        let device = unsafe { self.vulkan.device() };
+
+        // Upload the shapes, in the order the passes read them.
+        {
+            let shapes: Vec<_> = redactions
+                .fill
+                .iter()
+                .chain(&redactions.pixelate)
+                .chain(passes.iter().flat_map(|pass| match pass {
+                    AnnotationPass::Blend { shapes, .. } => shapes.as_slice(),
+                    _ => &[],
+                }))
+                .map(Shape::as_shader_shape)
+                .collect();
+
+            // Wait for previous reads of the buffer before overwriting it.
+            unsafe {
+                device.cmd_pipeline_barrier(
+                    command_buffer,
+                    vk::PipelineStageFlags::COMPUTE_SHADER,
+                    vk::PipelineStageFlags::TRANSFER,
+                    vk::DependencyFlags::empty(),
+                    &[],
+                    &[],
+                    &[],
+                );
+            }
+
+            let bytes: &[u8] = cast_slice(&shapes);
+            for (index, chunk) in bytes.chunks(MAX_UPDATE_SIZE).enumerate() {
+                unsafe {
+                    device.cmd_update_buffer(
+                        command_buffer,
+                        target.shape_buffer,
+                        (index * MAX_UPDATE_SIZE) as u64,
+                        chunk,
+                    );
+                }
+            }
+
+            let buffer_barrier = vk::BufferMemoryBarrier::default()
+                .buffer(target.shape_buffer)
+                .src_access_mask(vk::AccessFlags::TRANSFER_WRITE)
+                .dst_access_mask(vk::AccessFlags::SHADER_READ)
+                .offset(0)
+                .size(vk::WHOLE_SIZE);
+
+            unsafe {
+                device.cmd_pipeline_barrier(
+                    command_buffer,
+                    vk::PipelineStageFlags::TRANSFER,
+                    vk::PipelineStageFlags::COMPUTE_SHADER,
+                    vk::DependencyFlags::empty(),
+                    &[],
+                    slice::from_ref(&buffer_barrier),
+                    &[],
+                );
+            }
+        }
+
+        // Bind the shader
+        unsafe {
+            let shader_device: &ext::shader_object::Device = self.vulkan.context();
+            shader_device.cmd_bind_shaders(
+                command_buffer,
+                slice::from_ref(&composite_annotations::compute_main::STAGE),
+                slice::from_ref(&self.shader),
+            );
+        }
+
+        let fill_count = redactions.fill.len() as u32;
+        let pixelate_count = redactions.pixelate.len() as u32;
+
+        let base = PushConstants {
+            origin: target.origin,
+            area_start: target.area[0],
+            area_end: target.area[1],
+            whitepoint: whitepoint.unwrap_or(1.0),
+            fill_start: 0,
+            fill_count,
+            ..Default::default()
+        };
+
+        // Tonemap the capture and preview the redactions
+        if whitepoint.is_some() {
+            let push_constants = PushConstants {
+                mode: Mode::Tonemap as u32,
+                ..base
+            };
+            let groups = groups(target.area);
+            unsafe {
+                self.cmd_dispatch(command_buffer, target, target.view, push_constants, groups)
+            };
+
+            if !redactions.is_empty() {
+                let size = redactions.block_size.max(1);
+
+                let first_block =
+                    [0, 1].map(|axis| (target.origin[axis] + target.area[0][axis]) / size);
+                let last_block =
+                    [0, 1].map(|axis| (target.origin[axis] + target.area[1][axis]).div_ceil(size));
+
+                let push_constants = PushConstants {
+                    mode: Mode::Redact as u32,
+                    shape_start: fill_count,
+                    shape_count: pixelate_count,
+                    size,
+                    ..push_constants
+                };
+                let groups = [0, 1].map(|axis| {
+                    (last_block[axis] - first_block[axis]).div_ceil(DISPATCH_SIZE[axis])
+                });
+                unsafe {
+                    self.cmd_dispatch(command_buffer, target, target.view, push_constants, groups)
+                };
+            }
+        }
+
+        // Draw the annotations
+        let mut shape_start = fill_count + pixelate_count;
+        for pass in passes {
+            match pass {
+                AnnotationPass::Blend { shapes, colour } => {
+                    let shape_count = shapes.len() as u32;
+
+                    let bounds = shapes.iter().map(Shape::bounds).reduce(|a, b| {
+                        [
+                            [a[0][0].min(b[0][0]), a[0][1].min(b[0][1])],
+                            [a[1][0].max(b[1][0]), a[1][1].max(b[1][1])],
+                        ]
+                    });
+
+                    if let Some(area) = bounds.and_then(|[start, end]| target.clip(start, end)) {
+                        let push_constants = PushConstants {
+                            mode: Mode::Blend as u32,
+                            colour: *colour,
+                            area_start: area[0],
+                            area_end: area[1],
+                            shape_start,
+                            shape_count,
+                            ..base
+                        };
+                        unsafe {
+                            self.cmd_dispatch(
+                                command_buffer,
+                                target,
+                                target.view,
+                                push_constants,
+                                groups(area),
+                            )
+                        };
+                    }
+
+                    shape_start += shape_count;
+                }
+
+                AnnotationPass::Pixelate {
+                    start,
+                    end,
+                    block_size,
+                } => {
+                    let Some(area) = target.clip(*start, *end) else {
+                        continue;
+                    };
+                    let size = (*block_size).max(1);
+
+                    let push_constants = PushConstants {
+                        mode: Mode::Pixelate as u32,
+                        area_start: area[0],
+                        area_end: area[1],
+                        size,
+                        ..base
+                    };
+                    let groups = [0, 1].map(|axis| {
+                        (area[1][axis] - area[0][axis])
+                            .div_ceil(size)
+                            .div_ceil(DISPATCH_SIZE[axis])
+                    });
+                    unsafe {
+                        self.cmd_dispatch(
+                            command_buffer,
+                            target,
+                            target.view,
+                            push_constants,
+                            groups,
+                        )
+                    };
+                }
+
+                AnnotationPass::Blur { start, end, radius } => {
+                    let Some(area) = target.clip(*start, *end) else {
+                        continue;
+                    };
+
+                    let push_constants = PushConstants {
+                        area_start: area[0],
+                        area_end: area[1],
+                        size: *radius,
+                        ..base
+                    };
+
+                    // Each pass reads from one image and writes to the other, so it ends on the
+                    // target.
+                    for _ in 0..BLUR_PASSES {
+                        let horizontal = PushConstants {
+                            mode: Mode::BlurHorizontal as u32,
+                            ..push_constants
+                        };
+                        unsafe {
+                            self.cmd_dispatch(
+                                command_buffer,
+                                &Target {
+                                    view: target.scratch_view,
+                                    scratch_view: target.view,
+                                    ..*target
+                                },
+                                target.view,
+                                horizontal,
+                                groups(area),
+                            )
+                        };
+
+                        let vertical = PushConstants {
+                            mode: Mode::BlurVertical as u32,
+                            ..push_constants
+                        };
+                        unsafe {
+                            self.cmd_dispatch(
+                                command_buffer,
+                                target,
+                                target.scratch_view,
+                                vertical,
+                                groups(area),
+                            )
+                        };
+                    }
+                }
+            }
+        }
+    }
+
+    /// The number of shapes that the shape buffer must hold.
+    pub(crate) fn shape_count(redactions: &Redactions, passes: &[AnnotationPass]) -> usize {
+        let pass_shapes: usize = passes
+            .iter()
+            .map(|pass| match pass {
+                AnnotationPass::Blend { shapes, .. } => shapes.len(),
+                _ => 0,
+            })
+            .sum();
+
+        redactions.fill.len() + redactions.pixelate.len() + pass_shapes
+    }
+
+    /// Records a dispatch of the shader that writes to the target and reads from `source`, then
+    /// waits for the writes before the next dispatch.
+    unsafe fn cmd_dispatch(
+        &self,
+        command_buffer: vk::CommandBuffer,
+        target: &Target,
+        source: vk::ImageView,
+        push_constants: PushConstants,
+        groups: [u32; 2],
+    ) {
+        let device = unsafe { self.vulkan.device() };
+
+        // Push descriptor writes
+        {
+            let target_descriptor = vk::DescriptorImageInfo::default()
+                .image_layout(vk::ImageLayout::GENERAL)
+                .image_view(target.view)
+                .sampler(vk::Sampler::null());
+
+            let source_descriptor = vk::DescriptorImageInfo::default()
+                .image_layout(vk::ImageLayout::GENERAL)
+                .image_view(source)
+                .sampler(vk::Sampler::null());
+
+            let shape_descriptor = vk::DescriptorBufferInfo::default()
+                .buffer(target.shape_buffer)
+                .offset(0)
+                .range(vk::WHOLE_SIZE);
+
+            let capture_descriptor = vk::DescriptorImageInfo::default()
+                .image_layout(vk::ImageLayout::GENERAL)
+                .image_view(target.capture.view)
+                .sampler(vk::Sampler::null());
+
+            let descriptor_writes = [
+                // Target
+                vk::WriteDescriptorSet::default()
+                    .dst_set(vk::DescriptorSet::null())
+                    .dst_binding(0)
+                    .descriptor_count(1)
+                    .descriptor_type(vk::DescriptorType::STORAGE_IMAGE)
+                    .image_info(slice::from_ref(&target_descriptor)),
+                // Source
+                vk::WriteDescriptorSet::default()
+                    .dst_set(vk::DescriptorSet::null())
+                    .dst_binding(1)
+                    .descriptor_count(1)
+                    .descriptor_type(vk::DescriptorType::STORAGE_IMAGE)
+                    .image_info(slice::from_ref(&source_descriptor)),
+                // Shapes
+                vk::WriteDescriptorSet::default()
+                    .dst_set(vk::DescriptorSet::null())
+                    .dst_binding(2)
+                    .descriptor_count(1)
+                    .descriptor_type(vk::DescriptorType::STORAGE_BUFFER)
+                    .buffer_info(slice::from_ref(&shape_descriptor)),
+                // Capture
+                vk::WriteDescriptorSet::default()
+                    .dst_set(vk::DescriptorSet::null())
+                    .dst_binding(3)
+                    .descriptor_count(1)
+                    .descriptor_type(vk::DescriptorType::STORAGE_IMAGE)
+                    .image_info(slice::from_ref(&capture_descriptor)),
+            ];
+
+            unsafe {
+                let push_device: &khr::push_descriptor::Device = self.vulkan.context();
+                push_device.cmd_push_descriptor_set(
+                    command_buffer,
+                    vk::PipelineBindPoint::COMPUTE,
+                    self.pipeline_layout,
+                    0,
+                    &descriptor_writes,
+                );
+            }
+        }
+
+        unsafe {
+            device.cmd_push_constants(
+                command_buffer,
+                self.pipeline_layout,
+                PushConstants::STAGES,
+                0,
+                bytes_of(&push_constants),
+            );
+
+            device.cmd_dispatch(command_buffer, groups[0], groups[1], 1);
+        }
+
+        // Wait for the writes before the next pass reads them.
+        let memory_barrier = vk::MemoryBarrier::default()
+            .src_access_mask(vk::AccessFlags::SHADER_WRITE)
+            .dst_access_mask(vk::AccessFlags::SHADER_READ | vk::AccessFlags::SHADER_WRITE);
+
+        unsafe {
+            device.cmd_pipeline_barrier(
+                command_buffer,
+                vk::PipelineStageFlags::COMPUTE_SHADER,
+                vk::PipelineStageFlags::COMPUTE_SHADER,
+                vk::DependencyFlags::empty(),
+                slice::from_ref(&memory_barrier),
+                &[],
+                &[],
+            );
+        }
+    }
+
+    /// Creates an `R8G8B8A8_UNORM` image to composite onto, the image is in `UNDEFINED` layout.
+    pub(crate) unsafe fn create_image(
+        &self,
+        extent: vk::Extent2D,
+        usage: vk::ImageUsageFlags,
+        name: &str,
+    ) -> Result<(vk::Image, vk::DeviceMemory), CompositorError> {
+        let create_info = vk::ImageCreateInfo::default()
+            .array_layers(1)
+            .extent(extent.into())
+            .format(vk::Format::R8G8B8A8_UNORM)
+            .image_type(vk::ImageType::TYPE_2D)
+            .initial_layout(vk::ImageLayout::UNDEFINED)
+            .mip_levels(1)
+            .queue_family_indices(self.vulkan.queue_family_index_as_slice())
+            .samples(vk::SampleCountFlags::TYPE_1)
+            .sharing_mode(vk::SharingMode::EXCLUSIVE)
+            .tiling(vk::ImageTiling::OPTIMAL)
+            .usage(usage);
+
+        let (image, memory, _) = unsafe {
+            allocate_image(
+                self.vulkan.as_ref(),
+                &create_info,
+                vk::MemoryPropertyFlags::DEVICE_LOCAL,
+                name,
+            )?
+        };
+
+        Ok((image, memory))
+    }
+
+    /// Creates a view of an `R8G8B8A8_UNORM` image.
+    pub(crate) unsafe fn create_view(
+        &self,
+        image: vk::Image,
+    ) -> Result<vk::ImageView, CompositorError> {
+        let create_info = vk::ImageViewCreateInfo::default()
+            .image(image)
+            .view_type(vk::ImageViewType::TYPE_2D)
+            .format(vk::Format::R8G8B8A8_UNORM)
+            .subresource_range(
+                vk::ImageSubresourceRange::default()
+                    .aspect_mask(vk::ImageAspectFlags::COLOR)
+                    .layer_count(1)
+                    .level_count(1),
+            );
+
+        let view = unsafe {
+            self.vulkan
+                .device()
+                .create_image_view(&create_info, None)
+                .map_err(|e| VkError::new(e, "vkCreateImageView"))?
+        };
+
+        Ok(view)
+    }
+
+    /// Creates a buffer that holds `shape_count` shapes, it always holds at least one so it can be
+    /// bound.
+    pub(crate) unsafe fn create_shape_buffer(
+        &self,
+        shape_count: usize,
+        name: &str,
+    ) -> Result<(vk::Buffer, vk::DeviceMemory), CompositorError> {
+        let size = shape_count.max(1) * size_of::<composite_annotations::Shape>();
+
+        let create_info = vk::BufferCreateInfo::default()
+            .queue_family_indices(self.vulkan.queue_family_index_as_slice())
+            .usage(vk::BufferUsageFlags::STORAGE_BUFFER | vk::BufferUsageFlags::TRANSFER_DST)
+            .size(size as u64);
+
+        let (buffer, memory, _) = unsafe {
+            allocate_buffer(
+                self.vulkan.as_ref(),
+                &create_info,
+                vk::MemoryPropertyFlags::DEVICE_LOCAL,
+                name,
+            )?
+        };
+
+        Ok((buffer, memory))
+    }
+}
+
+/// The workgroups to dispatch for one thread per pixel of an area.
+fn groups(area: [[u32; 2]; 2]) -> [u32; 2] {
+    [0, 1].map(|axis| (area[1][axis] - area[0][axis]).div_ceil(DISPATCH_SIZE[axis]))
+}
@@ -1,8 +1,11 @@
-use ash::vk;
+use core::slice;
+
+use ash::{util::Align, vk};
 use ash_helper::{
-    AllocationError, VkError, VulkanContext, cmd_transition_image, find_memorytype_index,
-    onetime_command,
+    AllocationError, VkError, VulkanContext, allocate_buffer, allocate_image, cmd_transition_image,
+    find_memorytype_index, onetime_command,
 };
+use half::f16;
 use utilities::DebugTime;
 
 use crate::{QueuePurpose, Vulkan};
@@ -48,7 +51,11 @@ impl HdrImage {
                 .array_layers(1)
                 .samples(vk::SampleCountFlags::TYPE_1)
                 .tiling(vk::ImageTiling::OPTIMAL)
-                .usage(vk::ImageUsageFlags::STORAGE | vk::ImageUsageFlags::SAMPLED)
+                .usage(
+                    vk::ImageUsageFlags::STORAGE
+                        | vk::ImageUsageFlags::SAMPLED
+                        | vk::ImageUsageFlags::TRANSFER_SRC,
+                )
                 .sharing_mode(vk::SharingMode::EXCLUSIVE)
                 .initial_layout(vk::ImageLayout::UNDEFINED)
                 .push_next(&mut external_memory_image);
@@ -145,6 +152,256 @@ impl HdrImage {
         })
     }
 
+    /// Creates an image from `RGBA` pixel data in CPU memory, the data is row-major with no padding.
+    pub unsafe fn from_cpu(
+        vulkan: &Vulkan,
+        size: [u32; 2],
+        data: &[f16],
+    ) -> Result<Self, AllocationError> {
+        let _timer = DebugTime::start("Uploading HDR image");
+
+        let extent = vk::Extent2D::default().width(size[0]).height(size[1]);
+        let data_size = u64::from(size[0]) * u64::from(size[1]) * 4 * 2;
+        assert_eq!(
+            data.len() as u64 * 2,
+            data_size,
+            "Data must contain 4 channels for every pixel"
+        );
+
+        // Create staging
+        let (staging_buffer, staging_memory) = {
+            let buffer_info = vk::BufferCreateInfo::default()
+                .queue_family_indices(vulkan.queue_family_index_as_slice())
+                .usage(vk::BufferUsageFlags::TRANSFER_SRC)
+                .size(data_size);
+
+            let (buffer, memory, _) = unsafe {
+                allocate_buffer(
+                    vulkan,
+                    &buffer_info,
+                    vk::MemoryPropertyFlags::HOST_COHERENT | vk::MemoryPropertyFlags::HOST_VISIBLE,
+                    "CPU to HDR Staging",
+                )?
+            };
+
+            (buffer, memory)
+        };
+
+        // Copy data to staging
+        unsafe {
+            let pointer = vulkan
+                .device()
+                .map_memory(staging_memory, 0, data_size, vk::MemoryMapFlags::empty())
+                .map_err(|e| VkError::new(e, "vkMapMemory"))?;
+
+            let mut align: Align<f16> = Align::new(pointer, align_of::<f16>() as u64, data_size);
+            align.copy_from_slice(data);
+
+            vulkan.device().unmap_memory(staging_memory);
+        }
+
+        // Create image
+        let (image, memory) = {
+            let create_info = vk::ImageCreateInfo::default()
+                .image_type(vk::ImageType::TYPE_2D)
+                .format(vk::Format::R16G16B16A16_SFLOAT)
+                .extent(extent.into())
+                .mip_levels(1)
+                .array_layers(1)
+                .samples(vk::SampleCountFlags::TYPE_1)
+                .tiling(vk::ImageTiling::OPTIMAL)
+                .usage(
+                    vk::ImageUsageFlags::STORAGE
+                        | vk::ImageUsageFlags::SAMPLED
+                        | vk::ImageUsageFlags::TRANSFER_SRC
+                        | vk::ImageUsageFlags::TRANSFER_DST,
+                )
+                .sharing_mode(vk::SharingMode::EXCLUSIVE)
+                .initial_layout(vk::ImageLayout::UNDEFINED);
+
+            let (image, memory, _) = unsafe {
+                allocate_image(
+                    vulkan,
+                    &create_info,
+                    vk::MemoryPropertyFlags::DEVICE_LOCAL,
+                    "HDR Image",
+                )?
+            };
+
+            (image, memory)
+        };
+
+        // Copy staging to image
+        unsafe {
+            onetime_command(
+                vulkan,
+                vulkan.transient_pool(),
+                vulkan.queue(QueuePurpose::Compute),
+                |vulkan, command_buffer| {
+                    cmd_transition_image(
+                        vulkan,
+                        command_buffer,
+                        image,
+                        vk::ImageLayout::UNDEFINED,
+                        vk::ImageLayout::TRANSFER_DST_OPTIMAL,
+                    )
+                    .unwrap();
+
+                    let region = vk::BufferImageCopy::default()
+                        .buffer_image_height(extent.height)
+                        .buffer_row_length(extent.width)
+                        .buffer_offset(0)
+                        .image_extent(extent.into())
+                        .image_offset(vk::Offset3D::default())
+                        .image_subresource(
+                            vk::ImageSubresourceLayers::default()
+                                .aspect_mask(vk::ImageAspectFlags::COLOR)
+                                .base_array_layer(0)
+                                .layer_count(1)
+                                .mip_level(0),
+                        );
+
+                    vulkan.device().cmd_copy_buffer_to_image(
+                        command_buffer,
+                        staging_buffer,
+                        image,
+                        vk::ImageLayout::TRANSFER_DST_OPTIMAL,
+                        slice::from_ref(&region),
+                    );
+
+                    cmd_transition_image(
+                        vulkan,
+                        command_buffer,
+                        image,
+                        vk::ImageLayout::TRANSFER_DST_OPTIMAL,
+                        vk::ImageLayout::GENERAL,
+                    )
+                    .unwrap();
+                },
+                "Copy to HDR Image",
+            )?;
+        }
+
+        // Free staging
+        unsafe {
+            vulkan.device().destroy_buffer(staging_buffer, None);
+            vulkan.device().free_memory(staging_memory, None);
+        }
+
+        // Create image view
+        let view = unsafe {
+            let create_info = vk::ImageViewCreateInfo::default()
+                .format(vk::Format::R16G16B16A16_SFLOAT)
+                .view_type(vk::ImageViewType::TYPE_2D)
+                .image(image)
+                .subresource_range(
+                    vk::ImageSubresourceRange::default()
+                        .aspect_mask(vk::ImageAspectFlags::COLOR)
+                        .base_array_layer(0)
+                        .base_mip_level(0)
+                        .layer_count(1)
+                        .level_count(1),
+                );
+            vulkan
+                .device()
+                .create_image_view(&create_info, None)
+                .map_err(|e| VkError::new(e, "vkCreateImageView"))?
+        };
+
+        Ok(Self {
+            image,
+            memory,
+            view,
+            extent,
+        })
+    }
+
+    /// Copy the image to `RGBA` pixel data in CPU memory, the data is row-major with no padding.
+    ///
+    /// ## Image Requirements
+    /// * `layout: GENERAL`
+    /// * `usage: TRANSFER_SRC`
+    pub unsafe fn copy_to_cpu(&self, vulkan: &Vulkan) -> Result<Vec<f16>, AllocationError> {
+        let _timer = DebugTime::start("Downloading HDR image");
+
+        let values = self.extent.width as usize * self.extent.height as usize * 4;
+        let data_size = values as u64 * 2;
+
+        // Create staging
+        let (staging_buffer, staging_memory) = {
+            let buffer_info = vk::BufferCreateInfo::default()
+                .queue_family_indices(vulkan.queue_family_index_as_slice())
+                .usage(vk::BufferUsageFlags::TRANSFER_DST)
+                .size(data_size);
+
+            let (buffer, memory, _) = unsafe {
+                allocate_buffer(
+                    vulkan,
+                    &buffer_info,
+                    vk::MemoryPropertyFlags::HOST_COHERENT | vk::MemoryPropertyFlags::HOST_VISIBLE,
+                    "HDR to CPU Staging",
+                )?
+            };
+
+            (buffer, memory)
+        };
+
+        // Copy image to staging
+        unsafe {
+            onetime_command(
+                vulkan,
+                vulkan.transient_pool(),
+                vulkan.queue(QueuePurpose::Compute),
+                |vulkan, command_buffer| {
+                    let region = vk::BufferImageCopy::default()
+                        .buffer_image_height(self.extent.height)
+                        .buffer_row_length(self.extent.width)
+                        .buffer_offset(0)
+                        .image_extent(self.extent.into())
+                        .image_offset(vk::Offset3D::default())
+                        .image_subresource(
+                            vk::ImageSubresourceLayers::default()
+                                .aspect_mask(vk::ImageAspectFlags::COLOR)
+                                .base_array_layer(0)
+                                .layer_count(1)
+                                .mip_level(0),
+                        );
+
+                    vulkan.device().cmd_copy_image_to_buffer(
+                        command_buffer,
+                        self.image,
+                        vk::ImageLayout::GENERAL,
+                        staging_buffer,
+                        slice::from_ref(&region),
+                    );
+                },
+                "Copy HDR Image to Staging",
+            )?;
+        }
+
+        // Copy staging to CPU
+        let data = unsafe {
+            let pointer = vulkan
+                .device()
+                .map_memory(staging_memory, 0, data_size, vk::MemoryMapFlags::empty())
+                .map_err(|e| VkError::new(e, "vkMapMemory"))?;
+
+            let data = slice::from_raw_parts(pointer as *const f16, values).to_vec();
+
+            vulkan.device().unmap_memory(staging_memory);
+
+            data
+        };
+
+        // Free staging
+        unsafe {
+            vulkan.device().destroy_buffer(staging_buffer, None);
+            vulkan.device().free_memory(staging_memory, None);
+        }
+
+        Ok(data)
+    }
+
     /// Destroy the image.
     pub unsafe fn destroy(self, vulkan: &Vulkan) {
         unsafe {
@@ -6,13 +6,20 @@
 
 extern crate alloc;
 
+pub use annotation_compositor::{
+    AnnotationCompositor, AnnotationPass, CompositorError, Redactions, Shape as AnnotationShape,
+};
 pub use hdr_image::HdrImage;
 pub use hdr_scanner::{HdrScanner, HdrScannerError};
 pub use hdr_to_sdr_tonemapper::{HdrToSdrTonemapper, TonemapperError};
-pub use renderer::{CreationError as RendererCreationError, Renderer, State as RendererState};
+pub use renderer::{
+    CreationError as RendererCreationError, Renderer, State as RendererState,
+    Stroke as RendererStroke,
+};
 pub use sdr_image::{SdrImage, SdrImageError};
 pub use vulkan::{QueuePurpose, Vulkan, VulkanCreationError};
 
+mod annotation_compositor;
 mod hdr_image;
 mod hdr_scanner;
 mod hdr_to_sdr_tonemapper;
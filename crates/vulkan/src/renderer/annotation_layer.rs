@@ -0,0 +1,253 @@
+use alloc::sync::Arc;
+use core::slice;
+
+use ash::vk;
+use ash_helper::{VulkanContext, cmd_transition_image, onetime_command};
+
+use crate::{
+    AnnotationCompositor, AnnotationPass, CompositorError, HdrImage, QueuePurpose, Redactions,
+    Vulkan, annotation_compositor::Target,
+};
+
+use super::State;
+
+/// The fewest shapes the shape buffer holds, so it is not recreated for every new annotation.
+const MIN_SHAPE_CAPACITY: usize = 1024;
+
+/// The annotations composited over the selected area of the capture, drawn over the capture.
+pub struct AnnotationLayer {
+    vulkan: Arc<Vulkan>,
+
+    image: vk::Image,
+    memory: vk::DeviceMemory,
+    pub view: vk::ImageView,
+
+    scratch: vk::Image,
+    scratch_memory: vk::DeviceMemory,
+    scratch_view: vk::ImageView,
+
+    shape_buffer: vk::Buffer,
+    shape_memory: vk::DeviceMemory,
+    shape_capacity: usize,
+
+    /// The pixel of the capture at the top-left of the layer.
+    pub origin: [u32; 2],
+    pub extent: vk::Extent2D,
+
+    /// What the layer was last composited from, so it is only composited again when they change.
+    composited: Option<Composited>,
+}
+
+#[derive(PartialEq)]
+struct Composited {
+    capture: vk::Image,
+    whitepoint: f32,
+    redactions: Redactions,
+    annotations: Vec<AnnotationPass>,
+}
+
+impl AnnotationLayer {
+    /// Create a new layer covering an area of the capture, with room for at least `shape_count`
+    /// shapes.
+    pub unsafe fn new(
+        vulkan: Arc<Vulkan>,
+        compositor: &AnnotationCompositor,
+        origin: [u32; 2],
+        extent: vk::Extent2D,
+        shape_count: usize,
+    ) -> Result<Self, CompositorError> {
+        let (image, memory) = unsafe {
+            compositor.create_image(
+                extent,
+                vk::ImageUsageFlags::STORAGE | vk::ImageUsageFlags::SAMPLED,
+                "Annotation Layer",
+            )?
+        };
+        let view = unsafe { compositor.create_view(image)? };
+
+        let (scratch, scratch_memory) = unsafe {
+            compositor.create_image(
+                extent,
+                vk::ImageUsageFlags::STORAGE,
+                "Annotation Layer Blur Image",
+            )?
+        };
+        let scratch_view = unsafe { compositor.create_view(scratch)? };
+
+        let shape_capacity = shape_count.next_power_of_two().max(MIN_SHAPE_CAPACITY);
+        let (shape_buffer, shape_memory) = unsafe {
+            compositor.create_shape_buffer(shape_capacity, "Annotation Layer Shape Buffer")?
+        };
+
+        unsafe {
+            onetime_command(
+                vulkan.as_ref(),
+                vulkan.transient_pool(),
+                vulkan.queue(QueuePurpose::Graphics),
+                |vulkan, command_buffer| {
+                    for image in [image, scratch] {
+                        cmd_transition_image(
+                            vulkan,
+                            command_buffer,
+                            image,
+                            vk::ImageLayout::UNDEFINED,
+                            vk::ImageLayout::GENERAL,
+                        )
+                        .unwrap();
+                    }
+                },
+                "Transition Annotation Layer",
+            )?;
+        }
+
+        Ok(Self {
+            vulkan,
+
+            image,
+            memory,
+            view,
+
+            scratch,
+            scratch_memory,
+            scratch_view,
+
+            shape_buffer,
+            shape_memory,
+            shape_capacity,
+
+            origin,
+            extent,
+
+            composited: None,
+        })
+    }
+
+    /// Returns the area (origin, extent) of the capture that the layer covers for a state, `None`
+    /// if there is nothing to composite.
+    pub fn area(state: &State, capture: HdrImage) -> Option<([u32; 2], vk::Extent2D)> {
+        if state.annotations.is_empty() && state.redactions.is_empty() {
+            return None;
+        }
+
+        let [start, end] = state.selection;
+        let size = [capture.extent.width, capture.extent.height];
+
+        let [left, top] = [0, 1].map(|axis| start[axis].min(end[axis]).floor().max(0.0) as u32);
+        let [right, bottom] =
+            [0, 1].map(|axis| (start[axis].max(end[axis]).ceil().max(0.0) as u32).min(size[axis]));
+
+        if left >= right || top >= bottom {
+            return None;
+        }
+
+        let extent = vk::Extent2D::default()
+            .width(right - left)
+            .height(bottom - top);
+
+        Some(([left, top], extent))
+    }
+
+    /// Forgets what the layer was composited from, so it is composited again when it is next
+    /// used.
+    pub fn invalidate(&mut self) {
+        self.composited = None;
+    }
+
+    /// If the layer covers an area and has room for `shape_count` shapes.
+    pub fn fits(&self, origin: [u32; 2], extent: vk::Extent2D, shape_count: usize) -> bool {
+        self.origin == origin && self.extent == extent && shape_count <= self.shape_capacity
+    }
+
+    /// Records the commands to composite the annotations into the layer, if they have changed
+    /// since they were last composited.
+    pub unsafe fn cmd_composite(
+        &mut self,
+        compositor: &AnnotationCompositor,
+        command_buffer: vk::CommandBuffer,
+        capture: HdrImage,
+        state: &State,
+    ) {
+        let composited = Composited {
+            capture: capture.image,
+            whitepoint: state.whitepoint,
+            redactions: state.redactions.clone(),
+            annotations: state.annotations.clone(),
+        };
+
+        if self.composited.as_ref() == Some(&composited) {
+            return;
+        }
+
+        let device = unsafe { self.vulkan.device() };
+
+        // Wait for previous frames to finish reading the layer.
+        unsafe {
+            device.cmd_pipeline_barrier(
+                command_buffer,
+                vk::PipelineStageFlags::FRAGMENT_SHADER,
+                vk::PipelineStageFlags::COMPUTE_SHADER,
+                vk::DependencyFlags::empty(),
+                &[],
+                &[],
+                &[],
+            );
+        }
+
+        let target = Target {
+            view: self.view,
+            scratch_view: self.scratch_view,
+            shape_buffer: self.shape_buffer,
+            capture,
+            origin: self.origin,
+            area: [[0, 0], [self.extent.width, self.extent.height]],
+        };
+
+        unsafe {
+            compositor.cmd_composite(
+                command_buffer,
+                &target,
+                Some(state.whitepoint),
+                &state.redactions,
+                &state.annotations,
+            );
+        }
+
+        // Wait for the layer to be composited before it is drawn.
+        let memory_barrier = vk::MemoryBarrier::default()
+            .src_access_mask(vk::AccessFlags::SHADER_WRITE)
+            .dst_access_mask(vk::AccessFlags::SHADER_READ);
+
+        unsafe {
+            device.cmd_pipeline_barrier(
+                command_buffer,
+                vk::PipelineStageFlags::COMPUTE_SHADER,
+                vk::PipelineStageFlags::FRAGMENT_SHADER,
+                vk::DependencyFlags::empty(),
+                slice::from_ref(&memory_barrier),
+                &[],
+                &[],
+            );
+        }
+
+        self.composited = Some(composited);
+    }
+}
+
+impl Drop for AnnotationLayer {
+    fn drop(&mut self) {
+        unsafe {
+            let device = self.vulkan.device();
+
+            device.destroy_buffer(self.shape_buffer, None);
+            device.free_memory(self.shape_memory, None);
+
+            device.destroy_image_view(self.scratch_view, None);
+            device.destroy_image(self.scratch, None);
+            device.free_memory(self.scratch_memory, None);
+
+            device.destroy_image_view(self.view, None);
+            device.destroy_image(self.image, None);
+            device.free_memory(self.memory, None);
+        }
+    }
+}
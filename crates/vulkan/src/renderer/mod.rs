@@ -1,16 +1,18 @@
 use alloc::sync::Arc;
+use annotation_layer::AnnotationLayer;
 use ash_helper::{
     AllocationError, Swapchain, SwapchainPreferences, SwapchainRetirement, VkError, VulkanContext,
 };
 use buffer::RenderBuffer;
 use context::Surface;
 use parking_lot::Mutex;
-use pipelines::{CapturePipeline, LinePipeline, SelectionPipeline};
+use pipelines::{AnnotationPipeline, CapturePipeline, LinePipeline, SelectionPipeline};
 use thiserror::Error;
 use tracing::error;
 
-use crate::{HdrImage, Vulkan};
+use crate::{AnnotationCompositor, AnnotationPass, CompositorError, HdrImage, Redactions, Vulkan};
 
+mod annotation_layer;
 mod buffer;
 mod context;
 mod new;
@@ -18,7 +20,7 @@ mod pipelines;
 mod render;
 
 /// The dynamic state for the renderer, updates are provided externally.
-#[derive(Default, Clone, Copy)]
+#[derive(Default, Clone)]
 pub struct State {
     /// The whitepoint for previewing the tonemap.
     pub whitepoint: f32,
@@ -35,6 +37,32 @@ pub struct State {
 
     /// The position of the user's mouse relative to the top-left corner of the window.
     pub mouse_position: [f32; 2],
+
+    /// The points of the freeform selection, relative to the top-left corner of the window. Empty
+    /// if the selection is a rectangle.
+    pub lasso: Vec<[f32; 2]>,
+
+    /// The outline of the annotation the user is drawing.
+    pub strokes: Vec<Stroke>,
+
+    /// The passes that draw the finished annotations over the selection.
+    pub annotations: Vec<AnnotationPass>,
+
+    /// The redactions to preview over the selection.
+    pub redactions: Redactions,
+}
+
+/// A polyline drawn over the capture, points are relative to the top-left corner of the window.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Stroke {
+    /// The points of the polyline.
+    pub points: Vec<[f32; 2]>,
+
+    /// The width of the line in pixels.
+    pub width: f32,
+
+    /// The `RGBA` colour of the line.
+    pub colour: [f32; 4],
 }
 
 /// The renderer for HDR Snipping Tool.
@@ -51,6 +79,10 @@ pub struct Renderer {
     line_shader: LinePipeline,
     selection_shader: SelectionPipeline,
     capture_shader: CapturePipeline,
+    annotation_shader: AnnotationPipeline,
+
+    annotation_compositor: AnnotationCompositor,
+    annotation_layer: Option<AnnotationLayer>,
 
     /// The dynamic state for the renderer, expected to be written to by the main window thread
     /// and read from the render thread.
@@ -75,6 +107,10 @@ pub enum CreationError {
     /// A Vulkan call returned an error.
     #[error(transparent)]
     VkError(#[from] VkError),
+
+    /// Creating the annotation compositor failed.
+    #[error(transparent)]
+    CompositorError(#[from] CompositorError),
 }
 
 impl Drop for Renderer {
@@ -6,13 +6,13 @@ use parking_lot::Mutex;
 use raw_window_handle::{RawDisplayHandle, RawWindowHandle};
 use tracing::debug;
 
-use crate::Vulkan;
+use crate::{AnnotationCompositor, Vulkan};
 
 use super::{
     CreationError, Renderer, State,
     buffer::RenderBuffer,
     context::Surface,
-    pipelines::{CapturePipeline, LinePipeline, SelectionPipeline},
+    pipelines::{AnnotationPipeline, CapturePipeline, LinePipeline, SelectionPipeline},
 };
 
 impl Renderer {
@@ -57,6 +57,11 @@ impl Renderer {
         let line_shader = unsafe { LinePipeline::new(Arc::clone(&vulkan))? };
         let selection_shader = unsafe { SelectionPipeline::new(Arc::clone(&vulkan))? };
         let capture_shader = unsafe { CapturePipeline::new(Arc::clone(&vulkan))? };
+        let annotation_shader = unsafe { AnnotationPipeline::new(Arc::clone(&vulkan))? };
+
+        // Create the compositor for the annotations, the layer is created once there are
+        // annotations.
+        let annotation_compositor = AnnotationCompositor::new(Arc::clone(&vulkan))?;
 
         Ok(Self {
             vulkan,
@@ -67,6 +72,10 @@ impl Renderer {
             line_shader,
             selection_shader,
             capture_shader,
+            annotation_shader,
+
+            annotation_compositor,
+            annotation_layer: None,
 
             swapchain,
             swapchain_preferences,
@@ -0,0 +1,234 @@
+use alloc::sync::Arc;
+use core::slice;
+
+use ash::{ext, khr, vk};
+use ash_helper::{
+    Context, LabelledVkResult, Swapchain, VkError, VulkanContext, link_shader_objects, try_name,
+    try_name_all,
+};
+use bytemuck::bytes_of;
+
+use crate::{
+    RendererState, Vulkan,
+    renderer::annotation_layer::AnnotationLayer,
+    shaders::render_annotations::{self, PushConstants},
+};
+
+pub struct AnnotationPipeline {
+    vulkan: Arc<Vulkan>,
+
+    pub descriptor_layouts: Vec<vk::DescriptorSetLayout>,
+    pub pipeline_layout: vk::PipelineLayout,
+    pub shaders: Vec<vk::ShaderEXT>,
+    pub stages: Vec<vk::ShaderStageFlags>,
+}
+
+impl AnnotationPipeline {
+    /// The number of vertices in the quad covering the layer, triangle strip.
+    pub const VERTEX_COUNT: u32 = 4;
+
+    /// Create a new instance of the pipeline.
+    pub unsafe fn new(vulkan: Arc<Vulkan>) -> LabelledVkResult<Self> {
+        let descriptor_layouts = {
+            let layouts = unsafe {
+                render_annotations::set_layouts(
+                    vulkan.device(),
+                    vk::DescriptorSetLayoutCreateFlags::PUSH_DESCRIPTOR_KHR,
+                )
+                .map_err(|e| VkError::new(e, "vkCreateDescriptorSetLayout"))?
+            };
+
+            unsafe {
+                try_name_all(
+                    vulkan.as_ref(),
+                    &layouts,
+                    "AnnotationPipeline Descriptor Layout",
+                )
+            };
+
+            layouts
+        };
+
+        let pipeline_layout = {
+            let push_range = PushConstants::push_constant_range();
+
+            let create_info = vk::PipelineLayoutCreateInfo::default()
+                .set_layouts(&descriptor_layouts)
+                .push_constant_ranges(slice::from_ref(&push_range));
+
+            let layout = unsafe { vulkan.device().create_pipeline_layout(&create_info, None) }
+                .map_err(|e| VkError::new(e, "vkCreatePiplineLayout"))?;
+
+            unsafe {
+                try_name(
+                    vulkan.as_ref(),
+                    layout,
+                    "AnnotationPipeline Pipeline Layout",
+                )
+            };
+
+            layout
+        };
+
+        let (shaders, stages) = {
+            let push_range = PushConstants::push_constant_range();
+
+            let vertex_create_info = vk::ShaderCreateInfoEXT::default()
+                .code(render_annotations::BYTES)
+                .code_type(vk::ShaderCodeTypeEXT::SPIRV)
+                .stage(render_annotations::vertex_main::STAGE)
+                .name(render_annotations::vertex_main::ENTRY_POINT)
+                .set_layouts(&descriptor_layouts)
+                .push_constant_ranges(slice::from_ref(&push_range));
+
+            let fragment_create_info = vk::ShaderCreateInfoEXT::default()
+                .code(render_annotations::BYTES)
+                .code_type(vk::ShaderCodeTypeEXT::SPIRV)
+                .stage(render_annotations::fragment_main::STAGE)
+                .name(render_annotations::fragment_main::ENTRY_POINT)
+                .set_layouts(&descriptor_layouts)
+                .push_constant_ranges(slice::from_ref(&push_range));
+
+            let mut create_infos = [vertex_create_info, fragment_create_info];
+            let stages: Vec<_> = create_infos.iter().map(|info| info.stage).collect();
+
+            let shaders = unsafe {
+                link_shader_objects(
+                    vulkan.as_ref(),
+                    &mut create_infos,
+                    "Annotation Pipeline Shader",
+                )
+                .map_err(|e| VkError::new(e, "vkCreateShadersEXT"))?
+            };
+
+            (shaders, stages)
+        };
+
+        Ok(Self {
+            vulkan,
+            descriptor_layouts,
+            pipeline_layout,
+            shaders,
+            stages,
+        })
+    }
+
+    /// Draws the annotation layer over the area of the capture it covers.
+    pub unsafe fn cmd_draw(
+        &self,
+        command_buffer: vk::CommandBuffer,
+        swapchain: &Swapchain,
+        layer: &AnnotationLayer,
+        state: &RendererState,
+    ) {
+        unsafe { self.cmd_set_state(command_buffer) };
+
+        let shader_device: &ext::shader_object::Device = unsafe { self.vulkan.context() };
+
+        // Bind shaders
+        unsafe {
+            shader_device.cmd_bind_shaders(command_buffer, &self.stages, &self.shaders);
+        }
+
+        // Push descriptors
+        {
+            let image_info = vk::DescriptorImageInfo::default()
+                .image_layout(vk::ImageLayout::GENERAL)
+                .image_view(layer.view)
+                .sampler(vk::Sampler::null());
+
+            let descriptor_write = vk::WriteDescriptorSet::default()
+                .dst_binding(0)
+                .descriptor_count(1)
+                .descriptor_type(vk::DescriptorType::SAMPLED_IMAGE)
+                .image_info(slice::from_ref(&image_info));
+
+            unsafe {
+                let device: &khr::push_descriptor::Device = self.vulkan.context();
+                device.cmd_push_descriptor_set(
+                    command_buffer,
+                    vk::PipelineBindPoint::GRAPHICS,
+                    self.pipeline_layout,
+                    0,
+                    slice::from_ref(&descriptor_write),
+                );
+            }
+        }
+
+        // Push constants
+        unsafe {
+            let surface_format = swapchain.info.format;
+            let present_srgb =
+                if surface_format.color_space == vk::ColorSpaceKHR::EXTENDED_SRGB_LINEAR_EXT {
+                    vk::FALSE
+                } else {
+                    vk::TRUE
+                };
+
+            let start = layer.origin.map(|axis| axis as f32);
+            let end = [
+                start[0] + layer.extent.width as f32,
+                start[1] + layer.extent.height as f32,
+            ];
+
+            let push_constants = PushConstants {
+                start: swapchain.screen_to_vulkan_space(start),
+                end: swapchain.screen_to_vulkan_space(end),
+                max_brightness: state.max_brightness,
+                present_srgb,
+            };
+
+            self.vulkan.device().cmd_push_constants(
+                command_buffer,
+                self.pipeline_layout,
+                PushConstants::STAGES,
+                0,
+                bytes_of(&push_constants),
+            );
+        }
+
+        // Draw
+        unsafe {
+            self.vulkan
+                .device()
+                .cmd_draw(command_buffer, Self::VERTEX_COUNT, 1, 0, 0);
+        }
+    }
+
+    pub unsafe fn cmd_set_state(&self, command_buffer: vk::CommandBuffer) {
+        let shader_device: &ext::shader_object::Device = unsafe { self.vulkan.context() };
+
+        // The quad is built from the vertex index.
+        unsafe {
+            shader_device.cmd_set_vertex_input(command_buffer, &[], &[]);
+        }
+
+        unsafe {
+            shader_device
+                .cmd_set_primitive_topology(command_buffer, vk::PrimitiveTopology::TRIANGLE_STRIP);
+            shader_device.cmd_set_polygon_mode(command_buffer, vk::PolygonMode::FILL);
+        }
+    }
+}
+
+impl Drop for AnnotationPipeline {
+    fn drop(&mut self) {
+        unsafe {
+            let shader_device: &ext::shader_object::Device = self.vulkan.context();
+
+            self.shaders
+                .iter()
+                .for_each(|shader| shader_device.destroy_shader(*shader, None));
+
+            self.vulkan
+                .device()
+                .destroy_pipeline_layout(self.pipeline_layout, None);
+
+            self.descriptor_layouts.iter().for_each(|layout| {
+                self.vulkan
+                    .device()
+                    .destroy_descriptor_set_layout(*layout, None);
+            });
+        }
+    }
+}
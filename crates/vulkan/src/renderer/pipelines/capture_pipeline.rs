@@ -143,7 +143,7 @@ impl CapturePipeline {
         command_buffer: vk::CommandBuffer,
         surface_format: vk::SurfaceFormatKHR,
         render_buffer: &RenderBuffer,
-        state: RendererState,
+        state: &RendererState,
     ) {
         let Some(capture) = state.capture else { return };
 
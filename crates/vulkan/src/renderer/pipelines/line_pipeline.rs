@@ -106,7 +106,7 @@ impl LinePipeline {
     pub unsafe fn cmd_draw_border(
         &self,
         command_buffer: vk::CommandBuffer,
-        state: RendererState,
+        state: &RendererState,
         swapchain: &Swapchain,
     ) {
         let border_width = 4.0;
@@ -154,10 +154,55 @@ impl LinePipeline {
         }
     }
 
+    pub unsafe fn cmd_draw_lasso(
+        &self,
+        command_buffer: vk::CommandBuffer,
+        state: &RendererState,
+        swapchain: &Swapchain,
+    ) {
+        let lasso_width = 2.0;
+        let lasso_colour = [1.0, 1.0, 1.0, 1.0];
+
+        // Close the path by connecting the last point back to the first.
+        let lines: Vec<_> = state
+            .lasso
+            .iter()
+            .zip(state.lasso.iter().cycle().skip(1))
+            .map(|(start, end)| Line {
+                start: swapchain.screen_to_vulkan_space(*start),
+                end: swapchain.screen_to_vulkan_space(*end),
+                colour: lasso_colour,
+            })
+            .collect();
+
+        unsafe { self.cmd_draw(command_buffer, lasso_width, &lines) };
+    }
+
+    pub unsafe fn cmd_draw_strokes(
+        &self,
+        command_buffer: vk::CommandBuffer,
+        state: &RendererState,
+        swapchain: &Swapchain,
+    ) {
+        for stroke in &state.strokes {
+            let lines: Vec<_> = stroke
+                .points
+                .windows(2)
+                .map(|segment| Line {
+                    start: swapchain.screen_to_vulkan_space(segment[0]),
+                    end: swapchain.screen_to_vulkan_space(segment[1]),
+                    colour: stroke.colour,
+                })
+                .collect();
+
+            unsafe { self.cmd_draw(command_buffer, stroke.width, &lines) };
+        }
+    }
+
     pub unsafe fn cmd_draw_guides(
         &self,
         command_buffer: vk::CommandBuffer,
-        state: RendererState,
+        state: &RendererState,
         swapchain: &Swapchain,
     ) {
         let guide_colour = [0.5, 0.5, 0.5, 0.25];
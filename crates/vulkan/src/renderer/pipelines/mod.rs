@@ -1,7 +1,9 @@
+pub use annotation_pipeline::AnnotationPipeline;
 pub use capture_pipeline::CapturePipeline;
 pub use line_pipeline::LinePipeline;
 pub use selection_pipeline::SelectionPipeline;
 
+pub mod annotation_pipeline;
 pub mod capture_pipeline;
 pub mod line_pipeline;
 pub mod selection_pipeline;
@@ -156,7 +156,7 @@ impl SelectionPipeline {
         command_buffer: vk::CommandBuffer,
         swapchain: &Swapchain,
         render_buffer: &RenderBuffer,
-        state: RendererState,
+        state: &RendererState,
     ) {
         unsafe { self.cmd_set_state(command_buffer) };
 
@@ -1,3 +1,4 @@
+use alloc::sync::Arc;
 use core::slice;
 
 use ash::{ext, vk};
@@ -6,12 +7,12 @@ use ash_helper::{
     cmd_transition_image, cmd_try_begin_label, cmd_try_end_label, onetime_command,
     queue_try_begin_label, queue_try_end_label,
 };
-use tracing::debug;
+use tracing::{debug, error};
 use utilities::DebugTime;
 
-use crate::QueuePurpose;
+use crate::{AnnotationCompositor, QueuePurpose};
 
-use super::Renderer;
+use super::{Renderer, State, annotation_layer::AnnotationLayer};
 
 impl Renderer {
     /// Render a frame.
@@ -104,6 +105,8 @@ impl Renderer {
             command_buffer,
         } = resources;
 
+        let state = self.state.lock().clone();
+
         // Commands
         {
             // Reset command pool
@@ -131,6 +134,10 @@ impl Renderer {
                 };
             }
 
+            // Composite the annotations
+            let has_annotation_layer =
+                unsafe { self.cmd_composite_annotations(command_buffer, &state) };
+
             // Start rendering
             {
                 // Transition swapchain image from present to colour attachment
@@ -229,30 +236,53 @@ impl Renderer {
 
             // Draw
             {
-                let state = *self.state.lock();
                 unsafe {
                     self.capture_shader.cmd_draw(
                         command_buffer,
                         self.swapchain.info.format,
                         &self.render_buffer,
-                        state,
+                        &state,
                     )
                 };
+                if let Some(layer) = self
+                    .annotation_layer
+                    .as_ref()
+                    .filter(|_| has_annotation_layer)
+                {
+                    unsafe {
+                        self.annotation_shader.cmd_draw(
+                            command_buffer,
+                            &self.swapchain,
+                            layer,
+                            &state,
+                        )
+                    };
+                }
                 unsafe {
                     self.selection_shader.cmd_draw(
                         command_buffer,
                         &self.swapchain,
                         &self.render_buffer,
-                        state,
+                        &state,
                     );
                 };
                 unsafe {
                     self.line_shader
                         .cmd_setup_draw(command_buffer, &self.render_buffer);
+
                     self.line_shader
-                        .cmd_draw_border(command_buffer, state, &self.swapchain);
+                        .cmd_draw_strokes(command_buffer, &state, &self.swapchain);
+
+                    if state.lasso.is_empty() {
+                        self.line_shader
+                            .cmd_draw_border(command_buffer, &state, &self.swapchain);
+                    } else {
+                        self.line_shader
+                            .cmd_draw_lasso(command_buffer, &state, &self.swapchain);
+                    }
+
                     self.line_shader
-                        .cmd_draw_guides(command_buffer, state, &self.swapchain);
+                        .cmd_draw_guides(command_buffer, &state, &self.swapchain);
                 }
             }
 
@@ -324,4 +354,66 @@ impl Renderer {
 
         Ok(())
     }
+
+    /// Records the commands to composite the annotations into the annotation layer, returns if
+    /// the layer should be drawn.
+    unsafe fn cmd_composite_annotations(
+        &mut self,
+        command_buffer: vk::CommandBuffer,
+        state: &State,
+    ) -> bool {
+        let area = state
+            .capture
+            .and_then(|capture| AnnotationLayer::area(state, capture).map(|area| (capture, area)));
+
+        let Some((capture, (origin, extent))) = area else {
+            if let Some(layer) = self.annotation_layer.as_mut() {
+                layer.invalidate();
+            }
+            return false;
+        };
+
+        let shape_count = AnnotationCompositor::shape_count(&state.redactions, &state.annotations);
+
+        let fits = self
+            .annotation_layer
+            .as_ref()
+            .is_some_and(|layer| layer.fits(origin, extent, shape_count));
+
+        if !fits {
+            // Previous frames may still be reading the layer.
+            if let Some(layer) = self.annotation_layer.take() {
+                let _queue_guards = unsafe { self.vulkan.device_wait_idle() };
+                drop(layer);
+            }
+
+            let layer = unsafe {
+                AnnotationLayer::new(
+                    Arc::clone(&self.vulkan),
+                    &self.annotation_compositor,
+                    origin,
+                    extent,
+                    shape_count,
+                )
+            };
+
+            match layer {
+                Ok(layer) => self.annotation_layer = Some(layer),
+                Err(e) => {
+                    error!("Failed to create the annotation layer: {e}");
+                    return false;
+                }
+            }
+        }
+
+        let Some(layer) = self.annotation_layer.as_mut() else {
+            return false;
+        };
+
+        unsafe {
+            layer.cmd_composite(&self.annotation_compositor, command_buffer, capture, state);
+        }
+
+        true
+    }
 }
@@ -1,5 +1,90 @@
 #![doc = "Generated by rspirv-bindgen v0.2.0"]
 #![allow(unused)]
+pub mod composite_annotations {
+    pub mod compute_main {
+        pub const ENTRY_POINT: &core::ffi::CStr = c"main";
+        pub const STAGE: ash::vk::ShaderStageFlags = ash::vk::ShaderStageFlags::COMPUTE;
+        pub const DISPATCH_SIZE: [u32; 3] = [8u32, 8u32, 1u32];
+    }
+    #[repr(C)]
+    #[derive(Clone, Copy, Debug, Default, bytemuck :: Zeroable, bytemuck :: Pod)]
+    pub struct Shape {
+        pub start: [f32; 2usize],
+        pub end: [f32; 2usize],
+        pub radius: f32,
+        pub kind: u32,
+    }
+    #[repr(C)]
+    #[derive(Clone, Copy, Debug, Default, bytemuck :: Zeroable, bytemuck :: Pod)]
+    pub struct PushConstants {
+        pub colour: [f32; 4usize],
+        pub origin: [u32; 2usize],
+        pub area_start: [u32; 2usize],
+        pub area_end: [u32; 2usize],
+        pub mode: u32,
+        pub shape_start: u32,
+        pub shape_count: u32,
+        pub size: u32,
+        pub fill_start: u32,
+        pub fill_count: u32,
+        pub whitepoint: f32,
+    }
+    impl PushConstants {
+        pub const STAGES: ash::vk::ShaderStageFlags = ash::vk::ShaderStageFlags::COMPUTE;
+        pub fn push_constant_range() -> ash::vk::PushConstantRange {
+            ash::vk::PushConstantRange::default()
+                .offset(0)
+                .size(68u32)
+                .stage_flags(ash::vk::ShaderStageFlags::COMPUTE)
+        }
+    }
+    pub unsafe fn set_layouts(
+        device: &ash::Device,
+        flags: ash::vk::DescriptorSetLayoutCreateFlags,
+    ) -> Result<Vec<ash::vk::DescriptorSetLayout>, ash::vk::Result> {
+        let set_0 = {
+            let bindings = [
+                ash::vk::DescriptorSetLayoutBinding::default()
+                    .binding(0u32)
+                    .descriptor_type(ash::vk::DescriptorType::STORAGE_IMAGE)
+                    .descriptor_count(1)
+                    .stage_flags(ash::vk::ShaderStageFlags::COMPUTE),
+                ash::vk::DescriptorSetLayoutBinding::default()
+                    .binding(1u32)
+                    .descriptor_type(ash::vk::DescriptorType::STORAGE_IMAGE)
+                    .descriptor_count(1)
+                    .stage_flags(ash::vk::ShaderStageFlags::COMPUTE),
+                ash::vk::DescriptorSetLayoutBinding::default()
+                    .binding(2u32)
+                    .descriptor_type(ash::vk::DescriptorType::STORAGE_BUFFER)
+                    .descriptor_count(1)
+                    .stage_flags(ash::vk::ShaderStageFlags::COMPUTE),
+                ash::vk::DescriptorSetLayoutBinding::default()
+                    .binding(3u32)
+                    .descriptor_type(ash::vk::DescriptorType::STORAGE_IMAGE)
+                    .descriptor_count(1)
+                    .stage_flags(ash::vk::ShaderStageFlags::COMPUTE),
+            ];
+            let layout_info = ash::vk::DescriptorSetLayoutCreateInfo::default()
+                .bindings(&bindings)
+                .flags(flags);
+            match unsafe { device.create_descriptor_set_layout(&layout_info, None) } {
+                Ok(set) => set,
+                Err(error) => {
+                    return Err(error);
+                }
+            }
+        };
+        Ok(vec![set_0])
+    }
+    pub const BYTES: &[u8] = {
+        #[repr(C, align(4))]
+        struct Aligned<T: ?Sized>(T);
+        const ALIGNED_DATA: &Aligned<[u8]> =
+            &Aligned(*include_bytes!("spv/composite_annotations.spv"));
+        &ALIGNED_DATA.0
+    };
+}
 pub mod maximum_reduction {
     pub mod compute_main {
         pub const ENTRY_POINT: &core::ffi::CStr = c"main";
@@ -42,6 +127,62 @@ pub mod maximum_reduction {
         &ALIGNED_DATA.0
     };
 }
+pub mod render_annotations {
+    pub mod vertex_main {
+        pub const ENTRY_POINT: &core::ffi::CStr = c"vertexMain";
+        pub const STAGE: ash::vk::ShaderStageFlags = ash::vk::ShaderStageFlags::VERTEX;
+    }
+    pub mod fragment_main {
+        pub const ENTRY_POINT: &core::ffi::CStr = c"fragmentMain";
+        pub const STAGE: ash::vk::ShaderStageFlags = ash::vk::ShaderStageFlags::FRAGMENT;
+    }
+    #[repr(C)]
+    #[derive(Clone, Copy, Debug, Default, bytemuck :: Zeroable, bytemuck :: Pod)]
+    pub struct PushConstants {
+        pub start: [f32; 2usize],
+        pub end: [f32; 2usize],
+        pub max_brightness: f32,
+        pub present_srgb: u32,
+    }
+    impl PushConstants {
+        pub const STAGES: ash::vk::ShaderStageFlags = ash::vk::ShaderStageFlags::from_raw(17u32);
+        pub fn push_constant_range() -> ash::vk::PushConstantRange {
+            ash::vk::PushConstantRange::default()
+                .offset(0)
+                .size(24u32)
+                .stage_flags(ash::vk::ShaderStageFlags::from_raw(17u32))
+        }
+    }
+    pub unsafe fn set_layouts(
+        device: &ash::Device,
+        flags: ash::vk::DescriptorSetLayoutCreateFlags,
+    ) -> Result<Vec<ash::vk::DescriptorSetLayout>, ash::vk::Result> {
+        let set_0 = {
+            let bindings = [ash::vk::DescriptorSetLayoutBinding::default()
+                .binding(0u32)
+                .descriptor_type(ash::vk::DescriptorType::SAMPLED_IMAGE)
+                .descriptor_count(1)
+                .stage_flags(ash::vk::ShaderStageFlags::FRAGMENT)];
+            let layout_info = ash::vk::DescriptorSetLayoutCreateInfo::default()
+                .bindings(&bindings)
+                .flags(flags);
+            match unsafe { device.create_descriptor_set_layout(&layout_info, None) } {
+                Ok(set) => set,
+                Err(error) => {
+                    return Err(error);
+                }
+            }
+        };
+        Ok(vec![set_0])
+    }
+    pub const BYTES: &[u8] = {
+        #[repr(C, align(4))]
+        struct Aligned<T: ?Sized>(T);
+        const ALIGNED_DATA: &Aligned<[u8]> =
+            &Aligned(*include_bytes!("spv/render_annotations.spv"));
+        &ALIGNED_DATA.0
+    };
+}
 pub mod render_capture {
     pub mod vertex_main {
         pub const ENTRY_POINT: &core::ffi::CStr = c"vertexMain";
@@ -4,6 +4,7 @@ extern crate alloc;
 
 use alloc::sync::Arc;
 
+use half::f16;
 use vulkan::{HdrImage, HdrScanner, Vulkan};
 use windows_capture_provider::{CaptureItemCache, DirectX, Monitor, WindowsCapture};
 
@@ -41,3 +42,29 @@ fn import_hdr_image() {
     unsafe { hdr_image.destroy(&vulkan) };
     unsafe { resources.destroy(&direct_x).unwrap() };
 }
+
+#[test]
+fn cpu_round_trip() {
+    let vulkan = Arc::new(
+        Vulkan::new(
+            true,
+            std::env::current_exe().unwrap().parent().unwrap(),
+            None,
+        )
+        .unwrap(),
+    );
+
+    let size = [67, 31];
+    let data: Vec<_> = (0..size[0] * size[1] * 4)
+        .map(|index| f16::from_f32(index as f32 / 64.0))
+        .collect();
+
+    let hdr_image = unsafe { HdrImage::from_cpu(&vulkan, size, &data).unwrap() };
+    assert_eq!(hdr_image.extent.width, size[0]);
+    assert_eq!(hdr_image.extent.height, size[1]);
+
+    let downloaded = unsafe { hdr_image.copy_to_cpu(&vulkan).unwrap() };
+    assert_eq!(downloaded, data);
+
+    unsafe { hdr_image.destroy(&vulkan) };
+}
@@ -8,16 +8,24 @@ use windows::{
         DirectX::DirectXPixelFormat,
     },
     Win32::{
+        Foundation::{HANDLE, RECT},
         Graphics::{
-            Direct3D11::ID3D11Texture2D,
-            Dxgi::{DXGI_SHARED_RESOURCE_READ, DXGI_SHARED_RESOURCE_WRITE, IDXGIResource1},
+            Direct3D11::{
+                D3D11_BIND_SHADER_RESOURCE, D3D11_BOX, D3D11_RESOURCE_MISC_SHARED,
+                D3D11_RESOURCE_MISC_SHARED_NTHANDLE, D3D11_TEXTURE2D_DESC, D3D11_USAGE_DEFAULT,
+                ID3D11Texture2D,
+            },
+            Dxgi::{
+                Common::{DXGI_FORMAT_R16G16B16A16_FLOAT, DXGI_SAMPLE_DESC},
+                DXGI_SHARED_RESOURCE_READ, DXGI_SHARED_RESOURCE_WRITE, IDXGIResource1,
+            },
         },
         System::WinRT::Direct3D11::IDirect3DDxgiInterfaceAccess,
     },
 };
 use windows_core::{IInspectable, Interface};
 
-use crate::{DirectX, LabelledWinResult, SendHANDLE, WinError};
+use crate::{DirectX, LabelledWinResult, SendHANDLE, WinError, Window};
 
 /// A capture from Windows in R16G16B16A16_Float format
 #[derive(Debug, Clone, Copy)]
@@ -34,6 +42,7 @@ pub struct WindowsCapture {
 pub struct WindowsCaptureResources {
     frame: Direct3D11CaptureFrame,
     framepool: Direct3D11CaptureFramePool,
+    cropped_texture: Option<ID3D11Texture2D>,
 }
 
 impl WindowsCapture {
@@ -42,6 +51,115 @@ impl WindowsCapture {
         direct_x: &DirectX,
         capture_item: &GraphicsCaptureItem,
     ) -> LabelledWinResult<(Self, WindowsCaptureResources)> {
+        let (texture, size, resources) = Self::take_frame(direct_x, capture_item)?;
+        let handle = unsafe { Self::create_shared_handle(&texture)? };
+
+        Ok((
+            Self {
+                handle: SendHANDLE(handle),
+                size,
+            },
+            resources,
+        ))
+    }
+
+    /// Take and retrieve a WindowsCapture of a window from its capture item, cropped to the
+    /// window's visible bounds so the resize borders and drop shadow are removed.
+    pub fn take_window_capture(
+        direct_x: &DirectX,
+        capture_item: &GraphicsCaptureItem,
+        window: &Window,
+    ) -> LabelledWinResult<(Self, WindowsCaptureResources)> {
+        let (texture, frame_size, mut resources) = Self::take_frame(direct_x, capture_item)?;
+
+        // The window may have been resized since its bounds were retrieved.
+        let crop = window.crop();
+        let crop = RECT {
+            left: crop.left.clamp(0, frame_size[0] as i32),
+            top: crop.top.clamp(0, frame_size[1] as i32),
+            right: crop.right.clamp(0, frame_size[0] as i32),
+            bottom: crop.bottom.clamp(0, frame_size[1] as i32),
+        };
+        let size = [
+            crop.left.abs_diff(crop.right),
+            crop.top.abs_diff(crop.bottom),
+        ];
+
+        // Copy the visible area into a new texture
+        let cropped_texture = {
+            let descriptor = D3D11_TEXTURE2D_DESC {
+                Width: size[0],
+                Height: size[1],
+                MipLevels: 1,
+                ArraySize: 1,
+                Format: DXGI_FORMAT_R16G16B16A16_FLOAT,
+                SampleDesc: DXGI_SAMPLE_DESC {
+                    Count: 1,
+                    Quality: 0,
+                },
+                Usage: D3D11_USAGE_DEFAULT,
+                BindFlags: D3D11_BIND_SHADER_RESOURCE.0 as u32,
+                CPUAccessFlags: 0,
+                MiscFlags: (D3D11_RESOURCE_MISC_SHARED | D3D11_RESOURCE_MISC_SHARED_NTHANDLE).0
+                    as u32,
+            };
+
+            let mut cropped_texture = None;
+            unsafe {
+                direct_x
+                    .d3d11_device
+                    .CreateTexture2D(&descriptor, None, Some(&mut cropped_texture))
+            }
+            .map_err(|e| WinError::new(e, "ID3D11Device::CreateTexture2D"))?;
+
+            let cropped_texture = match cropped_texture {
+                Some(texture) => texture,
+                None => unreachable!("Texture should be Some if no errors were returned"),
+            };
+
+            let source_box = D3D11_BOX {
+                left: crop.left as u32,
+                top: crop.top as u32,
+                front: 0,
+                right: crop.right as u32,
+                bottom: crop.bottom as u32,
+                back: 1,
+            };
+
+            unsafe {
+                direct_x.d3d11_context.CopySubresourceRegion(
+                    &cropped_texture,
+                    0,
+                    0,
+                    0,
+                    0,
+                    &texture,
+                    0,
+                    Some(&source_box),
+                );
+                direct_x.d3d11_context.Flush();
+            }
+
+            cropped_texture
+        };
+
+        let handle = unsafe { Self::create_shared_handle(&cropped_texture)? };
+        resources.cropped_texture = Some(cropped_texture);
+
+        Ok((
+            Self {
+                handle: SendHANDLE(handle),
+                size,
+            },
+            resources,
+        ))
+    }
+
+    /// Takes a single frame from a capture item, returns the frame's texture and size.
+    fn take_frame(
+        direct_x: &DirectX,
+        capture_item: &GraphicsCaptureItem,
+    ) -> LabelledWinResult<(ID3D11Texture2D, [u32; 2], WindowsCaptureResources)> {
         // Get the capture size
         let capture_size = capture_item
             .Size()
@@ -108,37 +226,8 @@ impl WindowsCapture {
                 .map_err(|e| WinError::new(e, "Direct3D11CaptureFramePool::TryGetNextFrame"))?
         };
 
-        // Retreive the frame's handle
-        let handle = {
-            // Get texture resource of the frame
-            let texture = {
-                let surface = frame
-                    .Surface()
-                    .map_err(|e| WinError::new(e, "Direct3D11CaptureFrame::Surface"))?;
-
-                let access: IDirect3DDxgiInterfaceAccess = surface
-                    .cast()
-                    .map_err(|e| WinError::new(e, "IDirect3DSurface::cast"))?;
-
-                unsafe { access.GetInterface::<ID3D11Texture2D>() }
-                    .map_err(|e| WinError::new(e, "IDirect3DDxgiInterfaceAccess::GetInterface"))?
-            };
-
-            // Create and return handle to texture
-            unsafe {
-                let shared_resource: IDXGIResource1 = texture
-                    .cast()
-                    .map_err(|e| WinError::new(e, "ID3D11Texture2D::cast"))?;
-
-                shared_resource
-                    .CreateSharedHandle(
-                        None,
-                        (DXGI_SHARED_RESOURCE_READ | DXGI_SHARED_RESOURCE_WRITE).0,
-                        None,
-                    )
-                    .map_err(|e| WinError::new(e, "IDXGIResource1::CreateSharedHandle"))?
-            }
-        };
+        // Get texture resource of the frame
+        let texture = Self::frame_texture(&frame)?;
 
         // Clean up
         {
@@ -148,16 +237,53 @@ impl WindowsCapture {
         }
 
         Ok((
-            Self {
-                handle: SendHANDLE(handle),
-                size: [
-                    capture_size.Width.unsigned_abs(),
-                    capture_size.Height.unsigned_abs(),
-                ],
+            texture,
+            [
+                capture_size.Width.unsigned_abs(),
+                capture_size.Height.unsigned_abs(),
+            ],
+            WindowsCaptureResources {
+                frame,
+                framepool,
+                cropped_texture: None,
             },
-            WindowsCaptureResources { frame, framepool },
         ))
     }
+
+    /// Returns the texture resource of a frame.
+    pub(crate) fn frame_texture(
+        frame: &Direct3D11CaptureFrame,
+    ) -> LabelledWinResult<ID3D11Texture2D> {
+        let surface = frame
+            .Surface()
+            .map_err(|e| WinError::new(e, "Direct3D11CaptureFrame::Surface"))?;
+
+        let access: IDirect3DDxgiInterfaceAccess = surface
+            .cast()
+            .map_err(|e| WinError::new(e, "IDirect3DSurface::cast"))?;
+
+        unsafe { access.GetInterface::<ID3D11Texture2D>() }
+            .map_err(|e| WinError::new(e, "IDirect3DDxgiInterfaceAccess::GetInterface"))
+    }
+
+    /// Create and return a shared handle to a texture.
+    pub(crate) unsafe fn create_shared_handle(
+        texture: &ID3D11Texture2D,
+    ) -> LabelledWinResult<HANDLE> {
+        unsafe {
+            let shared_resource: IDXGIResource1 = texture
+                .cast()
+                .map_err(|e| WinError::new(e, "ID3D11Texture2D::cast"))?;
+
+            shared_resource
+                .CreateSharedHandle(
+                    None,
+                    (DXGI_SHARED_RESOURCE_READ | DXGI_SHARED_RESOURCE_WRITE).0,
+                    None,
+                )
+                .map_err(|e| WinError::new(e, "IDXGIResource1::CreateSharedHandle"))
+        }
+    }
 }
 
 impl WindowsCaptureResources {
@@ -1,16 +1,22 @@
 use windows::{
-    Graphics::Capture::GraphicsCaptureItem,
+    Graphics::{Capture::GraphicsCaptureItem, SizeInt32},
     Win32::{
-        Graphics::Gdi::HMONITOR, System::WinRT::Graphics::Capture::IGraphicsCaptureItemInterop,
+        Foundation::{HWND, RECT},
+        Graphics::Gdi::HMONITOR,
+        System::WinRT::Graphics::Capture::IGraphicsCaptureItemInterop,
+        UI::WindowsAndMessaging::{GetWindowRect, IsWindow},
     },
 };
 
 use crate::{DirectX, LabelledWinResult, WinError};
 
-/// A cache for monitors and their capture items.
+/// A cache for monitors, windows, and their capture items.
 pub struct CaptureItemCache {
     /// A map between HMONITOR handles and their capture item.
     capture_items: Vec<(HMONITOR, GraphicsCaptureItem)>,
+
+    /// A map between HWND handles and their capture item.
+    window_capture_items: Vec<(HWND, GraphicsCaptureItem)>,
 }
 
 impl CaptureItemCache {
@@ -19,6 +25,7 @@ impl CaptureItemCache {
     pub fn new() -> Self {
         Self {
             capture_items: Vec::new(),
+            window_capture_items: Vec::new(),
         }
     }
 
@@ -46,13 +53,67 @@ impl CaptureItemCache {
         Ok(capture_item)
     }
 
+    /// Returns if the cache contains a given window.
+    pub fn contains_window(&self, handle: HWND) -> bool {
+        self.window_capture_items
+            .iter()
+            .any(|(cache_handle, _)| *cache_handle == handle)
+    }
+
+    /// Gets the graphics capture item for a window from the cache or creates and caches it if it
+    /// doesn't exist.
+    ///
+    /// A window's capture item keeps the size the window had when the item was created, so the
+    /// item is recreated if the window has since been resized.
+    pub fn get_window_capture_item(
+        &mut self,
+        handle: HWND,
+    ) -> LabelledWinResult<GraphicsCaptureItem> {
+        let mut window_rect = RECT::default();
+        unsafe { GetWindowRect(handle, &mut window_rect) }
+            .map_err(|e| WinError::new(e, "GetWindowRect"))?;
+        let window_size = SizeInt32 {
+            Width: window_rect.right - window_rect.left,
+            Height: window_rect.bottom - window_rect.top,
+        };
+
+        let maybe_index = self
+            .window_capture_items
+            .iter()
+            .position(|(cache_handle, _)| *cache_handle == handle);
+
+        if let Some(index) = maybe_index {
+            let capture_item = &self.window_capture_items[index].1;
+            let item_size = capture_item
+                .Size()
+                .map_err(|e| WinError::new(e, "GraphicsCaptureItem::Size"))?;
+
+            if item_size == window_size {
+                return Ok(capture_item.clone());
+            }
+
+            self.window_capture_items.swap_remove(index);
+        }
+
+        let capture_item = Self::create_window_capture_item(handle)?;
+        self.window_capture_items
+            .push((handle, capture_item.clone()));
+
+        Ok(capture_item)
+    }
+
     /// Purges all capture items from the cache.
     pub fn purge(&mut self) {
         self.capture_items.clear();
+        self.window_capture_items.clear();
     }
 
-    /// Prunes the monitors in the cache that are no longer connected.
+    /// Prunes the monitors in the cache that are no longer connected and the windows that have
+    /// been destroyed.
     pub fn prune(&mut self, direct_x: &DirectX) -> Result<(), WinError> {
+        self.window_capture_items
+            .retain(|(cache_handle, _)| unsafe { IsWindow(Some(*cache_handle)) }.as_bool());
+
         let output_descriptors = direct_x.dxgi_output_descriptors()?;
 
         self.capture_items.retain(|(cache_handle, _)| {
@@ -93,4 +154,15 @@ impl CaptureItemCache {
 
         Ok(capture_item)
     }
+
+    /// Creates a graphics capture item for a window.
+    pub fn create_window_capture_item(handle: HWND) -> Result<GraphicsCaptureItem, WinError> {
+        let interop = windows::core::factory::<GraphicsCaptureItem, IGraphicsCaptureItemInterop>()
+            .map_err(|e| WinError::new(e, "factory::GraphicsCaptureItem"))?;
+
+        let capture_item: GraphicsCaptureItem = unsafe { interop.CreateForWindow(handle) }
+            .map_err(|e| WinError::new(e, "GraphicsCaptureItem::CreateForWindow"))?;
+
+        Ok(capture_item)
+    }
 }
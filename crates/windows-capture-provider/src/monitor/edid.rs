@@ -0,0 +1,81 @@
+use core::fmt;
+
+use windows::Win32::Devices::Display::{
+    DISPLAYCONFIG_DEVICE_INFO_GET_TARGET_NAME, DISPLAYCONFIG_PATH_INFO,
+    DISPLAYCONFIG_TARGET_DEVICE_NAME, DisplayConfigGetDeviceInfo,
+};
+use windows_core::HRESULT;
+
+use crate::{LabelledWinResult, WinError};
+
+/// The `edidIdsValid` bit of `DISPLAYCONFIG_TARGET_DEVICE_NAME_FLAGS`.
+const EDID_IDS_VALID: u32 = 0b100;
+
+/// The manufacturer and product code from a monitor's EDID, written like `DEL4123`. Identifies the
+/// model of a monitor regardless of which output it is connected to.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct EdidId {
+    /// The three letter PnP manufacturer ID.
+    pub manufacturer: [u8; 3],
+
+    /// The manufacturer's product code.
+    pub product: u16,
+}
+
+impl EdidId {
+    /// Decodes the IDs as Windows reports them, where the manufacturer ID keeps the EDID's
+    /// big-endian byte order.
+    fn from_raw(manufacturer_id: u16, product_code: u16) -> Self {
+        let manufacturer_id = manufacturer_id.swap_bytes();
+
+        // Each letter is five bits, where `1` is `A`.
+        let letter = |shift: u16| b'@' + ((manufacturer_id >> shift) & 0b1_1111) as u8;
+
+        Self {
+            manufacturer: [letter(10), letter(5), letter(0)],
+            product: product_code,
+        }
+    }
+}
+
+impl fmt::Display for EdidId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let manufacturer = String::from_utf8_lossy(&self.manufacturer);
+        write!(f, "{manufacturer}{:04X}", self.product)
+    }
+}
+
+/// Get the EDID IDs of the path's target, `None` if the monitor did not report them.
+pub(super) unsafe fn get_edid_id(
+    path_info: &DISPLAYCONFIG_PATH_INFO,
+) -> LabelledWinResult<Option<EdidId>> {
+    let header_size = size_of::<DISPLAYCONFIG_TARGET_DEVICE_NAME>() as u32;
+
+    let mut config = DISPLAYCONFIG_TARGET_DEVICE_NAME::default();
+    config.header.adapterId = path_info.targetInfo.adapterId;
+    config.header.id = path_info.targetInfo.id;
+    config.header.r#type = DISPLAYCONFIG_DEVICE_INFO_GET_TARGET_NAME;
+    config.header.size = header_size;
+
+    unsafe {
+        let result = DisplayConfigGetDeviceInfo(&mut config.header);
+
+        let hresult = HRESULT::from_nt(result);
+        if hresult.is_err() {
+            return Err(WinError::from_hresult(
+                hresult,
+                "DisplayConfigGetDeviceInfo",
+            ));
+        }
+    }
+
+    let flags = unsafe { config.flags.Anonymous.value };
+    if flags & EDID_IDS_VALID == 0 {
+        return Ok(None);
+    }
+
+    Ok(Some(EdidId::from_raw(
+        config.edidManufactureId,
+        config.edidProductCodeId,
+    )))
+}
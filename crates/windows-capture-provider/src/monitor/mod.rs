@@ -1,11 +1,14 @@
+mod edid;
 mod get_monitors;
 mod sdr_white;
 
+use tracing::warn;
 use windows::Win32::{Foundation::RECT, Graphics::Dxgi::DXGI_OUTPUT_DESC1};
-use windows_core::PCWSTR;
 
 use crate::{WinError, send::SendHMONITOR};
 
+pub use edid::EdidId;
+
 /// A monitor and related data
 #[derive(Clone, Copy)]
 #[non_exhaustive]
@@ -28,16 +31,31 @@ pub struct Monitor {
 
     /// The monitor's SDR White luminance.
     pub sdr_white: f32,
+
+    /// The monitor's EDID manufacturer and product code, `None` if the monitor did not report
+    /// them.
+    pub edid_id: Option<EdidId>,
 }
 
 impl Monitor {
     /// Create a new monitor, returns `Ok(None)` if the monitor is inactive.
     pub(crate) fn new(descriptor: DXGI_OUTPUT_DESC1) -> Result<Option<Self>, WinError> {
-        let sdr_white = match Self::get_sdr_white(descriptor)? {
-            Some(sdr_white) => sdr_white,
+        let path = match Self::get_display_path(descriptor)? {
+            Some(path) => path,
             None => return Ok(None),
         };
 
+        let sdr_white = unsafe { sdr_white::get_sdr_white(&path)? };
+
+        // The EDID is only used to identify the monitor, so the monitor is still usable without it.
+        let edid_id = match unsafe { edid::get_edid_id(&path) } {
+            Ok(edid_id) => edid_id,
+            Err(e) => {
+                warn!("Could not get the monitor's EDID IDs: {e}");
+                None
+            }
+        };
+
         Ok(Some(Self {
             handle: SendHMONITOR(descriptor.Monitor),
             desktop_coordinates: descriptor.DesktopCoordinates,
@@ -45,9 +63,21 @@ impl Monitor {
             max_brightness: descriptor.MaxLuminance / 80.0,
             min_brightness: descriptor.MinLuminance / 80.0,
             sdr_white,
+            edid_id,
         }))
     }
 
+    /// The monitor's GDI device name, like `\\.\DISPLAY1`.
+    pub fn name(&self) -> String {
+        let length = self
+            .device_name
+            .iter()
+            .position(|&character| character == 0)
+            .unwrap_or(self.device_name.len());
+
+        String::from_utf16_lossy(&self.device_name[..length])
+    }
+
     /// Calculates the monitor's width and height from it's Desktop Coordinates.
     pub fn size(&self) -> [u32; 2] {
         let rect = self.desktop_coordinates;
@@ -68,16 +98,14 @@ impl PartialEq for Monitor {
 
 impl core::fmt::Debug for Monitor {
     fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
-        let name = unsafe { PCWSTR::from_raw(self.device_name.as_ptr()).to_string() }
-            .unwrap_or("Invalid Name".to_string());
-
         f.debug_struct("Monitor")
             .field("handle", &self.handle.0)
             .field("desktop_coordinates", &self.desktop_coordinates)
-            .field("device_name", &name)
+            .field("device_name", &self.name())
             .field("min_brightness", &self.min_brightness)
             .field("max_brightness", &self.max_brightness)
             .field("sdr_white", &self.sdr_white)
+            .field("edid_id", &self.edid_id.map(|edid_id| edid_id.to_string()))
             .finish()
     }
 }
@@ -14,7 +14,10 @@ use crate::{LabelledWinResult, WinError};
 use super::Monitor;
 
 impl Monitor {
-    pub(super) fn get_sdr_white(descriptor: DXGI_OUTPUT_DESC1) -> Result<Option<f32>, WinError> {
+    /// Get the display path of the output, `None` if the output is inactive.
+    pub(super) fn get_display_path(
+        descriptor: DXGI_OUTPUT_DESC1,
+    ) -> Result<Option<DISPLAYCONFIG_PATH_INFO>, WinError> {
         let mut path_elements = 0;
         let mut mode_info_elements = 0;
         unsafe {
@@ -67,9 +70,7 @@ impl Monitor {
             paths[matching_path_index]
         };
 
-        let sdr_white = unsafe { get_sdr_white(&matching_path)? };
-
-        Ok(Some(sdr_white))
+        Ok(Some(matching_path))
     }
 }
 
@@ -98,7 +99,7 @@ unsafe fn get_device_name(path_info: &DISPLAYCONFIG_PATH_INFO) -> LabelledWinRes
     Ok(config.viewGdiDeviceName)
 }
 
-unsafe fn get_sdr_white(path_info: &DISPLAYCONFIG_PATH_INFO) -> LabelledWinResult<f32> {
+pub(super) unsafe fn get_sdr_white(path_info: &DISPLAYCONFIG_PATH_INFO) -> LabelledWinResult<f32> {
     let header_size = size_of::<DISPLAYCONFIG_SDR_WHITE_LEVEL>() as u32;
 
     let mut config = DISPLAYCONFIG_SDR_WHITE_LEVEL::default();
@@ -0,0 +1,166 @@
+use core::time::Duration;
+use std::sync::mpsc::{Receiver, RecvTimeoutError, channel};
+
+use tracing::error;
+use windows::{
+    Foundation::TypedEventHandler,
+    Graphics::{
+        Capture::{
+            Direct3D11CaptureFrame, Direct3D11CaptureFramePool, GraphicsCaptureItem,
+            GraphicsCaptureSession,
+        },
+        DirectX::DirectXPixelFormat,
+    },
+};
+use windows_core::IInspectable;
+
+use crate::{DirectX, LabelledWinResult, SendHANDLE, WinError, WindowsCapture};
+
+/// A running capture session that serves successive frames of a capture item.
+pub struct WindowsRecording {
+    framepool: Direct3D11CaptureFramePool,
+    session: GraphicsCaptureSession,
+    frame_arrived: Receiver<()>,
+
+    /// The latest frame, kept open until the next frame so its texture is not reused.
+    frame: Option<Direct3D11CaptureFrame>,
+
+    size: [u32; 2],
+}
+
+impl WindowsRecording {
+    /// Starts a capture session for a capture item.
+    pub fn start(
+        direct_x: &DirectX,
+        capture_item: &GraphicsCaptureItem,
+    ) -> LabelledWinResult<Self> {
+        // Get the capture size
+        let capture_size = capture_item
+            .Size()
+            .map_err(|e| WinError::new(e, "GraphicsCaptureItem::Size"))?;
+
+        // Setup sender and receiver the frame arrived event.
+        let (frame_arrived_sender, frame_arrived) = channel();
+
+        // Create the framepool, a single buffer is enough as each frame is imported before the
+        // next is requested.
+        let framepool = {
+            let framepool = Direct3D11CaptureFramePool::CreateFreeThreaded(
+                &direct_x.d3d_device,
+                DirectXPixelFormat::R16G16B16A16Float,
+                1,
+                capture_size,
+            )
+            .map_err(|e| WinError::new(e, "Direct3D11CaptureFramePool::CreateFreeThreaded"))?;
+
+            // Handle frame arrived event
+            framepool
+                .FrameArrived(
+                    &TypedEventHandler::<Direct3D11CaptureFramePool, IInspectable>::new({
+                        move |_, _| {
+                            match frame_arrived_sender.send(()) {
+                                Ok(_) => {}
+                                Err(e) => error!("Failed to send frame arrived event: {e}"),
+                            };
+
+                            Ok(())
+                        }
+                    }),
+                )
+                .map_err(|e| WinError::new(e, "Direct3D11CaptureFramePool::FrameArrived"))?;
+
+            framepool
+        };
+
+        // Create and start the capture session
+        let session = {
+            let session = framepool.CreateCaptureSession(capture_item).map_err(|e| {
+                WinError::new(e, "Direct3D11CaptureFramePool::CreateCaptureSession")
+            })?;
+
+            // Recordings show what the user is doing, so include the cursor.
+            session.SetIsCursorCaptureEnabled(true).map_err(|e| {
+                WinError::new(e, "GraphicsCaptureSession::SetIsCursorCaptureEnabled")
+            })?;
+
+            session
+                .StartCapture()
+                .map_err(|e| WinError::new(e, "GraphicsCaptureSession::StartCapture"))?;
+
+            session
+        };
+
+        Ok(Self {
+            framepool,
+            session,
+            frame_arrived,
+            frame: None,
+            size: [
+                capture_size.Width.unsigned_abs(),
+                capture_size.Height.unsigned_abs(),
+            ],
+        })
+    }
+
+    /// Waits for the next frame, `None` if the content did not change within the timeout.
+    ///
+    /// The previous frame is released, so any image imported from it must no longer be in use.
+    /// The returned handle must be closed by the caller.
+    pub fn next_frame(&mut self, timeout: Duration) -> LabelledWinResult<Option<WindowsCapture>> {
+        // Release the previous frame back to the framepool.
+        if let Some(frame) = self.frame.take() {
+            frame
+                .Close()
+                .map_err(|e| WinError::new(e, "Direct3D11CaptureFrame::Close"))?;
+        }
+
+        // Windows only sends frames when the content changes.
+        match self.frame_arrived.recv_timeout(timeout) {
+            Ok(()) => {}
+            Err(RecvTimeoutError::Timeout) => return Ok(None),
+            Err(RecvTimeoutError::Disconnected) => {
+                unreachable!("Framepool dropped the frame arrived sender while recording")
+            }
+        }
+        while self.frame_arrived.try_recv().is_ok() {}
+
+        let frame = self
+            .framepool
+            .TryGetNextFrame()
+            .map_err(|e| WinError::new(e, "Direct3D11CaptureFramePool::TryGetNextFrame"))?;
+
+        let texture = WindowsCapture::frame_texture(&frame)?;
+        let handle = unsafe { WindowsCapture::create_shared_handle(&texture)? };
+        self.frame = Some(frame);
+
+        Ok(Some(WindowsCapture {
+            handle: SendHANDLE(handle),
+            size: self.size,
+        }))
+    }
+
+    /// Stops the capture session and destroys its resources.
+    pub unsafe fn stop(self, direct_x: &DirectX) -> LabelledWinResult<()> {
+        self.session
+            .Close()
+            .map_err(|e| WinError::new(e, "GraphicsCaptureSession::Close"))?;
+
+        if let Some(frame) = self.frame {
+            frame
+                .Close()
+                .map_err(|e| WinError::new(e, "Direct3D11CaptureFrame::Close"))?;
+        }
+
+        self.framepool
+            .Close()
+            .map_err(|e| WinError::new(e, "Direct3D11CaptureFramePool::Close"))?;
+
+        unsafe { direct_x.d3d11_context.ClearState() }
+        direct_x
+            .d3d_device
+            .Trim()
+            .map_err(|e| WinError::new(e, "IDirect3DDevice::Trim"))?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,201 @@
+use tracing::debug;
+use windows::Win32::{
+    Foundation::{HWND, LPARAM, POINT, RECT},
+    Graphics::{
+        Dwm::{DWMWA_CLOAKED, DWMWA_EXTENDED_FRAME_BOUNDS, DwmGetWindowAttribute},
+        Gdi::{MONITOR_DEFAULTTONEAREST, MonitorFromWindow},
+    },
+    System::Threading::GetCurrentProcessId,
+    UI::WindowsAndMessaging::{
+        EnumWindows, GA_ROOT, GetAncestor, GetForegroundWindow, GetWindowRect,
+        GetWindowThreadProcessId, IsIconic, IsWindowVisible,
+    },
+};
+use windows_core::BOOL;
+
+use crate::{DirectX, LabelledWinResult, Monitor, WinError, send::SendHWND};
+
+/// A top-level window and related data
+#[derive(Clone, Copy, Debug)]
+#[non_exhaustive]
+pub struct Window {
+    /// The window's `HWND` handle.
+    pub handle: SendHWND,
+
+    /// The window's bounds including its invisible resize borders, this is the area covered by a
+    /// capture of the window.
+    pub window_rect: RECT,
+
+    /// The window's visible bounds, excluding the resize borders and drop shadow. Relative to the
+    /// top-left point of the primary monitor.
+    pub desktop_coordinates: RECT,
+
+    /// The monitor that the window is mostly on.
+    pub monitor: Monitor,
+}
+
+impl Window {
+    /// Returns the window if it is a visible top-level window on an active monitor.
+    pub fn from_handle(direct_x: &DirectX, handle: HWND) -> LabelledWinResult<Option<Self>> {
+        if handle.is_invalid() {
+            return Ok(None);
+        }
+
+        let handle = unsafe { GetAncestor(handle, GA_ROOT) };
+        if handle.is_invalid() || !is_capturable(handle) {
+            return Ok(None);
+        }
+
+        let mut window_rect = RECT::default();
+        unsafe { GetWindowRect(handle, &mut window_rect) }
+            .map_err(|e| WinError::new(e, "GetWindowRect"))?;
+
+        let Some(desktop_coordinates) = visible_bounds(handle)? else {
+            return Ok(None);
+        };
+
+        let monitor_handle = unsafe { MonitorFromWindow(handle, MONITOR_DEFAULTTONEAREST) };
+        let Some(monitor) = Monitor::get_active_monitors(direct_x)?
+            .into_iter()
+            .find(|monitor| monitor.handle.0 == monitor_handle)
+        else {
+            debug!("Window {handle:?} is not on an active monitor");
+            return Ok(None);
+        };
+
+        Ok(Some(Self {
+            handle: SendHWND(handle),
+            window_rect,
+            desktop_coordinates,
+            monitor,
+        }))
+    }
+
+    /// Returns the window that currently has focus.
+    pub fn get_foreground_window(direct_x: &DirectX) -> LabelledWinResult<Option<Self>> {
+        let handle = unsafe { GetForegroundWindow() };
+        debug!("Foreground window {handle:?}");
+
+        Self::from_handle(direct_x, handle)
+    }
+
+    /// Returns the top-most window at a point in desktop coordinates, ignoring the windows that
+    /// belong to this process.
+    pub fn get_window_at(direct_x: &DirectX, point: POINT) -> LabelledWinResult<Option<Self>> {
+        struct Search {
+            point: POINT,
+            process_id: u32,
+            found: Option<HWND>,
+        }
+
+        unsafe extern "system" fn enum_window(handle: HWND, search: LPARAM) -> BOOL {
+            let search = unsafe { &mut *(search.0 as *mut Search) };
+
+            let mut process_id = 0;
+            unsafe { GetWindowThreadProcessId(handle, Some(&mut process_id)) };
+            if process_id == search.process_id || !is_capturable(handle) {
+                return true.into();
+            }
+
+            let contains_point = visible_bounds(handle).ok().flatten().is_some_and(|rect| {
+                let point = search.point;
+                point.x >= rect.left
+                    && point.x < rect.right
+                    && point.y >= rect.top
+                    && point.y < rect.bottom
+            });
+
+            if contains_point {
+                search.found = Some(handle);
+                return false.into();
+            }
+
+            true.into()
+        }
+
+        let mut search = Search {
+            point,
+            process_id: unsafe { GetCurrentProcessId() },
+            found: None,
+        };
+
+        // EnumWindows reports an error when the callback stops the enumeration early.
+        let result = unsafe { EnumWindows(Some(enum_window), LPARAM((&raw mut search) as isize)) };
+        match search.found {
+            Some(handle) => {
+                debug!("Window at {point:?}: {handle:?}");
+                Self::from_handle(direct_x, handle)
+            }
+            None => {
+                result.map_err(|e| WinError::new(e, "EnumWindows"))?;
+                Ok(None)
+            }
+        }
+    }
+
+    /// Calculates the window's visible width and height from it's Desktop Coordinates.
+    pub fn size(&self) -> [u32; 2] {
+        let rect = self.desktop_coordinates;
+
+        let width = rect.left.abs_diff(rect.right);
+        let height = rect.top.abs_diff(rect.bottom);
+
+        [width, height]
+    }
+
+    /// The window's visible bounds relative to the top-left point of a capture of the window.
+    pub fn crop(&self) -> RECT {
+        let left = self.desktop_coordinates.left - self.window_rect.left;
+        let top = self.desktop_coordinates.top - self.window_rect.top;
+        let [width, height] = self.size();
+
+        RECT {
+            left,
+            top,
+            right: left + width as i32,
+            bottom: top + height as i32,
+        }
+    }
+}
+
+/// Returns if a window is visible, not minimised, and not cloaked.
+///
+/// Windows on other virtual desktops or suspended UWP windows are cloaked.
+fn is_capturable(handle: HWND) -> bool {
+    if !unsafe { IsWindowVisible(handle) }.as_bool() || unsafe { IsIconic(handle) }.as_bool() {
+        return false;
+    }
+
+    let mut cloaked = 0u32;
+    let is_cloaked = unsafe {
+        DwmGetWindowAttribute(
+            handle,
+            DWMWA_CLOAKED,
+            (&raw mut cloaked).cast(),
+            size_of::<u32>() as u32,
+        )
+    }
+    .is_ok_and(|_| cloaked != 0);
+
+    !is_cloaked
+}
+
+/// Returns the bounds of a window excluding its drop shadow, `None` if the window is empty.
+fn visible_bounds(handle: HWND) -> LabelledWinResult<Option<RECT>> {
+    let mut rect = RECT::default();
+    unsafe {
+        DwmGetWindowAttribute(
+            handle,
+            DWMWA_EXTENDED_FRAME_BOUNDS,
+            (&raw mut rect).cast(),
+            size_of::<RECT>() as u32,
+        )
+    }
+    .map_err(|e| WinError::new(e, "DwmGetWindowAttribute"))?;
+
+    if rect.right > rect.left && rect.bottom > rect.top {
+        Ok(Some(rect))
+    } else {
+        Ok(None)
+    }
+}
@@ -2,7 +2,7 @@
 //!
 
 use windows::Win32::Foundation::CloseHandle;
-use windows_capture_provider::{CaptureItemCache, DirectX, Monitor, WindowsCapture};
+use windows_capture_provider::{CaptureItemCache, DirectX, Monitor, Window, WindowsCapture};
 
 #[test]
 fn take_capture() {
@@ -20,3 +20,22 @@ fn take_capture() {
     unsafe { CloseHandle(capture.handle.0).unwrap() };
     unsafe { resources.destroy(&direct_x).unwrap() }
 }
+
+#[test]
+fn take_window_capture() {
+    let direct_x = DirectX::new().unwrap();
+
+    let Some(window) = Window::get_foreground_window(&direct_x).unwrap() else {
+        return;
+    };
+
+    let mut cache = CaptureItemCache::new();
+    let capture_item = cache.get_window_capture_item(window.handle.0).unwrap();
+
+    let (capture, resources) =
+        WindowsCapture::take_window_capture(&direct_x, &capture_item, &window).unwrap();
+    assert!(!capture.handle.0.is_invalid());
+    assert_eq!(capture.size, window.size());
+    unsafe { CloseHandle(capture.handle.0).unwrap() };
+    unsafe { resources.destroy(&direct_x).unwrap() }
+}
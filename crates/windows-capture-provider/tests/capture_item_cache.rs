@@ -1,7 +1,7 @@
 //! Tests for the CaptureItemCache
 //!
 
-use windows_capture_provider::{CaptureItemCache, DirectX, Monitor};
+use windows_capture_provider::{CaptureItemCache, DirectX, Monitor, Window};
 
 #[test]
 fn create_capture_item() {
@@ -74,3 +74,20 @@ fn get_capture_item_cached() {
         let _capture_item = cache.get_capture_item(monitor.handle.0).unwrap();
     }
 }
+
+#[test]
+fn get_window_capture_item() {
+    let direct_x = DirectX::new().unwrap();
+
+    let Some(window) = Window::get_foreground_window(&direct_x).unwrap() else {
+        return;
+    };
+
+    let mut cache = CaptureItemCache::new();
+    let _capture_item = cache.get_window_capture_item(window.handle.0).unwrap();
+    assert!(cache.contains_window(window.handle.0));
+
+    // The window still exists, so pruning keeps it.
+    cache.prune(&direct_x).unwrap();
+    assert!(cache.contains_window(window.handle.0));
+}
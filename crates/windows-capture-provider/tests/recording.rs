@@ -0,0 +1,36 @@
+//! Tests for recording
+//!
+
+use core::time::Duration;
+
+use windows::Win32::Foundation::CloseHandle;
+use windows_capture_provider::{CaptureItemCache, DirectX, Monitor, WindowsRecording};
+
+#[test]
+fn record_frames() {
+    let direct_x = DirectX::new().unwrap();
+
+    let monitor = Monitor::get_hovered_monitor(&direct_x)
+        .unwrap()
+        .expect("Monitor should be some");
+
+    let mut cache = CaptureItemCache::new();
+    let capture_item = cache.get_capture_item(monitor.handle.0).unwrap();
+
+    let mut recording = WindowsRecording::start(&direct_x, &capture_item).unwrap();
+
+    // The first frame is always sent, later frames only when the monitor changes.
+    let capture = recording
+        .next_frame(Duration::from_secs(1))
+        .unwrap()
+        .expect("First frame should be some");
+    assert!(!capture.handle.0.is_invalid());
+    assert_eq!(capture.size, monitor.size());
+    unsafe { CloseHandle(capture.handle.0).unwrap() };
+
+    if let Some(capture) = recording.next_frame(Duration::from_millis(100)).unwrap() {
+        unsafe { CloseHandle(capture.handle.0).unwrap() };
+    }
+
+    unsafe { recording.stop(&direct_x).unwrap() }
+}
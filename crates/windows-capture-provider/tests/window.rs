@@ -0,0 +1,40 @@
+//! Tests for Window functions
+
+use windows::Win32::Foundation::POINT;
+use windows_capture_provider::{DirectX, Window};
+
+#[test]
+fn get_foreground_window() {
+    let direct_x = DirectX::new().unwrap();
+
+    // The test may be run without a focused window.
+    if let Some(window) = Window::get_foreground_window(&direct_x).unwrap() {
+        assert!(!window.handle.0.is_invalid());
+
+        let [width, height] = window.size();
+        assert!(width > 0 && height > 0);
+
+        let crop = window.crop();
+        assert!(crop.left >= 0 && crop.top >= 0);
+    }
+}
+
+#[test]
+fn get_window_at() {
+    let direct_x = DirectX::new().unwrap();
+
+    let Some(foreground) = Window::get_foreground_window(&direct_x).unwrap() else {
+        return;
+    };
+
+    let rect = foreground.desktop_coordinates;
+    let centre = POINT {
+        x: (rect.left + rect.right) / 2,
+        y: (rect.top + rect.bottom) / 2,
+    };
+
+    let window = Window::get_window_at(&direct_x, centre)
+        .unwrap()
+        .expect("Window should be some");
+    assert!(!window.handle.0.is_invalid());
+}
@@ -0,0 +1,164 @@
+use core::{ffi::c_void, ptr, slice};
+use std::os::fd::OwnedFd;
+
+use half::f16;
+use rustix::mm::{MapFlags, ProtFlags, mmap, munmap};
+use tracing::warn;
+use x11rb::{
+    connection::Connection,
+    protocol::{
+        shm::{self, ConnectionExt as _},
+        xproto::{ConnectionExt as _, ImageFormat},
+    },
+    rust_connection::RustConnection,
+};
+
+use crate::{LabelledX11Result, Monitor, PixelFormat, X11Connection, X11Error};
+
+/// A capture from X11.
+#[derive(Debug, Clone, Copy)]
+#[non_exhaustive]
+pub struct X11Capture {
+    /// The size of the capture.
+    pub size: [u32; 2],
+}
+
+impl X11Capture {
+    /// Takes a capture of a monitor, returns the capture and its pixels as linear `RGBA` where
+    /// sRGB white has the monitor's SDR white value.
+    ///
+    /// Uses `MIT-SHM` if it is available, otherwise falls back to `GetImage`.
+    pub fn take_capture(
+        connection: &X11Connection,
+        monitor: &Monitor,
+    ) -> LabelledX11Result<(Self, Vec<f16>)> {
+        let format = connection.pixel_format()?;
+        if let Some(reason) = format.unsupported_reason() {
+            return Err(X11Error::unsupported_format(reason, "GetImage"));
+        }
+
+        let size = monitor.size();
+        let capture = Self { size };
+
+        if connection.has_shm {
+            match Self::convert_shm_image(connection, monitor, format) {
+                Ok(pixels) => return Ok((capture, pixels)),
+                Err(e) => warn!("Could not capture using shared memory, using GetImage: {e}"),
+            }
+        }
+
+        let rect = monitor.desktop_coordinates;
+        let image = connection
+            .connection
+            .get_image(
+                ImageFormat::Z_PIXMAP,
+                connection.screen().root,
+                rect.left as i16,
+                rect.top as i16,
+                size[0] as u16,
+                size[1] as u16,
+                !0,
+            )
+            .map_err(|e| X11Error::new(e, "GetImage"))?
+            .reply()
+            .map_err(|e| X11Error::new(e, "GetImage"))?;
+
+        let pixels = format.to_linear_rgba(&image.data, size, monitor.sdr_white);
+
+        Ok((capture, pixels))
+    }
+
+    /// Captures a monitor into a shared memory segment and converts it.
+    fn convert_shm_image(
+        connection: &X11Connection,
+        monitor: &Monitor,
+        format: PixelFormat,
+    ) -> LabelledX11Result<Vec<f16>> {
+        let size = monitor.size();
+        let rect = monitor.desktop_coordinates;
+
+        let segment = SharedSegment::new(&connection.connection, format.image_size(size))?;
+
+        connection
+            .connection
+            .shm_get_image(
+                connection.screen().root,
+                rect.left as i16,
+                rect.top as i16,
+                size[0] as u16,
+                size[1] as u16,
+                !0,
+                ImageFormat::Z_PIXMAP.into(),
+                segment.segment,
+                0,
+            )
+            .map_err(|e| X11Error::new(e, "ShmGetImage"))?
+            .reply()
+            .map_err(|e| X11Error::new(e, "ShmGetImage"))?;
+
+        let data = unsafe { slice::from_raw_parts(segment.pointer.cast::<u8>(), segment.length) };
+
+        Ok(format.to_linear_rgba(data, size, monitor.sdr_white))
+    }
+}
+
+/// A shared memory segment attached to the X server and mapped into this process.
+struct SharedSegment<'connection> {
+    connection: &'connection RustConnection,
+    segment: shm::Seg,
+    pointer: *mut c_void,
+    length: usize,
+}
+
+impl<'connection> SharedSegment<'connection> {
+    fn new(connection: &'connection RustConnection, length: usize) -> LabelledX11Result<Self> {
+        let segment = connection
+            .generate_id()
+            .map_err(|e| X11Error::new(e, "generate_id"))?;
+
+        let reply = connection
+            .shm_create_segment(segment, length as u32, false)
+            .map_err(|e| X11Error::new(e, "ShmCreateSegment"))?
+            .reply()
+            .map_err(|e| X11Error::new(e, "ShmCreateSegment"))?;
+        let fd: OwnedFd = reply.shm_fd;
+
+        let pointer = match unsafe {
+            mmap(
+                ptr::null_mut(),
+                length,
+                ProtFlags::READ,
+                MapFlags::SHARED,
+                &fd,
+                0,
+            )
+        } {
+            Ok(pointer) => pointer,
+            Err(e) => {
+                if let Err(e) = connection.shm_detach(segment) {
+                    warn!("Could not detach shared memory segment: {e}");
+                }
+                return Err(X11Error::new(e, "mmap"));
+            }
+        };
+
+        Ok(Self {
+            connection,
+            segment,
+            pointer,
+            length,
+        })
+    }
+}
+
+impl Drop for SharedSegment<'_> {
+    fn drop(&mut self) {
+        if let Err(e) = unsafe { munmap(self.pointer, self.length) } {
+            warn!("Could not unmap shared memory segment: {e}");
+        }
+
+        if let Err(e) = self.connection.shm_detach(self.segment) {
+            warn!("Could not detach shared memory segment: {e}");
+        }
+    }
+}
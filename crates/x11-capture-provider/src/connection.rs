@@ -0,0 +1,115 @@
+use tracing::{debug, warn};
+use x11rb::{
+    connection::{Connection, RequestConnection},
+    protocol::{
+        randr::{self, ConnectionExt as _},
+        shm::{self, ConnectionExt as _},
+        xproto::{ImageOrder, Screen},
+    },
+    rust_connection::RustConnection,
+};
+
+use crate::{ByteOrder, LabelledX11Result, PixelFormat, X11Error};
+
+/// A connection to an X server and the extensions that it supports.
+pub struct X11Connection {
+    pub(crate) connection: RustConnection,
+    screen: usize,
+
+    /// If the server supports creating shared memory segments, `MIT-SHM` 1.2.
+    pub(crate) has_shm: bool,
+
+    /// If the server supports listing monitors, `RandR` 1.5.
+    pub(crate) has_randr_monitors: bool,
+}
+
+impl X11Connection {
+    /// Connects to a display, or to `$DISPLAY` if `None`.
+    pub fn new(display: Option<&str>) -> LabelledX11Result<Self> {
+        let (connection, screen) =
+            RustConnection::connect(display).map_err(|e| X11Error::new(e, "connect"))?;
+
+        let has_shm = Self::supports(&connection, shm::X11_EXTENSION_NAME, || {
+            let version = connection.shm_query_version()?.reply()?;
+            Ok((version.major_version, version.minor_version) >= (1, 2))
+        });
+
+        let has_randr_monitors = Self::supports(&connection, randr::X11_EXTENSION_NAME, || {
+            let version = connection.randr_query_version(1, 5)?.reply()?;
+            Ok((version.major_version, version.minor_version) >= (1, 5))
+        });
+
+        debug!(
+            "Connected to X11 screen {screen}, MIT-SHM: {has_shm}, RandR 1.5: {has_randr_monitors}"
+        );
+
+        Ok(Self {
+            connection,
+            screen,
+            has_shm,
+            has_randr_monitors,
+        })
+    }
+
+    /// Returns if the server has an extension and its version is supported.
+    fn supports(
+        connection: &RustConnection,
+        extension: &'static str,
+        is_supported_version: impl FnOnce() -> Result<bool, x11rb::errors::ReplyError>,
+    ) -> bool {
+        match connection.extension_information(extension) {
+            Ok(Some(_)) => {}
+            Ok(None) => return false,
+            Err(e) => {
+                warn!("Could not query the {extension} extension: {e}");
+                return false;
+            }
+        }
+
+        is_supported_version()
+            .inspect_err(|e| warn!("Could not query the {extension} version: {e}"))
+            .unwrap_or(false)
+    }
+
+    /// The screen that captures are taken from.
+    pub(crate) fn screen(&self) -> &Screen {
+        &self.connection.setup().roots[self.screen]
+    }
+
+    /// Returns the layout of the pixels on the screen.
+    pub fn pixel_format(&self) -> LabelledX11Result<PixelFormat> {
+        let setup = self.connection.setup();
+        let screen = self.screen();
+
+        let visual = screen
+            .allowed_depths
+            .iter()
+            .flat_map(|depth| &depth.visuals)
+            .find(|visual| visual.visual_id == screen.root_visual)
+            .ok_or_else(|| X11Error::unsupported_format("the root visual is missing", "setup"))?;
+
+        let format = setup
+            .pixmap_formats
+            .iter()
+            .find(|format| format.depth == screen.root_depth)
+            .ok_or_else(|| {
+                X11Error::unsupported_format("the root depth has no pixmap format", "setup")
+            })?;
+
+        let byte_order = if setup.image_byte_order == ImageOrder::MSB_FIRST {
+            ByteOrder::MsbFirst
+        } else {
+            ByteOrder::LsbFirst
+        };
+
+        Ok(PixelFormat {
+            depth: format.depth,
+            bits_per_pixel: format.bits_per_pixel,
+            scanline_pad: format.scanline_pad,
+            red_mask: visual.red_mask,
+            green_mask: visual.green_mask,
+            blue_mask: visual.blue_mask,
+            byte_order,
+        })
+    }
+}
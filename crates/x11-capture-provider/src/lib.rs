@@ -0,0 +1,16 @@
+//! # X11 Capture Provider
+//! Library to take screenshots of X11 monitors and convert them to linear `R16G16B16A16Float`
+//! pixels.
+//!
+
+pub use capture::X11Capture;
+pub use connection::X11Connection;
+pub use monitor::{Monitor, Rect, SDR_WHITE};
+pub use pixel_format::{ByteOrder, PixelFormat};
+pub use result::{LabelledX11Result, X11Error, X11ErrorSource};
+
+mod capture;
+mod connection;
+mod monitor;
+mod pixel_format;
+mod result;
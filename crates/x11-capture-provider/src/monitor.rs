@@ -0,0 +1,146 @@
+use tracing::debug;
+use x11rb::protocol::{randr::ConnectionExt as _, xproto::ConnectionExt as _};
+
+use crate::{LabelledX11Result, X11Connection, X11Error};
+
+/// The SDR white luminance of X11 monitors, X11 has no HDR output so sRGB white is used.
+pub const SDR_WHITE: f32 = 1.0;
+
+/// A rectangle in root window coordinates.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Rect {
+    /// The x-coordinate of the left edge.
+    pub left: i32,
+
+    /// The y-coordinate of the top edge.
+    pub top: i32,
+
+    /// The x-coordinate of the right edge, exclusive.
+    pub right: i32,
+
+    /// The y-coordinate of the bottom edge, exclusive.
+    pub bottom: i32,
+}
+
+impl Rect {
+    /// Returns if the point is inside of the rectangle.
+    pub fn contains(&self, x: i32, y: i32) -> bool {
+        x >= self.left && x < self.right && y >= self.top && y < self.bottom
+    }
+}
+
+/// A monitor and related data
+#[derive(Clone, Debug)]
+#[non_exhaustive]
+pub struct Monitor {
+    /// The monitor's RandR name.
+    pub name: String,
+
+    /// The monitor's coordinates, relative to the top-left point of the root window.
+    pub desktop_coordinates: Rect,
+
+    /// If the monitor is the primary monitor.
+    pub primary: bool,
+
+    /// The monitor's maximum luminance.
+    pub max_brightness: f32,
+
+    /// The monitor's SDR White luminance.
+    pub sdr_white: f32,
+}
+
+impl Monitor {
+    /// Calculates the monitor's width and height from it's Desktop Coordinates.
+    pub fn size(&self) -> [u32; 2] {
+        let rect = self.desktop_coordinates;
+
+        let width = rect.left.abs_diff(rect.right);
+        let height = rect.top.abs_diff(rect.bottom);
+
+        [width, height]
+    }
+
+    /// Returns the currently active monitors.
+    ///
+    /// If the server does not support RandR 1.5, the screen is treated as a single monitor.
+    pub fn get_active_monitors(connection: &X11Connection) -> LabelledX11Result<Vec<Self>> {
+        let screen = connection.screen();
+
+        if !connection.has_randr_monitors {
+            return Ok(vec![Self {
+                name: String::from("Screen"),
+                desktop_coordinates: Rect {
+                    left: 0,
+                    top: 0,
+                    right: i32::from(screen.width_in_pixels),
+                    bottom: i32::from(screen.height_in_pixels),
+                },
+                primary: true,
+                max_brightness: SDR_WHITE,
+                sdr_white: SDR_WHITE,
+            }]);
+        }
+
+        let reply = connection
+            .connection
+            .randr_get_monitors(screen.root, true)
+            .map_err(|e| X11Error::new(e, "RRGetMonitors"))?
+            .reply()
+            .map_err(|e| X11Error::new(e, "RRGetMonitors"))?;
+
+        let mut monitors = Vec::with_capacity(reply.monitors.len());
+        for info in reply.monitors {
+            let name = connection
+                .connection
+                .get_atom_name(info.name)
+                .map_err(|e| X11Error::new(e, "GetAtomName"))?
+                .reply()
+                .map_err(|e| X11Error::new(e, "GetAtomName"))?
+                .name;
+
+            let left = i32::from(info.x);
+            let top = i32::from(info.y);
+
+            monitors.push(Self {
+                name: String::from_utf8_lossy(&name).into_owned(),
+                desktop_coordinates: Rect {
+                    left,
+                    top,
+                    right: left + i32::from(info.width),
+                    bottom: top + i32::from(info.height),
+                },
+                primary: info.primary,
+                max_brightness: SDR_WHITE,
+                sdr_white: SDR_WHITE,
+            });
+        }
+
+        Ok(monitors)
+    }
+
+    /// Returns the monitor that is currently hovered by the mouse.
+    pub fn get_hovered_monitor(connection: &X11Connection) -> LabelledX11Result<Option<Self>> {
+        let monitors = Self::get_active_monitors(connection)?;
+
+        for monitor in &monitors {
+            debug!("Active {:?}", monitor);
+        }
+
+        let pointer = connection
+            .connection
+            .query_pointer(connection.screen().root)
+            .map_err(|e| X11Error::new(e, "QueryPointer"))?
+            .reply()
+            .map_err(|e| X11Error::new(e, "QueryPointer"))?;
+
+        let x = i32::from(pointer.root_x);
+        let y = i32::from(pointer.root_y);
+        debug!("Mouse ({x}, {y})");
+
+        let monitor = monitors
+            .into_iter()
+            .find(|monitor| monitor.desktop_coordinates.contains(x, y));
+
+        Ok(monitor)
+    }
+}
@@ -0,0 +1,266 @@
+use half::f16;
+
+/// The order of the bytes in a pixel.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ByteOrder {
+    /// The least significant byte comes first.
+    LsbFirst,
+
+    /// The most significant byte comes first.
+    MsbFirst,
+}
+
+/// The layout of the pixels in a `ZPixmap` image from the X server.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PixelFormat {
+    /// The number of bits in a pixel that hold data.
+    pub depth: u8,
+
+    /// The number of bits used to store a pixel.
+    pub bits_per_pixel: u8,
+
+    /// The number of bits each row is padded to.
+    pub scanline_pad: u8,
+
+    /// The bits of a pixel that hold the red channel.
+    pub red_mask: u32,
+
+    /// The bits of a pixel that hold the green channel.
+    pub green_mask: u32,
+
+    /// The bits of a pixel that hold the blue channel.
+    pub blue_mask: u32,
+
+    /// The order of the bytes in a pixel.
+    pub byte_order: ByteOrder,
+}
+
+impl PixelFormat {
+    /// The number of bytes in each row of an image.
+    pub fn bytes_per_line(&self, width: u32) -> usize {
+        let pad = u64::from(self.scanline_pad.max(8));
+        let bits = u64::from(width) * u64::from(self.bits_per_pixel);
+
+        (bits.div_ceil(pad) * pad / 8) as usize
+    }
+
+    /// The number of bytes in an image.
+    pub fn image_size(&self, size: [u32; 2]) -> usize {
+        self.bytes_per_line(size[0]) * size[1] as usize
+    }
+
+    /// Returns why the format cannot be converted, if it cannot be converted.
+    pub fn unsupported_reason(&self) -> Option<&'static str> {
+        if !matches!(self.bits_per_pixel, 24 | 32) {
+            return Some("only 24 and 32 bits per pixel are supported");
+        }
+
+        for mask in [self.red_mask, self.green_mask, self.blue_mask] {
+            if mask == 0
+                || !(mask >> mask.trailing_zeros())
+                    .wrapping_add(1)
+                    .is_power_of_two()
+            {
+                return Some("channel masks must be contiguous");
+            }
+
+            if mask.count_ones() > 16 {
+                return Some("channels must have at most 16 bits");
+            }
+        }
+
+        None
+    }
+
+    /// Converts an image to linear `RGBA` pixels, where sRGB white has a value of `sdr_white`.
+    ///
+    /// # Panics
+    /// If the format is not supported or the data is smaller than the image.
+    pub fn to_linear_rgba(&self, data: &[u8], size: [u32; 2], sdr_white: f32) -> Vec<f16> {
+        if let Some(reason) = self.unsupported_reason() {
+            panic!("Pixel format is not supported: {reason}");
+        }
+
+        let bytes_per_line = self.bytes_per_line(size[0]);
+        let bytes_per_pixel = usize::from(self.bits_per_pixel / 8);
+        assert!(
+            data.len() >= self.image_size(size),
+            "Data must contain every row of the image"
+        );
+
+        let channels = [self.red_mask, self.green_mask, self.blue_mask]
+            .map(|mask| Channel::new(mask, sdr_white));
+
+        let mut pixels = Vec::with_capacity(size[0] as usize * size[1] as usize * 4);
+        for row in data.chunks_exact(bytes_per_line).take(size[1] as usize) {
+            for pixel in row.chunks_exact(bytes_per_pixel).take(size[0] as usize) {
+                let value = match self.byte_order {
+                    ByteOrder::LsbFirst => pixel
+                        .iter()
+                        .rev()
+                        .fold(0u32, |value, byte| (value << 8) | u32::from(*byte)),
+                    ByteOrder::MsbFirst => pixel
+                        .iter()
+                        .fold(0u32, |value, byte| (value << 8) | u32::from(*byte)),
+                };
+
+                pixels.extend(channels.iter().map(|channel| channel.linear(value)));
+                pixels.push(f16::ONE);
+            }
+        }
+
+        pixels
+    }
+}
+
+/// A colour channel of a pixel.
+struct Channel {
+    mask: u32,
+    shift: u32,
+
+    /// The linear value for every encoded value of the channel.
+    lookup: Vec<f16>,
+}
+
+impl Channel {
+    fn new(mask: u32, sdr_white: f32) -> Self {
+        let shift = mask.trailing_zeros();
+        let max = mask >> shift;
+
+        let lookup = (0..=max)
+            .map(|value| f16::from_f32(srgb_to_linear(value as f32 / max as f32) * sdr_white))
+            .collect();
+
+        Self {
+            mask,
+            shift,
+            lookup,
+        }
+    }
+
+    fn linear(&self, pixel: u32) -> f16 {
+        self.lookup[((pixel & self.mask) >> self.shift) as usize]
+    }
+}
+
+/// The sRGB electro-optical transfer function.
+fn srgb_to_linear(value: f32) -> f32 {
+    if value <= 0.04045 {
+        value / 12.92
+    } else {
+        ((value + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use half::f16;
+
+    use super::{ByteOrder, PixelFormat};
+
+    const RGB_8: PixelFormat = PixelFormat {
+        depth: 24,
+        bits_per_pixel: 32,
+        scanline_pad: 32,
+        red_mask: 0xff0000,
+        green_mask: 0xff00,
+        blue_mask: 0xff,
+        byte_order: ByteOrder::LsbFirst,
+    };
+
+    const RGB_10: PixelFormat = PixelFormat {
+        depth: 30,
+        bits_per_pixel: 32,
+        scanline_pad: 32,
+        red_mask: 0x3ff00000,
+        green_mask: 0xffc00,
+        blue_mask: 0x3ff,
+        byte_order: ByteOrder::LsbFirst,
+    };
+
+    fn rgba(pixels: &[f16], index: usize) -> [f32; 4] {
+        [0, 1, 2, 3].map(|channel| pixels[index * 4 + channel].to_f32())
+    }
+
+    #[test]
+    fn converts_8_bit() {
+        // Red, white, black, sRGB mid grey
+        let data = [
+            0x00, 0x00, 0xff, 0x00, //
+            0xff, 0xff, 0xff, 0x00, //
+            0x00, 0x00, 0x00, 0x00, //
+            0xbc, 0xbc, 0xbc, 0x00,
+        ];
+
+        let pixels = RGB_8.to_linear_rgba(&data, [2, 2], 2.5);
+
+        assert_eq!(rgba(&pixels, 0), [2.5, 0.0, 0.0, 1.0]);
+        assert_eq!(rgba(&pixels, 1), [2.5, 2.5, 2.5, 1.0]);
+        assert_eq!(rgba(&pixels, 2), [0.0, 0.0, 0.0, 1.0]);
+
+        let grey = rgba(&pixels, 3);
+        assert!((grey[0] / 2.5 - 0.5).abs() < 0.01, "{grey:?}");
+    }
+
+    #[test]
+    fn converts_10_bit() {
+        // Full blue, half green, the smallest red step
+        let value: u32 = (1 << 20) | (512 << 10) | 1023;
+        let pixels = RGB_10.to_linear_rgba(&value.to_le_bytes(), [1, 1], 1.0);
+
+        let [red, green, blue, alpha] = rgba(&pixels, 0);
+        assert!(red > 0.0 && red < 0.001, "{red}");
+        assert!((green - 0.2145).abs() < 0.001, "{green}");
+        assert_eq!(blue, 1.0);
+        assert_eq!(alpha, 1.0);
+    }
+
+    #[test]
+    fn converts_msb_first() {
+        let format = PixelFormat {
+            byte_order: ByteOrder::MsbFirst,
+            ..RGB_8
+        };
+
+        let pixels = format.to_linear_rgba(&[0x00, 0xff, 0x00, 0x00], [1, 1], 1.0);
+        assert_eq!(rgba(&pixels, 0), [1.0, 0.0, 0.0, 1.0]);
+    }
+
+    #[test]
+    fn skips_row_padding() {
+        let format = PixelFormat {
+            bits_per_pixel: 24,
+            ..RGB_8
+        };
+
+        // Each row of 3 pixels is 9 bytes padded to 12 bytes.
+        assert_eq!(format.bytes_per_line(3), 12);
+
+        let mut data = vec![0u8; 24];
+        data[9..12].fill(0xff);
+        data[12..15].copy_from_slice(&[0xff, 0x00, 0x00]);
+
+        let pixels = format.to_linear_rgba(&data, [3, 2], 1.0);
+        assert_eq!(pixels.len(), 3 * 2 * 4);
+        assert_eq!(rgba(&pixels, 2), [0.0, 0.0, 0.0, 1.0]);
+        assert_eq!(rgba(&pixels, 3), [0.0, 0.0, 1.0, 1.0]);
+    }
+
+    #[test]
+    fn rejects_unsupported_formats() {
+        assert!(RGB_8.unsupported_reason().is_none());
+        assert!(RGB_10.unsupported_reason().is_none());
+
+        let sixteen_bit = PixelFormat {
+            bits_per_pixel: 16,
+            ..RGB_8
+        };
+        assert!(sixteen_bit.unsupported_reason().is_some());
+
+        let split_mask = PixelFormat {
+            red_mask: 0xf0f000,
+            ..RGB_8
+        };
+        assert!(split_mask.unsupported_reason().is_some());
+    }
+}
@@ -0,0 +1,68 @@
+use core::fmt::Display;
+
+use thiserror::Error;
+use x11rb::errors::{ConnectError, ConnectionError, ReplyError, ReplyOrIdError};
+
+/// A shortcut for `Result<T, X11Error>`.
+pub type LabelledX11Result<T> = Result<T, X11Error>;
+
+/// An X11 error wrapped with some context for the request that triggered the error.
+#[derive(Debug, Error)]
+pub struct X11Error {
+    call: &'static str,
+    #[source]
+    source: X11ErrorSource,
+}
+
+/// Possible sources for an X11Error.
+#[derive(Debug, Error)]
+#[non_exhaustive]
+pub enum X11ErrorSource {
+    /// Connecting to the X server failed.
+    #[error(transparent)]
+    Connect(#[from] ConnectError),
+
+    /// The connection to the X server failed.
+    #[error(transparent)]
+    Connection(#[from] ConnectionError),
+
+    /// The X server replied with an error.
+    #[error(transparent)]
+    Reply(#[from] ReplyError),
+
+    /// The X server replied with an error or ran out of IDs.
+    #[error(transparent)]
+    ReplyOrId(#[from] ReplyOrIdError),
+
+    /// A system call failed.
+    #[error("Errno: {0}")]
+    Errno(#[from] rustix::io::Errno),
+
+    /// The framebuffer is in a format that cannot be converted.
+    #[error("Unsupported pixel format: {0}")]
+    UnsupportedFormat(&'static str),
+}
+
+impl X11Error {
+    /// Create an X11Error from a source and a label.
+    pub fn new(source: impl Into<X11ErrorSource>, call: &'static str) -> Self {
+        Self {
+            call,
+            source: source.into(),
+        }
+    }
+
+    /// Create an X11Error for a framebuffer format that cannot be converted.
+    pub fn unsupported_format(reason: &'static str, call: &'static str) -> Self {
+        Self {
+            call,
+            source: X11ErrorSource::UnsupportedFormat(reason),
+        }
+    }
+}
+
+impl Display for X11Error {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "X11 {} call failed: {}", self.call, self.source)
+    }
+}
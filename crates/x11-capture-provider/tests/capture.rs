@@ -0,0 +1,74 @@
+//! Tests for taking captures, these require an X server such as `Xvfb` so are ignored by
+//! default. Run them with `xvfb-run cargo test -p x11-capture-provider -- --ignored`, they change
+//! the root window's background.
+
+use x11_capture_provider::{Monitor, X11Capture, X11Connection};
+use x11rb::{
+    connection::Connection,
+    protocol::xproto::{ChangeWindowAttributesAux, ConnectionExt as _},
+    wrapper::ConnectionExt as _,
+};
+
+#[test]
+#[ignore = "Requires an X server"]
+fn get_active_monitors() {
+    let connection = X11Connection::new(None).unwrap();
+
+    let monitors = Monitor::get_active_monitors(&connection).unwrap();
+    assert!(!monitors.is_empty(), "At least one monitor must be active");
+
+    for monitor in monitors {
+        let [width, height] = monitor.size();
+        assert!(width > 0 && height > 0);
+    }
+}
+
+#[test]
+#[ignore = "Requires an X server"]
+fn get_hovered_monitor() {
+    let connection = X11Connection::new(None).unwrap();
+
+    Monitor::get_hovered_monitor(&connection)
+        .unwrap()
+        .expect("Monitor should be some");
+}
+
+#[test]
+#[ignore = "Requires an X server"]
+fn capture_solid_background() {
+    let connection = X11Connection::new(None).unwrap();
+    let format = connection.pixel_format().unwrap();
+
+    // Fill the root window with pure green.
+    {
+        let (x11, screen) = x11rb::connect(None).unwrap();
+        let root = x11.setup().roots[screen].root;
+
+        x11.change_window_attributes(
+            root,
+            &ChangeWindowAttributesAux::new().background_pixel(format.green_mask),
+        )
+        .unwrap();
+        x11.clear_area(false, root, 0, 0, 0, 0).unwrap();
+        x11.sync().unwrap();
+    }
+
+    let monitor = Monitor::get_active_monitors(&connection)
+        .unwrap()
+        .into_iter()
+        .next()
+        .unwrap();
+
+    let (capture, pixels) = X11Capture::take_capture(&connection, &monitor).unwrap();
+    assert_eq!(capture.size, monitor.size());
+    assert_eq!(
+        pixels.len(),
+        capture.size[0] as usize * capture.size[1] as usize * 4
+    );
+
+    let green = [0.0, monitor.sdr_white, 0.0, 1.0];
+    for pixel in pixels.chunks_exact(4) {
+        let pixel: Vec<_> = pixel.iter().map(|channel| channel.to_f32()).collect();
+        assert_eq!(pixel, green);
+    }
+}